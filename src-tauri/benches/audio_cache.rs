@@ -0,0 +1,129 @@
+// Benchmarks for the audio cache and its database queries.
+//
+// Run with `cargo bench --bench audio_cache`. The library fixture mirrors a
+// heavy user's database: 50k generated clips spread over the audio types.
+
+use bytes::Bytes;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use futures::stream::{self, StreamExt};
+use rusqlite::Connection;
+
+use opcode_lib::commands::eleven_labs::cache::{AudioCache, AudioCacheDb, VoiceProfileDb};
+use opcode_lib::commands::eleven_labs::client::AudioStream;
+use opcode_lib::commands::eleven_labs::schema;
+use opcode_lib::commands::eleven_labs::types::*;
+
+const LIBRARY_SIZE: usize = 50_000;
+
+/// In-memory database holding the tables the audio cache queries
+fn empty_library() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    schema::init(&conn).unwrap();
+    conn
+}
+
+fn record(i: usize) -> GeneratedAudio {
+    let audio_type = match i % 3 {
+        0 => AudioType::Tts,
+        1 => AudioType::Sfx,
+        _ => AudioType::Music,
+    };
+    GeneratedAudio {
+        id: format!("audio-{:06}", i),
+        audio_type,
+        prompt: format!("Line {} of the chapter, read with a little more warmth", i),
+        duration_seconds: 2.0 + (i % 40) as f32 * 0.25,
+        local_path: format!("/cache/tts/audio-{:06}.mp3", i),
+        supabase_url: None,
+        metadata: serde_json::json!({ "project_id": format!("project-{}", i % 12) }),
+        created_at: format!("2024-01-01T00:00:{:02}.{:06}Z", i % 60, i),
+        params: Some(GenerationParams {
+            voice_id: Some(format!("voice-{}", i % 25)),
+            ..GenerationParams::new(PROVIDER_ELEVENLABS)
+        }),
+    }
+}
+
+/// Library with `size` audio records, written in one transaction
+fn library(size: usize) -> Connection {
+    let mut conn = empty_library();
+    let tx = conn.transaction().unwrap();
+    for i in 0..size {
+        AudioCacheDb::save_audio_record(&tx, &record(i)).unwrap();
+    }
+    tx.commit().unwrap();
+    conn
+}
+
+fn voices(count: usize) -> Vec<VoiceProfile> {
+    (0..count)
+        .map(|i| VoiceProfile {
+            voice_id: format!("voice-{}", i),
+            name: format!("Voice {}", i),
+            description: None,
+            category: "premade".to_string(),
+            labels: Some(serde_json::json!({ "accent": "british" })),
+            preview_url: None,
+            settings: VoiceSettings::default(),
+        })
+        .collect()
+}
+
+fn bench_save_audio(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let cache = AudioCache::new(dir.path().to_path_buf()).unwrap();
+    let clip = Bytes::from(vec![0u8; 256 * 1024]);
+
+    c.bench_function("save_audio 256KB", |b| {
+        b.to_async(&runtime)
+            .iter(|| async { cache.save_audio(&AudioType::Tts, black_box(&clip), "mp3").await.unwrap() })
+    });
+
+    c.bench_function("save_audio_stream 256KB in 16KB chunks", |b| {
+        b.to_async(&runtime).iter_batched(
+            || -> AudioStream { stream::iter((0..clip.len()).step_by(16 * 1024).map(|i| Ok(clip.slice(i..i + 16 * 1024))).collect::<Vec<_>>()).boxed() },
+            |chunks| async { cache.save_audio_stream(&AudioType::Tts, chunks, "mp3", |_| {}).await.unwrap() },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_record_queries(c: &mut Criterion) {
+    let conn = library(LIBRARY_SIZE);
+
+    c.bench_function("get_audio_records tts of 50k", |b| {
+        b.iter(|| AudioCacheDb::get_audio_records(&conn, black_box(&AudioType::Tts)).unwrap())
+    });
+
+    c.bench_function("get_audio_record by id in 50k", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i = (i + 7919) % LIBRARY_SIZE;
+            AudioCacheDb::get_audio_record(&conn, black_box(&format!("audio-{:06}", i))).unwrap()
+        })
+    });
+
+    c.bench_function("save_audio_record into 50k", |b| {
+        let mut i = LIBRARY_SIZE;
+        b.iter(|| {
+            i += 1;
+            AudioCacheDb::save_audio_record(&conn, black_box(&record(i))).unwrap()
+        })
+    });
+}
+
+fn bench_voice_cache(c: &mut Criterion) {
+    let fetched = voices(500);
+
+    c.bench_function("save_voice_profiles 500", |b| {
+        b.iter_batched(
+            empty_library,
+            |mut conn| VoiceProfileDb::save_voice_profiles(&mut conn, black_box(&fetched)).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, bench_save_audio, bench_record_queries, bench_voice_cache);
+criterion_main!(benches);
@@ -1,3 +1,7 @@
+#[path = "build/audio_commands.rs"]
+mod audio_commands;
+
 fn main() {
+    audio_commands::generate(std::path::Path::new("src/commands/eleven_labs"));
     tauri_build::build()
 }
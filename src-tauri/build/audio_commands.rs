@@ -0,0 +1,326 @@
+// Registry of the audio commands, derived from the `#[tauri::command]`
+// functions in src/commands/eleven_labs so the handler list can't drift from
+// the code. build.rs writes `audio_commands.rs` to OUT_DIR, included by the
+// eleven_labs module (the `generate_handler!` used in main.rs and `COMMANDS`).
+// The TypeScript bindings in src/lib/audioCommands.ts are written by the
+// `opcode-audio-bindings` bin instead, so a build never touches the source tree.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use syn::{Attribute, FnArg, GenericArgument, Item, Pat, PathArguments, ReturnType, Type};
+
+/// Arguments Tauri injects rather than reading from the invoke payload
+const INJECTED_ARGS: &[&str] = &["State", "AppHandle", "Window", "WebviewWindow", "Webview"];
+
+struct Command {
+    /// Path below the eleven_labs module, e.g. `sync::configure_s3_backup`
+    path: String,
+    name: String,
+    doc: Option<String>,
+    /// (camelCase name, TypeScript type, optional)
+    args: Vec<(String, String, bool)>,
+    result: String,
+}
+
+struct Scan {
+    commands: Vec<Command>,
+    /// api.ts types the bindings import
+    imports: BTreeSet<String>,
+    files: Vec<PathBuf>,
+}
+
+/// Write the command registry to OUT_DIR (build.rs)
+#[allow(dead_code)]
+pub fn generate(module_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", module_dir.display());
+    let scan = scan(module_dir, &BTreeSet::new());
+    for file in &scan.files {
+        println!("cargo:rerun-if-changed={}", file.display());
+    }
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR");
+    write_if_changed(&Path::new(&out_dir).join("audio_commands.rs"), &rust_registry(&scan.commands));
+}
+
+/// The TypeScript bindings for the command modules in `module_dir`, typed
+/// against the frontend's `api.ts` in `frontend_lib`
+#[allow(dead_code)]
+pub fn typescript(module_dir: &Path, frontend_lib: &Path) -> String {
+    let api_ts = fs::read_to_string(frontend_lib.join("api.ts")).unwrap_or_default();
+    let scan = scan(module_dir, &exported_ts_types(&api_ts));
+    typescript_bindings(&scan.commands, &scan.imports)
+}
+
+fn scan(module_dir: &Path, exported: &BTreeSet<String>) -> Scan {
+    let mut files: Vec<_> = fs::read_dir(module_dir)
+        .expect("read eleven_labs module")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .collect();
+    files.sort();
+
+    let mut commands = Vec::new();
+    let mut imports = BTreeSet::new();
+    for file in &files {
+        let source = fs::read_to_string(file).expect("read command module");
+        let parsed = syn::parse_file(&source).unwrap_or_else(|e| panic!("parse {}: {}", file.display(), e));
+        let module = file.file_stem().unwrap().to_string_lossy();
+        for item in parsed.items {
+            if let Item::Fn(f) = item {
+                if f.attrs.iter().any(is_command_attr) {
+                    let path = match module.as_ref() {
+                        "mod" => f.sig.ident.to_string(),
+                        module => format!("{}::{}", module, f.sig.ident),
+                    };
+                    commands.push(command(path, &f, exported, &mut imports));
+                }
+            }
+        }
+    }
+    commands.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Scan { commands, imports, files }
+}
+
+fn is_command_attr(attr: &Attribute) -> bool {
+    let segments: Vec<_> = attr.path().segments.iter().map(|s| s.ident.to_string()).collect();
+    segments == ["tauri", "command"] || segments == ["command"]
+}
+
+fn command(path: String, f: &syn::ItemFn, exported: &BTreeSet<String>, imports: &mut BTreeSet<String>) -> Command {
+    let mut args = Vec::new();
+    for input in &f.sig.inputs {
+        let FnArg::Typed(arg) = input else { continue };
+        if INJECTED_ARGS.contains(&last_segment(&arg.ty).as_str()) {
+            continue;
+        }
+        let Pat::Ident(name) = &*arg.pat else { continue };
+        let (ts, optional) = match option_inner(&arg.ty) {
+            Some(inner) => (nullable(ts_type(inner, exported, imports)), true),
+            None => (ts_type(&arg.ty, exported, imports), false),
+        };
+        args.push((camel_case(&name.ident.to_string()), ts, optional));
+    }
+    // Commands return `Result<T, AudioError>`; the frontend sees `T`
+    let result = match &f.sig.output {
+        ReturnType::Default => "void".to_string(),
+        ReturnType::Type(_, ty) => match generic_args(ty).first() {
+            Some(ok) if last_segment(ty) == "Result" => ts_type(ok, exported, imports),
+            _ => ts_type(ty, exported, imports),
+        },
+    };
+    Command {
+        name: f.sig.ident.to_string(),
+        path,
+        doc: doc_summary(&f.attrs),
+        args,
+        result,
+    }
+}
+
+/// The TypeScript type of `ty` as serde serializes it. Named types are taken
+/// from api.ts when it exports one of that name, otherwise left `unknown`.
+fn ts_type(ty: &Type, exported: &BTreeSet<String>, imports: &mut BTreeSet<String>) -> String {
+    match ty {
+        Type::Tuple(tuple) if tuple.elems.is_empty() => return "void".to_string(),
+        Type::Reference(reference) => return ts_type(&reference.elem, exported, imports),
+        Type::Path(_) => {}
+        _ => return "unknown".to_string(),
+    }
+    let args = generic_args(ty);
+    let mut arg = |i: usize| args.get(i).map_or("unknown".to_string(), |t| ts_type(t, exported, imports));
+    match last_segment(ty).as_str() {
+        "String" | "str" | "PathBuf" => "string".to_string(),
+        "bool" => "boolean".to_string(),
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" | "f32" | "f64" => {
+            "number".to_string()
+        }
+        "Option" => nullable(arg(0)),
+        "Vec" => wrap_array(&arg(0)),
+        "HashMap" | "BTreeMap" => format!("Record<string, {}>", arg(1)),
+        "Box" | "Arc" => arg(0),
+        "Value" => "unknown".to_string(),
+        "Channel" => format!("Channel<{}>", arg(0)),
+        name if exported.contains(name) => {
+            imports.insert(name.to_string());
+            name.to_string()
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+fn nullable(ty: String) -> String {
+    if ty == "unknown" {
+        ty
+    } else {
+        format!("{} | null", ty)
+    }
+}
+
+fn wrap_array(element: &str) -> String {
+    if element.contains(' ') {
+        format!("({})[]", element)
+    } else {
+        format!("{}[]", element)
+    }
+}
+
+fn last_segment(ty: &Type) -> String {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default(),
+        Type::Reference(reference) => last_segment(&reference.elem),
+        _ => String::new(),
+    }
+}
+
+fn generic_args(ty: &Type) -> Vec<&Type> {
+    let Type::Path(path) = ty else { return Vec::new() };
+    let Some(PathArguments::AngleBracketed(args)) = path.path.segments.last().map(|s| &s.arguments) else {
+        return Vec::new();
+    };
+    args.args
+        .iter()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .collect()
+}
+
+fn option_inner(ty: &Type) -> Option<&Type> {
+    if last_segment(ty) == "Option" {
+        generic_args(ty).first().copied()
+    } else {
+        None
+    }
+}
+
+/// Tauri's default renaming of command arguments
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// First paragraph of the doc comment, on one line
+fn doc_summary(attrs: &[Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("doc")) {
+        let syn::Meta::NameValue(meta) = &attr.meta else { continue };
+        let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(text), .. }) = &meta.value else { continue };
+        let line = text.value().trim().to_string();
+        if line.is_empty() {
+            break;
+        }
+        lines.push(line);
+    }
+    (!lines.is_empty()).then(|| lines.join(" ").replace("*/", "* /"))
+}
+
+/// Names api.ts exports as `export interface X` or `export type X`
+fn exported_ts_types(api_ts: &str) -> BTreeSet<String> {
+    api_ts
+        .lines()
+        .filter_map(|line| {
+            let rest = line.strip_prefix("export interface ").or_else(|| line.strip_prefix("export type "))?;
+            let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+            (!name.is_empty()).then_some(name)
+        })
+        .collect()
+}
+
+fn rust_registry(commands: &[Command]) -> String {
+    let mut out = String::from(
+        "// @generated by build/audio_commands.rs\n\n\
+         /// `tauri::generate_handler!` over every audio command, followed by the\n\
+         /// commands given (which may end with a trailing comma)\n\
+         #[allow(unused_macros)]\n\
+         macro_rules! generate_handler {\n    ($($command:tt)*) => {\n        ::tauri::generate_handler![\n",
+    );
+    for command in commands {
+        let _ = writeln!(out, "            crate::commands::eleven_labs::{},", command.path);
+    }
+    out.push_str(
+        "            $($command)*\n        ]\n    };\n}\n\
+         // The library crate doesn't build an invoke handler\n\
+         #[allow(unused_imports)]\n\
+         pub(crate) use generate_handler;\n\n\
+         /// Names of the audio commands, as invoked from the frontend\n\
+         pub const COMMANDS: &[&str] = &[\n",
+    );
+    for command in commands {
+        let _ = writeln!(out, "    \"{}\",", command.name);
+    }
+    out.push_str("];\n");
+    out
+}
+
+fn typescript_bindings(commands: &[Command], imports: &BTreeSet<String>) -> String {
+    let mut out = String::from(
+        "// Generated by src-tauri/build/audio_commands.rs from the #[tauri::command]\n\
+         // functions in src-tauri/src/commands/eleven_labs. Do not edit; run\n\
+         // `bun run bindings:audio` after changing a command signature.\n\n\
+         import type { Channel } from '@tauri-apps/api/core';\n\
+         import { apiCall } from './apiAdapter';\n",
+    );
+    if !imports.is_empty() {
+        let names: Vec<_> = imports.iter().map(String::as_str).collect();
+        let _ = writeln!(out, "import type {{\n  {},\n}} from './api';", names.join(",\n  "));
+    }
+    out.push_str("\n/** Arguments and result of each audio command */\nexport interface AudioCommands {\n");
+    for command in commands {
+        if let Some(doc) = &command.doc {
+            let _ = writeln!(out, "  /** {} */", doc);
+        }
+        let _ = writeln!(out, "  {}: {{", command.name);
+        if command.args.is_empty() {
+            out.push_str("    args: Record<string, never>;\n");
+        } else {
+            out.push_str("    args: {\n");
+            for (name, ty, optional) in &command.args {
+                let _ = writeln!(out, "      {}{}: {};", name, if *optional { "?" } else { "" }, ty);
+            }
+            out.push_str("    };\n");
+        }
+        let _ = writeln!(out, "    result: {};\n  }};", command.result);
+    }
+    out.push_str("}\n\nexport type AudioCommand = keyof AudioCommands;\n\n");
+    out.push_str("export const AUDIO_COMMANDS: AudioCommand[] = [\n");
+    for command in commands {
+        let _ = writeln!(out, "  '{}',", command.name);
+    }
+    out.push_str(
+        "];\n\n\
+         type AudioCommandArgs<K extends AudioCommand> = Record<string, never> extends AudioCommands[K]['args']\n  \
+         ? [args?: AudioCommands[K]['args']]\n  \
+         : [args: AudioCommands[K]['args']];\n\n\
+         /** Invoke an audio command with its arguments and result type checked */\n\
+         export function invokeAudio<K extends AudioCommand>(\n  \
+         command: K,\n  \
+         ...args: AudioCommandArgs<K>\n\
+         ): Promise<AudioCommands[K]['result']> {\n  \
+         return apiCall<AudioCommands[K]['result']>(command, args[0]);\n\
+         }\n",
+    );
+    out
+}
+
+/// Skip unchanged files so dependents aren't rebuilt on every build
+fn write_if_changed(path: &Path, contents: &str) {
+    if fs::read_to_string(path).ok().as_deref() != Some(contents) {
+        fs::write(path, contents).unwrap_or_else(|e| panic!("write {}: {}", path.display(), e));
+    }
+}
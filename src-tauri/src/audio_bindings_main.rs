@@ -0,0 +1,33 @@
+use std::path::Path;
+use std::process::ExitCode;
+
+#[path = "../build/audio_commands.rs"]
+mod audio_commands;
+
+/// Regenerates the TypeScript bindings for the audio commands in
+/// src/lib/audioCommands.ts. With `--check`, fails instead of writing when the
+/// file is out of date.
+fn main() -> ExitCode {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let frontend_lib = root.join("../src/lib");
+    let path = frontend_lib.join("audioCommands.ts");
+    let bindings = audio_commands::typescript(&root.join("src/commands/eleven_labs"), &frontend_lib);
+
+    if std::fs::read_to_string(&path).ok().as_deref() == Some(bindings.as_str()) {
+        return ExitCode::SUCCESS;
+    }
+    if std::env::args().any(|arg| arg == "--check") {
+        eprintln!("{} is out of date; run `bun run bindings:audio`", path.display());
+        return ExitCode::FAILURE;
+    }
+    match std::fs::write(&path, bindings) {
+        Ok(()) => {
+            eprintln!("Wrote {}", path.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to write {}: {}", path.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
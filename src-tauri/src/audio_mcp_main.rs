@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+mod checkpoint;
+mod claude_binary;
+mod commands;
+mod process;
+
+use commands::eleven_labs::logging;
+use commands::eleven_labs::mcp_server::{serve_stdio, AudioMcpServer};
+use commands::eleven_labs::ElevenLabsState;
+
+/// Stdio MCP server exposing opcode's audio tools (tts, generate_sfx, list_voices,
+/// assign_voice) to Claude agents. Logs go to stderr; stdout carries JSON-RPC only.
+#[tokio::main]
+async fn main() {
+    logging::init();
+
+    let state = Arc::new(ElevenLabsState::new());
+    let saved = state.call_db(|conn| logging::LoggingConfig::load(conn)).await;
+    if let Err(e) = saved.and_then(|config| logging::apply(&config)) {
+        log::warn!("Failed to apply logging settings: {}", e);
+    }
+    let server = AudioMcpServer::new(state);
+
+    if let Err(e) = serve_stdio(server).await {
+        eprintln!("Audio MCP server failed: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -39,7 +39,7 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
     if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
         let db_path = app_data_dir.join("agents.db");
         if db_path.exists() {
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
+            if let Ok(conn) = crate::commands::eleven_labs::encryption::open(&db_path) {
                 // Check for stored path first
                 if let Ok(stored_path) = conn.query_row(
                     "SELECT value FROM app_settings WHERE key = 'claude_binary_path'",
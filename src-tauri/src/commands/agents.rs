@@ -14,6 +14,8 @@ use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 use tokio::process::Command;
 
+use crate::commands::eleven_labs::event_sounds::{self, AgentEvent};
+
 /// Finds the full path to the claude binary
 /// This is necessary because macOS apps have a limited PATH environment
 fn find_claude_binary(app_handle: &AppHandle) -> Result<String, String> {
@@ -35,6 +37,15 @@ pub struct Agent {
     pub hooks: Option<String>, // JSON string of hooks configuration
     pub created_at: String,
     pub updated_at: String,
+    /// TTS model used when narrating this agent; the global default when unset
+    #[serde(default)]
+    pub tts_model: Option<String>,
+    /// Speaking rate for narration (0.7 to 1.2)
+    #[serde(default)]
+    pub tts_speed: Option<f64>,
+    /// Narration verbosity: "full", "summary" or "off"; follows the global setting when unset
+    #[serde(default)]
+    pub narration: Option<String>,
 }
 
 /// Represents an agent execution run
@@ -222,7 +233,12 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
     std::fs::create_dir_all(&app_dir).expect("Failed to create app data dir");
 
     let db_path = app_dir.join("agents.db");
-    let conn = Connection::open(db_path)?;
+    // Apply a requested change of the encryption passphrase before anything
+    // opens the database
+    if let Err(e) = crate::commands::eleven_labs::encryption::prepare(&db_path) {
+        log::error!("Failed to read the database passphrase: {}", e);
+    }
+    let mut conn = crate::commands::eleven_labs::encryption::open(&db_path)?;
 
     // Create agents table
     conn.execute(
@@ -237,6 +253,9 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
             enable_file_write BOOLEAN NOT NULL DEFAULT 1,
             enable_network BOOLEAN NOT NULL DEFAULT 0,
             hooks TEXT,
+            tts_model TEXT,
+            tts_speed REAL,
+            narration TEXT,
             created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
             updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
         )",
@@ -262,6 +281,9 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
         "ALTER TABLE agents ADD COLUMN enable_network BOOLEAN DEFAULT 0",
         [],
     );
+    let _ = conn.execute("ALTER TABLE agents ADD COLUMN tts_model TEXT", []);
+    let _ = conn.execute("ALTER TABLE agents ADD COLUMN tts_speed REAL", []);
+    let _ = conn.execute("ALTER TABLE agents ADD COLUMN narration TEXT", []);
 
     // Create agent_runs table
     conn.execute(
@@ -342,129 +364,11 @@ pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
         [],
     )?;
 
-    // =====================================================
-    // Eleven Labs Audio Integration Tables
-    // =====================================================
-
-    // Voice profiles table (local cache of Eleven Labs voices)
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS voice_profiles (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            description TEXT,
-            category TEXT NOT NULL DEFAULT 'premade',
-            provider TEXT NOT NULL DEFAULT 'elevenlabs',
-            provider_voice_id TEXT,
-            labels TEXT,
-            preview_url TEXT,
-            settings_stability REAL DEFAULT 0.5,
-            settings_similarity_boost REAL DEFAULT 0.75,
-            settings_style REAL DEFAULT 0.0,
-            settings_use_speaker_boost INTEGER DEFAULT 1,
-            is_synced INTEGER DEFAULT 0,
-            local_audio_path TEXT,
-            supabase_id TEXT,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )",
-        [],
-    )?;
-
-    // Character voice mappings (link characters to voice profiles)
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS character_voices (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            character_name TEXT NOT NULL UNIQUE,
-            voice_profile_id TEXT NOT NULL,
-            project_id TEXT,
-            notes TEXT,
-            is_synced INTEGER DEFAULT 0,
-            supabase_id TEXT,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (voice_profile_id) REFERENCES voice_profiles(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-
-    // Generated audio cache (TTS, SFX, Music)
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS audio_cache (
-            id TEXT PRIMARY KEY,
-            audio_type TEXT NOT NULL,
-            input_text TEXT,
-            voice_profile_id TEXT,
-            model_id TEXT,
-            local_file_path TEXT NOT NULL,
-            file_size INTEGER,
-            duration_ms INTEGER,
-            generation_params TEXT,
-            cost_usd REAL,
-            is_synced INTEGER DEFAULT 0,
-            supabase_storage_path TEXT,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )",
-        [],
-    )?;
-
-    // Eleven Labs API usage tracking
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS eleven_labs_usage (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            operation TEXT NOT NULL,
-            voice_id TEXT,
-            characters_used INTEGER,
-            cost_usd REAL,
-            request_timestamp TEXT NOT NULL,
-            response_time_ms INTEGER,
-            success INTEGER DEFAULT 1,
-            error_message TEXT,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )",
-        [],
-    )?;
-
-    // Eleven Labs settings (API key storage)
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS eleven_labs_settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )",
-        [],
-    )?;
-
-    // Create triggers for Eleven Labs tables
-    conn.execute(
-        "CREATE TRIGGER IF NOT EXISTS update_voice_profiles_timestamp
-         AFTER UPDATE ON voice_profiles
-         FOR EACH ROW
-         BEGIN
-             UPDATE voice_profiles SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
-         END",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TRIGGER IF NOT EXISTS update_character_voices_timestamp
-         AFTER UPDATE ON character_voices
-         FOR EACH ROW
-         BEGIN
-             UPDATE character_voices SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
-         END",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TRIGGER IF NOT EXISTS update_eleven_labs_settings_timestamp
-         AFTER UPDATE ON eleven_labs_settings
-         FOR EACH ROW
-         BEGIN
-             UPDATE eleven_labs_settings SET updated_at = CURRENT_TIMESTAMP WHERE key = NEW.key;
-         END",
-        [],
-    )?;
+    // Eleven Labs audio tables. Damaged ones are rebuilt; if even that fails,
+    // only the audio features are unavailable
+    if let Err(e) = crate::commands::eleven_labs::recovery::init_or_recover(&mut conn) {
+        log::error!("Failed to set up the audio tables: {}", e);
+    }
 
     Ok(conn)
 }
@@ -475,7 +379,7 @@ pub async fn list_agents(db: State<'_, AgentDb>) -> Result<Vec<Agent>, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = conn
-        .prepare("SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at FROM agents ORDER BY created_at DESC")
+        .prepare("SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at, tts_model, tts_speed, narration FROM agents ORDER BY created_at DESC")
         .map_err(|e| e.to_string())?;
 
     let agents = stmt
@@ -495,6 +399,9 @@ pub async fn list_agents(db: State<'_, AgentDb>) -> Result<Vec<Agent>, String> {
                 hooks: row.get(9)?,
                 created_at: row.get(10)?,
                 updated_at: row.get(11)?,
+                tts_model: row.get(12)?,
+                tts_speed: row.get(13)?,
+                narration: row.get(14)?,
             })
         })
         .map_err(|e| e.to_string())?
@@ -504,6 +411,19 @@ pub async fn list_agents(db: State<'_, AgentDb>) -> Result<Vec<Agent>, String> {
     Ok(agents)
 }
 
+/// Check per-agent narration settings before they are stored
+fn validate_narration_settings(tts_speed: Option<f64>, narration: Option<&str>) -> Result<(), String> {
+    if let Some(speed) = tts_speed {
+        if !(0.7..=1.2).contains(&speed) {
+            return Err(format!("TTS speed must be between 0.7 and 1.2, got {}", speed));
+        }
+    }
+    match narration {
+        None | Some("") | Some("full") | Some("summary") | Some("off") => Ok(()),
+        Some(other) => Err(format!("Invalid narration setting: {}", other)),
+    }
+}
+
 /// Create a new agent
 #[tauri::command]
 pub async fn create_agent(
@@ -517,16 +437,22 @@ pub async fn create_agent(
     enable_file_write: Option<bool>,
     enable_network: Option<bool>,
     hooks: Option<String>,
+    tts_model: Option<String>,
+    tts_speed: Option<f64>,
+    narration: Option<String>,
 ) -> Result<Agent, String> {
+    validate_narration_settings(tts_speed, narration.as_deref())?;
     let conn = db.0.lock().map_err(|e| e.to_string())?;
     let model = model.unwrap_or_else(|| "sonnet".to_string());
     let enable_file_read = enable_file_read.unwrap_or(true);
     let enable_file_write = enable_file_write.unwrap_or(true);
     let enable_network = enable_network.unwrap_or(false);
+    let tts_model = tts_model.filter(|m| !m.is_empty());
+    let narration = narration.filter(|n| !n.is_empty());
 
     conn.execute(
-        "INSERT INTO agents (name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-        params![name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks],
+        "INSERT INTO agents (name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, tts_model, tts_speed, narration) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, tts_model, tts_speed, narration],
     )
     .map_err(|e| e.to_string())?;
 
@@ -535,7 +461,7 @@ pub async fn create_agent(
     // Fetch the created agent
     let agent = conn
         .query_row(
-            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at FROM agents WHERE id = ?1",
+            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at, tts_model, tts_speed, narration FROM agents WHERE id = ?1",
             params![id],
             |row| {
                 Ok(Agent {
@@ -551,6 +477,9 @@ pub async fn create_agent(
                     hooks: row.get(9)?,
                     created_at: row.get(10)?,
                     updated_at: row.get(11)?,
+                    tts_model: row.get(12)?,
+                    tts_speed: row.get(13)?,
+                    narration: row.get(14)?,
                 })
             },
         )
@@ -573,7 +502,11 @@ pub async fn update_agent(
     enable_file_write: Option<bool>,
     enable_network: Option<bool>,
     hooks: Option<String>,
+    tts_model: Option<String>,
+    tts_speed: Option<f64>,
+    narration: Option<String>,
 ) -> Result<Agent, String> {
+    validate_narration_settings(tts_speed, narration.as_deref())?;
     let conn = db.0.lock().map_err(|e| e.to_string())?;
     let model = model.unwrap_or_else(|| "sonnet".to_string());
 
@@ -606,6 +539,22 @@ pub async fn update_agent(
         query.push_str(&format!(", enable_network = ?{}", param_count));
         params_vec.push(Box::new(en));
     }
+    // Narration settings are only touched when given; an empty string clears them
+    if let Some(tm) = tts_model {
+        param_count += 1;
+        query.push_str(&format!(", tts_model = ?{}", param_count));
+        params_vec.push(Box::new(Some(tm).filter(|m| !m.is_empty())));
+    }
+    if let Some(ts) = tts_speed {
+        param_count += 1;
+        query.push_str(&format!(", tts_speed = ?{}", param_count));
+        params_vec.push(Box::new(ts));
+    }
+    if let Some(na) = narration {
+        param_count += 1;
+        query.push_str(&format!(", narration = ?{}", param_count));
+        params_vec.push(Box::new(Some(na).filter(|n| !n.is_empty())));
+    }
 
     param_count += 1;
     query.push_str(&format!(" WHERE id = ?{}", param_count));
@@ -620,7 +569,7 @@ pub async fn update_agent(
     // Fetch the updated agent
     let agent = conn
         .query_row(
-            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at FROM agents WHERE id = ?1",
+            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at, tts_model, tts_speed, narration FROM agents WHERE id = ?1",
             params![id],
             |row| {
                 Ok(Agent {
@@ -636,6 +585,9 @@ pub async fn update_agent(
                     hooks: row.get(9)?,
                     created_at: row.get(10)?,
                     updated_at: row.get(11)?,
+                    tts_model: row.get(12)?,
+                    tts_speed: row.get(13)?,
+                    narration: row.get(14)?,
                 })
             },
         )
@@ -662,7 +614,7 @@ pub async fn get_agent(db: State<'_, AgentDb>, id: i64) -> Result<Agent, String>
 
     let agent = conn
         .query_row(
-            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at FROM agents WHERE id = ?1",
+            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at, tts_model, tts_speed, narration FROM agents WHERE id = ?1",
             params![id],
             |row| {
                 Ok(Agent {
@@ -678,6 +630,9 @@ pub async fn get_agent(db: State<'_, AgentDb>, id: i64) -> Result<Agent, String>
                     hooks: row.get(9)?,
                     created_at: row.get(10)?,
                     updated_at: row.get(11)?,
+                    tts_model: row.get(12)?,
+                    tts_speed: row.get(13)?,
+                    narration: row.get(14)?,
                 })
             },
         )
@@ -969,6 +924,7 @@ async fn spawn_agent_system(
         ).map_err(|e| e.to_string())?;
         info!("📝 Updated database with running status and PID");
     }
+    event_sounds::play(&app, AgentEvent::RunStarted).await;
 
     // Get stdout and stderr
     let stdout = child.stdout.take().ok_or("Failed to get stdout")?;
@@ -999,6 +955,7 @@ async fn spawn_agent_system(
     let first_output = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
     let first_output_clone = first_output.clone();
     let db_path_for_stdout = db_path.clone(); // Clone the db_path for the stdout task
+    let project_path_for_stdout = project_path.clone();
 
     let stdout_task = tokio::spawn(async move {
         info!("📖 Starting to read Claude stdout...");
@@ -1045,7 +1002,7 @@ async fn spawn_agent_system(
                                 info!("🔑 Extracted session ID: {}", sid);
 
                                 // Update database immediately with session ID
-                                if let Ok(conn) = Connection::open(&db_path_for_stdout) {
+                                if let Ok(conn) = crate::commands::eleven_labs::encryption::open(&db_path_for_stdout) {
                                     match conn.execute(
                                         "UPDATE agent_runs SET session_id = ?1 WHERE id = ?2",
                                         params![sid, run_id],
@@ -1067,6 +1024,16 @@ async fn spawn_agent_system(
                         }
                     }
                 }
+
+                // Read completed assistant messages aloud when narration is enabled
+                crate::commands::eleven_labs::narration::on_agent_output(
+                    &app_handle,
+                    agent_id,
+                    &project_path_for_stdout,
+                    &json,
+                )
+                .await;
+                event_sounds::on_agent_output(&app_handle, &json).await;
             }
 
             // Emit the line to the frontend with run_id for isolation
@@ -1116,6 +1083,9 @@ async fn spawn_agent_system(
         }
     });
 
+    let agent_name_for_monitor = agent_name.clone();
+    let project_path_for_monitor = project_path.clone();
+
     // Register the process in the registry for live output tracking (after stdout/stderr setup)
     registry
         .0
@@ -1184,7 +1154,7 @@ async fn spawn_agent_system(
                 }
 
                 // Update database
-                if let Ok(conn) = Connection::open(&db_path_for_monitor) {
+                if let Ok(conn) = crate::commands::eleven_labs::encryption::open(&db_path_for_monitor) {
                     let _ = conn.execute(
                         "UPDATE agent_runs SET status = 'failed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
                         params![run_id],
@@ -1193,6 +1163,15 @@ async fn spawn_agent_system(
 
                 let _ = app.emit("agent-complete", false);
                 let _ = app.emit(&format!("agent-complete:{}", run_id), false);
+                event_sounds::play(&app, AgentEvent::Error).await;
+                crate::commands::eleven_labs::voice_alerts::on_agent_finished(
+                    &app,
+                    agent_id,
+                    &agent_name_for_monitor,
+                    &project_path_for_monitor,
+                    false,
+                )
+                .await;
                 return;
             }
 
@@ -1218,7 +1197,7 @@ async fn spawn_agent_system(
         info!("✅ Claude process execution monitoring complete");
 
         // Update the run record with session ID and mark as completed - open a new connection
-        if let Ok(conn) = Connection::open(&db_path_for_monitor) {
+        if let Ok(conn) = crate::commands::eleven_labs::encryption::open(&db_path_for_monitor) {
             info!(
                 "🔄 Updating database with extracted session ID: {}",
                 extracted_session_id
@@ -1249,6 +1228,15 @@ async fn spawn_agent_system(
 
         let _ = app.emit("agent-complete", true);
         let _ = app.emit(&format!("agent-complete:{}", run_id), true);
+        event_sounds::play(&app, AgentEvent::Completed).await;
+        crate::commands::eleven_labs::voice_alerts::on_agent_finished(
+            &app,
+            agent_id,
+            &agent_name_for_monitor,
+            &project_path_for_monitor,
+            true,
+        )
+        .await;
     });
 
     Ok(run_id)
@@ -1617,7 +1605,7 @@ pub async fn stream_session_output(
 
             // Check if the session is still running by querying the database
             // If the session is no longer running, stop streaming
-            if let Ok(conn) = rusqlite::Connection::open(
+            if let Ok(conn) = crate::commands::eleven_labs::encryption::open(
                 app.path()
                     .app_data_dir()
                     .expect("Failed to get app data dir")
@@ -1876,7 +1864,7 @@ pub async fn import_agent(db: State<'_, AgentDb>, json_data: String) -> Result<A
     // Fetch the created agent
     let agent = conn
         .query_row(
-            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at FROM agents WHERE id = ?1",
+            "SELECT id, name, icon, system_prompt, default_task, model, enable_file_read, enable_file_write, enable_network, hooks, created_at, updated_at, tts_model, tts_speed, narration FROM agents WHERE id = ?1",
             params![id],
             |row| {
                 Ok(Agent {
@@ -1892,6 +1880,9 @@ pub async fn import_agent(db: State<'_, AgentDb>, json_data: String) -> Result<A
                     hooks: row.get(9)?,
                     created_at: row.get(10)?,
                     updated_at: row.get(11)?,
+                    tts_model: row.get(12)?,
+                    tts_speed: row.get(13)?,
+                    narration: row.get(14)?,
                 })
             },
         )
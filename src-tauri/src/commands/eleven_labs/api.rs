@@ -0,0 +1,323 @@
+// The provider API as seen by the commands. `ElevenLabsClient` is the real
+// implementation; state holds an `Arc<dyn ElevenLabsApi>` so tests can put a
+// client for a mock server (see `fake`) or another implementation in its place.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::Path;
+
+use super::client::{AudioDownload, AudioStream, TimedSpeech, WebSocket};
+use super::types::*;
+
+/// Requests the audio commands make to Eleven Labs
+#[async_trait]
+pub trait ElevenLabsApi: Send + Sync {
+    /// Open a connection ahead of the first real request
+    async fn warm_up(&self) -> Result<()>;
+
+    /// List voices unless they are unchanged since the response tagged `etag`.
+    /// Returns `None` for 304 Not Modified, otherwise the voices and their ETag.
+    async fn list_voices_if_changed(&self, etag: Option<&str>) -> Result<Option<(Vec<VoiceProfile>, Option<String>)>>;
+
+    async fn get_voice(&self, voice_id: &str) -> Result<VoiceProfile>;
+
+    /// Models available to the account
+    async fn list_models(&self) -> Result<Vec<ModelInfo>>;
+
+    /// Create an instant voice clone from the sample files
+    async fn clone_voice(&self, request: VoiceCloneRequest) -> Result<VoiceProfile>;
+
+    /// Change a voice's name, description or labels and add or remove samples
+    async fn edit_voice(&self, request: VoiceEditRequest) -> Result<VoiceProfile>;
+
+    async fn delete_voice(&self, voice_id: &str) -> Result<()>;
+
+    /// Search the shared voice library
+    async fn search_shared_voices(&self, filter: &SharedVoiceFilter) -> Result<SharedVoicePage>;
+
+    /// Add a shared library voice to the account under `name`
+    async fn add_shared_voice(&self, public_owner_id: &str, voice_id: &str, name: &str) -> Result<VoiceProfile>;
+
+    /// Generate candidate voices from a description (voice design)
+    async fn design_voice_previews(&self, request: VoiceDesignRequest) -> Result<VoiceDesignPreviews>;
+
+    /// Save a voice design preview to the account
+    async fn create_designed_voice(&self, request: DesignedVoiceRequest) -> Result<VoiceProfile>;
+
+    async fn text_to_speech(&self, request: TtsRequest) -> Result<AudioStream>;
+
+    /// `text_to_speech` along with when each character is spoken
+    async fn text_to_speech_with_timestamps(&self, request: TtsRequest) -> Result<TimedSpeech>;
+
+    /// `text_to_speech` along with the size of the audio, when known
+    async fn text_to_speech_download(&self, request: TtsRequest) -> Result<AudioDownload>;
+
+    /// `text_to_speech` over the provider's input-streaming WebSocket, which
+    /// starts sending audio before the whole text is rendered
+    async fn text_to_speech_websocket(&self, request: TtsRequest) -> Result<AudioStream>;
+
+    async fn generate_sound_effects(&self, request: SfxRequest) -> Result<AudioStream>;
+
+    async fn generate_music(&self, request: MusicRequest) -> Result<AudioStream>;
+
+    /// Re-speak the recording at `request.source_path` in another voice
+    async fn speech_to_speech(&self, request: VoiceChangeRequest) -> Result<AudioStream>;
+
+    /// Strip background noise and music from the recording at `path`, keeping the voice
+    async fn isolate_audio(&self, path: &Path) -> Result<AudioStream>;
+
+    /// Start dubbing a file; returns once the provider has accepted it
+    async fn create_dubbing(&self, request: DubbingRequest) -> Result<DubbingSubmission>;
+
+    async fn get_dubbing(&self, dubbing_id: &str) -> Result<DubbingProject>;
+
+    /// Download a finished dub in `language`
+    async fn get_dubbed_audio(&self, dubbing_id: &str, language: &str) -> Result<AudioStream>;
+
+    /// Transcribe a WAV recording
+    async fn speech_to_text(&self, wav: Vec<u8>, language: Option<&str>) -> Result<String>;
+
+    /// Transcribe the audio file at `path`, with word timings
+    async fn transcribe_file(&self, path: &Path, language: Option<&str>) -> Result<TranscriptionResult>;
+
+    /// Open a Conversational AI session with an agent
+    async fn open_conversation(&self, agent_id: &str) -> Result<WebSocket>;
+
+    /// A page of server-side generation history, starting after `start_after`
+    async fn list_history(&self, page_size: u32, start_after: Option<&str>) -> Result<HistoryPage>;
+
+    async fn get_history_item(&self, history_item_id: &str) -> Result<HistoryItem>;
+
+    /// Download the audio of a history item
+    async fn get_history_audio(&self, history_item_id: &str) -> Result<AudioStream>;
+
+    async fn delete_history_item(&self, history_item_id: &str) -> Result<()>;
+
+    async fn get_usage(&self) -> Result<UsageInfo>;
+}
+
+/// A mock Eleven Labs server answering every endpoint the commands use, for
+/// tests. Mount mocks with a priority below `DEFAULT_PRIORITY` to override a
+/// response.
+#[cfg(test)]
+pub mod fake {
+    use base64::Engine;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path, path_regex};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::ElevenLabsApi;
+    use crate::commands::eleven_labs::client::ElevenLabsClient;
+
+    /// Priority of the canned responses; wiremock prefers lower values
+    pub const DEFAULT_PRIORITY: u8 = 10;
+
+    /// Body returned for every generation
+    pub const AUDIO: &[u8] = b"ID3\x04\0\0\0\0\0\0fake audio";
+
+    pub struct FakeElevenLabs {
+        pub server: MockServer,
+    }
+
+    fn voice(voice_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "voice_id": voice_id,
+            "name": format!("Voice {}", voice_id),
+            "category": "premade",
+            "settings": { "stability": 0.4, "similarity_boost": 0.8 }
+        })
+    }
+
+    fn history_item(history_item_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "history_item_id": history_item_id,
+            "voice_id": "rachel",
+            "voice_name": "Voice rachel",
+            "model_id": "eleven_multilingual_v2",
+            "text": "Generated somewhere else",
+            "date_unix": 1_700_000_000,
+            "content_type": "audio/mpeg",
+            "settings": { "stability": 0.5, "similarity_boost": 0.75 }
+        })
+    }
+
+    impl FakeElevenLabs {
+        pub async fn start() -> Self {
+            let server = MockServer::start().await;
+            // Each speech response gets a request id of its own, as the API's do
+            let requests = Arc::new(AtomicUsize::new(0));
+            let canned = [
+                Mock::given(method("POST"))
+                    .and(path_regex(r"^/text-to-speech/[^/]+$"))
+                    .respond_with(move |_: &wiremock::Request| {
+                        let request_id = format!("request-{}", requests.fetch_add(1, Ordering::Relaxed) + 1);
+                        ResponseTemplate::new(200).insert_header("request-id", request_id).set_body_bytes(AUDIO)
+                    }),
+                Mock::given(method("POST"))
+                    .and(path_regex(r"^/text-to-speech/[^/]+/with-timestamps$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                        "audio_base64": base64::engine::general_purpose::STANDARD.encode(AUDIO),
+                        "alignment": {
+                            "characters": ["H", "i", " ", "y", "o", "u"],
+                            "character_start_times_seconds": [0.0, 0.1, 0.2, 0.3, 0.4, 0.5],
+                            "character_end_times_seconds": [0.1, 0.2, 0.3, 0.4, 0.5, 0.6]
+                        },
+                        "normalized_alignment": null
+                    }))),
+                Mock::given(method("POST"))
+                    .and(path("/sound-generation"))
+                    .respond_with(ResponseTemplate::new(200).set_body_bytes(AUDIO)),
+                Mock::given(method("POST"))
+                    .and(path_regex(r"^/speech-to-speech/[^/]+$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_bytes(AUDIO)),
+                Mock::given(method("POST"))
+                    .and(path("/audio-isolation"))
+                    .respond_with(ResponseTemplate::new(200).set_body_bytes(AUDIO)),
+                Mock::given(method("POST"))
+                    .and(path("/dubbing"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                        "dubbing_id": "dub-1",
+                        "expected_duration_sec": 12.0
+                    }))),
+                Mock::given(method("GET"))
+                    .and(path_regex(r"^/dubbing/[^/]+$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                        "dubbing_id": "dub-1",
+                        "status": "dubbed",
+                        "target_languages": ["es"]
+                    }))),
+                Mock::given(method("GET"))
+                    .and(path_regex(r"^/dubbing/[^/]+/audio/[^/]+$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_bytes(AUDIO)),
+                Mock::given(method("POST"))
+                    .and(path("/music"))
+                    .respond_with(ResponseTemplate::new(200).set_body_bytes(AUDIO)),
+                Mock::given(method("GET")).and(path("/voices")).respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("etag", "\"v1\"")
+                        .set_body_json(serde_json::json!({ "voices": [voice("rachel"), voice("adam")] })),
+                ),
+                Mock::given(method("GET")).and(path("/models")).respond_with(
+                    ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                        {
+                            "model_id": "eleven_multilingual_v2",
+                            "name": "Eleven Multilingual v2",
+                            "can_do_text_to_speech": true,
+                            "can_do_voice_conversion": false,
+                            "languages": [{ "language_id": "en", "name": "English" }, { "language_id": "es", "name": "Spanish" }]
+                        },
+                        {
+                            "model_id": "eleven_multilingual_sts_v2",
+                            "name": "Eleven Multilingual v2 (STS)",
+                            "can_do_text_to_speech": false,
+                            "can_do_voice_conversion": true,
+                            "languages": [{ "language_id": "en", "name": "English" }]
+                        }
+                    ])),
+                ),
+                Mock::given(method("GET")).and(path("/shared-voices")).respond_with(
+                    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                        "voices": [{
+                            "public_owner_id": "owner-1",
+                            "voice_id": "shared-1",
+                            "name": "Old Radio Host",
+                            "gender": "male",
+                            "accent": "british",
+                            "use_case": "narrative_story",
+                            "cloned_by_count": 42
+                        }],
+                        "has_more": true
+                    })),
+                ),
+                Mock::given(method("POST"))
+                    .and(path_regex(r"^/voices/add/[^/]+/[^/]+$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "voice_id": "shared-1" }))),
+                Mock::given(method("GET"))
+                    .and(path("/voices/shared-1"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(voice("shared-1"))),
+                Mock::given(method("POST"))
+                    .and(path("/voices/add"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "voice_id": "cloned" }))),
+                Mock::given(method("GET"))
+                    .and(path("/voices/cloned"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(voice("cloned"))),
+                Mock::given(method("POST"))
+                    .and(path("/text-to-voice/create-previews"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                        "previews": [
+                            { "generated_voice_id": "gen-1", "audio_base_64": "SUQz", "media_type": "audio/mpeg", "duration_secs": 4.2 },
+                            { "generated_voice_id": "gen-2", "audio_base_64": "SUQz", "media_type": "audio/mpeg", "duration_secs": 3.9 }
+                        ],
+                        "text": "Ahoy there, and welcome aboard."
+                    }))),
+                Mock::given(method("POST"))
+                    .and(path("/text-to-voice/create-voice-from-preview"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(voice("designed"))),
+                Mock::given(method("POST"))
+                    .and(path_regex(r"^/voices/[^/]+/edit$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "ok" }))),
+                Mock::given(method("DELETE"))
+                    .and(path_regex(r"^/voices/[^/]+/samples/[^/]+$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "ok" }))),
+                Mock::given(method("DELETE"))
+                    .and(path_regex(r"^/voices/[^/]+$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "ok" }))),
+                Mock::given(method("POST"))
+                    .and(path("/speech-to-text"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                        "text": "hello there",
+                        "language_code": "en",
+                        "words": [
+                            { "text": "hello", "start": 0.0, "end": 0.4, "type": "word", "speaker_id": "speaker_0" },
+                            { "text": " ", "start": 0.4, "end": 0.5, "type": "spacing" },
+                            { "text": "there", "start": 0.5, "end": 0.9, "type": "word", "speaker_id": "speaker_0" }
+                        ]
+                    }))),
+                Mock::given(method("GET")).and(path("/history")).respond_with(
+                    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                        "history": [history_item("h-2"), history_item("h-1")],
+                        "last_history_item_id": "h-1",
+                        "has_more": false
+                    })),
+                ),
+                Mock::given(method("GET"))
+                    .and(path_regex(r"^/history/[^/]+/audio$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_bytes(AUDIO)),
+                Mock::given(method("GET"))
+                    .and(path_regex(r"^/history/[^/]+$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(history_item("h-1"))),
+                Mock::given(method("DELETE"))
+                    .and(path_regex(r"^/history/[^/]+$"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "ok" }))),
+                Mock::given(method("GET")).and(path("/user/subscription")).respond_with(
+                    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                        "character_count": 1000,
+                        "character_limit": 10000,
+                        "can_extend_character_limit": false,
+                        "allowed_to_extend_character_limit": false,
+                        "next_character_count_reset_unix": 0,
+                        "voice_limit": 10,
+                        "professional_voice_limit": 1,
+                        "can_extend_voice_limit": false,
+                        "can_use_instant_voice_cloning": true,
+                        "can_use_professional_voice_cloning": false
+                    })),
+                ),
+            ];
+            for mock in canned {
+                mock.with_priority(DEFAULT_PRIORITY).mount(&server).await;
+            }
+            Self { server }
+        }
+
+        /// A real client talking to the mock server
+        pub fn client(&self) -> ElevenLabsClient {
+            ElevenLabsClient::with_base_url("test-key".to_string(), &self.server.uri()).unwrap()
+        }
+
+        pub fn api(&self) -> Arc<dyn ElevenLabsApi> {
+            Arc::new(self.client())
+        }
+    }
+}
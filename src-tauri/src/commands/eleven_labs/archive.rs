@@ -0,0 +1,704 @@
+use anyhow::{anyhow, Result};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::State;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::cache::{AudioCache, AudioCacheDb, CharacterVoiceDb, SettingsDb, VoiceProfileDb};
+use super::error::AudioError;
+use super::external_editor::EXTERNAL_EDITOR_KEY;
+use super::live_output::LIVE_OUTPUT_KEY;
+use super::logging::LOGGING_CONFIG_KEY;
+use super::processing::AUDIO_HOOKS_KEY;
+use super::recovery::AUDIO_DB_RECOVERY_KEY;
+use super::types::*;
+use super::{conflicts, project_files};
+use super::{ensure_cache, ElevenLabsState, AUDIO_CACHE_DIR_KEY, AUDIO_CACHE_PREVIOUS_DIRS_KEY};
+
+/// Identifies opcode library archives
+const ARCHIVE_FORMAT: &str = "opcode-audio-library";
+
+/// Identifies bundles of selected audio made for handing off
+const BUNDLE_FORMAT: &str = "opcode-audio-bundle";
+
+/// Longest prompt excerpt used in a bundled file's name
+const BUNDLE_NAME_CHARS: usize = 40;
+
+/// Version of the archive payload layout. Bump when the JSON table dumps change shape
+/// and add an upgrade step to `PAYLOAD_UPGRADES`.
+const ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+/// First archive layout; nothing older can be imported
+const MIN_ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+/// Table dumps of an archive, by entry name
+type ArchiveTables = [(&'static str, serde_json::Value)];
+
+/// Steps bringing table dumps up one version: the first turns a
+/// `MIN_ARCHIVE_SCHEMA_VERSION` archive into the next version, and so on up to
+/// `ARCHIVE_SCHEMA_VERSION`
+const PAYLOAD_UPGRADES: &[fn(&mut ArchiveTables) -> Result<()>] = &[];
+
+/// Settings that must never leave the machine
+const SECRET_SETTING_KEYS: &[&str] = &["api_key"];
+
+/// Settings that belong to this machine: its sync identity, which another
+/// machine taking over would overwrite edits with, and local paths
+const MACHINE_SETTING_KEYS: &[&str] = &[
+    conflicts::DEVICE_ID_KEY,
+    AUDIO_CACHE_DIR_KEY,
+    AUDIO_CACHE_PREVIOUS_DIRS_KEY,
+    LIVE_OUTPUT_KEY,
+    LOGGING_CONFIG_KEY,
+    AUDIO_DB_RECOVERY_KEY,
+];
+
+/// Settings naming programs the app runs. They are exported, but an archive
+/// can't set them up: the user configures them again on the new machine.
+const EXECUTABLE_SETTING_KEYS: &[&str] = &[AUDIO_HOOKS_KEY, EXTERNAL_EDITOR_KEY];
+
+fn is_portable_setting(key: &str) -> bool {
+    !SECRET_SETTING_KEYS.contains(&key) && !MACHINE_SETTING_KEYS.contains(&key)
+}
+
+/// Also matches the per-project `audio_hooks:<project id>` keys
+fn is_executable_setting(key: &str) -> bool {
+    EXECUTABLE_SETTING_KEYS
+        .iter()
+        .any(|executable| key == *executable || key.strip_prefix(executable).is_some_and(|rest| rest.starts_with(':')))
+}
+
+/// JSON field names stripped from exported setting values
+const SECRET_FIELD_MARKERS: &[&str] = &["secret", "password", "token", "api_key"];
+
+/// Manifest written as `manifest.json` at the root of the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format: String,
+    pub schema_version: u32,
+    pub app_version: String,
+    pub exported_at: String,
+    pub audio_count: usize,
+    pub voice_count: usize,
+    pub character_voice_count: usize,
+    pub setting_count: usize,
+}
+
+/// Table dumps stored under `data/` in the archive
+#[derive(Debug, Default)]
+struct ArchivePayload {
+    audio: Vec<GeneratedAudio>,
+    voices: Vec<VoiceProfile>,
+    character_voices: Vec<CharacterVoice>,
+    settings: Vec<(String, String)>,
+}
+
+/// Summary returned by export and import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryArchiveSummary {
+    pub path: String,
+    pub schema_version: u32,
+    pub audio_count: usize,
+    pub voice_count: usize,
+    pub character_voice_count: usize,
+    pub setting_count: usize,
+    /// Settings in the archive that run programs and were not imported
+    #[serde(default)]
+    pub skipped_settings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// One audio file in a bundle, described for someone without the app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleEntry {
+    /// Path of the file within the bundle
+    pub file: String,
+    pub id: String,
+    pub audio_type: AudioType,
+    pub prompt: String,
+    pub duration_seconds: f32,
+    pub created_at: String,
+    #[serde(default)]
+    pub voice_id: Option<String>,
+    #[serde(default)]
+    pub voice_name: Option<String>,
+    /// Model, voice settings, seed and the like the audio was rendered with
+    #[serde(default)]
+    pub params: Option<GenerationParams>,
+}
+
+/// Manifest written as `manifest.json` at the root of an audio bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format: String,
+    pub app_version: String,
+    pub exported_at: String,
+    pub audio: Vec<BundleEntry>,
+}
+
+/// Summary returned by a bundle export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioBundleSummary {
+    pub path: String,
+    pub audio_count: usize,
+    /// Records that were not found or whose file could not be read
+    pub errors: Vec<String>,
+}
+
+/// Remove credential-looking fields from a JSON setting value
+fn strip_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|key, _| {
+                let key = key.to_lowercase();
+                !SECRET_FIELD_MARKERS.iter().any(|marker| key.contains(marker))
+            });
+            for nested in map.values_mut() {
+                strip_secrets(nested);
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_secrets),
+        _ => {}
+    }
+}
+
+fn exportable_settings(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut settings = vec![];
+    for (key, value) in SettingsDb::get_all_settings(conn)? {
+        if !is_portable_setting(&key) {
+            continue;
+        }
+        let value = match serde_json::from_str::<serde_json::Value>(&value) {
+            Ok(mut json) if json.is_object() => {
+                strip_secrets(&mut json);
+                serde_json::to_string(&json)?
+            }
+            _ => value,
+        };
+        settings.push((key, value));
+    }
+    Ok(settings)
+}
+
+fn audio_subdir(audio_type: &AudioType) -> &'static str {
+    match audio_type {
+        AudioType::Tts => "tts",
+        AudioType::Sfx => "sfx",
+        AudioType::Music => "music",
+    }
+}
+
+fn write_json<W: Write + std::io::Seek, T: Serialize>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    value: &T,
+) -> Result<()> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(name, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(value)?)?;
+    Ok(())
+}
+
+fn read_entry<R: Read + std::io::Seek>(zip: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>> {
+    let mut file = zip
+        .by_name(name)
+        .map_err(|e| anyhow!("Archive entry {} missing: {}", name, e))?;
+    let mut data = vec![];
+    file.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Write the whole audio library (tables, settings minus secrets, and files) to a zip archive
+pub fn export_library(conn: &Connection, dest: &Path) -> Result<LibraryArchiveSummary> {
+    let mut errors = vec![];
+    let mut payload = ArchivePayload::default();
+
+    for audio_type in [AudioType::Tts, AudioType::Sfx, AudioType::Music] {
+        payload
+            .audio
+            .extend(AudioCacheDb::get_audio_records(conn, &audio_type)?);
+    }
+    payload.voices = VoiceProfileDb::get_voice_profiles(conn)?;
+    payload.character_voices = CharacterVoiceDb::get_character_voices(conn, None)?;
+    payload.settings = exportable_settings(conn)?;
+
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = ZipWriter::new(File::create(dest)?);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    // Copy audio files and rewrite paths to be archive-relative
+    let mut exported_audio = vec![];
+    for mut record in payload.audio {
+        let source = PathBuf::from(&record.local_path);
+        let file_name = match source.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name.to_string(),
+            None => {
+                errors.push(format!("{}: invalid path {}", record.id, record.local_path));
+                continue;
+            }
+        };
+        let data = match std::fs::read(&source) {
+            Ok(data) => data,
+            Err(e) => {
+                errors.push(format!("{}: failed to read {}: {}", record.id, record.local_path, e));
+                continue;
+            }
+        };
+
+        let entry_name = format!("audio/{}/{}", audio_subdir(&record.audio_type), file_name);
+        zip.start_file(entry_name.as_str(), stored)?;
+        zip.write_all(&data)?;
+
+        record.local_path = entry_name;
+        exported_audio.push(record);
+    }
+
+    let manifest = ArchiveManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        schema_version: ARCHIVE_SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        audio_count: exported_audio.len(),
+        voice_count: payload.voices.len(),
+        character_voice_count: payload.character_voices.len(),
+        setting_count: payload.settings.len(),
+    };
+
+    write_json(&mut zip, "data/audio_cache.json", &exported_audio)?;
+    write_json(&mut zip, "data/voice_profiles.json", &payload.voices)?;
+    write_json(&mut zip, "data/character_voices.json", &payload.character_voices)?;
+    write_json(&mut zip, "data/settings.json", &payload.settings)?;
+    write_json(&mut zip, "manifest.json", &manifest)?;
+    zip.finish()?;
+
+    Ok(LibraryArchiveSummary {
+        path: dest.to_string_lossy().to_string(),
+        schema_version: ARCHIVE_SCHEMA_VERSION,
+        audio_count: manifest.audio_count,
+        voice_count: manifest.voice_count,
+        character_voice_count: manifest.character_voice_count,
+        setting_count: manifest.setting_count,
+        skipped_settings: Vec::new(),
+        errors,
+    })
+}
+
+/// Write the records `audio_ids` names, in that order, to a zip archive at
+/// `dest`: each file under its type's directory, named after its prompt, and
+/// a `manifest.json` describing them
+pub fn export_bundle(conn: &Connection, audio_ids: &[String], dest: &Path) -> Result<AudioBundleSummary> {
+    let mut errors = vec![];
+    let mut records = vec![];
+    for id in audio_ids {
+        match AudioCacheDb::get_audio_record(conn, id)? {
+            Some(record) => records.push(record),
+            None => errors.push(format!("{}: no such audio", id)),
+        }
+    }
+
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = ZipWriter::new(File::create(dest)?);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let mut entries = vec![];
+    for record in records {
+        let data = match std::fs::read(&record.local_path) {
+            Ok(data) => data,
+            Err(e) => {
+                errors.push(format!("{}: failed to read {}: {}", record.id, record.local_path, e));
+                continue;
+            }
+        };
+        let extension = Path::new(&record.local_path).extension().and_then(|e| e.to_str()).unwrap_or("mp3");
+        let excerpt: String = record.prompt.chars().take(BUNDLE_NAME_CHARS).collect();
+        // Numbered so that takes of the same prompt don't collide
+        let file = format!(
+            "{}/{:03}-{}.{}",
+            audio_subdir(&record.audio_type),
+            entries.len() + 1,
+            project_files::file_stem_for(&excerpt),
+            extension
+        );
+        zip.start_file(file.as_str(), stored)?;
+        zip.write_all(&data)?;
+
+        let voice_id = record.voice_id().map(str::to_string);
+        let voice_name = match &voice_id {
+            Some(voice_id) => VoiceProfileDb::get_voice_profile(conn, voice_id)?.map(|voice| voice.name),
+            None => None,
+        };
+        entries.push(BundleEntry {
+            file,
+            id: record.id,
+            audio_type: record.audio_type,
+            prompt: record.prompt,
+            duration_seconds: record.duration_seconds,
+            created_at: record.created_at,
+            voice_id,
+            voice_name,
+            params: record.params,
+        });
+    }
+
+    let manifest = BundleManifest {
+        format: BUNDLE_FORMAT.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        audio: entries,
+    };
+    write_json(&mut zip, "manifest.json", &manifest)?;
+    zip.finish()?;
+
+    Ok(AudioBundleSummary {
+        path: dest.to_string_lossy().to_string(),
+        audio_count: manifest.audio.len(),
+        errors,
+    })
+}
+
+/// Bring table dumps from an older archive up to the current layout
+fn upgrade_payload(schema_version: u32, tables: &mut ArchiveTables) -> Result<()> {
+    if schema_version > ARCHIVE_SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Archive schema version {} is newer than supported version {}; update opcode first",
+            schema_version,
+            ARCHIVE_SCHEMA_VERSION
+        ));
+    }
+    if schema_version < MIN_ARCHIVE_SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Archive schema version {} predates the oldest supported version {}",
+            schema_version,
+            MIN_ARCHIVE_SCHEMA_VERSION
+        ));
+    }
+
+    let pending = PAYLOAD_UPGRADES.iter().skip((schema_version - MIN_ARCHIVE_SCHEMA_VERSION) as usize);
+    for (from, upgrade) in (schema_version..).zip(pending) {
+        upgrade(tables).map_err(|e| anyhow!("Failed to upgrade archive from version {}: {}", from, e))?;
+    }
+    Ok(())
+}
+
+/// Audio files copied into the cache by an import, deleted on drop unless the
+/// import commits, so a failed import leaves no files without records
+struct ImportedFiles(Vec<PathBuf>);
+
+impl Drop for ImportedFiles {
+    fn drop(&mut self) {
+        for path in self.0.drain(..) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Restore a library archive into the local database and audio cache.
+///
+/// Audio files are copied into the cache and record paths rewritten to their new
+/// location. Existing records with the same id are replaced.
+pub fn import_library(
+    conn: &Connection,
+    cache: &AudioCache,
+    src: &Path,
+) -> Result<LibraryArchiveSummary> {
+    let mut zip = ZipArchive::new(File::open(src)?)?;
+    let mut errors = vec![];
+
+    let manifest: ArchiveManifest = serde_json::from_slice(&read_entry(&mut zip, "manifest.json")?)?;
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(anyhow!("Not an opcode library archive"));
+    }
+
+    let mut tables = [
+        ("data/audio_cache.json", serde_json::Value::Null),
+        ("data/voice_profiles.json", serde_json::Value::Null),
+        ("data/character_voices.json", serde_json::Value::Null),
+        ("data/settings.json", serde_json::Value::Null),
+    ];
+    for (name, value) in tables.iter_mut() {
+        *value = serde_json::from_slice(&read_entry(&mut zip, name)?)?;
+    }
+    upgrade_payload(manifest.schema_version, &mut tables)?;
+
+    let [(_, audio), (_, voices), (_, character_voices), (_, settings)] = tables;
+    let audio: Vec<GeneratedAudio> = serde_json::from_value(audio)?;
+    let voices: Vec<VoiceProfile> = serde_json::from_value(voices)?;
+    let character_voices: Vec<CharacterVoice> = serde_json::from_value(character_voices)?;
+    let settings: Vec<(String, String)> = serde_json::from_value(settings)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let mut imported = ImportedFiles(Vec::new());
+
+    let mut audio_count = 0;
+    for mut record in audio {
+        let data = match read_entry(&mut zip, &record.local_path) {
+            Ok(data) => data,
+            Err(e) => {
+                errors.push(format!("{}: {}", record.id, e));
+                continue;
+            }
+        };
+        let extension = Path::new(&record.local_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("mp3")
+            .to_string();
+
+        let dir = cache.cache_dir().join(audio_subdir(&record.audio_type));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.{}", uuid::Uuid::new_v4(), extension));
+        imported.0.push(path.clone());
+        std::fs::write(&path, &data)?;
+
+        record.local_path = path.to_string_lossy().to_string();
+        AudioCacheDb::save_audio_record(&tx, &record)?;
+        audio_count += 1;
+    }
+
+    for voice in &voices {
+        VoiceProfileDb::save_voice_profile(&tx, voice, &voice.voice_id)?;
+    }
+
+    for mapping in &character_voices {
+        CharacterVoiceDb::save_mapping(&tx, mapping)?;
+    }
+
+    // Archives written before machine settings were left out still carry them
+    let mut setting_count = 0;
+    let mut skipped_settings = vec![];
+    for (key, value) in &settings {
+        if !is_portable_setting(key) {
+            continue;
+        }
+        if is_executable_setting(key) {
+            skipped_settings.push(key.clone());
+            continue;
+        }
+        SettingsDb::save_setting(&tx, key, value)?;
+        setting_count += 1;
+    }
+
+    tx.commit()?;
+    imported.0.clear();
+
+    Ok(LibraryArchiveSummary {
+        path: src.to_string_lossy().to_string(),
+        schema_version: manifest.schema_version,
+        audio_count,
+        voice_count: voices.len(),
+        character_voice_count: character_voices.len(),
+        setting_count,
+        skipped_settings,
+        errors,
+    })
+}
+
+// ========== Tauri Commands ==========
+
+/// Export the full audio library to a single archive for machine migration
+#[tauri::command]
+pub async fn export_full_library(
+    state: State<'_, Arc<ElevenLabsState>>,
+    dest: String,
+) -> Result<LibraryArchiveSummary, AudioError> {
+    state.call_db(move |conn| export_library(conn, Path::new(&dest))).await
+}
+
+/// Package the given cached audio records into a zip archive at `dest`, with a
+/// `manifest.json` listing each file's prompt, voice, generation settings and
+/// duration, for handing assets to someone outside the app
+#[tauri::command]
+pub async fn export_audio_bundle(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_ids: Vec<String>,
+    dest: String,
+) -> Result<AudioBundleSummary, AudioError> {
+    if audio_ids.is_empty() {
+        return Err(AudioError::Validation("No audio selected for the bundle".to_string()));
+    }
+    state.call_db(move |conn| export_bundle(conn, &audio_ids, Path::new(&dest))).await
+}
+
+/// Import a full library archive produced by `export_full_library`
+#[tauri::command]
+pub async fn import_full_library(
+    state: State<'_, Arc<ElevenLabsState>>,
+    src: String,
+) -> Result<LibraryArchiveSummary, AudioError> {
+    let cache = ensure_cache(&state).await?;
+    state.call_db(move |conn| import_library(conn, &cache, Path::new(&src))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::eleven_labs::schema;
+
+    #[test]
+    fn test_export_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        let voice = VoiceProfile {
+            voice_id: "v1".to_string(),
+            name: "Rachel".to_string(),
+            description: None,
+            category: "premade".to_string(),
+            labels: None,
+            preview_url: None,
+            settings: Default::default(),
+            samples: Vec::new(),
+        };
+        VoiceProfileDb::save_voice_profile(&conn, &voice, "v1").unwrap();
+        let local_path = dir.path().join("a.mp3");
+        std::fs::write(&local_path, b"audio").unwrap();
+        let audio = GeneratedAudio {
+            id: "a".to_string(),
+            audio_type: AudioType::Tts,
+            prompt: "Hello, world!".to_string(),
+            duration_seconds: 1.5,
+            local_path: local_path.to_string_lossy().to_string(),
+            supabase_url: None,
+            metadata: serde_json::json!({}),
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            params: Some(GenerationParams {
+                voice_id: Some("v1".to_string()),
+                ..GenerationParams::new(PROVIDER_ELEVENLABS)
+            }),
+            cached: false,
+        };
+        AudioCacheDb::save_audio_record(&conn, &audio).unwrap();
+
+        let dest = dir.path().join("out").join("bundle.zip");
+        let ids = ["a".to_string(), "missing".to_string()];
+        let summary = export_bundle(&conn, &ids, &dest).unwrap();
+        assert_eq!(summary.audio_count, 1);
+        assert_eq!(summary.errors.len(), 1);
+
+        let mut zip = ZipArchive::new(File::open(&dest).unwrap()).unwrap();
+        let manifest: BundleManifest = serde_json::from_slice(&read_entry(&mut zip, "manifest.json").unwrap()).unwrap();
+        assert_eq!(manifest.format, BUNDLE_FORMAT);
+        let entry = &manifest.audio[0];
+        assert_eq!(entry.file, "tts/001-hello--world.mp3");
+        assert_eq!(entry.voice_name.as_deref(), Some("Rachel"));
+        assert_eq!(read_entry(&mut zip, &entry.file).unwrap(), b"audio");
+    }
+
+    #[test]
+    fn test_failed_import_leaves_no_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        let local_path = dir.path().join("a.mp3");
+        std::fs::write(&local_path, b"audio").unwrap();
+        let audio = GeneratedAudio {
+            id: "a".to_string(),
+            audio_type: AudioType::Sfx,
+            prompt: "Door".to_string(),
+            duration_seconds: 1.0,
+            local_path: local_path.to_string_lossy().to_string(),
+            supabase_url: None,
+            metadata: serde_json::json!({}),
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            params: None,
+            cached: false,
+        };
+        AudioCacheDb::save_audio_record(&conn, &audio).unwrap();
+        SettingsDb::save_setting(&conn, "report_cost_per_1k_chars", "0.30").unwrap();
+        let archive = dir.path().join("library.zip");
+        export_library(&conn, &archive).unwrap();
+
+        // Saving the settings fails after the audio file was copied in
+        let target = Connection::open_in_memory().unwrap();
+        schema::init(&target).unwrap();
+        target.execute_batch("DROP TABLE eleven_labs_settings").unwrap();
+        let cache = AudioCache::new(dir.path().join("cache")).unwrap();
+        assert!(import_library(&target, &cache, &archive).is_err());
+        assert_eq!(std::fs::read_dir(cache.cache_dir().join("sfx")).unwrap().count(), 0);
+        assert_eq!(target.query_row("SELECT COUNT(*) FROM audio_cache", [], |row| row.get::<_, i64>(0)).unwrap(), 0);
+
+        assert!(upgrade_payload(0, &mut []).is_err());
+        assert!(upgrade_payload(ARCHIVE_SCHEMA_VERSION + 1, &mut []).is_err());
+    }
+
+    #[test]
+    fn test_machine_settings_are_not_exported() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        SettingsDb::save_setting(&conn, conflicts::DEVICE_ID_KEY, "device-a").unwrap();
+        SettingsDb::save_setting(&conn, "api_key", "sk-1").unwrap();
+        SettingsDb::save_setting(&conn, AUDIO_CACHE_DIR_KEY, "/music").unwrap();
+        SettingsDb::save_setting(&conn, LIVE_OUTPUT_KEY, "{}").unwrap();
+        SettingsDb::save_setting(&conn, "report_cost_per_1k_chars", "0.30").unwrap();
+
+        let keys: Vec<String> = exportable_settings(&conn).unwrap().into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["report_cost_per_1k_chars".to_string()]);
+    }
+
+    #[test]
+    fn test_import_keeps_local_paths_and_skips_programs() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        SettingsDb::save_setting(&conn, "report_cost_per_1k_chars", "0.30").unwrap();
+        SettingsDb::save_setting(&conn, "audio_hooks:p1", r#"{"after_generation":[]}"#).unwrap();
+        SettingsDb::save_setting(&conn, EXTERNAL_EDITOR_KEY, r#"{"command":"/bin/sh"}"#).unwrap();
+        let archive = dir.path().join("library.zip");
+        export_library(&conn, &archive).unwrap();
+
+        // An archive from before machine settings were left out
+        let mut zip = ZipWriter::new(File::create(dir.path().join("old.zip")).unwrap());
+        let mut source = ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+        let tables = ["data/audio_cache.json", "data/voice_profiles.json", "data/character_voices.json"];
+        for name in std::iter::once("manifest.json").chain(tables) {
+            zip.raw_copy_file(source.by_name(name).unwrap()).unwrap();
+        }
+        let mut settings: Vec<(String, String)> =
+            serde_json::from_slice(&read_entry(&mut source, "data/settings.json").unwrap()).unwrap();
+        settings.push((AUDIO_CACHE_DIR_KEY.to_string(), "/elsewhere".to_string()));
+        write_json(&mut zip, "data/settings.json", &settings).unwrap();
+        zip.finish().unwrap();
+
+        let target = Connection::open_in_memory().unwrap();
+        schema::init(&target).unwrap();
+        SettingsDb::save_setting(&target, AUDIO_CACHE_DIR_KEY, "/here").unwrap();
+        let cache = AudioCache::new(dir.path().join("cache")).unwrap();
+        let summary = import_library(&target, &cache, &dir.path().join("old.zip")).unwrap();
+
+        assert_eq!(summary.setting_count, 1);
+        assert_eq!(summary.skipped_settings, vec!["audio_hooks:p1".to_string(), EXTERNAL_EDITOR_KEY.to_string()]);
+        assert_eq!(SettingsDb::get_setting(&target, "report_cost_per_1k_chars").unwrap().as_deref(), Some("0.30"));
+        assert_eq!(SettingsDb::get_setting(&target, AUDIO_CACHE_DIR_KEY).unwrap().as_deref(), Some("/here"));
+        assert_eq!(SettingsDb::get_setting(&target, EXTERNAL_EDITOR_KEY).unwrap(), None);
+        assert!(!is_executable_setting("audio_hooksx"));
+    }
+
+    #[test]
+    fn test_strip_secrets() {
+        let mut value = serde_json::json!({
+            "endpoint": "https://s3.example.com",
+            "access_key_id": "AKID",
+            "secret_access_key": "hunter2",
+            "nested": { "password": "x", "url": "y" }
+        });
+        strip_secrets(&mut value);
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "endpoint": "https://s3.example.com",
+                "access_key_id": "AKID",
+                "nested": { "url": "y" }
+            })
+        );
+    }
+}
@@ -0,0 +1,110 @@
+// Generated audio sent to the frontend over a Tauri channel as it arrives from
+// the provider, alongside the usual write to the cache, so the UI can start
+// playback with the first chunk instead of waiting for the file and reading it
+// back.
+
+use base64::Engine;
+use bytes::Bytes;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::ipc::Channel;
+use tauri::State;
+
+use super::error::AudioError;
+use super::pipeline::{self, ChunkTap, TtsTransport};
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Messages on the channel of a streamed generation, in order: `started`, a
+/// `chunk` per piece of audio, then `finished` with the saved record. On
+/// failure the command returns the error and nothing more is sent.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "camelCase")]
+pub enum AudioStreamEvent {
+    #[serde(rename_all = "camelCase")]
+    Started { mime_type: String },
+    /// Base64 audio bytes starting at `offset` in the file
+    Chunk { offset: u64, data: String },
+    Finished { audio: Box<GeneratedAudio> },
+}
+
+/// MIME type of audio in an Eleven Labs output format (`mp3_44100_128`, ...)
+pub fn mime_type(output_format: &str) -> &'static str {
+    match output_format.split('_').next() {
+        Some("pcm") => "audio/L16",
+        Some("ulaw") => "audio/basic",
+        Some("opus") => "audio/ogg",
+        _ => "audio/mpeg",
+    }
+}
+
+/// A tap forwarding each chunk to `channel`. A closed channel (e.g. the
+/// window went away) doesn't stop the generation; the file is still cached.
+fn channel_tap(channel: Channel<AudioStreamEvent>) -> ChunkTap {
+    let mut offset = 0u64;
+    Box::new(move |chunk: &Bytes| {
+        let event = AudioStreamEvent::Chunk {
+            offset,
+            data: base64::engine::general_purpose::STANDARD.encode(chunk),
+        };
+        offset += chunk.len() as u64;
+        if let Err(e) = channel.send(event) {
+            log::debug!("Audio stream channel closed: {}", e);
+        }
+    })
+}
+
+/// Generate text-to-speech like `eleven_labs_tts`, also sending the audio on
+/// `on_audio` while it is written to the cache
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn eleven_labs_tts_stream(
+    state: State<'_, Arc<ElevenLabsState>>,
+    text: String,
+    voice_id: String,
+    model_id: Option<String>,
+    voice_settings: Option<VoiceSettings>,
+    project_id: Option<String>,
+    normalize_text: Option<bool>,
+    preset: Option<String>,
+    on_audio: Channel<AudioStreamEvent>,
+) -> Result<GeneratedAudio, AudioError> {
+    let request = TtsRequest {
+        text,
+        voice_id,
+        model_id: model_id.unwrap_or_else(default_model_id),
+        voice_settings,
+        output_format: default_output_format(),
+        normalize_text,
+        preset,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
+    };
+
+    let _ = on_audio.send(AudioStreamEvent::Started {
+        mime_type: mime_type(&request.output_format).to_string(),
+    });
+    let tap = channel_tap(on_audio.clone());
+    let audio =
+        pipeline::generate_tts_streamed(&state, request, project_id.as_deref(), tap, TtsTransport::Http).await?;
+    let _ = on_audio.send(AudioStreamEvent::Finished { audio: Box::new(audio.clone()) });
+    Ok(audio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_shape() {
+        assert_eq!(mime_type("mp3_44100_128"), "audio/mpeg");
+        assert_eq!(mime_type("pcm_16000"), "audio/L16");
+
+        let started = serde_json::to_value(AudioStreamEvent::Started { mime_type: "audio/mpeg".to_string() }).unwrap();
+        assert_eq!(started, serde_json::json!({ "event": "started", "data": { "mimeType": "audio/mpeg" } }));
+        let chunk = serde_json::to_value(AudioStreamEvent::Chunk { offset: 3, data: "SUQz".to_string() }).unwrap();
+        assert_eq!(chunk["data"]["offset"], 3);
+    }
+}
@@ -0,0 +1,247 @@
+// Tags and favorites for organizing cached audio ("door", "take 3", "keep").
+// Tags live in `audio_tags`, matched regardless of case and kept in the case
+// they were first given; the favorite mark is a flag on the `audio_cache` row.
+// Both go with the record when it is deleted.
+
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::types::{AudioType, GeneratedAudio};
+use super::ElevenLabsState;
+
+/// Longest tag accepted, in characters
+pub const MAX_TAG_LEN: usize = 64;
+
+/// The favorite mark and tags of one cached audio record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioTags {
+    pub audio_id: String,
+    pub favorite: bool,
+    /// In the order they were added
+    pub tags: Vec<String>,
+}
+
+/// A tag and how many records carry it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: u32,
+}
+
+fn validate_tag(tag: &str) -> Result<&str> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(AudioError::Validation("Tag cannot be empty".to_string()).into());
+    }
+    if tag.chars().count() > MAX_TAG_LEN {
+        return Err(AudioError::Validation(format!("Tag is longer than {} characters: {}", MAX_TAG_LEN, tag)).into());
+    }
+    Ok(tag)
+}
+
+fn audio_exists(conn: &Connection, audio_id: &str) -> Result<()> {
+    conn.query_row("SELECT 1 FROM audio_cache WHERE id = ?1", [audio_id], |_| Ok(()))
+        .optional()?
+        .ok_or_else(|| AudioError::Validation(format!("No cached audio: {}", audio_id)))?;
+    Ok(())
+}
+
+pub fn get_tags(conn: &Connection, audio_id: &str) -> Result<Option<AudioTags>> {
+    let Some(favorite) = conn
+        .query_row("SELECT favorite FROM audio_cache WHERE id = ?1", [audio_id], |row| row.get(0))
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    let mut stmt = conn.prepare_cached("SELECT tag FROM audio_tags WHERE audio_id = ?1 ORDER BY added_at, rowid")?;
+    let tags = stmt.query_map([audio_id], |row| row.get(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(Some(AudioTags {
+        audio_id: audio_id.to_string(),
+        favorite,
+        tags,
+    }))
+}
+
+/// Add `tags` to a record; tags it already has, in any case, are skipped
+pub fn add_tags(conn: &mut Connection, audio_id: &str, tags: &[String]) -> Result<AudioTags> {
+    let tags = tags.iter().map(|tag| validate_tag(tag)).collect::<Result<Vec<_>>>()?;
+    audio_exists(conn, audio_id)?;
+    let added_at = chrono::Utc::now().to_rfc3339();
+    let tx = conn.transaction()?;
+    for tag in tags {
+        tx.execute(
+            "INSERT OR IGNORE INTO audio_tags (audio_id, tag, added_at) VALUES (?1, ?2, ?3)",
+            (audio_id, tag, &added_at),
+        )?;
+    }
+    tx.commit()?;
+    Ok(get_tags(conn, audio_id)?.expect("audio checked above"))
+}
+
+pub fn remove_tag(conn: &Connection, audio_id: &str, tag: &str) -> Result<AudioTags> {
+    audio_exists(conn, audio_id)?;
+    conn.execute("DELETE FROM audio_tags WHERE audio_id = ?1 AND tag = ?2", (audio_id, tag.trim()))?;
+    Ok(get_tags(conn, audio_id)?.expect("audio checked above"))
+}
+
+/// Flip the favorite mark of a record
+pub fn toggle_favorite(conn: &Connection, audio_id: &str) -> Result<AudioTags> {
+    audio_exists(conn, audio_id)?;
+    conn.execute("UPDATE audio_cache SET favorite = NOT favorite WHERE id = ?1", [audio_id])?;
+    Ok(get_tags(conn, audio_id)?.expect("audio checked above"))
+}
+
+/// Every tag in use with its number of records, most used first
+pub fn tag_counts(conn: &Connection) -> Result<Vec<TagCount>> {
+    let mut stmt = conn.prepare(
+        "SELECT MIN(tag), COUNT(*) FROM audio_tags GROUP BY tag ORDER BY COUNT(*) DESC, tag",
+    )?;
+    let counts = stmt
+        .query_map([], |row| Ok(TagCount { tag: row.get(0)?, count: row.get(1)? }))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(counts)
+}
+
+/// Records carrying `tag`, newest first, optionally of one type
+pub fn audio_with_tag(conn: &Connection, tag: &str, audio_type: Option<&AudioType>) -> Result<Vec<GeneratedAudio>> {
+    let ids: Vec<String> = conn
+        .prepare(
+            "SELECT a.id FROM audio_cache a JOIN audio_tags t ON t.audio_id = a.id
+             WHERE t.tag = ?1 AND (?2 IS NULL OR a.audio_type = ?2) AND a.deleted_at IS NULL
+             ORDER BY a.created_at DESC",
+        )?
+        .query_map((tag.trim(), audio_type.map(serde_json::to_string).transpose()?), |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut records = Vec::with_capacity(ids.len());
+    for id in ids {
+        records.extend(AudioCacheDb::get_audio_record(conn, &id)?);
+    }
+    Ok(records)
+}
+
+/// Add tags to a cached audio record, returning its tags afterwards
+#[tauri::command]
+pub async fn tag_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_id: String,
+    tags: Vec<String>,
+) -> Result<AudioTags, AudioError> {
+    state.call_db(move |conn| add_tags(conn, &audio_id, &tags)).await
+}
+
+#[tauri::command]
+pub async fn untag_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_id: String,
+    tag: String,
+) -> Result<AudioTags, AudioError> {
+    state.call_db(move |conn| remove_tag(conn, &audio_id, &tag)).await
+}
+
+/// Mark a cached audio record as a favorite, or clear the mark if it has one
+#[tauri::command]
+pub async fn toggle_audio_favorite(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_id: String,
+) -> Result<AudioTags, AudioError> {
+    state.call_db(move |conn| toggle_favorite(conn, &audio_id)).await
+}
+
+/// Favorite marks and tags of several records; unknown ids are left out
+#[tauri::command]
+pub async fn get_audio_tags(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_ids: Vec<String>,
+) -> Result<Vec<AudioTags>, AudioError> {
+    state
+        .call_db(move |conn| {
+            let mut tags = Vec::with_capacity(audio_ids.len());
+            for id in &audio_ids {
+                tags.extend(get_tags(conn, id)?);
+            }
+            Ok(tags)
+        })
+        .await
+}
+
+/// Every tag in use, with how many records carry it
+#[tauri::command]
+pub async fn list_audio_tags(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<TagCount>, AudioError> {
+    state.call_db(|conn| tag_counts(conn)).await
+}
+
+/// Cached audio carrying `tag`, newest first, optionally of one type
+#[tauri::command]
+pub async fn list_audio_by_tag(
+    state: State<'_, Arc<ElevenLabsState>>,
+    tag: String,
+    audio_type: Option<String>,
+) -> Result<Vec<GeneratedAudio>, AudioError> {
+    let audio_type = audio_type.as_deref().map(AudioType::parse).transpose()?;
+    state.call_db(move |conn| audio_with_tag(conn, &tag, audio_type.as_ref())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::eleven_labs::schema;
+    use crate::commands::eleven_labs::types::AudioQuery;
+
+    fn clip(id: &str, audio_type: AudioType, created_at: &str) -> GeneratedAudio {
+        GeneratedAudio {
+            id: id.to_string(),
+            audio_type,
+            prompt: id.to_string(),
+            duration_seconds: 1.0,
+            local_path: format!("/cache/{}.mp3", id),
+            supabase_url: None,
+            metadata: serde_json::json!({}),
+            created_at: created_at.to_string(),
+            params: None,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_tags_and_favorites() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        for audio in [
+            clip("a", AudioType::Sfx, "2024-01-01T00:00:00+00:00"),
+            clip("b", AudioType::Sfx, "2024-02-01T00:00:00+00:00"),
+            clip("c", AudioType::Tts, "2024-03-01T00:00:00+00:00"),
+        ] {
+            AudioCacheDb::save_audio_record(&conn, &audio).unwrap();
+        }
+
+        let tags = add_tags(&mut conn, "a", &["Door".to_string(), " door ".to_string(), "wood".to_string()]).unwrap();
+        assert_eq!(tags.tags, vec!["Door", "wood"]);
+        add_tags(&mut conn, "b", &["DOOR".to_string()]).unwrap();
+        add_tags(&mut conn, "c", &["door".to_string()]).unwrap();
+        assert!(add_tags(&mut conn, "a", &[" ".to_string()]).is_err());
+        assert!(add_tags(&mut conn, "missing", &["door".to_string()]).is_err());
+
+        let ids = |records: Vec<GeneratedAudio>| -> Vec<String> { records.into_iter().map(|a| a.id).collect() };
+        assert_eq!(ids(audio_with_tag(&conn, "door", None).unwrap()), vec!["c", "b", "a"]);
+        assert_eq!(ids(audio_with_tag(&conn, "Door", Some(&AudioType::Sfx)).unwrap()), vec!["b", "a"]);
+        let counts = tag_counts(&conn).unwrap();
+        assert_eq!(counts.len(), 2);
+        assert!(counts[0].tag.eq_ignore_ascii_case("door") && counts[0].count == 3);
+
+        assert!(toggle_favorite(&conn, "a").unwrap().favorite);
+        // Saving the record again keeps its tags and favorite mark
+        AudioCacheDb::save_audio_record(&conn, &clip("a", AudioType::Sfx, "2024-01-01T00:00:00+00:00")).unwrap();
+        let favorites = AudioQuery { favorites_only: true, tag: Some("DOOR".to_string()), ..Default::default() };
+        assert_eq!(ids(AudioCacheDb::query_audio_records(&conn, &AudioType::Sfx, &favorites).unwrap()), vec!["a"]);
+        assert!(!toggle_favorite(&conn, "a").unwrap().favorite);
+
+        assert_eq!(remove_tag(&conn, "a", "DOOR").unwrap().tags, vec!["wood"]);
+        AudioCacheDb::delete_audio_record(&conn, "b").unwrap();
+        assert_eq!(ids(audio_with_tag(&conn, "door", None).unwrap()), vec!["c"]);
+    }
+}
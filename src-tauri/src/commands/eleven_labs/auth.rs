@@ -0,0 +1,162 @@
+// Re-authentication when the API key stops working mid-session. A 401 drops
+// the shared client and emits `audio-auth-required` so the frontend can ask
+// for a new key; until one is saved, requests fail fast instead of hitting the
+// API, and queued jobs wait in `wait_accepted` and then resume.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::watch;
+
+use super::error::{AudioError, ProviderErrorKind};
+use super::ElevenLabsState;
+
+/// Emitted with an `AuthStatus` when the API key is rejected
+pub const AUTH_REQUIRED_EVENT: &str = "audio-auth-required";
+
+/// Whether a new API key is needed, and how many jobs are waiting for it
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthStatus {
+    pub auth_required: bool,
+    pub waiting_jobs: usize,
+}
+
+/// Whether the provider rejected the API key
+pub fn is_unauthorized(error: &AudioError) -> bool {
+    matches!(error, AudioError::Provider { kind: ProviderErrorKind::Unauthorized, .. })
+}
+
+/// Error returned without contacting the API while a new key is needed
+pub fn auth_required_error() -> AudioError {
+    AudioError::provider(
+        ProviderErrorKind::Unauthorized,
+        "The API key was rejected; save a new key to continue",
+    )
+}
+
+/// Tracks whether the current API key is accepted
+pub struct AuthGate {
+    rejected: watch::Sender<bool>,
+    waiting: AtomicUsize,
+    /// Set at startup; the standalone MCP server has no frontend to notify
+    app: OnceLock<AppHandle>,
+}
+
+impl AuthGate {
+    pub fn new() -> Self {
+        Self {
+            rejected: watch::channel(false).0,
+            waiting: AtomicUsize::new(0),
+            app: OnceLock::new(),
+        }
+    }
+
+    /// Send auth events to the frontend of `app`
+    pub fn attach(&self, app: AppHandle) {
+        let _ = self.app.set(app);
+    }
+
+    pub fn is_rejected(&self) -> bool {
+        *self.rejected.borrow()
+    }
+
+    pub fn status(&self) -> AuthStatus {
+        AuthStatus {
+            auth_required: self.is_rejected(),
+            waiting_jobs: self.waiting.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Mark the key rejected, notifying the frontend the first time
+    pub fn reject(&self) {
+        if self.rejected.send_replace(true) {
+            return;
+        }
+        log::warn!("Eleven Labs rejected the API key; waiting for a new one");
+        if let Some(app) = self.app.get() {
+            let _ = app.emit(AUTH_REQUIRED_EVENT, self.status());
+        }
+    }
+
+    /// A valid key was saved: release the waiting jobs
+    pub fn accept(&self) {
+        self.rejected.send_replace(false);
+    }
+
+    /// Wait until the key is accepted again, counted as a waiting job meanwhile
+    pub async fn wait_accepted(&self) {
+        let mut rejected = self.rejected.subscribe();
+        let _waiting = Waiting::new(&self.waiting);
+        let _ = rejected.wait_for(|rejected| !*rejected).await;
+    }
+}
+
+/// Counts a job as waiting until dropped, including when its wait is cancelled
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl<'a> Waiting<'a> {
+    fn new(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        Self(count)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Default for AuthGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pass through the result of a provider request, invalidating the shared
+/// client if it failed because the key was rejected
+pub async fn check<T, E: Into<AudioError>>(state: &ElevenLabsState, result: Result<T, E>) -> Result<T, AudioError> {
+    let result = result.map_err(Into::into);
+    if let Err(error) = &result {
+        if is_unauthorized(error) {
+            *state.client.write().await = None;
+            state.auth.reject();
+        }
+    }
+    result
+}
+
+// ========== Tauri Commands ==========
+
+/// Whether the API key needs to be re-entered
+#[tauri::command]
+pub async fn get_auth_status(state: State<'_, Arc<ElevenLabsState>>) -> Result<AuthStatus, AudioError> {
+    Ok(state.auth.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_waiting_jobs_resume_on_accept() {
+        let gate = Arc::new(AuthGate::new());
+        gate.reject();
+        assert!(gate.status().auth_required);
+
+        let waiter = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.wait_accepted().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(gate.status().waiting_jobs, 1);
+
+        gate.accept();
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert_eq!(gate.status().waiting_jobs, 0);
+        assert!(!gate.status().auth_required);
+    }
+}
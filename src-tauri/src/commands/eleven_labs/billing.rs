@@ -0,0 +1,55 @@
+// Character counting as Eleven Labs bills it. Every estimate, the quota guard
+// and local usage accounting go through `billable_characters` so the numbers
+// shown locally match the invoice.
+//
+// The rules: characters are Unicode scalar values (not bytes or UTF-16 units),
+// surrounding whitespace is trimmed before the request is billed, a CRLF line
+// break is one character, zero-width formatting characters are free, and SSML
+// markup (`<break time="1s"/>`, `<phoneme ...>`) is free while the text inside
+// it is billed.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// SSML elements the API interprets rather than speaks
+fn ssml_tag() -> &'static Regex {
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    TAG.get_or_init(|| {
+        Regex::new(r"(?i)</?(?:speak|break|phoneme|prosody|emphasis|say-as|sub|lang|voice)\b[^<>]*>").unwrap()
+    })
+}
+
+/// Invisible formatting characters that are stripped before billing
+fn is_zero_width(c: char) -> bool {
+    matches!(c, '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}')
+}
+
+/// Characters Eleven Labs bills for rendering `text` as speech
+pub fn billable_characters(text: &str) -> usize {
+    let spoken = ssml_tag().replace_all(text, "");
+    let spoken = spoken.trim();
+    let crlf = spoken.matches("\r\n").count();
+    spoken.chars().filter(|c| !is_zero_width(*c)).count() - crlf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_billable_characters() {
+        assert_eq!(billable_characters("Hello"), 5);
+        assert_eq!(billable_characters("  Hello\n"), 5);
+        assert_eq!(billable_characters("héllo wörld"), 11);
+        assert_eq!(billable_characters("日本語"), 3);
+        assert_eq!(billable_characters("one\r\ntwo"), 7);
+        assert_eq!(billable_characters("zero\u{200B}width\u{FEFF}"), 9);
+        assert_eq!(billable_characters(r#"Wait.<break time="1.5s"/> Go!"#), 9);
+        assert_eq!(
+            billable_characters(r#"<phoneme alphabet="ipa" ph="təˈmeɪtoʊ">tomato</phoneme>"#),
+            6
+        );
+        // Angle brackets that aren't SSML are spoken
+        assert_eq!(billable_characters("a <b> c"), 7);
+    }
+}
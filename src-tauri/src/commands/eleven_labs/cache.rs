@@ -1,21 +1,224 @@
 use anyhow::{anyhow, Result};
+use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::Connection;
 use std::path::{Path, PathBuf};
+use futures::StreamExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
+use super::client::AudioStream;
+use super::encryption;
+use super::error::AudioError;
+use super::recovery;
 use super::types::*;
+use crate::commands::agents::get_db_path;
+
+/// Most connections the audio commands hold to the app database at once
+const POOL_SIZE: u32 = 8;
+
+/// How long a write waits for another connection's to finish before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A connection taken from the pool, returned to it on drop
+pub type PooledConnection = r2d2::PooledConnection<SqliteConnectionManager>;
+
+#[derive(Clone)]
+enum Connections {
+    /// Connections to the app database, opened as concurrent callers need them
+    Pool(r2d2::Pool<SqliteConnectionManager>),
+    /// One connection handed in by the caller, like an in-memory database in
+    /// tests, used by one caller at a time
+    Single(Arc<Mutex<Connection>>),
+}
+
+/// Connections to the app database shared by the audio commands. Cheap to
+/// clone; every clone draws on the same pool.
+#[derive(Clone)]
+pub struct AudioDb {
+    conns: Connections,
+    /// Set once the audio tables were rebuilt, so damage elsewhere in the
+    /// file can't cause a rebuild on every failing query
+    recovered: Arc<AtomicBool>,
+}
+
+fn configure(conn: &mut Connection) -> rusqlite::Result<()> {
+    conn.busy_timeout(BUSY_TIMEOUT)
+}
+
+impl AudioDb {
+    /// Open the app database, creating the audio tables if needed. A damaged
+    /// database is backed up and its audio tables rebuilt.
+    pub fn open() -> Result<Self> {
+        let path = get_db_path().map_err(|e| anyhow!(e))?;
+        // The CLI and MCP server open the database here first; in the app
+        // `agents::init_database` already did this
+        if let Err(e) = encryption::prepare(&path) {
+            log::error!("Failed to read the database passphrase: {}", e);
+        }
+        Self::open_at(&path)
+    }
+
+    /// Open a pool of connections to the database at `path`
+    pub fn open_at(path: &Path) -> Result<Self> {
+        // Set up on one connection before the pool opens more
+        let mut conn = encryption::open(path)?;
+        configure(&mut conn)?;
+        let recovered = recovery::init_or_recover(&mut conn)?;
+        // Lets readers carry on while another connection writes; the mode is
+        // kept in the file
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        drop(conn);
+
+        let manager = SqliteConnectionManager::file(path).with_init(|conn| {
+            encryption::unlock(conn)?;
+            configure(conn)
+        });
+        let pool = r2d2::Pool::builder().max_size(POOL_SIZE).min_idle(Some(1)).build(manager)?;
+        Ok(Self {
+            conns: Connections::Pool(pool),
+            recovered: Arc::new(AtomicBool::new(recovered)),
+        })
+    }
+
+    pub fn from_connection(conn: Connection) -> Self {
+        Self {
+            conns: Connections::Single(Arc::new(Mutex::new(conn))),
+            recovered: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Run `f` with a connection of its own, waiting for one while the pool
+    /// is exhausted. `f` must not call back into `with_blocking` on the same
+    /// handle. If `f` finds the database corrupted, the audio tables are
+    /// rebuilt (once) before the error is returned.
+    ///
+    /// This blocks the calling thread, so it is only for synchronous setup
+    /// code and blocking threads; async code goes through `call`.
+    pub(crate) fn with_blocking<T>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> Result<T>,
+    ) -> std::result::Result<T, AudioError> {
+        match &self.conns {
+            Connections::Pool(pool) => self.run(&mut *pool.get()?, f),
+            Connections::Single(conn) => self.run(&mut *conn.lock()?, f),
+        }
+    }
+
+    fn run<T>(
+        &self,
+        conn: &mut Connection,
+        f: impl FnOnce(&mut Connection) -> Result<T>,
+    ) -> std::result::Result<T, AudioError> {
+        f(conn).map_err(|e| {
+            if recovery::is_corruption(&e) && !self.recovered.swap(true, Ordering::SeqCst) {
+                if let Err(recovery_error) = recovery::recover(conn) {
+                    log::error!("Failed to rebuild the damaged audio tables: {}", recovery_error);
+                }
+            }
+            e.into()
+        })
+    }
+
+    /// Take a connection out of the pool for work that holds one across
+    /// awaits, like a backup interleaving queries with uploads
+    pub fn dedicated(&self) -> std::result::Result<PooledConnection, AudioError> {
+        match &self.conns {
+            Connections::Pool(pool) => Ok(pool.get()?),
+            Connections::Single(_) => {
+                Err(AudioError::Other("A single-connection database has no connection to spare".to_string()))
+            }
+        }
+    }
+
+    /// Like `with_blocking`, but runs `f` on the blocking thread pool so slow
+    /// queries don't stall the async runtime
+    pub async fn call<T, F>(&self, f: F) -> std::result::Result<T, AudioError>
+    where
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let db = self.clone();
+        tokio::task::spawn_blocking(move || db.with_blocking(f)).await?
+    }
+}
+
+/// Directory in the cache holding downloads in progress. Being in the cache
+/// keeps them on the same filesystem, so finishing one is an atomic rename.
+const PARTIAL_DIR: &str = ".partial";
+
+/// File being written by `save_audio_stream`, deleted on drop unless cleared
+struct PartialFile(Option<PathBuf>);
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Size of the header of a WAV file as the provider writes it
+const WAV_HEADER_BYTES: u64 = 44;
+
+/// Playing time of `bytes` of audio in `format`: an output format
+/// (`mp3_44100_128`, `pcm_16000`, `wav_44100`, ...) or a file extension. MP3
+/// and Opus go by their bitrate, 128 kbps for a bare `mp3`; PCM and WAV are
+/// 16-bit mono and μ-law and A-law 8-bit mono at the format's sample rate.
+/// `None` when the format doesn't say enough, like a video or a WAV file of
+/// unknown layout.
+pub fn estimate_duration_seconds(bytes: u64, format: &str) -> Option<f32> {
+    let format = format.to_ascii_lowercase();
+    let mut parts = format.split('_');
+    let codec = parts.next()?;
+    let rate = parts.next().and_then(|part| part.parse::<f64>().ok());
+    let kbps = parts.next().and_then(|part| part.parse::<f64>().ok());
+
+    let (bytes, bytes_per_second) = match (codec, rate, kbps) {
+        ("mp3" | "opus", _, Some(kbps)) => (bytes, kbps * 125.0),
+        ("mp3", None, None) => (bytes, 16000.0),
+        ("pcm", Some(rate), _) => (bytes, rate * 2.0),
+        ("wav", Some(rate), _) => (bytes.saturating_sub(WAV_HEADER_BYTES), rate * 2.0),
+        ("ulaw" | "alaw", Some(rate), _) => (bytes, rate),
+        _ => return None,
+    };
+    Some((bytes as f64 / bytes_per_second) as f32)
+}
 
 /// Audio cache manager for local file storage
 pub struct AudioCache {
     cache_dir: PathBuf,
+    /// Other directories holding files recorded by the cache, such as
+    /// locations used before the cache was moved
+    other_roots: Vec<PathBuf>,
 }
 
 impl AudioCache {
     /// Create a new audio cache manager
     pub fn new(cache_dir: PathBuf) -> Result<Self> {
         std::fs::create_dir_all(&cache_dir)?;
-        Ok(Self { cache_dir })
+        Ok(Self {
+            cache_dir,
+            other_roots: Vec::new(),
+        })
+    }
+
+    /// Create a new audio cache manager from async code
+    pub async fn create(cache_dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&cache_dir).await?;
+        Ok(Self {
+            cache_dir,
+            other_roots: Vec::new(),
+        })
+    }
+
+    /// Also treat files under `roots` as part of the cache
+    pub fn with_roots(mut self, roots: Vec<PathBuf>) -> Self {
+        self.other_roots = roots;
+        self
     }
 
     /// Get the cache directory path
@@ -23,13 +226,24 @@ impl AudioCache {
         &self.cache_dir
     }
 
-    /// Save audio data to cache
-    pub async fn save_audio(
-        &self,
-        audio_type: &AudioType,
-        data: &[u8],
-        extension: &str,
-    ) -> Result<PathBuf> {
+    /// Whether the canonical `path` is a file inside the cache directory or
+    /// one of the other roots
+    async fn contains(&self, path: &Path) -> bool {
+        if !fs::metadata(path).await.map(|m| m.is_file()).unwrap_or(false) {
+            return false;
+        }
+        for root in std::iter::once(&self.cache_dir).chain(&self.other_roots) {
+            if let Ok(root) = fs::canonicalize(root).await {
+                if path.starts_with(&root) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// A fresh file path in the cache directory for `audio_type`
+    async fn new_path(&self, audio_type: &AudioType, extension: &str) -> Result<PathBuf> {
         let subdir = match audio_type {
             AudioType::Tts => "tts",
             AudioType::Sfx => "sfx",
@@ -40,7 +254,18 @@ impl AudioCache {
         fs::create_dir_all(&dir).await?;
 
         let filename = format!("{}.{}", Uuid::new_v4(), extension);
-        let path = dir.join(&filename);
+        Ok(dir.join(&filename))
+    }
+
+    /// Save audio data to cache
+    #[tracing::instrument(skip(self, data), fields(bytes = data.len()), err(Display))]
+    pub async fn save_audio(
+        &self,
+        audio_type: &AudioType,
+        data: &[u8],
+        extension: &str,
+    ) -> Result<PathBuf> {
+        let path = self.new_path(audio_type, extension).await?;
 
         fs::write(&path, data)
             .await
@@ -49,13 +274,72 @@ impl AudioCache {
         Ok(path)
     }
 
-    /// Delete a cached audio file
+    /// Save streamed audio to cache chunk by chunk, calling `on_progress` with
+    /// the number of bytes written so far. The audio is written to a temporary
+    /// file and renamed into place once complete, so the cache never holds a
+    /// partial file. Returns the path and total size.
+    #[tracing::instrument(skip(self, stream, on_progress), fields(bytes = tracing::field::Empty), err(Display))]
+    pub async fn save_audio_stream(
+        &self,
+        audio_type: &AudioType,
+        mut stream: AudioStream,
+        extension: &str,
+        mut on_progress: impl FnMut(u64),
+    ) -> Result<(PathBuf, u64)> {
+        let path = self.new_path(audio_type, extension).await?;
+        let partial_dir = self.cache_dir.join(PARTIAL_DIR);
+        fs::create_dir_all(&partial_dir).await?;
+        let temp_path = partial_dir.join(path.file_name().unwrap_or_default());
+        // Removes the file unless the download completes, including when the
+        // task writing it is aborted mid-stream
+        let mut partial = PartialFile(Some(temp_path.clone()));
+
+        let written = async {
+            let mut file = fs::File::create(&temp_path).await?;
+            let mut written = 0u64;
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk?;
+                file.write_all(&chunk).await?;
+                written += chunk.len() as u64;
+                on_progress(written);
+            }
+            file.sync_all().await?;
+            drop(file);
+            fs::rename(&temp_path, &path).await?;
+            Ok::<_, anyhow::Error>(written)
+        }
+        .await;
+
+        let written = written.map_err(|e| anyhow!("Failed to write audio file: {}", e))?;
+        partial.0 = None;
+        tracing::Span::current().record("bytes", written);
+        Ok((path, written))
+    }
+
+    /// The canonical form of `path` if it resolves (through symlinks and `..`)
+    /// to a file inside the cache
+    pub async fn resolve(&self, path: &Path) -> Option<PathBuf> {
+        let resolved = fs::canonicalize(path).await.ok()?;
+        self.contains(&resolved).await.then_some(resolved)
+    }
+
+    /// Delete a cached audio file. A path that doesn't resolve (through
+    /// symlinks and `..`) to a file inside the cache, e.g. from a corrupted
+    /// record, is logged and left alone.
+    #[tracing::instrument(skip(self), err(Display))]
     pub async fn delete_audio(&self, path: &Path) -> Result<()> {
-        if path.exists() {
-            fs::remove_file(path)
-                .await
-                .map_err(|e| anyhow!("Failed to delete audio file: {}", e))?;
+        // Nothing to delete if it doesn't exist
+        let Ok(resolved) = fs::canonicalize(path).await else {
+            return Ok(());
+        };
+        if !self.contains(&resolved).await {
+            log::warn!("Refusing to delete {}: not a file in the audio cache", path.display());
+            return Ok(());
         }
+
+        fs::remove_file(&resolved)
+            .await
+            .map_err(|e| anyhow!("Failed to delete audio file: {}", e))?;
         Ok(())
     }
 
@@ -85,6 +369,7 @@ impl AudioCache {
     }
 
     /// Clear all cached files for a given type
+    #[tracing::instrument(skip(self), err(Display))]
     pub async fn clear_cache(&self, audio_type: &AudioType) -> Result<u32> {
         let files = self.list_cached_files(audio_type).await?;
         let count = files.len() as u32;
@@ -117,46 +402,80 @@ impl AudioCache {
 pub struct AudioCacheDb;
 
 impl AudioCacheDb {
-    /// Save a generated audio record to the database
+    /// Map a row selecting `id, audio_type, prompt, duration_seconds,
+    /// local_path, supabase_url, metadata, created_at, params`
+    fn audio_from_row(row: &rusqlite::Row) -> rusqlite::Result<GeneratedAudio> {
+        Ok(GeneratedAudio {
+            id: row.get(0)?,
+            audio_type: serde_json::from_str(&row.get::<_, String>(1)?).unwrap_or(AudioType::Tts),
+            prompt: row.get(2)?,
+            duration_seconds: row.get(3)?,
+            local_path: row.get(4)?,
+            supabase_url: row.get(5)?,
+            metadata: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or(serde_json::json!({})),
+            created_at: row.get(7)?,
+            params: row
+                .get::<_, Option<String>>(8)?
+                .and_then(|params| serde_json::from_str(&params).ok()),
+            cached: false,
+        })
+    }
+
+    /// Save a generated audio record to the database. Saving over an existing
+    /// record keeps its request hash.
     pub fn save_audio_record(conn: &Connection, audio: &GeneratedAudio) -> Result<()> {
-        conn.execute(
-            "INSERT OR REPLACE INTO audio_cache
-             (id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            (
-                &audio.id,
-                serde_json::to_string(&audio.audio_type)?,
-                &audio.prompt,
-                audio.duration_seconds,
-                &audio.local_path,
-                &audio.supabase_url,
-                serde_json::to_string(&audio.metadata)?,
-                &audio.created_at,
-            ),
-        )?;
+        let params = audio.params.as_ref().map(serde_json::to_string).transpose()?;
+        conn.prepare_cached(
+            "INSERT INTO audio_cache
+             (id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at, params)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT (id) DO UPDATE SET
+                 audio_type = excluded.audio_type, prompt = excluded.prompt,
+                 duration_seconds = excluded.duration_seconds, local_path = excluded.local_path,
+                 supabase_url = excluded.supabase_url, metadata = excluded.metadata,
+                 created_at = excluded.created_at, params = excluded.params",
+        )?
+        .execute((
+            &audio.id,
+            serde_json::to_string(&audio.audio_type)?,
+            &audio.prompt,
+            audio.duration_seconds,
+            &audio.local_path,
+            &audio.supabase_url,
+            serde_json::to_string(&audio.metadata)?,
+            &audio.created_at,
+            params,
+        ))?;
         Ok(())
     }
 
-    /// Get all audio records of a given type
+    /// Record the hash of the TTS request a record was rendered from
+    pub fn set_request_hash(conn: &Connection, id: &str, hash: &str) -> Result<()> {
+        conn.execute("UPDATE audio_cache SET request_hash = ?2 WHERE id = ?1", (id, hash))?;
+        Ok(())
+    }
+
+    /// Records rendered from a TTS request with this hash, newest first
+    pub fn find_by_request_hash(conn: &Connection, hash: &str) -> Result<Vec<GeneratedAudio>> {
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at, params
+             FROM audio_cache WHERE request_hash = ?1 AND deleted_at IS NULL ORDER BY created_at DESC",
+        )?;
+        let records = stmt
+            .query_map([hash], Self::audio_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(records)
+    }
+
+    /// Get all audio records of a given type, except those in the trash
     pub fn get_audio_records(conn: &Connection, audio_type: &AudioType) -> Result<Vec<GeneratedAudio>> {
         let type_str = serde_json::to_string(audio_type)?;
         let mut stmt = conn.prepare(
-            "SELECT id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at
-             FROM audio_cache WHERE audio_type = ?1 ORDER BY created_at DESC"
+            "SELECT id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at, params
+             FROM audio_cache WHERE audio_type = ?1 AND deleted_at IS NULL ORDER BY created_at DESC"
         )?;
 
-        let rows = stmt.query_map([&type_str], |row| {
-            Ok(GeneratedAudio {
-                id: row.get(0)?,
-                audio_type: serde_json::from_str(&row.get::<_, String>(1)?).unwrap_or(AudioType::Tts),
-                prompt: row.get(2)?,
-                duration_seconds: row.get(3)?,
-                local_path: row.get(4)?,
-                supabase_url: row.get(5)?,
-                metadata: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or(serde_json::json!({})),
-                created_at: row.get(7)?,
-            })
-        })?;
+        let rows = stmt.query_map([&type_str], Self::audio_from_row)?;
 
         let mut records = vec![];
         for row in rows {
@@ -165,50 +484,232 @@ impl AudioCacheDb {
         Ok(records)
     }
 
-    /// Delete an audio record from the database
+    /// Records of a given type matching `query`, in its order and page.
+    /// Creation bounds compare as UTC timestamps, the form records are stored in.
+    pub fn query_audio_records(
+        conn: &Connection,
+        audio_type: &AudioType,
+        query: &AudioQuery,
+    ) -> Result<Vec<GeneratedAudio>> {
+        let utc = |bound: &Option<String>| -> Result<Option<String>> {
+            bound
+                .as_deref()
+                .map(|bound| {
+                    chrono::DateTime::parse_from_rfc3339(bound)
+                        .map(|at| at.with_timezone(&chrono::Utc).to_rfc3339())
+                        .map_err(|e| AudioError::Validation(format!("Invalid date {}: {}", bound, e)).into())
+                })
+                .transpose()
+        };
+        let order = match query.sort {
+            AudioSort::Newest => "created_at DESC",
+            AudioSort::Oldest => "created_at ASC",
+            AudioSort::Longest => "duration_seconds DESC, created_at DESC",
+            AudioSort::Shortest => "duration_seconds ASC, created_at DESC",
+            AudioSort::Prompt => "prompt COLLATE NOCASE ASC, created_at DESC",
+        };
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at, params
+             FROM audio_cache
+             WHERE audio_type = ?1 AND deleted_at IS NULL
+               AND (?2 IS NULL
+                    OR COALESCE(json_extract(params, '$.voice_id'), json_extract(metadata, '$.voice_id')) = ?2)
+               AND (?3 IS NULL OR created_at >= ?3)
+               AND (?4 IS NULL OR created_at <= ?4)
+               AND (?7 IS NULL OR id IN (SELECT audio_id FROM audio_tags WHERE tag = ?7))
+               AND (?8 = 0 OR favorite = 1)
+             ORDER BY {}
+             LIMIT ?5 OFFSET ?6",
+            order
+        ))?;
+        let records = stmt
+            .query_map(
+                (
+                    serde_json::to_string(audio_type)?,
+                    &query.voice_id,
+                    utc(&query.created_after)?,
+                    utc(&query.created_before)?,
+                    query.limit.map_or(-1, i64::from),
+                    query.offset.unwrap_or(0),
+                    query.tag.as_deref().map(str::trim),
+                    query.favorites_only,
+                ),
+                Self::audio_from_row,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(records)
+    }
+
+    /// Records whose prompt contains every word of `text` (each also matching
+    /// as a prefix, so "thund" finds "thunder"), best matches first
+    pub fn search_audio_records(
+        conn: &Connection,
+        text: &str,
+        audio_type: Option<&AudioType>,
+        limit: u32,
+    ) -> Result<Vec<GeneratedAudio>> {
+        // Quoted so words are matched literally rather than as query syntax
+        let terms: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| format!("\"{}\"*", word))
+            .collect();
+        if terms.is_empty() {
+            return Ok(vec![]);
+        }
+        let audio_type = audio_type.map(serde_json::to_string).transpose()?;
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT a.id, a.audio_type, a.prompt, a.duration_seconds, a.local_path, a.supabase_url, a.metadata,
+                    a.created_at, a.params
+             FROM audio_prompt_fts JOIN audio_cache a ON a.rowid = audio_prompt_fts.rowid
+             WHERE audio_prompt_fts MATCH ?1 AND (?2 IS NULL OR a.audio_type = ?2) AND a.deleted_at IS NULL
+             ORDER BY bm25(audio_prompt_fts), a.created_at DESC
+             LIMIT ?3",
+        )?;
+        let records = stmt
+            .query_map((terms.join(" "), audio_type, limit), Self::audio_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(records)
+    }
+
+    /// Move a live record to the trash, returning whether it was live
+    pub fn trash_audio_record(conn: &Connection, id: &str) -> Result<bool> {
+        let trashed = conn.execute(
+            "UPDATE audio_cache SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+            (id, chrono::Utc::now().to_rfc3339()),
+        )?;
+        Ok(trashed > 0)
+    }
+
+    /// Take a record out of the trash, returning whether it was in it
+    pub fn restore_audio_record(conn: &Connection, id: &str) -> Result<bool> {
+        let restored =
+            conn.execute("UPDATE audio_cache SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL", [id])?;
+        Ok(restored > 0)
+    }
+
+    /// Records in the trash, most recently deleted first, optionally of one type
+    pub fn get_trashed_records(conn: &Connection, audio_type: Option<&AudioType>) -> Result<Vec<TrashedAudio>> {
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at, params,
+                    deleted_at
+             FROM audio_cache
+             WHERE deleted_at IS NOT NULL AND (?1 IS NULL OR audio_type = ?1)
+             ORDER BY deleted_at DESC",
+        )?;
+        let records = stmt
+            .query_map([audio_type.map(serde_json::to_string).transpose()?], |row| {
+                Ok(TrashedAudio {
+                    audio: Self::audio_from_row(row)?,
+                    deleted_at: row.get(9)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(records)
+    }
+
+    /// Delete an audio record, and its tags, from the database
     pub fn delete_audio_record(conn: &Connection, id: &str) -> Result<()> {
+        conn.execute("DELETE FROM audio_tags WHERE audio_id = ?1", [id])?;
         conn.execute("DELETE FROM audio_cache WHERE id = ?1", [id])?;
         Ok(())
     }
 
     /// Get a single audio record by ID
     pub fn get_audio_record(conn: &Connection, id: &str) -> Result<Option<GeneratedAudio>> {
-        let mut stmt = conn.prepare(
-            "SELECT id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at, params
              FROM audio_cache WHERE id = ?1"
         )?;
 
-        let mut rows = stmt.query([id])?;
+        let mut rows = stmt.query_map([id], Self::audio_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
 
-        if let Some(row) = rows.next()? {
-            Ok(Some(GeneratedAudio {
-                id: row.get(0)?,
-                audio_type: serde_json::from_str(&row.get::<_, String>(1)?).unwrap_or(AudioType::Tts),
-                prompt: row.get(2)?,
-                duration_seconds: row.get(3)?,
-                local_path: row.get(4)?,
-                supabase_url: row.get(5)?,
-                metadata: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or(serde_json::json!({})),
-                created_at: row.get(7)?,
-            }))
-        } else {
-            Ok(None)
+    /// Get every take in the group of `audio_id` (the first take and those
+    /// regenerated from it), oldest first
+    pub fn get_takes(conn: &Connection, audio_id: &str) -> Result<Vec<GeneratedAudio>> {
+        let mut stmt = conn.prepare(
+            "WITH root AS (
+                 SELECT COALESCE(json_extract(metadata, '$.take_of'), id) AS id FROM audio_cache WHERE id = ?1
+             )
+             SELECT id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at, params
+             FROM audio_cache
+             WHERE (id = (SELECT id FROM root) OR json_extract(metadata, '$.take_of') = (SELECT id FROM root))
+               AND deleted_at IS NULL
+             ORDER BY created_at",
+        )?;
+        let takes = stmt
+            .query_map([audio_id], Self::audio_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(takes)
+    }
+
+    /// Get the takes tagged with a scene (`metadata.scene_id`) in script order
+    pub fn get_scene_takes(conn: &Connection, scene_id: &str) -> Result<Vec<GeneratedAudio>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, audio_type, prompt, duration_seconds, local_path, supabase_url, metadata, created_at, params
+             FROM audio_cache WHERE json_extract(metadata, '$.scene_id') = ?1 AND deleted_at IS NULL
+             ORDER BY json_extract(metadata, '$.line'), created_at"
+        )?;
+
+        let rows = stmt.query_map([scene_id], Self::audio_from_row)?;
+
+        let mut records = vec![];
+        for row in rows {
+            records.push(row?);
         }
+        Ok(records)
     }
 }
 
 /// Voice profile database operations
 pub struct VoiceProfileDb;
 
+const SAVE_VOICE_PROFILE_SQL: &str = "INSERT OR REPLACE INTO voice_profiles
+     (id, name, description, category, provider, provider_voice_id, labels, preview_url,
+      settings_stability, settings_similarity_boost, settings_style, settings_use_speaker_boost,
+      updated_at)
+     VALUES (?1, ?2, ?3, ?4, 'elevenlabs', ?5, ?6, ?7, ?8, ?9, ?10, ?11, CURRENT_TIMESTAMP)";
+
 impl VoiceProfileDb {
     /// Save a voice profile to the database
     pub fn save_voice_profile(conn: &Connection, voice: &VoiceProfile, provider_voice_id: &str) -> Result<()> {
-        conn.execute(
-            "INSERT OR REPLACE INTO voice_profiles
-             (id, name, description, category, provider, provider_voice_id, labels, preview_url,
-              settings_stability, settings_similarity_boost, settings_style, settings_use_speaker_boost,
-              updated_at)
-             VALUES (?1, ?2, ?3, ?4, 'elevenlabs', ?5, ?6, ?7, ?8, ?9, ?10, ?11, CURRENT_TIMESTAMP)",
+        let mut stmt = conn.prepare_cached(SAVE_VOICE_PROFILE_SQL)?;
+        Self::write_voice_profile(&mut stmt, voice, provider_voice_id)
+    }
+
+    /// Save fetched voices in a single transaction. A profile that fails to
+    /// write is logged and counted without aborting the rest of the batch.
+    #[tracing::instrument(skip_all, fields(voices = voices.len()), err(Display))]
+    pub fn save_voice_profiles(conn: &mut Connection, voices: &[VoiceProfile]) -> Result<VoiceCacheStats> {
+        let tx = conn.transaction()?;
+        let mut stats = VoiceCacheStats::default();
+        {
+            let existing: std::collections::HashSet<String> = tx
+                .prepare("SELECT id FROM voice_profiles")?
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            let mut stmt = tx.prepare(SAVE_VOICE_PROFILE_SQL)?;
+
+            for voice in voices {
+                match Self::write_voice_profile(&mut stmt, voice, &voice.voice_id) {
+                    Ok(()) if existing.contains(&voice.voice_id) => stats.updated += 1,
+                    Ok(()) => stats.inserted += 1,
+                    Err(e) => {
+                        log::warn!("Failed to cache voice {}: {}", voice.voice_id, e);
+                        stats.failed += 1;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(stats)
+    }
+
+    fn write_voice_profile(stmt: &mut rusqlite::Statement, voice: &VoiceProfile, provider_voice_id: &str) -> Result<()> {
+        stmt.execute(
             (
                 &voice.voice_id,
                 &voice.name,
@@ -250,7 +751,9 @@ impl VoiceProfileDb {
                     similarity_boost: row.get(7)?,
                     style: row.get(8)?,
                     use_speaker_boost: row.get::<_, i32>(9)? != 0,
+                    speed: None,
                 },
+                samples: Vec::new(),
             })
         })?;
 
@@ -261,11 +764,61 @@ impl VoiceProfileDb {
         Ok(profiles)
     }
 
+    /// Get a single voice profile by ID
+    pub fn get_voice_profile(conn: &Connection, voice_id: &str) -> Result<Option<VoiceProfile>> {
+        Ok(Self::get_voice_profiles(conn)?
+            .into_iter()
+            .find(|voice| voice.voice_id == voice_id))
+    }
+
     /// Delete a voice profile from the database
     pub fn delete_voice_profile(conn: &Connection, voice_id: &str) -> Result<()> {
         conn.execute("DELETE FROM voice_profiles WHERE id = ?1", [voice_id])?;
         Ok(())
     }
+
+    /// Character castings, agent assignments and cached renders that use a voice
+    pub fn get_voice_references(conn: &Connection, voice_id: &str) -> Result<VoiceReferences> {
+        let mut stmt = conn.prepare(
+            "SELECT id, character_name, voice_id, voice_name, project_id, created_at, preset
+             FROM character_voices WHERE voice_id = ?1 ORDER BY character_name",
+        )?;
+        let characters = stmt
+            .query_map([voice_id], CharacterVoiceDb::mapping_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, agent_id, voice_id, voice_name, created_at
+             FROM agent_voices WHERE voice_id = ?1 ORDER BY agent_id",
+        )?;
+        let agents = stmt
+            .query_map([voice_id], AgentVoiceDb::mapping_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id FROM audio_cache
+             WHERE json_extract(params, '$.voice_id') = ?1
+                OR (params IS NULL AND json_extract(metadata, '$.voice_id') = ?1)
+             ORDER BY created_at DESC",
+        )?;
+        let audio_ids = stmt
+            .query_map([voice_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(VoiceReferences {
+            characters,
+            agents,
+            audio_ids,
+        })
+    }
+
+    /// Remove the character castings and agent assignments that use a voice.
+    /// Cached renders are kept.
+    pub fn delete_voice_mappings(conn: &Connection, voice_id: &str) -> Result<usize> {
+        let characters = conn.execute("DELETE FROM character_voices WHERE voice_id = ?1", [voice_id])?;
+        let agents = conn.execute("DELETE FROM agent_voices WHERE voice_id = ?1", [voice_id])?;
+        Ok(characters + agents)
+    }
 }
 
 /// Character voice mapping database operations
@@ -297,42 +850,37 @@ impl CharacterVoiceDb {
             voice_name: voice_name.to_string(),
             project_id: project_id.map(|s| s.to_string()),
             created_at,
+            preset: None,
+        })
+    }
+
+    fn mapping_from_row(row: &rusqlite::Row) -> rusqlite::Result<CharacterVoice> {
+        Ok(CharacterVoice {
+            id: row.get(0)?,
+            character_name: row.get(1)?,
+            voice_id: row.get(2)?,
+            voice_name: row.get(3)?,
+            project_id: row.get(4)?,
+            created_at: row.get(5)?,
+            preset: row.get(6)?,
         })
     }
 
     /// Get all character voice mappings
     pub fn get_character_voices(conn: &Connection, project_id: Option<&str>) -> Result<Vec<CharacterVoice>> {
         let sql = match project_id {
-            Some(_) => "SELECT id, character_name, voice_id, voice_name, project_id, created_at
+            Some(_) => "SELECT id, character_name, voice_id, voice_name, project_id, created_at, preset
                         FROM character_voices WHERE project_id = ?1 ORDER BY character_name",
-            None => "SELECT id, character_name, voice_id, voice_name, project_id, created_at
+            None => "SELECT id, character_name, voice_id, voice_name, project_id, created_at, preset
                      FROM character_voices ORDER BY character_name",
         };
 
         let mut stmt = conn.prepare(sql)?;
 
         let rows = if let Some(pid) = project_id {
-            stmt.query_map([pid], |row| {
-                Ok(CharacterVoice {
-                    id: row.get(0)?,
-                    character_name: row.get(1)?,
-                    voice_id: row.get(2)?,
-                    voice_name: row.get(3)?,
-                    project_id: row.get(4)?,
-                    created_at: row.get(5)?,
-                })
-            })?
+            stmt.query_map([pid], Self::mapping_from_row)?
         } else {
-            stmt.query_map([], |row| {
-                Ok(CharacterVoice {
-                    id: row.get(0)?,
-                    character_name: row.get(1)?,
-                    voice_id: row.get(2)?,
-                    voice_name: row.get(3)?,
-                    project_id: row.get(4)?,
-                    created_at: row.get(5)?,
-                })
-            })?
+            stmt.query_map([], Self::mapping_from_row)?
         };
 
         let mut mappings = vec![];
@@ -342,6 +890,48 @@ impl CharacterVoiceDb {
         Ok(mappings)
     }
 
+    /// The mapping for a character in exactly one scope: a project, or the
+    /// global casting (`project_id IS NULL`) when no project is given
+    pub fn get_scoped_voice(
+        conn: &Connection,
+        character_name: &str,
+        project_id: Option<&str>,
+    ) -> Result<Option<CharacterVoice>> {
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, character_name, voice_id, voice_name, project_id, created_at, preset
+             FROM character_voices
+             WHERE character_name = ?1 COLLATE NOCASE AND project_id IS ?2
+             ORDER BY created_at DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query_map((character_name, project_id), Self::mapping_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Insert or replace a complete mapping, preserving its id and timestamp
+    pub fn save_mapping(conn: &Connection, mapping: &CharacterVoice) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO character_voices
+             (id, character_name, voice_id, voice_name, project_id, created_at, preset)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (
+                &mapping.id,
+                &mapping.character_name,
+                &mapping.voice_id,
+                &mapping.voice_name,
+                &mapping.project_id,
+                &mapping.created_at,
+                &mapping.preset,
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Set or clear the voice settings preset of a mapping
+    pub fn set_preset(conn: &Connection, id: &str, preset: Option<&str>) -> Result<()> {
+        conn.execute("UPDATE character_voices SET preset = ?2 WHERE id = ?1", (id, preset))?;
+        Ok(())
+    }
+
     /// Remove a character voice mapping
     pub fn remove_mapping(conn: &Connection, id: &str) -> Result<()> {
         conn.execute("DELETE FROM character_voices WHERE id = ?1", [id])?;
@@ -349,6 +939,106 @@ impl CharacterVoiceDb {
     }
 }
 
+/// Agent voice mapping database operations
+pub struct AgentVoiceDb;
+
+impl AgentVoiceDb {
+    /// Assign a voice to an agent, replacing any previous assignment
+    pub fn assign_voice(conn: &Connection, agent_id: i64, voice_id: &str, voice_name: &str) -> Result<AgentVoice> {
+        let id = Uuid::new_v4().to_string();
+        let created_at = chrono::Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO agent_voices (id, agent_id, voice_id, voice_name, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            (&id, agent_id, voice_id, voice_name, &created_at),
+        )?;
+
+        Ok(AgentVoice {
+            id,
+            agent_id,
+            voice_id: voice_id.to_string(),
+            voice_name: voice_name.to_string(),
+            created_at,
+        })
+    }
+
+    fn mapping_from_row(row: &rusqlite::Row) -> rusqlite::Result<AgentVoice> {
+        Ok(AgentVoice {
+            id: row.get(0)?,
+            agent_id: row.get(1)?,
+            voice_id: row.get(2)?,
+            voice_name: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    }
+
+    /// Get all agent voice mappings
+    pub fn get_agent_voices(conn: &Connection) -> Result<Vec<AgentVoice>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, agent_id, voice_id, voice_name, created_at FROM agent_voices ORDER BY agent_id",
+        )?;
+        let rows = stmt.query_map([], Self::mapping_from_row)?;
+
+        let mut mappings = vec![];
+        for row in rows {
+            mappings.push(row?);
+        }
+        Ok(mappings)
+    }
+
+    /// Get the voice assigned to an agent
+    pub fn get_agent_voice(conn: &Connection, agent_id: i64) -> Result<Option<AgentVoice>> {
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, agent_id, voice_id, voice_name, created_at FROM agent_voices WHERE agent_id = ?1",
+        )?;
+        let mut rows = stmt.query_map([agent_id], Self::mapping_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+}
+
+/// Per-voice usage database operations
+pub struct VoiceUsageDb;
+
+impl VoiceUsageDb {
+    /// Count a generation made with a voice
+    pub fn record_use(conn: &Connection, voice_id: &str) -> Result<()> {
+        conn.prepare_cached(
+            "INSERT INTO voice_usage (voice_id, use_count, last_used_at) VALUES (?1, 1, ?2)
+             ON CONFLICT(voice_id) DO UPDATE SET use_count = use_count + 1, last_used_at = excluded.last_used_at",
+        )?
+        .execute((voice_id, chrono::Utc::now().to_rfc3339()))?;
+        Ok(())
+    }
+
+    /// Get the most recently used voices, newest first
+    pub fn get_recent_voices(conn: &Connection, limit: usize) -> Result<Vec<RecentVoice>> {
+        let mut stmt = conn.prepare(
+            "SELECT u.voice_id, p.name, u.use_count, u.last_used_at
+             FROM voice_usage u LEFT JOIN voice_profiles p ON p.id = u.voice_id
+             ORDER BY u.last_used_at DESC, u.use_count DESC
+             LIMIT ?1",
+        )?;
+        let voices = stmt
+            .query_map([limit as i64], |row| {
+                Ok(RecentVoice {
+                    voice_id: row.get(0)?,
+                    name: row.get(1)?,
+                    use_count: row.get(2)?,
+                    last_used_at: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(voices)
+    }
+
+    /// Forget the usage of a deleted voice
+    pub fn delete_voice_usage(conn: &Connection, voice_id: &str) -> Result<()> {
+        conn.execute("DELETE FROM voice_usage WHERE voice_id = ?1", [voice_id])?;
+        Ok(())
+    }
+}
+
 /// Settings database operations
 pub struct SettingsDb;
 
@@ -384,4 +1074,329 @@ impl SettingsDb {
         conn.execute("DELETE FROM eleven_labs_settings WHERE key = 'api_key'", [])?;
         Ok(())
     }
+
+    /// Save an arbitrary setting value
+    pub fn save_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO eleven_labs_settings (key, value, updated_at)
+             VALUES (?1, ?2, CURRENT_TIMESTAMP)",
+            [key, value],
+        )?;
+        Ok(())
+    }
+
+    /// Get an arbitrary setting value
+    pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+        let mut stmt = conn.prepare_cached(
+            "SELECT value FROM eleven_labs_settings WHERE key = ?1"
+        )?;
+
+        let mut rows = stmt.query([key])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(row.get(0)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Remove an arbitrary setting value
+    pub fn remove_setting(conn: &Connection, key: &str) -> Result<()> {
+        conn.execute("DELETE FROM eleven_labs_settings WHERE key = ?1", [key])?;
+        Ok(())
+    }
+
+    /// Get all settings as key/value pairs
+    pub fn get_all_settings(conn: &Connection) -> Result<Vec<(String, String)>> {
+        let mut stmt = conn.prepare(
+            "SELECT key, value FROM eleven_labs_settings ORDER BY key"
+        )?;
+
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut settings = vec![];
+        for row in rows {
+            settings.push(row?);
+        }
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::eleven_labs::schema;
+    use bytes::Bytes;
+    use futures::stream;
+
+    #[test]
+    fn test_estimate_duration() {
+        assert_eq!(estimate_duration_seconds(32_000, "mp3"), Some(2.0));
+        assert_eq!(estimate_duration_seconds(24_000, "mp3_44100_192"), Some(1.0));
+        assert_eq!(estimate_duration_seconds(48_000, "pcm_24000"), Some(1.0));
+        assert_eq!(estimate_duration_seconds(88_244, "wav_44100"), Some(1.0));
+        assert_eq!(estimate_duration_seconds(8_000, "ulaw_8000"), Some(1.0));
+        assert_eq!(estimate_duration_seconds(8_000, "MP3"), Some(0.5));
+        assert_eq!(estimate_duration_seconds(1_000, "wav"), None);
+        assert_eq!(estimate_duration_seconds(1_000, "mp4"), None);
+    }
+
+    #[tokio::test]
+    async fn test_save_audio_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AudioCache::new(dir.path().to_path_buf()).unwrap();
+
+        let chunks: AudioStream = stream::iter(vec![Ok(Bytes::from_static(b"ID3")), Ok(Bytes::from(vec![0; 5]))]).boxed();
+        let mut progress = vec![];
+        let (path, written) = cache
+            .save_audio_stream(&AudioType::Tts, chunks, "mp3", |n| progress.push(n))
+            .await
+            .unwrap();
+        assert_eq!(written, 8);
+        assert_eq!(progress, vec![3, 8]);
+        assert_eq!(std::fs::read(&path).unwrap().len(), 8);
+
+        // A failed download leaves no partial file behind
+        let failing: AudioStream = stream::iter(vec![Ok(Bytes::from(vec![1; 4])), Err(anyhow!("connection reset"))]).boxed();
+        assert!(cache.save_audio_stream(&AudioType::Sfx, failing, "mp3", |_| {}).await.is_err());
+        assert_eq!(cache.list_cached_files(&AudioType::Sfx).await.unwrap().len(), 0);
+        assert_eq!(std::fs::read_dir(dir.path().join(PARTIAL_DIR)).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_delete_audio_stays_in_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AudioCache::new(dir.path().join("cache")).unwrap();
+        let outside = dir.path().join("notes.txt");
+        std::fs::write(&outside, "keep me").unwrap();
+
+        cache.delete_audio(&outside).await.unwrap();
+        cache.delete_audio(&dir.path().join("cache").join("..").join("notes.txt")).await.unwrap();
+        cache.delete_audio(cache.cache_dir()).await.unwrap();
+        assert!(outside.exists());
+        assert!(cache.cache_dir().exists());
+
+        let inside = cache.save_audio(&AudioType::Tts, b"ID3", "mp3").await.unwrap();
+        cache.delete_audio(&inside).await.unwrap();
+        assert!(!inside.exists());
+    }
+
+    #[test]
+    fn test_query_audio_records() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        let clip = |id: &str, voice_id: &str, duration_seconds: f32, created_at: &str| GeneratedAudio {
+            id: id.to_string(),
+            audio_type: AudioType::Tts,
+            prompt: id.to_string(),
+            duration_seconds,
+            local_path: format!("/cache/tts/{}.mp3", id),
+            supabase_url: None,
+            metadata: serde_json::json!({}),
+            created_at: created_at.to_string(),
+            params: Some(GenerationParams {
+                voice_id: Some(voice_id.to_string()),
+                ..GenerationParams::new(PROVIDER_ELEVENLABS)
+            }),
+            cached: false,
+        };
+        for audio in [
+            clip("a", "v1", 3.0, "2024-01-01T10:00:00+00:00"),
+            clip("b", "v2", 1.0, "2024-02-01T10:00:00+00:00"),
+            clip("c", "v1", 2.0, "2024-03-01T10:00:00+00:00"),
+        ] {
+            AudioCacheDb::save_audio_record(&conn, &audio).unwrap();
+        }
+        let ids = |query: AudioQuery| -> Vec<String> {
+            AudioCacheDb::query_audio_records(&conn, &AudioType::Tts, &query)
+                .unwrap()
+                .into_iter()
+                .map(|audio| audio.id)
+                .collect()
+        };
+
+        assert_eq!(ids(AudioQuery::default()), vec!["c", "b", "a"]);
+        assert_eq!(ids(AudioQuery { sort: AudioSort::Longest, ..Default::default() }), vec!["a", "c", "b"]);
+        assert_eq!(ids(AudioQuery { voice_id: Some("v1".to_string()), ..Default::default() }), vec!["c", "a"]);
+        assert_eq!(ids(AudioQuery { limit: Some(1), offset: Some(1), ..Default::default() }), vec!["b"]);
+        // Bounds in other zones are compared in UTC
+        let range = AudioQuery {
+            created_after: Some("2024-02-01T11:00:00+01:00".to_string()),
+            created_before: Some("2024-02-15T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(range), vec!["b"]);
+        let invalid = AudioQuery { created_after: Some("yesterday".to_string()), ..Default::default() };
+        let err = AudioCacheDb::query_audio_records(&conn, &AudioType::Tts, &invalid).unwrap_err();
+        assert!(matches!(AudioError::from(err), AudioError::Validation(_)));
+    }
+
+    #[test]
+    fn test_search_audio_records() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        let clip = |id: &str, audio_type: AudioType, prompt: &str| GeneratedAudio {
+            id: id.to_string(),
+            audio_type,
+            prompt: prompt.to_string(),
+            duration_seconds: 1.0,
+            local_path: format!("/cache/{}.mp3", id),
+            supabase_url: None,
+            metadata: serde_json::json!({}),
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            params: None,
+            cached: false,
+        };
+        for audio in [
+            clip("door", AudioType::Sfx, "Heavy wooden door creaking open"),
+            clip("storm", AudioType::Sfx, "Distant thunder over the sea"),
+            clip("line", AudioType::Tts, "The door was locked."),
+        ] {
+            AudioCacheDb::save_audio_record(&conn, &audio).unwrap();
+        }
+        let ids = |text: &str, audio_type: Option<&AudioType>| -> Vec<String> {
+            let records = AudioCacheDb::search_audio_records(&conn, text, audio_type, 10).unwrap();
+            records.into_iter().map(|audio| audio.id).collect()
+        };
+
+        assert_eq!(ids("thund", None), vec!["storm"]);
+        assert_eq!(ids("DOOR", Some(&AudioType::Sfx)), vec!["door"]);
+        assert_eq!(ids("door", None).len(), 2);
+        assert_eq!(ids("wooden door", None), vec!["door"]);
+        // Query syntax in the text is matched literally
+        assert!(ids("\"door\" OR -sea", None).is_empty());
+        assert!(ids("  ", None).is_empty());
+
+        let renamed = GeneratedAudio { prompt: "Steel gate".to_string(), ..clip("door", AudioType::Sfx, "") };
+        AudioCacheDb::save_audio_record(&conn, &renamed).unwrap();
+        assert_eq!(ids("door", None), vec!["line"]);
+        AudioCacheDb::delete_audio_record(&conn, "line").unwrap();
+        assert!(ids("door", None).is_empty());
+    }
+
+    #[test]
+    fn test_save_voice_profiles() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        let voice = |id: &str| VoiceProfile {
+            voice_id: id.to_string(),
+            name: id.to_uppercase(),
+            description: None,
+            category: "premade".to_string(),
+            labels: None,
+            preview_url: None,
+            settings: VoiceSettings::default(),
+            samples: Vec::new(),
+        };
+
+        let stats = VoiceProfileDb::save_voice_profiles(&mut conn, &[voice("a")]).unwrap();
+        assert_eq!(stats, VoiceCacheStats { inserted: 1, updated: 0, failed: 0 });
+        let stats = VoiceProfileDb::save_voice_profiles(&mut conn, &[voice("a"), voice("b")]).unwrap();
+        assert_eq!(stats, VoiceCacheStats { inserted: 1, updated: 1, failed: 0 });
+        assert_eq!(VoiceProfileDb::get_voice_profiles(&conn).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_voice_references() {
+        let conn = Connection::open_in_memory().unwrap();
+        // agent_voices references the agents table owned by the agents module
+        conn.execute_batch("CREATE TABLE agents (id INTEGER PRIMARY KEY); INSERT INTO agents (id) VALUES (7);")
+            .unwrap();
+        schema::init(&conn).unwrap();
+        CharacterVoiceDb::assign_voice(&conn, "Narrator", "v1", "Rachel", None).unwrap();
+        AgentVoiceDb::assign_voice(&conn, 7, "v1", "Rachel").unwrap();
+        CharacterVoiceDb::assign_voice(&conn, "Villain", "v2", "Adam", None).unwrap();
+        let audio = GeneratedAudio {
+            id: "clip".to_string(),
+            audio_type: AudioType::Tts,
+            prompt: "Hello".to_string(),
+            duration_seconds: 1.0,
+            local_path: "/cache/tts/clip.mp3".to_string(),
+            supabase_url: None,
+            metadata: serde_json::json!({}),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            params: Some(GenerationParams::from_tts(&TtsRequest {
+                text: "Hello".to_string(),
+                voice_id: "v1".to_string(),
+                model_id: default_model_id(),
+                voice_settings: None,
+                output_format: default_output_format(),
+                normalize_text: None,
+                preset: None,
+                previous_text: None,
+                next_text: None,
+                previous_request_ids: Vec::new(),
+                seed: None,
+            })),
+            cached: false,
+        };
+        AudioCacheDb::save_audio_record(&conn, &audio).unwrap();
+
+        let references = VoiceProfileDb::get_voice_references(&conn, "v1").unwrap();
+        assert_eq!(references.characters.len(), 1);
+        assert_eq!(references.agents.len(), 1);
+        assert_eq!(references.audio_ids, vec!["clip".to_string()]);
+        let saved = AudioCacheDb::get_audio_record(&conn, "clip").unwrap().unwrap();
+        assert_eq!(saved.params, audio.params);
+
+        assert_eq!(VoiceProfileDb::delete_voice_mappings(&conn, "v1").unwrap(), 2);
+        let references = VoiceProfileDb::get_voice_references(&conn, "v1").unwrap();
+        assert!(references.characters.is_empty());
+        assert_eq!(references.audio_ids.len(), 1);
+        assert_eq!(CharacterVoiceDb::get_character_voices(&conn, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_recent_voices() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        conn.execute("INSERT INTO voice_profiles (id, name) VALUES ('v2', 'Adam')", []).unwrap();
+        VoiceUsageDb::record_use(&conn, "v1").unwrap();
+        VoiceUsageDb::record_use(&conn, "v2").unwrap();
+        VoiceUsageDb::record_use(&conn, "v2").unwrap();
+
+        let recent = VoiceUsageDb::get_recent_voices(&conn, 10).unwrap();
+        assert_eq!(recent.iter().map(|v| v.voice_id.as_str()).collect::<Vec<_>>(), vec!["v2", "v1"]);
+        assert_eq!(recent[0].use_count, 2);
+        assert_eq!(recent[0].name.as_deref(), Some("Adam"));
+        assert_eq!(recent[1].name, None);
+        assert_eq!(VoiceUsageDb::get_recent_voices(&conn, 1).unwrap().len(), 1);
+
+        VoiceUsageDb::delete_voice_usage(&conn, "v2").unwrap();
+        assert_eq!(VoiceUsageDb::get_recent_voices(&conn, 10).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_call_on_blocking_thread() {
+        let db = AudioDb::from_connection(Connection::open_in_memory().unwrap());
+        db.call(|conn| Ok(conn.execute_batch("CREATE TABLE t (v TEXT); INSERT INTO t VALUES ('a');")?))
+            .await
+            .unwrap();
+        let count: i64 = db
+            .call(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))?))
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_pooled_connections_run_concurrently() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = AudioDb::open_at(&dir.path().join("app.db")).unwrap();
+
+        // A held connection doesn't keep other callers waiting
+        let held = db.dedicated().unwrap();
+        let writes = (0..4).map(|i| {
+            let db = db.clone();
+            tokio::spawn(async move {
+                db.call(move |conn| SettingsDb::save_setting(conn, &format!("key{}", i), "value")).await
+            })
+        });
+        for write in futures::future::join_all(writes).await {
+            write.unwrap().unwrap();
+        }
+        assert_eq!(SettingsDb::get_setting(&held, "key3").unwrap().as_deref(), Some("value"));
+        drop(held);
+        assert!(db.with_blocking(|conn| SettingsDb::get_setting(conn, "key0")).unwrap().is_some());
+    }
 }
@@ -0,0 +1,154 @@
+// Where the audio cache lives. The configured directory or the OS cache
+// directory can be missing or read-only (sandboxed and portable installs,
+// read-only profiles), so instead of failing every audio command the first
+// writable candidate is used and the reason for the fallback is kept for
+// `get_audio_cache_location`.
+
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::State;
+use tokio::fs;
+
+use super::error::AudioError;
+use super::ElevenLabsState;
+
+/// Which candidate the cache directory came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheLocationSource {
+    /// Chosen with `set_audio_cache_dir`
+    Configured,
+    /// Under the OS cache directory
+    Default,
+    /// Under the app's local data directory
+    AppData,
+    /// Under the system temp directory; may be cleared by the OS
+    Temp,
+}
+
+impl fmt::Display for CacheLocationSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Configured => "configured directory",
+            Self::Default => "OS cache directory",
+            Self::AppData => "app data directory",
+            Self::Temp => "temp directory",
+        })
+    }
+}
+
+/// The directory the audio cache is using
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheLocation {
+    pub path: String,
+    pub source: CacheLocationSource,
+    /// Why preferred directories were passed over; empty unless this is a fallback
+    pub skipped: Vec<String>,
+}
+
+impl CacheLocation {
+    pub fn is_fallback(&self) -> bool {
+        !self.skipped.is_empty()
+    }
+}
+
+/// Default audio cache location under the OS cache directory
+pub fn default_cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("opcode").join("audio"))
+}
+
+/// Directories tried when there is no usable configured directory, in order
+pub fn fallback_dirs() -> Vec<(CacheLocationSource, Option<PathBuf>)> {
+    vec![
+        (CacheLocationSource::Default, default_cache_dir()),
+        (
+            CacheLocationSource::AppData,
+            dirs::data_local_dir().map(|dir| dir.join("opcode").join("audio-cache")),
+        ),
+        (CacheLocationSource::Temp, Some(std::env::temp_dir().join("opcode-audio"))),
+    ]
+}
+
+/// Check that files can be created in `dir`, creating it if needed
+pub async fn probe_writable(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir).await?;
+    let probe = dir.join(format!(".write-test-{}", uuid::Uuid::new_v4()));
+    fs::write(&probe, b"").await?;
+    fs::remove_file(&probe).await
+}
+
+/// The first writable directory, preferring `configured`
+pub async fn resolve(configured: Option<PathBuf>) -> Result<CacheLocation, AudioError> {
+    let candidates = configured
+        .map(|dir| (CacheLocationSource::Configured, Some(dir)))
+        .into_iter()
+        .chain(fallback_dirs());
+
+    let mut skipped = Vec::new();
+    for (source, dir) in candidates {
+        let Some(dir) = dir else {
+            skipped.push(format!("{}: not available on this system", source));
+            continue;
+        };
+        match probe_writable(&dir).await {
+            Ok(()) => {
+                let location = CacheLocation {
+                    path: dir.to_string_lossy().to_string(),
+                    source,
+                    skipped,
+                };
+                if location.is_fallback() {
+                    log::warn!(
+                        "Audio cache falling back to {} ({}): {}",
+                        location.path,
+                        source,
+                        location.skipped.join("; ")
+                    );
+                }
+                return Ok(location);
+            }
+            Err(e) => skipped.push(format!("{} {}: {}", source, dir.display(), e)),
+        }
+    }
+
+    Err(AudioError::Other(format!(
+        "No writable directory for the audio cache: {}",
+        skipped.join("; ")
+    )))
+}
+
+// ========== Tauri Commands ==========
+
+/// The directory the audio cache is using, and why when it isn't the preferred one
+#[tauri::command]
+pub async fn get_audio_cache_location(state: State<'_, Arc<ElevenLabsState>>) -> Result<CacheLocation, AudioError> {
+    super::ensure_cache(&state).await?;
+    state
+        .cache_location
+        .lock()?
+        .clone()
+        .ok_or_else(|| AudioError::Other("Audio cache location is unknown".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resolve_skips_unwritable_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        // A regular file can't be used as a directory
+        let blocked = temp.path().join("file");
+        std::fs::write(&blocked, b"").unwrap();
+
+        let location = resolve(Some(blocked.join("audio"))).await.unwrap();
+        assert_ne!(location.source, CacheLocationSource::Configured);
+        assert!(location.is_fallback());
+
+        let location = resolve(Some(temp.path().join("audio"))).await.unwrap();
+        assert_eq!(location.source, CacheLocationSource::Configured);
+        assert!(!location.is_fallback());
+    }
+}
@@ -0,0 +1,115 @@
+// Cancellation of generations in flight. A generation command given a job id
+// runs under it until it returns; cancelling the job drops the provider call
+// where it is, and the partly downloaded file with it. A request coalesced
+// with the cancelled one makes its own call instead of failing too.
+
+use futures::future::{AbortHandle, Abortable};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use super::error::AudioError;
+use super::ElevenLabsState;
+
+/// Generations that can be cancelled, by job id
+#[derive(Default)]
+pub struct GenerationJobs {
+    jobs: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl GenerationJobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `work` as job `job_id`. Without an id it runs as is and can't be
+    /// cancelled.
+    pub async fn run<T, Fut>(&self, job_id: Option<String>, work: Fut) -> Result<T, AudioError>
+    where
+        Fut: Future<Output = Result<T, AudioError>>,
+    {
+        let Some(job_id) = job_id else {
+            return work.await;
+        };
+        let (abort, registration) = AbortHandle::new_pair();
+        {
+            let mut jobs = self.jobs.lock()?;
+            if jobs.contains_key(&job_id) {
+                return Err(AudioError::Validation(format!("Generation job {} is already running", job_id)));
+            }
+            jobs.insert(job_id.clone(), abort);
+        }
+        let _job = Job { jobs: self, id: &job_id };
+        Abortable::new(work, registration).await.unwrap_or(Err(AudioError::Cancelled))
+    }
+
+    /// Cancel a running job. Returns whether there was one with this id.
+    pub fn cancel(&self, job_id: &str) -> Result<bool, AudioError> {
+        let abort = self.jobs.lock()?.remove(job_id);
+        if let Some(abort) = &abort {
+            abort.abort();
+        }
+        Ok(abort.is_some())
+    }
+
+    /// Ids of the jobs running now
+    pub fn running(&self) -> Result<Vec<String>, AudioError> {
+        Ok(self.jobs.lock()?.keys().cloned().collect())
+    }
+}
+
+/// Removes a job from the registry when its generation ends
+struct Job<'a> {
+    jobs: &'a GenerationJobs,
+    id: &'a str,
+}
+
+impl Drop for Job<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut jobs) = self.jobs.jobs.lock() {
+            jobs.remove(self.id);
+        }
+    }
+}
+
+/// Cancel a generation started with `job_id`. It fails with a `cancelled`
+/// error and leaves nothing in the cache. Returns whether it was still running.
+#[tauri::command]
+pub async fn eleven_labs_cancel_generation(
+    state: State<'_, Arc<ElevenLabsState>>,
+    job_id: String,
+) -> Result<bool, AudioError> {
+    state.generation_jobs.cancel(&job_id)
+}
+
+/// Job ids of the generations that can be cancelled now
+#[tauri::command]
+pub async fn list_generation_jobs(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<String>, AudioError> {
+    state.generation_jobs.running()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_running_job() {
+        let jobs = GenerationJobs::new();
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok(())
+        };
+        let cancel = async {
+            tokio::task::yield_now().await;
+            assert_eq!(jobs.running().unwrap(), vec!["job".to_string()]);
+            assert!(jobs.cancel("job").unwrap());
+        };
+        let (result, _) = tokio::join!(jobs.run(Some("job".to_string()), slow), cancel);
+
+        assert!(matches!(result, Err(AudioError::Cancelled)));
+        assert!(jobs.running().unwrap().is_empty());
+        assert!(!jobs.cancel("job").unwrap());
+    }
+}
@@ -0,0 +1,80 @@
+// Splitting text too long for one text-to-speech request. Chunks end on
+// sentence boundaries where possible; a sentence longer than the limit is
+// broken between words, and a word longer than it between characters.
+
+use super::narration::split_sentences;
+
+fn len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Pieces of `sentence` of at most `max_chars`, broken between words
+fn split_long_sentence(sentence: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces: Vec<String> = vec![];
+    let mut current = String::new();
+    for word in sentence.split_whitespace() {
+        if !current.is_empty() && len(&current) + 1 + len(word) <= max_chars {
+            current.push(' ');
+            current.push_str(word);
+            continue;
+        }
+        if !current.is_empty() {
+            pieces.push(std::mem::take(&mut current));
+        }
+        let chars: Vec<char> = word.chars().collect();
+        let mut parts = chars.chunks(max_chars).map(|part| part.iter().collect::<String>()).peekable();
+        while let Some(part) = parts.next() {
+            if parts.peek().is_some() {
+                pieces.push(part);
+            } else {
+                current = part;
+            }
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Split `text` into chunks of at most `max_chars` characters, packing whole
+/// sentences into each
+pub fn split_text(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks: Vec<String> = vec![];
+    let pieces = split_sentences(text).into_iter().flat_map(|sentence| {
+        if len(&sentence) > max_chars {
+            split_long_sentence(&sentence, max_chars)
+        } else {
+            vec![sentence]
+        }
+    });
+    for piece in pieces {
+        match chunks.last_mut() {
+            Some(chunk) if len(chunk) + 1 + len(&piece) <= max_chars => {
+                chunk.push(' ');
+                chunk.push_str(&piece);
+            }
+            _ => chunks.push(piece),
+        }
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_text() {
+        let text = "One two. Three four five. Six.";
+        assert_eq!(split_text(text, 100), vec![text]);
+        assert_eq!(split_text(text, 20), vec!["One two.", "Three four five.", "Six."]);
+        assert_eq!(split_text(text, 25), vec!["One two. Three four five.", "Six."]);
+
+        // Long sentences break between words, long words anywhere
+        assert_eq!(split_text("aa bb cc dd.", 5), vec!["aa bb", "cc", "dd."]);
+        assert_eq!(split_text("abcdefgh ij", 3), vec!["abc", "def", "gh", "ij"]);
+        assert!(split_text(&"word ".repeat(5000), 1000).iter().all(|chunk| len(chunk) <= 1000));
+    }
+}
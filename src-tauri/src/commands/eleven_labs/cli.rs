@@ -0,0 +1,305 @@
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::pipeline;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// `opcode audio ...` — run the audio pipeline without launching the UI
+#[derive(Parser)]
+#[command(name = "opcode audio", about = "Headless access to opcode's audio pipeline")]
+struct AudioCli {
+    #[command(subcommand)]
+    command: AudioCommand,
+}
+
+#[derive(Subcommand)]
+enum AudioCommand {
+    /// Render a single line of text to speech
+    Tts {
+        /// Voice ID or voice name (as cached in the voice library)
+        #[arg(long)]
+        voice: String,
+        /// Text to speak
+        #[arg(long)]
+        text: String,
+        #[arg(long)]
+        model: Option<String>,
+        /// Copy the rendered file to this path
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Render a `CHARACTER: line` script using the character voice mappings
+    RenderScript {
+        /// Script file; lines without a `NAME:` prefix use the narrator voice
+        #[arg(long)]
+        script: PathBuf,
+        /// Voice ID or name for unattributed lines
+        #[arg(long)]
+        narrator: Option<String>,
+        /// Project whose character mappings should be used
+        #[arg(long)]
+        project: Option<String>,
+        /// Directory to copy numbered takes into
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+        /// Scene ID recorded on each take (defaults to the script file name)
+        #[arg(long)]
+        scene: Option<String>,
+    },
+    /// Write a JSON manifest of cached audio records
+    ExportManifest {
+        /// tts, sfx, music or all
+        #[arg(long, default_value = "all")]
+        r#type: String,
+        /// Output file (stdout if omitted)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Parse `CHARACTER: text` lines; blank lines and `#` comments are skipped
+pub fn parse_script(content: &str) -> Vec<ScriptLine> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.split_once(':') {
+            Some((name, text))
+                if !name.trim().is_empty()
+                    && name.trim().len() <= 40
+                    && name.split_whitespace().count() <= 3 =>
+            {
+                ScriptLine {
+                    character: Some(name.trim().to_string()),
+                    text: text.trim().to_string(),
+                }
+            }
+            _ => ScriptLine {
+                character: None,
+                text: line.to_string(),
+            },
+        })
+        .filter(|line| !line.text.is_empty())
+        .collect()
+}
+
+/// File name for a rendered script line. The character name is reduced to
+/// `[a-z0-9_-]` so it can't reach outside the output directory, and the
+/// extension follows the cached file's format.
+fn line_file_name(index: usize, character: Option<&str>, local_path: &str) -> String {
+    let name: String = character
+        .unwrap_or("narrator")
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+    let extension = Path::new(local_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("mp3");
+    format!("{:03}_{}.{}", index + 1, name, extension)
+}
+
+fn copy_output(audio: &GeneratedAudio, dest: &Path) -> Result<(), AudioError> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(&audio.local_path, dest)
+        .map(|_| ())
+        .map_err(|e| AudioError::Other(format!("Failed to copy to {}: {}", dest.display(), e)))
+}
+
+fn tts_request(text: String, voice_id: String, model: Option<String>) -> TtsRequest {
+    TtsRequest {
+        text,
+        voice_id,
+        model_id: model.unwrap_or_else(default_model_id),
+        voice_settings: None,
+        output_format: default_output_format(),
+        normalize_text: None,
+        preset: None,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), AudioError> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+async fn run(cli: AudioCli) -> Result<(), AudioError> {
+    let state = ElevenLabsState::new();
+
+    match cli.command {
+        AudioCommand::Tts { voice, text, model, out } => {
+            let voice_id = pipeline::resolve_voice_id(&state.db()?, &voice).await?;
+            let audio = pipeline::generate_tts(&state, tts_request(text, voice_id, model)).await?;
+            if let Some(out) = out {
+                copy_output(&audio, &out)?;
+            }
+            print_json(&audio)
+        }
+        AudioCommand::RenderScript { script, narrator, project, out_dir, scene } => {
+            let content = std::fs::read_to_string(&script)
+                .map_err(|e| format!("Failed to read {}: {}", script.display(), e))?;
+            let lines = parse_script(&content);
+            let scene_id = scene.unwrap_or_else(|| {
+                script
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "script".to_string())
+            });
+
+            let db = state.db()?;
+            let narrator_id = match narrator.as_deref() {
+                Some(voice) => Some(pipeline::resolve_voice_id(&db, voice).await?),
+                None => None,
+            };
+
+            let mut rendered = vec![];
+            for (index, line) in lines.into_iter().enumerate() {
+                // --narrator replaces the cast narrator, including as the
+                // fallback for characters without a voice of their own
+                let voice_id = match (&line.character, &narrator_id) {
+                    (None, Some(narrator_id)) => Ok(narrator_id.clone()),
+                    _ => pipeline::resolve_voice(&db, &VoiceContext {
+                        project_id: project.clone(),
+                        character: line.character.clone(),
+                        ..Default::default()
+                    })
+                    .await
+                    .map(|voice| match (&narrator_id, voice.scope) {
+                        (Some(narrator_id), VoiceScope::Narrator) => narrator_id.clone(),
+                        _ => voice.voice_id,
+                    })
+                    .or_else(|e| narrator_id.clone().ok_or(e)),
+                }
+                .map_err(|e| format!("Line {}: {}", index + 1, e))?;
+
+                // Tag the take so the scene can be exported to a DAW session
+                let mut metadata = serde_json::Map::new();
+                metadata.insert("scene_id".to_string(), serde_json::json!(scene_id));
+                metadata.insert("line".to_string(), serde_json::json!(index + 1));
+                metadata.insert(
+                    "character".to_string(),
+                    serde_json::json!(line.character.as_deref().unwrap_or("Narrator")),
+                );
+                if let Some(project) = &project {
+                    metadata.insert("project_id".to_string(), serde_json::json!(project));
+                }
+
+                let request = tts_request(line.text, voice_id, None);
+                let audio = pipeline::generate_tts_with_metadata(&state, request, project.as_deref(), metadata).await?;
+
+                if let Some(dir) = &out_dir {
+                    let file_name = line_file_name(index, line.character.as_deref(), &audio.local_path);
+                    copy_output(&audio, &dir.join(file_name))?;
+                }
+                eprintln!("Rendered line {}", index + 1);
+                rendered.push(audio);
+            }
+
+            print_json(&rendered)
+        }
+        AudioCommand::ExportManifest { r#type, out } => {
+            let types = if r#type.eq_ignore_ascii_case("all") {
+                vec![AudioType::Tts, AudioType::Sfx, AudioType::Music]
+            } else {
+                vec![AudioType::parse(&r#type)?]
+            };
+
+            let records = state
+                .call_db(move |conn| {
+                    let mut records = vec![];
+                    for audio_type in &types {
+                        records.extend(AudioCacheDb::get_audio_records(conn, audio_type)?);
+                    }
+                    Ok(records)
+                })
+                .await?;
+
+            let manifest = serde_json::json!({
+                "exported_at": chrono::Utc::now().to_rfc3339(),
+                "count": records.len(),
+                "audio": records,
+            });
+
+            match out {
+                Some(out) => std::fs::write(
+                    &out,
+                    serde_json::to_vec_pretty(&manifest)?,
+                )
+                .map_err(|e| AudioError::Other(format!("Failed to write {}: {}", out.display(), e))),
+                None => print_json(&manifest),
+            }
+        }
+    }
+}
+
+/// If the process was started as `opcode audio ...`, run the CLI and return its exit code.
+/// Returns `None` for a normal UI launch.
+pub fn run_from_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) != Some("audio") {
+        return None;
+    }
+
+    let cli = match AudioCli::try_parse_from(args.iter().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return Some(if e.use_stderr() { 2 } else { 0 });
+        }
+    };
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start runtime: {}", e);
+            return Some(1);
+        }
+    };
+
+    match runtime.block_on(run(cli)) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            Some(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script() {
+        let script = "# Scene 1\nALICE: Hello there.\n\nThe door creaks open.\nBob: Who's there?\n";
+        let lines = parse_script(script);
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].character.as_deref(), Some("ALICE"));
+        assert_eq!(lines[0].text, "Hello there.");
+        assert_eq!(lines[1].character, None);
+        assert_eq!(lines[2].character.as_deref(), Some("Bob"));
+    }
+
+    #[test]
+    fn test_line_file_name() {
+        assert_eq!(line_file_name(0, None, "/cache/tts/a.mp3"), "001_narrator.mp3");
+        assert_eq!(line_file_name(1, Some("Old Tom"), "/cache/tts/b.wav"), "002_old_tom.wav");
+        assert_eq!(line_file_name(2, Some("../../etc/x"), "/cache/tts/c.mp3"), "003_______etc_x.mp3");
+        assert_eq!(line_file_name(3, Some("Bob"), "/cache/tts/d"), "004_bob.mp3");
+    }
+}
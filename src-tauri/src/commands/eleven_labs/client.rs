@@ -1,21 +1,160 @@
 use anyhow::{anyhow, Result};
-use reqwest::{header, Client, multipart};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{BoxStream, StreamExt};
+use reqwest::{header, Client, StatusCode, multipart};
 use std::path::Path;
+use base64::Engine;
+use futures::SinkExt;
+use std::time::Duration;
 use tokio::fs;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message as WsMessage};
 
+use super::api::ElevenLabsApi;
+use super::billing::billable_characters;
+use super::clone_samples;
+use super::error::{AudioError, ProviderErrorKind};
 use super::types::*;
 
 const ELEVEN_LABS_BASE_URL: &str = "https://api.elevenlabs.io/v1";
 
+/// How long an idle pooled connection is kept for reuse
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Interval for TCP and HTTP/2 keep-alive probes on open connections
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Audio body of a generation response, yielded in chunks as it arrives. The
+/// chunks are the response buffers themselves, not copies.
+pub type AudioStream = BoxStream<'static, Result<Bytes>>;
+
+/// A WebSocket connection to the API
+pub type WebSocket = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Speech rendered in one piece, with when each character is spoken
+pub struct TimedSpeech {
+    pub audio: Bytes,
+    pub alignment: Option<CharacterAlignment>,
+    pub request_id: Option<String>,
+}
+
+/// An `AudioStream` with the size the provider announced for it, if any
+pub struct AudioDownload {
+    pub stream: AudioStream,
+    pub expected_bytes: Option<u64>,
+    /// The provider's id for the request, for stitching later ones onto it
+    pub request_id: Option<String>,
+}
+
+/// Error for a failed response, classified so callers can tell auth, quota
+/// and rate limit failures apart
+fn api_error(status: StatusCode, body: &str) -> anyhow::Error {
+    let kind = ProviderErrorKind::from_response(status.as_u16(), body);
+    tracing::debug!(status = status.as_u16(), ?kind, "Provider request failed");
+    AudioError::provider(kind, format!("API error {}: {}", status, body)).into()
+}
+
+/// Body of the text-to-speech endpoints
+#[derive(serde::Serialize)]
+struct TtsBody {
+    text: String,
+    model_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    voice_settings: Option<VoiceSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_text: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    previous_request_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u32>,
+}
+
+impl From<TtsRequest> for TtsBody {
+    fn from(request: TtsRequest) -> Self {
+        Self {
+            text: request.text,
+            model_id: request.model_id,
+            voice_settings: request.voice_settings,
+            previous_text: request.previous_text,
+            next_text: request.next_text,
+            previous_request_ids: request.previous_request_ids,
+            seed: request.seed,
+        }
+    }
+}
+
+/// Error for a request that got no response or lost it partway, classified as
+/// a network failure so callers know it may succeed if made again
+fn network_error(action: &str, e: impl std::fmt::Display) -> anyhow::Error {
+    AudioError::provider(ProviderErrorKind::Network, format!("Failed to {}: {}", action, e)).into()
+}
+
+/// The `request-id` header of a generation response
+fn request_id(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get("request-id")
+        .and_then(|id| id.to_str().ok())
+        .map(str::to_string)
+}
+
+fn audio_stream(response: reqwest::Response) -> AudioStream {
+    response
+        .bytes_stream()
+        .map(|chunk| chunk.map_err(|e| network_error("read audio data", e)))
+        .boxed()
+}
+
+/// A voice sample file as a multipart part, typed by its contents
+async fn sample_part(file_path: &str) -> Result<multipart::Part> {
+    let path = Path::new(file_path);
+    let file_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("audio.mp3")
+        .to_string();
+
+    let file_bytes = fs::read(path)
+        .await
+        .map_err(|e| anyhow!("Failed to read file {}: {}", file_path, e))?;
+
+    Ok(multipart::Part::bytes(file_bytes)
+        .file_name(file_name)
+        .mime_str(clone_samples::mime_type(path).await)?)
+}
+
+/// HTTP client builder tuned for the provider APIs: idle connections stay
+/// pooled and alive between requests, and HTTP/2 is used when the server
+/// offers it, so interactive requests skip the TCP/TLS handshake
+pub fn http_client_builder() -> reqwest::ClientBuilder {
+    Client::builder()
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .pool_max_idle_per_host(4)
+        .tcp_keepalive(KEEPALIVE_INTERVAL)
+        .tcp_nodelay(true)
+        .http2_keep_alive_interval(KEEPALIVE_INTERVAL)
+        .http2_keep_alive_while_idle(true)
+        .http2_adaptive_window(true)
+}
+
 /// Eleven Labs API client
+#[derive(Clone)]
 pub struct ElevenLabsClient {
     client: Client,
     api_key: String,
+    base_url: String,
 }
 
 impl ElevenLabsClient {
     /// Create a new Eleven Labs client
     pub fn new(api_key: String) -> Result<Self> {
+        Self::with_base_url(api_key, ELEVEN_LABS_BASE_URL)
+    }
+
+    /// Create a client for an API at `base_url`, such as a mock server in tests
+    pub fn with_base_url(api_key: String, base_url: &str) -> Result<Self> {
         let mut headers = header::HeaderMap::new();
         headers.insert(
             "xi-api-key",
@@ -23,12 +162,16 @@ impl ElevenLabsClient {
                 .map_err(|e| anyhow!("Invalid API key format: {}", e))?,
         );
 
-        let client = Client::builder()
+        let client = http_client_builder()
             .default_headers(headers)
             .build()
             .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
 
-        Ok(Self { client, api_key })
+        Ok(Self {
+            client,
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
     }
 
     /// Get the API key (for storage)
@@ -36,46 +179,147 @@ impl ElevenLabsClient {
         &self.api_key
     }
 
-    // ========== Voice Management ==========
-
     /// List all available voices
     pub async fn list_voices(&self) -> Result<Vec<VoiceProfile>> {
-        let url = format!("{}/voices", ELEVEN_LABS_BASE_URL);
+        self.list_voices_if_changed(None)
+            .await?
+            .map(|(voices, _)| voices)
+            .ok_or_else(|| anyhow!("Unexpected 304 response for voices"))
+    }
+
+    /// Validate the API key by making a simple request
+    pub async fn validate_api_key(&self) -> Result<bool> {
+        match self.get_usage().await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if e.to_string().contains("401") {
+                    Ok(false)
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    /// Send audio to Scribe; `language` is detected when unset
+    async fn transcribe(&self, part: multipart::Part, language: Option<&str>) -> Result<TranscriptionResult> {
+        let url = format!("{}/speech-to-text", self.base_url);
+
+        let mut form = multipart::Form::new()
+            .text("model_id", "scribe_v1")
+            .part("file", part);
+        if let Some(language) = language {
+            form = form.text("language_code", language.to_string());
+        }
 
         let response = self.client
-            .get(&url)
+            .post(&url)
+            .multipart(form)
             .send()
             .await
-            .map_err(|e| anyhow!("Failed to fetch voices: {}", e))?;
+            .map_err(|e| network_error("transcribe audio", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("API error {}: {}", status, text));
+            return Err(api_error(status, &text));
         }
 
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse transcription response: {}", e))
+    }
+
+    /// Open a WebSocket to `path` (with its query) on the API, authenticated
+    /// with the API key. A rejected handshake is classified like any other
+    /// error response.
+    async fn connect_websocket(&self, path: &str) -> Result<WebSocket> {
+        let base_url = self.base_url.replacen("https://", "wss://", 1).replacen("http://", "ws://", 1);
+        let mut request = format!("{}{}", base_url, path).into_client_request()?;
+        request.headers_mut().insert("xi-api-key", header::HeaderValue::from_str(&self.api_key)?);
+        let (socket, _) = tokio_tungstenite::connect_async(request).await.map_err(|e| match e {
+            WsError::Http(response) => {
+                let body = response.body().as_deref().map(String::from_utf8_lossy).unwrap_or_default();
+                api_error(response.status(), &body)
+            }
+            e => network_error("open WebSocket", e),
+        })?;
+        Ok(socket)
+    }
+}
+
+#[async_trait]
+impl ElevenLabsApi for ElevenLabsClient {
+    /// Open a pooled connection to the API ahead of the first real request.
+    /// The response itself is ignored.
+    #[tracing::instrument(skip_all, err(level = "debug", Display))]
+    async fn warm_up(&self) -> Result<()> {
+        self.client
+            .head(&self.base_url)
+            .send()
+            .await
+            .map_err(|e| network_error("connect to Eleven Labs", e))?;
+        Ok(())
+    }
+
+    // ========== Voice Management ==========
+
+    /// List voices unless they are unchanged since the response tagged `etag`.
+    /// Returns `None` for 304 Not Modified, otherwise the voices and their ETag.
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn list_voices_if_changed(
+        &self,
+        etag: Option<&str>,
+    ) -> Result<Option<(Vec<VoiceProfile>, Option<String>)>> {
+        let url = format!("{}/voices", self.base_url);
+
+        let mut request = self.client.get(&url);
+        if let Some(etag) = etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| network_error("fetch voices", e))?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
         let voices_response: VoicesResponse = response
             .json()
             .await
             .map_err(|e| anyhow!("Failed to parse voices response: {}", e))?;
 
-        Ok(voices_response.voices.into_iter().map(VoiceProfile::from).collect())
+        Ok(Some((voices_response.voices.into_iter().map(VoiceProfile::from).collect(), etag)))
     }
 
     /// Get a specific voice by ID
-    pub async fn get_voice(&self, voice_id: &str) -> Result<VoiceProfile> {
-        let url = format!("{}/voices/{}", ELEVEN_LABS_BASE_URL, voice_id);
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn get_voice(&self, voice_id: &str) -> Result<VoiceProfile> {
+        let url = format!("{}/voices/{}", self.base_url, voice_id);
 
         let response = self.client
             .get(&url)
             .send()
             .await
-            .map_err(|e| anyhow!("Failed to fetch voice: {}", e))?;
+            .map_err(|e| network_error("fetch voice", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("API error {}: {}", status, text));
+            return Err(api_error(status, &text));
         }
 
         let voice: ElevenLabsVoice = response
@@ -86,9 +330,33 @@ impl ElevenLabsClient {
         Ok(VoiceProfile::from(voice))
     }
 
+    /// List the models available to the account
+    #[tracing::instrument(skip_all, err(level = "warn", Display))]
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let url = format!("{}/models", self.base_url);
+
+        let response = self.client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| network_error("fetch models", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse models response: {}", e))
+    }
+
     /// Clone a voice from audio files
-    pub async fn clone_voice(&self, request: VoiceCloneRequest) -> Result<VoiceProfile> {
-        let url = format!("{}/voices/add", ELEVEN_LABS_BASE_URL);
+    #[tracing::instrument(skip_all, fields(name = %request.name, files = request.files.len()), err(level = "warn", Display))]
+    async fn clone_voice(&self, request: VoiceCloneRequest) -> Result<VoiceProfile> {
+        let url = format!("{}/voices/add", self.base_url);
 
         let mut form = multipart::Form::new()
             .text("name", request.name.clone());
@@ -103,21 +371,7 @@ impl ElevenLabsClient {
 
         // Add audio files
         for file_path in &request.files {
-            let path = Path::new(file_path);
-            let file_name = path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("audio.mp3")
-                .to_string();
-
-            let file_bytes = fs::read(path)
-                .await
-                .map_err(|e| anyhow!("Failed to read file {}: {}", file_path, e))?;
-
-            let part = multipart::Part::bytes(file_bytes)
-                .file_name(file_name)
-                .mime_str("audio/mpeg")?;
-
-            form = form.part("files", part);
+            form = form.part("files", sample_part(file_path).await?);
         }
 
         let response = self.client
@@ -125,12 +379,12 @@ impl ElevenLabsClient {
             .multipart(form)
             .send()
             .await
-            .map_err(|e| anyhow!("Failed to clone voice: {}", e))?;
+            .map_err(|e| network_error("clone voice", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("API error {}: {}", status, text));
+            return Err(api_error(status, &text));
         }
 
         #[derive(serde::Deserialize)]
@@ -147,20 +401,224 @@ impl ElevenLabsClient {
         self.get_voice(&clone_response.voice_id).await
     }
 
+    /// Search the shared voice library
+    #[tracing::instrument(skip_all, fields(page = filter.page), err(level = "warn", Display))]
+    async fn search_shared_voices(&self, filter: &SharedVoiceFilter) -> Result<SharedVoicePage> {
+        let url = format!("{}/shared-voices", self.base_url);
+
+        let mut query = vec![
+            ("page", filter.page.to_string()),
+            ("page_size", filter.page_size.to_string()),
+        ];
+        let optional = [
+            ("search", &filter.search),
+            ("gender", &filter.gender),
+            ("accent", &filter.accent),
+            ("use_cases", &filter.use_case),
+            ("language", &filter.language),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                query.push((name, value.clone()));
+            }
+        }
+
+        let response = self.client
+            .get(&url)
+            .query(&query)
+            .send()
+            .await
+            .map_err(|e| network_error("search shared voices", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse shared voices response: {}", e))
+    }
+
+    /// Add a shared library voice to the account
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn add_shared_voice(&self, public_owner_id: &str, voice_id: &str, name: &str) -> Result<VoiceProfile> {
+        let url = format!("{}/voices/add/{}/{}", self.base_url, public_owner_id, voice_id);
+
+        let response = self.client
+            .post(&url)
+            .json(&serde_json::json!({ "new_name": name }))
+            .send()
+            .await
+            .map_err(|e| network_error("add shared voice", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        #[derive(serde::Deserialize)]
+        struct AddResponse {
+            voice_id: String,
+        }
+
+        let added: AddResponse = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse add voice response: {}", e))?;
+
+        // Fetch the full voice profile
+        self.get_voice(&added.voice_id).await
+    }
+
+    /// Generate candidate voices from a description
+    #[tracing::instrument(skip_all, err(level = "warn", Display))]
+    async fn design_voice_previews(&self, request: VoiceDesignRequest) -> Result<VoiceDesignPreviews> {
+        let url = format!("{}/text-to-voice/create-previews", self.base_url);
+
+        #[derive(serde::Serialize)]
+        struct PreviewsBody {
+            voice_description: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            text: Option<String>,
+            auto_generate_text: bool,
+        }
+
+        let body = PreviewsBody {
+            auto_generate_text: request.text.is_none(),
+            voice_description: request.voice_description,
+            text: request.text,
+        };
+
+        let response = self.client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| network_error("design voice", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse voice design response: {}", e))
+    }
+
+    /// Save a voice design preview as a new voice
+    #[tracing::instrument(skip_all, fields(name = %request.name), err(level = "warn", Display))]
+    async fn create_designed_voice(&self, request: DesignedVoiceRequest) -> Result<VoiceProfile> {
+        let url = format!("{}/text-to-voice/create-voice-from-preview", self.base_url);
+
+        #[derive(serde::Serialize)]
+        struct CreateBody {
+            voice_name: String,
+            voice_description: String,
+            generated_voice_id: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            labels: Option<serde_json::Value>,
+        }
+
+        let body = CreateBody {
+            voice_name: request.name,
+            voice_description: request.description,
+            generated_voice_id: request.generated_voice_id,
+            labels: request.labels,
+        };
+
+        let response = self.client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| network_error("create designed voice", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        let voice: ElevenLabsVoice = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse voice response: {}", e))?;
+
+        Ok(VoiceProfile::from(voice))
+    }
+
+    /// Edit a voice. The provider needs the name and description with every
+    /// edit, so unset ones are filled in from the current voice.
+    #[tracing::instrument(skip_all, fields(voice_id = %request.voice_id), err(level = "warn", Display))]
+    async fn edit_voice(&self, request: VoiceEditRequest) -> Result<VoiceProfile> {
+        let current = self.get_voice(&request.voice_id).await?;
+        let url = format!("{}/voices/{}/edit", self.base_url, request.voice_id);
+
+        let mut form = multipart::Form::new()
+            .text("name", request.name.unwrap_or(current.name));
+        if let Some(desc) = request.description.or(current.description) {
+            form = form.text("description", desc);
+        }
+        if let Some(labels) = request.labels.or(current.labels) {
+            form = form.text("labels", serde_json::to_string(&labels)?);
+        }
+        for file_path in &request.add_files {
+            form = form.part("files", sample_part(file_path).await?);
+        }
+
+        let response = self.client
+            .post(&url)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| network_error("edit voice", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        for sample_id in &request.remove_sample_ids {
+            let url = format!("{}/voices/{}/samples/{}", self.base_url, request.voice_id, sample_id);
+            let response = self.client
+                .delete(&url)
+                .send()
+                .await
+                .map_err(|e| network_error("delete voice sample", e))?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let text = response.text().await.unwrap_or_default();
+                return Err(api_error(status, &text));
+            }
+        }
+
+        self.get_voice(&request.voice_id).await
+    }
+
     /// Delete a voice
-    pub async fn delete_voice(&self, voice_id: &str) -> Result<()> {
-        let url = format!("{}/voices/{}", ELEVEN_LABS_BASE_URL, voice_id);
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn delete_voice(&self, voice_id: &str) -> Result<()> {
+        let url = format!("{}/voices/{}", self.base_url, voice_id);
 
         let response = self.client
             .delete(&url)
             .send()
             .await
-            .map_err(|e| anyhow!("Failed to delete voice: {}", e))?;
+            .map_err(|e| network_error("delete voice", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("API error {}: {}", status, text));
+            return Err(api_error(status, &text));
         }
 
         Ok(())
@@ -168,55 +626,260 @@ impl ElevenLabsClient {
 
     // ========== Text-to-Speech ==========
 
-    /// Generate speech from text
-    pub async fn text_to_speech(&self, request: TtsRequest) -> Result<Vec<u8>> {
+    /// Generate speech from text. The audio is streamed rather than buffered.
+    async fn text_to_speech(&self, request: TtsRequest) -> Result<AudioStream> {
+        Ok(self.text_to_speech_download(request).await?.stream)
+    }
+
+    /// `text_to_speech`, with the response's content length for progress
+    #[tracing::instrument(skip_all, fields(voice_id = %request.voice_id, chars = billable_characters(&request.text)), err(level = "warn", Display))]
+    async fn text_to_speech_download(&self, request: TtsRequest) -> Result<AudioDownload> {
         let url = format!(
             "{}/text-to-speech/{}?output_format={}",
-            ELEVEN_LABS_BASE_URL,
+            self.base_url,
             request.voice_id,
             request.output_format
         );
 
-        #[derive(serde::Serialize)]
-        struct TtsBody {
-            text: String,
-            model_id: String,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            voice_settings: Option<VoiceSettings>,
+        let response = self.client
+            .post(&url)
+            .json(&TtsBody::from(request))
+            .send()
+            .await
+            .map_err(|e| network_error("generate speech", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
         }
 
-        let body = TtsBody {
-            text: request.text,
-            model_id: request.model_id,
-            voice_settings: request.voice_settings,
-        };
+        Ok(AudioDownload {
+            expected_bytes: response.content_length(),
+            request_id: request_id(&response),
+            stream: audio_stream(response),
+        })
+    }
+
+    /// Generate speech with character timings. The audio comes back base64
+    /// encoded in one JSON response, so it is buffered rather than streamed.
+    #[tracing::instrument(skip_all, fields(voice_id = %request.voice_id, chars = billable_characters(&request.text)), err(level = "warn", Display))]
+    async fn text_to_speech_with_timestamps(&self, request: TtsRequest) -> Result<TimedSpeech> {
+        let url = format!(
+            "{}/text-to-speech/{}/with-timestamps?output_format={}",
+            self.base_url,
+            request.voice_id,
+            request.output_format
+        );
 
         let response = self.client
             .post(&url)
-            .json(&body)
+            .json(&TtsBody::from(request))
             .send()
             .await
-            .map_err(|e| anyhow!("Failed to generate speech: {}", e))?;
+            .map_err(|e| network_error("generate speech", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("API error {}: {}", status, text));
+            return Err(api_error(status, &text));
         }
+        let request_id = request_id(&response);
 
-        let bytes = response
-            .bytes()
+        #[derive(serde::Deserialize)]
+        struct TimestampsResponse {
+            audio_base64: String,
+            #[serde(default)]
+            alignment: Option<CharacterAlignment>,
+            #[serde(default)]
+            normalized_alignment: Option<CharacterAlignment>,
+        }
+
+        let body: TimestampsResponse = response
+            .json()
             .await
-            .map_err(|e| anyhow!("Failed to read audio data: {}", e))?;
+            .map_err(|e| anyhow!("Failed to parse speech response: {}", e))?;
+        let audio = base64::engine::general_purpose::STANDARD
+            .decode(body.audio_base64)
+            .map_err(|e| anyhow!("Failed to decode speech audio: {}", e))?;
+        Ok(TimedSpeech {
+            audio: Bytes::from(audio),
+            // The normalized alignment follows the text as spoken ("3" as "three")
+            alignment: body.alignment.or(body.normalized_alignment),
+            request_id,
+        })
+    }
+
+    /// Generate speech over the input-streaming WebSocket. The text is sent in
+    /// one message and flushed; audio frames are yielded as they arrive.
+    #[tracing::instrument(skip_all, fields(voice_id = %request.voice_id, chars = request.text.len()), err(level = "warn", Display))]
+    async fn text_to_speech_websocket(&self, request: TtsRequest) -> Result<AudioStream> {
+        let path = format!(
+            "/text-to-speech/{}/stream-input?model_id={}&output_format={}",
+            request.voice_id,
+            request.model_id,
+            request.output_format
+        );
+        let mut socket = self.connect_websocket(&path).await?;
+
+        // The first message opens the stream (its text must be a single
+        // space), the second carries the text and an empty one ends the input
+        let mut opening = serde_json::json!({ "text": " " });
+        if let Some(settings) = &request.voice_settings {
+            opening["voice_settings"] = serde_json::to_value(settings)?;
+        }
+        let messages = [
+            opening,
+            serde_json::json!({ "text": format!("{} ", request.text), "flush": true }),
+            serde_json::json!({ "text": "" }),
+        ];
+        for message in messages {
+            socket
+                .send(WsMessage::text(message.to_string()))
+                .await
+                .map_err(|e| network_error("send text to speech stream", e))?;
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Frame {
+            #[serde(default)]
+            audio: Option<String>,
+            #[serde(default)]
+            is_final: Option<bool>,
+            #[serde(default)]
+            error: Option<String>,
+            #[serde(default)]
+            message: Option<String>,
+        }
 
-        Ok(bytes.to_vec())
+        let frames = futures::stream::unfold(Some(socket), |socket| async move {
+            let mut socket = socket?;
+            loop {
+                let message = match socket.next().await? {
+                    Ok(message) => message,
+                    Err(e) => return Some((Err(network_error("read audio data", e)), None)),
+                };
+                let text = match message {
+                    WsMessage::Text(text) => text,
+                    WsMessage::Close(_) => return None,
+                    _ => continue,
+                };
+                let frame: Frame = match serde_json::from_str(&text) {
+                    Ok(frame) => frame,
+                    Err(e) => return Some((Err(anyhow!("Failed to parse speech stream frame: {}", e)), None)),
+                };
+                if let Some(error) = frame.error {
+                    let message = frame.message.unwrap_or_default();
+                    return Some((Err(anyhow!("Speech stream error {}: {}", error, message)), None));
+                }
+                let chunk = match frame.audio.filter(|audio| !audio.is_empty()) {
+                    Some(audio) => match base64::engine::general_purpose::STANDARD.decode(audio) {
+                        Ok(bytes) => Some(Ok(Bytes::from(bytes))),
+                        Err(e) => Some(Err(anyhow!("Invalid audio in speech stream: {}", e))),
+                    },
+                    None => None,
+                };
+                let next = if frame.is_final == Some(true) { None } else { Some(socket) };
+                match (chunk, next) {
+                    (Some(chunk), next) => return Some((chunk, next)),
+                    (None, None) => return None,
+                    (None, Some(rest)) => socket = rest,
+                }
+            }
+        });
+        Ok(frames.boxed())
+    }
+
+    // ========== Speech-to-Speech ==========
+
+    /// Convert a recording into another voice. The audio is streamed rather than buffered.
+    #[tracing::instrument(skip_all, fields(voice_id = %request.voice_id), err(level = "warn", Display))]
+    async fn speech_to_speech(&self, request: VoiceChangeRequest) -> Result<AudioStream> {
+        let url = format!(
+            "{}/speech-to-speech/{}?output_format={}",
+            self.base_url,
+            request.voice_id,
+            request.output_format
+        );
+
+        let path = Path::new(&request.source_path);
+        let file_name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("audio.mp3")
+            .to_string();
+        let file_bytes = fs::read(path)
+            .await
+            .map_err(|e| anyhow!("Failed to read file {}: {}", request.source_path, e))?;
+        let part = multipart::Part::bytes(file_bytes)
+            .file_name(file_name)
+            .mime_str(clone_samples::mime_type(path).await)?;
+
+        let mut form = multipart::Form::new()
+            .part("audio", part)
+            .text("model_id", request.model_id)
+            .text("remove_background_noise", request.remove_background_noise.to_string());
+        if let Some(settings) = &request.voice_settings {
+            form = form.text("voice_settings", serde_json::to_string(settings)?);
+        }
+
+        let response = self.client
+            .post(&url)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| network_error("convert speech", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        Ok(audio_stream(response))
+    }
+
+    // ========== Audio Isolation ==========
+
+    /// Remove background noise from a recording. The audio is streamed rather than buffered.
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn isolate_audio(&self, path: &Path) -> Result<AudioStream> {
+        let url = format!("{}/audio-isolation", self.base_url);
+
+        let file_name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("audio.mp3")
+            .to_string();
+        let file_bytes = fs::read(path)
+            .await
+            .map_err(|e| anyhow!("Failed to read file {}: {}", path.display(), e))?;
+        let part = multipart::Part::bytes(file_bytes)
+            .file_name(file_name)
+            .mime_str(clone_samples::mime_type(path).await)?;
+        let form = multipart::Form::new().part("audio", part);
+
+        let response = self.client
+            .post(&url)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| network_error("isolate audio", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        Ok(audio_stream(response))
     }
 
     // ========== Sound Effects ==========
 
-    /// Generate sound effects
-    pub async fn generate_sound_effects(&self, request: SfxRequest) -> Result<Vec<u8>> {
-        let url = format!("{}/sound-generation", ELEVEN_LABS_BASE_URL);
+    /// Generate sound effects. The audio is streamed rather than buffered.
+    #[tracing::instrument(skip_all, fields(duration_seconds = request.duration_seconds), err(level = "warn", Display))]
+    async fn generate_sound_effects(&self, request: SfxRequest) -> Result<AudioStream> {
+        let url = format!("{}/sound-generation", self.base_url);
 
         #[derive(serde::Serialize)]
         struct SfxBody {
@@ -236,38 +899,288 @@ impl ElevenLabsClient {
             .json(&body)
             .send()
             .await
-            .map_err(|e| anyhow!("Failed to generate sound effects: {}", e))?;
+            .map_err(|e| network_error("generate sound effects", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        Ok(audio_stream(response))
+    }
+
+    // ========== Music ==========
+
+    /// Compose music from a prompt. The audio is streamed rather than buffered.
+    #[tracing::instrument(skip_all, fields(duration_seconds = request.duration_seconds), err(level = "warn", Display))]
+    async fn generate_music(&self, request: MusicRequest) -> Result<AudioStream> {
+        let url = format!("{}/music", self.base_url);
+
+        #[derive(serde::Serialize)]
+        struct MusicBody {
+            prompt: String,
+            music_length_ms: u32,
+            model_id: &'static str,
+        }
+
+        let body = MusicBody {
+            prompt: request.full_prompt(),
+            music_length_ms: (request.duration_seconds * 1000.0).round() as u32,
+            model_id: MUSIC_MODEL_ID,
+        };
+
+        let response = self.client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| network_error("generate music", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("API error {}: {}", status, text));
+            return Err(api_error(status, &text));
         }
 
-        let bytes = response
-            .bytes()
+        Ok(audio_stream(response))
+    }
+
+    // ========== Dubbing ==========
+
+    /// Upload a file to be dubbed into another language
+    #[tracing::instrument(skip_all, fields(target_lang = %request.target_lang), err(level = "warn", Display))]
+    async fn create_dubbing(&self, request: DubbingRequest) -> Result<DubbingSubmission> {
+        let url = format!("{}/dubbing", self.base_url);
+
+        let path = Path::new(&request.source_path);
+        let file_name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("audio.mp3")
+            .to_string();
+        let file_bytes = fs::read(path)
+            .await
+            .map_err(|e| anyhow!("Failed to read file {}: {}", request.source_path, e))?;
+        let part = multipart::Part::bytes(file_bytes)
+            .file_name(file_name.clone())
+            .mime_str(clone_samples::mime_type(path).await)?;
+
+        let form = multipart::Form::new()
+            .part("file", part)
+            .text("name", file_name)
+            .text("target_lang", request.target_lang)
+            .text("source_lang", request.source_lang.unwrap_or_else(|| "auto".to_string()))
+            .text("num_speakers", request.num_speakers.unwrap_or(0).to_string());
+
+        let response = self.client
+            .post(&url)
+            .multipart(form)
+            .send()
             .await
-            .map_err(|e| anyhow!("Failed to read audio data: {}", e))?;
+            .map_err(|e| network_error("submit dubbing", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
 
-        Ok(bytes.to_vec())
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse dubbing response: {}", e))
+    }
+
+    /// Get the status of a dubbing project
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn get_dubbing(&self, dubbing_id: &str) -> Result<DubbingProject> {
+        let url = format!("{}/dubbing/{}", self.base_url, dubbing_id);
+
+        let response = self.client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| network_error("fetch dubbing status", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse dubbing status: {}", e))
+    }
+
+    /// Download a finished dub. The audio is streamed rather than buffered.
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn get_dubbed_audio(&self, dubbing_id: &str, language: &str) -> Result<AudioStream> {
+        let url = format!("{}/dubbing/{}/audio/{}", self.base_url, dubbing_id, language);
+
+        let response = self.client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| network_error("download dubbed audio", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        Ok(audio_stream(response))
+    }
+
+    // ========== Speech-to-Text ==========
+
+    /// Transcribe a WAV recording
+    #[tracing::instrument(skip(self, wav), fields(bytes = wav.len()), err(level = "warn", Display))]
+    async fn speech_to_text(&self, wav: Vec<u8>, language: Option<&str>) -> Result<String> {
+        let part = multipart::Part::bytes(wav)
+            .file_name("recording.wav")
+            .mime_str("audio/wav")?;
+        Ok(self.transcribe(part, language).await?.text)
+    }
+
+    /// Transcribe an audio file, with word timings
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn transcribe_file(&self, path: &Path, language: Option<&str>) -> Result<TranscriptionResult> {
+        let file_name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("audio.mp3")
+            .to_string();
+        let file_bytes = fs::read(path)
+            .await
+            .map_err(|e| anyhow!("Failed to read file {}: {}", path.display(), e))?;
+        let part = multipart::Part::bytes(file_bytes)
+            .file_name(file_name)
+            .mime_str(clone_samples::mime_type(path).await)?;
+        self.transcribe(part, language).await
+    }
+
+    // ========== Conversational AI ==========
+
+    /// Open a realtime conversation with an agent. Audio and events flow both
+    /// ways over the socket; see `realtime` for the messages.
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn open_conversation(&self, agent_id: &str) -> Result<WebSocket> {
+        self.connect_websocket(&format!("/convai/conversation?agent_id={}", agent_id)).await
+    }
+
+    // ========== History ==========
+
+    /// List generation history, newest first
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn list_history(&self, page_size: u32, start_after: Option<&str>) -> Result<HistoryPage> {
+        let url = format!("{}/history", self.base_url);
+
+        let mut query = vec![("page_size", page_size.to_string())];
+        if let Some(start_after) = start_after {
+            query.push(("start_after_history_item_id", start_after.to_string()));
+        }
+
+        let response = self.client
+            .get(&url)
+            .query(&query)
+            .send()
+            .await
+            .map_err(|e| network_error("fetch history", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse history response: {}", e))
+    }
+
+    /// Get a single history item
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn get_history_item(&self, history_item_id: &str) -> Result<HistoryItem> {
+        let url = format!("{}/history/{}", self.base_url, history_item_id);
+
+        let response = self.client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| network_error("fetch history item", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse history item: {}", e))
+    }
+
+    /// Download the audio of a history item. The audio is streamed rather than buffered.
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn get_history_audio(&self, history_item_id: &str) -> Result<AudioStream> {
+        let url = format!("{}/history/{}/audio", self.base_url, history_item_id);
+
+        let response = self.client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| network_error("download history audio", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        Ok(audio_stream(response))
+    }
+
+    /// Delete a history item on the provider
+    #[tracing::instrument(skip(self), err(level = "warn", Display))]
+    async fn delete_history_item(&self, history_item_id: &str) -> Result<()> {
+        let url = format!("{}/history/{}", self.base_url, history_item_id);
+
+        let response = self.client
+            .delete(&url)
+            .send()
+            .await
+            .map_err(|e| network_error("delete history item", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(api_error(status, &text));
+        }
+
+        Ok(())
     }
 
     // ========== Usage & Subscription ==========
 
     /// Get subscription/usage info
-    pub async fn get_usage(&self) -> Result<UsageInfo> {
-        let url = format!("{}/user/subscription", ELEVEN_LABS_BASE_URL);
+    #[tracing::instrument(skip_all, err(level = "warn", Display))]
+    async fn get_usage(&self) -> Result<UsageInfo> {
+        let url = format!("{}/user/subscription", self.base_url);
 
         let response = self.client
             .get(&url)
             .send()
             .await
-            .map_err(|e| anyhow!("Failed to fetch usage info: {}", e))?;
+            .map_err(|e| network_error("fetch usage info", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("API error {}: {}", status, text));
+            return Err(api_error(status, &text));
         }
 
         let subscription: SubscriptionInfo = response
@@ -277,20 +1190,6 @@ impl ElevenLabsClient {
 
         Ok(UsageInfo::from(subscription))
     }
-
-    /// Validate the API key by making a simple request
-    pub async fn validate_api_key(&self) -> Result<bool> {
-        match self.get_usage().await {
-            Ok(_) => Ok(true),
-            Err(e) => {
-                if e.to_string().contains("401") {
-                    Ok(false)
-                } else {
-                    Err(e)
-                }
-            }
-        }
-    }
 }
 
 #[cfg(test)]
@@ -302,4 +1201,16 @@ mod tests {
         let client = ElevenLabsClient::new("test_key".to_string());
         assert!(client.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_unreachable_host_is_retryable_network_error() {
+        // A port that was just free, so nothing accepts the connection
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let client = ElevenLabsClient::with_base_url("test_key".to_string(), &format!("http://127.0.0.1:{}", port))
+            .unwrap();
+
+        let error = AudioError::from(client.list_models().await.unwrap_err());
+        assert!(matches!(error, AudioError::Provider { kind: ProviderErrorKind::Network, .. }));
+        assert!(error.retryable());
+    }
 }
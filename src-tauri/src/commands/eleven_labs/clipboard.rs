@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use super::billing::billable_characters;
+use super::cache::{AudioDb, SettingsDb};
+use super::error::AudioError;
+use super::pipeline;
+use super::speech_queue::SpeechQueue;
+use super::supervisor::TaskHandle;
+use super::types::VoiceContext;
+use super::ElevenLabsState;
+
+/// Settings key holding the clipboard speak configuration
+pub const CLIPBOARD_SPEAK_KEY: &str = "clipboard_speak";
+
+/// How often the clipboard is checked for new text
+const POLL_INTERVAL: Duration = Duration::from_millis(750);
+
+/// Read copied text aloud
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardSpeakConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Voice to use; falls back to the project's narrator
+    #[serde(default)]
+    pub voice_id: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Longer clipboard contents are ignored rather than billed
+    #[serde(default = "default_max_chars")]
+    pub max_chars: usize,
+    /// Minimum time between two spoken clips
+    #[serde(default = "default_cooldown_seconds")]
+    pub cooldown_seconds: u64,
+}
+
+fn default_max_chars() -> usize {
+    1500
+}
+
+fn default_cooldown_seconds() -> u64 {
+    3
+}
+
+impl Default for ClipboardSpeakConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            voice_id: None,
+            project_id: None,
+            max_chars: default_max_chars(),
+            cooldown_seconds: default_cooldown_seconds(),
+        }
+    }
+}
+
+impl ClipboardSpeakConfig {
+    pub async fn load(db: &AudioDb) -> Result<Self, AudioError> {
+        match db.call(|conn| SettingsDb::get_setting(conn, CLIPBOARD_SPEAK_KEY)).await? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub async fn resolve_voice(&self, db: &AudioDb) -> Result<String, AudioError> {
+        match &self.voice_id {
+            Some(voice_id) => Ok(voice_id.clone()),
+            None => {
+                let context = VoiceContext {
+                    project_id: self.project_id.clone(),
+                    ..Default::default()
+                };
+                Ok(pipeline::resolve_voice(db, &context).await?.voice_id)
+            }
+        }
+    }
+}
+
+/// Poll the clipboard and queue newly copied text for speech
+fn spawn_watcher(app: AppHandle, db: AudioDb, config: ClipboardSpeakConfig) -> TaskHandle {
+    let tasks = app.state::<Arc<ElevenLabsState>>().tasks().clone();
+    tasks.spawn("clipboard-speak", |shutdown| async move {
+        // Whatever was on the clipboard before enabling is not spoken
+        let mut last_seen = app.clipboard().read_text().unwrap_or_default();
+        let mut last_spoken: Option<Instant> = None;
+        let cooldown = Duration::from_secs(config.cooldown_seconds);
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                _ = shutdown.wait() => break,
+            }
+
+            let text = match app.clipboard().read_text() {
+                Ok(text) => text,
+                Err(_) => continue, // non-text contents
+            };
+            if text == last_seen {
+                continue;
+            }
+            last_seen = text.clone();
+
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            if billable_characters(text) > config.max_chars {
+                log::info!("Clipboard text exceeds {} characters, not speaking", config.max_chars);
+                continue;
+            }
+            if last_spoken.is_some_and(|at| at.elapsed() < cooldown) {
+                continue;
+            }
+
+            let voice_id = match config.resolve_voice(&db).await {
+                Ok(voice_id) => voice_id,
+                Err(e) => {
+                    log::warn!("Clipboard speak has no voice: {}", e);
+                    continue;
+                }
+            };
+
+            let queue = app.state::<SpeechQueue>();
+            if let Err(e) = queue.enqueue(text.to_string(), voice_id, "clipboard") {
+                log::warn!("Failed to queue clipboard text: {}", e);
+            }
+            last_spoken = Some(Instant::now());
+        }
+    })
+}
+
+/// Start the watcher at launch if clipboard speak was left enabled
+pub async fn start_if_enabled(app: &AppHandle) -> Result<(), AudioError> {
+    let state = app.state::<Arc<ElevenLabsState>>();
+    let db = state.db()?;
+    let config = ClipboardSpeakConfig::load(&db).await?;
+    if config.enabled {
+        *state.clipboard_watcher.lock().await = Some(spawn_watcher(app.clone(), db, config));
+    }
+    Ok(())
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the clipboard speak configuration
+#[tauri::command]
+pub async fn get_clipboard_speak_config(state: State<'_, Arc<ElevenLabsState>>) -> Result<ClipboardSpeakConfig, AudioError> {
+    ClipboardSpeakConfig::load(&state.db()?).await
+}
+
+/// Update the clipboard speak configuration, starting or stopping the watcher
+#[tauri::command]
+pub async fn set_clipboard_speak_config(
+    app: AppHandle,
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: ClipboardSpeakConfig,
+) -> Result<ClipboardSpeakConfig, AudioError> {
+    let db = state.db()?;
+    if config.enabled {
+        config.resolve_voice(&db).await?;
+    }
+
+    let json = serde_json::to_string(&config)?;
+    db.call(move |conn| SettingsDb::save_setting(conn, CLIPBOARD_SPEAK_KEY, &json)).await?;
+
+    let mut watcher = state.clipboard_watcher.lock().await;
+    if let Some(handle) = watcher.take() {
+        handle.abort();
+    }
+    if config.enabled {
+        *watcher = Some(spawn_watcher(app, db, config.clone()));
+    }
+
+    Ok(config)
+}
@@ -0,0 +1,263 @@
+// Voice clone sample checks, run before anything is uploaded. Eleven Labs
+// rejects the whole clone request when one sample breaks its limits, and only
+// after the multipart body has been sent; checking locally reports every
+// problem file at once. Recordings that are only too long are split into
+// pieces that fit instead of being rejected.
+
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+
+use super::error::AudioError;
+use super::types::{SampleDiagnostic, SampleIssue};
+
+/// Most samples accepted by one clone request
+pub const MAX_SAMPLES: usize = 25;
+
+/// Per-file upload limit
+pub const MAX_SAMPLE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Shortest sample the provider can learn anything from
+pub const MIN_SAMPLE_SECONDS: f64 = 1.0;
+
+/// Longest piece a recording is split into
+const MAX_SEGMENT_SECONDS: f64 = 180.0;
+
+/// Audio formats the clone endpoint accepts, recognised by their contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    Mp3,
+    Wav,
+    M4a,
+    Flac,
+    Ogg,
+    Webm,
+}
+
+impl SampleFormat {
+    /// Recognise a format from the first bytes of a file
+    pub fn sniff(header: &[u8]) -> Option<Self> {
+        match header {
+            [b'I', b'D', b'3', ..] => Some(Self::Mp3),
+            [0xFF, second, ..] if second & 0xE0 == 0xE0 => Some(Self::Mp3),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(Self::Wav),
+            [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(Self::M4a),
+            [b'f', b'L', b'a', b'C', ..] => Some(Self::Flac),
+            [b'O', b'g', b'g', b'S', ..] => Some(Self::Ogg),
+            [0x1A, 0x45, 0xDF, 0xA3, ..] => Some(Self::Webm),
+            _ => None,
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            Self::Mp3 => "audio/mpeg",
+            Self::Wav => "audio/wav",
+            Self::M4a => "audio/mp4",
+            Self::Flac => "audio/flac",
+            Self::Ogg => "audio/ogg",
+            Self::Webm => "audio/webm",
+        }
+    }
+}
+
+/// MIME type for an upload, from its contents
+pub async fn mime_type(path: &Path) -> &'static str {
+    match read_header(path).await {
+        Ok(header) => SampleFormat::sniff(&header).map_or("application/octet-stream", SampleFormat::mime),
+        Err(_) => "application/octet-stream",
+    }
+}
+
+async fn read_header(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(12);
+    tokio::fs::File::open(path).await?.take(12).read_to_end(&mut header).await?;
+    Ok(header)
+}
+
+/// Duration from the `Duration: 00:01:02.35` line ffmpeg prints for its input
+fn parse_ffmpeg_duration(stderr: &str) -> Option<f64> {
+    let value = stderr.split("Duration: ").nth(1)?.split(',').next()?.trim();
+    let mut parts = value.split(':').map(|part| part.parse::<f64>().ok());
+    let (hours, minutes, seconds) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Length of an audio file, from ffmpeg or, for WAV files, the header
+async fn probe_duration(ffmpeg: &str, path: &Path, format: SampleFormat) -> Option<f64> {
+    let output = tokio::process::Command::new(ffmpeg)
+        .args(["-hide_banner", "-i"])
+        .arg(path)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await;
+    if let Some(seconds) = output.ok().and_then(|o| parse_ffmpeg_duration(&String::from_utf8_lossy(&o.stderr))) {
+        return Some(seconds);
+    }
+    if format == SampleFormat::Wav {
+        let reader = hound::WavReader::open(path).ok()?;
+        return Some(reader.duration() as f64 / reader.spec().sample_rate as f64);
+    }
+    None
+}
+
+/// Length of the pieces a recording is split into so each fits the upload limit
+fn segment_seconds(seconds: f64, bytes: u64) -> f64 {
+    // Leave headroom for container overhead and variable bitrates
+    let fitting = seconds * (MAX_SAMPLE_BYTES as f64 * 0.9) / bytes as f64;
+    fitting.min(MAX_SEGMENT_SECONDS).floor().max(MIN_SAMPLE_SECONDS)
+}
+
+/// Split `path` into pieces of `segment` seconds inside `dir`
+async fn split(ffmpeg: &str, path: &Path, dir: &Path, segment: f64) -> Result<Vec<PathBuf>, String> {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("sample");
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("mp3");
+    let prefix = format!("{}-part", stem);
+    let pattern = dir.join(format!("{}%03d.{}", prefix, extension));
+
+    let output = tokio::process::Command::new(ffmpeg)
+        .args(["-hide_banner", "-loglevel", "error", "-i"])
+        .arg(path)
+        .args(["-f", "segment", "-segment_time", &segment.to_string(), "-c", "copy"])
+        .arg(&pattern)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", ffmpeg, e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let mut pieces = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await.map_err(|e| e.to_string())?;
+    while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            pieces.push(entry.path());
+        }
+    }
+    pieces.sort();
+    Ok(pieces)
+}
+
+fn diagnostic(path: &str, issue: SampleIssue, message: String) -> SampleDiagnostic {
+    SampleDiagnostic {
+        path: path.to_string(),
+        issue,
+        message,
+    }
+}
+
+/// Check clone samples against the upload limits, splitting recordings that
+/// are too long. Returns the files to upload and the directory holding any
+/// split pieces, which must be kept until the upload is done.
+pub async fn prepare_samples(
+    ffmpeg: &str,
+    paths: &[String],
+) -> Result<(Vec<String>, Option<tempfile::TempDir>), AudioError> {
+    let mut uploads = Vec::with_capacity(paths.len());
+    let mut diagnostics = Vec::new();
+    let mut split_dir: Option<tempfile::TempDir> = None;
+
+    for path in paths {
+        let file = Path::new(path);
+        let bytes = match tokio::fs::metadata(file).await {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                diagnostics.push(diagnostic(path, SampleIssue::Unreadable, format!("{}: {}", path, e)));
+                continue;
+            }
+        };
+        let Some(format) = read_header(file).await.ok().and_then(|header| SampleFormat::sniff(&header)) else {
+            diagnostics.push(diagnostic(
+                path,
+                SampleIssue::UnsupportedCodec,
+                format!("{} is not MP3, WAV, M4A, FLAC, OGG or WebM audio", path),
+            ));
+            continue;
+        };
+
+        let seconds = probe_duration(ffmpeg, file, format).await;
+        if let Some(seconds) = seconds.filter(|s| *s < MIN_SAMPLE_SECONDS) {
+            diagnostics.push(diagnostic(
+                path,
+                SampleIssue::TooShort { seconds },
+                format!("{} is {:.1}s long; samples need at least {}s", path, seconds, MIN_SAMPLE_SECONDS),
+            ));
+            continue;
+        }
+
+        let too_long = seconds.is_some_and(|s| s > MAX_SEGMENT_SECONDS);
+        if bytes <= MAX_SAMPLE_BYTES && !too_long {
+            uploads.push(path.clone());
+            continue;
+        }
+
+        let too_large = |reason: String| {
+            diagnostic(
+                path,
+                SampleIssue::TooLarge { bytes },
+                format!(
+                    "{} is larger than {} MB and could not be split: {}",
+                    path,
+                    MAX_SAMPLE_BYTES / 1024 / 1024,
+                    reason
+                ),
+            )
+        };
+        let Some(seconds) = seconds else {
+            diagnostics.push(too_large(format!("its length is unknown (is {} installed?)", ffmpeg)));
+            continue;
+        };
+        let dir = match &split_dir {
+            Some(dir) => dir,
+            None => split_dir.insert(tempfile::tempdir()?),
+        };
+
+        let segment = segment_seconds(seconds, bytes);
+        match split(ffmpeg, file, dir.path(), segment).await {
+            Ok(mut pieces) if !pieces.is_empty() => {
+                // A trailing piece too short to upload is dropped
+                let last = seconds - segment * (pieces.len() - 1) as f64;
+                if pieces.len() > 1 && last < MIN_SAMPLE_SECONDS {
+                    pieces.pop();
+                }
+                log::info!("Split {} into {} clone samples of {}s", path, pieces.len(), segment);
+                uploads.extend(pieces.into_iter().map(|piece| piece.to_string_lossy().to_string()));
+            }
+            Ok(_) => diagnostics.push(too_large("ffmpeg produced no output".to_string())),
+            Err(e) => diagnostics.push(too_large(e)),
+        }
+    }
+
+    if !diagnostics.is_empty() {
+        return Err(AudioError::InvalidSamples(diagnostics));
+    }
+    if uploads.len() > MAX_SAMPLES {
+        return Err(AudioError::Validation(format!(
+            "{} samples to upload (after splitting long recordings); at most {} are accepted",
+            uploads.len(),
+            MAX_SAMPLES
+        )));
+    }
+    Ok((uploads, split_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_inspection() {
+        assert_eq!(SampleFormat::sniff(b"ID3\x04\0\0\0\0\0\0\0\0"), Some(SampleFormat::Mp3));
+        assert_eq!(SampleFormat::sniff(b"RIFF\x24\0\0\0WAVEfmt "), Some(SampleFormat::Wav));
+        assert_eq!(SampleFormat::sniff(b"\0\0\0\x20ftypM4A "), Some(SampleFormat::M4a));
+        assert_eq!(SampleFormat::sniff(b"<html>"), None);
+
+        let stderr = "Input #0, mp3, from 'a.mp3':\n  Duration: 00:01:02.50, start: 0.025057, bitrate: 128 kb/s";
+        assert_eq!(parse_ffmpeg_duration(stderr), Some(62.5));
+        assert_eq!(parse_ffmpeg_duration("  Duration: N/A, bitrate: N/A"), None);
+
+        // 20 minutes at 128kbps (~19 MB) is split into pieces under the limit
+        assert_eq!(segment_seconds(1200.0, 19_200_000), 180.0);
+        assert_eq!(segment_seconds(600.0, 60_000_000), 94.0);
+    }
+}
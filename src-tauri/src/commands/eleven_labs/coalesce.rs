@@ -0,0 +1,124 @@
+// Coalescing of identical concurrent requests. When a generation is requested
+// while the same one is already in flight (a double-clicked button, two
+// windows speaking the same line), the second caller waits for the first
+// provider call instead of paying for another, and both get the same record.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+use super::error::AudioError;
+
+type Outcome<T> = Option<Result<T, AudioError>>;
+
+/// Requests in flight, by key
+pub struct Coalescer<T> {
+    in_flight: Mutex<HashMap<String, watch::Receiver<Outcome<T>>>>,
+}
+
+enum Role<T> {
+    Lead(watch::Sender<Outcome<T>>),
+    Follow(watch::Receiver<Outcome<T>>),
+}
+
+impl<T: Clone> Coalescer<T> {
+    pub fn new() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `work` unless a request with the same `key` is in flight, in which
+    /// case wait for that one's result instead
+    pub async fn run<F, Fut>(&self, key: String, work: F) -> Result<T, AudioError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, AudioError>>,
+    {
+        let mut work = Some(work);
+        loop {
+            let role = {
+                let mut in_flight = self.in_flight.lock()?;
+                match in_flight.get(&key) {
+                    Some(receiver) => Role::Follow(receiver.clone()),
+                    None => {
+                        let (sender, receiver) = watch::channel(None);
+                        in_flight.insert(key.clone(), receiver);
+                        Role::Lead(sender)
+                    }
+                }
+            };
+
+            match role {
+                Role::Follow(mut receiver) => {
+                    // An error means the leading caller was cancelled; take over
+                    if let Ok(outcome) = receiver.wait_for(Option::is_some).await {
+                        return match outcome.as_ref() {
+                            Some(Ok(value)) => Ok(value.clone()),
+                            Some(Err(error)) => Err(error.to_shared()),
+                            None => unreachable!("waited for an outcome"),
+                        };
+                    }
+                }
+                Role::Lead(sender) => {
+                    let _in_flight = InFlight { coalescer: self, key: &key };
+                    let work = work.take().expect("only one request leads");
+                    let result = work().await;
+                    sender.send_replace(Some(match &result {
+                        Ok(value) => Ok(value.clone()),
+                        Err(error) => Err(error.to_shared()),
+                    }));
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+impl<T: Clone> Default for Coalescer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Removes the leading request's key when it finishes or is cancelled
+struct InFlight<'a, T> {
+    coalescer: &'a Coalescer<T>,
+    key: &'a str,
+}
+
+impl<T> Drop for InFlight<'_, T> {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.coalescer.in_flight.lock() {
+            in_flight.remove(self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_identical_requests_share_one_call() {
+        let coalescer = Coalescer::new();
+        let calls = AtomicUsize::new(0);
+        let work = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(calls.load(Ordering::SeqCst))
+        };
+
+        let (a, b) = tokio::join!(
+            coalescer.run("same".to_string(), work),
+            coalescer.run("same".to_string(), work)
+        );
+        assert_eq!((a.unwrap(), b.unwrap()), (1, 1));
+
+        // Finished requests aren't reused
+        assert_eq!(coalescer.run("same".to_string(), work).await.unwrap(), 2);
+    }
+}
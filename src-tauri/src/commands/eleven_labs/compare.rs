@@ -0,0 +1,183 @@
+// A/B comparison of voice settings: the same text and voice rendered with two
+// settings variants side by side, so stability/style can be tuned by ear
+// without generating and matching up the takes by hand. Both renders are
+// ordinary cached records; the pair is kept in `audio_comparisons`.
+
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::pipeline;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// A setting that differs between the two variants
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingChange {
+    pub field: String,
+    /// `null` when the variant leaves the setting unset
+    pub a: serde_json::Value,
+    pub b: serde_json::Value,
+}
+
+/// Two renders of the same text with different voice settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationComparison {
+    pub id: String,
+    pub audio_a: GeneratedAudio,
+    pub audio_b: GeneratedAudio,
+    pub settings_diff: Vec<SettingChange>,
+    pub created_at: String,
+}
+
+/// Settings that differ between `a` and `b`, by field name
+pub fn diff_settings(a: &VoiceSettings, b: &VoiceSettings) -> Vec<SettingChange> {
+    let (serde_json::Value::Object(a), serde_json::Value::Object(b)) =
+        (serde_json::json!(a), serde_json::json!(b))
+    else {
+        return Vec::new();
+    };
+    let fields: std::collections::BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let (a, b) = (a.get(field).cloned().unwrap_or_default(), b.get(field).cloned().unwrap_or_default());
+            (a != b).then(|| SettingChange { field: field.clone(), a, b })
+        })
+        .collect()
+}
+
+pub fn save_comparison(conn: &Connection, comparison: &GenerationComparison) -> Result<()> {
+    conn.execute(
+        "INSERT INTO audio_comparisons (id, audio_a_id, audio_b_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+        (&comparison.id, &comparison.audio_a.id, &comparison.audio_b.id, &comparison.created_at),
+    )?;
+    Ok(())
+}
+
+/// A stored comparison, or `None` if it or either of its records is gone
+pub fn get_comparison(conn: &Connection, id: &str) -> Result<Option<GenerationComparison>> {
+    let Some((a_id, b_id, created_at)) = conn
+        .query_row(
+            "SELECT audio_a_id, audio_b_id, created_at FROM audio_comparisons WHERE id = ?1",
+            [id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?)),
+        )
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    let (Some(audio_a), Some(audio_b)) =
+        (AudioCacheDb::get_audio_record(conn, &a_id)?, AudioCacheDb::get_audio_record(conn, &b_id)?)
+    else {
+        return Ok(None);
+    };
+    let settings = |audio: &GeneratedAudio| {
+        audio.params.as_ref().and_then(|p| p.voice_settings.clone()).unwrap_or_default()
+    };
+    Ok(Some(GenerationComparison {
+        id: id.to_string(),
+        settings_diff: diff_settings(&settings(&audio_a), &settings(&audio_b)),
+        audio_a,
+        audio_b,
+        created_at,
+    }))
+}
+
+/// Forget the comparisons a deleted record was part of
+pub fn forget_audio(conn: &Connection, audio_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM audio_comparisons WHERE audio_a_id = ?1 OR audio_b_id = ?1",
+        [audio_id],
+    )?;
+    Ok(())
+}
+
+/// Render `text` with both settings variants concurrently and store the pair
+pub async fn compare(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+    settings_a: VoiceSettings,
+    settings_b: VoiceSettings,
+    project_id: Option<&str>,
+) -> Result<GenerationComparison, AudioError> {
+    let settings_diff = diff_settings(&settings_a, &settings_b);
+    if settings_diff.is_empty() {
+        return Err(AudioError::Validation("Settings A and B are identical".to_string()));
+    }
+
+    let request_a = TtsRequest { voice_settings: Some(settings_a), ..request.clone() };
+    let request_b = TtsRequest { voice_settings: Some(settings_b), ..request };
+    let (audio_a, audio_b) = tokio::try_join!(
+        pipeline::generate_tts_for_project(state, request_a, project_id),
+        pipeline::generate_tts_for_project(state, request_b, project_id),
+    )?;
+
+    let comparison = GenerationComparison {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_a,
+        audio_b,
+        settings_diff,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let record = comparison.clone();
+    state.call_db(move |conn| save_comparison(conn, &record)).await?;
+    Ok(comparison)
+}
+
+/// Render the same text and voice with two voice settings variants, returning
+/// both records and the settings that differ
+#[tauri::command]
+pub async fn compare_generations(
+    state: State<'_, Arc<ElevenLabsState>>,
+    text: String,
+    voice_id: String,
+    settings_a: VoiceSettings,
+    settings_b: VoiceSettings,
+    model_id: Option<String>,
+    project_id: Option<String>,
+) -> Result<GenerationComparison, AudioError> {
+    let request = TtsRequest {
+        text,
+        voice_id,
+        model_id: model_id.unwrap_or_else(default_model_id),
+        voice_settings: None,
+        output_format: default_output_format(),
+        normalize_text: None,
+        preset: None,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
+    };
+    compare(&state, request, settings_a, settings_b, project_id.as_deref()).await
+}
+
+/// A comparison made earlier by `compare_generations`
+#[tauri::command]
+pub async fn get_generation_comparison(
+    state: State<'_, Arc<ElevenLabsState>>,
+    comparison_id: String,
+) -> Result<Option<GenerationComparison>, AudioError> {
+    state.call_db(move |conn| get_comparison(conn, &comparison_id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_settings() {
+        let a = VoiceSettings::default();
+        let b = VoiceSettings { stability: 0.3, speed: Some(1.1), ..a.clone() };
+        let diff = diff_settings(&a, &b);
+        let fields: Vec<_> = diff.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["speed", "stability"]);
+        assert_eq!(diff[0].a, serde_json::Value::Null);
+        assert!(diff_settings(&a, &a.clone()).is_empty());
+    }
+}
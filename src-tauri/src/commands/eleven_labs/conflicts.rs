@@ -0,0 +1,412 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+use uuid::Uuid;
+
+use super::cache::{AudioCacheDb, CharacterVoiceDb, SettingsDb};
+use super::error::AudioError;
+use super::sync::{RemoteAudioEntry, SyncStateDb};
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Settings key holding this machine's id in revision vectors
+pub const DEVICE_ID_KEY: &str = "sync_device_id";
+
+pub const KIND_AUDIO: &str = "audio";
+pub const KIND_CASTING: &str = "casting";
+
+/// How two revisions of the same record relate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Causality {
+    Equal,
+    /// The local revision includes every remote edit
+    Ahead,
+    /// The remote revision includes every local edit
+    Behind,
+    /// Both sides have edits the other hasn't seen
+    Concurrent,
+}
+
+/// Compare two revision vectors
+pub fn compare(local: &RevisionVector, remote: &RevisionVector) -> Causality {
+    let mut ahead = false;
+    let mut behind = false;
+    for device in local.keys().chain(remote.keys()) {
+        let l = local.get(device).copied().unwrap_or(0);
+        let r = remote.get(device).copied().unwrap_or(0);
+        if l > r {
+            ahead = true;
+        } else if l < r {
+            behind = true;
+        }
+    }
+
+    match (ahead, behind) {
+        (false, false) => Causality::Equal,
+        (true, false) => Causality::Ahead,
+        (false, true) => Causality::Behind,
+        (true, true) => Causality::Concurrent,
+    }
+}
+
+/// Element-wise maximum of two revision vectors
+pub fn merge(a: &RevisionVector, b: &RevisionVector) -> RevisionVector {
+    let mut merged = a.clone();
+    for (device, &counter) in b {
+        let entry = merged.entry(device.clone()).or_insert(0);
+        *entry = (*entry).max(counter);
+    }
+    merged
+}
+
+fn bump(revision: &mut RevisionVector, device_id: &str) {
+    *revision.entry(device_id.to_string()).or_insert(0) += 1;
+}
+
+fn hash_json(value: &serde_json::Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(value.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hash of the synced fields of an audio record. The local path differs per machine
+/// and is left out.
+pub fn audio_fingerprint(record: &GeneratedAudio, content_hash: &str) -> String {
+    hash_json(&serde_json::json!({
+        "content_hash": content_hash,
+        "audio_type": record.audio_type,
+        "prompt": record.prompt,
+        "duration_seconds": record.duration_seconds,
+        "supabase_url": record.supabase_url,
+        "metadata": record.metadata,
+    }))
+}
+
+/// Hash of the synced fields of a casting entry
+pub fn casting_fingerprint(mapping: &CharacterVoice) -> String {
+    hash_json(&serde_json::json!({
+        "voice_id": mapping.voice_id,
+        "voice_name": mapping.voice_name,
+    }))
+}
+
+/// Casting entries get a fresh id on every machine, so they are matched by
+/// project and character instead
+pub fn casting_key(mapping: &CharacterVoice) -> String {
+    format!(
+        "{}/{}",
+        mapping.project_id.as_deref().unwrap_or(""),
+        mapping.character_name.to_lowercase()
+    )
+}
+
+/// This machine's id, created on first use
+pub fn device_id(conn: &Connection) -> Result<String> {
+    if let Some(id) = SettingsDb::get_setting(conn, DEVICE_ID_KEY)? {
+        return Ok(id);
+    }
+    let id = Uuid::new_v4().to_string();
+    SettingsDb::save_setting(conn, DEVICE_ID_KEY, &id)?;
+    Ok(id)
+}
+
+/// Revision vectors of local records, with the fingerprint they were taken at
+pub struct SyncRevisionDb;
+
+impl SyncRevisionDb {
+    pub fn get(conn: &Connection, kind: &str, record_id: &str) -> Result<Option<(RevisionVector, String)>> {
+        let mut stmt = conn.prepare(
+            "SELECT revision, fingerprint FROM sync_revisions WHERE kind = ?1 AND record_id = ?2",
+        )?;
+        let mut rows = stmt.query(params![kind, record_id])?;
+
+        if let Some(row) = rows.next()? {
+            let revision: String = row.get(0)?;
+            Ok(Some((serde_json::from_str(&revision)?, row.get(1)?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set(
+        conn: &Connection,
+        kind: &str,
+        record_id: &str,
+        revision: &RevisionVector,
+        fingerprint: &str,
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_revisions (kind, record_id, revision, fingerprint)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![kind, record_id, serde_json::to_string(revision)?, fingerprint],
+        )?;
+        Ok(())
+    }
+
+    /// Current revision of a local record. Counts a local edit when the record no
+    /// longer matches the fingerprint its revision was recorded at.
+    pub fn current(
+        conn: &Connection,
+        device_id: &str,
+        kind: &str,
+        record_id: &str,
+        fingerprint: &str,
+    ) -> Result<RevisionVector> {
+        let mut revision = match Self::get(conn, kind, record_id)? {
+            Some((revision, seen)) if seen == fingerprint => return Ok(revision),
+            Some((revision, _)) => revision,
+            None => RevisionVector::new(),
+        };
+        bump(&mut revision, device_id);
+        Self::set(conn, kind, record_id, &revision, fingerprint)?;
+        Ok(revision)
+    }
+}
+
+/// Unresolved sync conflicts
+pub struct SyncConflictDb;
+
+impl SyncConflictDb {
+    const COLUMNS: &'static str =
+        "id, kind, record_id, backend, local, remote, local_revision, remote_revision, detected_at";
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<SyncConflict> {
+        fn json<T: serde::de::DeserializeOwned>(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<T> {
+            let text: String = row.get(idx)?;
+            serde_json::from_str(&text).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+            })
+        }
+
+        Ok(SyncConflict {
+            id: row.get(0)?,
+            kind: row.get(1)?,
+            record_id: row.get(2)?,
+            backend: row.get(3)?,
+            local: json(row, 4)?,
+            remote: json(row, 5)?,
+            local_revision: json(row, 6)?,
+            remote_revision: json(row, 7)?,
+            detected_at: row.get(8)?,
+        })
+    }
+
+    /// Store a conflict, replacing an older one for the same record and backend
+    pub fn save(conn: &Connection, conflict: &SyncConflict) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_conflicts
+             (id, kind, record_id, backend, local, remote, local_revision, remote_revision, detected_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                conflict.id,
+                conflict.kind,
+                conflict.record_id,
+                conflict.backend,
+                serde_json::to_string(&conflict.local)?,
+                serde_json::to_string(&conflict.remote)?,
+                serde_json::to_string(&conflict.local_revision)?,
+                serde_json::to_string(&conflict.remote_revision)?,
+                conflict.detected_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn find(conn: &Connection, kind: &str, record_id: &str, backend: &str) -> Result<Option<SyncConflict>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM sync_conflicts WHERE kind = ?1 AND record_id = ?2 AND backend = ?3",
+            Self::COLUMNS
+        ))?;
+        let mut rows = stmt.query(params![kind, record_id, backend])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(Self::from_row(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn get(conn: &Connection, id: &str) -> Result<Option<SyncConflict>> {
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM sync_conflicts WHERE id = ?1", Self::COLUMNS))?;
+        let mut rows = stmt.query([id])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(Self::from_row(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn list(conn: &Connection) -> Result<Vec<SyncConflict>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM sync_conflicts ORDER BY detected_at",
+            Self::COLUMNS
+        ))?;
+        let rows = stmt.query_map([], Self::from_row)?;
+
+        let mut conflicts = vec![];
+        for row in rows {
+            conflicts.push(row?);
+        }
+        Ok(conflicts)
+    }
+
+    pub fn delete(conn: &Connection, id: &str) -> Result<()> {
+        conn.execute("DELETE FROM sync_conflicts WHERE id = ?1", [id])?;
+        Ok(())
+    }
+}
+
+/// How to settle a conflict
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// Discard the remote edit; the local version is pushed on the next backup
+    KeepLocal,
+    /// Replace the local version with the remote one
+    KeepRemote,
+    /// Keep the local version and import the remote one as a new take (audio only)
+    KeepBoth,
+}
+
+fn remove_file_if_exists(path: &str) {
+    if !path.is_empty() && Path::new(path).exists() {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Failed to remove {}: {}", path, e);
+        }
+    }
+}
+
+/// Apply a resolution. The resulting revision supersedes both sides, so the choice
+/// propagates to other machines on the next sync instead of conflicting again.
+pub fn resolve_conflict(conn: &mut Connection, conflict: &SyncConflict, strategy: ConflictStrategy) -> Result<()> {
+    let device_id = device_id(conn)?;
+    let mut revision = merge(&conflict.local_revision, &conflict.remote_revision);
+    bump(&mut revision, &device_id);
+
+    let local_fingerprint = SyncRevisionDb::get(conn, &conflict.kind, &conflict.record_id)?
+        .map(|(_, fingerprint)| fingerprint)
+        .unwrap_or_default();
+
+    let tx = conn.transaction()?;
+    match conflict.kind.as_str() {
+        KIND_AUDIO => {
+            let remote: RemoteAudioEntry = serde_json::from_value(conflict.remote.clone())?;
+            match strategy {
+                ConflictStrategy::KeepLocal => {
+                    SyncRevisionDb::set(&tx, KIND_AUDIO, &conflict.record_id, &revision, &local_fingerprint)?;
+                    tx.commit()?;
+                    remove_file_if_exists(&remote.record.local_path);
+                }
+                ConflictStrategy::KeepRemote => {
+                    let previous = AudioCacheDb::get_audio_record(&tx, &conflict.record_id)?;
+                    AudioCacheDb::save_audio_record(&tx, &remote.record)?;
+                    SyncStateDb::mark_synced(&tx, &remote.record.id, &conflict.backend, &remote.content_hash)?;
+                    let fingerprint = audio_fingerprint(&remote.record, &remote.content_hash);
+                    SyncRevisionDb::set(&tx, KIND_AUDIO, &conflict.record_id, &revision, &fingerprint)?;
+                    tx.commit()?;
+                    if let Some(previous) = previous.filter(|p| p.local_path != remote.record.local_path) {
+                        remove_file_if_exists(&previous.local_path);
+                    }
+                }
+                ConflictStrategy::KeepBoth => {
+                    let mut copy = remote.record;
+                    copy.id = Uuid::new_v4().to_string();
+                    match copy.metadata.as_object_mut() {
+                        Some(metadata) => {
+                            metadata.insert("conflict_of".to_string(), conflict.record_id.clone().into());
+                        }
+                        None => copy.metadata = serde_json::json!({ "conflict_of": conflict.record_id }),
+                    }
+                    AudioCacheDb::save_audio_record(&tx, &copy)?;
+                    SyncRevisionDb::set(&tx, KIND_AUDIO, &conflict.record_id, &revision, &local_fingerprint)?;
+                    tx.commit()?;
+                }
+            }
+        }
+        KIND_CASTING => {
+            let remote: CharacterVoice = serde_json::from_value(conflict.remote.clone())?;
+            match strategy {
+                ConflictStrategy::KeepLocal => {
+                    SyncRevisionDb::set(&tx, KIND_CASTING, &conflict.record_id, &revision, &local_fingerprint)?;
+                }
+                ConflictStrategy::KeepRemote => {
+                    let mut mapping = CharacterVoiceDb::get_character_voices(&tx, remote.project_id.as_deref())?
+                        .into_iter()
+                        .find(|m| casting_key(m) == conflict.record_id)
+                        .unwrap_or_else(|| remote.clone());
+                    mapping.voice_id = remote.voice_id;
+                    mapping.voice_name = remote.voice_name;
+                    CharacterVoiceDb::save_mapping(&tx, &mapping)?;
+                    SyncRevisionDb::set(
+                        &tx,
+                        KIND_CASTING,
+                        &conflict.record_id,
+                        &revision,
+                        &casting_fingerprint(&mapping),
+                    )?;
+                }
+                ConflictStrategy::KeepBoth => {
+                    return Err(anyhow!("A character has a single voice; choose keep_local or keep_remote"));
+                }
+            }
+            tx.commit()?;
+        }
+        other => return Err(anyhow!("Unknown conflict kind: {}", other)),
+    }
+
+    SyncConflictDb::delete(conn, &conflict.id)?;
+    Ok(())
+}
+
+// ========== Tauri Commands ==========
+
+/// List records edited on two machines that need a decision
+#[tauri::command]
+pub async fn list_sync_conflicts(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<SyncConflict>, AudioError> {
+    state.call_db(|conn| SyncConflictDb::list(conn)).await
+}
+
+/// Resolve a sync conflict with `keep_local`, `keep_remote` or `keep_both`
+#[tauri::command]
+pub async fn resolve_sync_conflict(
+    state: State<'_, Arc<ElevenLabsState>>,
+    id: String,
+    strategy: ConflictStrategy,
+) -> Result<SyncConflict, AudioError> {
+    state
+        .call_db(move |conn| {
+            let conflict = SyncConflictDb::get(conn, &id)?
+                .ok_or_else(|| anyhow!("Sync conflict not found: {}", id))?;
+            resolve_conflict(conn, &conflict, strategy)?;
+            Ok(conflict)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rev(counters: &[(&str, u64)]) -> RevisionVector {
+        counters.iter().map(|(d, c)| (d.to_string(), *c)).collect()
+    }
+
+    #[test]
+    fn test_compare_revisions() {
+        let a1 = rev(&[("a", 1)]);
+        let a2b1 = rev(&[("a", 2), ("b", 1)]);
+        let b1 = rev(&[("b", 1)]);
+
+        assert_eq!(compare(&a1, &a1), Causality::Equal);
+        assert_eq!(compare(&a2b1, &a1), Causality::Ahead);
+        assert_eq!(compare(&a1, &a2b1), Causality::Behind);
+        assert_eq!(compare(&a1, &b1), Causality::Concurrent);
+        assert_eq!(compare(&a1, &RevisionVector::new()), Causality::Ahead);
+        assert_eq!(merge(&a1, &b1), rev(&[("a", 1), ("b", 1)]));
+    }
+}
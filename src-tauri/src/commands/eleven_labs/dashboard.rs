@@ -0,0 +1,238 @@
+// Combined usage data for the usage chart: Claude token usage from the session
+// transcripts (split into agent runs and interactive sessions via the agents
+// DB) alongside audio generation from the library, bucketed over time.
+
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDateTime, Timelike};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::types::*;
+use super::ElevenLabsState;
+use crate::commands::usage::get_all_usage_entries;
+
+/// Estimated price of 1,000 characters of speech, in USD
+const AUDIO_PRICE_PER_1K_CHARACTERS: f64 = 0.30;
+
+/// Characters billed per second of generated sound effects and music
+const CHARACTERS_PER_GENERATED_SECOND: f64 = 40.0;
+
+/// Range covered by the dashboard and the size of its buckets
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsagePeriod {
+    /// Last 24 hours, hourly buckets
+    Day,
+    /// Last 7 days, daily buckets
+    Week,
+    /// Last 30 days, daily buckets
+    Month,
+    /// Last 12 months, monthly buckets
+    Year,
+}
+
+impl UsagePeriod {
+    /// Start of the bucket containing `time`
+    fn bucket_start(&self, time: NaiveDateTime) -> NaiveDateTime {
+        let hour = time.date().and_hms_opt(time.hour(), 0, 0).unwrap_or(time);
+        let day = time.date().and_hms_opt(0, 0, 0).unwrap_or(time);
+        match self {
+            UsagePeriod::Day => hour,
+            UsagePeriod::Week | UsagePeriod::Month => day,
+            UsagePeriod::Year => day.with_day0(0).unwrap_or(day),
+        }
+    }
+
+    /// Bucket starts in chronological order, ending with the bucket containing `now`
+    fn bucket_starts(&self, now: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let current = self.bucket_start(now);
+        let mut starts: Vec<NaiveDateTime> = match self {
+            UsagePeriod::Day => (0..24).map(|i| current - Duration::hours(i)).collect(),
+            UsagePeriod::Week => (0..7).map(|i| current - Duration::days(i)).collect(),
+            UsagePeriod::Month => (0..30).map(|i| current - Duration::days(i)).collect(),
+            UsagePeriod::Year => (0..12)
+                .filter_map(|i| current.checked_sub_months(Months::new(i)))
+                .collect(),
+        };
+        starts.reverse();
+        starts
+    }
+}
+
+/// Usage within one time bucket. Agent figures are the part of the Claude
+/// figures that came from agent runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageBucket {
+    /// Local start time of the bucket
+    pub start: String,
+    pub claude_tokens: u64,
+    pub claude_cost: f64,
+    pub agent_tokens: u64,
+    pub agent_cost: f64,
+    pub audio_characters: u64,
+    pub audio_clips: u64,
+    /// Estimate based on list pricing
+    pub audio_cost: f64,
+}
+
+impl UsageBucket {
+    fn add(&mut self, other: &UsageBucket) {
+        self.claude_tokens += other.claude_tokens;
+        self.claude_cost += other.claude_cost;
+        self.agent_tokens += other.agent_tokens;
+        self.agent_cost += other.agent_cost;
+        self.audio_characters += other.audio_characters;
+        self.audio_clips += other.audio_clips;
+        self.audio_cost += other.audio_cost;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageDashboard {
+    pub period: UsagePeriod,
+    pub buckets: Vec<UsageBucket>,
+    pub totals: UsageBucket,
+}
+
+/// Billable characters for a library clip
+fn audio_characters(audio: &GeneratedAudio) -> u64 {
+    match audio.audio_type {
+        AudioType::Tts => audio.text_characters() as u64,
+        AudioType::Sfx | AudioType::Music => {
+            (audio.duration_seconds as f64 * CHARACTERS_PER_GENERATED_SECOND).round() as u64
+        }
+    }
+}
+
+/// Session IDs of agent runs
+fn agent_session_ids(conn: &Connection) -> anyhow::Result<HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT session_id FROM agent_runs WHERE session_id != ''")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Library clips across all audio types
+fn audio_records(conn: &Connection) -> anyhow::Result<Vec<GeneratedAudio>> {
+    let mut records = vec![];
+    for audio_type in [AudioType::Tts, AudioType::Sfx, AudioType::Music] {
+        records.extend(AudioCacheDb::get_audio_records(conn, &audio_type)?);
+    }
+    Ok(records)
+}
+
+/// Buckets being filled for a dashboard
+struct UsageTimeline {
+    period: UsagePeriod,
+    starts: Vec<NaiveDateTime>,
+    buckets: Vec<UsageBucket>,
+}
+
+impl UsageTimeline {
+    /// Empty buckets covering `period` up to `now`
+    fn new(period: UsagePeriod, now: NaiveDateTime) -> Self {
+        let starts = period.bucket_starts(now);
+        let buckets = starts
+            .iter()
+            .map(|start| UsageBucket {
+                start: start.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                ..Default::default()
+            })
+            .collect();
+        Self { period, starts, buckets }
+    }
+
+    /// The bucket containing an RFC 3339 timestamp, if it falls within the period
+    fn bucket_mut(&mut self, timestamp: &str) -> Option<&mut UsageBucket> {
+        let time = DateTime::parse_from_rfc3339(timestamp)
+            .ok()?
+            .with_timezone(&Local)
+            .naive_local();
+        let index = self.starts.binary_search(&self.period.bucket_start(time)).ok()?;
+        self.buckets.get_mut(index)
+    }
+
+    fn finish(self) -> UsageDashboard {
+        let mut totals = UsageBucket {
+            start: self.buckets.first().map(|bucket| bucket.start.clone()).unwrap_or_default(),
+            ..Default::default()
+        };
+        for bucket in &self.buckets {
+            totals.add(bucket);
+        }
+        UsageDashboard {
+            period: self.period,
+            buckets: self.buckets,
+            totals,
+        }
+    }
+}
+
+// ========== Tauri Commands ==========
+
+/// Claude token usage and audio generation for `period`, bucketed for a combined chart
+#[tauri::command]
+pub async fn get_usage_dashboard(
+    state: State<'_, Arc<ElevenLabsState>>,
+    period: UsagePeriod,
+) -> Result<UsageDashboard, AudioError> {
+    let claude_path = dirs::home_dir()
+        .ok_or("Failed to get home directory")?
+        .join(".claude");
+    let entries = get_all_usage_entries(&claude_path);
+    let (agent_sessions, audio) = state
+        .call_db(|conn| Ok((agent_session_ids(conn)?, audio_records(conn)?)))
+        .await?;
+
+    let mut timeline = UsageTimeline::new(period, Local::now().naive_local());
+    for entry in &entries {
+        let Some(bucket) = timeline.bucket_mut(&entry.timestamp) else { continue };
+        let tokens = entry.input_tokens + entry.output_tokens + entry.cache_creation_tokens + entry.cache_read_tokens;
+        bucket.claude_tokens += tokens;
+        bucket.claude_cost += entry.cost;
+        if agent_sessions.contains(&entry.session_id) {
+            bucket.agent_tokens += tokens;
+            bucket.agent_cost += entry.cost;
+        }
+    }
+
+    for clip in &audio {
+        let Some(bucket) = timeline.bucket_mut(&clip.created_at) else { continue };
+        let characters = audio_characters(clip);
+        bucket.audio_clips += 1;
+        bucket.audio_characters += characters;
+        bucket.audio_cost += characters as f64 / 1000.0 * AUDIO_PRICE_PER_1K_CHARACTERS;
+    }
+
+    Ok(timeline.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_buckets() {
+        let now = NaiveDateTime::parse_from_str("2025-03-15 10:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+
+        let year = UsagePeriod::Year.bucket_starts(now);
+        assert_eq!(year.len(), 12);
+        assert_eq!(year[0].to_string(), "2024-04-01 00:00:00");
+        assert_eq!(year[11].to_string(), "2025-03-01 00:00:00");
+
+        let mut timeline = UsageTimeline::new(UsagePeriod::Week, now);
+        let yesterday = (now - Duration::days(1)).and_local_timezone(Local).unwrap().to_rfc3339();
+        let last_week = (now - Duration::days(8)).and_local_timezone(Local).unwrap().to_rfc3339();
+        timeline.bucket_mut(&yesterday).unwrap().claude_tokens += 5;
+        assert!(timeline.bucket_mut(&last_week).is_none());
+
+        let dashboard = timeline.finish();
+        assert_eq!(dashboard.buckets.len(), 7);
+        assert_eq!(dashboard.buckets[5].start, "2025-03-14T00:00:00");
+        assert_eq!(dashboard.buckets[5].claude_tokens, 5);
+        assert_eq!(dashboard.totals.claude_tokens, 5);
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::types::*;
+use super::{ensure_cache, ElevenLabsState};
+
+/// Silence left between consecutive takes on the timeline, in seconds
+const TAKE_GAP_SECONDS: f64 = 0.25;
+
+/// Session formats that can be exported
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DawFormat {
+    /// Reaper project (`.rpp`), one track per character
+    Reaper,
+    /// Audacity List Of Files (`.lof`). AUP3 projects are opaque SQLite
+    /// containers, so Audacity gets the import list it can open directly.
+    Audacity,
+}
+
+impl DawFormat {
+    pub fn parse(value: &str) -> Result<Self, AudioError> {
+        match value.to_lowercase().as_str() {
+            "reaper" | "rpp" => Ok(DawFormat::Reaper),
+            "audacity" | "lof" | "aup3" => Ok(DawFormat::Audacity),
+            other => Err(AudioError::Validation(format!("Unsupported DAW format: {}", other))),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            DawFormat::Reaper => "rpp",
+            DawFormat::Audacity => "lof",
+        }
+    }
+}
+
+/// A take placed on the session timeline
+#[derive(Debug, Clone)]
+pub struct TimelineItem {
+    pub track: String,
+    pub name: String,
+    pub file: String,
+    pub position: f64,
+    pub length: f64,
+}
+
+/// Lay takes out back to back, each on the track of its character
+pub fn layout_timeline(takes: &[GeneratedAudio]) -> Vec<TimelineItem> {
+    let mut position = 0.0;
+    takes
+        .iter()
+        .map(|take| {
+            let track = take
+                .metadata
+                .get("character")
+                .and_then(|c| c.as_str())
+                .unwrap_or("Narrator")
+                .to_string();
+            let length = f64::from(take.duration_seconds.max(0.1));
+            let item = TimelineItem {
+                track,
+                name: take.prompt.chars().take(60).collect(),
+                file: take.local_path.clone(),
+                position,
+                length,
+            };
+            position += length + TAKE_GAP_SECONDS;
+            item
+        })
+        .collect()
+}
+
+fn rpp_string(value: &str) -> String {
+    // RPP strings can't contain double quotes; Reaper itself swaps to single quotes
+    format!("\"{}\"", value.replace('"', "'").replace(['\n', '\r'], " "))
+}
+
+pub fn render_reaper(items: &[TimelineItem]) -> String {
+    let mut tracks: Vec<&str> = vec![];
+    for item in items {
+        if !tracks.contains(&item.track.as_str()) {
+            tracks.push(&item.track);
+        }
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "<REAPER_PROJECT 0.1 \"6.0\" {}", chrono::Utc::now().timestamp());
+    let _ = writeln!(out, "  TEMPO 120 4 4");
+    for track in tracks {
+        let _ = writeln!(out, "  <TRACK");
+        let _ = writeln!(out, "    NAME {}", rpp_string(track));
+        for item in items.iter().filter(|i| i.track == track) {
+            let source = if item.file.to_lowercase().ends_with(".wav") { "WAVE" } else { "MP3" };
+            let _ = writeln!(out, "    <ITEM");
+            let _ = writeln!(out, "      POSITION {:.3}", item.position);
+            let _ = writeln!(out, "      LENGTH {:.3}", item.length);
+            let _ = writeln!(out, "      NAME {}", rpp_string(&item.name));
+            let _ = writeln!(out, "      <SOURCE {}", source);
+            let _ = writeln!(out, "        FILE {}", rpp_string(&item.file));
+            let _ = writeln!(out, "      >");
+            let _ = writeln!(out, "    >");
+        }
+        let _ = writeln!(out, "  >");
+    }
+    let _ = writeln!(out, ">");
+    out
+}
+
+pub fn render_audacity(items: &[TimelineItem]) -> String {
+    items
+        .iter()
+        .map(|item| format!("file \"{}\" offset {:.3}\n", item.file, item.position))
+        .collect()
+}
+
+// ========== Tauri Commands ==========
+
+/// Write a DAW session referencing a scene's takes laid out on the timeline.
+/// Returns the path of the written session file.
+#[tauri::command]
+pub async fn export_daw_session(
+    state: State<'_, Arc<ElevenLabsState>>,
+    scene_id: String,
+    format: String,
+    destination: Option<String>,
+) -> Result<String, AudioError> {
+    let format = DawFormat::parse(&format)?;
+
+    let scene = scene_id.clone();
+    let takes = state.call_db(move |conn| AudioCacheDb::get_scene_takes(conn, &scene)).await?;
+    if takes.is_empty() {
+        return Err(AudioError::Validation(format!("No takes found for scene {}", scene_id)));
+    }
+
+    let items = layout_timeline(&takes);
+    let session = match format {
+        DawFormat::Reaper => render_reaper(&items),
+        DawFormat::Audacity => render_audacity(&items),
+    };
+
+    let path = match destination {
+        Some(destination) => PathBuf::from(destination),
+        None => {
+            let cache = ensure_cache(&state).await?;
+            let safe_id: String = scene_id
+                .chars()
+                .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+                .collect();
+            cache
+                .cache_dir()
+                .join("exports")
+                .join(format!("{}.{}", safe_id, format.extension()))
+        }
+    };
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, session)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(character: &str, duration: f32) -> GeneratedAudio {
+        GeneratedAudio {
+            id: uuid::Uuid::new_v4().to_string(),
+            audio_type: AudioType::Tts,
+            prompt: format!("{} says \"hi\"", character),
+            duration_seconds: duration,
+            local_path: format!("/tmp/{}.mp3", character),
+            supabase_url: None,
+            metadata: serde_json::json!({ "character": character }),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            params: None,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_reaper_layout() {
+        let items = layout_timeline(&[take("ALICE", 2.0), take("BOB", 1.5), take("ALICE", 1.0)]);
+        assert_eq!(items[1].position, 2.25);
+        assert_eq!(items[2].position, 4.0);
+
+        let rpp = render_reaper(&items);
+        assert_eq!(rpp.matches("<TRACK").count(), 2);
+        assert_eq!(rpp.matches("<ITEM").count(), 3);
+        assert!(rpp.contains("NAME \"ALICE says 'hi'\""));
+    }
+}
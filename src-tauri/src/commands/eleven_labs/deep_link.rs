@@ -0,0 +1,177 @@
+use reqwest::Url;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::pipeline;
+use super::types::{default_model_id, GeneratedAudio};
+use super::ElevenLabsState;
+
+/// URL scheme registered for the app
+pub const SCHEME: &str = "opcode";
+
+/// Actions reachable through `opcode://` links
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLinkAction {
+    /// `opcode://tts?voice=Rachel&text=...[&model=...]`
+    Tts {
+        voice: String,
+        text: String,
+        model: Option<String>,
+    },
+    /// `opcode://open-audio/<id>`
+    OpenAudio { id: String },
+}
+
+/// Speech a `tts` link asks for. Any page can open such a link, so nothing is
+/// generated until the user confirms it in the frontend, which then calls
+/// `eleven_labs_tts` with these arguments.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeepLinkTts {
+    pub text: String,
+    pub voice_id: String,
+    pub model_id: String,
+}
+
+/// Payload emitted to the frontend once a deep link has been handled
+#[derive(Debug, Clone, Serialize)]
+pub struct DeepLinkEvent {
+    pub url: String,
+    pub action: String,
+    /// The audio an `open-audio` link points at
+    pub audio: Option<GeneratedAudio>,
+    /// Speech waiting for the user's confirmation
+    pub tts: Option<DeepLinkTts>,
+    pub error: Option<String>,
+}
+
+impl DeepLinkAction {
+    pub fn parse(raw: &str) -> Result<Self, AudioError> {
+        let url = Url::parse(raw).map_err(|e| AudioError::Validation(format!("Invalid deep link: {}", e)))?;
+        if url.scheme() != SCHEME {
+            return Err(AudioError::Validation(format!("Unsupported scheme: {}", url.scheme())));
+        }
+
+        let query = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+                .filter(|value| !value.is_empty())
+        };
+        let required = |name: &str| {
+            query(name).ok_or_else(|| AudioError::Validation(format!("Missing '{}' parameter", name)))
+        };
+
+        match url.host_str().unwrap_or("") {
+            "tts" => Ok(DeepLinkAction::Tts {
+                voice: required("voice")?,
+                text: required("text")?,
+                model: query("model"),
+            }),
+            "open-audio" => {
+                let id = url.path().trim_matches('/');
+                if id.is_empty() || id.contains('/') {
+                    return Err(AudioError::Validation("Expected opcode://open-audio/<id>".to_string()));
+                }
+                Ok(DeepLinkAction::OpenAudio { id: id.to_string() })
+            }
+            other => Err(AudioError::Validation(format!("Unknown deep link action: {}", other))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            DeepLinkAction::Tts { .. } => "tts",
+            DeepLinkAction::OpenAudio { .. } => "open-audio",
+        }
+    }
+}
+
+/// Run the action of a link, filling in its event. Speech is only prepared for
+/// confirmation, never generated here.
+async fn run_action(app: &AppHandle, action: DeepLinkAction, event: &mut DeepLinkEvent) -> Result<(), AudioError> {
+    let state = app.state::<Arc<ElevenLabsState>>();
+    match action {
+        DeepLinkAction::Tts { voice, text, model } => {
+            let voice_id = pipeline::resolve_voice_id(&state.db()?, &voice).await?;
+            event.tts = Some(DeepLinkTts {
+                text,
+                voice_id,
+                model_id: model.unwrap_or_else(default_model_id),
+            });
+        }
+        DeepLinkAction::OpenAudio { id } => {
+            let audio_id = id.clone();
+            let audio = state
+                .call_db(move |conn| AudioCacheDb::get_audio_record(conn, &audio_id))
+                .await?
+                .ok_or_else(|| AudioError::Validation(format!("Audio not found: {}", id)))?;
+            event.audio = Some(audio);
+        }
+    }
+    Ok(())
+}
+
+/// Handle an incoming `opcode://` URL: bring the main window forward, run the action
+/// and report the outcome to the frontend via the `audio-deep-link` event
+pub fn handle_url(app: &AppHandle, raw: String) {
+    log::info!("Handling deep link: {}", raw);
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut event = DeepLinkEvent {
+            url: raw,
+            action: "unknown".to_string(),
+            audio: None,
+            tts: None,
+            error: None,
+        };
+        let handled = match DeepLinkAction::parse(&event.url) {
+            Ok(action) => {
+                event.action = action.name().to_string();
+                run_action(&app, action, &mut event).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = handled {
+            event.error = Some(e.to_string());
+        }
+
+        if let Some(error) = &event.error {
+            log::warn!("Deep link {} failed: {}", event.url, error);
+        }
+        let _ = app.emit("audio-deep-link", &event);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deep_links() {
+        assert_eq!(
+            DeepLinkAction::parse("opcode://tts?voice=Rachel&text=Hello%20world").unwrap(),
+            DeepLinkAction::Tts {
+                voice: "Rachel".to_string(),
+                text: "Hello world".to_string(),
+                model: None,
+            }
+        );
+        assert_eq!(
+            DeepLinkAction::parse("opcode://open-audio/abc-123").unwrap(),
+            DeepLinkAction::OpenAudio { id: "abc-123".to_string() }
+        );
+        let missing = DeepLinkAction::parse("opcode://tts?voice=Rachel").unwrap_err();
+        assert_eq!(missing.code(), "validation");
+        assert!(DeepLinkAction::parse("https://tts?voice=a&text=b").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+// Progress of a generation's download from the provider, for long renders
+// where the UI shows a progress bar rather than playing the audio as it
+// arrives (see `audio_channel` for that).
+
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+
+use super::error::AudioError;
+use super::pipeline::{self, ProgressTap};
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Emitted with a `DownloadProgress` as each chunk is written to the cache
+pub const DOWNLOAD_PROGRESS_EVENT: &str = "audio-download-progress";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+    /// The id the caller gave the download
+    pub download_id: String,
+    pub received_bytes: u64,
+    /// Total size announced by the provider, if any
+    pub expected_bytes: Option<u64>,
+}
+
+/// A progress tap emitting `DOWNLOAD_PROGRESS_EVENT`s for `download_id`
+fn event_progress(app: AppHandle, download_id: String) -> ProgressTap {
+    Box::new(move |received_bytes, expected_bytes| {
+        let progress = DownloadProgress { download_id: download_id.clone(), received_bytes, expected_bytes };
+        let _ = app.emit(DOWNLOAD_PROGRESS_EVENT, progress);
+    })
+}
+
+/// Generate text-to-speech like `eleven_labs_tts`, emitting
+/// `audio-download-progress` events tagged with `download_id` while the audio
+/// is written to the cache
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn eleven_labs_tts_with_progress(
+    app: AppHandle,
+    state: State<'_, Arc<ElevenLabsState>>,
+    download_id: String,
+    text: String,
+    voice_id: String,
+    model_id: Option<String>,
+    voice_settings: Option<VoiceSettings>,
+    project_id: Option<String>,
+    normalize_text: Option<bool>,
+    preset: Option<String>,
+) -> Result<GeneratedAudio, AudioError> {
+    let request = TtsRequest {
+        text,
+        voice_id,
+        model_id: model_id.unwrap_or_else(default_model_id),
+        voice_settings,
+        output_format: default_output_format(),
+        normalize_text,
+        preset,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
+    };
+    let progress = event_progress(app, download_id);
+    pipeline::generate_tts_with_progress(&state, request, project_id.as_deref(), progress).await
+}
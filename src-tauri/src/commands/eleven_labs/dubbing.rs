@@ -0,0 +1,377 @@
+// Dubbing of video and audio files into another language. The provider works
+// on a submitted file for anything from seconds to many minutes, so the job is
+// recorded locally and polled in the background; once dubbed, the result is
+// downloaded into the audio cache like any other generation.
+
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::auth;
+use super::cache::{estimate_duration_seconds, AudioCacheDb};
+use super::error::AudioError;
+use super::types::*;
+use super::{ensure_cache, get_client, ElevenLabsState};
+
+/// Emitted with the `DubbingJob` after each status check of a running job
+pub const DUBBING_PROGRESS_EVENT: &str = "audio-dubbing-progress";
+
+/// How often a running job is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Failed status checks in a row after which polling gives up; the job can
+/// still be checked again with `refresh_dubbing_job`
+const MAX_POLL_FAILURES: u32 = 5;
+
+/// Source extensions dubbed into a video rather than an audio file
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "webm", "mkv", "avi"];
+
+/// After this long a download claim is taken to be from a run of the app that
+/// exited mid-download, and the job can be claimed again
+const STALE_DOWNLOAD: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DubbingStatus {
+    /// Submitted; the provider is still working on it
+    Dubbing,
+    /// Dubbed; the result is being downloaded
+    Downloading,
+    /// Dubbed and downloaded into the cache
+    Completed,
+    Failed,
+}
+
+impl DubbingStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DubbingStatus::Dubbing => "dubbing",
+            DubbingStatus::Downloading => "downloading",
+            DubbingStatus::Completed => "completed",
+            DubbingStatus::Failed => "failed",
+        }
+    }
+
+    /// Completed or failed; nothing more will happen to the job
+    pub fn is_finished(&self) -> bool {
+        matches!(self, DubbingStatus::Completed | DubbingStatus::Failed)
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "downloading" => DubbingStatus::Downloading,
+            "completed" => DubbingStatus::Completed,
+            "failed" => DubbingStatus::Failed,
+            _ => DubbingStatus::Dubbing,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DubbingJob {
+    pub id: String,
+    /// The provider's id of the dubbing project
+    pub dubbing_id: String,
+    pub source_path: String,
+    #[serde(default)]
+    pub source_lang: Option<String>,
+    pub target_lang: String,
+    pub status: DubbingStatus,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Rough time the provider expects the dub to take, for progress display
+    #[serde(default)]
+    pub expected_duration_seconds: Option<f32>,
+    /// The downloaded dub, once completed
+    #[serde(default)]
+    pub audio_id: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+const JOB_COLUMNS: &str = "id, dubbing_id, source_path, source_lang, target_lang, status, error,
+    expected_duration_seconds, audio_id, project_id, created_at, updated_at";
+
+fn job_from_row(row: &rusqlite::Row) -> rusqlite::Result<DubbingJob> {
+    let status: String = row.get(5)?;
+    Ok(DubbingJob {
+        id: row.get(0)?,
+        dubbing_id: row.get(1)?,
+        source_path: row.get(2)?,
+        source_lang: row.get(3)?,
+        target_lang: row.get(4)?,
+        status: DubbingStatus::parse(&status),
+        error: row.get(6)?,
+        expected_duration_seconds: row.get(7)?,
+        audio_id: row.get(8)?,
+        project_id: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+    })
+}
+
+pub fn save_job(conn: &Connection, job: &DubbingJob) -> Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO dubbing_jobs ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            JOB_COLUMNS
+        ),
+        rusqlite::params![
+            job.id,
+            job.dubbing_id,
+            job.source_path,
+            job.source_lang,
+            job.target_lang,
+            job.status.as_str(),
+            job.error,
+            job.expected_duration_seconds,
+            job.audio_id,
+            job.project_id,
+            job.created_at,
+            job.updated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get_job(conn: &Connection, id: &str) -> Result<Option<DubbingJob>> {
+    let job = conn
+        .query_row(&format!("SELECT {} FROM dubbing_jobs WHERE id = ?1", JOB_COLUMNS), [id], job_from_row)
+        .optional()?;
+    Ok(job)
+}
+
+/// Mark a dubbed job as downloading, returning whether this caller got it.
+/// Only one of several concurrent checks of a job downloads its result.
+pub fn claim_download(conn: &Connection, id: &str) -> Result<bool> {
+    let now = chrono::Utc::now();
+    let stale = now - chrono::Duration::from_std(STALE_DOWNLOAD)?;
+    let claimed = conn.execute(
+        "UPDATE dubbing_jobs SET status = 'downloading', updated_at = ?2
+         WHERE id = ?1 AND (status = 'dubbing' OR (status = 'downloading' AND updated_at < ?3))",
+        (id, now.to_rfc3339(), stale.to_rfc3339()),
+    )?;
+    Ok(claimed == 1)
+}
+
+/// Hand a claimed job back after its download failed, so it is tried again
+pub fn release_download(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE dubbing_jobs SET status = 'dubbing' WHERE id = ?1 AND status = 'downloading'",
+        [id],
+    )?;
+    Ok(())
+}
+
+/// Jobs newest first, only those of `project_id` when given
+pub fn get_jobs(conn: &Connection, project_id: Option<&str>) -> Result<Vec<DubbingJob>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM dubbing_jobs WHERE ?1 IS NULL OR project_id = ?1 ORDER BY created_at DESC",
+        JOB_COLUMNS
+    ))?;
+    let jobs = stmt.query_map([project_id], job_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(jobs)
+}
+
+/// Upload `request.source_path` for dubbing and record the job
+pub async fn submit(
+    state: &ElevenLabsState,
+    request: DubbingRequest,
+    project_id: Option<&str>,
+) -> Result<DubbingJob, AudioError> {
+    if !Path::new(&request.source_path).is_file() {
+        return Err(AudioError::Validation(format!("No file at {}", request.source_path)));
+    }
+    if request.target_lang.trim().is_empty() {
+        return Err(AudioError::Validation("A target language is required".to_string()));
+    }
+    let client = get_client(state).await?;
+    let submission = auth::check(state, client.create_dubbing(request.clone()).await).await?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let job = DubbingJob {
+        id: uuid::Uuid::new_v4().to_string(),
+        dubbing_id: submission.dubbing_id,
+        source_path: request.source_path,
+        source_lang: request.source_lang,
+        target_lang: request.target_lang,
+        status: DubbingStatus::Dubbing,
+        error: None,
+        expected_duration_seconds: submission.expected_duration_sec,
+        audio_id: None,
+        project_id: project_id.map(str::to_string),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    let record = job.clone();
+    state.call_db(move |conn| save_job(conn, &record)).await?;
+    Ok(job)
+}
+
+/// Check a running job with the provider, downloading the dub once it is
+/// done. Finished jobs, and jobs another check is downloading, are returned as
+/// they are.
+pub async fn refresh(state: &ElevenLabsState, job_id: &str) -> Result<DubbingJob, AudioError> {
+    let id = job_id.to_string();
+    let mut job = state
+        .call_db(move |conn| get_job(conn, &id))
+        .await?
+        .ok_or_else(|| AudioError::Validation(format!("No dubbing job: {}", job_id)))?;
+    if job.status != DubbingStatus::Dubbing {
+        return Ok(job);
+    }
+
+    let client = get_client(state).await?;
+    let project = auth::check(state, client.get_dubbing(&job.dubbing_id).await).await?;
+    match project.status.as_str() {
+        "dubbed" => {
+            let id = job.id.clone();
+            if !state.call_db(move |conn| claim_download(conn, &id)).await? {
+                let id = job.id.clone();
+                return Ok(state.call_db(move |conn| get_job(conn, &id)).await?.unwrap_or(job));
+            }
+            match download(state, &job).await {
+                Ok(audio) => {
+                    job.audio_id = Some(audio.id);
+                    job.status = DubbingStatus::Completed;
+                }
+                Err(e) => {
+                    let id = job.id.clone();
+                    state.call_db(move |conn| release_download(conn, &id)).await?;
+                    return Err(e);
+                }
+            }
+        }
+        "failed" => {
+            job.error = Some(project.error.unwrap_or_else(|| "Dubbing failed".to_string()));
+            job.status = DubbingStatus::Failed;
+        }
+        _ => return Ok(job),
+    }
+
+    job.updated_at = chrono::Utc::now().to_rfc3339();
+    let record = job.clone();
+    state.call_db(move |conn| save_job(conn, &record)).await?;
+    Ok(job)
+}
+
+/// Save a finished dub to the audio cache and record it
+async fn download(state: &ElevenLabsState, job: &DubbingJob) -> Result<GeneratedAudio, AudioError> {
+    let client = get_client(state).await?;
+    let stream = auth::check(state, client.get_dubbed_audio(&job.dubbing_id, &job.target_lang).await).await?;
+
+    let is_video = Path::new(&job.source_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| VIDEO_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+    let cache = ensure_cache(state).await?;
+    let (path, received) = cache
+        .save_audio_stream(&AudioType::Tts, stream, if is_video { "mp4" } else { "mp3" }, |_| {})
+        .await?;
+
+    let audio = GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type: AudioType::Tts,
+        prompt: String::new(),
+        // A video's size says nothing about its length, so it is left unknown
+        duration_seconds: if is_video { 0.0 } else { estimate_duration_seconds(received, "mp3").unwrap_or(0.0) },
+        local_path: path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata: serde_json::json!({ DUBBED_FROM_KEY: job.source_path, "target_lang": job.target_lang }),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        params: Some(GenerationParams::new(PROVIDER_ELEVENLABS)),
+        cached: false,
+    };
+    let record = audio.clone();
+    state.call_db(move |conn| AudioCacheDb::save_audio_record(conn, &record)).await?;
+    Ok(audio)
+}
+
+/// Poll a job until it completes or fails, emitting `DUBBING_PROGRESS_EVENT`
+/// after every check. Does nothing if the job is already being polled.
+fn spawn_poller(app: AppHandle, job_id: String) {
+    let tasks = app.state::<Arc<ElevenLabsState>>().tasks().clone();
+    tasks.spawn_unique(format!("dubbing-{}", job_id), |shutdown| async move {
+        let state = app.state::<Arc<ElevenLabsState>>();
+        let mut failures = 0;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                _ = shutdown.wait() => break,
+            }
+
+            match refresh(&state, &job_id).await {
+                Ok(job) => {
+                    failures = 0;
+                    let _ = app.emit(DUBBING_PROGRESS_EVENT, &job);
+                    if job.status.is_finished() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Failed to check dubbing job {}: {}", job_id, e);
+                    failures += 1;
+                    if failures >= MAX_POLL_FAILURES {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Submit a video or audio file for dubbing into `target_lang`. Progress is
+/// reported with `audio-dubbing-progress` events until the dub is downloaded.
+#[tauri::command]
+pub async fn eleven_labs_dub_file(
+    app: AppHandle,
+    state: State<'_, Arc<ElevenLabsState>>,
+    source_path: String,
+    target_lang: String,
+    source_lang: Option<String>,
+    num_speakers: Option<u32>,
+    project_id: Option<String>,
+) -> Result<DubbingJob, AudioError> {
+    let request = DubbingRequest { source_path, target_lang, source_lang, num_speakers };
+    let job = submit(&state, request, project_id.as_deref()).await?;
+    spawn_poller(app, job.id.clone());
+    Ok(job)
+}
+
+/// Check a job now, e.g. one left running when the app was closed, and resume
+/// polling it if it is still being dubbed
+#[tauri::command]
+pub async fn refresh_dubbing_job(
+    app: AppHandle,
+    state: State<'_, Arc<ElevenLabsState>>,
+    job_id: String,
+) -> Result<DubbingJob, AudioError> {
+    let job = refresh(&state, &job_id).await?;
+    if !job.status.is_finished() {
+        spawn_poller(app, job.id.clone());
+    }
+    Ok(job)
+}
+
+#[tauri::command]
+pub async fn get_dubbing_job(
+    state: State<'_, Arc<ElevenLabsState>>,
+    job_id: String,
+) -> Result<Option<DubbingJob>, AudioError> {
+    state.call_db(move |conn| get_job(conn, &job_id)).await
+}
+
+/// Dubbing jobs, newest first
+#[tauri::command]
+pub async fn list_dubbing_jobs(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: Option<String>,
+) -> Result<Vec<DubbingJob>, AudioError> {
+    state.call_db(move |conn| get_jobs(conn, project_id.as_deref())).await
+}
@@ -0,0 +1,293 @@
+// Opt-in encryption of the app database with SQLCipher, for builds with the
+// `sqlcipher` feature. The passphrase is kept in the OS keyring and every
+// connection to the database is opened through `open`, which keys it.
+//
+// Setting, rotating or removing the passphrase only records the request; it
+// is carried out by `prepare` at the next start, before any connection is
+// open, by exporting the database to a copy under the new key and swapping it
+// in. Connections already open would otherwise keep using the old file.
+
+use anyhow::{anyhow, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use super::error::AudioError;
+use super::schema::PROMPT_SEARCH_TABLE;
+use super::secrets;
+
+/// Keyring entry holding the passphrase of the encrypted database
+const PASSPHRASE_SECRET: &str = "app_db_passphrase";
+
+/// Keyring entry holding a passphrase change to make at the next start; empty
+/// to decrypt the database
+const PENDING_SECRET: &str = "app_db_passphrase_pending";
+
+/// Shortest passphrase accepted
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// How connections to the database are keyed
+enum Key {
+    Plain,
+    Passphrase(String),
+    /// The keyring couldn't be read, so whether there is a passphrase is
+    /// unknown
+    Unavailable,
+}
+
+/// Loaded once by `prepare`
+static KEY: OnceLock<Key> = OnceLock::new();
+
+/// Whether the database is encrypted and whether that is about to change
+#[derive(Debug, Clone, Serialize)]
+pub struct DbEncryptionStatus {
+    /// This build links SQLCipher
+    pub supported: bool,
+    pub encrypted: bool,
+    /// A passphrase change is waiting for the app to restart
+    pub pending_change: bool,
+}
+
+fn passphrase() -> Option<&'static str> {
+    match KEY.get() {
+        Some(Key::Passphrase(passphrase)) => Some(passphrase),
+        _ => None,
+    }
+}
+
+/// Whether the database may be encrypted: a passphrase is set, or the keyring
+/// couldn't be read (or hasn't been yet) to tell
+pub fn key_expected() -> bool {
+    !matches!(KEY.get(), Some(Key::Plain))
+}
+
+/// Whether `error` means the database couldn't be read because it is
+/// encrypted with a key this process doesn't have, rather than damaged
+pub fn is_locked(error: &rusqlite::Error) -> bool {
+    error.sqlite_error_code() == Some(rusqlite::ErrorCode::NotADatabase) && key_expected()
+}
+
+/// Whether this build links SQLCipher rather than plain SQLite
+pub fn is_supported() -> bool {
+    Connection::open_in_memory()
+        .and_then(|conn| {
+            conn.query_row("PRAGMA cipher_version", [], |row| row.get::<_, String>(0)).optional()
+        })
+        .is_ok_and(|version| version.is_some())
+}
+
+fn key(conn: &Connection, passphrase: &str) -> rusqlite::Result<()> {
+    conn.pragma_update(None, "key", passphrase)
+}
+
+/// Key a new connection to the app database when it is encrypted. Must come
+/// before any other statement on the connection.
+pub fn unlock(conn: &Connection) -> rusqlite::Result<()> {
+    match passphrase() {
+        Some(passphrase) => key(conn, passphrase),
+        None => Ok(()),
+    }
+}
+
+/// Open the app database at `path`, keyed when it is encrypted
+pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    unlock(&conn)?;
+    Ok(conn)
+}
+
+/// Copy the database at `path`, opened with `current`, to a file next to it
+/// encrypted with `new` (or not encrypted when `None`)
+fn export(path: &Path, current: Option<&str>, new: Option<&str>) -> Result<PathBuf> {
+    let conn = Connection::open(path)?;
+    if let Some(current) = current {
+        key(&conn, current)?;
+    }
+    // Move everything out of the write-ahead log into the file being copied
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+    let mut name = path.as_os_str().to_owned();
+    name.push(".rekey");
+    let target = PathBuf::from(name);
+    if target.exists() {
+        std::fs::remove_file(&target)?;
+    }
+    conn.execute(
+        "ATTACH DATABASE ?1 AS rekeyed KEY ?2",
+        (target.to_string_lossy(), new.unwrap_or("")),
+    )?;
+    conn.query_row("SELECT sqlcipher_export('rekeyed')", [], |_| Ok(()))?;
+    conn.execute_batch("DETACH DATABASE rekeyed")?;
+    drop(conn);
+
+    // The prompt index's shadow tables are copied as plain tables; build it
+    // again from the copied prompts
+    let copy = Connection::open(&target)?;
+    if let Some(new) = new {
+        key(&copy, new)?;
+    }
+    let indexed: bool = copy.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = ?1",
+        [PROMPT_SEARCH_TABLE],
+        |row| row.get(0),
+    )?;
+    if indexed {
+        copy.execute(&format!("INSERT INTO {0} ({0}) VALUES ('rebuild')", PROMPT_SEARCH_TABLE), [])?;
+    }
+    Ok(target)
+}
+
+/// Re-encrypt the database at `path` from `current` to `new`, keeping the
+/// keyring in step with the file
+fn change_passphrase(path: &Path, current: Option<&str>, new: Option<&str>) -> Result<()> {
+    let copy = export(path, current, new)?;
+    match new {
+        Some(new) => secrets::store_secret(PASSPHRASE_SECRET, new)?,
+        None => secrets::delete_secret(PASSPHRASE_SECRET)?,
+    }
+    for suffix in ["-wal", "-shm"] {
+        let mut name = path.as_os_str().to_owned();
+        name.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(name));
+    }
+    if let Err(e) = std::fs::rename(&copy, path) {
+        // Put the keyring back to match the file still in place
+        match current {
+            Some(current) => secrets::store_secret(PASSPHRASE_SECRET, current)?,
+            None => secrets::delete_secret(PASSPHRASE_SECRET)?,
+        }
+        return Err(anyhow!("Failed to replace the database with its re-encrypted copy: {}", e));
+    }
+    Ok(())
+}
+
+/// Carry out a passphrase change requested with `set_app_db_passphrase`, then
+/// load the passphrase `open` keys connections with. Every entrypoint runs it
+/// before the database is first opened; later calls do nothing. A failed
+/// change is logged and tried again at the next start, leaving the database as
+/// it was. When the keyring can't be read the database is treated as locked.
+pub fn prepare(path: &Path) -> Result<()> {
+    static PREPARING: Mutex<()> = Mutex::new(());
+    let _guard = PREPARING.lock().unwrap_or_else(|e| e.into_inner());
+    if KEY.get().is_some() {
+        return Ok(());
+    }
+    let secrets = secrets::get_secret(PASSPHRASE_SECRET)
+        .and_then(|current| Ok((current, secrets::get_secret(PENDING_SECRET)?)));
+    let (current, pending) = match secrets {
+        Ok(secrets) => secrets,
+        Err(e) => {
+            let _ = KEY.set(Key::Unavailable);
+            return Err(e);
+        }
+    };
+    let mut passphrase = current.clone();
+    if let Some(pending) = pending {
+        let new = (!pending.is_empty()).then_some(pending);
+        let changed = if path.exists() {
+            change_passphrase(path, current.as_deref(), new.as_deref())
+        } else {
+            // Nothing to convert; the database is created under the new key
+            match &new {
+                Some(new) => secrets::store_secret(PASSPHRASE_SECRET, new),
+                None => secrets::delete_secret(PASSPHRASE_SECRET),
+            }
+        };
+        match changed {
+            Ok(()) => {
+                secrets::delete_secret(PENDING_SECRET)?;
+                log::info!("{} the app database", if new.is_some() { "Encrypted" } else { "Decrypted" });
+                passphrase = new;
+            }
+            Err(e) => log::error!("Failed to change the app database passphrase: {}", e),
+        }
+    }
+    let _ = KEY.set(passphrase.map_or(Key::Plain, Key::Passphrase));
+    Ok(())
+}
+
+fn status() -> Result<DbEncryptionStatus> {
+    Ok(DbEncryptionStatus {
+        supported: is_supported(),
+        encrypted: passphrase().is_some(),
+        pending_change: secrets::get_secret(PENDING_SECRET)?.is_some(),
+    })
+}
+
+// ========== Tauri Commands ==========
+
+/// Whether the app database is encrypted
+#[tauri::command]
+pub async fn get_app_db_encryption() -> Result<DbEncryptionStatus, AudioError> {
+    Ok(tokio::task::spawn_blocking(status).await??)
+}
+
+/// Encrypt the app database with `passphrase`, change its passphrase, or with
+/// `None` decrypt it. Takes effect when the app next starts.
+#[tauri::command]
+pub async fn set_app_db_passphrase(passphrase: Option<String>) -> Result<DbEncryptionStatus, AudioError> {
+    if !is_supported() {
+        return Err(AudioError::NotConfigured(
+            "This build can't encrypt the database; it needs the sqlcipher feature".to_string(),
+        ));
+    }
+    if let Some(passphrase) = &passphrase {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(AudioError::Validation(format!(
+                "The passphrase needs at least {} characters",
+                MIN_PASSPHRASE_LEN
+            )));
+        }
+    }
+    tokio::task::spawn_blocking(move || {
+        secrets::store_secret(PENDING_SECRET, passphrase.as_deref().unwrap_or(""))?;
+        status()
+    })
+    .await?
+    .map_err(AudioError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_build_opens_without_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        let conn = open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE t (v TEXT)").unwrap();
+        assert_eq!(is_supported(), cfg!(feature = "sqlcipher"));
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn test_export_encrypts_and_decrypts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        let conn = Connection::open(&path).unwrap();
+        crate::commands::eleven_labs::schema::init(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO audio_cache (id, audio_type, prompt, local_path, created_at)
+             VALUES ('a', '\"sfx\"', 'Thunder', '/a.mp3', '2024-01-01')",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        let encrypted = export(&path, None, Some("correct horse")).unwrap();
+        let locked = Connection::open(&encrypted).unwrap();
+        assert!(locked.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0)).is_err());
+        std::fs::rename(&encrypted, &path).unwrap();
+
+        let decrypted = export(&path, Some("correct horse"), None).unwrap();
+        let conn = Connection::open(&decrypted).unwrap();
+        let found: i64 = conn
+            .query_row("SELECT COUNT(*) FROM audio_prompt_fts WHERE audio_prompt_fts MATCH 'thunder'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(found, 1);
+    }
+}
@@ -0,0 +1,253 @@
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use thiserror::Error;
+
+use super::types::{SampleDiagnostic, VoiceReferences};
+
+/// What kind of failure a provider error was
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderErrorKind {
+    /// The API key was rejected
+    Unauthorized,
+    /// The account has no characters left
+    QuotaExceeded,
+    RateLimited,
+    /// No response was received
+    Network,
+    /// The provider failed or is overloaded (5xx)
+    Unavailable,
+    /// Any other error response
+    Api,
+}
+
+impl ProviderErrorKind {
+    /// Classify an error response by status code and body
+    pub fn from_response(status: u16, body: &str) -> Self {
+        match status {
+            401 if body.contains("quota_exceeded") => ProviderErrorKind::QuotaExceeded,
+            401 => ProviderErrorKind::Unauthorized,
+            429 => ProviderErrorKind::RateLimited,
+            500..=599 => ProviderErrorKind::Unavailable,
+            _ => ProviderErrorKind::Api,
+        }
+    }
+}
+
+/// Error returned by the audio commands. Serialized for the frontend as
+/// `{ code, message, retryable }`, plus `kind` for provider errors.
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("Database error: {0}")]
+    Db(#[from] rusqlite::Error),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{message}")]
+    Provider { kind: ProviderErrorKind, message: String },
+    /// A required setting (API key, backend, ...) is missing
+    #[error("{0}")]
+    NotConfigured(String),
+    /// The request was rejected before doing any work
+    #[error("{0}")]
+    Validation(String),
+    /// A voice that characters, agents or cached renders still use was
+    /// deleted without `force`
+    #[error(
+        "Voice {voice_id} is still used by {} characters, {} agents and {} audio clips",
+        references.characters.len(),
+        references.agents.len(),
+        references.audio_ids.len()
+    )]
+    VoiceInUse { voice_id: String, references: VoiceReferences },
+    /// Voice clone samples that would be rejected, found before uploading
+    #[error(
+        "{} voice sample(s) can't be uploaded: {}",
+        .0.len(),
+        .0.iter().map(|d| d.message.as_str()).collect::<Vec<_>>().join("; ")
+    )]
+    InvalidSamples(Vec<SampleDiagnostic>),
+    /// The generation was cancelled with `eleven_labs_cancel_generation`
+    #[error("Generation cancelled")]
+    Cancelled,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl AudioError {
+    pub fn provider(kind: ProviderErrorKind, message: impl Into<String>) -> Self {
+        AudioError::Provider {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Stable code the frontend can match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            AudioError::Db(_) => "db",
+            AudioError::Io(_) => "io",
+            AudioError::Provider { .. } => "provider",
+            AudioError::NotConfigured(_) => "not_configured",
+            AudioError::Validation(_) => "validation",
+            AudioError::VoiceInUse { .. } => "voice_in_use",
+            AudioError::InvalidSamples(_) => "invalid_samples",
+            AudioError::Cancelled => "cancelled",
+            AudioError::Other(_) => "other",
+        }
+    }
+
+    /// Whether the same request may succeed if made again later: rate limits,
+    /// network failures, provider outages and a busy database
+    pub fn retryable(&self) -> bool {
+        match self {
+            AudioError::Provider { kind, .. } => matches!(
+                kind,
+                ProviderErrorKind::RateLimited | ProviderErrorKind::Network | ProviderErrorKind::Unavailable
+            ),
+            AudioError::Db(e) => matches!(
+                e.sqlite_error_code(),
+                Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+            ),
+            AudioError::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::Interrupted | std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+            ),
+            _ => false,
+        }
+    }
+
+    /// A copy for another caller waiting on the same request. Database and
+    /// I/O errors can't be cloned and keep only their message.
+    pub fn to_shared(&self) -> AudioError {
+        match self {
+            AudioError::Db(_) | AudioError::Io(_) => AudioError::Other(self.to_string()),
+            AudioError::Provider { kind, message } => AudioError::provider(*kind, message.clone()),
+            AudioError::NotConfigured(message) => AudioError::NotConfigured(message.clone()),
+            AudioError::Validation(message) => AudioError::Validation(message.clone()),
+            AudioError::VoiceInUse { voice_id, references } => AudioError::VoiceInUse {
+                voice_id: voice_id.clone(),
+                references: references.clone(),
+            },
+            AudioError::InvalidSamples(samples) => AudioError::InvalidSamples(samples.clone()),
+            AudioError::Cancelled => AudioError::Cancelled,
+            AudioError::Other(message) => AudioError::Other(message.clone()),
+        }
+    }
+}
+
+impl Serialize for AudioError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("AudioError", 6)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.serialize_field("retryable", &self.retryable())?;
+        match self {
+            AudioError::Provider { kind, .. } => error.serialize_field("kind", kind)?,
+            _ => error.skip_field("kind")?,
+        }
+        match self {
+            AudioError::VoiceInUse { references, .. } => error.serialize_field("references", references)?,
+            _ => error.skip_field("references")?,
+        }
+        match self {
+            AudioError::InvalidSamples(samples) => error.serialize_field("samples", samples)?,
+            _ => error.skip_field("samples")?,
+        }
+        error.end()
+    }
+}
+
+impl From<anyhow::Error> for AudioError {
+    /// Recover the typed error when there is one; anything else keeps its message
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<AudioError>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<rusqlite::Error>() {
+            Ok(e) => return AudioError::Db(e),
+            Err(e) => e,
+        };
+        match e.downcast::<std::io::Error>() {
+            Ok(e) => AudioError::Io(e),
+            Err(e) => AudioError::Other(e.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AudioError {
+    fn from(e: reqwest::Error) -> Self {
+        AudioError::provider(ProviderErrorKind::Network, e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AudioError {
+    fn from(e: serde_json::Error) -> Self {
+        AudioError::Other(e.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AudioError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        AudioError::Other(e.to_string())
+    }
+}
+
+impl From<r2d2::Error> for AudioError {
+    fn from(e: r2d2::Error) -> Self {
+        AudioError::Other(format!("No database connection available: {}", e))
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AudioError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        AudioError::Other(e.to_string())
+    }
+}
+
+impl From<String> for AudioError {
+    fn from(message: String) -> Self {
+        AudioError::Other(message)
+    }
+}
+
+impl From<&str> for AudioError {
+    fn from(message: &str) -> Self {
+        AudioError::Other(message.to_string())
+    }
+}
+
+/// For callers outside the audio module that still report errors as strings
+impl From<AudioError> for String {
+    fn from(e: AudioError) -> Self {
+        e.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_serialization() {
+        let error = AudioError::provider(ProviderErrorKind::QuotaExceeded, "Out of characters");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "provider",
+                "message": "Out of characters",
+                "retryable": false,
+                "kind": "quota_exceeded"
+            })
+        );
+        let outage = AudioError::provider(ProviderErrorKind::from_response(503, ""), "Service unavailable");
+        assert_eq!(serde_json::to_value(&outage).unwrap()["retryable"], true);
+        assert!(!AudioError::NotConfigured("No API key".to_string()).retryable());
+
+        // Typed errors survive a trip through anyhow
+        let wrapped = anyhow::Error::new(AudioError::NotConfigured("No API key".to_string()));
+        assert_eq!(AudioError::from(wrapped).code(), "not_configured");
+        let db = anyhow::Error::new(rusqlite::Error::QueryReturnedNoRows);
+        assert_eq!(AudioError::from(db).code(), "db");
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+use super::cache::{estimate_duration_seconds, AudioCacheDb, AudioDb};
+use super::error::AudioError;
+use super::pipeline;
+use super::speech_queue::SpeechQueue;
+use super::types::*;
+use super::{ensure_cache, ElevenLabsState};
+
+/// Agent lifecycle events that can play a sound cue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentEvent {
+    RunStarted,
+    ToolCall,
+    Error,
+    Completed,
+}
+
+impl AgentEvent {
+    pub const ALL: [AgentEvent; 4] = [
+        AgentEvent::RunStarted,
+        AgentEvent::ToolCall,
+        AgentEvent::Error,
+        AgentEvent::Completed,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AgentEvent::RunStarted => "run_started",
+            AgentEvent::ToolCall => "tool_call",
+            AgentEvent::Error => "error",
+            AgentEvent::Completed => "completed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|event| event.as_str() == value)
+            .ok_or_else(|| anyhow!("Unknown agent event: {}", value))
+    }
+
+    /// Sound effect prompt and length used for the one-click defaults
+    fn default_sfx(&self) -> (&'static str, f32) {
+        match self {
+            AgentEvent::RunStarted => ("Soft rising synth chime, short user interface notification", 1.0),
+            AgentEvent::ToolCall => ("Single quiet mechanical keyboard click", 0.5),
+            AgentEvent::Error => ("Low two-tone error buzz, short user interface alert", 1.0),
+            AgentEvent::Completed => ("Bright pleasant success chime, short user interface notification", 1.5),
+        }
+    }
+}
+
+/// A sound effect mapped to an agent event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSound {
+    pub event: AgentEvent,
+    pub audio_id: String,
+    pub enabled: bool,
+    pub updated_at: String,
+}
+
+/// Event sound database operations
+pub struct EventSoundDb;
+
+impl EventSoundDb {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<EventSound> {
+        let event: String = row.get(0)?;
+        Ok(EventSound {
+            event: AgentEvent::parse(&event).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, e.into())
+            })?,
+            audio_id: row.get(1)?,
+            enabled: row.get(2)?,
+            updated_at: row.get(3)?,
+        })
+    }
+
+    /// Map an event to a library clip, replacing any previous mapping
+    pub fn set_sound(conn: &Connection, event: AgentEvent, audio_id: &str, enabled: bool) -> Result<EventSound> {
+        let updated_at = chrono::Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT OR REPLACE INTO event_sounds (event, audio_id, enabled, updated_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            (event.as_str(), audio_id, enabled, &updated_at),
+        )?;
+
+        Ok(EventSound {
+            event,
+            audio_id: audio_id.to_string(),
+            enabled,
+            updated_at,
+        })
+    }
+
+    pub fn get_sound(conn: &Connection, event: AgentEvent) -> Result<Option<EventSound>> {
+        Ok(conn
+            .query_row(
+                "SELECT event, audio_id, enabled, updated_at FROM event_sounds WHERE event = ?1",
+                [event.as_str()],
+                Self::from_row,
+            )
+            .optional()?)
+    }
+
+    pub fn get_sounds(conn: &Connection) -> Result<Vec<EventSound>> {
+        let mut stmt = conn.prepare("SELECT event, audio_id, enabled, updated_at FROM event_sounds ORDER BY event")?;
+        let rows = stmt.query_map([], Self::from_row)?;
+
+        let mut sounds = vec![];
+        for row in rows {
+            sounds.push(row?);
+        }
+        Ok(sounds)
+    }
+
+    pub fn remove_sound(conn: &Connection, event: AgentEvent) -> Result<()> {
+        conn.execute("DELETE FROM event_sounds WHERE event = ?1", [event.as_str()])?;
+        Ok(())
+    }
+}
+
+/// The enabled clip mapped to `event`, if its file is still on disk
+async fn event_audio(db: &AudioDb, event: AgentEvent) -> Result<Option<GeneratedAudio>, AudioError> {
+    db.call(move |conn| {
+        let audio = match EventSoundDb::get_sound(conn, event)? {
+            Some(sound) if sound.enabled => AudioCacheDb::get_audio_record(conn, &sound.audio_id)?,
+            _ => None,
+        };
+        Ok(audio.filter(|audio| Path::new(&audio.local_path).exists()))
+    })
+    .await
+}
+
+/// Play the clip mapped to `event`, if any. Missing files are skipped silently.
+pub async fn play(app: &AppHandle, event: AgentEvent) {
+    let audio = match app.state::<Arc<ElevenLabsState>>().db() {
+        Ok(db) => event_audio(&db, event).await,
+        Err(e) => Err(e),
+    };
+    match audio {
+        Ok(Some(audio)) => app.state::<SpeechQueue>().play(app, audio, "agent-event"),
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to play {} sound: {}", event.as_str(), e),
+    }
+}
+
+/// Agent output hook: cue tool calls in assistant messages
+pub async fn on_agent_output(app: &AppHandle, message: &serde_json::Value) {
+    if message.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+        return;
+    }
+    let has_tool_call = message
+        .pointer("/message/content")
+        .and_then(|content| content.as_array())
+        .is_some_and(|blocks| {
+            blocks
+                .iter()
+                .any(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+        });
+    if has_tool_call {
+        play(app, AgentEvent::ToolCall).await;
+    }
+}
+
+/// Generate the default sound for an event (or one from `prompt`) and map it
+async fn generate_sound(state: &ElevenLabsState, event: AgentEvent, prompt: Option<String>) -> Result<EventSound, AudioError> {
+    let (default_prompt, duration_seconds) = event.default_sfx();
+    let request = SfxRequest {
+        text: prompt.unwrap_or_else(|| default_prompt.to_string()),
+        duration_seconds,
+        prompt_influence: 0.5,
+    };
+    let mut audio = pipeline::generate_sfx(state, request).await?;
+
+    audio.metadata["event_sound"] = serde_json::json!(event.as_str());
+    state
+        .call_db(move |conn| {
+            AudioCacheDb::save_audio_record(conn, &audio)?;
+            EventSoundDb::set_sound(conn, event, &audio.id, true)
+        })
+        .await
+}
+
+// ========== Tauri Commands ==========
+
+/// List the configured event sounds
+#[tauri::command]
+pub async fn list_event_sounds(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<EventSound>, AudioError> {
+    state.call_db(|conn| EventSoundDb::get_sounds(conn)).await
+}
+
+/// Map an event to an existing clip from the audio library
+#[tauri::command]
+pub async fn set_event_sound(
+    state: State<'_, Arc<ElevenLabsState>>,
+    event: AgentEvent,
+    audio_id: String,
+    enabled: Option<bool>,
+) -> Result<EventSound, AudioError> {
+    state
+        .call_db(move |conn| {
+            if AudioCacheDb::get_audio_record(conn, &audio_id)?.is_none() {
+                return Err(anyhow!("Audio not found: {}", audio_id));
+            }
+            EventSoundDb::set_sound(conn, event, &audio_id, enabled.unwrap_or(true))
+        })
+        .await
+}
+
+/// Remove the sound mapped to an event
+#[tauri::command]
+pub async fn remove_event_sound(state: State<'_, Arc<ElevenLabsState>>, event: AgentEvent) -> Result<(), AudioError> {
+    state.call_db(move |conn| EventSoundDb::remove_sound(conn, event)).await
+}
+
+/// Generate a sound effect for an event from a prompt (or the built-in default prompt)
+#[tauri::command]
+pub async fn generate_event_sound(
+    state: State<'_, Arc<ElevenLabsState>>,
+    event: AgentEvent,
+    prompt: Option<String>,
+) -> Result<EventSound, AudioError> {
+    generate_sound(&state, event, prompt).await
+}
+
+/// Generate the built-in default sound for every event that has none yet
+#[tauri::command]
+pub async fn generate_default_event_sounds(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<EventSound>, AudioError> {
+    let configured: Vec<AgentEvent> = state
+        .call_db(|conn| EventSoundDb::get_sounds(conn))
+        .await?
+        .into_iter()
+        .map(|sound| sound.event)
+        .collect();
+
+    let mut generated = vec![];
+    for event in AgentEvent::ALL {
+        if !configured.contains(&event) {
+            generated.push(generate_sound(&state, event, None).await?);
+        }
+    }
+    Ok(generated)
+}
+
+/// Import an audio file into the library as a sound effect and map it to an event
+#[tauri::command]
+pub async fn import_event_sound(
+    state: State<'_, Arc<ElevenLabsState>>,
+    event: AgentEvent,
+    path: String,
+) -> Result<EventSound, AudioError> {
+    let source = Path::new(&path);
+    let extension = source
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .ok_or_else(|| format!("{} has no file extension", path))?;
+    let data = tokio::fs::read(source)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    let cache = ensure_cache(&state).await?;
+    let local_path = cache
+        .save_audio(&AudioType::Sfx, &data, &extension)
+        .await?;
+
+    let audio = GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type: AudioType::Sfx,
+        prompt: source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        duration_seconds: estimate_duration_seconds(data.len() as u64, &extension).unwrap_or(0.0),
+        local_path: local_path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata: serde_json::json!({ "imported_from": path, "event_sound": event.as_str() }),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        params: None,
+        cached: false,
+    };
+
+    state
+        .call_db(move |conn| {
+            AudioCacheDb::save_audio_record(conn, &audio)?;
+            EventSoundDb::set_sound(conn, event, &audio.id, true)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_sound_mapping() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::commands::eleven_labs::schema::init(&conn).unwrap();
+
+        EventSoundDb::set_sound(&conn, AgentEvent::Completed, "a", true).unwrap();
+        EventSoundDb::set_sound(&conn, AgentEvent::Completed, "b", false).unwrap();
+        EventSoundDb::set_sound(&conn, AgentEvent::ToolCall, "c", true).unwrap();
+
+        let completed = EventSoundDb::get_sound(&conn, AgentEvent::Completed).unwrap().unwrap();
+        assert_eq!(completed.audio_id, "b");
+        assert!(!completed.enabled);
+        assert_eq!(EventSoundDb::get_sounds(&conn).unwrap().len(), 2);
+
+        EventSoundDb::remove_sound(&conn, AgentEvent::ToolCall).unwrap();
+        assert!(EventSoundDb::get_sound(&conn, AgentEvent::ToolCall).unwrap().is_none());
+        assert_eq!(AgentEvent::parse("run_started").unwrap(), AgentEvent::RunStarted);
+    }
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+use super::cache::{estimate_duration_seconds, AudioCache, AudioCacheDb, AudioDb, SettingsDb};
+use super::error::AudioError;
+use super::supervisor::TaskSupervisor;
+use super::types::*;
+use super::{ensure_cache, ElevenLabsState};
+
+/// Settings key holding the external editor configuration
+pub const EXTERNAL_EDITOR_KEY: &str = "external_audio_editor";
+
+/// How often the exported file is checked for saves
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Editors that hand the file to an already running instance exit right away;
+/// in that case the file is watched until this timeout instead
+const LAUNCHER_EXIT_WINDOW: Duration = Duration::from_secs(5);
+const WATCH_TIMEOUT: Duration = Duration::from_secs(4 * 60 * 60);
+
+/// Editor used for audio cleanup, e.g. Audacity or iZotope RX
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExternalEditorConfig {
+    /// Executable to launch
+    pub command: Option<String>,
+    /// Arguments; `{file}` is replaced with the exported path, which is appended when absent
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl ExternalEditorConfig {
+    pub fn load(conn: &rusqlite::Connection) -> anyhow::Result<Self> {
+        match SettingsDb::get_setting(conn, EXTERNAL_EDITOR_KEY)? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn launch(&self, file: &Path) -> Result<Child, AudioError> {
+        let command = self
+            .command
+            .as_deref()
+            .filter(|c| !c.trim().is_empty())
+            .ok_or("No external audio editor configured")?;
+
+        let file = file.to_string_lossy();
+        let mut args: Vec<String> = self.args.iter().map(|a| a.replace("{file}", &file)).collect();
+        if !self.args.iter().any(|a| a.contains("{file}")) {
+            args.push(file.to_string());
+        }
+
+        std::process::Command::new(command)
+            .args(&args)
+            .spawn()
+            .map_err(|e| AudioError::Other(format!("Failed to launch {}: {}", command, e)))
+    }
+}
+
+/// Emitted as `audio-external-edit` each time a save is re-imported
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalEditEvent {
+    pub original_id: String,
+    pub audio: GeneratedAudio,
+}
+
+fn modified_at(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Save the edited file as a take of `original`, or update the take created by an
+/// earlier save in the same session
+async fn import_edit(
+    db: &AudioDb,
+    cache: &AudioCache,
+    original: &GeneratedAudio,
+    take: Option<GeneratedAudio>,
+    edited: &Path,
+) -> Result<GeneratedAudio, AudioError> {
+    let data = tokio::fs::read(edited).await?;
+    let extension = edited.extension().and_then(|e| e.to_str()).unwrap_or("mp3");
+
+    let mut take = match take {
+        Some(take) => {
+            tokio::fs::write(&take.local_path, &data).await?;
+            take
+        }
+        None => {
+            let path = cache
+                .save_audio(&original.audio_type, &data, extension)
+                .await?;
+
+            let mut metadata = original.metadata.clone();
+            match metadata.as_object_mut() {
+                Some(map) => {
+                    map.insert("edited_from".to_string(), original.id.clone().into());
+                }
+                None => metadata = serde_json::json!({ "edited_from": original.id }),
+            }
+
+            GeneratedAudio {
+                id: Uuid::new_v4().to_string(),
+                audio_type: original.audio_type.clone(),
+                prompt: original.prompt.clone(),
+                duration_seconds: original.duration_seconds,
+                local_path: path.to_string_lossy().to_string(),
+                supabase_url: None,
+                metadata,
+                created_at: chrono::Utc::now().to_rfc3339(),
+                params: original.params.clone(),
+                cached: false,
+            }
+        }
+    };
+
+    // Formats the size says nothing about keep the original length
+    if let Some(duration_seconds) = estimate_duration_seconds(data.len() as u64, extension) {
+        take.duration_seconds = duration_seconds;
+    }
+
+    let record = take.clone();
+    db.call(move |conn| AudioCacheDb::save_audio_record(conn, &record)).await?;
+    Ok(take)
+}
+
+/// Poll the exported file until the editor closes, re-importing every completed save
+fn spawn_watcher(
+    tasks: &TaskSupervisor,
+    app: AppHandle,
+    db: AudioDb,
+    cache: Arc<AudioCache>,
+    original: GeneratedAudio,
+    file: PathBuf,
+    mut editor: Child,
+) {
+    let name = format!("external-edit:{}", original.id);
+    tasks.spawn(name, |shutdown| async move {
+        let started = Instant::now();
+        let mut last_seen = modified_at(&file);
+        let mut pending: Option<(SystemTime, u64)> = None;
+        let mut take: Option<GeneratedAudio> = None;
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                _ = shutdown.wait() => {
+                    // Keep a save the watcher hasn't imported yet
+                    if modified_at(&file) != last_seen {
+                        if let Err(e) = import_edit(&db, &cache, &original, take.clone(), &file).await {
+                            log::warn!("Failed to import edit of {}: {}", original.id, e);
+                        }
+                    }
+                    break;
+                }
+            }
+
+            let exited = matches!(editor.try_wait(), Ok(Some(_)));
+            let current = modified_at(&file);
+
+            if current.is_some() && current != last_seen {
+                // Wait for one quiet interval so a save in progress isn't imported half-written
+                if pending == current {
+                    last_seen = current;
+                    pending = None;
+                    match import_edit(&db, &cache, &original, take.clone(), &file).await {
+                        Ok(audio) => {
+                            take = Some(audio.clone());
+                            let _ = app.emit(
+                                "audio-external-edit",
+                                ExternalEditEvent { original_id: original.id.clone(), audio },
+                            );
+                        }
+                        Err(e) => log::warn!("Failed to import edit of {}: {}", original.id, e),
+                    }
+                } else {
+                    pending = current;
+                }
+                continue;
+            }
+
+            let handed_off = started.elapsed() < LAUNCHER_EXIT_WINDOW;
+            if (exited && !handed_off && pending.is_none()) || started.elapsed() > WATCH_TIMEOUT {
+                break;
+            }
+        }
+
+        if let Some(dir) = file.parent() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the external editor configuration
+#[tauri::command]
+pub async fn get_external_editor_config(state: State<'_, Arc<ElevenLabsState>>) -> Result<ExternalEditorConfig, AudioError> {
+    state.call_db(|conn| ExternalEditorConfig::load(conn)).await
+}
+
+/// Save the external editor configuration
+#[tauri::command]
+pub async fn set_external_editor_config(
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: ExternalEditorConfig,
+) -> Result<(), AudioError> {
+    let json = serde_json::to_string(&config)?;
+    state.call_db(move |conn| SettingsDb::save_setting(conn, EXTERNAL_EDITOR_KEY, &json)).await
+}
+
+/// Open a cached file in the configured editor. Each save is imported as a new take
+/// whose metadata links back to the original via `edited_from`. Returns the path of
+/// the exported copy.
+#[tauri::command]
+pub async fn open_in_external_editor(
+    app: AppHandle,
+    state: State<'_, Arc<ElevenLabsState>>,
+    id: String,
+) -> Result<String, AudioError> {
+    let cache = ensure_cache(&state).await?;
+    let db = state.db()?;
+
+    let (original, config) = db
+        .call(move |conn| {
+            let original = AudioCacheDb::get_audio_record(conn, &id)?
+                .ok_or_else(|| anyhow::anyhow!("Audio not found: {}", id))?;
+            Ok((original, ExternalEditorConfig::load(conn)?))
+        })
+        .await?;
+
+    // A session directory per edit keeps the original file name readable in the editor
+    let dir = std::env::temp_dir()
+        .join("opcode-edit")
+        .join(Uuid::new_v4().to_string());
+    tokio::fs::create_dir_all(&dir).await?;
+
+    let source = Path::new(&original.local_path);
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| format!("Invalid audio path: {}", original.local_path))?;
+    let file = dir.join(file_name);
+    tokio::fs::copy(source, &file)
+        .await
+        .map_err(|e| format!("Failed to export {}: {}", original.local_path, e))?;
+
+    let editor = match config.launch(&file) {
+        Ok(editor) => editor,
+        Err(e) => {
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+            return Err(e);
+        }
+    };
+
+    let exported = file.to_string_lossy().to_string();
+    spawn_watcher(state.tasks(), app, db, cache, original, file, editor);
+    Ok(exported)
+}
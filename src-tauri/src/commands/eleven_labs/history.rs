@@ -0,0 +1,122 @@
+// The provider's server-side generation history. Every render made with the
+// API key is kept there, so audio generated on another machine can be found
+// and downloaded into the local cache, and old entries cleaned up remotely.
+
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension};
+use std::sync::Arc;
+use tauri::State;
+
+use super::auth;
+use super::cache::{estimate_duration_seconds, AudioCacheDb};
+use super::error::AudioError;
+use super::types::*;
+use super::{ensure_cache, get_client, ElevenLabsState};
+
+/// History items per page when no page size is given
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// The provider's largest page
+const MAX_PAGE_SIZE: u32 = 1000;
+
+/// The local record already downloaded from a history item, if any
+pub fn find_imported(conn: &Connection, history_item_id: &str) -> Result<Option<String>> {
+    let id = conn
+        .query_row(
+            "SELECT id FROM audio_cache WHERE json_extract(metadata, '$.history_item_id') = ?1",
+            [history_item_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(id)
+}
+
+/// Download a history item into the audio cache and record it. An item
+/// downloaded before returns the existing record instead.
+pub async fn import(
+    state: &ElevenLabsState,
+    history_item_id: &str,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    let item_id = history_item_id.to_string();
+    let existing = state
+        .call_db(move |conn| {
+            find_imported(conn, &item_id)?
+                .map(|id| AudioCacheDb::get_audio_record(conn, &id))
+                .transpose()
+                .map(Option::flatten)
+        })
+        .await?;
+    if let Some(audio) = existing {
+        return Ok(audio);
+    }
+
+    let client = get_client(state).await?;
+    let item = auth::check(state, client.get_history_item(history_item_id).await).await?;
+    let stream = auth::check(state, client.get_history_audio(history_item_id).await).await?;
+
+    let cache = ensure_cache(state).await?;
+    let (path, received) = cache.save_audio_stream(&AudioType::Tts, stream, "mp3", |_| {}).await?;
+
+    let mut metadata = serde_json::json!({ HISTORY_ITEM_KEY: item.history_item_id });
+    if let Some(project_id) = project_id {
+        metadata["project_id"] = serde_json::json!(project_id);
+    }
+    let audio = GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type: AudioType::Tts,
+        prompt: item.text,
+        duration_seconds: estimate_duration_seconds(received, "mp3").unwrap_or(0.0),
+        local_path: path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata,
+        // Keep the original generation time rather than the download time
+        created_at: chrono::DateTime::from_timestamp(item.date_unix, 0)
+            .unwrap_or_else(chrono::Utc::now)
+            .to_rfc3339(),
+        params: Some(GenerationParams {
+            model_id: item.model_id,
+            voice_id: item.voice_id,
+            voice_settings: item.settings.and_then(|s| serde_json::from_value(s).ok()),
+            ..GenerationParams::new(PROVIDER_ELEVENLABS)
+        }),
+        cached: false,
+    };
+    let record = audio.clone();
+    state.call_db(move |conn| AudioCacheDb::save_audio_record(conn, &record)).await?;
+    Ok(audio)
+}
+
+/// A page of the provider's generation history, newest first. Pass the
+/// page's `last_history_item_id` as `start_after` for the next one.
+#[tauri::command]
+pub async fn eleven_labs_list_history(
+    state: State<'_, Arc<ElevenLabsState>>,
+    page_size: Option<u32>,
+    start_after: Option<String>,
+) -> Result<HistoryPage, AudioError> {
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let client = get_client(&state).await?;
+    auth::check(&state, client.list_history(page_size, start_after.as_deref()).await).await
+}
+
+/// Download a history item's audio into the local cache
+#[tauri::command]
+pub async fn eleven_labs_import_history_item(
+    state: State<'_, Arc<ElevenLabsState>>,
+    history_item_id: String,
+    project_id: Option<String>,
+) -> Result<GeneratedAudio, AudioError> {
+    import(&state, &history_item_id, project_id.as_deref()).await
+}
+
+/// Delete a history item on the provider. Copies already downloaded stay in
+/// the cache.
+#[tauri::command]
+pub async fn eleven_labs_delete_history_item(
+    state: State<'_, Arc<ElevenLabsState>>,
+    history_item_id: String,
+) -> Result<(), AudioError> {
+    let client = get_client(&state).await?;
+    auth::check(&state, client.delete_history_item(&history_item_id).await).await
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use super::billing::billable_characters;
+use super::cache::SettingsDb;
+use super::clipboard::ClipboardSpeakConfig;
+use super::error::AudioError;
+use super::speech_queue::{SpeakEvent, SpeechQueue};
+use super::ElevenLabsState;
+
+/// Settings key holding the hotkey bindings
+pub const HOTKEYS_KEY: &str = "audio_hotkeys";
+
+/// What a global shortcut does
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// Speak the current clipboard contents (copy a selection first)
+    SpeakClipboard,
+    /// Drop queued speech and stop the player
+    StopPlayback,
+    /// Play the last spoken render again
+    ReplayLast,
+}
+
+/// Accelerators for each action, e.g. `CmdOrCtrl+Shift+S`; `None` leaves it unbound
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub speak_clipboard: Option<String>,
+    #[serde(default)]
+    pub stop_playback: Option<String>,
+    #[serde(default)]
+    pub replay_last: Option<String>,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speak_clipboard: Some("CmdOrCtrl+Shift+S".to_string()),
+            stop_playback: Some("CmdOrCtrl+Shift+X".to_string()),
+            replay_last: Some("CmdOrCtrl+Shift+R".to_string()),
+        }
+    }
+}
+
+impl HotkeyConfig {
+    pub fn load(conn: &rusqlite::Connection) -> anyhow::Result<Self> {
+        match SettingsDb::get_setting(conn, HOTKEYS_KEY)? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Parse the configured accelerators
+    fn bindings(&self) -> Result<Vec<(Shortcut, HotkeyAction)>, AudioError> {
+        let mut bindings = vec![];
+        for (accelerator, action) in [
+            (&self.speak_clipboard, HotkeyAction::SpeakClipboard),
+            (&self.stop_playback, HotkeyAction::StopPlayback),
+            (&self.replay_last, HotkeyAction::ReplayLast),
+        ] {
+            let Some(accelerator) = accelerator.as_deref().filter(|a| !a.trim().is_empty()) else {
+                continue;
+            };
+            let shortcut: Shortcut = accelerator
+                .parse()
+                .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))?;
+            if bindings.iter().any(|(existing, _)| *existing == shortcut) {
+                return Err(AudioError::Validation(format!("Shortcut '{}' is bound twice", accelerator)));
+            }
+            bindings.push((shortcut, action));
+        }
+        Ok(bindings)
+    }
+}
+
+/// Replace the registered shortcuts with those from `config`
+fn apply(app: &AppHandle, state: &ElevenLabsState, config: &HotkeyConfig) -> Result<(), AudioError> {
+    let bindings = if config.enabled { config.bindings()? } else { vec![] };
+
+    let mut registered = state.hotkeys.lock()?;
+    for (shortcut, _) in registered.drain(..) {
+        let _ = app.global_shortcut().unregister(shortcut);
+    }
+
+    for (shortcut, action) in bindings {
+        app.global_shortcut()
+            .register(shortcut)
+            .map_err(|e| format!("Failed to register {:?} shortcut: {}", action, e))?;
+        registered.push((shortcut, action));
+    }
+
+    Ok(())
+}
+
+async fn run_action(app: &AppHandle, action: HotkeyAction) -> Result<(), AudioError> {
+    let queue = app.state::<SpeechQueue>();
+    match action {
+        HotkeyAction::SpeakClipboard => {
+            let text = app.clipboard().read_text().map_err(|e| AudioError::Other(e.to_string()))?;
+            let text = text.trim();
+            if text.is_empty() {
+                return Ok(());
+            }
+
+            let db = app.state::<Arc<ElevenLabsState>>().db()?;
+            let config = ClipboardSpeakConfig::load(&db).await?;
+            if billable_characters(text) > config.max_chars {
+                return Err(AudioError::Validation(format!("Clipboard text exceeds {} characters", config.max_chars)));
+            }
+            let voice_id = config.resolve_voice(&db).await?;
+            queue.enqueue(text.to_string(), voice_id, "hotkey")
+        }
+        HotkeyAction::StopPlayback => {
+            queue.stop(app);
+            Ok(())
+        }
+        HotkeyAction::ReplayLast => {
+            if let Some(audio) = queue.last() {
+                let _ = app.emit("audio-speak", SpeakEvent { source: "replay".to_string(), audio });
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Global shortcut handler: dispatch a pressed shortcut to its audio action
+pub fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut) {
+    let state = app.state::<Arc<ElevenLabsState>>();
+    let action = state
+        .hotkeys
+        .lock()
+        .ok()
+        .and_then(|bindings| bindings.iter().find(|(s, _)| s == shortcut).map(|(_, a)| *a));
+
+    if let Some(action) = action {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = run_action(&app, action).await {
+                log::warn!("Hotkey {:?} failed: {}", action, e);
+            }
+        });
+    }
+}
+
+/// Register the saved shortcuts at launch, from the synchronous setup hook
+pub fn register_saved(app: &AppHandle) -> Result<(), AudioError> {
+    let state = app.state::<Arc<ElevenLabsState>>();
+    let config = state.db()?.with_blocking(|conn| HotkeyConfig::load(conn))?;
+    apply(app, &state, &config)
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the audio hotkey configuration
+#[tauri::command]
+pub async fn get_audio_hotkeys(state: State<'_, Arc<ElevenLabsState>>) -> Result<HotkeyConfig, AudioError> {
+    state.call_db(|conn| HotkeyConfig::load(conn)).await
+}
+
+/// Save and re-register the audio hotkeys
+#[tauri::command]
+pub async fn set_audio_hotkeys(
+    app: AppHandle,
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: HotkeyConfig,
+) -> Result<HotkeyConfig, AudioError> {
+    apply(&app, &state, &config)?;
+
+    let json = serde_json::to_string(&config)?;
+    state.call_db(move |conn| SettingsDb::save_setting(conn, HOTKEYS_KEY, &json)).await?;
+
+    Ok(config)
+}
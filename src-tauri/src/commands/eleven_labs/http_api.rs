@@ -0,0 +1,396 @@
+use axum::{
+    extract::{Path, Query, Request, State as AxumState},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::{AudioCacheDb, CharacterVoiceDb, SettingsDb};
+use super::error::{AudioError, ProviderErrorKind};
+use super::pipeline;
+use super::secrets;
+use super::supervisor::{TaskHandle, SHUTDOWN_GRACE};
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Settings key holding the HTTP API configuration
+const HTTP_API_CONFIG_KEY: &str = "http_api";
+
+/// Keyring entry holding the HTTP API bearer token
+const HTTP_API_TOKEN_SECRET: &str = "audio-http-api-token";
+
+const DEFAULT_HTTP_API_PORT: u16 = 8766;
+
+/// Persisted configuration of the local REST API (disabled by default)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for HttpApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_HTTP_API_PORT,
+        }
+    }
+}
+
+/// Configuration plus runtime details returned to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpApiStatus {
+    pub config: HttpApiConfig,
+    pub running: bool,
+    pub base_url: Option<String>,
+    pub token: Option<String>,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    audio: Arc<ElevenLabsState>,
+    token: Arc<String>,
+}
+
+type ApiResult<T> = Result<Json<T>, (StatusCode, Json<serde_json::Value>)>;
+
+fn api_error(status: StatusCode, message: String) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(json!({ "error": message })))
+}
+
+/// HTTP status for an audio error. A key the provider rejects is a gateway
+/// failure, not a problem with the caller's bearer token.
+fn error_status(error: &AudioError) -> StatusCode {
+    match error {
+        AudioError::Validation(_) => StatusCode::BAD_REQUEST,
+        AudioError::NotConfigured(_) => StatusCode::SERVICE_UNAVAILABLE,
+        AudioError::Cancelled => StatusCode::CONFLICT,
+        AudioError::Provider { kind, .. } => match kind {
+            ProviderErrorKind::Unauthorized => StatusCode::BAD_GATEWAY,
+            ProviderErrorKind::QuotaExceeded | ProviderErrorKind::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        },
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Respond with an audio error, keeping its code and retryable flag for API
+/// clients
+fn audio_error(error: AudioError) -> (StatusCode, Json<serde_json::Value>) {
+    let status = error_status(&error);
+    let body = json!({ "error": error.to_string(), "code": error.code(), "retryable": error.retryable() });
+    (status, Json(body))
+}
+
+/// Compare tokens without short-circuiting on the first differing byte
+pub(crate) fn tokens_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn require_token(AxumState(state): AxumState<ApiState>, request: Request, next: Next) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if tokens_match(token, &state.token) => next.run(request).await,
+        _ => api_error(StatusCode::UNAUTHORIZED, "Missing or invalid token".to_string()).into_response(),
+    }
+}
+
+async fn post_tts(
+    AxumState(state): AxumState<ApiState>,
+    Json(request): Json<TtsRequest>,
+) -> ApiResult<GeneratedAudio> {
+    pipeline::generate_tts(&state.audio, request).await.map(Json).map_err(audio_error)
+}
+
+async fn post_sfx(
+    AxumState(state): AxumState<ApiState>,
+    Json(request): Json<SfxRequest>,
+) -> ApiResult<GeneratedAudio> {
+    pipeline::generate_sfx(&state.audio, request).await.map(Json).map_err(audio_error)
+}
+
+async fn get_voices(AxumState(state): AxumState<ApiState>) -> ApiResult<Vec<VoiceProfile>> {
+    pipeline::list_voices(&state.audio).await.map(Json).map_err(audio_error)
+}
+
+#[derive(Deserialize)]
+struct LibraryQuery {
+    #[serde(rename = "type", default = "default_library_type")]
+    audio_type: String,
+}
+
+fn default_library_type() -> String {
+    "tts".to_string()
+}
+
+async fn get_library(
+    AxumState(state): AxumState<ApiState>,
+    Query(query): Query<LibraryQuery>,
+) -> ApiResult<Vec<GeneratedAudio>> {
+    let audio_type =
+        AudioType::parse(&query.audio_type).map_err(audio_error)?;
+
+    state
+        .audio
+        .call_db(move |conn| AudioCacheDb::get_audio_records(conn, &audio_type))
+        .await
+        .map(Json)
+        .map_err(audio_error)
+}
+
+async fn find_record(state: &ApiState, id: &str) -> Result<GeneratedAudio, (StatusCode, Json<serde_json::Value>)> {
+    let audio_id = id.to_string();
+    state
+        .audio
+        .call_db(move |conn| AudioCacheDb::get_audio_record(conn, &audio_id))
+        .await
+        .map_err(audio_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, format!("Audio not found: {}", id)))
+}
+
+async fn get_library_item(
+    AxumState(state): AxumState<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<GeneratedAudio> {
+    find_record(&state, &id).await.map(Json)
+}
+
+async fn get_library_file(AxumState(state): AxumState<ApiState>, Path(id): Path<String>) -> Response {
+    let record = match find_record(&state, &id).await {
+        Ok(record) => record,
+        Err(e) => return e.into_response(),
+    };
+
+    match tokio::fs::read(&record.local_path).await {
+        Ok(data) => ([(header::CONTENT_TYPE, "audio/mpeg")], data).into_response(),
+        Err(e) => audio_error(AudioError::Other(format!("Failed to read audio file: {}", e))).into_response(),
+    }
+}
+
+async fn delete_library_item(
+    AxumState(state): AxumState<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<serde_json::Value> {
+    pipeline::delete_audio(&state.audio, &id).await.map_err(audio_error)?;
+    Ok(Json(json!({ "deleted": id })))
+}
+
+#[derive(Deserialize)]
+struct CharacterQuery {
+    project_id: Option<String>,
+}
+
+async fn get_characters(
+    AxumState(state): AxumState<ApiState>,
+    Query(query): Query<CharacterQuery>,
+) -> ApiResult<Vec<CharacterVoice>> {
+    state
+        .audio
+        .call_db(move |conn| CharacterVoiceDb::get_character_voices(conn, query.project_id.as_deref()))
+        .await
+        .map(Json)
+        .map_err(audio_error)
+}
+
+#[derive(Deserialize)]
+struct AssignBody {
+    character_name: String,
+    voice_id: String,
+    voice_name: Option<String>,
+    project_id: Option<String>,
+}
+
+async fn post_character(
+    AxumState(state): AxumState<ApiState>,
+    Json(body): Json<AssignBody>,
+) -> ApiResult<CharacterVoice> {
+    let db = state.audio.db().map_err(audio_error)?;
+    pipeline::assign_voice(&db, body.character_name, body.voice_id, body.voice_name, body.project_id)
+        .await
+        .map(Json)
+        .map_err(audio_error)
+}
+
+/// Build the REST router; every route requires `Authorization: Bearer <token>`
+fn router(audio: Arc<ElevenLabsState>, token: String) -> Router {
+    let state = ApiState {
+        audio,
+        token: Arc::new(token),
+    };
+
+    Router::new()
+        .route("/api/audio/tts", post(post_tts))
+        .route("/api/audio/sfx", post(post_sfx))
+        .route("/api/audio/voices", get(get_voices))
+        .route("/api/audio/library", get(get_library))
+        .route(
+            "/api/audio/library/{id}",
+            get(get_library_item).delete(delete_library_item),
+        )
+        .route("/api/audio/library/{id}/file", get(get_library_file))
+        .route("/api/audio/characters", get(get_characters).post(post_character))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+async fn load_config(state: &ElevenLabsState) -> Result<HttpApiConfig, AudioError> {
+    match state.call_db(|conn| SettingsDb::get_setting(conn, HTTP_API_CONFIG_KEY)).await? {
+        Some(raw) => Ok(serde_json::from_str(&raw)?),
+        None => Ok(HttpApiConfig::default()),
+    }
+}
+
+/// Get the API token, generating one on first use. The keyring blocks, so
+/// this runs on the blocking pool.
+pub(crate) async fn ensure_token() -> Result<String, AudioError> {
+    let token = tokio::task::spawn_blocking(|| -> anyhow::Result<String> {
+        if let Some(token) = secrets::get_secret(HTTP_API_TOKEN_SECRET)? {
+            return Ok(token);
+        }
+        let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        secrets::store_secret(HTTP_API_TOKEN_SECRET, &token)?;
+        Ok(token)
+    })
+    .await??;
+    Ok(token)
+}
+
+/// Stop the running server, returning once its port is released
+async fn stop_server(handle: Option<TaskHandle>) {
+    if let Some(handle) = handle {
+        handle.stop(SHUTDOWN_GRACE).await;
+    }
+}
+
+async fn start_server(state: &Arc<ElevenLabsState>, port: u16) -> Result<(), AudioError> {
+    let token = ensure_token().await?;
+
+    let mut handle_guard = state.http_api.lock().await;
+    stop_server(handle_guard.take()).await;
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("Failed to bind port {}: {}", port, e))?;
+    let app = router(Arc::clone(state), token);
+
+    *handle_guard = Some(state.tasks.spawn("audio-http-api", |shutdown| async move {
+        let serve = axum::serve(listener, app).with_graceful_shutdown(async move { shutdown.wait().await });
+        if let Err(e) = serve.await {
+            log::error!("Audio HTTP API stopped: {}", e);
+        }
+    }));
+
+    log::info!("Audio HTTP API listening on 127.0.0.1:{}", port);
+    Ok(())
+}
+
+async fn status(state: &ElevenLabsState, config: HttpApiConfig) -> Result<HttpApiStatus, AudioError> {
+    let running = state.http_api.lock().await.is_some();
+    Ok(HttpApiStatus {
+        base_url: running.then(|| format!("http://127.0.0.1:{}/api/audio", config.port)),
+        token: if config.enabled {
+            tokio::task::spawn_blocking(|| secrets::get_secret(HTTP_API_TOKEN_SECRET)).await??
+        } else {
+            None
+        },
+        running,
+        config,
+    })
+}
+
+/// Start the HTTP API at app launch if the user enabled it
+pub async fn start_if_enabled(state: &Arc<ElevenLabsState>) -> Result<(), AudioError> {
+    let config = load_config(state).await?;
+
+    if config.enabled {
+        start_server(state, config.port).await?;
+    }
+    Ok(())
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the local HTTP API configuration and status
+#[tauri::command]
+pub async fn get_audio_http_api_status(
+    state: State<'_, Arc<ElevenLabsState>>,
+) -> Result<HttpApiStatus, AudioError> {
+    let config = load_config(&state).await?;
+
+    status(&state, config).await
+}
+
+/// Enable or disable the local HTTP API, starting or stopping the server
+#[tauri::command]
+pub async fn set_audio_http_api_enabled(
+    state: State<'_, Arc<ElevenLabsState>>,
+    enabled: bool,
+    port: Option<u16>,
+) -> Result<HttpApiStatus, AudioError> {
+    let mut config = load_config(&state).await?;
+    config.enabled = enabled;
+    if let Some(port) = port {
+        config.port = port;
+    }
+    let value = serde_json::to_string(&config)?;
+    state.call_db(move |conn| SettingsDb::save_setting(conn, HTTP_API_CONFIG_KEY, &value)).await?;
+
+    if enabled {
+        start_server(&state, config.port).await?;
+    } else {
+        let mut handle_guard = state.http_api.lock().await;
+        stop_server(handle_guard.take()).await;
+    }
+
+    status(&state, config).await
+}
+
+/// Replace the HTTP API token, invalidating the old one
+#[tauri::command]
+pub async fn regenerate_audio_http_api_token(
+    state: State<'_, Arc<ElevenLabsState>>,
+) -> Result<HttpApiStatus, AudioError> {
+    tokio::task::spawn_blocking(|| secrets::delete_secret(HTTP_API_TOKEN_SECRET)).await??;
+
+    let config = load_config(&state).await?;
+
+    ensure_token().await?;
+    if config.enabled {
+        start_server(&state, config.port).await?;
+    }
+
+    status(&state, config).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_status() {
+        let status = |error: AudioError| error_status(&error).as_u16();
+        assert_eq!(status(AudioError::Validation("bad".to_string())), 400);
+        assert_eq!(status(AudioError::NotConfigured("no key".to_string())), 503);
+        assert_eq!(status(AudioError::provider(ProviderErrorKind::Unauthorized, "bad key")), 502);
+        assert_eq!(status(AudioError::provider(ProviderErrorKind::QuotaExceeded, "quota")), 429);
+        assert_eq!(status(AudioError::provider(ProviderErrorKind::RateLimited, "slow down")), 429);
+        assert_eq!(status(AudioError::Cancelled), 409);
+        assert_eq!(status(AudioError::Other("boom".to_string())), 500);
+    }
+}
@@ -0,0 +1,796 @@
+// End-to-end flows through the pipeline against the mock provider in
+// `api::fake`, with an in-memory database and a temporary cache directory.
+
+use std::path::Path;
+use std::sync::Arc;
+use wiremock::matchers::{method, path, path_regex};
+use wiremock::{Mock, ResponseTemplate};
+
+use super::api::fake::{FakeElevenLabs, AUDIO};
+use super::api::ElevenLabsApi;
+use super::cache::{AudioCacheDb, AudioDb, SettingsDb, VoiceProfileDb};
+use super::error::AudioError;
+use super::types::*;
+use super::{
+    audio_tags, auth, compare, dubbing, history, pipeline, presets, reconcile, schema, transcriptions, trash, usage_history,
+    voice_design, voice_library, ElevenLabsState, AUDIO_CACHE_DIR_KEY,
+};
+
+struct Harness {
+    state: ElevenLabsState,
+    fake: FakeElevenLabs,
+    _cache_dir: tempfile::TempDir,
+}
+
+async fn harness() -> Harness {
+    let fake = FakeElevenLabs::start().await;
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    schema::init(&conn).unwrap();
+    SettingsDb::save_setting(&conn, AUDIO_CACHE_DIR_KEY, &cache_dir.path().to_string_lossy()).unwrap();
+
+    let state = ElevenLabsState::new();
+    *state.db.lock().unwrap() = Some(AudioDb::from_connection(conn));
+    state.set_client(fake.api()).await;
+
+    Harness {
+        state,
+        fake,
+        _cache_dir: cache_dir,
+    }
+}
+
+fn tts(text: &str) -> TtsRequest {
+    TtsRequest {
+        text: text.to_string(),
+        voice_id: "rachel".to_string(),
+        model_id: default_model_id(),
+        voice_settings: None,
+        output_format: default_output_format(),
+        normalize_text: None,
+        preset: None,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
+    }
+}
+
+#[tokio::test]
+async fn test_tts_is_cached_and_recorded() {
+    let Harness { state, fake, .. } = harness().await;
+
+    let audio = pipeline::generate_tts(&state, tts("Hello there")).await.unwrap();
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO);
+    assert_eq!(audio.voice_id(), Some("rachel"));
+
+    let saved = state
+        .with_db(|conn| AudioCacheDb::get_audio_record(conn, &audio.id))
+        .unwrap()
+        .unwrap();
+    assert_eq!(saved.params, audio.params);
+    let history = state
+        .with_db(|conn| usage_history::get_history(conn, &Default::default(), Default::default()))
+        .unwrap();
+    assert_eq!((history[0].generations, history[0].characters), (1, 11));
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let speech = requests.iter().find(|r| r.url.path() == "/text-to-speech/rachel").unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&speech.body).unwrap();
+    assert_eq!(body["text"], "Hello there");
+    assert_eq!(speech.headers.get("xi-api-key").unwrap(), "test-key");
+}
+
+#[tokio::test]
+async fn test_trashed_audio_is_restored_or_purged() {
+    let Harness { state, .. } = harness().await;
+    let request = |text: &str| SfxRequest {
+        text: text.to_string(),
+        duration_seconds: 2.0,
+        prompt_influence: 0.3,
+    };
+    let kept = pipeline::generate_sfx(&state, request("Door creak")).await.unwrap();
+    let purged = pipeline::generate_sfx(&state, request("Glass break")).await.unwrap();
+    let listed = || {
+        state
+            .with_db(|conn| AudioCacheDb::get_audio_records(conn, &AudioType::Sfx))
+            .unwrap()
+            .into_iter()
+            .map(|audio| audio.id)
+            .collect::<Vec<_>>()
+    };
+
+    trash::trash_audio(&state, &kept.id).await.unwrap();
+    trash::trash_audio(&state, &purged.id).await.unwrap();
+    assert!(listed().is_empty());
+    let trashed = state.with_db(|conn| AudioCacheDb::get_trashed_records(conn, None)).unwrap();
+    assert_eq!(trashed.len(), 2);
+    // Trashed files are not orphans
+    let report = reconcile::reconcile(&state, None, false).await.unwrap();
+    assert!(report.orphan_files.is_empty());
+
+    // Nothing has been in the trash for a day yet
+    assert_eq!(trash::purge_trash(&state, 1).await.unwrap(), 0);
+    assert!(state.with_db(|conn| AudioCacheDb::restore_audio_record(conn, &kept.id)).unwrap());
+    assert_eq!(listed(), vec![kept.id.clone()]);
+
+    assert_eq!(trash::purge_trash(&state, 0).await.unwrap(), 1);
+    assert!(tokio::fs::metadata(&purged.local_path).await.is_err());
+    assert!(tokio::fs::metadata(&kept.local_path).await.is_ok());
+    assert!(state.with_db(|conn| AudioCacheDb::get_audio_record(conn, &purged.id)).unwrap().is_none());
+    assert!(trash::trash_audio(&state, &purged.id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_batch_delete_by_tag() {
+    let Harness { state, .. } = harness().await;
+    let mut clips = Vec::new();
+    for text in ["Door creak", "Glass break", "Footsteps"] {
+        let request = SfxRequest {
+            text: text.to_string(),
+            duration_seconds: 2.0,
+            prompt_influence: 0.3,
+        };
+        clips.push(pipeline::generate_sfx(&state, request).await.unwrap());
+    }
+    for audio in &clips[..2] {
+        let id = audio.id.clone();
+        state.with_db(move |conn| audio_tags::add_tags(conn, &id, &["junk".to_string()])).unwrap();
+    }
+
+    let error = pipeline::delete_audio_batch(&state, None, AudioQuery::default(), true).await.unwrap_err();
+    assert!(matches!(error, AudioError::Validation(_)));
+    let sfx_only = pipeline::delete_audio_batch(&state, Some(AudioType::Sfx), AudioQuery::default(), true).await;
+    assert!(matches!(sfx_only.unwrap_err(), AudioError::Validation(_)));
+
+    let junk = AudioQuery { tag: Some("Junk".to_string()), ..Default::default() };
+    let trashed = pipeline::delete_audio_batch(&state, None, junk.clone(), false).await.unwrap();
+    assert_eq!((trashed.deleted, trashed.bytes_freed), (2, 0));
+    assert!(tokio::fs::metadata(&clips[0].local_path).await.is_ok());
+    let in_trash = state.with_db(|conn| AudioCacheDb::get_trashed_records(conn, None)).unwrap();
+    assert_eq!(in_trash.len(), 2);
+    for audio in &clips[..2] {
+        let id = audio.id.clone();
+        state.with_db(move |conn| AudioCacheDb::restore_audio_record(conn, &id)).unwrap();
+    }
+
+    let result = pipeline::delete_audio_batch(&state, None, junk, true).await.unwrap();
+    assert_eq!(result.deleted, 2);
+    assert_eq!(result.bytes_freed, 2 * AUDIO.len() as u64);
+    assert!(tokio::fs::metadata(&clips[0].local_path).await.is_err());
+    let left = state.with_db(|conn| AudioCacheDb::get_audio_records(conn, &AudioType::Sfx)).unwrap();
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].id, clips[2].id);
+}
+
+#[tokio::test]
+async fn test_sfx_delete_removes_file_and_record() {
+    let Harness { state, .. } = harness().await;
+
+    let request = SfxRequest {
+        text: "Door creak".to_string(),
+        duration_seconds: 2.0,
+        prompt_influence: 0.3,
+    };
+    let audio = pipeline::generate_sfx(&state, request).await.unwrap();
+    assert!(tokio::fs::metadata(&audio.local_path).await.is_ok());
+
+    pipeline::delete_audio(&state, &audio.id).await.unwrap();
+    assert!(tokio::fs::metadata(&audio.local_path).await.is_err());
+    assert!(state
+        .with_db(|conn| AudioCacheDb::get_audio_record(conn, &audio.id))
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_rejected_key_drops_client() {
+    let Harness { state, fake, .. } = harness().await;
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/text-to-speech/"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("invalid_api_key"))
+        .with_priority(1)
+        .mount(&fake.server)
+        .await;
+
+    let error = pipeline::generate_tts(&state, tts("Hello")).await.unwrap_err();
+    assert!(auth::is_unauthorized(&error));
+    assert!(state.auth.is_rejected());
+    assert!(state.client.read().await.is_none());
+}
+
+#[tokio::test]
+async fn test_voices_refresh_and_clone() {
+    let Harness { state, fake, .. } = harness().await;
+
+    let list = pipeline::refresh_voices(&state).await.unwrap();
+    assert_eq!(list.voices.len(), 2);
+    assert_eq!(
+        state.with_db(|conn| SettingsDb::get_setting(conn, "voices_etag")).unwrap().as_deref(),
+        Some("\"v1\"")
+    );
+
+    let sample = tempfile::Builder::new().suffix(".wav").tempfile().unwrap();
+    std::fs::write(sample.path(), b"RIFF\x24\0\0\0WAVEfmt sample").unwrap();
+    let client: Arc<dyn ElevenLabsApi> = fake.api();
+    let voice = client
+        .clone_voice(VoiceCloneRequest {
+            name: "Narrator".to_string(),
+            description: None,
+            labels: None,
+            files: vec![sample.path().to_string_lossy().to_string()],
+        })
+        .await
+        .unwrap();
+    assert_eq!(voice.voice_id, "cloned");
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let upload = requests.iter().find(|r| r.url.path() == "/voices/add").unwrap();
+    assert!(String::from_utf8_lossy(&upload.body).contains("Content-Type: audio/wav"));
+
+    client
+        .edit_voice(VoiceEditRequest {
+            voice_id: "cloned".to_string(),
+            description: Some("Warm and slow".to_string()),
+            add_files: vec![sample.path().to_string_lossy().to_string()],
+            remove_sample_ids: vec!["s-1".to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let requests = fake.server.received_requests().await.unwrap();
+    let edit = requests.iter().find(|r| r.url.path() == "/voices/cloned/edit").unwrap();
+    let body = String::from_utf8_lossy(&edit.body);
+    // The name isn't changing, so the current one is sent
+    assert!(body.contains("Voice cloned") && body.contains("Warm and slow"));
+    assert!(requests.iter().any(|r| r.url.path() == "/voices/cloned/samples/s-1" && r.method.as_str() == "DELETE"));
+
+    Mock::given(method("DELETE"))
+        .and(path("/voices/cloned"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("voice_not_found"))
+        .with_priority(1)
+        .mount(&fake.server)
+        .await;
+    assert!(client.delete_voice("cloned").await.is_err());
+    assert!(client.delete_voice("rachel").await.is_ok());
+
+    let models = client.list_models().await.unwrap();
+    let sts: Vec<_> = models.iter().filter(|m| m.can_do_voice_conversion).map(|m| m.model_id.as_str()).collect();
+    assert_eq!(sts, vec![default_sts_model_id().as_str()]);
+    assert_eq!(models[0].languages.len(), 2);
+}
+
+#[tokio::test]
+async fn test_regenerate_links_takes() {
+    let Harness { state, fake, .. } = harness().await;
+
+    let original = pipeline::generate_tts(&state, tts("Take one")).await.unwrap();
+    let overrides = RegenerateOverrides {
+        voice_id: Some("adam".to_string()),
+        ..Default::default()
+    };
+    let take = pipeline::regenerate_audio(&state, &original.id, overrides, None).await.unwrap();
+    assert_eq!(take.prompt, "Take one");
+    assert_eq!(take.voice_id(), Some("adam"));
+    assert_eq!(take.metadata[TAKE_OF_KEY], original.id.as_str());
+    assert_eq!(take.params.as_ref().unwrap().model_id, original.params.as_ref().unwrap().model_id);
+
+    let requests = fake.server.received_requests().await.unwrap();
+    assert!(requests.iter().any(|r| r.url.path() == "/text-to-speech/adam"));
+
+    // A take of a take joins the original's group
+    let again = pipeline::regenerate_audio(&state, &take.id, RegenerateOverrides::default(), None)
+        .await
+        .unwrap();
+    assert_eq!(again.metadata[TAKE_OF_KEY], original.id.as_str());
+    let takes = state.with_db(|conn| AudioCacheDb::get_takes(conn, &take.id)).unwrap();
+    assert_eq!(takes.len(), 3);
+    assert_eq!(takes[0].id, original.id);
+}
+
+#[tokio::test]
+async fn test_streamed_tts_taps_every_chunk() {
+    let Harness { state, .. } = harness().await;
+
+    let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let tap: pipeline::ChunkTap = Box::new(move |chunk| sink.lock().unwrap().extend_from_slice(chunk));
+    let transport = pipeline::TtsTransport::Http;
+    let audio = pipeline::generate_tts_streamed(&state, tts("Streamed"), None, tap, transport).await.unwrap();
+
+    assert_eq!(received.lock().unwrap().as_slice(), AUDIO);
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO);
+}
+
+#[tokio::test]
+async fn test_tts_parts_are_stitched() {
+    let Harness { state, fake, .. } = harness().await;
+
+    let parts = ["One.", "Two.", "Three.", "Four.", "Five."].map(String::from).to_vec();
+    let audios = pipeline::generate_tts_parts_for_project(&state, tts(""), parts, None).await.unwrap();
+    let ids: Vec<_> = audios.iter().map(|audio| audio.metadata[REQUEST_ID_KEY].as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["request-1", "request-2", "request-3", "request-4", "request-5"]);
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let bodies: Vec<serde_json::Value> = requests
+        .iter()
+        .filter(|r| r.url.path().starts_with("/text-to-speech/"))
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect();
+    assert_eq!(bodies[0]["next_text"], "Two.");
+    assert!(bodies[0].get("previous_text").is_none() && bodies[0].get("previous_request_ids").is_none());
+    assert_eq!(bodies[2]["previous_text"], "Two.");
+    assert_eq!(bodies[2]["previous_request_ids"], serde_json::json!(["request-1", "request-2"]));
+    // Only the latest requests are passed on
+    assert_eq!(bodies[4]["previous_request_ids"], serde_json::json!(["request-2", "request-3", "request-4"]));
+    assert!(bodies[4].get("next_text").is_none());
+}
+
+#[tokio::test]
+async fn test_long_tts_is_rendered_in_parts() {
+    let Harness { state, fake, .. } = harness().await;
+
+    // eleven_v3 takes 3,000 characters a request
+    let text = "This sentence is part of a long text. ".repeat(180);
+    let request = TtsRequest { model_id: "eleven_v3".to_string(), ..tts(&text) };
+    let audio = pipeline::generate_tts(&state, request).await.unwrap();
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO.repeat(3));
+
+    let chunks: Vec<SpeechChunk> = serde_json::from_value(audio.metadata[CHUNKS_KEY].clone()).unwrap();
+    let parts: Vec<_> = chunks.iter().map(|chunk| (chunk.request_id.as_deref(), chunk.offset)).collect();
+    let len = AUDIO.len() as u64;
+    assert_eq!(parts, vec![(Some("request-1"), 0), (Some("request-2"), len), (Some("request-3"), 2 * len)]);
+    assert_eq!(chunks.iter().map(|chunk| chunk.text.as_str()).collect::<Vec<_>>().join(" "), text.trim());
+    assert_eq!(audio.metadata[REQUEST_ID_KEY], "request-3");
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let last = requests.iter().rev().find(|r| r.url.path().starts_with("/text-to-speech/")).unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&last.body).unwrap();
+    assert_eq!(body["previous_request_ids"], serde_json::json!(["request-1", "request-2"]));
+    assert_eq!(body["previous_text"], chunks[1].text);
+}
+
+#[tokio::test]
+async fn test_identical_tts_request_reuses_record() {
+    let Harness { state, fake, .. } = harness().await;
+    let tts_requests = || async {
+        let requests = fake.server.received_requests().await.unwrap();
+        requests.iter().filter(|r| r.url.path().starts_with("/text-to-speech/")).count()
+    };
+
+    let first = pipeline::generate_tts_or_reuse(&state, tts("Once"), None, false).await.unwrap();
+    assert!(!first.cached);
+    let again = pipeline::generate_tts_or_reuse(&state, tts("Once"), None, false).await.unwrap();
+    assert!(again.cached);
+    assert_eq!(again.id, first.id);
+    assert_eq!(tts_requests().await, 1);
+
+    // Different settings, a forced render or a missing file each render anew
+    let other = TtsRequest { voice_settings: Some(VoiceSettings::default()), ..tts("Once") };
+    assert!(!pipeline::generate_tts_or_reuse(&state, other, None, false).await.unwrap().cached);
+    let forced = pipeline::generate_tts_or_reuse(&state, tts("Once"), None, true).await.unwrap();
+    assert_ne!(forced.id, first.id);
+    tokio::fs::remove_file(&forced.local_path).await.unwrap();
+    let rendered = pipeline::generate_tts_or_reuse(&state, tts("Once"), None, false).await.unwrap();
+    assert_eq!(rendered.id, first.id);
+    assert!(rendered.cached);
+    tokio::fs::remove_file(&first.local_path).await.unwrap();
+    assert!(!pipeline::generate_tts_or_reuse(&state, tts("Once"), None, false).await.unwrap().cached);
+    assert_eq!(tts_requests().await, 4);
+}
+
+#[tokio::test]
+async fn test_reconcile_finds_and_repairs_cache_mismatches() {
+    let Harness { state, .. } = harness().await;
+    let kept = pipeline::generate_tts(&state, tts("Kept")).await.unwrap();
+    let lost = pipeline::generate_tts(&state, tts("Lost")).await.unwrap();
+    tokio::fs::remove_file(&lost.local_path).await.unwrap();
+    let stray = Path::new(&kept.local_path).with_file_name("stray.mp3");
+    tokio::fs::write(&stray, AUDIO).await.unwrap();
+
+    // A scan alone changes nothing
+    let report = reconcile::reconcile(&state, None, false).await.unwrap();
+    assert_eq!(report.orphan_files, vec![stray.to_string_lossy().to_string()]);
+    assert_eq!(report.dangling_records.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), vec![lost.id.as_str()]);
+    assert_eq!(reconcile::reconcile(&state, None, false).await.unwrap().orphan_files.len(), 1);
+
+    let report = reconcile::reconcile(&state, Some(reconcile::OrphanRepair::Register), true).await.unwrap();
+    assert_eq!(report.removed_records, 1);
+    let registered = &report.registered[0];
+    assert_eq!(registered.audio_type, AudioType::Tts);
+    assert!(state.with_db(|conn| AudioCacheDb::get_audio_record(conn, &lost.id)).unwrap().is_none());
+    assert!(state.with_db(|conn| AudioCacheDb::get_audio_record(conn, &registered.id)).unwrap().is_some());
+
+    let clean = reconcile::reconcile(&state, Some(reconcile::OrphanRepair::Delete), true).await.unwrap();
+    assert!(clean.orphan_files.is_empty() && clean.dangling_records.is_empty());
+}
+
+#[tokio::test]
+async fn test_regenerate_with_same_seed() {
+    let Harness { state, fake, .. } = harness().await;
+    let last_body = || async {
+        let requests = fake.server.received_requests().await.unwrap();
+        let last = requests.iter().rev().find(|r| r.url.path().starts_with("/text-to-speech/")).unwrap();
+        serde_json::from_slice::<serde_json::Value>(&last.body).unwrap()
+    };
+
+    let original = pipeline::generate_tts(&state, TtsRequest { seed: Some(42), ..tts("Again") }).await.unwrap();
+    assert_eq!(original.metadata[SEED_KEY], 42);
+    assert_eq!(last_body().await["seed"], 42);
+
+    let take = pipeline::regenerate_with_same_seed(&state, &original.id, None).await.unwrap();
+    assert_eq!(last_body().await["seed"], 42);
+    assert_eq!(take.metadata[SEED_KEY], 42);
+    assert_eq!(take.metadata[TAKE_OF_KEY], original.id.as_str());
+
+    // An ordinary take varies
+    let varied = pipeline::regenerate_audio(&state, &original.id, RegenerateOverrides::default(), None).await.unwrap();
+    assert!(last_body().await.get("seed").is_none());
+    assert!(varied.metadata.get(SEED_KEY).is_none());
+    assert!(matches!(
+        pipeline::regenerate_with_same_seed(&state, &varied.id, None).await,
+        Err(AudioError::Validation(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_tts_with_timestamps_stores_word_alignment() {
+    let Harness { state, .. } = harness().await;
+
+    let audio = pipeline::generate_tts_with_timestamps_for_project(&state, tts("Hi you"), None).await.unwrap();
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO);
+    let alignment: SpeechAlignment = serde_json::from_value(audio.metadata[ALIGNMENT_KEY].clone()).unwrap();
+    let words: Vec<_> = alignment.words.iter().map(|w| (w.word.as_str(), w.start, w.end, w.char_start)).collect();
+    assert_eq!(words, vec![("Hi", 0.0, 0.2, 0), ("you", 0.3, 0.6, 3)]);
+
+    // A regenerated take gets timings of its own
+    let take = pipeline::regenerate_audio(&state, &audio.id, RegenerateOverrides::default(), None).await.unwrap();
+    assert_eq!(take.metadata[ALIGNMENT_KEY], audio.metadata[ALIGNMENT_KEY]);
+    assert_eq!(take.metadata[TAKE_OF_KEY], serde_json::json!(audio.id));
+}
+
+#[tokio::test]
+async fn test_tts_with_metadata_is_saved_with_it() {
+    let Harness { state, .. } = harness().await;
+
+    let mut metadata = serde_json::Map::new();
+    metadata.insert("scene_id".to_string(), serde_json::json!("intro"));
+    let audio = pipeline::generate_tts_with_metadata(&state, tts("Line one"), None, metadata).await.unwrap();
+    assert_eq!(audio.metadata["scene_id"], "intro");
+    let saved = state.with_db(|conn| AudioCacheDb::get_audio_record(conn, &audio.id)).unwrap().unwrap();
+    assert_eq!(saved.metadata, audio.metadata);
+}
+
+#[tokio::test]
+async fn test_tts_download_reports_progress() {
+    let Harness { state, .. } = harness().await;
+
+    let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = reports.clone();
+    let progress: pipeline::ProgressTap = Box::new(move |received, expected| {
+        sink.lock().unwrap().push((received, expected));
+    });
+    let audio = pipeline::generate_tts_with_progress(&state, tts("Tracked"), None, progress).await.unwrap();
+
+    let total = AUDIO.len() as u64;
+    assert_eq!(reports.lock().unwrap().last(), Some(&(total, Some(total))));
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO);
+}
+
+#[tokio::test]
+async fn test_websocket_tts_assembles_frames() {
+    use axum::extract::ws::{Message, WebSocketUpgrade};
+    use base64::Engine;
+    use futures::StreamExt;
+
+    // Records the messages sent by the client, then answers with the audio
+    // in base64 frames and a final frame
+    let (sent_tx, mut sent_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let router = axum::Router::new().route(
+        "/text-to-speech/{voice_id}/stream-input",
+        axum::routing::get(move |ws: WebSocketUpgrade| async move {
+            ws.on_upgrade(move |mut socket| async move {
+                for _ in 0..3 {
+                    if let Some(Ok(Message::Text(text))) = socket.recv().await {
+                        let _ = sent_tx.send(text.to_string());
+                    }
+                }
+                for chunk in AUDIO.chunks(3) {
+                    let audio = base64::engine::general_purpose::STANDARD.encode(chunk);
+                    let frame = serde_json::json!({ "audio": audio, "isFinal": null });
+                    let _ = socket.send(Message::Text(frame.to_string().into())).await;
+                }
+                let _ = socket.send(Message::Text(r#"{"isFinal":true}"#.into())).await;
+            })
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = super::client::ElevenLabsClient::with_base_url("key".to_string(), &base_url).unwrap();
+    let stream = client.text_to_speech_websocket(tts("Over the socket")).await.unwrap();
+    let chunks: Vec<_> = stream.map(|chunk| chunk.unwrap()).collect().await;
+
+    assert!(chunks.len() > 1);
+    assert_eq!(chunks.concat(), AUDIO);
+    let sent: Vec<serde_json::Value> =
+        (0..3).map(|_| serde_json::from_str(&sent_rx.try_recv().unwrap()).unwrap()).collect();
+    assert_eq!(sent[0]["text"], " ");
+    assert_eq!(sent[1]["text"], "Over the socket ");
+    assert_eq!(sent[2]["text"], "");
+}
+
+#[tokio::test]
+async fn test_compare_generations_links_pair() {
+    let Harness { state, .. } = harness().await;
+
+    let a = VoiceSettings::default();
+    let b = VoiceSettings { style: 0.6, ..a.clone() };
+    assert!(compare::compare(&state, tts("Same"), a.clone(), a.clone(), None).await.is_err());
+
+    let comparison = compare::compare(&state, tts("Which is better?"), a, b, None).await.unwrap();
+    assert_ne!(comparison.audio_a.id, comparison.audio_b.id);
+    assert_eq!(comparison.settings_diff.len(), 1);
+    assert_eq!(comparison.settings_diff[0].field, "style");
+
+    let id = comparison.id.clone();
+    let stored = state.with_db(|conn| compare::get_comparison(conn, &id)).unwrap().unwrap();
+    assert_eq!(stored.audio_b.id, comparison.audio_b.id);
+    assert_eq!(stored.settings_diff, comparison.settings_diff);
+
+    pipeline::delete_audio(&state, &comparison.audio_a.id).await.unwrap();
+    assert!(state.with_db(|conn| compare::get_comparison(conn, &id)).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_tts_applies_preset_by_name() {
+    let Harness { state, fake, .. } = harness().await;
+    let calm = VoiceSettings { stability: 0.9, ..Default::default() };
+    state.with_db(|conn| presets::save_preset(conn, "Calm narration", &calm)).unwrap();
+
+    let request = TtsRequest { preset: Some("Calm narration".to_string()), ..tts("Slowly now") };
+    let audio = pipeline::generate_tts(&state, request).await.unwrap();
+    assert_eq!(audio.params.unwrap().voice_settings, Some(calm));
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let tts_request = requests.iter().rev().find(|r| r.url.path().starts_with("/text-to-speech/")).unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&tts_request.body).unwrap();
+    assert_eq!(body["voice_settings"]["stability"], 0.9);
+
+    let unknown = TtsRequest { preset: Some("Nope".to_string()), ..tts("Hi") };
+    assert!(matches!(pipeline::generate_tts(&state, unknown).await, Err(AudioError::Validation(_))));
+}
+
+#[tokio::test]
+async fn test_music_is_cached_and_recorded() {
+    let Harness { state, fake, .. } = harness().await;
+    let request = MusicRequest {
+        prompt: "Lo-fi beat for studying".to_string(),
+        duration_seconds: 12.5,
+        style: Some("hip hop".to_string()),
+        mood: None,
+    };
+    let audio = pipeline::generate_music_for_project(&state, request.clone(), None).await.unwrap();
+    assert_eq!(audio.audio_type, AudioType::Music);
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO);
+    let stored = state.with_db(|conn| AudioCacheDb::get_audio_record(conn, &audio.id)).unwrap().unwrap();
+    assert_eq!(stored.metadata["style"], "hip hop");
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let music = requests.iter().find(|r| r.url.path() == "/music").unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&music.body).unwrap();
+    assert_eq!(body["music_length_ms"], 12500);
+    assert_eq!(body["prompt"], "Lo-fi beat for studying\nStyle: hip hop");
+
+    let too_short = MusicRequest { duration_seconds: 5.0, ..request };
+    assert!(matches!(
+        pipeline::generate_music_for_project(&state, too_short, None).await,
+        Err(AudioError::Validation(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_speech_to_speech_keeps_source() {
+    let Harness { state, fake, .. } = harness().await;
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("take.wav");
+    tokio::fs::write(&source, b"RIFF....WAVE").await.unwrap();
+    let request = VoiceChangeRequest {
+        source_path: source.to_string_lossy().to_string(),
+        voice_id: "voice-1".to_string(),
+        model_id: default_sts_model_id(),
+        voice_settings: None,
+        output_format: default_output_format(),
+        remove_background_noise: true,
+    };
+    let audio = pipeline::generate_voice_change_for_project(&state, request.clone(), None).await.unwrap();
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO);
+    assert_eq!(audio.metadata[VOICE_CHANGED_FROM_KEY], request.source_path.as_str());
+    assert!(matches!(
+        pipeline::regenerate_audio(&state, &audio.id, RegenerateOverrides::default(), None).await,
+        Err(AudioError::Validation(_))
+    ));
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let sts = requests.iter().find(|r| r.url.path() == "/speech-to-speech/voice-1").unwrap();
+    let body = String::from_utf8_lossy(&sts.body);
+    assert!(body.contains("RIFF....WAVE"));
+    assert!(body.contains("eleven_multilingual_sts_v2"));
+
+    let missing = VoiceChangeRequest { source_path: dir.path().join("nope.wav").to_string_lossy().to_string(), ..request };
+    assert!(matches!(
+        pipeline::generate_voice_change_for_project(&state, missing, None).await,
+        Err(AudioError::Validation(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_designed_voice_is_cached() {
+    let Harness { state, fake, .. } = harness().await;
+    let request = VoiceDesignRequest {
+        voice_description: "A gravelly old sea captain with a warm laugh".to_string(),
+        text: None,
+    };
+    let previews = voice_design::preview(&state, request).await.unwrap();
+    assert_eq!(previews.previews.len(), 2);
+    assert!(voice_design::preview(&state, VoiceDesignRequest { voice_description: "Pirate".to_string(), text: None })
+        .await
+        .is_err());
+
+    let voice = voice_design::create(
+        &state,
+        DesignedVoiceRequest {
+            name: "Captain".to_string(),
+            description: "A gravelly old sea captain with a warm laugh".to_string(),
+            generated_voice_id: previews.previews[0].generated_voice_id.clone(),
+            labels: None,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(voice.voice_id, "designed");
+    assert!(state.with_db(|conn| VoiceProfileDb::get_voice_profile(conn, "designed")).unwrap().is_some());
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let create = requests.iter().find(|r| r.url.path() == "/text-to-voice/create-voice-from-preview").unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&create.body).unwrap();
+    assert_eq!(body["generated_voice_id"], "gen-1");
+    let previews = requests.iter().find(|r| r.url.path() == "/text-to-voice/create-previews").unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&previews.body).unwrap();
+    assert_eq!(body["auto_generate_text"], true);
+}
+
+#[tokio::test]
+async fn test_dubbing_job_downloads_when_dubbed() {
+    let Harness { state, fake, .. } = harness().await;
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("scene.mp4");
+    tokio::fs::write(&source, b"fake video").await.unwrap();
+    let request = DubbingRequest {
+        source_path: source.to_string_lossy().to_string(),
+        target_lang: "es".to_string(),
+        source_lang: None,
+        num_speakers: None,
+    };
+
+    let job = dubbing::submit(&state, request, Some("p1")).await.unwrap();
+    assert_eq!((job.dubbing_id.as_str(), job.status), ("dub-1", dubbing::DubbingStatus::Dubbing));
+    assert_eq!(job.expected_duration_seconds, Some(12.0));
+
+    // Concurrent checks download the dub once
+    let (first, second) = tokio::join!(dubbing::refresh(&state, &job.id), dubbing::refresh(&state, &job.id));
+    let (first, second) = (first.unwrap(), second.unwrap());
+    let done = if first.status == dubbing::DubbingStatus::Completed { first } else { second };
+    assert_eq!(done.status, dubbing::DubbingStatus::Completed);
+    let audio = state
+        .with_db(|conn| AudioCacheDb::get_audio_record(conn, done.audio_id.as_deref().unwrap()))
+        .unwrap()
+        .unwrap();
+    assert!(audio.local_path.ends_with(".mp4"));
+    assert_eq!(audio.duration_seconds, 0.0);
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO);
+    assert_eq!(state.with_db(|conn| dubbing::get_jobs(conn, Some("p1"))).unwrap()[0].status, done.status);
+
+    // A finished job isn't checked again
+    dubbing::refresh(&state, &job.id).await.unwrap();
+    let requests = fake.server.received_requests().await.unwrap();
+    assert_eq!(requests.iter().filter(|r| r.url.path() == "/dubbing/dub-1").count(), 2);
+    assert_eq!(requests.iter().filter(|r| r.url.path() == "/dubbing/dub-1/audio/es").count(), 1);
+}
+
+#[tokio::test]
+async fn test_history_item_is_imported_once() {
+    let Harness { state, fake, .. } = harness().await;
+    let page = fake.api().list_history(10, None).await.unwrap();
+    assert_eq!(page.history.len(), 2);
+
+    let audio = history::import(&state, "h-1", Some("p1")).await.unwrap();
+    assert_eq!(audio.prompt, "Generated somewhere else");
+    assert_eq!(audio.metadata[HISTORY_ITEM_KEY], "h-1");
+    assert_eq!(audio.params.as_ref().unwrap().voice_id.as_deref(), Some("rachel"));
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO);
+
+    let again = history::import(&state, "h-1", None).await.unwrap();
+    assert_eq!(again.id, audio.id);
+    let requests = fake.server.received_requests().await.unwrap();
+    assert_eq!(requests.iter().filter(|r| r.url.path() == "/history/h-1/audio").count(), 1);
+}
+
+#[tokio::test]
+async fn test_shared_voice_search_and_add() {
+    let Harness { state, fake, .. } = harness().await;
+    let filter = SharedVoiceFilter { gender: Some("male".to_string()), page: 2, ..Default::default() };
+    let page = voice_library::search(&state, filter).await.unwrap();
+    assert!(page.has_more);
+    let found = &page.voices[0];
+
+    let voice = voice_library::add(&state, &found.public_owner_id, &found.voice_id, "Radio Host").await.unwrap();
+    assert_eq!(voice.voice_id, "shared-1");
+    assert!(state.with_db(|conn| VoiceProfileDb::get_voice_profile(conn, "shared-1")).unwrap().is_some());
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let search = requests.iter().find(|r| r.url.path() == "/shared-voices").unwrap();
+    let query = search.url.query().unwrap();
+    assert!(query.contains("gender=male") && query.contains("page=2") && query.contains("page_size=30"));
+    let add = requests.iter().find(|r| r.url.path() == "/voices/add/owner-1/shared-1").unwrap();
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&add.body).unwrap()["new_name"], "Radio Host");
+}
+
+#[tokio::test]
+async fn test_isolated_audio_is_cached() {
+    let Harness { state, fake, .. } = harness().await;
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("noisy.mp3");
+    tokio::fs::write(&source, b"ID3 noisy sample").await.unwrap();
+    let source = source.to_string_lossy().to_string();
+
+    let audio = pipeline::isolate_audio_for_project(&state, &source, None).await.unwrap();
+    assert_eq!(tokio::fs::read(&audio.local_path).await.unwrap(), AUDIO);
+    let stored = state.with_db(|conn| AudioCacheDb::get_audio_record(conn, &audio.id)).unwrap().unwrap();
+    assert_eq!(stored.metadata[ISOLATED_FROM_KEY], source.as_str());
+    assert_eq!(stored.prompt, "noisy");
+
+    // Recorded like every other render, without any text sent
+    let history = state
+        .with_db(|conn| usage_history::get_history(conn, &Default::default(), Default::default()))
+        .unwrap();
+    assert_eq!((history[0].generations, history[0].characters), (1, 0));
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let isolation = requests.iter().find(|r| r.url.path() == "/audio-isolation").unwrap();
+    assert!(String::from_utf8_lossy(&isolation.body).contains("ID3 noisy sample"));
+}
+
+#[tokio::test]
+async fn test_transcription_is_stored_with_word_timings() {
+    let Harness { state, fake, .. } = harness().await;
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("line.mp3");
+    tokio::fs::write(&source, AUDIO).await.unwrap();
+    let source = source.to_string_lossy().to_string();
+
+    let transcription = transcriptions::transcribe(&state, &source, Some("en"), Some("p1")).await.unwrap();
+    assert_eq!(transcription.text, "hello there");
+    assert_eq!(transcription.language_code.as_deref(), Some("en"));
+    assert_eq!(transcription.words[2].text, "there");
+    assert_eq!(transcription.words[2].start, 0.5);
+
+    let listed = state.with_db(|conn| transcriptions::get_transcriptions(conn, Some("p1"), 10)).unwrap();
+    assert_eq!(listed, vec![transcription]);
+    assert!(state.with_db(|conn| transcriptions::get_transcriptions(conn, Some("p2"), 10)).unwrap().is_empty());
+
+    let requests = fake.server.received_requests().await.unwrap();
+    let stt = requests.iter().find(|r| r.url.path() == "/speech-to-text").unwrap();
+    assert!(String::from_utf8_lossy(&stt.body).contains("fake audio"));
+}
+
+#[test]
+fn test_command_registry_covers_submodules() {
+    let names: std::collections::HashSet<_> = super::COMMANDS.iter().collect();
+    assert_eq!(names.len(), super::COMMANDS.len(), "duplicate command name");
+    for command in ["eleven_labs_tts", "configure_s3_backup", "get_usage_history", "eleven_labs_tts_stream"] {
+        assert!(names.contains(&command), "{} is not registered", command);
+    }
+}
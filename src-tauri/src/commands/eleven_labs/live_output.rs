@@ -0,0 +1,112 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::{AudioDb, SettingsDb};
+use super::error::AudioError;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Settings key holding the live output configuration
+pub const LIVE_OUTPUT_KEY: &str = "live_output";
+
+/// "Live output" mirrors the latest render to fixed paths that streaming
+/// software (OBS media/text sources) can watch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LiveOutputConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// File the latest audio is swapped into
+    pub audio_path: Option<String>,
+    /// File receiving the prompt text; defaults to `audio_path` with a `.txt` extension
+    pub text_path: Option<String>,
+}
+
+impl LiveOutputConfig {
+    pub fn load(conn: &rusqlite::Connection) -> Result<Self> {
+        match SettingsDb::get_setting(conn, LIVE_OUTPUT_KEY)? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn text_path(&self, audio_path: &Path) -> PathBuf {
+        self.text_path
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| audio_path.with_extension("txt"))
+    }
+}
+
+/// Write `data` next to `dest` and rename over it, so readers never see a partial file
+fn atomic_write(dest: &Path, data: &[u8]) -> Result<()> {
+    let parent = dest
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid output path: {}", dest.display()))?;
+    std::fs::create_dir_all(parent)?;
+
+    let file_name = dest
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("Invalid output path: {}", dest.display()))?;
+    let tmp_path = parent.join(format!(".{}.tmp", file_name));
+
+    std::fs::write(&tmp_path, data)?;
+    std::fs::rename(&tmp_path, dest).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        anyhow!("Failed to replace {}: {}", dest.display(), e)
+    })
+}
+
+/// Mirror a render to the configured live output files
+pub fn publish(config: &LiveOutputConfig, audio: &GeneratedAudio) -> Result<()> {
+    let audio_path = match (&config.audio_path, config.enabled) {
+        (Some(path), true) => PathBuf::from(path),
+        _ => return Ok(()),
+    };
+
+    let data = std::fs::read(&audio.local_path)?;
+    atomic_write(&audio_path, &data)?;
+    atomic_write(&config.text_path(&audio_path), audio.prompt.as_bytes())?;
+
+    Ok(())
+}
+
+/// Publish if live output is enabled, logging rather than failing the render
+pub async fn publish_if_enabled(db: &AudioDb, audio: &GeneratedAudio) {
+    if let Err(e) = publish_with_stored_config(db, audio.clone()).await {
+        log::warn!("Failed to update live output: {}", e);
+    }
+}
+
+async fn publish_with_stored_config(db: &AudioDb, audio: GeneratedAudio) -> Result<(), AudioError> {
+    let config = db.call(|conn| LiveOutputConfig::load(conn)).await?;
+    tokio::task::spawn_blocking(move || publish(&config, &audio)).await??;
+    Ok(())
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the live output configuration
+#[tauri::command]
+pub async fn get_live_output_config(state: State<'_, Arc<ElevenLabsState>>) -> Result<LiveOutputConfig, AudioError> {
+    state.call_db(|conn| LiveOutputConfig::load(conn)).await
+}
+
+/// Update the live output configuration
+#[tauri::command]
+pub async fn set_live_output_config(
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: LiveOutputConfig,
+) -> Result<LiveOutputConfig, AudioError> {
+    if config.enabled && config.audio_path.as_deref().is_none_or(str::is_empty) {
+        return Err(AudioError::Validation("An audio output path is required to enable live output".to_string()));
+    }
+
+    let json = serde_json::to_string(&config)?;
+    state.call_db(move |conn| SettingsDb::save_setting(conn, LIVE_OUTPUT_KEY, &json)).await?;
+
+    Ok(config)
+}
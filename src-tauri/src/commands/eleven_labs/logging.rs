@@ -0,0 +1,265 @@
+// Tracing setup for the app. `init` installs the subscriber at launch, logging
+// to stderr at the level in RUST_LOG (or `info`); the saved `LoggingConfig` is
+// applied once the database is available and again whenever it changes.
+// Records from the `log` crate, used by the rest of the app, are forwarded to
+// the same subscriber.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::State;
+use tracing::level_filters::LevelFilter;
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::writer::{MakeWriter, OptionalWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+use super::cache::{AudioDb, SettingsDb};
+use super::error::AudioError;
+use super::ElevenLabsState;
+
+/// Settings key holding the logging configuration
+pub const LOGGING_CONFIG_KEY: &str = "logging";
+
+/// Prefix of the log file names, e.g. `opcode.2024-05-01.log`
+const LOG_FILE_PREFIX: &str = "opcode";
+
+/// How often a new log file is started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Hourly,
+    Daily,
+    Never,
+}
+
+/// Log levels and file output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Default level: `error`, `warn`, `info`, `debug` or `trace`
+    #[serde(default = "default_level")]
+    pub level: String,
+    /// Levels for individual modules, e.g.
+    /// `{"opcode_lib::commands::eleven_labs::client": "debug"}`
+    #[serde(default)]
+    pub modules: BTreeMap<String, String>,
+    /// Also write logs to rotating files
+    #[serde(default)]
+    pub file_enabled: bool,
+    /// Directory for log files; defaults to the app's local data directory
+    #[serde(default)]
+    pub directory: Option<String>,
+    #[serde(default = "default_rotation")]
+    pub rotation: LogRotation,
+    /// Rotated files kept before the oldest is deleted
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+}
+
+fn default_level() -> String {
+    "info".to_string()
+}
+
+fn default_rotation() -> LogRotation {
+    LogRotation::Daily
+}
+
+fn default_max_files() -> usize {
+    7
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: default_level(),
+            modules: BTreeMap::new(),
+            file_enabled: false,
+            directory: None,
+            rotation: default_rotation(),
+            max_files: default_max_files(),
+        }
+    }
+}
+
+impl LoggingConfig {
+    pub fn load(conn: &rusqlite::Connection) -> anyhow::Result<Self> {
+        match SettingsDb::get_setting(conn, LOGGING_CONFIG_KEY)? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Filter for the configured levels, e.g.
+    /// `info,opcode_lib::commands::eleven_labs::client=debug`
+    fn filter(&self) -> Result<EnvFilter, AudioError> {
+        let parse_level = |level: &str| {
+            LevelFilter::from_str(level).map_err(|_| AudioError::Validation(format!("Invalid log level: {}", level)))
+        };
+
+        let mut directives = parse_level(&self.level)?.to_string();
+        for (module, level) in &self.modules {
+            if module.is_empty() || module.contains([',', '=', '[', ' ']) {
+                return Err(AudioError::Validation(format!("Invalid module name: {}", module)));
+            }
+            directives.push_str(&format!(",{}={}", module, parse_level(level)?));
+        }
+        EnvFilter::try_new(&directives).map_err(|e| AudioError::Validation(format!("Invalid log filter: {}", e)))
+    }
+
+    fn log_dir(&self) -> Result<PathBuf, AudioError> {
+        match &self.directory {
+            Some(dir) => Ok(PathBuf::from(dir)),
+            None => Ok(dirs::data_local_dir()
+                .ok_or("Could not find local data directory")?
+                .join("opcode")
+                .join("logs")),
+        }
+    }
+
+    fn appender(&self) -> Result<RollingFileAppender, AudioError> {
+        if self.max_files == 0 {
+            return Err(AudioError::Validation("max_files must be at least 1".to_string()));
+        }
+        let rotation = match self.rotation {
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Never => Rotation::NEVER,
+        };
+        RollingFileAppender::builder()
+            .rotation(rotation)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix("log")
+            .max_log_files(self.max_files)
+            .build(self.log_dir()?)
+            .map_err(|e| AudioError::Other(format!("Failed to open log file: {}", e)))
+    }
+}
+
+/// Log file writer that can be swapped or removed after the subscriber is installed
+#[derive(Clone, Default)]
+struct FileWriter(Arc<Mutex<Option<NonBlocking>>>);
+
+impl<'a> MakeWriter<'a> for FileWriter {
+    type Writer = OptionalWriter<NonBlocking>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        match self.0.lock().ok().and_then(|writer| writer.clone()) {
+            Some(writer) => OptionalWriter::some(writer),
+            None => OptionalWriter::none(),
+        }
+    }
+}
+
+struct Handles {
+    filter: reload::Handle<EnvFilter, Registry>,
+    file: FileWriter,
+    /// Flushes the current log file when replaced or dropped
+    guard: Mutex<Option<WorkerGuard>>,
+}
+
+static HANDLES: OnceLock<Handles> = OnceLock::new();
+
+/// Filter from RUST_LOG, which takes precedence over the saved levels
+fn env_filter() -> Option<EnvFilter> {
+    std::env::var(EnvFilter::DEFAULT_ENV).ok().and_then(|spec| EnvFilter::try_new(spec).ok())
+}
+
+/// Install the global subscriber. Call once, before anything logs.
+pub fn init() {
+    let (filter, handle) = reload::Layer::new(env_filter().unwrap_or_else(|| EnvFilter::new(default_level())));
+    let file = FileWriter::default();
+
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(fmt::layer().with_ansi(false).with_writer(file.clone()))
+        .try_init();
+    if let Err(e) = installed {
+        eprintln!("Failed to install log subscriber: {}", e);
+        return;
+    }
+
+    let _ = HANDLES.set(Handles {
+        filter: handle,
+        file,
+        guard: Mutex::new(None),
+    });
+}
+
+/// Apply levels and file output from `config`. Does nothing if `init` wasn't called.
+pub fn apply(config: &LoggingConfig) -> Result<(), AudioError> {
+    let filter = config.filter()?;
+    let Some(handles) = HANDLES.get() else {
+        return Ok(());
+    };
+
+    let filter = env_filter().unwrap_or(filter);
+    handles
+        .filter
+        .reload(filter)
+        .map_err(|e| AudioError::Other(format!("Failed to update log filter: {}", e)))?;
+
+    let (writer, guard) = match config.file_enabled {
+        true => {
+            let (writer, guard) = tracing_appender::non_blocking(config.appender()?);
+            (Some(writer), Some(guard))
+        }
+        false => (None, None),
+    };
+    *handles.file.0.lock()? = writer;
+    *handles.guard.lock()? = guard;
+    Ok(())
+}
+
+/// Apply the saved configuration at launch, from the synchronous setup hook
+pub fn apply_saved(db: &AudioDb) -> Result<(), AudioError> {
+    apply(&db.with_blocking(|conn| LoggingConfig::load(conn))?)
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the logging configuration
+#[tauri::command]
+pub async fn get_logging_config(state: State<'_, Arc<ElevenLabsState>>) -> Result<LoggingConfig, AudioError> {
+    state.call_db(|conn| LoggingConfig::load(conn)).await
+}
+
+/// Update the logging configuration and apply it immediately
+#[tauri::command]
+pub async fn set_logging_config(
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: LoggingConfig,
+) -> Result<LoggingConfig, AudioError> {
+    apply(&config)?;
+
+    let json = serde_json::to_string(&config)?;
+    state
+        .call_db(move |conn| SettingsDb::save_setting(conn, LOGGING_CONFIG_KEY, &json))
+        .await?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_validation() {
+        let mut config = LoggingConfig::default();
+        config.modules.insert("opcode_lib::commands::eleven_labs::client".to_string(), "debug".to_string());
+        assert!(config.filter().is_ok());
+
+        config.modules.insert("opcode_lib::commands::eleven_labs::cache".to_string(), "loud".to_string());
+        assert!(matches!(config.filter(), Err(AudioError::Validation(_))));
+
+        let config = LoggingConfig {
+            modules: BTreeMap::from([("a=trace,b".to_string(), "info".to_string())]),
+            ..LoggingConfig::default()
+        };
+        assert!(matches!(config.filter(), Err(AudioError::Validation(_))));
+    }
+}
@@ -0,0 +1,402 @@
+use axum::{
+    extract::{Query, Request, State as AxumState},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tauri::State;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, Mutex};
+
+use super::error::AudioError;
+use super::http_api::{ensure_token, tokens_match};
+use super::pipeline;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// MCP protocol revision implemented by this server
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Default port for the in-app SSE transport
+const DEFAULT_SSE_PORT: u16 = 8765;
+
+/// Model Context Protocol server exposing the audio pipeline as tools
+#[derive(Clone)]
+pub struct AudioMcpServer {
+    state: Arc<ElevenLabsState>,
+}
+
+impl AudioMcpServer {
+    pub fn new(state: Arc<ElevenLabsState>) -> Self {
+        Self { state }
+    }
+
+    /// Tool definitions advertised via `tools/list`
+    fn tool_definitions() -> Value {
+        json!([
+            {
+                "name": "tts",
+                "description": "Render text to speech with an ElevenLabs voice. Returns the cached audio record including its local file path.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "text": { "type": "string", "description": "Text to speak" },
+                        "voice_id": { "type": "string", "description": "Voice ID (see list_voices)" },
+                        "model_id": { "type": "string" },
+                        "voice_settings": {
+                            "type": "object",
+                            "properties": {
+                                "stability": { "type": "number" },
+                                "similarity_boost": { "type": "number" },
+                                "style": { "type": "number" },
+                                "use_speaker_boost": { "type": "boolean" }
+                            }
+                        }
+                    },
+                    "required": ["text", "voice_id"]
+                }
+            },
+            {
+                "name": "generate_sfx",
+                "description": "Generate a sound effect from a text prompt. Returns the cached audio record.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "text": { "type": "string", "description": "Description of the sound" },
+                        "duration_seconds": { "type": "number" },
+                        "prompt_influence": { "type": "number" }
+                    },
+                    "required": ["text"]
+                }
+            },
+            {
+                "name": "list_voices",
+                "description": "List the voices available on the configured ElevenLabs account.",
+                "inputSchema": { "type": "object", "properties": {} }
+            },
+            {
+                "name": "assign_voice",
+                "description": "Assign a voice to a named character so later narration uses it.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "character_name": { "type": "string" },
+                        "voice_id": { "type": "string" },
+                        "voice_name": { "type": "string" },
+                        "project_id": { "type": "string" }
+                    },
+                    "required": ["character_name", "voice_id"]
+                }
+            }
+        ])
+    }
+
+    /// Handle a single JSON-RPC message, returning the response (if any)
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let method = message.get("method").and_then(|m| m.as_str()).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        // Notifications carry no id and never get a response
+        let id = id?;
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "opcode-audio", "version": env!("CARGO_PKG_VERSION") }
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": Self::tool_definitions() })),
+            "tools/call" => Ok(self.call_tool(params).await),
+            _ => Err((-32601, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message }
+            }),
+        })
+    }
+
+    /// Execute a tool, reporting failures as tool errors rather than protocol errors
+    async fn call_tool(&self, params: Value) -> Value {
+        let name = params.get("name").and_then(|n| n.as_str()).unwrap_or("").to_string();
+        let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+        let outcome = match name.as_str() {
+            "tts" => self.tool_tts(arguments).await,
+            "generate_sfx" => self.tool_generate_sfx(arguments).await,
+            "list_voices" => pipeline::list_voices(&self.state)
+                .await
+                .and_then(|voices| Ok(serde_json::to_value(voices)?)),
+            "assign_voice" => self.tool_assign_voice(arguments).await,
+            _ => Err(AudioError::Validation(format!("Unknown tool: {}", name))),
+        };
+
+        match outcome {
+            Ok(value) => json!({
+                "content": [{
+                    "type": "text",
+                    "text": serde_json::to_string_pretty(&value).unwrap_or_default()
+                }]
+            }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": e.to_string() }],
+                "isError": true
+            }),
+        }
+    }
+
+    async fn tool_tts(&self, arguments: Value) -> Result<Value, AudioError> {
+        let request: TtsRequest = serde_json::from_value(arguments)?;
+        let audio = pipeline::generate_tts(&self.state, request).await?;
+        Ok(serde_json::to_value(audio)?)
+    }
+
+    async fn tool_generate_sfx(&self, arguments: Value) -> Result<Value, AudioError> {
+        let request: SfxRequest = serde_json::from_value(arguments)?;
+        let audio = pipeline::generate_sfx(&self.state, request).await?;
+        Ok(serde_json::to_value(audio)?)
+    }
+
+    async fn tool_assign_voice(&self, arguments: Value) -> Result<Value, AudioError> {
+        #[derive(Deserialize)]
+        struct AssignArgs {
+            character_name: String,
+            voice_id: String,
+            voice_name: Option<String>,
+            project_id: Option<String>,
+        }
+
+        let args: AssignArgs = serde_json::from_value(arguments)?;
+        let mapping = pipeline::assign_voice(
+            &self.state.db()?,
+            args.character_name,
+            args.voice_id,
+            args.voice_name,
+            args.project_id,
+        )
+        .await?;
+        Ok(serde_json::to_value(mapping)?)
+    }
+}
+
+/// Serve MCP over stdin/stdout (newline-delimited JSON-RPC) until stdin closes
+pub async fn serve_stdio(server: AudioMcpServer) -> std::io::Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => server.handle_message(message).await,
+            Err(e) => Some(json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32700, "message": format!("Parse error: {}", e) }
+            })),
+        };
+
+        if let Some(response) = response {
+            stdout.write_all(response.to_string().as_bytes()).await?;
+            stdout.write_all(b"\n").await?;
+            stdout.flush().await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+struct SseState {
+    server: AudioMcpServer,
+    sessions: Arc<Mutex<HashMap<String, mpsc::Sender<String>>>>,
+    token: Arc<String>,
+}
+
+#[derive(Deserialize)]
+struct SessionQuery {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+async fn sse_connect(
+    AxumState(state): AxumState<SseState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = mpsc::channel::<String>(32);
+    state.sessions.lock().await.insert(session_id.clone(), tx);
+
+    let endpoint = stream::once(async move {
+        Ok(Event::default()
+            .event("endpoint")
+            .data(format!("/messages?sessionId={}", session_id)))
+    });
+    let messages = stream::unfold(rx, |mut rx| async move {
+        rx.recv()
+            .await
+            .map(|message| (Ok(Event::default().event("message").data(message)), rx))
+    });
+
+    Sse::new(endpoint.chain(messages)).keep_alive(KeepAlive::default())
+}
+
+async fn sse_message(
+    AxumState(state): AxumState<SseState>,
+    Query(query): Query<SessionQuery>,
+    Json(message): Json<Value>,
+) -> StatusCode {
+    let sender = match state.sessions.lock().await.get(&query.session_id) {
+        Some(sender) => sender.clone(),
+        None => return StatusCode::NOT_FOUND,
+    };
+
+    if let Some(response) = state.server.handle_message(message).await {
+        if sender.send(response.to_string()).await.is_err() {
+            state.sessions.lock().await.remove(&query.session_id);
+            return StatusCode::GONE;
+        }
+    }
+
+    StatusCode::ACCEPTED
+}
+
+/// Whether a `Host` header names the loopback interface. A page on another
+/// origin that rebinds its DNS name to 127.0.0.1 still sends its own name.
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    matches!(name, "127.0.0.1" | "localhost" | "::1")
+}
+
+async fn require_local_client(AxumState(state): AxumState<SseState>, request: Request, next: Next) -> Response {
+    let host = request.headers().get(header::HOST).and_then(|value| value.to_str().ok());
+    if !host.is_some_and(is_loopback_host) {
+        return (StatusCode::FORBIDDEN, "Host not allowed").into_response();
+    }
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if tokens_match(token, &state.token) => next.run(request).await,
+        _ => (StatusCode::UNAUTHORIZED, "Missing or invalid token").into_response(),
+    }
+}
+
+/// Build the HTTP+SSE transport router (`GET /sse`, `POST /messages`).
+/// Every request needs a loopback `Host` and `Authorization: Bearer <token>`.
+pub fn sse_router(server: AudioMcpServer, token: String) -> Router {
+    let state = SseState {
+        server,
+        sessions: Arc::new(Mutex::new(HashMap::new())),
+        token: Arc::new(token),
+    };
+
+    Router::new()
+        .route("/sse", get(sse_connect))
+        .route("/messages", post(sse_message))
+        .layer(middleware::from_fn_with_state(state.clone(), require_local_client))
+        .with_state(state)
+}
+
+// ========== Tauri Commands ==========
+
+/// Start the MCP SSE server on localhost so agents can call the audio tools.
+/// Clients authenticate with the HTTP API token.
+#[tauri::command]
+pub async fn start_audio_mcp_server(
+    state: State<'_, Arc<ElevenLabsState>>,
+    port: Option<u16>,
+) -> Result<String, AudioError> {
+    let token = ensure_token().await?;
+    let mut handle_guard = state.mcp_server.lock().await;
+    if handle_guard.is_some() {
+        return Err(AudioError::Other("Audio MCP server is already running".to_string()));
+    }
+
+    let port = port.unwrap_or(DEFAULT_SSE_PORT);
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("Failed to bind port {}: {}", port, e))?;
+
+    let server = AudioMcpServer::new(Arc::clone(&state));
+    let router = sse_router(server, token);
+
+    *handle_guard = Some(state.tasks.spawn("audio-mcp-server", |shutdown| async move {
+        let serve = axum::serve(listener, router).with_graceful_shutdown(async move { shutdown.wait().await });
+        if let Err(e) = serve.await {
+            log::error!("Audio MCP server stopped: {}", e);
+        }
+    }));
+
+    Ok(format!("http://127.0.0.1:{}/sse", port))
+}
+
+/// Stop the in-app MCP SSE server
+#[tauri::command]
+pub async fn stop_audio_mcp_server(state: State<'_, Arc<ElevenLabsState>>) -> Result<bool, AudioError> {
+    let mut handle_guard = state.mcp_server.lock().await;
+    match handle_guard.take() {
+        Some(handle) => {
+            handle.abort();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tool_error_text_is_a_string() {
+        let server = AudioMcpServer::new(Arc::new(ElevenLabsState::new()));
+        let response = server
+            .handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": "no_such_tool", "arguments": {} }
+            }))
+            .await
+            .unwrap();
+
+        let result = &response["result"];
+        assert_eq!(result["isError"], true);
+        assert_eq!(result["content"][0]["text"], "Unknown tool: no_such_tool");
+    }
+
+    #[test]
+    fn test_is_loopback_host() {
+        assert!(is_loopback_host("127.0.0.1:8765"));
+        assert!(is_loopback_host("localhost:8765"));
+        assert!(is_loopback_host("localhost"));
+        assert!(is_loopback_host("[::1]:8765"));
+        assert!(!is_loopback_host("attacker.example:8765"));
+        assert!(!is_loopback_host("localhost.attacker.example"));
+        assert!(!is_loopback_host("127.0.0.1.nip.io:8765"));
+    }
+}
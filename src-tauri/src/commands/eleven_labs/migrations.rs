@@ -0,0 +1,332 @@
+// Versioned changes to the audio tables. Each migration moves the schema up
+// one version and knows how to undo itself; `audio_schema_version` records
+// those applied. `schema::init` runs the pending ones at startup.
+//
+// Databases from before versioning have no record and start at 0, though they
+// may already have some of the changes, so steps must be safe to apply to a
+// database that partly has them (`IF NOT EXISTS`, `add_column_if_missing`).
+
+use anyhow::{anyhow, Result};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::State;
+
+use super::error::AudioError;
+use super::schema::{self, PROMPT_SEARCH_TABLE};
+use super::ElevenLabsState;
+
+/// Table recording the migrations applied to the database
+pub const VERSION_TABLE: &str = "audio_schema_version";
+
+/// One step in the schema's history
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    up: fn(&Connection) -> rusqlite::Result<()>,
+    down: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+/// Every migration, oldest first, numbered from 1 without gaps
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create the audio tables",
+        up: |conn| conn.execute_batch(schema::SCHEMA),
+        down: |conn| {
+            for table in schema::TABLES.iter().rev() {
+                conn.execute_batch(&format!("DROP TABLE IF EXISTS {}", table))?;
+            }
+            Ok(())
+        },
+    },
+    Migration {
+        version: 2,
+        description: "Record the parameters cached audio was generated with",
+        up: |conn| {
+            add_column_if_missing(conn, "audio_cache", "params", "TEXT")?;
+            conn.execute_batch(
+                "CREATE INDEX IF NOT EXISTS idx_audio_cache_voice ON audio_cache (json_extract(params, '$.voice_id'))",
+            )
+        },
+        down: |conn| {
+            conn.execute_batch("DROP INDEX IF EXISTS idx_audio_cache_voice")?;
+            drop_column(conn, "audio_cache", "params")
+        },
+    },
+    Migration {
+        version: 3,
+        description: "Let character castings name a voice settings preset",
+        up: |conn| add_column_if_missing(conn, "character_voices", "preset", "TEXT"),
+        down: |conn| drop_column(conn, "character_voices", "preset"),
+    },
+    Migration {
+        version: 4,
+        description: "Hash TTS requests so identical ones reuse earlier renders",
+        up: |conn| {
+            add_column_if_missing(conn, "audio_cache", "request_hash", "TEXT")?;
+            conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_audio_cache_request_hash ON audio_cache (request_hash)")
+        },
+        down: |conn| {
+            conn.execute_batch("DROP INDEX IF EXISTS idx_audio_cache_request_hash")?;
+            drop_column(conn, "audio_cache", "request_hash")
+        },
+    },
+    Migration {
+        version: 5,
+        description: "Index cached audio prompts for full-text search",
+        up: |conn| {
+            conn.execute_batch(schema::PROMPT_SEARCH)?;
+            // Index the prompts already there
+            conn.execute_batch("INSERT INTO audio_prompt_fts (audio_prompt_fts) VALUES ('rebuild')")
+        },
+        down: |conn| {
+            conn.execute_batch(&format!(
+                "DROP TRIGGER IF EXISTS audio_prompt_fts_insert;
+                 DROP TRIGGER IF EXISTS audio_prompt_fts_delete;
+                 DROP TRIGGER IF EXISTS audio_prompt_fts_update;
+                 DROP TABLE IF EXISTS {};",
+                PROMPT_SEARCH_TABLE
+            ))
+        },
+    },
+    Migration {
+        version: 6,
+        description: "Add tags and a favorite mark to cached audio",
+        up: |conn| {
+            add_column_if_missing(conn, "audio_cache", "favorite", "INTEGER NOT NULL DEFAULT 0")?;
+            conn.execute_batch(
+                "-- User tags on cached audio, matched regardless of case
+                 CREATE TABLE IF NOT EXISTS audio_tags (
+                     audio_id TEXT NOT NULL,
+                     tag TEXT NOT NULL COLLATE NOCASE,
+                     added_at TEXT NOT NULL,
+                     PRIMARY KEY (audio_id, tag)
+                 );
+                 CREATE INDEX IF NOT EXISTS idx_audio_tags_tag ON audio_tags (tag);",
+            )
+        },
+        down: |conn| {
+            conn.execute_batch("DROP TABLE IF EXISTS audio_tags")?;
+            drop_column(conn, "audio_cache", "favorite")
+        },
+    },
+    Migration {
+        version: 7,
+        description: "Move deleted audio to a trash before removing it",
+        // When the audio was moved to the trash; NULL while it is live
+        up: |conn| add_column_if_missing(conn, "audio_cache", "deleted_at", "TEXT"),
+        down: |conn| drop_column(conn, "audio_cache", "deleted_at"),
+    },
+];
+
+/// A migration recorded as applied
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub description: String,
+    pub applied_at: String,
+}
+
+/// Where the database's schema stands against the migrations this build has
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaStatus {
+    pub version: u32,
+    pub latest: u32,
+    pub applied: Vec<AppliedMigration>,
+}
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    if !has_column(conn, table, column)? {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+fn drop_column(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<()> {
+    if has_column(conn, table, column)? {
+        conn.execute(&format!("ALTER TABLE {} DROP COLUMN {}", table, column), [])?;
+    }
+    Ok(())
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        (table, column),
+        |row| row.get(0),
+    )
+}
+
+fn ensure_version_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (
+             version INTEGER PRIMARY KEY,
+             description TEXT NOT NULL,
+             applied_at TEXT NOT NULL
+         )",
+        VERSION_TABLE
+    ))
+}
+
+/// The newest migration applied; 0 for a database from before versioning
+pub fn current_version(conn: &Connection) -> rusqlite::Result<u32> {
+    ensure_version_table(conn)?;
+    conn.query_row(&format!("SELECT COALESCE(MAX(version), 0) FROM {}", VERSION_TABLE), [], |row| {
+        row.get(0)
+    })
+}
+
+pub fn applied_migrations(conn: &Connection) -> rusqlite::Result<Vec<AppliedMigration>> {
+    ensure_version_table(conn)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT version, description, applied_at FROM {} ORDER BY version",
+        VERSION_TABLE
+    ))?;
+    let applied = stmt
+        .query_map([], |row| {
+            Ok(AppliedMigration {
+                version: row.get(0)?,
+                description: row.get(1)?,
+                applied_at: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(applied)
+}
+
+/// Run one direction of `migration` and record it, all or nothing. A
+/// savepoint rather than a transaction so this also works inside one.
+fn apply(conn: &Connection, migration: &Migration, up: bool) -> rusqlite::Result<()> {
+    conn.execute_batch("SAVEPOINT audio_migration")?;
+    let result = if up {
+        (migration.up)(conn).and_then(|()| {
+            conn.execute(
+                &format!("INSERT INTO {} (version, description, applied_at) VALUES (?1, ?2, ?3)", VERSION_TABLE),
+                (migration.version, migration.description, chrono::Utc::now().to_rfc3339()),
+            )
+        })
+    } else {
+        (migration.down)(conn).and_then(|()| {
+            conn.execute(&format!("DELETE FROM {} WHERE version = ?1", VERSION_TABLE), [migration.version])
+        })
+    };
+    match result {
+        Ok(_) => conn.execute_batch("RELEASE audio_migration"),
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO audio_migration; RELEASE audio_migration")?;
+            Err(e)
+        }
+    }
+}
+
+/// Apply the migrations the database hasn't had, returning the version it is
+/// at afterwards. A database from a newer build is left as it is.
+pub fn migrate(conn: &Connection) -> rusqlite::Result<u32> {
+    let current = current_version(conn)?;
+    if current > latest_version() {
+        log::warn!(
+            "Audio schema version {} is newer than this build's {}; leaving it as it is",
+            current,
+            latest_version()
+        );
+        return Ok(current);
+    }
+    for migration in MIGRATIONS.iter().filter(|migration| migration.version > current) {
+        apply(conn, migration, true)?;
+        if current > 0 {
+            log::info!("Migrated the audio schema to version {}: {}", migration.version, migration.description);
+        }
+    }
+    Ok(latest_version())
+}
+
+/// Move the schema up or down to `target`, undoing newer migrations on the
+/// way down. Version 0 drops every audio table.
+pub fn migrate_to(conn: &Connection, target: u32) -> Result<u32> {
+    if target > latest_version() {
+        return Err(anyhow!("No audio schema version {}; the latest is {}", target, latest_version()));
+    }
+    let current = current_version(conn)?;
+    if target >= current {
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current && m.version <= target) {
+            apply(conn, migration, true)?;
+        }
+    } else {
+        for migration in MIGRATIONS.iter().rev().filter(|m| m.version <= current && m.version > target) {
+            apply(conn, migration, false)?;
+            log::info!("Reverted audio schema version {}: {}", migration.version, migration.description);
+        }
+    }
+    Ok(target)
+}
+
+/// The audio schema version of the database and the migrations applied to it
+#[tauri::command]
+pub async fn get_audio_schema_status(state: State<'_, Arc<ElevenLabsState>>) -> Result<SchemaStatus, AudioError> {
+    state
+        .call_db(|conn| {
+            Ok(SchemaStatus {
+                version: current_version(conn)?,
+                latest: latest_version(),
+                applied: applied_migrations(conn)?,
+            })
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1)").unwrap();
+        stmt.query_map([table], |row| row.get(0)).unwrap().map(|name| name.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_migrations_are_numbered_in_order() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, index + 1, "{}", migration.description);
+        }
+    }
+
+    #[test]
+    fn test_migrate_down_and_up() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(migrate(&conn).unwrap(), latest_version());
+        assert_eq!(applied_migrations(&conn).unwrap().len(), MIGRATIONS.len());
+        conn.execute(
+            "INSERT INTO audio_cache (id, audio_type, prompt, local_path, created_at, favorite)
+             VALUES ('a', '\"sfx\"', 'Thunder', '/a.mp3', '2024-01-01', 1)",
+            [],
+        )
+        .unwrap();
+
+        // Down to the first version, keeping the rows
+        migrate_to(&conn, 1).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        assert!(!columns(&conn, "audio_cache").contains(&"params".to_string()));
+        assert!(columns(&conn, "audio_tags").is_empty());
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM audio_cache", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+
+        // ... and up again, indexing the existing prompt
+        migrate(&conn).unwrap();
+        assert!(columns(&conn, "audio_cache").contains(&"deleted_at".to_string()));
+        let found: i64 = conn
+            .query_row("SELECT COUNT(*) FROM audio_prompt_fts WHERE audio_prompt_fts MATCH 'thunder'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(found, 1);
+
+        migrate_to(&conn, 0).unwrap();
+        assert!(columns(&conn, "audio_cache").is_empty());
+        assert!(migrate_to(&conn, latest_version() + 1).is_err());
+    }
+}
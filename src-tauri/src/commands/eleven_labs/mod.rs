@@ -1,29 +1,203 @@
+pub mod api;
+pub mod archive;
+pub mod audio_channel;
+pub mod audio_tags;
+pub mod auth;
+pub mod billing;
 pub mod cache;
+pub mod cache_location;
+pub mod cancellation;
+pub mod chunking;
+pub mod cli;
 pub mod client;
+pub mod coalesce;
+pub mod compare;
+pub mod clipboard;
+pub mod clone_samples;
+pub mod conflicts;
+pub mod daw;
+pub mod dashboard;
+pub mod deep_link;
+pub mod download_progress;
+pub mod dubbing;
+pub mod encryption;
+pub mod error;
+pub mod event_sounds;
+pub mod external_editor;
+pub mod history;
+pub mod hotkeys;
+pub mod http_api;
+pub mod live_output;
+pub mod logging;
+pub mod mcp_server;
+pub mod migrations;
+pub mod narration;
+pub mod notifications;
+pub mod pipeline;
+pub mod presets;
+pub mod podcast;
+pub mod processing;
+pub mod project_files;
+pub mod protocol;
+pub mod quota;
+pub mod reconcile;
+pub mod realtime;
+pub mod recovery;
+pub mod remote;
+pub mod report;
+pub mod s3;
+pub mod schema;
+pub mod secrets;
+pub mod sessions;
+pub mod sources;
+pub mod speech_queue;
+pub mod subtitles;
+pub mod summarizer;
+pub mod supervisor;
+pub mod sync;
+pub mod text_normalize;
+pub mod transcriptions;
+pub mod trash;
+pub mod tts_stream;
 pub mod types;
+pub mod usage_history;
+pub mod voice_alerts;
+pub mod voice_collections;
+pub mod voice_commands;
+pub mod voice_design;
+pub mod voice_input;
+pub mod voice_library;
+pub mod webdav;
+pub mod webhooks;
+
+#[cfg(test)]
+mod integration_tests;
 
 use anyhow::Result;
-use std::path::PathBuf;
-use std::sync::Mutex;
-use tauri::State;
-
-use crate::commands::agents::get_db_path;
-use cache::{AudioCache, AudioCacheDb, CharacterVoiceDb, SettingsDb, VoiceProfileDb};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State};
+use tokio::sync::RwLock;
+
+use cache::{AgentVoiceDb, AudioCache, AudioCacheDb, AudioDb, CharacterVoiceDb, SettingsDb, VoiceProfileDb, VoiceUsageDb};
+use api::ElevenLabsApi;
 use client::ElevenLabsClient;
+use error::AudioError;
+use auth::AuthGate;
+use cache_location::{CacheLocation, CacheLocationSource};
+use voice_collections::VoiceFilter;
+use cancellation::GenerationJobs;
+use coalesce::Coalescer;
+use quota::QuotaTracker;
+use supervisor::{TaskHandle, TaskSupervisor};
 use types::*;
 
-/// Shared state for Eleven Labs client
+/// Settings key overriding the audio cache directory
+pub const AUDIO_CACHE_DIR_KEY: &str = "audio_cache_dir";
+
+/// Settings key holding the cache directories used before the current one
+pub const AUDIO_CACHE_PREVIOUS_DIRS_KEY: &str = "audio_cache_previous_dirs";
+
+/// Shared state for Eleven Labs client. The app manages it as an `Arc` so the
+/// MCP and HTTP servers it starts share it with the commands.
 pub struct ElevenLabsState {
-    client: Mutex<Option<ElevenLabsClient>>,
-    cache: Mutex<Option<AudioCache>>,
+    /// One client (and so one connection pool) shared by every command
+    client: RwLock<Option<Arc<dyn ElevenLabsApi>>>,
+    /// Opened on first use
+    db: Mutex<Option<AudioDb>>,
+    /// Created once and shared; replaced by `set_audio_cache_dir`
+    cache: Mutex<Option<Arc<AudioCache>>>,
+    /// Where `cache` lives and whether it is a fallback
+    cache_location: Mutex<Option<CacheLocation>>,
+    /// Long-running tasks, stopped together on app exit
+    tasks: TaskSupervisor,
+    mcp_server: tokio::sync::Mutex<Option<TaskHandle>>,
+    http_api: tokio::sync::Mutex<Option<TaskHandle>>,
+    clipboard_watcher: tokio::sync::Mutex<Option<TaskHandle>>,
+    /// WebSocket speech streams in progress, by stream id
+    tts_streams: tokio::sync::Mutex<std::collections::HashMap<String, TaskHandle>>,
+    hotkeys: Mutex<Vec<(tauri_plugin_global_shortcut::Shortcut, hotkeys::HotkeyAction)>>,
+    notification_target: Mutex<Option<(String, std::time::Instant)>>,
+    voice_prompt: Mutex<Option<voice_input::VoiceRecording>>,
+    /// Set while a background voice list refresh is running
+    voice_refresh: AtomicBool,
+    /// Remaining character quota, checked before TTS requests
+    quota: QuotaTracker,
+    /// Whether the saved API key is still accepted
+    auth: AuthGate,
+    /// Generations in flight, so identical concurrent requests share one call
+    generations: Coalescer<GeneratedAudio>,
+    /// Generations started with a job id, which can be cancelled
+    generation_jobs: GenerationJobs,
 }
 
 impl ElevenLabsState {
     pub fn new() -> Self {
         Self {
-            client: Mutex::new(None),
+            client: RwLock::new(None),
+            db: Mutex::new(None),
             cache: Mutex::new(None),
+            cache_location: Mutex::new(None),
+            tasks: TaskSupervisor::new(),
+            mcp_server: tokio::sync::Mutex::new(None),
+            http_api: tokio::sync::Mutex::new(None),
+            clipboard_watcher: tokio::sync::Mutex::new(None),
+            tts_streams: tokio::sync::Mutex::new(std::collections::HashMap::new()),
+            hotkeys: Mutex::new(Vec::new()),
+            notification_target: Mutex::new(None),
+            voice_prompt: Mutex::new(None),
+            voice_refresh: AtomicBool::new(false),
+            quota: QuotaTracker::default(),
+            auth: AuthGate::new(),
+            generations: Coalescer::new(),
+            generation_jobs: GenerationJobs::new(),
+        }
+    }
+}
+
+impl ElevenLabsState {
+    /// Supervisor of the audio background tasks
+    pub fn tasks(&self) -> &TaskSupervisor {
+        &self.tasks
+    }
+
+    /// Whether the API key needs to be re-entered
+    pub fn auth(&self) -> &AuthGate {
+        &self.auth
+    }
+
+    /// The shared database connection
+    pub fn db(&self) -> Result<AudioDb, AudioError> {
+        let mut db_guard = self.db.lock()?;
+        if let Some(db) = db_guard.as_ref() {
+            return Ok(db.clone());
         }
+        let db = AudioDb::open()?;
+        *db_guard = Some(db.clone());
+        Ok(db)
+    }
+
+    /// Run `f` on the shared database connection, blocking the calling thread
+    #[cfg(test)]
+    pub fn with_db<T>(&self, f: impl FnOnce(&mut rusqlite::Connection) -> Result<T>) -> Result<T, AudioError> {
+        self.db()?.with_blocking(f)
+    }
+
+    /// Run `f` on the shared database connection from a blocking thread.
+    /// Commands use this so queries never block the async runtime.
+    pub async fn call_db<T, F>(&self, f: F) -> Result<T, AudioError>
+    where
+        F: FnOnce(&mut rusqlite::Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.db()?.call(f).await
+    }
+
+    /// Use `api` for provider requests instead of a client created from the
+    /// stored API key
+    pub async fn set_client(&self, api: Arc<dyn ElevenLabsApi>) {
+        *self.client.write().await = Some(api);
     }
 }
 
@@ -33,44 +207,88 @@ impl Default for ElevenLabsState {
     }
 }
 
-/// Initialize the Eleven Labs state with stored API key
-fn ensure_client(state: &ElevenLabsState) -> Result<(), String> {
-    let mut client_guard = state.client.lock().map_err(|e| e.to_string())?;
+/// The shared client, created from the stored API key on first use.
+/// `None` when no key has been configured.
+async fn load_client(state: &ElevenLabsState) -> Result<Option<Arc<dyn ElevenLabsApi>>, AudioError> {
+    if let Some(client) = state.client.read().await.as_ref() {
+        return Ok(Some(client.clone()));
+    }
 
-    if client_guard.is_some() {
-        return Ok(());
+    let mut client_guard = state.client.write().await;
+    // Another command may have created it while we waited for the write lock
+    if let Some(client) = client_guard.as_ref() {
+        return Ok(Some(client.clone()));
     }
 
     // Try to load API key from database
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    if let Some(api_key) = state.call_db(|conn| SettingsDb::get_api_key(conn)).await? {
+        *client_guard = Some(Arc::new(ElevenLabsClient::new(api_key)?));
+    }
 
-    if let Some(api_key) = SettingsDb::get_api_key(&conn).map_err(|e| e.to_string())? {
-        let client = ElevenLabsClient::new(api_key).map_err(|e| e.to_string())?;
-        *client_guard = Some(client);
+    Ok(client_guard.clone())
+}
+
+/// Get a handle to the configured client without holding the state lock
+async fn get_client(state: &ElevenLabsState) -> Result<Arc<dyn ElevenLabsApi>, AudioError> {
+    // Don't retry a rejected key until a new one is saved
+    if state.auth.is_rejected() {
+        return Err(auth::auth_required_error());
     }
+    load_client(state)
+        .await?
+        .ok_or_else(|| AudioError::NotConfigured("API key not configured".to_string()))
+}
 
+/// Create the client and open its connection in the background at startup,
+/// so the first generation doesn't pay for the handshake. Does nothing when
+/// no API key is configured.
+pub async fn warm_up_client(state: &ElevenLabsState) -> Result<(), AudioError> {
+    if let Some(client) = load_client(state).await? {
+        client.warm_up().await?;
+    }
     Ok(())
 }
 
-/// Ensure audio cache is initialized
-fn ensure_cache(state: &ElevenLabsState) -> Result<AudioCache, String> {
-    let mut cache_guard = state.cache.lock().map_err(|e| e.to_string())?;
-
-    if let Some(cache) = cache_guard.as_ref() {
-        return Ok(AudioCache::new(cache.cache_dir().to_path_buf()).map_err(|e| e.to_string())?);
+/// Directories the cache used before it was moved, oldest first
+async fn previous_cache_dirs(state: &ElevenLabsState) -> Result<Vec<String>, AudioError> {
+    match state.call_db(|conn| SettingsDb::get_setting(conn, AUDIO_CACHE_PREVIOUS_DIRS_KEY)).await? {
+        Some(json) => Ok(serde_json::from_str(&json)?),
+        None => Ok(Vec::new()),
     }
+}
 
-    // Create cache directory
-    let cache_dir = dirs::cache_dir()
-        .ok_or("Could not find cache directory")?
-        .join("opcode")
-        .join("audio");
+/// Open the cache at `location`. Files in the default, fallback and
+/// `previous` cache directories are still treated as cached, so they can be
+/// deleted.
+async fn open_cache(location: &CacheLocation, previous: &[String]) -> Result<AudioCache, AudioError> {
+    let roots = previous
+        .iter()
+        .map(PathBuf::from)
+        .chain(cache_location::fallback_dirs().into_iter().filter_map(|(_, dir)| dir))
+        .collect();
+    Ok(AudioCache::create(PathBuf::from(&location.path)).await?.with_roots(roots))
+}
 
-    let cache = AudioCache::new(cache_dir).map_err(|e| e.to_string())?;
-    *cache_guard = Some(AudioCache::new(cache.cache_dir().to_path_buf()).map_err(|e| e.to_string())?);
+/// Ensure audio cache is initialized, returning the shared instance
+async fn ensure_cache(state: &ElevenLabsState) -> Result<Arc<AudioCache>, AudioError> {
+    if let Some(cache) = state.cache.lock()?.as_ref() {
+        return Ok(cache.clone());
+    }
 
-    Ok(cache)
+    let configured = state
+        .call_db(|conn| SettingsDb::get_setting(conn, AUDIO_CACHE_DIR_KEY))
+        .await?
+        .map(PathBuf::from);
+    let location = cache_location::resolve(configured).await?;
+    let previous = previous_cache_dirs(state).await?;
+    let cache = Arc::new(open_cache(&location, &previous).await?);
+
+    // Keep the instance another command may have created in the meantime
+    let mut current = state.cache.lock()?;
+    if current.is_none() {
+        *state.cache_location.lock()? = Some(location);
+    }
+    Ok(current.get_or_insert(cache).clone())
 }
 
 // ========== Tauri Commands ==========
@@ -78,25 +296,23 @@ fn ensure_cache(state: &ElevenLabsState) -> Result<AudioCache, String> {
 /// Set the Eleven Labs API key
 #[tauri::command]
 pub async fn eleven_labs_set_api_key(
-    state: State<'_, ElevenLabsState>,
+    state: State<'_, Arc<ElevenLabsState>>,
     api_key: String,
-) -> Result<bool, String> {
+) -> Result<bool, AudioError> {
     // Validate the API key first
-    let client = ElevenLabsClient::new(api_key.clone()).map_err(|e| e.to_string())?;
-    let valid = client.validate_api_key().await.map_err(|e| e.to_string())?;
+    let client = ElevenLabsClient::new(api_key.clone())?;
+    let valid = client.validate_api_key().await?;
 
     if !valid {
-        return Err("Invalid API key".to_string());
+        return Err(AudioError::Validation("Invalid API key".to_string()));
     }
 
     // Save to database
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
-    SettingsDb::save_api_key(&conn, &api_key).map_err(|e| e.to_string())?;
+    state.call_db(move |conn| SettingsDb::save_api_key(conn, &api_key)).await?;
 
-    // Update state
-    let mut client_guard = state.client.lock().map_err(|e| e.to_string())?;
-    *client_guard = Some(client);
+    // Update state, resuming jobs that were waiting for a valid key
+    *state.client.write().await = Some(Arc::new(client));
+    state.auth.accept();
 
     Ok(true)
 }
@@ -104,286 +320,622 @@ pub async fn eleven_labs_set_api_key(
 /// Check if API key is configured
 #[tauri::command]
 pub async fn eleven_labs_has_api_key(
-    state: State<'_, ElevenLabsState>,
-) -> Result<bool, String> {
-    ensure_client(&state)?;
-    let client_guard = state.client.lock().map_err(|e| e.to_string())?;
-    Ok(client_guard.is_some())
+    state: State<'_, Arc<ElevenLabsState>>,
+) -> Result<bool, AudioError> {
+    Ok(load_client(&state).await?.is_some())
 }
 
-/// List all available voices
+/// List voices from the local cache, refreshing them from the provider in
+/// the background (see `pipeline::VOICES_UPDATED_EVENT`). `filter` keeps only
+/// favorites and/or the voices of a collection; the event is not filtered.
 #[tauri::command]
 pub async fn eleven_labs_list_voices(
-    state: State<'_, ElevenLabsState>,
-) -> Result<Vec<VoiceProfile>, String> {
-    ensure_client(&state)?;
-
-    let client_guard = state.client.lock().map_err(|e| e.to_string())?;
-    let client = client_guard.as_ref().ok_or("API key not configured")?;
+    app: AppHandle,
+    state: State<'_, Arc<ElevenLabsState>>,
+    filter: Option<VoiceFilter>,
+) -> Result<VoiceList, AudioError> {
+    let mut list = pipeline::list_voices_local_first(&app, &state).await?;
+    if let Some(filter) = filter {
+        list.voices = state
+            .call_db(move |conn| voice_collections::apply_filter(conn, list.voices, &filter))
+            .await?;
+    }
+    Ok(list)
+}
 
-    let voices = client.list_voices().await.map_err(|e| e.to_string())?;
+/// Number of voices `list_recent_voices` returns by default
+const DEFAULT_RECENT_VOICES: usize = 8;
 
-    // Cache voices locally
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+/// List the voices most recently used for generation, newest first
+#[tauri::command]
+pub async fn list_recent_voices(
+    state: State<'_, Arc<ElevenLabsState>>,
+    limit: Option<usize>,
+) -> Result<Vec<RecentVoice>, AudioError> {
+    let limit = limit.unwrap_or(DEFAULT_RECENT_VOICES);
+    state.call_db(move |conn| VoiceUsageDb::get_recent_voices(conn, limit)).await
+}
 
-    for voice in &voices {
-        let _ = VoiceProfileDb::save_voice_profile(&conn, voice, &voice.voice_id);
-    }
+/// Voice samples ready to upload. The temporary directories holding processed
+/// copies are removed when this is dropped, so keep it until the upload is done.
+struct PreparedSamples {
+    /// Managed copies of the files, kept so the samples stay available
+    managed_paths: Vec<String>,
+    uploads: Vec<String>,
+    _dirs: (Option<tempfile::TempDir>, Option<tempfile::TempDir>),
+}
 
-    Ok(voices)
+/// Copy sample files into the managed source directory, run the before-upload
+/// hooks on them and catch any the provider would reject before uploading
+async fn prepare_voice_samples(
+    state: &ElevenLabsState,
+    voice_name: &str,
+    files: Vec<String>,
+    project_id: Option<&str>,
+) -> Result<PreparedSamples, AudioError> {
+    let voice_name = voice_name.to_string();
+    let sources = state.call_db(move |conn| sources::import_sources(conn, &voice_name, &files)).await?;
+    let managed_paths: Vec<String> = sources.into_iter().map(|s| s.managed_path).collect();
+
+    let db = state.db()?;
+    let (files, processed_dir) = processing::prepare_uploads(&db, project_id, &managed_paths).await?;
+    let ffmpeg = processing::AudioHookConfig::load(&db, project_id).await?.ffmpeg;
+    let (uploads, split_dir) = clone_samples::prepare_samples(ffmpeg.as_deref().unwrap_or("ffmpeg"), &files).await?;
+
+    Ok(PreparedSamples { managed_paths, uploads, _dirs: (processed_dir, split_dir) })
 }
 
 /// Clone a voice from audio files
 #[tauri::command]
 pub async fn eleven_labs_clone_voice(
-    state: State<'_, ElevenLabsState>,
+    state: State<'_, Arc<ElevenLabsState>>,
     name: String,
     files: Vec<String>,
     description: Option<String>,
     labels: Option<serde_json::Value>,
-) -> Result<VoiceProfile, String> {
-    ensure_client(&state)?;
-
-    let client_guard = state.client.lock().map_err(|e| e.to_string())?;
-    let client = client_guard.as_ref().ok_or("API key not configured")?;
+    project_id: Option<String>,
+) -> Result<VoiceProfile, AudioError> {
+    let client = get_client(&state).await?;
+    let samples = prepare_voice_samples(&state, &name, files, project_id.as_deref()).await?;
+    let managed_paths = samples.managed_paths.clone();
 
     let request = VoiceCloneRequest {
         name,
         description,
         labels,
-        files,
+        files: samples.uploads.clone(),
     };
 
-    let voice = client.clone_voice(request).await.map_err(|e| e.to_string())?;
+    let voice = auth::check(&state, client.clone_voice(request).await).await?;
 
     // Cache the new voice
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
-    VoiceProfileDb::save_voice_profile(&conn, &voice, &voice.voice_id).map_err(|e| e.to_string())?;
-
-    Ok(voice)
+    state
+        .call_db(move |conn| {
+            VoiceProfileDb::save_voice_profile(conn, &voice, &voice.voice_id)?;
+            sources::CloneSourceDb::set_voice_id(conn, &managed_paths, &voice.voice_id)?;
+            Ok(voice)
+        })
+        .await
 }
 
-/// Delete a voice
+/// Edit a voice: rename it, change its description or labels, upload more
+/// samples or delete some by `sample_id`. Unset fields are left as they are.
 #[tauri::command]
-pub async fn eleven_labs_delete_voice(
-    state: State<'_, ElevenLabsState>,
+#[allow(clippy::too_many_arguments)]
+pub async fn eleven_labs_edit_voice(
+    state: State<'_, Arc<ElevenLabsState>>,
     voice_id: String,
-) -> Result<(), String> {
-    ensure_client(&state)?;
+    name: Option<String>,
+    description: Option<String>,
+    labels: Option<serde_json::Value>,
+    add_files: Option<Vec<String>>,
+    remove_sample_ids: Option<Vec<String>>,
+    project_id: Option<String>,
+) -> Result<VoiceProfile, AudioError> {
+    let client = get_client(&state).await?;
+
+    // New samples go through the same managed copies, hooks and checks as cloning
+    let add_files = add_files.unwrap_or_default();
+    let samples = if add_files.is_empty() {
+        None
+    } else {
+        let voice_name = match &name {
+            Some(name) => name.clone(),
+            None => auth::check(&state, client.get_voice(&voice_id).await).await?.name,
+        };
+        Some(prepare_voice_samples(&state, &voice_name, add_files, project_id.as_deref()).await?)
+    };
+    let managed_paths = samples.as_ref().map(|s| s.managed_paths.clone()).unwrap_or_default();
 
-    let client_guard = state.client.lock().map_err(|e| e.to_string())?;
-    let client = client_guard.as_ref().ok_or("API key not configured")?;
+    let request = VoiceEditRequest {
+        voice_id,
+        name,
+        description,
+        labels,
+        add_files: samples.as_ref().map(|s| s.uploads.clone()).unwrap_or_default(),
+        remove_sample_ids: remove_sample_ids.unwrap_or_default(),
+    };
+    let voice = auth::check(&state, client.edit_voice(request).await).await?;
+
+    // Refresh the cached voice
+    state
+        .call_db(move |conn| {
+            VoiceProfileDb::save_voice_profile(conn, &voice, &voice.voice_id)?;
+            sources::CloneSourceDb::set_voice_id(conn, &managed_paths, &voice.voice_id)?;
+            Ok(voice)
+        })
+        .await
+}
 
-    client.delete_voice(&voice_id).await.map_err(|e| e.to_string())?;
+/// Delete a voice. Fails with `voice_in_use`, listing the references, while
+/// characters, agents or cached renders still use it, unless `force` is set;
+/// forcing also removes the voice's character and agent mappings.
+#[tauri::command]
+pub async fn eleven_labs_delete_voice(
+    state: State<'_, Arc<ElevenLabsState>>,
+    voice_id: String,
+    force: Option<bool>,
+) -> Result<(), AudioError> {
+    let id = voice_id.clone();
+    let references = state
+        .call_db(move |conn| VoiceProfileDb::get_voice_references(conn, &id))
+        .await?;
+    if !references.is_empty() && !force.unwrap_or(false) {
+        return Err(AudioError::VoiceInUse { voice_id, references });
+    }
 
-    // Remove from local cache
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
-    VoiceProfileDb::delete_voice_profile(&conn, &voice_id).map_err(|e| e.to_string())?;
+    let client = get_client(&state).await?;
+
+    auth::check(&state, client.delete_voice(&voice_id).await).await?;
+
+    // Remove from local cache along with any mappings to it
+    state
+        .call_db(move |conn| {
+            let tx = conn.transaction()?;
+            let removed = VoiceProfileDb::delete_voice_mappings(&tx, &voice_id)?;
+            VoiceProfileDb::delete_voice_profile(&tx, &voice_id)?;
+            VoiceUsageDb::delete_voice_usage(&tx, &voice_id)?;
+            voice_collections::forget_voice(&tx, &voice_id)?;
+            tx.commit()?;
+            if removed > 0 {
+                log::info!("Removed {} mappings to deleted voice {}", removed, voice_id);
+            }
+            Ok(())
+        })
+        .await?;
 
     Ok(())
 }
 
-/// Generate text-to-speech
+/// Generate text-to-speech. An identical earlier request returns its record,
+/// marked `cached`, without using quota unless `force_regenerate` is set.
+/// With a `job_id` it can be cancelled with `eleven_labs_cancel_generation`
+/// while it runs; with a `seed` it can be reproduced by
+/// `regenerate_with_same_seed`.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn eleven_labs_tts(
-    state: State<'_, ElevenLabsState>,
+    state: State<'_, Arc<ElevenLabsState>>,
     text: String,
     voice_id: String,
     model_id: Option<String>,
     voice_settings: Option<VoiceSettings>,
-) -> Result<GeneratedAudio, String> {
-    ensure_client(&state)?;
+    project_id: Option<String>,
+    normalize_text: Option<bool>,
+    preset: Option<String>,
+    job_id: Option<String>,
+    seed: Option<u32>,
+    force_regenerate: Option<bool>,
+) -> Result<GeneratedAudio, AudioError> {
+    let request = TtsRequest {
+        text,
+        voice_id,
+        model_id: model_id.unwrap_or_else(default_model_id),
+        voice_settings,
+        output_format: default_output_format(),
+        normalize_text,
+        preset,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed,
+    };
 
-    let client_guard = state.client.lock().map_err(|e| e.to_string())?;
-    let client = client_guard.as_ref().ok_or("API key not configured")?;
+    let force_regenerate = force_regenerate.unwrap_or(false);
+    let generation = pipeline::generate_tts_or_reuse(&state, request, project_id.as_deref(), force_regenerate);
+    state.generation_jobs.run(job_id, generation).await
+}
 
+/// Generate consecutive parts of a text (paragraphs, say) as separate records
+/// that sound continuous: each is conditioned on its neighbours' text and the
+/// requests before it. Cancellable by `job_id` like `eleven_labs_tts`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn eleven_labs_tts_parts(
+    state: State<'_, Arc<ElevenLabsState>>,
+    parts: Vec<String>,
+    voice_id: String,
+    model_id: Option<String>,
+    voice_settings: Option<VoiceSettings>,
+    project_id: Option<String>,
+    normalize_text: Option<bool>,
+    preset: Option<String>,
+    job_id: Option<String>,
+) -> Result<Vec<GeneratedAudio>, AudioError> {
     let request = TtsRequest {
-        text: text.clone(),
-        voice_id: voice_id.clone(),
-        model_id: model_id.unwrap_or_else(|| "eleven_monolingual_v1".to_string()),
+        text: String::new(),
+        voice_id,
+        model_id: model_id.unwrap_or_else(default_model_id),
         voice_settings,
-        output_format: "mp3_44100_128".to_string(),
+        output_format: default_output_format(),
+        normalize_text,
+        preset,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
     };
 
-    let audio_data = client.text_to_speech(request).await.map_err(|e| e.to_string())?;
+    let generation = pipeline::generate_tts_parts_for_project(&state, request, parts, project_id.as_deref());
+    state.generation_jobs.run(job_id, generation).await
+}
 
-    // Save to cache
-    let cache = ensure_cache(&state)?;
-    let path = cache.save_audio(&AudioType::Tts, &audio_data, "mp3")
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Estimate duration (rough: ~128kbps = 16KB/s)
-    let duration_seconds = audio_data.len() as f32 / 16000.0;
-
-    let audio = GeneratedAudio {
-        id: uuid::Uuid::new_v4().to_string(),
-        audio_type: AudioType::Tts,
-        prompt: text,
-        duration_seconds,
-        local_path: path.to_string_lossy().to_string(),
-        supabase_url: None,
-        metadata: serde_json::json!({ "voice_id": voice_id }),
-        created_at: chrono::Utc::now().to_rfc3339(),
+/// Convert a recording into another voice (speech-to-speech)
+#[tauri::command]
+pub async fn eleven_labs_speech_to_speech(
+    state: State<'_, Arc<ElevenLabsState>>,
+    source_path: String,
+    voice_id: String,
+    model_id: Option<String>,
+    voice_settings: Option<VoiceSettings>,
+    remove_background_noise: Option<bool>,
+    project_id: Option<String>,
+) -> Result<GeneratedAudio, AudioError> {
+    let request = VoiceChangeRequest {
+        source_path,
+        voice_id,
+        model_id: model_id.unwrap_or_else(default_sts_model_id),
+        voice_settings,
+        output_format: default_output_format(),
+        remove_background_noise: remove_background_noise.unwrap_or(false),
+    };
+
+    pipeline::generate_voice_change_for_project(&state, request, project_id.as_deref()).await
+}
+
+/// Generate text-to-speech with the time each character and word is spoken,
+/// stored as `alignment` in the record's metadata for highlighting the text
+/// during playback. Cancellable by `job_id` like `eleven_labs_tts`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn eleven_labs_tts_with_timestamps(
+    state: State<'_, Arc<ElevenLabsState>>,
+    text: String,
+    voice_id: String,
+    model_id: Option<String>,
+    voice_settings: Option<VoiceSettings>,
+    project_id: Option<String>,
+    normalize_text: Option<bool>,
+    preset: Option<String>,
+    job_id: Option<String>,
+) -> Result<GeneratedAudio, AudioError> {
+    let request = TtsRequest {
+        text,
+        voice_id,
+        model_id: model_id.unwrap_or_else(default_model_id),
+        voice_settings,
+        output_format: default_output_format(),
+        normalize_text,
+        preset,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
     };
 
-    // Save record to database
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
-    AudioCacheDb::save_audio_record(&conn, &audio).map_err(|e| e.to_string())?;
+    let generation = pipeline::generate_tts_with_timestamps_for_project(&state, request, project_id.as_deref());
+    state.generation_jobs.run(job_id, generation).await
+}
 
-    Ok(audio)
+/// Remove background noise from a recording, keeping the voice; useful for
+/// cleaning up samples before cloning
+#[tauri::command]
+pub async fn eleven_labs_isolate_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
+    source_path: String,
+    project_id: Option<String>,
+) -> Result<GeneratedAudio, AudioError> {
+    pipeline::isolate_audio_for_project(&state, &source_path, project_id.as_deref()).await
 }
 
-/// Generate sound effects
+/// Generate sound effects, cancellable by `job_id` like `eleven_labs_tts`
 #[tauri::command]
 pub async fn eleven_labs_generate_sfx(
-    state: State<'_, ElevenLabsState>,
+    state: State<'_, Arc<ElevenLabsState>>,
     text: String,
     duration_seconds: Option<f32>,
     prompt_influence: Option<f32>,
-) -> Result<GeneratedAudio, String> {
-    ensure_client(&state)?;
-
-    let client_guard = state.client.lock().map_err(|e| e.to_string())?;
-    let client = client_guard.as_ref().ok_or("API key not configured")?;
-
-    let duration = duration_seconds.unwrap_or(3.0);
+    project_id: Option<String>,
+    job_id: Option<String>,
+) -> Result<GeneratedAudio, AudioError> {
     let request = SfxRequest {
-        text: text.clone(),
-        duration_seconds: duration,
+        text,
+        duration_seconds: duration_seconds.unwrap_or(3.0),
         prompt_influence: prompt_influence.unwrap_or(0.5),
     };
 
-    let audio_data = client.generate_sound_effects(request).await.map_err(|e| e.to_string())?;
+    let generation = pipeline::generate_sfx_for_project(&state, request, project_id.as_deref());
+    state.generation_jobs.run(job_id, generation).await
+}
 
-    // Save to cache
-    let cache = ensure_cache(&state)?;
-    let path = cache.save_audio(&AudioType::Sfx, &audio_data, "mp3")
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let audio = GeneratedAudio {
-        id: uuid::Uuid::new_v4().to_string(),
-        audio_type: AudioType::Sfx,
-        prompt: text,
-        duration_seconds: duration,
-        local_path: path.to_string_lossy().to_string(),
-        supabase_url: None,
-        metadata: serde_json::json!({}),
-        created_at: chrono::Utc::now().to_rfc3339(),
+/// Compose music from a prompt, 10 seconds to 5 minutes long. Cancellable by
+/// `job_id` like `eleven_labs_tts`.
+#[tauri::command]
+pub async fn eleven_labs_generate_music(
+    state: State<'_, Arc<ElevenLabsState>>,
+    prompt: String,
+    duration_seconds: Option<f32>,
+    style: Option<String>,
+    mood: Option<String>,
+    project_id: Option<String>,
+    job_id: Option<String>,
+) -> Result<GeneratedAudio, AudioError> {
+    let request = MusicRequest {
+        prompt,
+        duration_seconds: duration_seconds.unwrap_or(30.0),
+        style,
+        mood,
     };
 
-    // Save record to database
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
-    AudioCacheDb::save_audio_record(&conn, &audio).map_err(|e| e.to_string())?;
+    let generation = pipeline::generate_music_for_project(&state, request, project_id.as_deref());
+    state.generation_jobs.run(job_id, generation).await
+}
 
-    Ok(audio)
+/// List the available models with their languages and whether they do
+/// text-to-speech and/or speech-to-speech
+#[tauri::command]
+pub async fn eleven_labs_list_models(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<ModelInfo>, AudioError> {
+    let client = get_client(&state).await?;
+    auth::check(&state, client.list_models().await).await
 }
 
 /// Get usage information
 #[tauri::command]
 pub async fn eleven_labs_get_usage(
-    state: State<'_, ElevenLabsState>,
-) -> Result<UsageInfo, String> {
-    ensure_client(&state)?;
+    state: State<'_, Arc<ElevenLabsState>>,
+) -> Result<UsageInfo, AudioError> {
+    let client = get_client(&state).await?;
+
+    let usage = auth::check(&state, client.get_usage().await).await?;
+    state.quota.update(&usage);
 
-    let client_guard = state.client.lock().map_err(|e| e.to_string())?;
-    let client = client_guard.as_ref().ok_or("API key not configured")?;
+    if usage.character_limit > 0
+        && usage.character_count as f64 >= usage.character_limit as f64 * webhooks::QUOTA_WARNING_RATIO
+    {
+        webhooks::dispatch(&state, webhooks::EVENT_QUOTA_WARNING, &usage);
+    }
 
-    client.get_usage().await.map_err(|e| e.to_string())
+    Ok(usage)
 }
 
-/// Assign a voice to a character
+/// Assign a voice to a character, optionally with a voice settings preset
 #[tauri::command]
 pub async fn assign_voice_to_character(
+    state: State<'_, Arc<ElevenLabsState>>,
     character_name: String,
     voice_id: String,
     voice_name: String,
     project_id: Option<String>,
-) -> Result<CharacterVoice, String> {
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
-
-    CharacterVoiceDb::assign_voice(
-        &conn,
-        &character_name,
-        &voice_id,
-        &voice_name,
-        project_id.as_deref(),
-    )
-    .map_err(|e| e.to_string())
+    preset: Option<String>,
+) -> Result<CharacterVoice, AudioError> {
+    state
+        .call_db(move |conn| {
+            let preset = preset.map(|name| presets::require_preset(conn, &name)).transpose()?;
+            let mut mapping = CharacterVoiceDb::assign_voice(
+                conn,
+                &character_name,
+                &voice_id,
+                &voice_name,
+                project_id.as_deref(),
+            )?;
+            if let Some(preset) = preset {
+                CharacterVoiceDb::set_preset(conn, &mapping.id, Some(&preset.name))?;
+                mapping.preset = Some(preset.name);
+            }
+            Ok(mapping)
+        })
+        .await
 }
 
 /// List character voice mappings
 #[tauri::command]
 pub async fn list_character_voices(
+    state: State<'_, Arc<ElevenLabsState>>,
     project_id: Option<String>,
-) -> Result<Vec<CharacterVoice>, String> {
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+) -> Result<Vec<CharacterVoice>, AudioError> {
+    state.call_db(move |conn| CharacterVoiceDb::get_character_voices(conn, project_id.as_deref())).await
+}
+
+/// Assign a voice to an agent, looking up the voice name from the cache if not given
+#[tauri::command]
+pub async fn assign_voice_to_agent(
+    state: State<'_, Arc<ElevenLabsState>>,
+    agent_id: i64,
+    voice_id: String,
+    voice_name: Option<String>,
+) -> Result<AgentVoice, AudioError> {
+    state
+        .call_db(move |conn| {
+            let voice_name = match voice_name {
+                Some(name) => name,
+                None => VoiceProfileDb::get_voice_profile(conn, &voice_id)?
+                    .map(|voice| voice.name)
+                    .ok_or_else(|| AudioError::Validation(format!("Unknown voice: {}", voice_id)))?,
+            };
+
+            AgentVoiceDb::assign_voice(conn, agent_id, &voice_id, &voice_name)
+        })
+        .await
+}
 
-    CharacterVoiceDb::get_character_voices(&conn, project_id.as_deref())
-        .map_err(|e| e.to_string())
+/// List agent voice mappings
+#[tauri::command]
+pub async fn list_agent_voices(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<AgentVoice>, AudioError> {
+    state.call_db(|conn| AgentVoiceDb::get_agent_voices(conn)).await
 }
 
-/// Get cached audio records
+/// Resolve which voice speaks for an agent, project and character, and the
+/// scope the assignment came from
+#[tauri::command]
+pub async fn resolve_voice(
+    state: State<'_, Arc<ElevenLabsState>>,
+    context: VoiceContext,
+) -> Result<ResolvedVoice, AudioError> {
+    pipeline::resolve_voice(&state.db()?, &context).await
+}
+
+/// Get cached audio records of a type, newest first unless `query` says
+/// otherwise. `query` also filters by voice and creation date and pages the
+/// results with `limit` and `offset`.
 #[tauri::command]
 pub async fn get_cached_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
     audio_type: String,
-) -> Result<Vec<GeneratedAudio>, String> {
-    let audio_type = match audio_type.to_lowercase().as_str() {
-        "tts" => AudioType::Tts,
-        "sfx" => AudioType::Sfx,
-        "music" => AudioType::Music,
-        _ => return Err("Invalid audio type".to_string()),
-    };
+    query: Option<AudioQuery>,
+) -> Result<Vec<GeneratedAudio>, AudioError> {
+    let audio_type = AudioType::parse(&audio_type)?;
+    let query = query.unwrap_or_default();
 
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    state.call_db(move |conn| AudioCacheDb::query_audio_records(conn, &audio_type, &query)).await
+}
 
-    AudioCacheDb::get_audio_records(&conn, &audio_type).map_err(|e| e.to_string())
+/// Cached audio whose prompt contains every word of `query`, best matches
+/// first, optionally of one type
+#[tauri::command]
+pub async fn search_cached_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
+    query: String,
+    audio_type: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<GeneratedAudio>, AudioError> {
+    let audio_type = audio_type.as_deref().map(AudioType::parse).transpose()?;
+    let limit = limit.unwrap_or(50).clamp(1, 500);
+
+    state
+        .call_db(move |conn| AudioCacheDb::search_audio_records(conn, &query, audio_type.as_ref(), limit))
+        .await
 }
 
-/// Delete a cached audio record
+/// Move a cached audio record to the trash, or with `permanent` delete it and
+/// its file right away
 #[tauri::command]
 pub async fn delete_cached_audio(
-    state: State<'_, ElevenLabsState>,
+    state: State<'_, Arc<ElevenLabsState>>,
     audio_id: String,
-) -> Result<(), String> {
-    let db_path = get_db_path().map_err(|e| e.to_string())?;
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
-
-    // Get the record to find the file path
-    if let Some(audio) = AudioCacheDb::get_audio_record(&conn, &audio_id).map_err(|e| e.to_string())? {
-        // Delete the file
-        let cache = ensure_cache(&state)?;
-        let path = PathBuf::from(&audio.local_path);
-        cache.delete_audio(&path).await.map_err(|e| e.to_string())?;
+    permanent: Option<bool>,
+) -> Result<(), AudioError> {
+    if permanent.unwrap_or(false) {
+        pipeline::delete_audio(&state, &audio_id).await
+    } else {
+        trash::trash_audio(&state, &audio_id).await
     }
+}
 
-    // Delete from database
-    AudioCacheDb::delete_audio_record(&conn, &audio_id).map_err(|e| e.to_string())
-}
-
-/// Get all commands for registration
-pub fn get_commands() -> Vec<&'static str> {
-    vec![
-        "eleven_labs_set_api_key",
-        "eleven_labs_has_api_key",
-        "eleven_labs_list_voices",
-        "eleven_labs_clone_voice",
-        "eleven_labs_delete_voice",
-        "eleven_labs_tts",
-        "eleven_labs_generate_sfx",
-        "eleven_labs_get_usage",
-        "assign_voice_to_character",
-        "list_character_voices",
-        "get_cached_audio",
-        "delete_cached_audio",
-    ]
+/// Move every cached record matching `query` (its voice, date, tag and
+/// favorite filters; paging applies per type) to the trash, of `audio_type` or
+/// of every type, or with `permanent` delete them and their files right away.
+/// At least one filter besides the type is required.
+#[tauri::command]
+pub async fn delete_cached_audio_batch(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_type: Option<String>,
+    query: Option<AudioQuery>,
+    permanent: Option<bool>,
+) -> Result<BatchDeleteResult, AudioError> {
+    let audio_type = audio_type.as_deref().map(AudioType::parse).transpose()?;
+    pipeline::delete_audio_batch(&state, audio_type, query.unwrap_or_default(), permanent.unwrap_or(false)).await
+}
+
+/// Render a cached record again from its stored parameters with `overrides`
+/// applied, saving the result as another take of the original
+#[tauri::command]
+pub async fn regenerate_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_id: String,
+    overrides: Option<RegenerateOverrides>,
+    project_id: Option<String>,
+) -> Result<GeneratedAudio, AudioError> {
+    pipeline::regenerate_audio(&state, &audio_id, overrides.unwrap_or_default(), project_id.as_deref()).await
 }
+
+/// Render a record generated with a seed again with the same seed,
+/// reproducing it as another take
+#[tauri::command]
+pub async fn regenerate_with_same_seed(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_id: String,
+    project_id: Option<String>,
+) -> Result<GeneratedAudio, AudioError> {
+    pipeline::regenerate_with_same_seed(&state, &audio_id, project_id.as_deref()).await
+}
+
+/// List every take in the group of a cached record, oldest first
+#[tauri::command]
+pub async fn list_audio_takes(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_id: String,
+) -> Result<Vec<GeneratedAudio>, AudioError> {
+    state.call_db(move |conn| AudioCacheDb::get_takes(conn, &audio_id)).await
+}
+
+/// Move the audio cache to `cache_dir` (the default location when `None`) and
+/// re-initialize it. Files already cached stay where they are. Returns the
+/// directory now in use, which for `None` may be a fallback (see
+/// `cache_location::get_audio_cache_location`).
+#[tauri::command]
+pub async fn set_audio_cache_dir(
+    state: State<'_, Arc<ElevenLabsState>>,
+    cache_dir: Option<String>,
+) -> Result<String, AudioError> {
+    let location = match &cache_dir {
+        Some(dir) => {
+            cache_location::probe_writable(Path::new(dir))
+                .await
+                .map_err(|e| AudioError::Validation(format!("Cannot use {} for the audio cache: {}", dir, e)))?;
+            CacheLocation {
+                path: dir.clone(),
+                source: CacheLocationSource::Configured,
+                skipped: Vec::new(),
+            }
+        }
+        None => cache_location::resolve(None).await?,
+    };
+
+    // Remember where the old files are
+    let mut previous = previous_cache_dirs(&state).await?;
+    let current = ensure_cache(&state).await?.cache_dir().to_string_lossy().to_string();
+    if current != location.path && !previous.contains(&current) {
+        previous.push(current);
+    }
+    let cache = Arc::new(open_cache(&location, &previous).await?);
+    let previous_json = serde_json::to_string(&previous)?;
+
+    state
+        .call_db(move |conn| {
+            SettingsDb::save_setting(conn, AUDIO_CACHE_PREVIOUS_DIRS_KEY, &previous_json)?;
+            match &cache_dir {
+                Some(dir) => SettingsDb::save_setting(conn, AUDIO_CACHE_DIR_KEY, dir),
+                None => SettingsDb::remove_setting(conn, AUDIO_CACHE_DIR_KEY),
+            }
+        })
+        .await?;
+    let path = location.path.clone();
+    *state.cache.lock()? = Some(cache);
+    *state.cache_location.lock()? = Some(location);
+
+    Ok(path)
+}
+
+// `generate_handler!` and `COMMANDS`, listing every `#[tauri::command]` in this
+// module and its submodules
+include!(concat!(env!("OUT_DIR"), "/audio_commands.rs"));
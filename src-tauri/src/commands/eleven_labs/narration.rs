@@ -0,0 +1,458 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+use super::cache::{AudioDb, SettingsDb, VoiceProfileDb};
+use super::error::AudioError;
+use super::pipeline;
+use super::speech_queue::SpeechQueue;
+use super::summarizer::{open_summarizer, SummarizerKind};
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Settings key holding the agent narration configuration
+pub const AGENT_NARRATION_KEY: &str = "agent_narration";
+
+/// Settings key prefix for per-project narration settings, followed by the project path
+pub const PROJECT_NARRATION_PREFIX: &str = "project_narration:";
+
+/// Sentences are grouped up to this length so the first clip renders quickly
+/// without splitting speech into choppy fragments
+const CHUNK_CHARS: usize = 240;
+
+/// How much of a message is spoken
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NarrationMode {
+    /// The whole message, minus code blocks
+    #[default]
+    Full,
+    /// Only the opening sentences of the message
+    Summary,
+}
+
+/// Read agent responses aloud as they arrive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentNarrationConfig {
+    /// Narrate agents that don't have their own narration setting
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub mode: NarrationMode,
+    /// Sentences spoken in summary mode
+    #[serde(default = "default_summary_sentences")]
+    pub summary_sentences: usize,
+    /// Voice for agents without an assigned voice; defaults to the global narrator
+    #[serde(default)]
+    pub voice_id: Option<String>,
+    /// In full mode, messages longer than this many characters are condensed to
+    /// `summary_sentences` by the summarizer before being spoken
+    #[serde(default)]
+    pub summarize_over_chars: Option<usize>,
+    #[serde(default)]
+    pub summarizer: SummarizerKind,
+    /// Model used by the Claude summarizer
+    #[serde(default = "default_summarizer_model")]
+    pub summarizer_model: String,
+}
+
+fn default_summary_sentences() -> usize {
+    2
+}
+
+fn default_summarizer_model() -> String {
+    "haiku".to_string()
+}
+
+impl Default for AgentNarrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: NarrationMode::default(),
+            summary_sentences: default_summary_sentences(),
+            voice_id: None,
+            summarize_over_chars: None,
+            summarizer: SummarizerKind::default(),
+            summarizer_model: default_summarizer_model(),
+        }
+    }
+}
+
+impl AgentNarrationConfig {
+    pub async fn load(db: &AudioDb) -> Result<Self, AudioError> {
+        match db.call(|conn| SettingsDb::get_setting(conn, AGENT_NARRATION_KEY)).await? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// The agent's voice, then the project's narrator, then the configured voice
+    pub async fn voice_for(&self, db: &AudioDb, agent_id: i64, project_path: &str) -> Result<String, AudioError> {
+        let context = VoiceContext {
+            agent_id: Some(agent_id),
+            project_id: Some(project_path.to_string()),
+            character: None,
+            default_voice_id: self.voice_id.clone(),
+        };
+        Ok(pipeline::resolve_voice(db, &context).await?.voice_id)
+    }
+}
+
+/// Auto-narration settings for one project. They take precedence over the
+/// global configuration for runs in that project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectNarrationConfig {
+    /// When false, nothing in the project is narrated or announced
+    pub enabled: bool,
+    /// Verbosity for agents without their own narration setting
+    #[serde(default)]
+    pub mode: NarrationMode,
+}
+
+impl ProjectNarrationConfig {
+    fn key(project_path: &str) -> String {
+        format!("{}{}", PROJECT_NARRATION_PREFIX, project_path)
+    }
+
+    pub async fn load(db: &AudioDb, project_path: &str) -> Result<Option<Self>, AudioError> {
+        let key = Self::key(project_path);
+        db.call(move |conn| SettingsDb::get_setting(conn, &key))
+            .await?
+            .map(|json| serde_json::from_str(&json).map_err(AudioError::from))
+            .transpose()
+    }
+
+    /// Whether voice output is allowed for runs in `project_path`
+    pub async fn allows_speech(db: &AudioDb, project_path: &str) -> Result<bool, AudioError> {
+        Ok(Self::load(db, project_path).await?.is_none_or(|project| project.enabled))
+    }
+}
+
+/// Narration preferences stored on the agent itself
+#[derive(Debug, Clone, Default)]
+pub struct AgentTtsPreferences {
+    pub tts_model: Option<String>,
+    pub tts_speed: Option<f64>,
+    /// `full`, `summary` or `off`; `None` follows the global configuration
+    pub narration: Option<String>,
+}
+
+impl AgentTtsPreferences {
+    pub async fn load(db: &AudioDb, agent_id: i64) -> Result<Self, AudioError> {
+        db.call(move |conn| {
+            let mut stmt = conn.prepare("SELECT tts_model, tts_speed, narration FROM agents WHERE id = ?1")?;
+            let mut rows = stmt.query([agent_id])?;
+
+            match rows.next()? {
+                Some(row) => Ok(Self {
+                    tts_model: row.get(0)?,
+                    tts_speed: row.get(1)?,
+                    narration: row.get(2)?,
+                }),
+                None => Ok(Self::default()),
+            }
+        })
+        .await
+    }
+
+    /// The effective mode; `None` means the agent isn't narrated. A project
+    /// with narration disabled silences every agent; otherwise an explicit
+    /// per-agent setting wins, then the project's verbosity, then the global
+    /// configuration.
+    fn mode(&self, config: &AgentNarrationConfig, project: Option<&ProjectNarrationConfig>) -> Option<NarrationMode> {
+        if project.is_some_and(|project| !project.enabled) {
+            return None;
+        }
+        match self.narration.as_deref() {
+            Some("off") => None,
+            Some("full") => Some(NarrationMode::Full),
+            Some("summary") => Some(NarrationMode::Summary),
+            _ => match project {
+                Some(project) => Some(project.mode),
+                None => config.enabled.then_some(config.mode),
+            },
+        }
+    }
+
+    /// Build a request for `text`, applying the agent's model and speed
+    async fn request(&self, db: &AudioDb, text: String, voice_id: String) -> Result<TtsRequest, AudioError> {
+        let voice_settings = match self.tts_speed {
+            Some(speed) => {
+                let profile_id = voice_id.clone();
+                let mut settings = db
+                    .call(move |conn| VoiceProfileDb::get_voice_profile(conn, &profile_id))
+                    .await?
+                    .map(|voice| voice.settings)
+                    .unwrap_or_default();
+                settings.speed = Some(speed as f32);
+                Some(settings)
+            }
+            None => None,
+        };
+
+        Ok(TtsRequest {
+            text,
+            voice_id,
+            model_id: self.tts_model.clone().unwrap_or_else(default_model_id),
+            voice_settings,
+            output_format: default_output_format(),
+            normalize_text: None,
+            preset: None,
+            previous_text: None,
+            next_text: None,
+            previous_request_ids: Vec::new(),
+            seed: None,
+        })
+    }
+}
+
+/// Text blocks of an assistant message from the `stream-json` output
+fn assistant_text(message: &serde_json::Value) -> Option<String> {
+    if message.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+        return None;
+    }
+
+    let text: Vec<&str> = message
+        .pointer("/message/content")?
+        .as_array()?
+        .iter()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+        .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+        .collect();
+
+    (!text.is_empty()).then(|| text.join("\n\n"))
+}
+
+/// Drop code blocks and markdown punctuation that shouldn't be read out
+pub fn speakable_text(markdown: &str) -> String {
+    let mut lines = vec![];
+    let mut in_code = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let line = line.trim().trim_start_matches(['#', '>', '-', '*']).trim();
+        lines.push(line.replace(['*', '`', '_'], ""));
+    }
+    lines.join("\n")
+}
+
+/// Split text into sentences, keeping terminal punctuation
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = vec![];
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            // Paragraph and list breaks end a sentence too
+            if !current.trim().is_empty() && chars.peek() == Some(&'\n') {
+                sentences.push(current.trim().to_string());
+                current.clear();
+            } else {
+                current.push(' ');
+            }
+            continue;
+        }
+
+        current.push(c);
+        if matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|next| next.is_whitespace()) {
+            sentences.push(current.trim().to_string());
+            current.clear();
+        }
+    }
+
+    if !current.trim().is_empty() {
+        sentences.push(current.trim().to_string());
+    }
+    sentences
+}
+
+/// Group sentences into chunks of roughly `max_chars`
+pub fn chunk_sentences(sentences: &[String], max_chars: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = vec![];
+    for sentence in sentences {
+        match chunks.last_mut() {
+            Some(chunk) if chunk.len() + sentence.len() < max_chars => {
+                chunk.push(' ');
+                chunk.push_str(sentence);
+            }
+            _ => chunks.push(sentence.clone()),
+        }
+    }
+    chunks
+}
+
+/// Queue sentences for speech in chunks, tagging each record with `metadata`
+async fn queue_sentences(
+    app: &AppHandle,
+    preferences: &AgentTtsPreferences,
+    voice_id: &str,
+    sentences: &[String],
+    metadata: &serde_json::Value,
+) -> Result<(), AudioError> {
+    if sentences.is_empty() {
+        return Ok(());
+    }
+
+    let db = app.state::<Arc<ElevenLabsState>>().db()?;
+    // The first chunk is a single sentence so playback starts as soon as possible
+    let (first, rest) = sentences.split_at(1);
+    let chunks = std::iter::once(first[0].clone()).chain(chunk_sentences(rest, CHUNK_CHARS));
+    for chunk in chunks {
+        let request = preferences.request(&db, chunk, voice_id.to_string()).await?;
+        app.state::<SpeechQueue>().enqueue_annotated(request, "agent", metadata.clone())?;
+    }
+    Ok(())
+}
+
+/// Condense a long message with the configured summarizer and speak the summary
+async fn narrate_summary(
+    app: &AppHandle,
+    config: &AgentNarrationConfig,
+    preferences: &AgentTtsPreferences,
+    voice_id: &str,
+    full_text: &str,
+    speakable: &str,
+) -> Result<(), AudioError> {
+    let summarizer = open_summarizer(app, config.summarizer, &config.summarizer_model)?;
+    let summary = summarizer
+        .summarize(speakable, config.summary_sentences)
+        .await?;
+
+    let metadata = serde_json::json!({
+        "full_text": full_text,
+        "spoken_summary": summary,
+        "summarizer": summarizer.name(),
+    });
+    queue_sentences(app, preferences, voice_id, &split_sentences(&summary), &metadata).await
+}
+
+/// Queue the speakable part of an agent message, chunked by sentence
+async fn narrate(app: &AppHandle, agent_id: i64, project_path: &str, text: &str) -> Result<(), AudioError> {
+    let db = app.state::<Arc<ElevenLabsState>>().db()?;
+    let config = AgentNarrationConfig::load(&db).await?;
+    let preferences = AgentTtsPreferences::load(&db, agent_id).await?;
+    let project = ProjectNarrationConfig::load(&db, project_path).await?;
+    let Some(mode) = preferences.mode(&config, project.as_ref()) else {
+        return Ok(());
+    };
+
+    let speakable = speakable_text(text);
+    let voice_id = config.voice_for(&db, agent_id, project_path).await?;
+
+    let too_long = config.summarize_over_chars.is_some_and(|limit| speakable.len() > limit);
+    if mode == NarrationMode::Full && too_long {
+        let app = app.clone();
+        let full_text = text.to_string();
+        tauri::async_runtime::spawn(async move {
+            let result = narrate_summary(&app, &config, &preferences, &voice_id, &full_text, &speakable).await;
+            if let Err(e) = result {
+                log::warn!("Failed to narrate summary of agent {} output: {}", agent_id, e);
+            }
+        });
+        return Ok(());
+    }
+
+    let mut sentences = split_sentences(&speakable);
+    if mode == NarrationMode::Summary {
+        sentences.truncate(config.summary_sentences.max(1));
+    }
+    queue_sentences(app, &preferences, &voice_id, &sentences, &serde_json::Value::Null).await
+}
+
+/// Agent output hook: narrate completed assistant messages
+pub async fn on_agent_output(app: &AppHandle, agent_id: i64, project_path: &str, message: &serde_json::Value) {
+    if let Some(text) = assistant_text(message) {
+        if let Err(e) = narrate(app, agent_id, project_path, &text).await {
+            log::warn!("Failed to narrate agent {} output: {}", agent_id, e);
+        }
+    }
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the agent narration configuration
+#[tauri::command]
+pub async fn get_agent_narration_config(state: State<'_, Arc<ElevenLabsState>>) -> Result<AgentNarrationConfig, AudioError> {
+    AgentNarrationConfig::load(&state.db()?).await
+}
+
+/// Save the agent narration configuration
+#[tauri::command]
+pub async fn set_agent_narration_config(
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: AgentNarrationConfig,
+) -> Result<AgentNarrationConfig, AudioError> {
+    let json = serde_json::to_string(&config)?;
+    state.call_db(move |conn| SettingsDb::save_setting(conn, AGENT_NARRATION_KEY, &json)).await?;
+    Ok(config)
+}
+
+/// Get a project's narration settings; `None` when the project follows the global configuration
+#[tauri::command]
+pub async fn get_project_narration_config(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_path: String,
+) -> Result<Option<ProjectNarrationConfig>, AudioError> {
+    ProjectNarrationConfig::load(&state.db()?, &project_path).await
+}
+
+/// Save a project's narration settings, or clear them with `None`
+#[tauri::command]
+pub async fn set_project_narration_config(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_path: String,
+    config: Option<ProjectNarrationConfig>,
+) -> Result<Option<ProjectNarrationConfig>, AudioError> {
+    let key = ProjectNarrationConfig::key(&project_path);
+    state
+        .call_db(move |conn| {
+            match &config {
+                Some(config) => SettingsDb::save_setting(conn, &key, &serde_json::to_string(config)?)?,
+                None => SettingsDb::remove_setting(conn, &key)?,
+            }
+            Ok(config)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_chunking() {
+        let text = speakable_text(
+            "# Done\n\nI updated **main.rs**. Tests pass!\n\n```rust\nfn main() {}\n```\nVersion 1.2 is out?",
+        );
+        let sentences = split_sentences(&text);
+        assert_eq!(
+            sentences,
+            vec!["Done", "I updated main.rs.", "Tests pass!", "Version 1.2 is out?"]
+        );
+        assert_eq!(
+            chunk_sentences(&sentences[1..], 30),
+            vec!["I updated main.rs. Tests pass!", "Version 1.2 is out?"]
+        );
+    }
+
+    #[test]
+    fn test_narration_mode_precedence() {
+        let global = AgentNarrationConfig { enabled: true, ..Default::default() };
+        let quiet = ProjectNarrationConfig { enabled: false, mode: NarrationMode::Full };
+        let brief = ProjectNarrationConfig { enabled: true, mode: NarrationMode::Summary };
+        let unset = AgentTtsPreferences::default();
+        let full = AgentTtsPreferences { narration: Some("full".into()), ..Default::default() };
+
+        assert_eq!(unset.mode(&global, None), Some(NarrationMode::Full));
+        assert_eq!(unset.mode(&global, Some(&brief)), Some(NarrationMode::Summary));
+        assert_eq!(full.mode(&global, Some(&brief)), Some(NarrationMode::Full));
+        assert_eq!(full.mode(&global, Some(&quiet)), None);
+        assert_eq!(unset.mode(&AgentNarrationConfig::default(), Some(&brief)), Some(NarrationMode::Summary));
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use super::deep_link;
+use super::ElevenLabsState;
+
+/// How long after a notification focusing the window still jumps to its item
+const FOCUS_TARGET_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Outcome of a finished render or batch
+#[derive(Debug, Clone, Default)]
+pub struct CompletionSummary {
+    pub completed: usize,
+    pub errors: usize,
+    pub duration_seconds: f32,
+    /// Audio to open when the user comes back to the app
+    pub audio_id: Option<String>,
+}
+
+impl CompletionSummary {
+    fn title(&self) -> String {
+        match (self.completed, self.errors) {
+            (0, _) => "Audio generation failed".to_string(),
+            (1, 0) => "Audio ready".to_string(),
+            (n, 0) => format!("{} audio clips ready", n),
+            (n, _) => format!("{} audio clips finished", n),
+        }
+    }
+
+    fn body(&self) -> String {
+        let mut body = format!("{:.1}s of audio", self.duration_seconds);
+        if self.errors > 0 {
+            body.push_str(&format!(
+                ", {} error{}",
+                self.errors,
+                if self.errors == 1 { "" } else { "s" }
+            ));
+        }
+        body
+    }
+}
+
+fn main_window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false)
+}
+
+/// Send a native notification for a finished render unless the user is already
+/// looking at the app. Focusing the window afterwards opens the finished item.
+pub fn notify_completion(app: &AppHandle, summary: &CompletionSummary) {
+    if main_window_focused(app) {
+        return;
+    }
+
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(summary.title())
+        .body(summary.body())
+        .show()
+    {
+        log::warn!("Failed to show notification: {}", e);
+        return;
+    }
+
+    // Desktop notifications can't carry a click payload back to the app, so the
+    // next focus of the main window stands in for the click
+    let state = app.state::<Arc<ElevenLabsState>>();
+    if let Ok(mut target) = state.notification_target.lock() {
+        *target = summary.audio_id.clone().map(|id| (id, Instant::now()));
+    };
+}
+
+/// Window focus hook: deep link to the item from the last notification, if any
+pub fn on_window_focused(app: &AppHandle) {
+    let state = app.state::<Arc<ElevenLabsState>>();
+    let target = state.notification_target.lock().ok().and_then(|mut t| t.take());
+
+    if let Some((audio_id, sent_at)) = target {
+        if sent_at.elapsed() < FOCUS_TARGET_TTL {
+            deep_link::handle_url(app, format!("{}://open-audio/{}", deep_link::SCHEME, audio_id));
+        }
+    }
+}
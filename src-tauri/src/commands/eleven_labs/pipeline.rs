@@ -0,0 +1,1062 @@
+// Generation pipeline shared by the Tauri commands, the MCP server and other
+// non-UI entrypoints, so every caller goes through the same provider calls,
+// cache layout and database records.
+
+use bytes::Bytes;
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::api::ElevenLabsApi;
+use super::auth;
+use super::billing::billable_characters;
+use super::chunking;
+use super::cache::{
+    estimate_duration_seconds, AgentVoiceDb, AudioCacheDb, AudioDb, CharacterVoiceDb, SettingsDb, VoiceProfileDb,
+    VoiceUsageDb,
+};
+use super::client::{AudioDownload, AudioStream};
+use super::compare;
+use super::error::AudioError;
+use super::live_output;
+use super::presets;
+use super::processing::{self, HookStage};
+use super::quota;
+use super::text_normalize;
+use super::usage_history;
+use super::types::*;
+use super::webhooks;
+use super::{ensure_cache, get_client, ElevenLabsState};
+
+/// Render text to speech, save it to the audio cache and record it in the database
+pub async fn generate_tts(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+) -> Result<GeneratedAudio, AudioError> {
+    generate_tts_for_project(state, request, None).await
+}
+
+/// `generate_tts`, running the project's after-generation hooks on the file.
+/// An identical request already in flight is joined rather than repeated.
+pub async fn generate_tts_for_project(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    generate_tts_or_reuse(state, request, project_id, true).await
+}
+
+/// `generate_tts_for_project`, but an identical request made before returns
+/// the newest record rendered from it (marked `cached`) while its file
+/// exists, unless `force_regenerate`. Either way the new record can be
+/// reused by later requests.
+#[tracing::instrument(skip(state, request), err(Display))]
+pub async fn generate_tts_or_reuse(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+    project_id: Option<&str>,
+    force_regenerate: bool,
+) -> Result<GeneratedAudio, AudioError> {
+    let db = state.db()?;
+    let request = prepare_tts(&db, request, project_id).await?;
+    let key = generation_key("tts", &request, project_id)?;
+    let hash = format!("{:x}", Sha256::digest(key.as_bytes()));
+
+    if !force_regenerate {
+        let lookup = hash.clone();
+        let earlier = db.call(move |conn| AudioCacheDb::find_by_request_hash(conn, &lookup)).await?;
+        for audio in earlier {
+            if tokio::fs::try_exists(&audio.local_path).await.unwrap_or(false) {
+                return Ok(GeneratedAudio { cached: true, ..audio });
+            }
+        }
+    }
+
+    let audio = state
+        .generations
+        .run(key, || render_tts(state, request, project_id, None, TtsTransport::Http, None, serde_json::Map::new()))
+        .await?;
+    let id = audio.id.clone();
+    db.call(move |conn| AudioCacheDb::set_request_hash(conn, &id, &hash)).await?;
+    Ok(audio)
+}
+
+/// `generate_tts_for_project`, also storing when each character and word is
+/// spoken under `ALIGNMENT_KEY` in the record's metadata
+#[tracing::instrument(skip(state, request), err(Display))]
+pub async fn generate_tts_with_timestamps_for_project(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    let db = state.db()?;
+    let request = prepare_tts(&db, request, project_id).await?;
+    let key = generation_key("tts_timestamps", &request, project_id)?;
+    state
+        .generations
+        .run(key, || {
+            render_tts(state, request, project_id, None, TtsTransport::WithTimestamps, None, serde_json::Map::new())
+        })
+        .await
+}
+
+/// Render consecutive parts of one text, each conditioned on the text around
+/// it and on the provider requests of the parts before it, so the prosody
+/// carries across the joins. `request` supplies the voice and settings; its
+/// own text is ignored. Parts are rendered in order, one at a time.
+pub async fn generate_tts_parts_for_project(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+    parts: Vec<String>,
+    project_id: Option<&str>,
+) -> Result<Vec<GeneratedAudio>, AudioError> {
+    if parts.iter().any(|part| part.trim().is_empty()) {
+        return Err(AudioError::Validation("Parts cannot be empty".to_string()));
+    }
+
+    let mut audios: Vec<GeneratedAudio> = Vec::with_capacity(parts.len());
+    for index in 0..parts.len() {
+        let request_ids: Vec<String> = audios
+            .iter()
+            .filter_map(|audio| audio.metadata.get(REQUEST_ID_KEY)?.as_str().map(str::to_string))
+            .collect();
+        let part = stitched_part(&request, &parts, index, &request_ids);
+        audios.push(generate_tts_for_project(state, part, project_id).await?);
+    }
+    Ok(audios)
+}
+
+/// `request` for part `index` of `parts`, conditioned on the parts around it
+/// and the latest of `request_ids`, the provider requests of the parts before it
+fn stitched_part(request: &TtsRequest, parts: &[String], index: usize, request_ids: &[String]) -> TtsRequest {
+    let skip = request_ids.len().saturating_sub(MAX_PREVIOUS_REQUEST_IDS);
+    TtsRequest {
+        text: parts[index].clone(),
+        previous_text: index.checked_sub(1).map(|previous| parts[previous].clone()),
+        next_text: parts.get(index + 1).cloned(),
+        previous_request_ids: request_ids[skip..].to_vec(),
+        ..request.clone()
+    }
+}
+
+/// Speech for text too long for one request, split into `parts`: each part is
+/// requested, stitched onto those before it, once the previous one has
+/// downloaded, and its audio follows theirs in one stream. `chunks` records
+/// each part as it arrives.
+async fn download_in_parts(
+    client: Arc<dyn ElevenLabsApi>,
+    request: TtsRequest,
+    parts: Vec<String>,
+    chunks: Arc<Mutex<Vec<SpeechChunk>>>,
+) -> anyhow::Result<AudioDownload> {
+    struct Parts {
+        client: Arc<dyn ElevenLabsApi>,
+        request: TtsRequest,
+        parts: Vec<String>,
+        chunks: Arc<Mutex<Vec<SpeechChunk>>>,
+        current: AudioStream,
+        offset: u64,
+    }
+
+    impl Parts {
+        /// Start downloading the next part, if there is one
+        async fn next_part(&mut self) -> Option<anyhow::Result<()>> {
+            let (index, request_ids) = {
+                let chunks = self.chunks.lock().ok()?;
+                (chunks.len(), chunks.iter().filter_map(|chunk| chunk.request_id.clone()).collect::<Vec<_>>())
+            };
+            let text = self.parts.get(index)?.clone();
+            let part = stitched_part(&self.request, &self.parts, index, &request_ids);
+            Some(self.client.text_to_speech_download(part).await.map(|download| {
+                let chunk = SpeechChunk { text, request_id: download.request_id, offset: self.offset, bytes: 0 };
+                if let Ok(mut chunks) = self.chunks.lock() {
+                    chunks.push(chunk);
+                }
+                self.current = download.stream;
+            }))
+        }
+    }
+
+    let mut parts = Parts {
+        client,
+        request,
+        parts,
+        chunks,
+        current: futures::stream::empty().boxed(),
+        offset: 0,
+    };
+    // The first request is made up front so its failure fails the generation
+    parts.next_part().await.unwrap_or_else(|| Err(anyhow::anyhow!("No text to speak")))?;
+
+    let stream = futures::stream::unfold(Some(parts), |parts| async move {
+        let mut parts = parts?;
+        loop {
+            match parts.current.next().await {
+                Some(Ok(data)) => {
+                    parts.offset += data.len() as u64;
+                    if let Some(chunk) = parts.chunks.lock().ok().as_mut().and_then(|chunks| chunks.last_mut()) {
+                        chunk.bytes += data.len() as u64;
+                    }
+                    return Some((Ok(data), Some(parts)));
+                }
+                Some(Err(e)) => return Some((Err(e), None)),
+                None => match parts.next_part().await? {
+                    Ok(()) => continue,
+                    Err(e) => return Some((Err(e), None)),
+                },
+            }
+        }
+    });
+    Ok(AudioDownload { stream: stream.boxed(), expected_bytes: None, request_id: None })
+}
+
+/// Called with each chunk of audio as it arrives from the provider
+pub type ChunkTap = Box<dyn FnMut(&Bytes) + Send>;
+
+/// How speech is requested from the provider
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TtsTransport {
+    /// A single HTTP request with a streamed response
+    #[default]
+    Http,
+    /// The input-streaming WebSocket, which starts sending audio sooner
+    WebSocket,
+    /// A single HTTP request answered with the whole audio and its timings
+    WithTimestamps,
+}
+
+/// `generate_tts_for_project`, also passing the audio to `tap` while it is
+/// written to the cache. Not coalesced, since every caller needs the chunks.
+pub async fn generate_tts_streamed(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+    project_id: Option<&str>,
+    tap: ChunkTap,
+    transport: TtsTransport,
+) -> Result<GeneratedAudio, AudioError> {
+    let db = state.db()?;
+    let request = prepare_tts(&db, request, project_id).await?;
+    render_tts(state, request, project_id, Some(tap), transport, None, serde_json::Map::new()).await
+}
+
+/// Called with the bytes of audio received so far and the total expected,
+/// when the provider announced it
+pub type ProgressTap = Box<dyn FnMut(u64, Option<u64>) + Send>;
+
+/// `generate_tts_for_project`, reporting the download's progress to
+/// `progress` as the audio is written to the cache. Not coalesced, since every
+/// caller needs its own progress.
+pub async fn generate_tts_with_progress(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+    project_id: Option<&str>,
+    progress: ProgressTap,
+) -> Result<GeneratedAudio, AudioError> {
+    let db = state.db()?;
+    let request = prepare_tts(&db, request, project_id).await?;
+    render_tts(state, request, project_id, None, TtsTransport::Http, Some(progress), serde_json::Map::new()).await
+}
+
+/// `generate_tts_for_project`, storing `metadata` (where a line sits in a
+/// script, say) on the record from the start, so it is saved once and
+/// announced with it. Not coalesced, since the metadata is the caller's.
+pub async fn generate_tts_with_metadata(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+    project_id: Option<&str>,
+    metadata: serde_json::Map<String, serde_json::Value>,
+) -> Result<GeneratedAudio, AudioError> {
+    let db = state.db()?;
+    let request = prepare_tts(&db, request, project_id).await?;
+    let hash = format!("{:x}", Sha256::digest(generation_key("tts", &request, project_id)?.as_bytes()));
+    let audio = render_tts(state, request, project_id, None, TtsTransport::Http, None, metadata).await?;
+    let id = audio.id.clone();
+    db.call(move |conn| AudioCacheDb::set_request_hash(conn, &id, &hash)).await?;
+    Ok(audio)
+}
+
+/// The request with its preset applied and its text normalized for the project
+async fn prepare_tts(db: &AudioDb, request: TtsRequest, project_id: Option<&str>) -> Result<TtsRequest, AudioError> {
+    let request = db.call(move |conn| presets::apply(conn, request)).await?;
+    text_normalize::prepare(db, project_id, request).await
+}
+
+/// Identifies a generation for coalescing: the same kind, request and project
+fn generation_key(kind: &str, request: &impl serde::Serialize, project_id: Option<&str>) -> Result<String, AudioError> {
+    Ok(format!("{}\0{}\0{}", kind, project_id.unwrap_or_default(), serde_json::to_string(request)?))
+}
+
+async fn render_tts(
+    state: &ElevenLabsState,
+    request: TtsRequest,
+    project_id: Option<&str>,
+    tap: Option<ChunkTap>,
+    transport: TtsTransport,
+    mut progress: Option<ProgressTap>,
+    metadata: serde_json::Map<String, serde_json::Value>,
+) -> Result<GeneratedAudio, AudioError> {
+    let client = get_client(state).await?;
+
+    // Text over the model's limit is sent in parts, over HTTP only
+    let parts = match quota::model_char_limit(&request.model_id) {
+        Some(limit) if transport == TtsTransport::Http && billable_characters(&request.text) > limit => {
+            chunking::split_text(&request.text, limit)
+        }
+        _ => vec![request.text.clone()],
+    };
+    state.quota.check_tts_chunks(client.as_ref(), &request, &parts).await?;
+
+    let text = request.text.clone();
+    let voice_id = request.voice_id.clone();
+    let params = GenerationParams::from_tts(&request);
+    let seed = request.seed;
+    let mut alignment = None;
+    let chunks = Arc::new(Mutex::new(Vec::new()));
+    let download = match transport {
+        TtsTransport::Http if parts.len() > 1 => download_in_parts(client, request, parts, chunks.clone()).await,
+        TtsTransport::Http => client.text_to_speech_download(request).await,
+        TtsTransport::WebSocket => client
+            .text_to_speech_websocket(request)
+            .await
+            .map(|stream| AudioDownload { stream, expected_bytes: None, request_id: None }),
+        TtsTransport::WithTimestamps => client.text_to_speech_with_timestamps(request).await.map(|speech| {
+            alignment = speech.alignment.map(SpeechAlignment::from);
+            AudioDownload {
+                expected_bytes: Some(speech.audio.len() as u64),
+                request_id: speech.request_id,
+                stream: futures::stream::once(async move { Ok(speech.audio) }).boxed(),
+            }
+        }),
+    };
+    let AudioDownload { mut stream, expected_bytes, request_id } = auth::check(state, download).await?;
+    state.quota.consume(billable_characters(&text));
+    if let Some(mut tap) = tap {
+        stream = stream
+            .inspect(move |chunk| {
+                if let Ok(chunk) = chunk {
+                    tap(chunk);
+                }
+            })
+            .boxed();
+    }
+
+    // Save to cache as the audio arrives
+    let cache = ensure_cache(state).await?;
+    let (path, received) = cache
+        .save_audio_stream(&AudioType::Tts, stream, "mp3", |received| {
+            if let Some(progress) = progress.as_mut() {
+                progress(received, expected_bytes);
+            }
+        })
+        .await?;
+    let db = state.db()?;
+    processing::run_hooks(&db, HookStage::AfterGeneration, project_id, &path).await?;
+
+    let size = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(received);
+    let format = params.output_format.as_deref().unwrap_or("mp3");
+    let duration_seconds = estimate_duration_seconds(size, format).unwrap_or(0.0);
+
+    let mut metadata = serde_json::Value::Object(metadata);
+    if let Some(alignment) = alignment {
+        metadata[ALIGNMENT_KEY] = serde_json::to_value(alignment)?;
+    }
+    let chunks = std::mem::take(&mut *chunks.lock()?);
+    // Later generations are stitched onto the last part
+    if let Some(request_id) = request_id.or_else(|| chunks.last().and_then(|chunk| chunk.request_id.clone())) {
+        metadata[REQUEST_ID_KEY] = serde_json::json!(request_id);
+    }
+    if !chunks.is_empty() {
+        metadata[CHUNKS_KEY] = serde_json::to_value(&chunks)?;
+    }
+    if let Some(seed) = seed {
+        metadata[SEED_KEY] = serde_json::json!(seed);
+    }
+    let audio = GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type: AudioType::Tts,
+        prompt: text,
+        duration_seconds,
+        local_path: path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        params: Some(params),
+        cached: false,
+    };
+
+    finish_render(state, &audio, Some(&voice_id)).await?;
+    Ok(audio)
+}
+
+/// Convert a recording into another voice, save the result to the audio cache
+/// and record it in the database, running the project's after-generation
+/// hooks on the file. An identical request already in flight is joined.
+#[tracing::instrument(skip(state, request), err(Display))]
+pub async fn generate_voice_change_for_project(
+    state: &ElevenLabsState,
+    request: VoiceChangeRequest,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    if !tokio::fs::metadata(&request.source_path).await.is_ok_and(|m| m.is_file()) {
+        return Err(AudioError::Validation(format!("No recording at {}", request.source_path)));
+    }
+    let key = generation_key("sts", &request, project_id)?;
+    state
+        .generations
+        .run(key, || render_voice_change(state, request, project_id))
+        .await
+}
+
+async fn render_voice_change(
+    state: &ElevenLabsState,
+    request: VoiceChangeRequest,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    let client = get_client(state).await?;
+
+    let source_path = request.source_path.clone();
+    let voice_id = request.voice_id.clone();
+    let params = GenerationParams {
+        model_id: Some(request.model_id.clone()),
+        voice_id: Some(request.voice_id.clone()),
+        voice_settings: request.voice_settings.clone(),
+        output_format: Some(request.output_format.clone()),
+        ..GenerationParams::new(PROVIDER_ELEVENLABS)
+    };
+    let stream = auth::check(state, client.speech_to_speech(request).await).await?;
+
+    // Save to cache as the audio arrives
+    let cache = ensure_cache(state).await?;
+    let (path, received) = cache.save_audio_stream(&AudioType::Tts, stream, "mp3", |_| {})
+        .await?;
+    let db = state.db()?;
+    processing::run_hooks(&db, HookStage::AfterGeneration, project_id, &path).await?;
+
+    let size = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(received);
+    let format = params.output_format.as_deref().unwrap_or("mp3");
+    let duration_seconds = estimate_duration_seconds(size, format).unwrap_or(0.0);
+
+    // There is no text; the prompt names the recording, which is kept in metadata
+    let audio = GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type: AudioType::Tts,
+        prompt: recording_name(&source_path),
+        duration_seconds,
+        local_path: path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata: serde_json::json!({ VOICE_CHANGED_FROM_KEY: source_path }),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        params: Some(params),
+        cached: false,
+    };
+
+    finish_render(state, &audio, Some(&voice_id)).await?;
+    Ok(audio)
+}
+
+/// Clean the background noise out of a recording (e.g. a voice sample before
+/// cloning), save the result to the audio cache and record it in the database.
+/// An identical request already in flight is joined.
+#[tracing::instrument(skip(state), err(Display))]
+pub async fn isolate_audio_for_project(
+    state: &ElevenLabsState,
+    source_path: &str,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    if !tokio::fs::metadata(source_path).await.is_ok_and(|m| m.is_file()) {
+        return Err(AudioError::Validation(format!("No recording at {}", source_path)));
+    }
+    let key = generation_key("isolation", &source_path, project_id)?;
+    state
+        .generations
+        .run(key, || render_isolation(state, source_path, project_id))
+        .await
+}
+
+async fn render_isolation(
+    state: &ElevenLabsState,
+    source_path: &str,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    let client = get_client(state).await?;
+    let stream = auth::check(state, client.isolate_audio(Path::new(source_path)).await).await?;
+
+    // Save to cache as the audio arrives
+    let cache = ensure_cache(state).await?;
+    let (path, received) = cache.save_audio_stream(&AudioType::Tts, stream, "mp3", |_| {})
+        .await?;
+    let db = state.db()?;
+    processing::run_hooks(&db, HookStage::AfterGeneration, project_id, &path).await?;
+
+    let size = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(received);
+    let duration_seconds = estimate_duration_seconds(size, "mp3").unwrap_or(0.0);
+
+    let audio = GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type: AudioType::Tts,
+        prompt: recording_name(source_path),
+        duration_seconds,
+        local_path: path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata: serde_json::json!({ ISOLATED_FROM_KEY: source_path }),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        params: Some(GenerationParams::new(PROVIDER_ELEVENLABS)),
+        cached: false,
+    };
+
+    finish_render(state, &audio, None).await?;
+    Ok(audio)
+}
+
+/// Generate a sound effect, save it to the audio cache and record it in the database
+pub async fn generate_sfx(
+    state: &ElevenLabsState,
+    request: SfxRequest,
+) -> Result<GeneratedAudio, AudioError> {
+    generate_sfx_for_project(state, request, None).await
+}
+
+/// `generate_sfx`, running the project's after-generation hooks on the file.
+/// An identical request already in flight is joined rather than repeated.
+#[tracing::instrument(skip(state, request), err(Display))]
+pub async fn generate_sfx_for_project(
+    state: &ElevenLabsState,
+    request: SfxRequest,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    let key = generation_key("sfx", &request, project_id)?;
+    state
+        .generations
+        .run(key, || render_sfx(state, request, project_id))
+        .await
+}
+
+async fn render_sfx(
+    state: &ElevenLabsState,
+    request: SfxRequest,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    let client = get_client(state).await?;
+
+    let text = request.text.clone();
+    let duration = request.duration_seconds;
+    let params = GenerationParams::from_sfx(&request);
+    let stream = auth::check(state, client.generate_sound_effects(request).await).await?;
+
+    // Save to cache as the audio arrives
+    let cache = ensure_cache(state).await?;
+    let (path, _) = cache.save_audio_stream(&AudioType::Sfx, stream, "mp3", |_| {})
+        .await?;
+    let db = state.db()?;
+    processing::run_hooks(&db, HookStage::AfterGeneration, project_id, &path).await?;
+
+    let audio = GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type: AudioType::Sfx,
+        prompt: text,
+        duration_seconds: duration,
+        local_path: path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata: serde_json::json!({}),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        params: Some(params),
+        cached: false,
+    };
+
+    finish_render(state, &audio, None).await?;
+    Ok(audio)
+}
+
+/// Compose music, save it to the audio cache and record it in the database,
+/// running the project's after-generation hooks on the file. An identical
+/// request already in flight is joined rather than repeated.
+#[tracing::instrument(skip(state, request), err(Display))]
+pub async fn generate_music_for_project(
+    state: &ElevenLabsState,
+    request: MusicRequest,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    if request.prompt.trim().is_empty() {
+        return Err(AudioError::Validation("Music prompt cannot be empty".to_string()));
+    }
+    if !MUSIC_DURATION_RANGE.contains(&request.duration_seconds) {
+        return Err(AudioError::Validation(format!(
+            "Music must be between {} and {} seconds long",
+            MUSIC_DURATION_RANGE.start(),
+            MUSIC_DURATION_RANGE.end()
+        )));
+    }
+    let key = generation_key("music", &request, project_id)?;
+    state
+        .generations
+        .run(key, || render_music(state, request, project_id))
+        .await
+}
+
+async fn render_music(
+    state: &ElevenLabsState,
+    request: MusicRequest,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    let client = get_client(state).await?;
+
+    let prompt = request.prompt.clone();
+    let duration = request.duration_seconds;
+    let params = GenerationParams::from_music(&request);
+    let metadata = serde_json::json!({ "style": request.style, "mood": request.mood });
+    let stream = auth::check(state, client.generate_music(request).await).await?;
+
+    // Save to cache as the audio arrives
+    let cache = ensure_cache(state).await?;
+    let (path, _) = cache.save_audio_stream(&AudioType::Music, stream, "mp3", |_| {})
+        .await?;
+    let db = state.db()?;
+    processing::run_hooks(&db, HookStage::AfterGeneration, project_id, &path).await?;
+
+    let audio = GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type: AudioType::Music,
+        prompt,
+        duration_seconds: duration,
+        local_path: path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        params: Some(params),
+        cached: false,
+    };
+
+    finish_render(state, &audio, None).await?;
+    Ok(audio)
+}
+
+/// Record a render saved to the cache and announce it: the record, the use of
+/// `voice_id`, the usage history, live output and webhooks. Only failing to
+/// save the record fails the render.
+async fn finish_render(
+    state: &ElevenLabsState,
+    audio: &GeneratedAudio,
+    voice_id: Option<&str>,
+) -> Result<(), AudioError> {
+    let db = state.db()?;
+    let (record, voice_id) = (audio.clone(), voice_id.map(str::to_string));
+    db.call(move |conn| {
+        AudioCacheDb::save_audio_record(conn, &record)?;
+        if let Some(voice_id) = voice_id {
+            if let Err(e) = VoiceUsageDb::record_use(conn, &voice_id) {
+                log::warn!("Failed to record use of voice {}: {}", voice_id, e);
+            }
+        }
+        if let Err(e) = usage_history::record(conn, &record) {
+            log::warn!("Failed to record usage of {}: {}", record.id, e);
+        }
+        Ok(())
+    })
+    .await?;
+
+    live_output::publish_if_enabled(&db, audio).await;
+    webhooks::dispatch(state, webhooks::EVENT_JOB_COMPLETED, audio);
+    Ok(())
+}
+
+/// Prompt of audio made from a recording: the recording's file name
+fn recording_name(source_path: &str) -> String {
+    Path::new(source_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Settings key holding the ETag of the last voices response
+const VOICES_ETAG_KEY: &str = "voices_etag";
+
+/// Emitted with a `VoiceList` when a background refresh changed the cached voices
+pub const VOICES_UPDATED_EVENT: &str = "audio-voices-updated";
+
+/// Fetch voices from the provider and refresh the local `voice_profiles` cache
+#[tracing::instrument(skip_all, err(Display))]
+pub async fn refresh_voices(state: &ElevenLabsState) -> Result<VoiceList, AudioError> {
+    let client = get_client(state).await?;
+    let (voices, etag) = auth::check(state, client.list_voices_if_changed(None).await)
+        .await?
+        .ok_or("Unexpected 304 response for voices")?;
+    cache_voices(state, voices, etag).await
+}
+
+/// Like `refresh_voices`, but sends the stored ETag and returns `None` when
+/// the provider reports the voices unchanged
+async fn refresh_voices_if_changed(state: &ElevenLabsState) -> Result<Option<VoiceList>, AudioError> {
+    let client = get_client(state).await?;
+    let etag = state.call_db(|conn| SettingsDb::get_setting(conn, VOICES_ETAG_KEY)).await?;
+    match auth::check(state, client.list_voices_if_changed(etag.as_deref()).await).await? {
+        Some((voices, etag)) => Ok(Some(cache_voices(state, voices, etag).await?)),
+        None => Ok(None),
+    }
+}
+
+async fn cache_voices(
+    state: &ElevenLabsState,
+    voices: Vec<VoiceProfile>,
+    etag: Option<String>,
+) -> Result<VoiceList, AudioError> {
+    state
+        .call_db(move |conn| {
+            let cache = VoiceProfileDb::save_voice_profiles(conn, &voices)?;
+            match &etag {
+                Some(etag) => SettingsDb::save_setting(conn, VOICES_ETAG_KEY, etag)?,
+                None => SettingsDb::remove_setting(conn, VOICES_ETAG_KEY)?,
+            }
+            Ok(VoiceList { voices, cache: Some(cache) })
+        })
+        .await
+}
+
+/// Cached voices right away. When there are any, they are refreshed from the
+/// provider in the background and `VOICES_UPDATED_EVENT` is emitted if they
+/// changed; with an empty cache the provider is asked directly.
+pub async fn list_voices_local_first(app: &AppHandle, state: &ElevenLabsState) -> Result<VoiceList, AudioError> {
+    let voices = state.call_db(|conn| VoiceProfileDb::get_voice_profiles(conn)).await?;
+    if voices.is_empty() {
+        return refresh_voices(state).await;
+    }
+
+    // At most one refresh at a time; later calls are served by the running one
+    if !state.voice_refresh.swap(true, Ordering::SeqCst) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<Arc<ElevenLabsState>>();
+            match refresh_voices_if_changed(&state).await {
+                Ok(Some(list)) => {
+                    let _ = app.emit(VOICES_UPDATED_EVENT, &list);
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to refresh voices: {}", e),
+            }
+            state.voice_refresh.store(false, Ordering::SeqCst);
+        });
+    }
+
+    Ok(VoiceList { voices, cache: None })
+}
+
+/// Fetch voices from the provider, refreshing the local cache
+pub async fn list_voices(state: &ElevenLabsState) -> Result<Vec<VoiceProfile>, AudioError> {
+    Ok(refresh_voices(state).await?.voices)
+}
+
+/// Delete a cached audio file and its database record
+pub async fn delete_audio(state: &ElevenLabsState, audio_id: &str) -> Result<(), AudioError> {
+    // Get the record to find the file path
+    let id = audio_id.to_string();
+    if let Some(audio) = state.call_db(move |conn| AudioCacheDb::get_audio_record(conn, &id)).await? {
+        // Delete the file
+        let cache = ensure_cache(state).await?;
+        let path = PathBuf::from(&audio.local_path);
+        cache.delete_audio(&path).await?;
+    }
+
+    // Delete from database
+    let id = audio_id.to_string();
+    state
+        .call_db(move |conn| {
+            compare::forget_audio(conn, &id)?;
+            AudioCacheDb::delete_audio_record(conn, &id)
+        })
+        .await
+}
+
+/// Move the live records matching `query`, of `audio_type` or of every type,
+/// to the trash, or with `permanent` delete them and their files. The type
+/// alone is not a filter. The rows go in one transaction; files are removed
+/// once it has committed, so a failure part way leaves orphan files rather
+/// than records without audio.
+pub async fn delete_audio_batch(
+    state: &ElevenLabsState,
+    audio_type: Option<AudioType>,
+    query: AudioQuery,
+    permanent: bool,
+) -> Result<BatchDeleteResult, AudioError> {
+    let unfiltered = query.voice_id.is_none()
+        && query.created_after.is_none()
+        && query.created_before.is_none()
+        && query.tag.is_none()
+        && !query.favorites_only;
+    if unfiltered {
+        return Err(AudioError::Validation(
+            "A batch delete needs a voice, date, tag or favorites filter".to_string(),
+        ));
+    }
+
+    let deleted = state
+        .call_db(move |conn| {
+            let tx = conn.transaction()?;
+            let types = match audio_type {
+                Some(audio_type) => vec![audio_type],
+                None => vec![AudioType::Tts, AudioType::Sfx, AudioType::Music],
+            };
+            let mut deleted = Vec::new();
+            for audio_type in &types {
+                deleted.extend(AudioCacheDb::query_audio_records(&tx, audio_type, &query)?);
+            }
+            for audio in &deleted {
+                if permanent {
+                    compare::forget_audio(&tx, &audio.id)?;
+                    AudioCacheDb::delete_audio_record(&tx, &audio.id)?;
+                } else {
+                    AudioCacheDb::trash_audio_record(&tx, &audio.id)?;
+                }
+            }
+            tx.commit()?;
+            Ok(deleted)
+        })
+        .await?;
+
+    let mut bytes_freed = 0;
+    if permanent {
+        let cache = ensure_cache(state).await?;
+        for audio in &deleted {
+            let path = Path::new(&audio.local_path);
+            let size = tokio::fs::metadata(path).await.map(|m| m.len()).unwrap_or(0);
+            match cache.delete_audio(path).await {
+                Ok(()) if !tokio::fs::try_exists(path).await.unwrap_or(true) => bytes_freed += size,
+                Ok(()) => {}
+                Err(e) => log::warn!("Failed to delete {}: {}", audio.local_path, e),
+            }
+        }
+    }
+    Ok(BatchDeleteResult {
+        deleted: deleted.len(),
+        bytes_freed,
+    })
+}
+
+/// Render a cached record again from its stored generation parameters with
+/// `overrides` applied. The new record keeps the original's metadata and is
+/// linked to it as another take (`metadata.take_of`).
+#[tracing::instrument(skip(state, overrides), err(Display))]
+pub async fn regenerate_audio(
+    state: &ElevenLabsState,
+    audio_id: &str,
+    overrides: RegenerateOverrides,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    let id = audio_id.to_string();
+    let original = state
+        .call_db(move |conn| AudioCacheDb::get_audio_record(conn, &id))
+        .await?
+        .ok_or_else(|| AudioError::Validation(format!("No audio record: {}", audio_id)))?;
+    if original.metadata.get(VOICE_CHANGED_FROM_KEY).is_some() {
+        return Err(AudioError::Validation("Voice-changed records cannot be regenerated".to_string()));
+    }
+    if original.metadata.get(ISOLATED_FROM_KEY).is_some() {
+        return Err(AudioError::Validation("Isolated recordings cannot be regenerated".to_string()));
+    }
+
+    // Records from before parameters were stored only carry the voice in metadata
+    let stored = original.params.clone().unwrap_or_else(|| GenerationParams {
+        voice_id: original.voice_id().map(str::to_string),
+        ..GenerationParams::new(PROVIDER_ELEVENLABS)
+    });
+    let params = overrides.apply(stored);
+
+    let mut audio = match original.audio_type {
+        AudioType::Tts => {
+            let request = params.to_tts(original.prompt.clone()).ok_or_else(|| {
+                AudioError::Validation(format!("Audio record {} has no voice to regenerate with", audio_id))
+            })?;
+            // Keep the timings current for records made with them
+            if original.metadata.get(ALIGNMENT_KEY).is_some() {
+                generate_tts_with_timestamps_for_project(state, request, project_id).await?
+            } else {
+                generate_tts_for_project(state, request, project_id).await?
+            }
+        }
+        AudioType::Sfx => {
+            let mut request = params.to_sfx(original.prompt.clone());
+            if params.duration_seconds.is_none() {
+                request.duration_seconds = original.duration_seconds;
+            }
+            generate_sfx_for_project(state, request, project_id).await?
+        }
+        AudioType::Music => {
+            return Err(AudioError::Validation("Music records cannot be regenerated".to_string()));
+        }
+    };
+
+    let mut metadata = original.metadata.clone();
+    match metadata.as_object_mut() {
+        Some(fields) => {
+            fields.remove("edited_from");
+            fields.remove(REQUEST_ID_KEY);
+            fields.remove(SEED_KEY);
+            fields.insert(TAKE_OF_KEY.to_string(), original.take_root().into());
+        }
+        None => metadata = serde_json::json!({ TAKE_OF_KEY: original.take_root() }),
+    }
+    for key in [ALIGNMENT_KEY, REQUEST_ID_KEY, SEED_KEY] {
+        if let Some(value) = audio.metadata.get(key) {
+            metadata[key] = value.clone();
+        }
+    }
+    audio.metadata = metadata;
+    let record = audio.clone();
+    state.call_db(move |conn| AudioCacheDb::save_audio_record(conn, &record)).await?;
+
+    Ok(audio)
+}
+
+/// Render a cached record again with the seed it was rendered with, so the
+/// new take reproduces it
+pub async fn regenerate_with_same_seed(
+    state: &ElevenLabsState,
+    audio_id: &str,
+    project_id: Option<&str>,
+) -> Result<GeneratedAudio, AudioError> {
+    let id = audio_id.to_string();
+    let original = state
+        .call_db(move |conn| AudioCacheDb::get_audio_record(conn, &id))
+        .await?
+        .ok_or_else(|| AudioError::Validation(format!("No audio record: {}", audio_id)))?;
+    let seed = original
+        .params
+        .as_ref()
+        .and_then(|params| params.seed)
+        .or_else(|| original.metadata.get(SEED_KEY)?.as_u64().and_then(|seed| u32::try_from(seed).ok()))
+        .ok_or_else(|| AudioError::Validation(format!("Audio record {} was generated without a seed", audio_id)))?;
+
+    let overrides = RegenerateOverrides { seed: Some(seed), ..Default::default() };
+    regenerate_audio(state, audio_id, overrides, project_id).await
+}
+
+/// Resolve a voice given either its ID or its (case-insensitive) cached name.
+/// With an empty voice cache the value is passed through as an ID.
+pub async fn resolve_voice_id(db: &AudioDb, voice: &str) -> Result<String, AudioError> {
+    let profiles = db.call(|conn| VoiceProfileDb::get_voice_profiles(conn)).await?;
+    profiles
+        .iter()
+        .find(|p| p.voice_id == voice)
+        .or_else(|| profiles.iter().find(|p| p.name.eq_ignore_ascii_case(voice)))
+        .map(|p| p.voice_id.clone())
+        .or_else(|| profiles.is_empty().then(|| voice.to_string()))
+        .ok_or_else(|| AudioError::Validation(format!("Unknown voice: {}", voice)))
+}
+
+/// Character whose casting is used for narration and as the last fallback
+pub const NARRATOR: &str = "Narrator";
+
+/// Resolve a voice from the most specific scope that has one: the agent's own
+/// voice, the project's casting, the caller's default, then the global casting.
+/// Characters with no casting of their own fall back to the narrator.
+pub fn resolve_voice_in(conn: &rusqlite::Connection, context: &VoiceContext) -> anyhow::Result<Option<ResolvedVoice>> {
+    let resolved = |voice_id: String, scope: VoiceScope, preset: Option<String>| {
+        Ok(Some(ResolvedVoice { voice_id, scope, preset }))
+    };
+
+    if let Some(agent) = context.agent_id.map(|id| AgentVoiceDb::get_agent_voice(conn, id)).transpose()?.flatten() {
+        return resolved(agent.voice_id, VoiceScope::Agent, None);
+    }
+
+    let character = context.character.as_deref().unwrap_or(NARRATOR);
+    if let Some(project_id) = context.project_id.as_deref() {
+        if let Some(mapping) = CharacterVoiceDb::get_scoped_voice(conn, character, Some(project_id))? {
+            return resolved(mapping.voice_id, VoiceScope::Project, mapping.preset);
+        }
+    }
+    if let Some(voice_id) = &context.default_voice_id {
+        return resolved(voice_id.clone(), VoiceScope::Default, None);
+    }
+    if let Some(mapping) = CharacterVoiceDb::get_scoped_voice(conn, character, None)? {
+        return resolved(mapping.voice_id, VoiceScope::Global, mapping.preset);
+    }
+
+    if character.eq_ignore_ascii_case(NARRATOR) {
+        return Ok(None);
+    }
+    let narrator = VoiceContext {
+        agent_id: None,
+        character: None,
+        ..context.clone()
+    };
+    Ok(resolve_voice_in(conn, &narrator)?.map(|voice| ResolvedVoice {
+        scope: VoiceScope::Narrator,
+        ..voice
+    }))
+}
+
+/// `resolve_voice_in` against the app database, failing when nothing is cast
+pub async fn resolve_voice(db: &AudioDb, context: &VoiceContext) -> Result<ResolvedVoice, AudioError> {
+    let lookup = context.clone();
+    db.call(move |conn| resolve_voice_in(conn, &lookup))
+        .await?
+        .ok_or_else(|| {
+            AudioError::NotConfigured(format!(
+                "No voice assigned for {}",
+                context.character.as_deref().unwrap_or("the narrator")
+            ))
+        })
+}
+
+/// Assign a voice to a character, looking up the voice name from the cache if not given
+pub async fn assign_voice(
+    db: &AudioDb,
+    character_name: String,
+    voice_id: String,
+    voice_name: Option<String>,
+    project_id: Option<String>,
+) -> Result<CharacterVoice, AudioError> {
+    db.call(move |conn| {
+        let voice_name = match voice_name {
+            Some(name) => name,
+            None => VoiceProfileDb::get_voice_profile(conn, &voice_id)?
+                .map(|voice| voice.name)
+                .ok_or_else(|| AudioError::Validation(format!("Unknown voice: {}", voice_id)))?,
+        };
+
+        CharacterVoiceDb::assign_voice(conn, &character_name, &voice_id, &voice_name, project_id.as_deref())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_voice_scopes() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        // agent_voices references the agents table owned by the agents module
+        conn.execute_batch("CREATE TABLE agents (id INTEGER PRIMARY KEY); INSERT INTO agents (id) VALUES (7);")
+            .unwrap();
+        crate::commands::eleven_labs::schema::init(&conn).unwrap();
+        CharacterVoiceDb::assign_voice(&conn, "Narrator", "global-narrator", "A", None).unwrap();
+        CharacterVoiceDb::assign_voice(&conn, "Narrator", "project-narrator", "B", Some("/work/app")).unwrap();
+        CharacterVoiceDb::assign_voice(&conn, "User", "global-user", "C", None).unwrap();
+        AgentVoiceDb::assign_voice(&conn, 7, "agent-voice", "D").unwrap();
+
+        let resolve = |context: VoiceContext| resolve_voice_in(&conn, &context).unwrap().unwrap();
+        let in_project = |agent_id| VoiceContext {
+            agent_id,
+            project_id: Some("/work/app".into()),
+            default_voice_id: Some("configured".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(resolve(in_project(Some(7))).scope, VoiceScope::Agent);
+        assert_eq!(resolve(in_project(Some(8))).voice_id, "project-narrator");
+        assert_eq!(
+            resolve(VoiceContext { project_id: Some("/work/other".into()), ..in_project(None) }).scope,
+            VoiceScope::Default
+        );
+        assert_eq!(resolve(VoiceContext::default()).voice_id, "global-narrator");
+
+        let character = |name: &str| VoiceContext { character: Some(name.into()), ..Default::default() };
+        assert_eq!(resolve(character("user")).voice_id, "global-user");
+        assert_eq!(
+            resolve(character("Alice")),
+            ResolvedVoice { voice_id: "global-narrator".into(), scope: VoiceScope::Narrator, preset: None }
+        );
+    }
+}
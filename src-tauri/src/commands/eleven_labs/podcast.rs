@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::remote::{open_remote, RemoteBackend};
+use super::types::*;
+use super::{ensure_cache, ElevenLabsState};
+
+/// An episode in the feed, referencing an audio record in the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PodcastEpisode {
+    pub audio_id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Feed-level settings for `export_podcast_feed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PodcastConfig {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Public URL the episode files (and feed) are served from
+    pub base_url: Option<String>,
+    /// Upload episodes and the feed through a configured cloud backend (`s3`/`webdav`)
+    #[serde(default)]
+    pub upload_backend: Option<String>,
+    /// Explicit episode list; defaults to every TTS take tagged with the project
+    #[serde(default)]
+    pub episodes: Vec<PodcastEpisode>,
+    /// Local path for the rendered feed
+    #[serde(default)]
+    pub destination: Option<String>,
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+/// An episode resolved to its audio file and public URL
+#[derive(Debug, Clone)]
+pub struct FeedItem {
+    pub guid: String,
+    pub title: String,
+    pub description: String,
+    pub url: String,
+    pub length: u64,
+    pub duration_seconds: f32,
+    pub published: String,
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn rfc2822(created_at: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(created_at)
+        .map(|dt| dt.to_rfc2822())
+        .unwrap_or_else(|_| chrono::Utc::now().to_rfc2822())
+}
+
+fn itunes_duration(seconds: f32) -> String {
+    let total = seconds.max(0.0).round() as u64;
+    format!("{:02}:{:02}:{:02}", total / 3600, total / 60 % 60, total % 60)
+}
+
+pub fn render_feed(config: &PodcastConfig, feed_url: Option<&str>, items: &[FeedItem]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+    let _ = writeln!(
+        out,
+        "<rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\" xmlns:atom=\"http://www.w3.org/2005/Atom\">"
+    );
+    let _ = writeln!(out, "  <channel>");
+    let _ = writeln!(out, "    <title>{}</title>", xml_escape(&config.title));
+    let _ = writeln!(out, "    <description>{}</description>", xml_escape(&config.description));
+    let _ = writeln!(out, "    <language>{}</language>", xml_escape(&config.language));
+    if let Some(base_url) = &config.base_url {
+        let _ = writeln!(out, "    <link>{}</link>", xml_escape(base_url));
+    }
+    if let Some(feed_url) = feed_url {
+        let _ = writeln!(
+            out,
+            "    <atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>",
+            xml_escape(feed_url)
+        );
+    }
+    if let Some(author) = &config.author {
+        let _ = writeln!(out, "    <itunes:author>{}</itunes:author>", xml_escape(author));
+    }
+    // Private feeds should stay out of public directories
+    let _ = writeln!(out, "    <itunes:block>Yes</itunes:block>");
+
+    for item in items {
+        let _ = writeln!(out, "    <item>");
+        let _ = writeln!(out, "      <title>{}</title>", xml_escape(&item.title));
+        let _ = writeln!(out, "      <description>{}</description>", xml_escape(&item.description));
+        let _ = writeln!(out, "      <guid isPermaLink=\"false\">{}</guid>", xml_escape(&item.guid));
+        let _ = writeln!(out, "      <pubDate>{}</pubDate>", item.published);
+        let _ = writeln!(
+            out,
+            "      <enclosure url=\"{}\" length=\"{}\" type=\"audio/mpeg\"/>",
+            xml_escape(&item.url),
+            item.length
+        );
+        let _ = writeln!(out, "      <itunes:duration>{}</itunes:duration>", itunes_duration(item.duration_seconds));
+        let _ = writeln!(out, "    </item>");
+    }
+
+    let _ = writeln!(out, "  </channel>");
+    let _ = writeln!(out, "</rss>");
+    out
+}
+
+fn public_url(base_url: Option<&str>, key: &str, local_path: &Path) -> String {
+    match base_url {
+        Some(base) => format!("{}/{}", base.trim_end_matches('/'), key),
+        None => format!("file://{}", local_path.to_string_lossy()),
+    }
+}
+
+/// Episodes for a project: explicit list, or all TTS takes tagged with the project
+fn resolve_episodes(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+    config: &PodcastConfig,
+) -> Result<Vec<(GeneratedAudio, String, Option<String>)>, AudioError> {
+    if !config.episodes.is_empty() {
+        return config
+            .episodes
+            .iter()
+            .map(|episode| {
+                AudioCacheDb::get_audio_record(conn, &episode.audio_id)?
+                    .map(|audio| (audio, episode.title.clone(), episode.description.clone()))
+                    .ok_or_else(|| AudioError::Validation(format!("Audio not found: {}", episode.audio_id)))
+            })
+            .collect();
+    }
+
+    let mut takes: Vec<_> = AudioCacheDb::get_audio_records(conn, &AudioType::Tts)?
+        .into_iter()
+        .filter(|audio| audio.metadata.get("project_id").and_then(|p| p.as_str()) == Some(project_id))
+        .collect();
+    takes.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+    Ok(takes
+        .into_iter()
+        .map(|audio| {
+            let title: String = audio.prompt.chars().take(80).collect();
+            (audio, title, None)
+        })
+        .collect())
+}
+
+// ========== Tauri Commands ==========
+
+/// Render an RSS feed for a project's narrated episodes, optionally uploading the
+/// episode files and feed through a cloud backend. Returns the local feed path.
+#[tauri::command]
+pub async fn export_podcast_feed(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: String,
+    config: PodcastConfig,
+) -> Result<String, AudioError> {
+    let backend = config
+        .upload_backend
+        .as_deref()
+        .map(RemoteBackend::parse)
+        .transpose()?;
+    if backend.is_some() && config.base_url.is_none() {
+        return Err(AudioError::Validation("A public base URL is required when uploading the feed".to_string()));
+    }
+
+    let (project, feed_config) = (project_id.clone(), config.clone());
+    let (episodes, remote) = state
+        .call_db(move |conn| {
+            let episodes = resolve_episodes(conn, &project, &feed_config)?;
+            let remote = backend.map(|backend| open_remote(conn, backend)).transpose()?;
+            Ok((episodes, remote))
+        })
+        .await?;
+    if episodes.is_empty() {
+        return Err(AudioError::Validation(format!("No episodes found for project {}", project_id)));
+    }
+
+    let safe_project: String = project_id
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let key_prefix = format!("podcast/{}", safe_project);
+
+    let mut items = Vec::with_capacity(episodes.len());
+    for (audio, title, description) in episodes {
+        let local_path = PathBuf::from(&audio.local_path);
+        let data = tokio::fs::read(&local_path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", local_path.display(), e))?;
+        let extension = local_path.extension().and_then(|e| e.to_str()).unwrap_or("mp3");
+        let key = format!("{}/{}.{}", key_prefix, audio.id, extension);
+        let length = data.len() as u64;
+
+        if let Some(remote) = &remote {
+            remote.put_object(&key, data.into()).await?;
+        }
+
+        items.push(FeedItem {
+            url: public_url(config.base_url.as_deref(), &key, &local_path),
+            guid: audio.id,
+            title,
+            description: description.unwrap_or_else(|| audio.prompt.clone()),
+            length,
+            duration_seconds: audio.duration_seconds,
+            published: rfc2822(&audio.created_at),
+        });
+    }
+
+    let feed_key = format!("{}/feed.xml", key_prefix);
+    let feed_url = config
+        .base_url
+        .as_deref()
+        .map(|base| format!("{}/{}", base.trim_end_matches('/'), feed_key));
+    let feed = render_feed(&config, feed_url.as_deref(), &items);
+
+    if let Some(remote) = &remote {
+        remote
+            .put_object(&feed_key, feed.clone().into())
+            .await?;
+    }
+
+    let path = match &config.destination {
+        Some(destination) => PathBuf::from(destination),
+        None => ensure_cache(&state).await?
+            .cache_dir()
+            .join("exports")
+            .join(&safe_project)
+            .join("feed.xml"),
+    };
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, feed)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_feed_escapes() {
+        let config: PodcastConfig = serde_json::from_value(serde_json::json!({
+            "title": "Tales & Tunes",
+            "base_url": "https://example.com/pod"
+        }))
+        .unwrap();
+        let items = vec![FeedItem {
+            guid: "1".to_string(),
+            title: "Episode <1>".to_string(),
+            description: String::new(),
+            url: "https://example.com/pod/1.mp3".to_string(),
+            length: 1024,
+            duration_seconds: 75.0,
+            published: rfc2822("2024-01-01T00:00:00Z"),
+        }];
+
+        let feed = render_feed(&config, None, &items);
+        assert!(feed.contains("<title>Tales &amp; Tunes</title>"));
+        assert!(feed.contains("<title>Episode &lt;1&gt;</title>"));
+        assert!(feed.contains("length=\"1024\""));
+        assert!(feed.contains("<itunes:duration>00:01:15</itunes:duration>"));
+    }
+}
@@ -0,0 +1,262 @@
+// Named voice settings presets ("Calm narration", "Excited ad read") so tuned
+// stability/style values can be reused by name: in TTS requests, on character
+// mappings, and shared between team members as a JSON file.
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+
+use super::error::AudioError;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Version written to exported preset files
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoicePreset {
+    pub name: String,
+    pub settings: VoiceSettings,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+/// Contents of an exported presets file
+#[derive(Debug, Serialize, Deserialize)]
+struct PresetFile {
+    version: u32,
+    presets: Vec<VoicePreset>,
+}
+
+/// What an import brought in
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PresetImportSummary {
+    pub imported: usize,
+    /// Presets left alone because one with the same name exists
+    pub skipped: Vec<String>,
+}
+
+fn preset_from_row(row: &rusqlite::Row) -> rusqlite::Result<VoicePreset> {
+    let settings: String = row.get(1)?;
+    Ok(VoicePreset {
+        name: row.get(0)?,
+        settings: serde_json::from_str(&settings).unwrap_or_default(),
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+    })
+}
+
+/// All presets, by name
+pub fn get_presets(conn: &Connection) -> Result<Vec<VoicePreset>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, settings, created_at, updated_at FROM voice_presets ORDER BY name COLLATE NOCASE",
+    )?;
+    let presets = stmt.query_map([], preset_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(presets)
+}
+
+/// A preset by (case-insensitive) name
+pub fn get_preset(conn: &Connection, name: &str) -> Result<Option<VoicePreset>> {
+    let preset = conn
+        .prepare_cached("SELECT name, settings, created_at, updated_at FROM voice_presets WHERE name = ?1")?
+        .query_row([name], preset_from_row)
+        .optional()?;
+    Ok(preset)
+}
+
+/// A preset that must exist, as a validation error otherwise
+pub fn require_preset(conn: &Connection, name: &str) -> Result<VoicePreset> {
+    get_preset(conn, name)?.ok_or_else(|| AudioError::Validation(format!("No voice preset named {}", name)).into())
+}
+
+/// Create a preset or replace the settings of the one with this name
+pub fn save_preset(conn: &Connection, name: &str, settings: &VoiceSettings) -> Result<VoicePreset> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AudioError::Validation("Preset name cannot be empty".to_string()).into());
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO voice_presets (name, settings, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)
+         ON CONFLICT(name) DO UPDATE SET settings = excluded.settings, updated_at = excluded.updated_at",
+        (name, serde_json::to_string(settings)?, &now),
+    )?;
+    require_preset(conn, name)
+}
+
+/// Rename a preset, updating the character mappings that use it
+pub fn rename_preset(conn: &mut Connection, name: &str, new_name: &str) -> Result<VoicePreset> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err(AudioError::Validation("Preset name cannot be empty".to_string()).into());
+    }
+    let preset = require_preset(conn, name)?;
+    if !preset.name.eq_ignore_ascii_case(new_name) && get_preset(conn, new_name)?.is_some() {
+        return Err(AudioError::Validation(format!("A voice preset named {} already exists", new_name)).into());
+    }
+    let tx = conn.transaction()?;
+    tx.execute(
+        "UPDATE voice_presets SET name = ?2, updated_at = ?3 WHERE name = ?1",
+        (&preset.name, new_name, chrono::Utc::now().to_rfc3339()),
+    )?;
+    tx.execute("UPDATE character_voices SET preset = ?2 WHERE preset = ?1 COLLATE NOCASE", (&preset.name, new_name))?;
+    tx.commit()?;
+    require_preset(conn, new_name)
+}
+
+/// Delete a preset; character mappings using it go back to the voice's settings
+pub fn delete_preset(conn: &mut Connection, name: &str) -> Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("UPDATE character_voices SET preset = NULL WHERE preset = ?1 COLLATE NOCASE", [name])?;
+    tx.execute("DELETE FROM voice_presets WHERE name = ?1", [name])?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Resolve `request.preset` into its voice settings. Settings given with the
+/// request take precedence over the preset.
+pub fn apply(conn: &Connection, mut request: TtsRequest) -> Result<TtsRequest> {
+    if let Some(name) = request.preset.take() {
+        let preset = require_preset(conn, &name)?;
+        if request.voice_settings.is_none() {
+            request.voice_settings = Some(preset.settings);
+        }
+    }
+    Ok(request)
+}
+
+/// Write the named presets (all of them when `names` is `None`) to `dest`
+pub fn export_presets(conn: &Connection, names: Option<&[String]>, dest: &Path) -> Result<usize> {
+    let presets = match names {
+        Some(names) => names.iter().map(|name| require_preset(conn, name)).collect::<Result<Vec<_>>>()?,
+        None => get_presets(conn)?,
+    };
+    let count = presets.len();
+    let file = PresetFile { version: EXPORT_VERSION, presets };
+    std::fs::write(dest, serde_json::to_string_pretty(&file)?)
+        .with_context(|| format!("Failed to write {}", dest.display()))?;
+    Ok(count)
+}
+
+/// Add the presets in a file written by `export_presets`. Presets whose name
+/// is taken are skipped unless `overwrite` is set.
+pub fn import_presets(conn: &mut Connection, src: &Path, overwrite: bool) -> Result<PresetImportSummary> {
+    let json = std::fs::read_to_string(src).with_context(|| format!("Failed to read {}", src.display()))?;
+    let file: PresetFile = serde_json::from_str(&json)
+        .map_err(|e| AudioError::Validation(format!("Not a voice presets file: {}", e)))?;
+    if file.version > EXPORT_VERSION {
+        return Err(AudioError::Validation(format!("Unsupported voice presets version {}", file.version)).into());
+    }
+
+    let mut summary = PresetImportSummary::default();
+    let tx = conn.transaction()?;
+    for preset in file.presets {
+        if !overwrite && get_preset(&tx, &preset.name)?.is_some() {
+            summary.skipped.push(preset.name);
+            continue;
+        }
+        save_preset(&tx, &preset.name, &preset.settings)?;
+        summary.imported += 1;
+    }
+    tx.commit()?;
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn list_voice_presets(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<VoicePreset>, AudioError> {
+    state.call_db(|conn| get_presets(conn)).await
+}
+
+/// Create a preset, or update the settings of the one with this name
+#[tauri::command]
+pub async fn save_voice_preset(
+    state: State<'_, Arc<ElevenLabsState>>,
+    name: String,
+    settings: VoiceSettings,
+) -> Result<VoicePreset, AudioError> {
+    state.call_db(move |conn| save_preset(conn, &name, &settings)).await
+}
+
+#[tauri::command]
+pub async fn rename_voice_preset(
+    state: State<'_, Arc<ElevenLabsState>>,
+    name: String,
+    new_name: String,
+) -> Result<VoicePreset, AudioError> {
+    state.call_db(move |conn| rename_preset(conn, &name, &new_name)).await
+}
+
+#[tauri::command]
+pub async fn delete_voice_preset(state: State<'_, Arc<ElevenLabsState>>, name: String) -> Result<(), AudioError> {
+    state.call_db(move |conn| delete_preset(conn, &name)).await
+}
+
+/// Write presets to a JSON file for sharing; all of them when `names` is not given
+#[tauri::command]
+pub async fn export_voice_presets(
+    state: State<'_, Arc<ElevenLabsState>>,
+    dest: String,
+    names: Option<Vec<String>>,
+) -> Result<usize, AudioError> {
+    state.call_db(move |conn| export_presets(conn, names.as_deref(), Path::new(&dest))).await
+}
+
+/// Import a presets file, replacing same-named presets only with `overwrite`
+#[tauri::command]
+pub async fn import_voice_presets(
+    state: State<'_, Arc<ElevenLabsState>>,
+    src: String,
+    overwrite: Option<bool>,
+) -> Result<PresetImportSummary, AudioError> {
+    state
+        .call_db(move |conn| import_presets(conn, Path::new(&src), overwrite.unwrap_or(false)))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::eleven_labs::schema;
+
+    #[test]
+    fn test_presets_round_trip() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        let calm = VoiceSettings { stability: 0.8, style: 0.1, ..Default::default() };
+        save_preset(&conn, "Calm narration", &calm).unwrap();
+        save_preset(&conn, "Excited ad read", &VoiceSettings { style: 0.9, ..Default::default() }).unwrap();
+
+        let request = TtsRequest {
+            text: "Hi".to_string(),
+            voice_id: "v".to_string(),
+            model_id: default_model_id(),
+            voice_settings: None,
+            output_format: default_output_format(),
+            normalize_text: None,
+            preset: Some("calm narration".to_string()),
+            previous_text: None,
+            next_text: None,
+            previous_request_ids: Vec::new(),
+            seed: None,
+        };
+        assert_eq!(apply(&conn, request.clone()).unwrap().voice_settings, Some(calm.clone()));
+        let explicit = TtsRequest { voice_settings: Some(VoiceSettings::default()), ..request.clone() };
+        assert_eq!(apply(&conn, explicit).unwrap().voice_settings, Some(VoiceSettings::default()));
+        assert!(apply(&conn, TtsRequest { preset: Some("Missing".to_string()), ..request }).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("presets.json");
+        assert_eq!(export_presets(&conn, Some(&["Calm narration".to_string()]), &file).unwrap(), 1);
+
+        save_preset(&conn, "Calm narration", &VoiceSettings::default()).unwrap();
+        let summary = import_presets(&mut conn, &file, false).unwrap();
+        assert_eq!((summary.imported, summary.skipped), (0, vec!["Calm narration".to_string()]));
+        assert_eq!(import_presets(&mut conn, &file, true).unwrap().imported, 1);
+        assert_eq!(get_preset(&conn, "Calm narration").unwrap().unwrap().settings, calm);
+    }
+}
@@ -0,0 +1,252 @@
+// Audio processing hooks. Processors run on clone samples before they are
+// uploaded and on generated clips before they are recorded in the library.
+// Built-in processors are ffmpeg presets; anything else can be plugged in as
+// an external command.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::{AudioDb, SettingsDb};
+use super::ElevenLabsState;
+use super::error::AudioError;
+
+/// Settings key holding the global hook configuration; per-project
+/// configurations are stored under `audio_hooks:<project id>`
+pub const AUDIO_HOOKS_KEY: &str = "audio_hooks";
+
+/// Where in the pipeline a processor chain runs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookStage {
+    /// Voice clone samples, before upload
+    BeforeUpload,
+    /// Generated speech and sound effects, before they are added to the library
+    AfterGeneration,
+}
+
+/// A single processing step. Each step reads `{input}` and writes `{output}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AudioProcessor {
+    /// External program; `{input}` and `{output}` in the arguments are
+    /// replaced with file paths
+    Command { command: String, args: Vec<String> },
+    /// EBU R128 loudness normalization
+    Normalize {
+        #[serde(default = "default_target_lufs")]
+        target_lufs: f32,
+    },
+    /// Strip leading and trailing silence quieter than the threshold
+    Trim {
+        #[serde(default = "default_trim_threshold_db")]
+        threshold_db: f32,
+    },
+}
+
+fn default_target_lufs() -> f32 {
+    -16.0
+}
+
+fn default_trim_threshold_db() -> f32 {
+    -50.0
+}
+
+impl AudioProcessor {
+    /// Program and arguments processing `input` into `output`
+    fn invocation(&self, ffmpeg: &str, input: &Path, output: &Path) -> (String, Vec<String>) {
+        let input = input.to_string_lossy();
+        let output = output.to_string_lossy();
+        let ffmpeg_filter = |filter: String| {
+            let args = ["-y", "-hide_banner", "-loglevel", "error", "-i", &input, "-af", &filter, &output];
+            (ffmpeg.to_string(), args.iter().map(|a| a.to_string()).collect())
+        };
+
+        match self {
+            AudioProcessor::Command { command, args } => (
+                command.clone(),
+                args.iter()
+                    .map(|arg| arg.replace("{input}", &input).replace("{output}", &output))
+                    .collect(),
+            ),
+            AudioProcessor::Normalize { target_lufs } => {
+                ffmpeg_filter(format!("loudnorm=I={}:TP=-1.5:LRA=11", target_lufs))
+            }
+            AudioProcessor::Trim { threshold_db } => {
+                // silenceremove only trims the start, so trim, reverse, trim and reverse back
+                let trim = format!("silenceremove=start_periods=1:start_threshold={}dB", threshold_db);
+                ffmpeg_filter(format!("{},areverse,{},areverse", trim, trim))
+            }
+        }
+    }
+}
+
+/// Processor chains for each stage
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioHookConfig {
+    #[serde(default)]
+    pub before_upload: Vec<AudioProcessor>,
+    #[serde(default)]
+    pub after_generation: Vec<AudioProcessor>,
+    /// ffmpeg executable for the built-in processors, `ffmpeg` on the PATH by default
+    #[serde(default)]
+    pub ffmpeg: Option<String>,
+}
+
+impl AudioHookConfig {
+    fn key(project_id: Option<&str>) -> String {
+        match project_id {
+            Some(project_id) => format!("{}:{}", AUDIO_HOOKS_KEY, project_id),
+            None => AUDIO_HOOKS_KEY.to_string(),
+        }
+    }
+
+    /// The configuration stored for exactly this scope
+    pub async fn load_scope(db: &AudioDb, project_id: Option<&str>) -> Result<Option<Self>, AudioError> {
+        let key = Self::key(project_id);
+        db.call(move |conn| SettingsDb::get_setting(conn, &key))
+            .await?
+            .map(|json| serde_json::from_str(&json).map_err(AudioError::from))
+            .transpose()
+    }
+
+    /// The project's configuration, falling back to the global one
+    pub async fn load(db: &AudioDb, project_id: Option<&str>) -> Result<Self, AudioError> {
+        if let Some(project_id) = project_id {
+            if let Some(config) = Self::load_scope(db, Some(project_id)).await? {
+                return Ok(config);
+            }
+        }
+        Ok(Self::load_scope(db, None).await?.unwrap_or_default())
+    }
+
+    fn processors(&self, stage: HookStage) -> &[AudioProcessor] {
+        match stage {
+            HookStage::BeforeUpload => &self.before_upload,
+            HookStage::AfterGeneration => &self.after_generation,
+        }
+    }
+}
+
+/// Run one processor, replacing `path` with its output
+async fn run_processor(processor: &AudioProcessor, ffmpeg: &str, path: &Path) -> Result<(), AudioError> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("mp3");
+    let output = path.with_extension(format!("processing.{}", extension));
+    let (program, args) = processor.invocation(ffmpeg, path, &output);
+
+    let result = tokio::process::Command::new(&program)
+        .args(&args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", program, e));
+    let result = match result {
+        Ok(result) if result.status.success() && output.exists() => Ok(()),
+        Ok(result) => Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&result.stderr).trim()
+        )),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&output).await;
+        return Err(AudioError::Other(e));
+    }
+
+    tokio::fs::rename(&output, path)
+        .await
+        .map_err(|e| AudioError::Other(format!("Failed to replace {}: {}", path.display(), e)))
+}
+
+/// Run the configured chain for `stage` on a file in place
+pub async fn run_hooks(db: &AudioDb, stage: HookStage, project_id: Option<&str>, path: &Path) -> Result<(), AudioError> {
+    let config = AudioHookConfig::load(db, project_id).await?;
+    let ffmpeg = config.ffmpeg.as_deref().unwrap_or("ffmpeg");
+    for processor in config.processors(stage) {
+        run_processor(processor, ffmpeg, path).await?;
+    }
+    Ok(())
+}
+
+/// Processed copies of clone samples for upload. The managed originals are
+/// left untouched; returns the original paths when no processors are configured.
+pub async fn prepare_uploads(
+    db: &AudioDb,
+    project_id: Option<&str>,
+    paths: &[String],
+) -> Result<(Vec<String>, Option<tempfile::TempDir>), AudioError> {
+    let config = AudioHookConfig::load(db, project_id).await?;
+    if config.before_upload.is_empty() {
+        return Ok((paths.to_vec(), None));
+    }
+
+    let dir = tempfile::tempdir()?;
+    let mut processed = Vec::with_capacity(paths.len());
+    for path in paths {
+        let source = PathBuf::from(path);
+        let name = source.file_name().ok_or_else(|| format!("Invalid sample path: {}", path))?;
+        let copy = dir.path().join(name);
+        tokio::fs::copy(&source, &copy)
+            .await
+            .map_err(|e| format!("Failed to copy {}: {}", path, e))?;
+        run_hooks(db, HookStage::BeforeUpload, project_id, &copy).await?;
+        processed.push(copy.to_string_lossy().to_string());
+    }
+    Ok((processed, Some(dir)))
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the hook configuration stored for a project, or the global one without a project
+#[tauri::command]
+pub async fn get_audio_hooks(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: Option<String>,
+) -> Result<Option<AudioHookConfig>, AudioError> {
+    AudioHookConfig::load_scope(&state.db()?, project_id.as_deref()).await
+}
+
+/// Save the hook configuration for a project (or globally); `None` removes it
+#[tauri::command]
+pub async fn set_audio_hooks(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: Option<String>,
+    config: Option<AudioHookConfig>,
+) -> Result<Option<AudioHookConfig>, AudioError> {
+    let key = AudioHookConfig::key(project_id.as_deref());
+    state
+        .call_db(move |conn| {
+            match &config {
+                Some(config) => SettingsDb::save_setting(conn, &key, &serde_json::to_string(config)?)?,
+                None => SettingsDb::remove_setting(conn, &key)?,
+            }
+            Ok(config)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_processor_invocation() {
+        let (input, output) = (Path::new("/tmp/a.mp3"), Path::new("/tmp/a.processing.mp3"));
+
+        let command = AudioProcessor::Command {
+            command: "sox".into(),
+            args: vec!["{input}".into(), "{output}".into(), "norm".into()],
+        };
+        assert_eq!(
+            command.invocation("ffmpeg", input, output),
+            ("sox".to_string(), vec!["/tmp/a.mp3".to_string(), "/tmp/a.processing.mp3".to_string(), "norm".to_string()])
+        );
+
+        let (program, args) = AudioProcessor::Normalize { target_lufs: -16.0 }.invocation("ffmpeg", input, output);
+        assert_eq!(program, "ffmpeg");
+        assert!(args.contains(&"loudnorm=I=-16:TP=-1.5:LRA=11".to_string()));
+        assert_eq!(args.last().map(String::as_str), Some("/tmp/a.processing.mp3"));
+    }
+}
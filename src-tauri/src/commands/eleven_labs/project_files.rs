@@ -0,0 +1,366 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::CharacterVoiceDb;
+use super::error::AudioError;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// File holding the project's character -> voice casting
+const CASTING_FILE: &str = "casting.yaml";
+
+/// Kinds of per-project documents stored in `audio_project_documents`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentKind {
+    Script,
+    Glossary,
+    Pacing,
+}
+
+impl DocumentKind {
+    pub const ALL: [DocumentKind; 3] = [DocumentKind::Script, DocumentKind::Glossary, DocumentKind::Pacing];
+
+    pub fn parse(value: &str) -> Result<Self, AudioError> {
+        match value {
+            "script" => Ok(DocumentKind::Script),
+            "glossary" => Ok(DocumentKind::Glossary),
+            "pacing" => Ok(DocumentKind::Pacing),
+            other => Err(AudioError::Validation(format!("Unknown project document kind: {}", other))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentKind::Script => "script",
+            DocumentKind::Glossary => "glossary",
+            DocumentKind::Pacing => "pacing",
+        }
+    }
+
+    /// Subfolder of the project folder holding this kind of document
+    fn dir_name(&self) -> &'static str {
+        match self {
+            DocumentKind::Script => "scripts",
+            DocumentKind::Glossary => "glossaries",
+            DocumentKind::Pacing => "pacing",
+        }
+    }
+
+    /// Validate a document against its schema, returning its name and normalized JSON
+    fn normalize(&self, document: serde_json::Value) -> Result<(String, serde_json::Value)> {
+        fn typed<T: DeserializeOwned + Serialize>(
+            document: serde_json::Value,
+            name: impl Fn(&T) -> &str,
+        ) -> Result<(String, serde_json::Value)> {
+            let parsed: T = serde_json::from_value(document)?;
+            let name = name(&parsed).trim().to_string();
+            if name.is_empty() {
+                return Err(anyhow!("Document name is required"));
+            }
+            Ok((name, serde_json::to_value(&parsed)?))
+        }
+
+        match self {
+            DocumentKind::Script => typed::<ProjectScript>(document, |d| &d.name),
+            DocumentKind::Glossary => typed::<PronunciationGlossary>(document, |d| &d.name),
+            DocumentKind::Pacing => typed::<PacingProfile>(document, |d| &d.name),
+        }
+    }
+
+    fn render_yaml(&self, document: serde_json::Value) -> Result<String> {
+        Ok(match self {
+            DocumentKind::Script => serde_yaml::to_string(&serde_json::from_value::<ProjectScript>(document)?)?,
+            DocumentKind::Glossary => {
+                serde_yaml::to_string(&serde_json::from_value::<PronunciationGlossary>(document)?)?
+            }
+            DocumentKind::Pacing => serde_yaml::to_string(&serde_json::from_value::<PacingProfile>(document)?)?,
+        })
+    }
+
+    fn parse_yaml(&self, yaml: &str) -> Result<serde_json::Value> {
+        Ok(match self {
+            DocumentKind::Script => serde_json::to_value(serde_yaml::from_str::<ProjectScript>(yaml)?)?,
+            DocumentKind::Glossary => serde_json::to_value(serde_yaml::from_str::<PronunciationGlossary>(yaml)?)?,
+            DocumentKind::Pacing => serde_json::to_value(serde_yaml::from_str::<PacingProfile>(yaml)?)?,
+        })
+    }
+}
+
+/// Database operations for project documents
+pub struct ProjectDocumentDb;
+
+impl ProjectDocumentDb {
+    pub fn save(conn: &Connection, project_id: &str, kind: DocumentKind, name: &str, content: &serde_json::Value) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO audio_project_documents (project_id, kind, name, content, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                project_id,
+                kind.as_str(),
+                name,
+                serde_json::to_string(content)?,
+                chrono::Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn list(conn: &Connection, project_id: &str, kind: DocumentKind) -> Result<Vec<serde_json::Value>> {
+        let mut stmt = conn.prepare(
+            "SELECT content FROM audio_project_documents WHERE project_id = ?1 AND kind = ?2 ORDER BY name",
+        )?;
+        let rows = stmt.query_map(params![project_id, kind.as_str()], |row| row.get::<_, String>(0))?;
+
+        let mut documents = vec![];
+        for row in rows {
+            documents.push(serde_json::from_str(&row?)?);
+        }
+        Ok(documents)
+    }
+
+    pub fn delete(conn: &Connection, project_id: &str, kind: DocumentKind, name: &str) -> Result<()> {
+        conn.execute(
+            "DELETE FROM audio_project_documents WHERE project_id = ?1 AND kind = ?2 AND name = ?3",
+            params![project_id, kind.as_str(), name],
+        )?;
+        Ok(())
+    }
+}
+
+/// `casting.yaml` contents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CastingFile {
+    #[serde(default)]
+    pub characters: Vec<CastingEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastingEntry {
+    pub character: String,
+    pub voice_id: String,
+    pub voice_name: String,
+    /// Name of a voice settings preset; the preset itself is shared separately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+}
+
+/// What an import brought in
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectImportSummary {
+    pub characters: usize,
+    pub scripts: usize,
+    pub glossaries: usize,
+    pub pacing_profiles: usize,
+}
+
+pub fn file_stem_for(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug = slug.trim_matches('-').to_string();
+    if slug.is_empty() { "untitled".to_string() } else { slug }
+}
+
+/// Write the project's casting and documents as YAML under `folder`, removing files
+/// for documents that no longer exist so the folder mirrors the database
+pub fn export_project(conn: &Connection, project_id: &str, folder: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(folder)?;
+    let mut written = vec![];
+
+    let mut characters: Vec<CastingEntry> = CharacterVoiceDb::get_character_voices(conn, Some(project_id))?
+        .into_iter()
+        .map(|m| CastingEntry {
+            character: m.character_name,
+            voice_id: m.voice_id,
+            voice_name: m.voice_name,
+            preset: m.preset,
+        })
+        .collect();
+    characters.sort_by(|a, b| a.character.cmp(&b.character));
+    let casting_path = folder.join(CASTING_FILE);
+    std::fs::write(&casting_path, serde_yaml::to_string(&CastingFile { characters })?)?;
+    written.push(casting_path);
+
+    for kind in DocumentKind::ALL {
+        let dir = folder.join(kind.dir_name());
+        std::fs::create_dir_all(&dir)?;
+
+        let mut expected = vec![];
+        for document in ProjectDocumentDb::list(conn, project_id, kind)? {
+            let name = document.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+            let path = dir.join(format!("{}.yaml", file_stem_for(name)));
+            std::fs::write(&path, kind.render_yaml(document)?)?;
+            expected.push(path.clone());
+            written.push(path);
+        }
+
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("yaml") && !expected.contains(&path) {
+                std::fs::remove_file(&path)?;
+            }
+        }
+    }
+
+    Ok(written)
+}
+
+/// Load casting and documents from a project folder into the database
+pub fn import_project(conn: &mut Connection, project_id: &str, folder: &Path) -> Result<ProjectImportSummary> {
+    let mut summary = ProjectImportSummary::default();
+    let tx = conn.transaction()?;
+
+    let casting_path = folder.join(CASTING_FILE);
+    if casting_path.exists() {
+        let casting: CastingFile = serde_yaml::from_str(&std::fs::read_to_string(&casting_path)?)
+            .map_err(|e| anyhow!("{}: {}", casting_path.display(), e))?;
+        let existing = CharacterVoiceDb::get_character_voices(&tx, Some(project_id))?;
+
+        for entry in casting.characters {
+            match existing.iter().find(|m| m.character_name == entry.character) {
+                Some(mapping) => CharacterVoiceDb::save_mapping(
+                    &tx,
+                    &CharacterVoice {
+                        voice_id: entry.voice_id,
+                        voice_name: entry.voice_name,
+                        preset: entry.preset,
+                        ..mapping.clone()
+                    },
+                )?,
+                None => {
+                    let mapping = CharacterVoiceDb::assign_voice(
+                        &tx,
+                        &entry.character,
+                        &entry.voice_id,
+                        &entry.voice_name,
+                        Some(project_id),
+                    )?;
+                    if let Some(preset) = &entry.preset {
+                        CharacterVoiceDb::set_preset(&tx, &mapping.id, Some(preset))?;
+                    }
+                }
+            }
+            summary.characters += 1;
+        }
+    }
+
+    for kind in DocumentKind::ALL {
+        let dir = folder.join(kind.dir_name());
+        if !dir.is_dir() {
+            continue;
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml")))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let document = kind
+                .parse_yaml(&std::fs::read_to_string(&path)?)
+                .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+            let (name, document) = kind.normalize(document)?;
+            ProjectDocumentDb::save(&tx, project_id, kind, &name, &document)?;
+
+            match kind {
+                DocumentKind::Script => summary.scripts += 1,
+                DocumentKind::Glossary => summary.glossaries += 1,
+                DocumentKind::Pacing => summary.pacing_profiles += 1,
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(summary)
+}
+
+// ========== Tauri Commands ==========
+
+/// Create or replace a script, glossary or pacing profile for a project
+#[tauri::command]
+pub async fn save_project_document(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: String,
+    kind: String,
+    document: serde_json::Value,
+) -> Result<serde_json::Value, AudioError> {
+    let kind = DocumentKind::parse(&kind)?;
+    let (name, document) = kind.normalize(document)?;
+
+    state
+        .call_db(move |conn| {
+            ProjectDocumentDb::save(conn, &project_id, kind, &name, &document)?;
+            Ok(document)
+        })
+        .await
+}
+
+/// List a project's documents of one kind
+#[tauri::command]
+pub async fn list_project_documents(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: String,
+    kind: String,
+) -> Result<Vec<serde_json::Value>, AudioError> {
+    let kind = DocumentKind::parse(&kind)?;
+    state.call_db(move |conn| ProjectDocumentDb::list(conn, &project_id, kind)).await
+}
+
+/// Delete a project document
+#[tauri::command]
+pub async fn delete_project_document(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: String,
+    kind: String,
+    name: String,
+) -> Result<(), AudioError> {
+    let kind = DocumentKind::parse(&kind)?;
+    state.call_db(move |conn| ProjectDocumentDb::delete(conn, &project_id, kind, &name)).await
+}
+
+/// Serialize the project's casting, scripts, glossaries and pacing profiles to YAML files
+#[tauri::command]
+pub async fn export_project_files(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: String,
+    folder: String,
+) -> Result<Vec<String>, AudioError> {
+    let paths = state
+        .call_db(move |conn| export_project(conn, &project_id, Path::new(&folder)))
+        .await?;
+    Ok(paths.iter().map(|p| p.to_string_lossy().to_string()).collect())
+}
+
+/// Re-import YAML project files written by `export_project_files`
+#[tauri::command]
+pub async fn import_project_files(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: String,
+    folder: String,
+) -> Result<ProjectImportSummary, AudioError> {
+    state.call_db(move |conn| import_project(conn, &project_id, Path::new(&folder))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glossary_yaml_is_deterministic() {
+        let document = serde_json::json!({
+            "name": "Names",
+            "entries": { "Zoë": "zo-ee", "Aoife": "ee-fa" }
+        });
+        let (_, normalized) = DocumentKind::Glossary.normalize(document).unwrap();
+        let yaml = DocumentKind::Glossary.render_yaml(normalized.clone()).unwrap();
+
+        assert!(yaml.find("Aoife").unwrap() < yaml.find("Zoë").unwrap());
+        assert_eq!(DocumentKind::Glossary.parse_yaml(&yaml).unwrap(), normalized);
+    }
+}
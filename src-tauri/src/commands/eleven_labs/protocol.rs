@@ -0,0 +1,150 @@
+// `opcode-audio://` protocol. The webview loads cached audio by id, e.g.
+// `convertFileSrc(id, "opcode-audio")`, instead of reading raw filesystem paths
+// through the asset protocol. Range requests are answered with 206 so the
+// player can seek without downloading the whole file. Only files inside the
+// audio cache are served, whatever path a record holds.
+
+use std::io::SeekFrom;
+use std::path::Path;
+use tauri::http::{header, Request, Response, StatusCode};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::cache::AudioCacheDb;
+use super::{ensure_cache, ElevenLabsState};
+
+/// URI scheme registered with the webview
+pub const SCHEME: &str = "opcode-audio";
+
+/// Largest body sent for one range request; longer ranges are cut short
+const MAX_RANGE_LEN: u64 = 1024 * 1024;
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("ogg") | Some("opus") => "audio/ogg",
+        Some("flac") => "audio/flac",
+        Some("m4a") | Some("aac") => "audio/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Inclusive byte range requested by a `Range` header for a file of `len`
+/// bytes, at most `MAX_RANGE_LEN` long. Only the first range of a multi-range
+/// request is served. `None` means the range can't be satisfied.
+fn parse_range(range: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = range.strip_prefix("bytes=")?.split(',').next()?.trim();
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        // Suffix range: the last N bytes
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return None;
+            }
+            (len.saturating_sub(suffix), len.checked_sub(1)?)
+        }
+        (start, "") => (start.parse().ok()?, u64::MAX),
+        (start, end) => (start.parse().ok()?, end.parse().ok()?),
+    };
+    let end = end.min(len.checked_sub(1)?).min(start.saturating_add(MAX_RANGE_LEN - 1));
+    (start <= end && start < len).then_some((start, end))
+}
+
+fn status(status: StatusCode) -> Response<Vec<u8>> {
+    Response::builder().status(status).body(Vec::new()).unwrap_or_default()
+}
+
+/// Serve the cached file of the audio record named by the request path
+pub async fn respond(state: &ElevenLabsState, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let id = request.uri().path().trim_matches('/').to_string();
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return status(StatusCode::BAD_REQUEST);
+    }
+
+    let audio = match state.call_db(move |conn| AudioCacheDb::get_audio_record(conn, &id)).await {
+        Ok(Some(audio)) => audio,
+        Ok(None) => return status(StatusCode::NOT_FOUND),
+        Err(e) => {
+            log::warn!("Failed to look up audio for {}: {}", SCHEME, e);
+            return status(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let cache = match ensure_cache(state).await {
+        Ok(cache) => cache,
+        Err(e) => {
+            log::warn!("Failed to open the audio cache for {}: {}", SCHEME, e);
+            return status(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let Some(path) = cache.resolve(Path::new(&audio.local_path)).await else {
+        log::warn!("Refusing to serve {}: not a file in the audio cache", audio.local_path);
+        return status(StatusCode::NOT_FOUND);
+    };
+    match serve_file(&path, request.headers().get(header::RANGE).and_then(|r| r.to_str().ok())).await {
+        Ok(response) => response,
+        Err(e) => {
+            log::warn!("Failed to serve {}: {}", path.display(), e);
+            status(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+async fn serve_file(path: &Path, range: Option<&str>) -> std::io::Result<Response<Vec<u8>>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, content_type(path))
+        .header(header::ACCEPT_RANGES, "bytes");
+
+    let Some(range) = range else {
+        let mut body = Vec::with_capacity(len as usize);
+        file.read_to_end(&mut body).await?;
+        return Ok(response
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(body)
+            .unwrap_or_default());
+    };
+
+    let Some((start, end)) = parse_range(range, len) else {
+        return Ok(response
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+            .body(Vec::new())
+            .unwrap_or_default());
+    };
+
+    let mut body = vec![0; (end - start + 1) as usize];
+    file.seek(SeekFrom::Start(start)).await?;
+    file.read_exact(&mut body).await?;
+    Ok(response
+        .status(StatusCode::PARTIAL_CONTENT)
+        .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len))
+        .header(header::CONTENT_LENGTH, body.len())
+        .body(body)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=900-2000", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=10-", 1000), Some((10, 999)));
+        assert_eq!(parse_range("bytes=0-", 4 * MAX_RANGE_LEN), Some((0, MAX_RANGE_LEN - 1)));
+        assert_eq!(parse_range("bytes=10-", 4 * MAX_RANGE_LEN), Some((10, MAX_RANGE_LEN + 9)));
+        assert_eq!(parse_range("bytes=0-99999999", 4 * MAX_RANGE_LEN), Some((0, MAX_RANGE_LEN - 1)));
+        let suffix = format!("bytes=-{}", 4 * MAX_RANGE_LEN);
+        assert_eq!(parse_range(&suffix, 4 * MAX_RANGE_LEN), Some((0, MAX_RANGE_LEN - 1)));
+        assert_eq!(parse_range("bytes=18446744073709551615-", 1000), None);
+        assert_eq!(parse_range("bytes=0-9, 20-29", 1000), Some((0, 9)));
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=50-10", 1000), None);
+        assert_eq!(parse_range("items=0-10", 1000), None);
+    }
+}
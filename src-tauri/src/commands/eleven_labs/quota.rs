@@ -0,0 +1,143 @@
+// Checks run before a TTS request is sent: the text must fit the model's
+// per-request character limit and the account's remaining character quota.
+// The quota is fetched from the subscription endpoint at most once a minute
+// and counted down locally between fetches.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::billing::billable_characters;
+use super::api::ElevenLabsApi;
+use super::error::{AudioError, ProviderErrorKind};
+use super::types::{TtsRequest, UsageInfo};
+
+/// How long a fetched quota is trusted before it is fetched again
+const QUOTA_TTL: Duration = Duration::from_secs(60);
+
+/// Most characters a single request to `model_id` may contain, if known
+pub fn model_char_limit(model_id: &str) -> Option<usize> {
+    match model_id {
+        "eleven_v3" => Some(3_000),
+        "eleven_monolingual_v1" | "eleven_multilingual_v1" | "eleven_multilingual_v2" => Some(10_000),
+        "eleven_turbo_v2" | "eleven_flash_v2" => Some(30_000),
+        "eleven_turbo_v2_5" | "eleven_flash_v2_5" => Some(40_000),
+        _ => None,
+    }
+}
+
+/// Reject `chars` characters for `model_id` if they exceed the model limit or
+/// the `remaining` quota (when known)
+fn check(chars: usize, model_id: &str, remaining: Option<i64>) -> Result<(), AudioError> {
+    if let Some(limit) = model_char_limit(model_id) {
+        if chars > limit {
+            return Err(AudioError::Validation(format!(
+                "Text is {} characters; {} accepts at most {} per request",
+                chars, model_id, limit
+            )));
+        }
+    }
+    check_remaining(chars, remaining)
+}
+
+/// Reject `chars` characters if they exceed the `remaining` quota (when known)
+fn check_remaining(chars: usize, remaining: Option<i64>) -> Result<(), AudioError> {
+    if let Some(remaining) = remaining {
+        if chars as i64 > remaining {
+            return Err(AudioError::provider(
+                ProviderErrorKind::QuotaExceeded,
+                format!("Needs {} characters, {} remaining", chars, remaining.max(0)),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Characters left in the billing period; `None` when the plan reports no limit
+fn remaining_of(usage: &UsageInfo) -> Option<i64> {
+    (usage.character_limit > 0).then(|| usage.character_limit - usage.character_count)
+}
+
+/// Remaining character quota, cached between subscription fetches
+#[derive(Default)]
+pub struct QuotaTracker {
+    remaining: Mutex<Option<(i64, Instant)>>,
+}
+
+impl QuotaTracker {
+    /// Record the quota from a freshly fetched subscription
+    pub fn update(&self, usage: &UsageInfo) {
+        if let Ok(mut remaining) = self.remaining.lock() {
+            *remaining = remaining_of(usage).map(|left| (left, Instant::now()));
+        }
+    }
+
+    /// Count characters billed by a successful request against the cached quota
+    pub fn consume(&self, chars: usize) {
+        if let Ok(mut remaining) = self.remaining.lock() {
+            if let Some((left, _)) = remaining.as_mut() {
+                *left -= chars as i64;
+            }
+        }
+    }
+
+    fn cached(&self) -> Option<i64> {
+        let remaining = self.remaining.lock().ok()?;
+        remaining.filter(|(_, at)| at.elapsed() < QUOTA_TTL).map(|(left, _)| left)
+    }
+
+    /// Remaining quota, fetched if the cached value is stale. `None` if it
+    /// can't be fetched (e.g. the key lacks access to the subscription), in
+    /// which case the request is sent and the API has the final say.
+    async fn remaining(&self, client: &dyn ElevenLabsApi) -> Option<i64> {
+        if let Some(left) = self.cached() {
+            return Some(left);
+        }
+        match client.get_usage().await {
+            Ok(usage) => {
+                self.update(&usage);
+                remaining_of(&usage)
+            }
+            Err(e) => {
+                log::debug!("Skipping quota check, usage unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Validate a TTS request before it is sent
+    pub async fn check_tts(&self, client: &dyn ElevenLabsApi, request: &TtsRequest) -> Result<(), AudioError> {
+        self.check_tts_chunks(client, request, std::slice::from_ref(&request.text)).await
+    }
+
+    /// Validate a TTS request sent as one request per chunk: each must fit
+    /// the model and all of them the quota
+    pub async fn check_tts_chunks(
+        &self,
+        client: &dyn ElevenLabsApi,
+        request: &TtsRequest,
+        chunks: &[String],
+    ) -> Result<(), AudioError> {
+        // Over-long text is rejected without fetching the quota
+        for chunk in chunks {
+            check(billable_characters(chunk), &request.model_id, None)?;
+        }
+        let chars = chunks.iter().map(|chunk| billable_characters(chunk)).sum();
+        check_remaining(chars, self.remaining(client).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        assert!(check(500, "eleven_multilingual_v2", Some(1_000)).is_ok());
+        assert!(check(50_000, "custom_model", None).is_ok());
+        assert!(matches!(check(12_000, "eleven_multilingual_v2", None), Err(AudioError::Validation(_))));
+
+        let err = check(1_500, "eleven_flash_v2_5", Some(1_000)).unwrap_err();
+        assert!(matches!(err, AudioError::Provider { kind: ProviderErrorKind::QuotaExceeded, .. }));
+        assert_eq!(err.to_string(), "Needs 1500 characters, 1000 remaining");
+    }
+}
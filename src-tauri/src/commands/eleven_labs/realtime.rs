@@ -0,0 +1,382 @@
+// Conversational AI sessions: a realtime conversation with an agent configured
+// on the provider. Microphone audio is streamed up to the agent and its speech
+// comes back down, passed to the frontend for playback together with
+// transcripts of both sides. Sessions belong to the window holding the
+// conversation, so they are kept in their own managed `RealtimeState`.
+
+use base64::Engine;
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{oneshot, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+
+use super::auth;
+use super::cache::SettingsDb;
+use super::client::WebSocket;
+use super::error::AudioError;
+use super::supervisor::Shutdown;
+use super::voice_input::{self, Capture};
+use super::{get_client, ElevenLabsState};
+
+/// Settings key holding the agent used when a session names none
+pub const REALTIME_AGENT_KEY: &str = "realtime_agent_id";
+
+/// Emitted with a `RealtimeSessionEvent` for everything happening in a session
+pub const REALTIME_SESSION_EVENT: &str = "realtime-session";
+
+/// How often captured microphone audio is sent to the agent
+const MIC_CHUNK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Input sample rate agents use unless the session says otherwise
+const DEFAULT_INPUT_SAMPLE_RATE: u32 = 16_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "camelCase")]
+pub enum RealtimeEvent {
+    /// The agent is listening
+    #[serde(rename_all = "camelCase")]
+    Started { conversation_id: String, audio_format: String },
+    /// Base64 agent speech in the `audio_format` given with `started`
+    Audio { data: String },
+    /// What the user said
+    UserTranscript { text: String },
+    /// What the agent is saying
+    AgentResponse { text: String },
+    /// What the agent actually got to say before it was interrupted
+    AgentResponseCorrection { text: String },
+    /// The user spoke over the agent; agent audio not yet played should be dropped
+    Interruption,
+    /// The session closed; `error` says why unless it was stopped
+    Ended { error: Option<String> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealtimeSessionEvent {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub event: RealtimeEvent,
+}
+
+/// A session as listed by `list_realtime_sessions`
+#[derive(Debug, Clone, Serialize)]
+pub struct RealtimeSessionInfo {
+    pub session_id: String,
+    pub agent_id: String,
+    pub microphone: bool,
+    pub started_at: String,
+}
+
+struct RealtimeSession {
+    info: RealtimeSessionInfo,
+    stop: oneshot::Sender<()>,
+}
+
+/// Open Conversational AI sessions, by session id
+#[derive(Default)]
+pub struct RealtimeState {
+    sessions: Mutex<HashMap<String, RealtimeSession>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InitiationMetadata {
+    conversation_id: String,
+    #[serde(default)]
+    agent_output_audio_format: Option<String>,
+    #[serde(default)]
+    user_input_audio_format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AudioChunk {
+    audio_base_64: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserTranscription {
+    user_transcript: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AgentResponse {
+    agent_response: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AgentResponseCorrection {
+    corrected_agent_response: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Ping {
+    event_id: u64,
+}
+
+/// Messages from the provider; ones the bridge doesn't use are `Other`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    ConversationInitiationMetadata { conversation_initiation_metadata_event: InitiationMetadata },
+    Audio { audio_event: AudioChunk },
+    UserTranscript { user_transcription_event: UserTranscription },
+    AgentResponse { agent_response_event: AgentResponse },
+    AgentResponseCorrection { agent_response_correction_event: AgentResponseCorrection },
+    Interruption {},
+    Ping { ping_event: Ping },
+    #[serde(other)]
+    Other,
+}
+
+/// Sample rate of a `pcm_<rate>` audio format
+fn pcm_sample_rate(format: &str) -> Option<u32> {
+    format.strip_prefix("pcm_")?.parse().ok()
+}
+
+/// Mono samples as 16-bit little-endian PCM at `to` Hz
+fn encode_pcm16(samples: &[f32], from: u32, to: u32) -> Vec<u8> {
+    voice_input::resample(samples, from, to)
+        .into_iter()
+        .flat_map(|sample| ((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+        .collect()
+}
+
+/// The event for the frontend and the reply to send back, if any, for a
+/// message from the provider. The agent's input sample rate is updated when
+/// the session announces it.
+fn handle_message(
+    text: &str,
+    input_rate: &mut u32,
+) -> serde_json::Result<(Option<RealtimeEvent>, Option<serde_json::Value>)> {
+    let event = match serde_json::from_str(text)? {
+        ServerMessage::ConversationInitiationMetadata { conversation_initiation_metadata_event: metadata } => {
+            if let Some(rate) = metadata.user_input_audio_format.as_deref().and_then(pcm_sample_rate) {
+                *input_rate = rate;
+            }
+            RealtimeEvent::Started {
+                conversation_id: metadata.conversation_id,
+                audio_format: metadata.agent_output_audio_format.unwrap_or_else(|| "pcm_16000".to_string()),
+            }
+        }
+        ServerMessage::Audio { audio_event } => RealtimeEvent::Audio { data: audio_event.audio_base_64 },
+        ServerMessage::UserTranscript { user_transcription_event } => {
+            RealtimeEvent::UserTranscript { text: user_transcription_event.user_transcript }
+        }
+        ServerMessage::AgentResponse { agent_response_event } => {
+            RealtimeEvent::AgentResponse { text: agent_response_event.agent_response }
+        }
+        ServerMessage::AgentResponseCorrection { agent_response_correction_event } => {
+            RealtimeEvent::AgentResponseCorrection { text: agent_response_correction_event.corrected_agent_response }
+        }
+        ServerMessage::Interruption {} => RealtimeEvent::Interruption,
+        // Unanswered pings end the session
+        ServerMessage::Ping { ping_event } => {
+            return Ok((None, Some(serde_json::json!({ "type": "pong", "event_id": ping_event.event_id }))));
+        }
+        ServerMessage::Other => return Ok((None, None)),
+    };
+    Ok((Some(event), None))
+}
+
+fn emit(app: &AppHandle, session_id: &str, event: RealtimeEvent) {
+    let event = RealtimeSessionEvent { session_id: session_id.to_string(), event };
+    let _ = app.emit(REALTIME_SESSION_EVENT, event);
+}
+
+/// Pass messages both ways until the provider closes the session or it is
+/// stopped
+async fn run_session(
+    app: &AppHandle,
+    session_id: &str,
+    socket: WebSocket,
+    capture: Option<Capture>,
+    mut stop: oneshot::Receiver<()>,
+    shutdown: Shutdown,
+) -> Result<(), AudioError> {
+    let (mut sink, mut messages) = socket.split();
+    let mut input_rate = DEFAULT_INPUT_SAMPLE_RATE;
+    let mut mic_interval = tokio::time::interval(MIC_CHUNK_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = &mut stop => break,
+            _ = shutdown.wait() => break,
+            _ = mic_interval.tick(), if capture.is_some() => {
+                let Some(capture) = &capture else { continue };
+                let samples = capture.drain();
+                if samples.is_empty() {
+                    continue;
+                }
+                let audio = encode_pcm16(&samples, capture.sample_rate(), input_rate);
+                let chunk = serde_json::json!({
+                    "user_audio_chunk": base64::engine::general_purpose::STANDARD.encode(audio),
+                });
+                sink.send(Message::text(chunk.to_string()))
+                    .await
+                    .map_err(|e| format!("Failed to send microphone audio: {}", e))?;
+            }
+            message = messages.next() => {
+                let text = match message {
+                    None | Some(Ok(Message::Close(_))) => break,
+                    Some(Err(e)) => return Err(format!("Conversation connection lost: {}", e).into()),
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(_)) => continue,
+                };
+                let (event, reply) = match handle_message(&text, &mut input_rate) {
+                    Ok(handled) => handled,
+                    Err(e) => {
+                        log::debug!("Ignoring conversation message: {}", e);
+                        continue;
+                    }
+                };
+                if let Some(reply) = reply {
+                    sink.send(Message::text(reply.to_string()))
+                        .await
+                        .map_err(|e| format!("Failed to answer conversation ping: {}", e))?;
+                }
+                if let Some(event) = event {
+                    emit(app, session_id, event);
+                }
+            }
+        }
+    }
+
+    let _ = sink.close().await;
+    Ok(())
+}
+
+/// The agent to talk to: `agent_id`, or the saved default
+async fn resolve_agent(state: &ElevenLabsState, agent_id: Option<String>) -> Result<String, AudioError> {
+    let agent_id = match agent_id {
+        Some(agent_id) => Some(agent_id),
+        None => state.call_db(|conn| SettingsDb::get_setting(conn, REALTIME_AGENT_KEY)).await?,
+    };
+    agent_id
+        .filter(|id| !id.trim().is_empty())
+        .ok_or_else(|| AudioError::NotConfigured("No Conversational AI agent configured".to_string()))
+}
+
+/// Open a conversation with an agent (the saved default when `agent_id` is not
+/// given), streaming the default microphone to it unless `microphone` is
+/// false. Everything that happens arrives as `realtime-session` events tagged
+/// with the returned session id.
+#[tauri::command]
+pub async fn start_realtime_session(
+    app: AppHandle,
+    state: State<'_, Arc<ElevenLabsState>>,
+    realtime: State<'_, RealtimeState>,
+    agent_id: Option<String>,
+    microphone: Option<bool>,
+) -> Result<String, AudioError> {
+    let agent_id = resolve_agent(&state, agent_id).await?;
+    let client = get_client(&state).await?;
+    let socket = auth::check(&state, client.open_conversation(&agent_id).await).await?;
+    let microphone = microphone.unwrap_or(true);
+    // Opening the device blocks until the capture thread has started it
+    let capture = if microphone {
+        Some(tokio::task::spawn_blocking(voice_input::start_capture).await??)
+    } else {
+        None
+    };
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let (stop_tx, stop_rx) = oneshot::channel();
+    let info = RealtimeSessionInfo {
+        session_id: session_id.clone(),
+        agent_id,
+        microphone,
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    // Held until the session is stored, so one that ends straight away can't
+    // try to remove itself first
+    let mut sessions = realtime.sessions.lock().await;
+    let (app, id) = (app.clone(), session_id.clone());
+    state.tasks().spawn(format!("realtime-{}", session_id), |shutdown| async move {
+        let result = run_session(&app, &id, socket, capture, stop_rx, shutdown).await;
+        app.state::<RealtimeState>().sessions.lock().await.remove(&id);
+        if let Err(e) = &result {
+            log::warn!("Conversation {} ended: {}", id, e);
+        }
+        emit(&app, &id, RealtimeEvent::Ended { error: result.err().map(|e| e.to_string()) });
+    });
+    sessions.insert(session_id.clone(), RealtimeSession { info, stop: stop_tx });
+    Ok(session_id)
+}
+
+/// Close a session and its microphone. Returns whether it was still open.
+#[tauri::command]
+pub async fn stop_realtime_session(
+    realtime: State<'_, RealtimeState>,
+    session_id: String,
+) -> Result<bool, AudioError> {
+    let Some(session) = realtime.sessions.lock().await.remove(&session_id) else {
+        return Ok(false);
+    };
+    let _ = session.stop.send(());
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn list_realtime_sessions(
+    realtime: State<'_, RealtimeState>,
+) -> Result<Vec<RealtimeSessionInfo>, AudioError> {
+    let sessions = realtime.sessions.lock().await;
+    let mut infos: Vec<_> = sessions.values().map(|session| session.info.clone()).collect();
+    infos.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(infos)
+}
+
+/// The agent sessions talk to when none is given
+#[tauri::command]
+pub async fn get_realtime_agent(state: State<'_, Arc<ElevenLabsState>>) -> Result<Option<String>, AudioError> {
+    state.call_db(|conn| SettingsDb::get_setting(conn, REALTIME_AGENT_KEY)).await
+}
+
+#[tauri::command]
+pub async fn set_realtime_agent(state: State<'_, Arc<ElevenLabsState>>, agent_id: String) -> Result<(), AudioError> {
+    state
+        .call_db(move |conn| SettingsDb::save_setting(conn, REALTIME_AGENT_KEY, agent_id.trim()))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_messages() {
+        let mut input_rate = DEFAULT_INPUT_SAMPLE_RATE;
+        let metadata = r#"{"type":"conversation_initiation_metadata","conversation_initiation_metadata_event":
+            {"conversation_id":"conv_1","agent_output_audio_format":"pcm_22050","user_input_audio_format":"pcm_48000"}}"#;
+        let (event, reply) = handle_message(metadata, &mut input_rate).unwrap();
+        let started = RealtimeEvent::Started {
+            conversation_id: "conv_1".to_string(),
+            audio_format: "pcm_22050".to_string(),
+        };
+        assert_eq!(event, Some(started));
+        assert_eq!((reply, input_rate), (None, 48_000));
+
+        let transcript = r#"{"type":"user_transcript","user_transcription_event":{"user_transcript":"Hello"}}"#;
+        let (event, _) = handle_message(transcript, &mut input_rate).unwrap();
+        assert_eq!(event, Some(RealtimeEvent::UserTranscript { text: "Hello".to_string() }));
+        let interruption = r#"{"type":"interruption","interruption_event":{"event_id":4}}"#;
+        assert_eq!(handle_message(interruption, &mut input_rate).unwrap().0, Some(RealtimeEvent::Interruption));
+
+        let ping = r#"{"type":"ping","ping_event":{"event_id":7,"ping_ms":40}}"#;
+        let (event, reply) = handle_message(ping, &mut input_rate).unwrap();
+        assert_eq!((event, reply), (None, Some(serde_json::json!({ "type": "pong", "event_id": 7 }))));
+        assert_eq!(handle_message(r#"{"type":"vad_score"}"#, &mut input_rate).unwrap(), (None, None));
+
+        let audio = RealtimeEvent::Audio { data: "AA==".to_string() };
+        let event = RealtimeSessionEvent { session_id: "s".to_string(), event: audio };
+        assert_eq!(
+            serde_json::to_value(event).unwrap(),
+            serde_json::json!({ "sessionId": "s", "event": "audio", "data": { "data": "AA==" } })
+        );
+        assert_eq!(encode_pcm16(&[0.0, 1.0], 16_000, 16_000), vec![0, 0, 0xff, 0x7f]);
+    }
+}
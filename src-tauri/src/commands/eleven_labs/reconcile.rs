@@ -0,0 +1,170 @@
+// Reconciliation of the audio cache directory with the `audio_cache` table.
+// Manual deletion or a crash between writing a file and recording it can leave
+// files nobody references (orphans) and records whose file is gone (dangling).
+// A scan reports both; a repair deletes or registers the orphans and removes
+// the dangling records.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::{estimate_duration_seconds, AudioCacheDb};
+use super::compare;
+use super::error::AudioError;
+use super::types::*;
+use super::{ensure_cache, ElevenLabsState};
+
+/// Metadata key marking a record registered for a file found in the cache
+pub const RECONCILED_KEY: &str = "reconciled";
+
+/// What to do with files in the cache that no record references
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanRepair {
+    Delete,
+    /// Add a record for each, typed by its directory
+    Register,
+}
+
+/// A record whose file no longer exists
+#[derive(Debug, Clone, Serialize)]
+pub struct DanglingRecord {
+    pub id: String,
+    pub audio_type: AudioType,
+    pub prompt: String,
+    pub local_path: String,
+}
+
+/// What a reconciliation found and, when repairing, did
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconcileReport {
+    /// Files in the cache directory that no record references
+    pub orphan_files: Vec<String>,
+    pub dangling_records: Vec<DanglingRecord>,
+    pub deleted_files: usize,
+    /// Records added for orphan files
+    pub registered: Vec<GeneratedAudio>,
+    pub removed_records: usize,
+}
+
+/// `path` with symlinks and `..` resolved, or as given when it doesn't exist
+fn resolved(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// A record for a file found in the cache without one
+async fn orphan_record(path: &Path, audio_type: AudioType) -> GeneratedAudio {
+    let metadata = tokio::fs::metadata(path).await.ok();
+    let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+    let modified = metadata.and_then(|m| m.modified().ok()).map(chrono::DateTime::<chrono::Utc>::from);
+    GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type,
+        prompt: String::new(),
+        duration_seconds: path
+            .extension()
+            .and_then(|ext| estimate_duration_seconds(size, &ext.to_string_lossy()))
+            .unwrap_or(0.0),
+        local_path: path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata: serde_json::json!({ RECONCILED_KEY: true }),
+        created_at: modified.unwrap_or_else(chrono::Utc::now).to_rfc3339(),
+        params: None,
+        cached: false,
+    }
+}
+
+/// Compare the cache directory with the records, repairing what `orphans` and
+/// `remove_dangling` ask for
+pub async fn reconcile(
+    state: &ElevenLabsState,
+    orphans: Option<OrphanRepair>,
+    remove_dangling: bool,
+) -> Result<ReconcileReport, AudioError> {
+    let cache = ensure_cache(state).await?;
+    let records = state
+        .call_db(|conn| {
+            let mut records = Vec::new();
+            for audio_type in [AudioType::Tts, AudioType::Sfx, AudioType::Music] {
+                records.extend(AudioCacheDb::get_audio_records(conn, &audio_type)?);
+            }
+            // Files in the trash are still referenced until purged
+            records.extend(AudioCacheDb::get_trashed_records(conn, None)?.into_iter().map(|trashed| trashed.audio));
+            Ok(records)
+        })
+        .await?;
+
+    let mut report = ReconcileReport::default();
+    let mut recorded = HashSet::new();
+    for record in records {
+        let path = Path::new(&record.local_path);
+        if path.is_file() {
+            recorded.insert(resolved(path));
+        } else {
+            report.dangling_records.push(DanglingRecord {
+                id: record.id,
+                audio_type: record.audio_type,
+                prompt: record.prompt,
+                local_path: record.local_path,
+            });
+        }
+    }
+
+    for audio_type in [AudioType::Tts, AudioType::Sfx, AudioType::Music] {
+        for file in cache.list_cached_files(&audio_type).await? {
+            if recorded.contains(&resolved(&file)) {
+                continue;
+            }
+            report.orphan_files.push(file.to_string_lossy().to_string());
+            match orphans {
+                Some(OrphanRepair::Delete) => {
+                    cache.delete_audio(&file).await?;
+                    report.deleted_files += 1;
+                }
+                Some(OrphanRepair::Register) => {
+                    let audio = orphan_record(&file, audio_type.clone()).await;
+                    let record = audio.clone();
+                    state.call_db(move |conn| AudioCacheDb::save_audio_record(conn, &record)).await?;
+                    report.registered.push(audio);
+                }
+                None => {}
+            }
+        }
+    }
+
+    if remove_dangling && !report.dangling_records.is_empty() {
+        let ids: Vec<String> = report.dangling_records.iter().map(|record| record.id.clone()).collect();
+        report.removed_records = state
+            .call_db(move |conn| {
+                for id in &ids {
+                    compare::forget_audio(conn, id)?;
+                    AudioCacheDb::delete_audio_record(conn, id)?;
+                }
+                Ok(ids.len())
+            })
+            .await?;
+    }
+
+    if !report.orphan_files.is_empty() || !report.dangling_records.is_empty() {
+        log::info!(
+            "Audio cache reconciled: {} orphan files, {} dangling records",
+            report.orphan_files.len(),
+            report.dangling_records.len()
+        );
+    }
+    Ok(report)
+}
+
+/// Scan the audio cache against its records, reporting files without a record
+/// and records without a file. Pass `orphans` to delete or register the former
+/// and `remove_dangling` to drop the latter; without either nothing changes.
+#[tauri::command]
+pub async fn reconcile_audio_cache(
+    state: State<'_, Arc<ElevenLabsState>>,
+    orphans: Option<OrphanRepair>,
+    remove_dangling: Option<bool>,
+) -> Result<ReconcileReport, AudioError> {
+    reconcile(&state, orphans, remove_dangling.unwrap_or(false)).await
+}
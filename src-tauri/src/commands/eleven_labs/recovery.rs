@@ -0,0 +1,259 @@
+// Recovery from a corrupted audio database. When a query on the audio tables
+// fails with SQLITE_CORRUPT or SQLITE_NOTADB, the database file is copied
+// aside, whatever rows can still be read are salvaged, and the audio tables
+// are rebuilt from `schema` with those rows, so one bad shutdown doesn't leave
+// the audio features failing for good. Other tables in the app database are
+// left alone. SQLITE_NOTADB from a database that may be encrypted means it is
+// locked, not damaged, and is never recovered from.
+
+use anyhow::{anyhow, Result};
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::SettingsDb;
+use super::encryption;
+use super::error::AudioError;
+use super::migrations;
+use super::schema;
+use super::ElevenLabsState;
+
+/// Settings key holding the report of the last recovery
+pub const AUDIO_DB_RECOVERY_KEY: &str = "audio_db_recovery";
+
+/// How many rows of one table survived
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSalvage {
+    pub table: String,
+    pub rows_salvaged: usize,
+    /// Whether every row could be read; unreadable rows are lost
+    pub complete: bool,
+}
+
+/// What a recovery did, kept for the frontend to show
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// Copy of the damaged database, `None` for in-memory databases
+    pub backup_path: Option<String>,
+    pub tables: Vec<TableSalvage>,
+    pub recovered_at: String,
+}
+
+fn is_corrupt_sqlite(error: &rusqlite::Error) -> bool {
+    match error.sqlite_error_code() {
+        Some(ErrorCode::DatabaseCorrupt) => true,
+        Some(ErrorCode::NotADatabase) => !encryption::is_locked(error),
+        _ => false,
+    }
+}
+
+/// Whether `error` was caused by a damaged database file
+pub fn is_corruption(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| cause.downcast_ref::<rusqlite::Error>().is_some_and(is_corrupt_sqlite))
+}
+
+/// Copy the database file (and its WAL) next to it before anything is changed
+fn back_up(conn: &Connection) -> Result<Option<String>> {
+    let Some(path) = conn.path().filter(|path| !path.is_empty()) else {
+        return Ok(None);
+    };
+    let backup = format!("{}.corrupt-{}", path, chrono::Utc::now().format("%Y%m%d%H%M%S"));
+    std::fs::copy(path, &backup).map_err(|e| anyhow!("Failed to back up {}: {}", path, e))?;
+    let wal = format!("{}-wal", path);
+    if std::path::Path::new(&wal).exists() {
+        std::fs::copy(&wal, format!("{}-wal", backup))?;
+    }
+    Ok(Some(backup))
+}
+
+/// Salvaged rows of one table, by rowid
+struct Rows {
+    columns: Vec<String>,
+    rows: BTreeMap<i64, Vec<Value>>,
+    complete: bool,
+}
+
+/// Read the rows of `table` in rowid order until a damaged page is hit, then
+/// from the other end, so only rows on unreadable pages are lost
+fn salvage(conn: &Connection, table: &str) -> Rows {
+    let mut salvaged = Rows {
+        columns: Vec::new(),
+        rows: BTreeMap::new(),
+        complete: false,
+    };
+
+    let columns = conn
+        .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))
+        .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<Vec<String>>>());
+    match columns {
+        Ok(columns) if !columns.is_empty() => salvaged.columns = columns,
+        _ => return salvaged,
+    }
+
+    salvaged.complete = read_rows(conn, table, "ASC", &mut salvaged).is_ok();
+    if !salvaged.complete {
+        let _ = read_rows(conn, table, "DESC", &mut salvaged);
+    }
+    salvaged
+}
+
+/// Add rows to `salvaged` in the given rowid order, stopping at the first
+/// damaged page or at a row already read
+fn read_rows(conn: &Connection, table: &str, order: &str, salvaged: &mut Rows) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!("SELECT rowid, * FROM {} ORDER BY rowid {}", table, order))?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let rowid: i64 = row.get(0)?;
+        if salvaged.rows.contains_key(&rowid) {
+            break;
+        }
+        let values = (1..=salvaged.columns.len())
+            .map(|i| row.get::<_, Value>(i))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        salvaged.rows.insert(rowid, values);
+    }
+    Ok(())
+}
+
+/// Back up the database, then rebuild the audio tables with every row that
+/// can still be read. The report is also saved for `get_audio_db_recovery`.
+pub fn recover(conn: &mut Connection) -> Result<RecoveryReport> {
+    let backup_path = back_up(conn)?;
+    let salvaged: Vec<(&str, Rows)> = schema::TABLES.iter().map(|table| (*table, salvage(conn, table))).collect();
+
+    let tx = conn.transaction()?;
+    for table in schema::TABLES.iter().rev() {
+        tx.execute_batch(&format!("DROP TABLE IF EXISTS {}", table))
+            .map_err(|e| anyhow!("Failed to drop damaged table {}: {}", table, e))?;
+    }
+    // The prompt index is rebuilt as the rows go back in, and every migration
+    // applied again
+    tx.execute_batch(&format!("DROP TABLE IF EXISTS {}", schema::PROMPT_SEARCH_TABLE))?;
+    tx.execute_batch(&format!("DROP TABLE IF EXISTS {}", migrations::VERSION_TABLE))?;
+    schema::init(&tx)?;
+
+    let mut tables = Vec::with_capacity(salvaged.len());
+    for (table, rows) in salvaged {
+        let mut restored = 0;
+        let insert = tx.prepare(&format!(
+            "INSERT OR IGNORE INTO {} (rowid, {}) VALUES ({})",
+            table,
+            rows.columns.join(", "),
+            vec!["?"; rows.columns.len() + 1].join(", ")
+        ));
+        // Columns that no longer exist or a missing parent table lose the rows
+        if let (false, Ok(mut stmt)) = (rows.columns.is_empty(), insert) {
+            for (rowid, values) in rows.rows {
+                let values = std::iter::once(Value::Integer(rowid)).chain(values);
+                // Rows that no longer fit the schema are dropped with the damaged ones
+                if stmt.execute(params_from_iter(values)).is_ok_and(|inserted| inserted > 0) {
+                    restored += 1;
+                }
+            }
+        }
+        tables.push(TableSalvage {
+            table: table.to_string(),
+            rows_salvaged: restored,
+            complete: rows.complete,
+        });
+    }
+
+    let report = RecoveryReport {
+        backup_path,
+        tables,
+        recovered_at: chrono::Utc::now().to_rfc3339(),
+    };
+    SettingsDb::save_setting(&tx, AUDIO_DB_RECOVERY_KEY, &serde_json::to_string(&report)?)?;
+    tx.commit()?;
+
+    log::error!(
+        "Rebuilt the damaged audio tables (backup: {}): {}",
+        report.backup_path.as_deref().unwrap_or("none"),
+        report
+            .tables
+            .iter()
+            .filter(|table| !table.complete)
+            .map(|table| format!("{} partly lost, {} rows kept", table.table, table.rows_salvaged))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Ok(report)
+}
+
+/// Create the audio tables, rebuilding them if the database is damaged.
+/// Returns whether a rebuild was needed.
+pub fn init_or_recover(conn: &mut Connection) -> Result<bool> {
+    match schema::init(conn) {
+        Ok(()) => Ok(false),
+        Err(e) if encryption::is_locked(&e) => Err(AudioError::NotConfigured(
+            "The app database is encrypted and couldn't be unlocked; check its passphrase in the keyring".to_string(),
+        )
+        .into()),
+        Err(e) if is_corrupt_sqlite(&e) => recover(conn).map(|_| true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// ========== Tauri Commands ==========
+
+/// The report of the last automatic or manual audio database recovery
+#[tauri::command]
+pub async fn get_audio_db_recovery(state: State<'_, Arc<ElevenLabsState>>) -> Result<Option<RecoveryReport>, AudioError> {
+    state
+        .call_db(|conn| {
+            SettingsDb::get_setting(conn, AUDIO_DB_RECOVERY_KEY)?
+                .map(|json| Ok(serde_json::from_str(&json)?))
+                .transpose()
+        })
+        .await
+}
+
+/// Back up and rebuild the audio tables now, salvaging what can be read
+#[tauri::command]
+pub async fn repair_audio_db(state: State<'_, Arc<ElevenLabsState>>) -> Result<RecoveryReport, AudioError> {
+    state.call_db(recover).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recover_keeps_readable_rows() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        SettingsDb::save_setting(&conn, "audio_cache_dir", "/music").unwrap();
+
+        let report = recover(&mut conn).unwrap();
+        assert!(report.backup_path.is_none());
+        let settings = report.tables.iter().find(|t| t.table == "eleven_labs_settings").unwrap();
+        assert_eq!(settings.rows_salvaged, 1);
+        assert!(settings.complete);
+        assert_eq!(SettingsDb::get_setting(&conn, "audio_cache_dir").unwrap().as_deref(), Some("/music"));
+        assert!(SettingsDb::get_setting(&conn, AUDIO_DB_RECOVERY_KEY).unwrap().is_some());
+
+        let corrupt = rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT), None);
+        assert!(is_corruption(&anyhow::Error::new(AudioError::Db(corrupt))));
+        assert!(!is_corruption(&anyhow::Error::new(rusqlite::Error::QueryReturnedNoRows)));
+    }
+
+    #[test]
+    fn test_unreadable_database_is_locked_while_key_unknown() {
+        // No passphrase was loaded in this process, so a file that isn't a
+        // plain database may be encrypted and must be left as it is
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        std::fs::write(&path, vec![0x5a; 4096]).unwrap();
+        let mut conn = Connection::open(&path).unwrap();
+
+        let err = AudioError::from(init_or_recover(&mut conn).unwrap_err());
+        assert_eq!(err.code(), "not_configured");
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0x5a; 4096]);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
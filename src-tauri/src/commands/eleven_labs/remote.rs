@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::cache::SettingsDb;
+use super::s3::S3Storage;
+use super::secrets;
+use super::webdav::WebDavStorage;
+
+/// Settings key holding the S3-compatible backend configuration (secret key
+/// lives in the keyring)
+pub const S3_CONFIG_KEY: &str = "remote_s3";
+
+/// Settings key holding the WebDAV backend configuration (password lives in the keyring)
+pub const WEBDAV_CONFIG_KEY: &str = "remote_webdav";
+
+/// Object storage used to back up and restore the audio library
+#[async_trait]
+pub trait RemoteStorage: Send + Sync {
+    /// Short backend identifier recorded alongside sync state
+    fn backend_name(&self) -> &'static str;
+
+    /// Upload an object, replacing any existing object with the same key
+    async fn put_object(&self, key: &str, data: Bytes) -> Result<()>;
+
+    /// Download an object, returning `None` if it does not exist
+    async fn get_object(&self, key: &str) -> Result<Option<Bytes>>;
+
+    /// List all object keys under a prefix
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>>;
+
+    /// Delete an object (missing objects are not an error)
+    async fn delete_object(&self, key: &str) -> Result<()>;
+}
+
+/// Configuration for an S3-compatible bucket (AWS S3, Cloudflare R2, MinIO, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3Config {
+    /// Endpoint URL, e.g. `https://s3.us-east-1.amazonaws.com` or `http://localhost:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    pub access_key_id: String,
+    /// Optional key prefix so several libraries can share one bucket
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+impl S3Config {
+    /// Keyring entry name for this bucket's secret access key
+    pub fn secret_name(&self) -> String {
+        format!("s3:{}@{}", self.access_key_id, self.endpoint)
+    }
+}
+
+/// The secret key of an S3 configuration saved before it moved to the keyring
+#[derive(Deserialize)]
+struct PlaintextS3Secret {
+    secret_access_key: Option<String>,
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+/// Configuration for a WebDAV server such as Nextcloud or ownCloud
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebDavConfig {
+    /// Base URL, e.g. `https://cloud.example.com/remote.php/dav/files/alice`
+    pub url: String,
+    pub username: String,
+    /// Optional folder below the base URL, e.g. `opcode-library`
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+impl WebDavConfig {
+    /// Keyring entry name for this server's password
+    pub fn secret_name(&self) -> String {
+        format!("webdav:{}@{}", self.username, self.url)
+    }
+}
+
+/// Supported remote backends
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteBackend {
+    S3,
+    WebDav,
+}
+
+impl RemoteBackend {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "s3" => Ok(RemoteBackend::S3),
+            "webdav" => Ok(RemoteBackend::WebDav),
+            other => Err(anyhow!("Unknown remote backend: {}", other)),
+        }
+    }
+}
+
+/// Load the stored configuration for a backend and build its storage client
+pub fn open_remote(conn: &Connection, backend: RemoteBackend) -> Result<Box<dyn RemoteStorage>> {
+    match backend {
+        RemoteBackend::S3 => {
+            let raw = SettingsDb::get_setting(conn, S3_CONFIG_KEY)?
+                .ok_or_else(|| anyhow!("S3 backup is not configured"))?;
+            let config: S3Config = serde_json::from_str(&raw)?;
+            // Move a secret saved with the configuration into the keyring
+            if let Some(secret) = serde_json::from_str::<PlaintextS3Secret>(&raw)?.secret_access_key {
+                secrets::store_secret(&config.secret_name(), &secret)?;
+                SettingsDb::save_setting(conn, S3_CONFIG_KEY, &serde_json::to_string(&config)?)?;
+            }
+            let secret_access_key = secrets::get_secret(&config.secret_name())?
+                .ok_or_else(|| anyhow!("S3 secret access key not found in keyring"))?;
+            Ok(Box::new(S3Storage::new(config, secret_access_key)?))
+        }
+        RemoteBackend::WebDav => {
+            let raw = SettingsDb::get_setting(conn, WEBDAV_CONFIG_KEY)?
+                .ok_or_else(|| anyhow!("WebDAV sync is not configured"))?;
+            let config: WebDavConfig = serde_json::from_str(&raw)?;
+            let password = secrets::get_secret(&config.secret_name())?
+                .ok_or_else(|| anyhow!("WebDAV password not found in keyring"))?;
+            Ok(Box::new(WebDavStorage::new(config, password)?))
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::{AudioCacheDb, SettingsDb, VoiceProfileDb};
+use super::error::AudioError;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Settings key for the billing rate used in cost estimates (USD per 1000 characters)
+pub const COST_RATE_KEY: &str = "report_cost_per_1k_chars";
+
+/// Default rate when none is configured, roughly the Creator plan overage price
+const DEFAULT_COST_PER_1K: f64 = 0.30;
+
+/// Credits ElevenLabs bills per second of generated sound effect
+const SFX_CREDITS_PER_SECOND: f64 = 40.0;
+
+/// Credits billed for a sound effect generated without an explicit duration
+const SFX_CREDITS_AUTO_DURATION: i64 = 200;
+
+/// Which generations to include in a report. All fields are optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportFilter {
+    pub audio_type: Option<String>,
+    pub voice_id: Option<String>,
+    /// Inclusive RFC 3339 lower bound on `created_at`
+    pub from: Option<String>,
+    /// Exclusive RFC 3339 upper bound on `created_at`
+    pub to: Option<String>,
+    pub scene_id: Option<String>,
+}
+
+/// One line of the report
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportRow {
+    pub id: String,
+    pub audio_type: String,
+    pub prompt: String,
+    pub voice_id: Option<String>,
+    pub voice_name: Option<String>,
+    pub duration_seconds: f32,
+    pub characters_billed: i64,
+    pub provider: String,
+    pub cost_estimate: f64,
+    pub created_at: String,
+}
+
+/// Characters (credits) a generation was billed for
+pub fn characters_billed(audio: &GeneratedAudio) -> i64 {
+    match audio.audio_type {
+        AudioType::Tts => audio.text_characters() as i64,
+        AudioType::Sfx if audio.duration_seconds > 0.0 => {
+            (f64::from(audio.duration_seconds) * SFX_CREDITS_PER_SECOND).ceil() as i64
+        }
+        AudioType::Sfx => SFX_CREDITS_AUTO_DURATION,
+        AudioType::Music => 0,
+    }
+}
+
+impl ReportFilter {
+    fn matches(&self, audio: &GeneratedAudio) -> bool {
+        let metadata_str = |key: &str| audio.metadata.get(key).and_then(|v| v.as_str());
+
+        self.voice_id.as_deref().is_none_or(|v| audio.voice_id() == Some(v))
+            && self.scene_id.as_deref().is_none_or(|s| metadata_str("scene_id") == Some(s))
+            && self.from.as_deref().is_none_or(|from| audio.created_at.as_str() >= from)
+            && self.to.as_deref().is_none_or(|to| audio.created_at.as_str() < to)
+    }
+}
+
+/// Collect report rows for the records matching `filter`, oldest first
+pub fn build_report(conn: &rusqlite::Connection, filter: &ReportFilter) -> anyhow::Result<Vec<ReportRow>> {
+    let types = match &filter.audio_type {
+        Some(audio_type) => vec![AudioType::parse(audio_type).map_err(anyhow::Error::msg)?],
+        None => vec![AudioType::Tts, AudioType::Sfx, AudioType::Music],
+    };
+
+    let rate = SettingsDb::get_setting(conn, COST_RATE_KEY)?
+        .and_then(|value| value.parse::<f64>().ok())
+        .unwrap_or(DEFAULT_COST_PER_1K);
+
+    let voice_names: HashMap<String, String> = VoiceProfileDb::get_voice_profiles(conn)?
+        .into_iter()
+        .map(|voice| (voice.voice_id, voice.name))
+        .collect();
+
+    let mut rows = vec![];
+    for audio_type in &types {
+        for audio in AudioCacheDb::get_audio_records(conn, audio_type)? {
+            if !filter.matches(&audio) {
+                continue;
+            }
+
+            let voice_id = audio.voice_id().map(String::from);
+            let billed = characters_billed(&audio);
+
+            rows.push(ReportRow {
+                voice_name: voice_id.as_ref().and_then(|id| voice_names.get(id).cloned()),
+                voice_id,
+                audio_type: serde_json::to_value(&audio.audio_type)?
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                prompt: audio.prompt,
+                duration_seconds: audio.duration_seconds,
+                characters_billed: billed,
+                provider: audio
+                    .params
+                    .as_ref()
+                    .map_or(PROVIDER_ELEVENLABS, |params| params.provider.as_str())
+                    .to_string(),
+                cost_estimate: (billed as f64 / 1000.0 * rate * 10000.0).round() / 10000.0,
+                created_at: audio.created_at,
+                id: audio.id,
+            });
+        }
+    }
+
+    rows.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(rows)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn render_csv(rows: &[ReportRow]) -> String {
+    let mut out = String::from(
+        "id,audio_type,prompt,voice_id,voice_name,duration_seconds,characters_billed,provider,cost_estimate,created_at\n",
+    );
+    for row in rows {
+        let fields = [
+            csv_field(&row.id),
+            csv_field(&row.audio_type),
+            csv_field(&row.prompt),
+            csv_field(row.voice_id.as_deref().unwrap_or("")),
+            csv_field(row.voice_name.as_deref().unwrap_or("")),
+            format!("{:.2}", row.duration_seconds),
+            row.characters_billed.to_string(),
+            csv_field(&row.provider),
+            format!("{:.4}", row.cost_estimate),
+            csv_field(&row.created_at),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+// ========== Tauri Commands ==========
+
+/// Produce a CSV or JSON report of generations for invoicing and usage audits.
+/// The report is returned, and also written to `destination` when given.
+#[tauri::command]
+pub async fn export_library_report(
+    state: State<'_, Arc<ElevenLabsState>>,
+    filter: Option<ReportFilter>,
+    format: String,
+    destination: Option<String>,
+) -> Result<String, AudioError> {
+    let filter = filter.unwrap_or_default();
+
+    let (rows, filter) = state.call_db(move |conn| Ok((build_report(conn, &filter)?, filter))).await?;
+
+    let report = match format.to_lowercase().as_str() {
+        "csv" => render_csv(&rows),
+        "json" => serde_json::to_string_pretty(&serde_json::json!({
+            "generated_at": chrono::Utc::now().to_rfc3339(),
+            "filter": filter,
+            "total_characters_billed": rows.iter().map(|r| r.characters_billed).sum::<i64>(),
+            "total_cost_estimate": rows.iter().map(|r| r.cost_estimate).sum::<f64>(),
+            "rows": rows,
+        }))?,
+        other => return Err(AudioError::Validation(format!("Unsupported report format: {}", other))),
+    };
+
+    if let Some(destination) = destination {
+        tokio::fs::write(&destination, &report)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", destination, e))?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_escaping() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,384 @@
+// Tables used by the audio subsystem, created in one place for the app database
+// (`agents::init_database`), standalone entrypoints that open it through
+// `AudioDb::open`, and tests working on an in-memory connection. `SCHEMA` is
+// the first version of the tables; later changes are steps in `migrations`.
+
+use rusqlite::Connection;
+
+use super::migrations;
+
+/// Tables created by `init`, parents before the tables referencing them
+pub const TABLES: &[&str] = &[
+    "voice_profiles",
+    "character_voices",
+    "agent_voices",
+    "audio_cache",
+    "audio_tags",
+    "eleven_labs_usage",
+    "eleven_labs_settings",
+    "audio_sync_state",
+    "voice_clone_sources",
+    "audio_project_documents",
+    "sync_revisions",
+    "sync_conflicts",
+    "event_sounds",
+    "voice_usage",
+    "voice_favorites",
+    "voice_collections",
+    "voice_collection_members",
+    "usage_history",
+    "audio_comparisons",
+    "voice_presets",
+    "transcriptions",
+    "dubbing_jobs",
+];
+
+pub const SCHEMA: &str = "
+    -- Local cache of Eleven Labs voices
+    CREATE TABLE IF NOT EXISTS voice_profiles (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT,
+        category TEXT NOT NULL DEFAULT 'premade',
+        provider TEXT NOT NULL DEFAULT 'elevenlabs',
+        provider_voice_id TEXT,
+        labels TEXT,
+        preview_url TEXT,
+        settings_stability REAL DEFAULT 0.5,
+        settings_similarity_boost REAL DEFAULT 0.75,
+        settings_style REAL DEFAULT 0.0,
+        settings_use_speaker_boost INTEGER DEFAULT 1,
+        is_synced INTEGER DEFAULT 0,
+        local_audio_path TEXT,
+        supabase_id TEXT,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    -- Character casting, global (project_id NULL) or per project
+    CREATE TABLE IF NOT EXISTS character_voices (
+        id TEXT PRIMARY KEY,
+        character_name TEXT NOT NULL,
+        voice_id TEXT NOT NULL,
+        voice_name TEXT NOT NULL,
+        project_id TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (character_name, project_id)
+    );
+    CREATE INDEX IF NOT EXISTS idx_character_voices_lookup
+        ON character_voices (character_name COLLATE NOCASE, project_id);
+
+    -- Voice used for each agent's narration and spoken alerts
+    CREATE TABLE IF NOT EXISTS agent_voices (
+        id TEXT PRIMARY KEY,
+        agent_id INTEGER NOT NULL UNIQUE,
+        voice_id TEXT NOT NULL,
+        voice_name TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        FOREIGN KEY (agent_id) REFERENCES agents(id) ON DELETE CASCADE
+    );
+
+    -- Generated audio (TTS, SFX, music) and where its file lives
+    CREATE TABLE IF NOT EXISTS audio_cache (
+        id TEXT PRIMARY KEY,
+        audio_type TEXT NOT NULL,
+        prompt TEXT NOT NULL DEFAULT '',
+        duration_seconds REAL NOT NULL DEFAULT 0,
+        local_path TEXT NOT NULL,
+        supabase_url TEXT,
+        metadata TEXT NOT NULL DEFAULT '{}',
+        created_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_audio_cache_type_created
+        ON audio_cache (audio_type, created_at DESC);
+
+    -- Eleven Labs API usage tracking
+    CREATE TABLE IF NOT EXISTS eleven_labs_usage (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        operation TEXT NOT NULL,
+        voice_id TEXT,
+        characters_used INTEGER,
+        cost_usd REAL,
+        request_timestamp TEXT NOT NULL,
+        response_time_ms INTEGER,
+        success INTEGER DEFAULT 1,
+        error_message TEXT,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    -- Audio settings (API key, feature configs)
+    CREATE TABLE IF NOT EXISTS eleven_labs_settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    -- Remote backup state (which audio files have been uploaded to which backend)
+    CREATE TABLE IF NOT EXISTS audio_sync_state (
+        audio_id TEXT NOT NULL,
+        backend TEXT NOT NULL,
+        content_hash TEXT NOT NULL,
+        synced_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (audio_id, backend)
+    );
+
+    -- Provenance of audio samples imported for voice cloning
+    CREATE TABLE IF NOT EXISTS voice_clone_sources (
+        id TEXT PRIMARY KEY,
+        voice_name TEXT NOT NULL,
+        voice_id TEXT,
+        original_path TEXT NOT NULL,
+        managed_path TEXT NOT NULL UNIQUE,
+        content_hash TEXT NOT NULL,
+        size_bytes INTEGER NOT NULL,
+        imported_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    -- Per-project scripts, pronunciation glossaries and pacing profiles
+    CREATE TABLE IF NOT EXISTS audio_project_documents (
+        project_id TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        name TEXT NOT NULL,
+        content TEXT NOT NULL,
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (project_id, kind, name)
+    );
+
+    -- Revision vectors of synced audio and casting records
+    CREATE TABLE IF NOT EXISTS sync_revisions (
+        kind TEXT NOT NULL,
+        record_id TEXT NOT NULL,
+        revision TEXT NOT NULL,
+        fingerprint TEXT NOT NULL,
+        PRIMARY KEY (kind, record_id)
+    );
+
+    -- Records modified concurrently on two machines, awaiting resolution
+    CREATE TABLE IF NOT EXISTS sync_conflicts (
+        id TEXT PRIMARY KEY,
+        kind TEXT NOT NULL,
+        record_id TEXT NOT NULL,
+        backend TEXT NOT NULL,
+        local TEXT NOT NULL,
+        remote TEXT NOT NULL,
+        local_revision TEXT NOT NULL,
+        remote_revision TEXT NOT NULL,
+        detected_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (kind, record_id, backend)
+    );
+
+    -- Sound effects played on agent lifecycle events
+    CREATE TABLE IF NOT EXISTS event_sounds (
+        event TEXT PRIMARY KEY,
+        audio_id TEXT NOT NULL,
+        enabled INTEGER NOT NULL DEFAULT 1,
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    -- How often and how recently each voice was used, for the voice picker
+    CREATE TABLE IF NOT EXISTS voice_usage (
+        voice_id TEXT PRIMARY KEY,
+        use_count INTEGER NOT NULL DEFAULT 0,
+        last_used_at TEXT NOT NULL
+    );
+
+    -- Voices the user marked as favorites
+    CREATE TABLE IF NOT EXISTS voice_favorites (
+        voice_id TEXT PRIMARY KEY,
+        created_at TEXT NOT NULL
+    );
+
+    -- User-defined groups of voices, separate from the provider's categories
+    CREATE TABLE IF NOT EXISTS voice_collections (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        created_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS voice_collection_members (
+        collection_id TEXT NOT NULL,
+        voice_id TEXT NOT NULL,
+        added_at TEXT NOT NULL,
+        PRIMARY KEY (collection_id, voice_id),
+        FOREIGN KEY (collection_id) REFERENCES voice_collections(id) ON DELETE CASCADE
+    );
+
+    -- Characters and credits consumed per local day, provider and audio type
+    CREATE TABLE IF NOT EXISTS usage_history (
+        day TEXT NOT NULL,
+        provider TEXT NOT NULL,
+        audio_type TEXT NOT NULL,
+        generations INTEGER NOT NULL DEFAULT 0,
+        characters INTEGER NOT NULL DEFAULT 0,
+        credits INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (day, provider, audio_type)
+    );
+
+    -- Pairs of renders made to compare two voice settings variants
+    CREATE TABLE IF NOT EXISTS audio_comparisons (
+        id TEXT PRIMARY KEY,
+        audio_a_id TEXT NOT NULL,
+        audio_b_id TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+
+    -- Named voice settings, applied by name to requests and character castings
+    CREATE TABLE IF NOT EXISTS voice_presets (
+        name TEXT PRIMARY KEY COLLATE NOCASE,
+        settings TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+
+    -- Speech-to-text results of local audio files, with word timings as JSON
+    CREATE TABLE IF NOT EXISTS transcriptions (
+        id TEXT PRIMARY KEY,
+        source_path TEXT NOT NULL,
+        project_id TEXT,
+        language_code TEXT,
+        text TEXT NOT NULL,
+        words TEXT NOT NULL DEFAULT '[]',
+        created_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_transcriptions_project
+        ON transcriptions (project_id, created_at DESC);
+
+    -- Files submitted for dubbing, polled until the dub is downloaded
+    CREATE TABLE IF NOT EXISTS dubbing_jobs (
+        id TEXT PRIMARY KEY,
+        dubbing_id TEXT NOT NULL,
+        source_path TEXT NOT NULL,
+        source_lang TEXT,
+        target_lang TEXT NOT NULL,
+        status TEXT NOT NULL,
+        error TEXT,
+        expected_duration_seconds REAL,
+        audio_id TEXT,
+        project_id TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+
+    CREATE TRIGGER IF NOT EXISTS update_voice_profiles_timestamp
+    AFTER UPDATE ON voice_profiles
+    FOR EACH ROW
+    BEGIN
+        UPDATE voice_profiles SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS update_character_voices_timestamp
+    AFTER UPDATE ON character_voices
+    FOR EACH ROW
+    BEGIN
+        UPDATE character_voices SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS update_eleven_labs_settings_timestamp
+    AFTER UPDATE ON eleven_labs_settings
+    FOR EACH ROW
+    BEGIN
+        UPDATE eleven_labs_settings SET updated_at = CURRENT_TIMESTAMP WHERE key = NEW.key;
+    END;
+";
+
+/// Full-text index over `audio_cache.prompt`, kept in step by triggers. It
+/// stores no text of its own (`content='audio_cache'`).
+pub const PROMPT_SEARCH_TABLE: &str = "audio_prompt_fts";
+
+pub const PROMPT_SEARCH: &str = "
+    CREATE VIRTUAL TABLE IF NOT EXISTS audio_prompt_fts
+        USING fts5(prompt, content = 'audio_cache', content_rowid = 'rowid');
+
+    CREATE TRIGGER IF NOT EXISTS audio_prompt_fts_insert
+    AFTER INSERT ON audio_cache
+    BEGIN
+        INSERT INTO audio_prompt_fts (rowid, prompt) VALUES (NEW.rowid, NEW.prompt);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS audio_prompt_fts_delete
+    AFTER DELETE ON audio_cache
+    BEGIN
+        INSERT INTO audio_prompt_fts (audio_prompt_fts, rowid, prompt) VALUES ('delete', OLD.rowid, OLD.prompt);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS audio_prompt_fts_update
+    AFTER UPDATE OF prompt ON audio_cache
+    BEGIN
+        INSERT INTO audio_prompt_fts (audio_prompt_fts, rowid, prompt) VALUES ('delete', OLD.rowid, OLD.prompt);
+        INSERT INTO audio_prompt_fts (rowid, prompt) VALUES (NEW.rowid, NEW.prompt);
+    END;
+";
+
+/// Create the audio tables, or bring them up to date by applying the
+/// migrations the database hasn't had yet
+pub fn init(conn: &Connection) -> rusqlite::Result<()> {
+    migrations::migrate(conn)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_is_idempotent() {
+        let conn = Connection::open_in_memory().unwrap();
+        init(&conn).unwrap();
+        init(&conn).unwrap();
+        let tables: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'audio_cache'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tables, 1);
+
+        // The prompt index and its shadow tables are created alongside
+        let created: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master
+                 WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name NOT LIKE 'audio_prompt_fts%'
+                   AND name != 'audio_schema_version'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(created as usize, TABLES.len());
+    }
+
+    #[test]
+    fn test_init_indexes_existing_prompts() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute(
+            "INSERT INTO audio_cache (id, audio_type, prompt, local_path, created_at)
+             VALUES ('a', '\"sfx\"', 'Thunder rolling', '/a.mp3', '2024-01-01')",
+            [],
+        )
+        .unwrap();
+        init(&conn).unwrap();
+        let search = |term: &str| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM audio_prompt_fts WHERE audio_prompt_fts MATCH ?1", [term], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(search("thunder"), 1);
+
+        conn.execute("UPDATE audio_cache SET prompt = 'Rain' WHERE id = 'a'", []).unwrap();
+        assert_eq!((search("thunder"), search("rain")), (0, 1));
+        conn.execute("DELETE FROM audio_cache", []).unwrap();
+        assert_eq!(search("rain"), 0);
+    }
+
+    #[test]
+    fn test_init_adds_params_column() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE audio_cache (
+                id TEXT PRIMARY KEY, audio_type TEXT NOT NULL, prompt TEXT NOT NULL DEFAULT '',
+                duration_seconds REAL NOT NULL DEFAULT 0, local_path TEXT NOT NULL, supabase_url TEXT,
+                metadata TEXT NOT NULL DEFAULT '{}', created_at TEXT NOT NULL
+            );",
+        )
+        .unwrap();
+        init(&conn).unwrap();
+        conn.execute("UPDATE audio_cache SET params = NULL, request_hash = NULL, favorite = 1, deleted_at = NULL", []).unwrap();
+    }
+}
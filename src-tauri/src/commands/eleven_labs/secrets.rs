@@ -0,0 +1,34 @@
+use anyhow::{anyhow, Result};
+
+/// Service name under which opcode stores credentials in the OS keyring
+const KEYRING_SERVICE: &str = "opcode";
+
+/// Store a secret in the OS keyring (Keychain, Credential Manager, Secret Service)
+pub fn store_secret(name: &str, value: &str) -> Result<()> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, name)
+        .map_err(|e| anyhow!("Failed to open keyring entry: {}", e))?;
+    entry
+        .set_password(value)
+        .map_err(|e| anyhow!("Failed to store secret in keyring: {}", e))
+}
+
+/// Read a secret from the OS keyring, returning `None` if it has not been stored
+pub fn get_secret(name: &str) -> Result<Option<String>> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, name)
+        .map_err(|e| anyhow!("Failed to open keyring entry: {}", e))?;
+    match entry.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(anyhow!("Failed to read secret from keyring: {}", e)),
+    }
+}
+
+/// Remove a secret from the OS keyring (missing entries are not an error)
+pub fn delete_secret(name: &str) -> Result<()> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, name)
+        .map_err(|e| anyhow!("Failed to open keyring entry: {}", e))?;
+    match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(anyhow!("Failed to delete secret from keyring: {}", e)),
+    }
+}
@@ -0,0 +1,499 @@
+// Reading stored Claude Code sessions aloud. Sessions are the JSONL
+// transcripts under ~/.claude/projects, one file per session id.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::State;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::cache::{AudioCacheDb, AudioDb};
+use super::error::AudioError;
+use super::narration::{chunk_sentences, speakable_text, split_sentences};
+use super::pipeline;
+use super::types::*;
+use super::{ensure_cache, ElevenLabsState};
+
+/// Find a session transcript in any project directory
+fn session_file(session_id: &str) -> Result<PathBuf, AudioError> {
+    let projects_dir = dirs::home_dir()
+        .ok_or("Failed to get home directory")?
+        .join(".claude")
+        .join("projects");
+
+    let file_name = format!("{}.jsonl", session_id);
+    let entries = std::fs::read_dir(&projects_dir)
+        .map_err(|e| format!("Failed to read projects directory: {}", e))?;
+    entries
+        .flatten()
+        .map(|entry| entry.path().join(&file_name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| AudioError::Validation(format!("Session file not found: {}", session_id)))
+}
+
+/// Load every entry of a session transcript, skipping lines that aren't JSON
+pub fn load_session(session_id: &str) -> Result<Vec<serde_json::Value>, AudioError> {
+    let content = std::fs::read_to_string(session_file(session_id)?)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The text of a user or assistant entry. Tool calls and tool results are
+/// left out, so entries holding only those have no text.
+pub fn message_text(entry: &serde_json::Value) -> Option<String> {
+    let content = entry.pointer("/message/content")?;
+    if let Some(text) = content.as_str() {
+        return (!text.trim().is_empty()).then(|| text.to_string());
+    }
+
+    let text: Vec<&str> = content
+        .as_array()?
+        .iter()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+        .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+        .collect();
+    (!text.is_empty()).then(|| text.join("\n\n"))
+}
+
+/// The agent and project of the run that produced a session; empty for
+/// interactive sessions
+async fn session_voice_context(db: &AudioDb, session_id: &str) -> Result<VoiceContext, AudioError> {
+    let session_id = session_id.to_string();
+    db.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT agent_id, project_path FROM agent_runs WHERE session_id = ?1 ORDER BY id DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query([&session_id])?;
+        match rows.next()? {
+            Some(row) => Ok(VoiceContext {
+                agent_id: Some(row.get(0)?),
+                project_id: Some(row.get(1)?),
+                ..Default::default()
+            }),
+            None => Ok(VoiceContext::default()),
+        }
+    })
+    .await
+}
+
+/// The voice of the agent that ran the session, or the narrator for
+/// interactive sessions
+pub async fn session_voice_id(db: &AudioDb, session_id: &str) -> Result<String, AudioError> {
+    let context = session_voice_context(db, session_id).await?;
+    Ok(pipeline::resolve_voice(db, &context).await?.voice_id)
+}
+
+/// Longest text sent in a single replay take
+const REPLAY_CHUNK_CHARS: usize = 2500;
+
+/// Who is speaking a line of a session replay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speaker {
+    User,
+    Assistant,
+}
+
+impl Speaker {
+    /// Character name used for the take and for voice casting
+    fn character(self) -> &'static str {
+        match self {
+            Speaker::User => "User",
+            Speaker::Assistant => "Assistant",
+        }
+    }
+}
+
+/// The spoken lines of a session in order. Meta entries, sub-agent
+/// (sidechain) traffic and messages without text are skipped, and long
+/// messages are split into several lines.
+pub fn replay_lines(entries: &[serde_json::Value]) -> Vec<(Speaker, String)> {
+    let flag = |entry: &serde_json::Value, key: &str| entry.get(key).and_then(|v| v.as_bool()) == Some(true);
+
+    let mut lines = vec![];
+    for entry in entries {
+        let speaker = match entry.get("type").and_then(|t| t.as_str()) {
+            Some("user") => Speaker::User,
+            Some("assistant") => Speaker::Assistant,
+            _ => continue,
+        };
+        if flag(entry, "isMeta") || flag(entry, "isSidechain") {
+            continue;
+        }
+        let Some(text) = message_text(entry) else { continue };
+
+        let sentences = split_sentences(&speakable_text(&text));
+        for chunk in chunk_sentences(&sentences, REPLAY_CHUNK_CHARS) {
+            lines.push((speaker, chunk));
+        }
+    }
+    lines
+}
+
+/// The voice cast as "User" for the session's project, falling back to the narrator
+async fn user_voice(db: &AudioDb, session_id: &str) -> Result<ResolvedVoice, AudioError> {
+    let context = VoiceContext {
+        agent_id: None,
+        character: Some(Speaker::User.character().to_string()),
+        ..session_voice_context(db, session_id).await?
+    };
+    pipeline::resolve_voice(db, &context).await
+}
+
+/// Render a session replay, returning the joined recap and its takes in order
+async fn render_replay(
+    state: &ElevenLabsState,
+    session_id: &str,
+) -> Result<(GeneratedAudio, Vec<GeneratedAudio>), AudioError> {
+    let lines = replay_lines(&load_session(session_id)?);
+    if lines.is_empty() {
+        return Err(AudioError::Validation("Session has no messages to narrate".to_string()));
+    }
+
+    let db = state.db()?;
+    let user_voice = user_voice(&db, session_id).await?;
+    let assistant_voice = pipeline::resolve_voice(&db, &session_voice_context(&db, session_id).await?).await?;
+    let scene_id = format!("session-{}", session_id);
+
+    let mut takes = vec![];
+    let mut recap = vec![];
+    for (index, (speaker, text)) in lines.into_iter().enumerate() {
+        let voice = match speaker {
+            Speaker::User => &user_voice,
+            Speaker::Assistant => &assistant_voice,
+        };
+        let request = TtsRequest {
+            text,
+            voice_id: voice.voice_id.clone(),
+            model_id: default_model_id(),
+            voice_settings: None,
+            output_format: default_output_format(),
+            normalize_text: None,
+            preset: voice.preset.clone(),
+            previous_text: None,
+            next_text: None,
+            previous_request_ids: Vec::new(),
+            seed: None,
+        };
+        let mut audio = pipeline::generate_tts(state, request).await?;
+
+        audio.metadata["scene_id"] = serde_json::json!(scene_id);
+        audio.metadata["line"] = serde_json::json!(index + 1);
+        audio.metadata["character"] = serde_json::json!(speaker.character());
+        audio.metadata["session_id"] = serde_json::json!(session_id);
+        let record = audio.clone();
+        db.call(move |conn| AudioCacheDb::save_audio_record(conn, &record)).await?;
+
+        // MP3 streams are sequences of self-contained frames, so takes join by concatenation
+        let data = tokio::fs::read(&audio.local_path)
+            .await
+            .map_err(|e| format!("Failed to read take {}: {}", audio.id, e))?;
+        recap.extend_from_slice(&data);
+        takes.push(audio);
+    }
+
+    let cache = ensure_cache(state).await?;
+    let path = cache
+        .save_audio(&AudioType::Tts, &recap, "mp3")
+        .await?;
+
+    let audio = GeneratedAudio {
+        id: uuid::Uuid::new_v4().to_string(),
+        audio_type: AudioType::Tts,
+        prompt: format!("Session replay {}", session_id),
+        duration_seconds: takes.iter().map(|take| take.duration_seconds).sum(),
+        local_path: path.to_string_lossy().to_string(),
+        supabase_url: None,
+        metadata: serde_json::json!({
+            "session_id": session_id,
+            "session_replay": true,
+            "takes": takes.iter().map(|take| &take.id).collect::<Vec<_>>(),
+        }),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        params: None,
+        cached: false,
+    };
+
+    let record = audio.clone();
+    db.call(move |conn| AudioCacheDb::save_audio_record(conn, &record)).await?;
+    Ok((audio, takes))
+}
+
+/// The most recent replay of a session whose files are all still on disk
+async fn latest_replay(
+    db: &AudioDb,
+    session_id: String,
+) -> Result<Option<(GeneratedAudio, Vec<GeneratedAudio>)>, AudioError> {
+    db.call(move |conn| {
+        let replay = AudioCacheDb::get_audio_records(conn, &AudioType::Tts)?
+            .into_iter()
+            .filter(|audio| {
+                audio.metadata["session_replay"].as_bool() == Some(true)
+                    && audio.metadata["session_id"].as_str() == Some(session_id.as_str())
+            })
+            .max_by(|a, b| a.created_at.cmp(&b.created_at));
+        let Some(replay) = replay else {
+            return Ok(None);
+        };
+
+        let mut takes = vec![];
+        for id in replay.metadata["takes"].as_array().into_iter().flatten() {
+            let take = id
+                .as_str()
+                .map(|id| AudioCacheDb::get_audio_record(conn, id))
+                .transpose()?
+                .flatten();
+            match take {
+                Some(take) if Path::new(&take.local_path).exists() => takes.push(take),
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(Path::new(&replay.local_path).exists().then_some((replay, takes)))
+    })
+    .await
+}
+
+/// A line of the exported transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptLine {
+    pub speaker: String,
+    pub start_seconds: f32,
+    pub end_seconds: f32,
+    pub text: String,
+}
+
+/// Lay the takes end to end on the replay timeline
+fn transcript_lines(takes: &[GeneratedAudio]) -> Vec<TranscriptLine> {
+    let mut start = 0.0;
+    takes
+        .iter()
+        .map(|take| {
+            let line = TranscriptLine {
+                speaker: take.metadata["character"].as_str().unwrap_or("Narrator").to_string(),
+                start_seconds: start,
+                end_seconds: start + take.duration_seconds,
+                text: take.prompt.clone(),
+            };
+            start = line.end_seconds;
+            line
+        })
+        .collect()
+}
+
+/// `HH:MM:SS,mmm` as used by SRT cues
+fn srt_timestamp(seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Render transcript lines as SRT, prefixing each cue with its speaker
+pub fn srt_transcript(lines: &[TranscriptLine]) -> String {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            format!(
+                "{}\n{} --> {}\n{}: {}\n",
+                index + 1,
+                srt_timestamp(line.start_seconds),
+                srt_timestamp(line.end_seconds),
+                line.speaker,
+                line.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Written as `metadata.json` in a session audio export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionAudioMetadata {
+    pub session_id: String,
+    pub replay_audio_id: String,
+    pub app_version: String,
+    pub exported_at: String,
+    pub duration_seconds: f32,
+    pub lines: Vec<TranscriptLine>,
+}
+
+/// Summary returned by `export_session_audio`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionAudioExport {
+    pub path: String,
+    pub duration_seconds: f32,
+    pub line_count: usize,
+}
+
+fn write_session_bundle(dest: &Path, replay: &GeneratedAudio, metadata: &SessionAudioMetadata) -> anyhow::Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = ZipWriter::new(File::create(dest)?);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("replay.mp3", stored)?;
+    zip.write_all(&std::fs::read(&replay.local_path)?)?;
+    zip.start_file("transcript.srt", deflated)?;
+    zip.write_all(srt_transcript(&metadata.lines).as_bytes())?;
+    zip.start_file("metadata.json", deflated)?;
+    zip.write_all(&serde_json::to_vec_pretty(metadata)?)?;
+    zip.finish()?;
+    Ok(())
+}
+
+// ========== Tauri Commands ==========
+
+/// Render one message of a stored session with the session's voice
+#[tauri::command]
+pub async fn speak_session_message(
+    state: State<'_, Arc<ElevenLabsState>>,
+    session_id: String,
+    message_id: String,
+) -> Result<GeneratedAudio, AudioError> {
+    let entries = load_session(&session_id)?;
+    let entry = entries
+        .iter()
+        .find(|entry| entry.get("uuid").and_then(|id| id.as_str()) == Some(message_id.as_str()))
+        .ok_or_else(|| format!("Message not found: {}", message_id))?;
+
+    let text = message_text(entry)
+        .map(|text| speakable_text(&text))
+        .filter(|text| !text.trim().is_empty())
+        .ok_or("Message has no text to speak")?;
+
+    let db = state.db()?;
+    let request = TtsRequest {
+        text,
+        voice_id: session_voice_id(&db, &session_id).await?,
+        model_id: default_model_id(),
+        voice_settings: None,
+        output_format: default_output_format(),
+        normalize_text: None,
+        preset: None,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
+    };
+    let mut audio = pipeline::generate_tts(&state, request).await?;
+
+    // Link the clip back to the message so the UI can offer replay instead of re-rendering
+    audio.metadata["session_id"] = serde_json::json!(session_id);
+    audio.metadata["message_id"] = serde_json::json!(message_id);
+    let record = audio.clone();
+    db.call(move |conn| AudioCacheDb::save_audio_record(conn, &record)).await?;
+    Ok(audio)
+}
+
+/// Render a whole session as a two-voice replay. Each line is saved as a take
+/// tagged with the scene `session-<id>` (so it can also be exported to a DAW),
+/// and the takes are joined into one recap file.
+#[tauri::command]
+pub async fn narrate_session(
+    state: State<'_, Arc<ElevenLabsState>>,
+    session_id: String,
+) -> Result<GeneratedAudio, AudioError> {
+    render_replay(&state, &session_id).await.map(|(replay, _)| replay)
+}
+
+/// Export a session as a zip holding the narrated replay, an SRT transcript and
+/// a metadata JSON. The latest replay is reused; the session is narrated if it
+/// has none.
+#[tauri::command]
+pub async fn export_session_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
+    session_id: String,
+    dest: String,
+) -> Result<SessionAudioExport, AudioError> {
+    let (replay, takes) = match latest_replay(&state.db()?, session_id.clone()).await? {
+        Some(replay) => replay,
+        None => render_replay(&state, &session_id).await?,
+    };
+
+    let metadata = SessionAudioMetadata {
+        session_id,
+        replay_audio_id: replay.id.clone(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        duration_seconds: replay.duration_seconds,
+        lines: transcript_lines(&takes),
+    };
+    write_session_bundle(Path::new(&dest), &replay, &metadata)
+        .map_err(|e| format!("Failed to write {}: {}", dest, e))?;
+
+    Ok(SessionAudioExport {
+        path: dest,
+        duration_seconds: metadata.duration_seconds,
+        line_count: metadata.lines.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_text() {
+        let assistant = serde_json::json!({
+            "type": "assistant",
+            "message": { "content": [
+                { "type": "text", "text": "Fixed it." },
+                { "type": "tool_use", "name": "Edit", "input": {} },
+                { "type": "text", "text": "Run the tests." }
+            ]}
+        });
+        assert_eq!(message_text(&assistant).as_deref(), Some("Fixed it.\n\nRun the tests."));
+
+        let user = serde_json::json!({ "type": "user", "message": { "content": "Why?" } });
+        assert_eq!(message_text(&user).as_deref(), Some("Why?"));
+
+        let tool_result = serde_json::json!({
+            "type": "user",
+            "message": { "content": [{ "type": "tool_result", "content": "ok" }] }
+        });
+        assert_eq!(message_text(&tool_result), None);
+    }
+
+    #[test]
+    fn test_replay_lines() {
+        let entries = vec![
+            serde_json::json!({ "type": "summary", "summary": "Fix tests" }),
+            serde_json::json!({ "type": "user", "isMeta": true, "message": { "content": "<command-name>" } }),
+            serde_json::json!({ "type": "user", "message": { "content": "Fix the **tests**." } }),
+            serde_json::json!({ "type": "assistant", "isSidechain": true, "message": { "content": "Searching." } }),
+            serde_json::json!({ "type": "assistant", "message": { "content": [{ "type": "text", "text": "Done." }] } }),
+        ];
+        assert_eq!(
+            replay_lines(&entries),
+            vec![
+                (Speaker::User, "Fix the tests.".to_string()),
+                (Speaker::Assistant, "Done.".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_srt_transcript() {
+        let lines = vec![
+            TranscriptLine { speaker: "User".into(), start_seconds: 0.0, end_seconds: 1.5, text: "Fix it.".into() },
+            TranscriptLine { speaker: "Assistant".into(), start_seconds: 1.5, end_seconds: 3725.25, text: "Done.".into() },
+        ];
+        assert_eq!(
+            srt_transcript(&lines),
+            "1\n00:00:00,000 --> 00:00:01,500\nUser: Fix it.\n\n2\n00:00:01,500 --> 01:02:05,250\nAssistant: Done.\n"
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::State;
+
+use super::error::AudioError;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Audio formats accepted by the voice cloning endpoint
+const ALLOWED_EXTENSIONS: &[&str] = &["mp3", "wav", "m4a", "flac", "ogg", "webm"];
+
+/// Largest recording accepted for import. Samples over the upload limit are
+/// split before cloning (see `clone_samples`).
+const MAX_SOURCE_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Root of the managed clone source directory
+pub fn sources_root() -> Result<PathBuf> {
+    Ok(dirs::data_dir()
+        .ok_or_else(|| anyhow!("Could not find data directory"))?
+        .join("opcode")
+        .join("voice_sources"))
+}
+
+/// Turn a voice name into a safe directory name
+fn voice_dir_name(voice_name: &str) -> String {
+    let slug: String = voice_name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    let slug = slug.trim_matches('_').to_string();
+    if slug.is_empty() { "voice".to_string() } else { slug }
+}
+
+/// Check that a dropped file is a readable audio sample within the upload limits
+fn validate_source(path: &Path) -> Result<(String, u64)> {
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| anyhow!("Cannot read {}: {}", path.display(), e))?;
+    if !metadata.is_file() {
+        return Err(anyhow!("{} is not a file", path.display()));
+    }
+    if metadata.len() == 0 {
+        return Err(anyhow!("{} is empty", path.display()));
+    }
+    if metadata.len() > MAX_SOURCE_BYTES {
+        return Err(anyhow!(
+            "{} is larger than {} MB",
+            path.display(),
+            MAX_SOURCE_BYTES / 1024 / 1024
+        ));
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .filter(|e| ALLOWED_EXTENSIONS.contains(&e.as_str()))
+        .ok_or_else(|| anyhow!("{} is not a supported audio file", path.display()))?;
+
+    Ok((extension, metadata.len()))
+}
+
+/// Copy `source` to a temporary file in `dir`, hashing it on the way so a
+/// large recording is read once and never held in memory
+fn copy_hashed(source: &Path, dir: &Path) -> Result<(tempfile::NamedTempFile, String)> {
+    let mut reader = std::fs::File::open(source)
+        .map_err(|e| anyhow!("Failed to read {}: {}", source.display(), e))?;
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let read = reader
+            .read(&mut buf)
+            .map_err(|e| anyhow!("Failed to read {}: {}", source.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+        tmp.write_all(&buf[..read])?;
+    }
+    tmp.flush()?;
+    Ok((tmp, format!("{:x}", hasher.finalize())))
+}
+
+/// Database operations for clone source provenance
+pub struct CloneSourceDb;
+
+impl CloneSourceDb {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<CloneSource> {
+        Ok(CloneSource {
+            id: row.get(0)?,
+            voice_name: row.get(1)?,
+            voice_id: row.get(2)?,
+            original_path: row.get(3)?,
+            managed_path: row.get(4)?,
+            content_hash: row.get(5)?,
+            size_bytes: row.get(6)?,
+            imported_at: row.get(7)?,
+        })
+    }
+
+    /// Record an imported source, returning the existing row if the managed file is already known
+    pub fn save_source(conn: &Connection, source: &CloneSource) -> Result<CloneSource> {
+        conn.execute(
+            "INSERT OR IGNORE INTO voice_clone_sources
+             (id, voice_name, voice_id, original_path, managed_path, content_hash, size_bytes, imported_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                source.id,
+                source.voice_name,
+                source.voice_id,
+                source.original_path,
+                source.managed_path,
+                source.content_hash,
+                source.size_bytes,
+                source.imported_at,
+            ],
+        )?;
+
+        Self::get_by_managed_path(conn, &source.managed_path)?
+            .ok_or_else(|| anyhow!("Failed to record clone source"))
+    }
+
+    pub fn get_by_managed_path(conn: &Connection, managed_path: &str) -> Result<Option<CloneSource>> {
+        Ok(conn
+            .query_row(
+                "SELECT id, voice_name, voice_id, original_path, managed_path, content_hash, size_bytes, imported_at
+                 FROM voice_clone_sources WHERE managed_path = ?1",
+                [managed_path],
+                Self::from_row,
+            )
+            .optional()?)
+    }
+
+    pub fn get_sources(conn: &Connection, voice_name: &str) -> Result<Vec<CloneSource>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, voice_name, voice_id, original_path, managed_path, content_hash, size_bytes, imported_at
+             FROM voice_clone_sources WHERE voice_name = ?1 ORDER BY imported_at",
+        )?;
+        let sources = stmt
+            .query_map([voice_name], Self::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(sources)
+    }
+
+    /// Link imported sources to the provider voice created from them
+    pub fn set_voice_id(conn: &Connection, managed_paths: &[String], voice_id: &str) -> Result<()> {
+        for path in managed_paths {
+            conn.execute(
+                "UPDATE voice_clone_sources SET voice_id = ?1 WHERE managed_path = ?2",
+                params![voice_id, path],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate and copy files into `voice_sources/<voice>/`, recording provenance.
+/// Files already inside the managed directory are returned as-is.
+pub fn import_sources(conn: &Connection, voice_name: &str, paths: &[String]) -> Result<Vec<CloneSource>> {
+    let root = sources_root()?;
+    let dir = root.join(voice_dir_name(voice_name));
+    std::fs::create_dir_all(&dir)?;
+
+    let mut imported = Vec::with_capacity(paths.len());
+    for original in paths {
+        let original_path = Path::new(original);
+
+        if original_path.starts_with(&root) {
+            if let Some(existing) = CloneSourceDb::get_by_managed_path(conn, original)? {
+                imported.push(existing);
+                continue;
+            }
+        }
+
+        let (extension, size) = validate_source(original_path)?;
+        let (tmp, content_hash) = copy_hashed(original_path, &dir)?;
+
+        // Content-addressed so re-importing the same sample is a no-op; the
+        // temporary copy is deleted when it isn't needed
+        let managed_path = dir.join(format!("{}.{}", &content_hash[..16], extension));
+        if !managed_path.exists() {
+            tmp.persist(&managed_path)?;
+        }
+
+        let source = CloneSource {
+            id: uuid::Uuid::new_v4().to_string(),
+            voice_name: voice_name.to_string(),
+            voice_id: None,
+            original_path: original.clone(),
+            managed_path: managed_path.to_string_lossy().to_string(),
+            content_hash,
+            size_bytes: size as i64,
+            imported_at: chrono::Utc::now().to_rfc3339(),
+        };
+        imported.push(CloneSourceDb::save_source(conn, &source)?);
+    }
+
+    Ok(imported)
+}
+
+// ========== Tauri Commands ==========
+
+/// Copy dropped audio files into the managed voice source directory for cloning
+#[tauri::command]
+pub async fn import_clone_sources(
+    state: State<'_, Arc<ElevenLabsState>>,
+    voice_name: String,
+    paths: Vec<String>,
+) -> Result<Vec<CloneSource>, AudioError> {
+    if paths.is_empty() {
+        return Err(AudioError::Validation("No files provided".to_string()));
+    }
+
+    state.call_db(move |conn| import_sources(conn, &voice_name, &paths)).await
+}
+
+/// List the managed source files imported for a voice
+#[tauri::command]
+pub async fn list_clone_sources(
+    state: State<'_, Arc<ElevenLabsState>>,
+    voice_name: String,
+) -> Result<Vec<CloneSource>, AudioError> {
+    state.call_db(move |conn| CloneSourceDb::get_sources(conn, &voice_name)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voice_dir_name() {
+        assert_eq!(voice_dir_name("Narrator (Warm)"), "narrator__warm");
+        assert_eq!(voice_dir_name("../../etc"), "etc");
+        assert_eq!(voice_dir_name("  "), "voice");
+    }
+
+    #[test]
+    fn test_copy_hashed() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("take.wav");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&source, &data).unwrap();
+
+        let (tmp, hash) = copy_hashed(&source, dir.path()).unwrap();
+        assert_eq!(hash, format!("{:x}", Sha256::digest(&data)));
+        assert_eq!(std::fs::read(tmp.path()).unwrap(), data);
+
+        let tmp_path = tmp.path().to_path_buf();
+        drop(tmp);
+        assert!(!tmp_path.exists());
+    }
+}
@@ -0,0 +1,224 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
+
+use super::auth;
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::notifications::{self, CompletionSummary};
+use super::pipeline;
+use super::supervisor::Shutdown;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Text waiting to be rendered and played
+#[derive(Debug, Clone)]
+pub struct SpeechJob {
+    pub request: TtsRequest,
+    /// What queued the job (`clipboard`, `hotkey`, ...), passed through to the frontend
+    pub source: String,
+    /// Extra fields merged into the audio record's metadata
+    pub metadata: serde_json::Value,
+    epoch: u64,
+}
+
+/// Emitted as `audio-speak` when a job has been rendered and should be played
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeakEvent {
+    pub source: String,
+    pub audio: GeneratedAudio,
+}
+
+/// Emitted as `audio-speak-error` when a job fails
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeakErrorEvent {
+    pub source: String,
+    pub error: String,
+}
+
+/// Merge a job's extra metadata into the rendered record and save it
+async fn annotate(
+    state: &ElevenLabsState,
+    audio: &mut GeneratedAudio,
+    metadata: serde_json::Value,
+) -> Result<(), AudioError> {
+    let serde_json::Value::Object(fields) = metadata else {
+        return Ok(());
+    };
+    for (key, value) in fields {
+        audio.metadata[key.as_str()] = value;
+    }
+
+    let record = audio.clone();
+    state.call_db(move |conn| AudioCacheDb::save_audio_record(conn, &record)).await
+}
+
+/// Render a job. While the API key is rejected the job waits for a new key
+/// and is retried; `None` if shutdown or `stop()` came first.
+async fn render(
+    state: &ElevenLabsState,
+    job: &SpeechJob,
+    epoch: &AtomicU64,
+    shutdown: &Shutdown,
+) -> Option<Result<GeneratedAudio, AudioError>> {
+    loop {
+        match pipeline::generate_tts(state, job.request.clone()).await {
+            Err(e) if auth::is_unauthorized(&e) => {
+                log::info!("Speech job from {} is waiting for a new API key", job.source);
+                tokio::select! {
+                    _ = state.auth().wait_accepted() => {}
+                    _ = shutdown.wait() => return None,
+                }
+                if job.epoch != epoch.load(Ordering::SeqCst) {
+                    return None;
+                }
+            }
+            result => return Some(result),
+        }
+    }
+}
+
+/// Background queue that renders speech requests in order and hands the results
+/// to the frontend player. Managed as Tauri state.
+pub struct SpeechQueue {
+    tx: mpsc::UnboundedSender<SpeechJob>,
+    epoch: Arc<AtomicU64>,
+    last: Arc<Mutex<Option<GeneratedAudio>>>,
+}
+
+impl SpeechQueue {
+    /// Spawn the worker under the audio task supervisor. `ElevenLabsState`
+    /// must already be managed.
+    pub fn start(app: AppHandle) -> Self {
+        let (tx, mut rx) = mpsc::unbounded_channel::<SpeechJob>();
+        let epoch = Arc::new(AtomicU64::new(0));
+        let last = Arc::new(Mutex::new(None));
+
+        let worker_epoch = epoch.clone();
+        let worker_last = last.clone();
+        let tasks = app.state::<Arc<ElevenLabsState>>().tasks().clone();
+        tasks.spawn("speech-queue", |shutdown| async move {
+            let mut batch = CompletionSummary::default();
+
+            loop {
+                // The job in progress is finished on shutdown; queued ones are not started
+                let job = tokio::select! {
+                    job = rx.recv() => job,
+                    _ = shutdown.wait() => None,
+                };
+                let Some(job) = job else {
+                    break;
+                };
+
+                // Jobs queued before the last stop() are dropped
+                if job.epoch != worker_epoch.load(Ordering::SeqCst) {
+                    continue;
+                }
+
+                let state = app.state::<Arc<ElevenLabsState>>();
+                let Some(result) = render(&state, &job, &worker_epoch, &shutdown).await else {
+                    continue;
+                };
+                match result {
+                    Ok(mut audio) => {
+                        if let Err(e) = annotate(&state, &mut audio, job.metadata).await {
+                            log::warn!("Failed to save metadata for {}: {}", audio.id, e);
+                        }
+                        batch.completed += 1;
+                        batch.duration_seconds += audio.duration_seconds;
+                        batch.audio_id = Some(audio.id.clone());
+
+                        if job.epoch == worker_epoch.load(Ordering::SeqCst) {
+                            if let Ok(mut last) = worker_last.lock() {
+                                *last = Some(audio.clone());
+                            }
+                            let _ = app.emit("audio-speak", SpeakEvent { source: job.source, audio });
+                        }
+                    }
+                    Err(error) => {
+                        batch.errors += 1;
+                        log::warn!("Speech job from {} failed: {}", job.source, error);
+                        let _ = app.emit("audio-speak-error", SpeakErrorEvent { source: job.source, error: error.to_string() });
+                    }
+                }
+
+                // The queue has drained: report the batch
+                if rx.is_empty() {
+                    notifications::notify_completion(&app, &batch);
+                    batch = CompletionSummary::default();
+                }
+            }
+
+            rx.close();
+            let mut interrupted = 0;
+            while let Ok(job) = rx.try_recv() {
+                if job.epoch == worker_epoch.load(Ordering::SeqCst) {
+                    interrupted += 1;
+                }
+            }
+            if interrupted > 0 {
+                log::warn!("Shutdown interrupted {} queued speech jobs", interrupted);
+            }
+        });
+
+        Self { tx, epoch, last }
+    }
+
+    /// Queue text to be spoken with the given voice and default settings
+    pub fn enqueue(&self, text: String, voice_id: String, source: &str) -> Result<(), AudioError> {
+        self.enqueue_request(
+            TtsRequest {
+                text,
+                voice_id,
+                model_id: default_model_id(),
+                voice_settings: None,
+                output_format: default_output_format(),
+                normalize_text: None,
+                preset: None,
+                previous_text: None,
+                next_text: None,
+                previous_request_ids: Vec::new(),
+                seed: None,
+            },
+            source,
+        )
+    }
+
+    /// Queue a fully specified TTS request
+    pub fn enqueue_request(&self, request: TtsRequest, source: &str) -> Result<(), AudioError> {
+        self.enqueue_annotated(request, source, serde_json::Value::Null)
+    }
+
+    /// Queue a request whose audio record gets `metadata` merged into its metadata
+    pub fn enqueue_annotated(&self, request: TtsRequest, source: &str, metadata: serde_json::Value) -> Result<(), AudioError> {
+        self.tx
+            .send(SpeechJob {
+                request,
+                source: source.to_string(),
+                metadata,
+                epoch: self.epoch.load(Ordering::SeqCst),
+            })
+            .map_err(|_| AudioError::Other("Speech queue is not running".to_string()))
+    }
+
+    /// Drop pending jobs and ask the frontend to stop playback
+    pub fn stop(&self, app: &AppHandle) {
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        let _ = app.emit("audio-stop-playback", ());
+    }
+
+    /// Play an already rendered clip, bypassing the render queue
+    pub fn play(&self, app: &AppHandle, audio: GeneratedAudio, source: &str) {
+        if let Ok(mut last) = self.last.lock() {
+            *last = Some(audio.clone());
+        }
+        let _ = app.emit("audio-speak", SpeakEvent { source: source.to_string(), audio });
+    }
+
+    /// The most recently spoken render
+    pub fn last(&self) -> Option<GeneratedAudio> {
+        self.last.lock().ok().and_then(|last| last.clone())
+    }
+}
@@ -0,0 +1,183 @@
+// Subtitles for speech rendered with timestamps. The word timings stored under
+// `ALIGNMENT_KEY` are grouped into cues of limited length and duration and
+// written out as SRT or WebVTT.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::types::*;
+use super::ElevenLabsState;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    #[default]
+    Srt,
+    Vtt,
+}
+
+/// How words are grouped into cues
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CueOptions {
+    /// Longest cue text, in characters; a longer single word gets a cue of its own
+    #[serde(default = "default_max_line_length")]
+    pub max_line_length: usize,
+    /// Longest time a cue stays on screen
+    #[serde(default = "default_max_cue_seconds")]
+    pub max_cue_seconds: f32,
+}
+
+fn default_max_line_length() -> usize {
+    42
+}
+
+fn default_max_cue_seconds() -> f32 {
+    5.0
+}
+
+impl Default for CueOptions {
+    fn default() -> Self {
+        Self {
+            max_line_length: default_max_line_length(),
+            max_cue_seconds: default_max_cue_seconds(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cue {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+}
+
+/// Group words into cues, starting a new one when the next word would make
+/// the text too long or keep it on screen too long, and after the end of a
+/// sentence
+pub fn build_cues(words: &[WordTiming], options: CueOptions) -> Vec<Cue> {
+    let mut cues: Vec<Cue> = Vec::new();
+    let mut current: Option<Cue> = None;
+    for word in words {
+        if let Some(cue) = &mut current {
+            let fits = cue.text.chars().count() + 1 + word.word.chars().count() <= options.max_line_length
+                && word.end - cue.start <= options.max_cue_seconds;
+            if fits {
+                cue.text.push(' ');
+                cue.text.push_str(&word.word);
+                cue.end = word.end;
+            } else {
+                cues.extend(current.take());
+            }
+        }
+        if current.is_none() {
+            current = Some(Cue { start: word.start, end: word.end, text: word.word.clone() });
+        }
+        if word.word.ends_with(['.', '?', '!']) {
+            cues.extend(current.take());
+        }
+    }
+    cues.extend(current);
+    cues
+}
+
+/// `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (WebVTT)
+fn timestamp(seconds: f32, format: SubtitleFormat) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    let separator = match format {
+        SubtitleFormat::Srt => ',',
+        SubtitleFormat::Vtt => '.',
+    };
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// The cues as a subtitle file
+pub fn render(cues: &[Cue], format: SubtitleFormat) -> String {
+    let mut out = String::new();
+    if format == SubtitleFormat::Vtt {
+        out.push_str("WEBVTT\n\n");
+    }
+    for (index, cue) in cues.iter().enumerate() {
+        if format == SubtitleFormat::Srt {
+            out.push_str(&format!("{}\n", index + 1));
+        }
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            timestamp(cue.start, format),
+            timestamp(cue.end, format),
+            cue.text
+        ));
+    }
+    out
+}
+
+/// Cues for a cached record rendered with timestamps
+pub fn cues_for(audio: &GeneratedAudio, options: CueOptions) -> Result<Vec<Cue>, AudioError> {
+    let alignment = audio.metadata.get(ALIGNMENT_KEY).ok_or_else(|| {
+        AudioError::Validation(format!("Audio record {} has no word timings; generate it with timestamps", audio.id))
+    })?;
+    let alignment: SpeechAlignment = serde_json::from_value(alignment.clone())?;
+    Ok(build_cues(&alignment.words, options))
+}
+
+/// Write subtitles for a record rendered with timestamps to `dest`, as SRT
+/// unless `format` says otherwise. Returns the number of cues written.
+#[tauri::command]
+pub async fn export_subtitles(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_id: String,
+    dest: String,
+    format: Option<SubtitleFormat>,
+    options: Option<CueOptions>,
+) -> Result<usize, AudioError> {
+    let id = audio_id.clone();
+    let audio = state
+        .call_db(move |conn| AudioCacheDb::get_audio_record(conn, &id))
+        .await?
+        .ok_or_else(|| AudioError::Validation(format!("No audio record: {}", audio_id)))?;
+    let options = options.unwrap_or_default();
+    if options.max_line_length == 0 || options.max_cue_seconds <= 0.0 {
+        return Err(AudioError::Validation("Cue length and duration must be positive".to_string()));
+    }
+
+    let cues = cues_for(&audio, options)?;
+    tokio::fs::write(Path::new(&dest), render(&cues, format.unwrap_or_default())).await?;
+    Ok(cues.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cues_and_formats() {
+        let alignment = CharacterAlignment {
+            characters: "Hi there. How are you".chars().map(String::from).collect(),
+            character_start_times_seconds: (0..21).map(|i| i as f32 * 0.1).collect(),
+            character_end_times_seconds: (1..22).map(|i| i as f32 * 0.1).collect(),
+        };
+        let words = alignment.words();
+
+        // Sentence ends and the line length both close a cue
+        let options = CueOptions { max_line_length: 9, ..CueOptions::default() };
+        let texts: Vec<_> = build_cues(&words, options).into_iter().map(|cue| cue.text).collect();
+        assert_eq!(texts, vec!["Hi there.", "How are", "you"]);
+        let short = CueOptions { max_cue_seconds: 0.5, ..CueOptions::default() };
+        assert_eq!(build_cues(&words, short).len(), 5);
+
+        let cues = vec![Cue { start: 0.0, end: 1.25, text: "Hi".to_string() }];
+        assert_eq!(render(&cues, SubtitleFormat::Srt), "1\n00:00:00,000 --> 00:00:01,250\nHi\n\n");
+        assert_eq!(render(&cues, SubtitleFormat::Vtt), "WEBVTT\n\n00:00:00.000 --> 00:00:01.250\nHi\n\n");
+        assert_eq!(timestamp(3725.5, SubtitleFormat::Srt), "01:02:05,500");
+    }
+}
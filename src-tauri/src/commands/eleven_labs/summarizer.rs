@@ -0,0 +1,113 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::narration::split_sentences;
+
+/// Condenses long text before it is spoken
+#[async_trait]
+pub trait Summarizer: Send + Sync {
+    /// Short identifier recorded in the audio metadata
+    fn name(&self) -> &'static str;
+
+    /// Condense `text` to roughly `max_sentences` sentences
+    async fn summarize(&self, text: &str, max_sentences: usize) -> Result<String>;
+}
+
+/// Available summarizers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummarizerKind {
+    /// The opening sentences of the text; instant and offline
+    #[default]
+    Lead,
+    /// A one-shot prompt to the Claude Code CLI
+    Claude,
+}
+
+/// Keeps the opening sentences
+pub struct LeadSummarizer;
+
+#[async_trait]
+impl Summarizer for LeadSummarizer {
+    fn name(&self) -> &'static str {
+        "lead"
+    }
+
+    async fn summarize(&self, text: &str, max_sentences: usize) -> Result<String> {
+        let mut sentences = split_sentences(text);
+        sentences.truncate(max_sentences.max(1));
+        Ok(sentences.join(" "))
+    }
+}
+
+/// Asks Claude for a spoken-style summary through `claude -p`
+pub struct ClaudeSummarizer {
+    claude_path: String,
+    model: String,
+}
+
+#[async_trait]
+impl Summarizer for ClaudeSummarizer {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    async fn summarize(&self, text: &str, max_sentences: usize) -> Result<String> {
+        let prompt = format!(
+            "Summarize the following message in at most {} short sentences of plain spoken English. \
+             No markdown, lists or code. Reply with the summary only.\n\n{}",
+            max_sentences.max(1),
+            text
+        );
+        let output = tokio::process::Command::new(&self.claude_path)
+            .arg("-p")
+            .arg(prompt)
+            .arg("--model")
+            .arg(&self.model)
+            .arg("--output-format")
+            .arg("text")
+            .stdin(std::process::Stdio::null())
+            .output()
+            .await
+            .map_err(|e| anyhow!("Failed to run claude: {}", e))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "claude failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let summary = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if summary.is_empty() {
+            return Err(anyhow!("claude returned an empty summary"));
+        }
+        Ok(summary)
+    }
+}
+
+/// Build the configured summarizer
+pub fn open_summarizer(app: &AppHandle, kind: SummarizerKind, model: &str) -> Result<Box<dyn Summarizer>> {
+    match kind {
+        SummarizerKind::Lead => Ok(Box::new(LeadSummarizer)),
+        SummarizerKind::Claude => Ok(Box::new(ClaudeSummarizer {
+            claude_path: crate::claude_binary::find_claude_binary(app).map_err(|e| anyhow!(e))?,
+            model: model.to_string(),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lead_summarizer() {
+        let summary = LeadSummarizer
+            .summarize("Refactored the parser. Added tests. Updated the docs.", 2)
+            .await
+            .unwrap();
+        assert_eq!(summary, "Refactored the parser. Added tests.");
+    }
+}
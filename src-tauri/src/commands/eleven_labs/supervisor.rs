@@ -0,0 +1,269 @@
+// Owner of the audio subsystem's long-running tasks (speech queue worker,
+// watchers, local servers). Tasks get a `Shutdown` signal to finish their
+// current unit of work; on app exit `shutdown` waits a grace period for them
+// and aborts whatever is still running.
+
+use futures::future::{AbortHandle, Abortable};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::State;
+use tokio::sync::watch;
+
+use super::error::AudioError;
+use super::ElevenLabsState;
+
+/// How long tasks get to finish their current work when the app exits
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Resolves once shutdown has been requested, for every task on app exit or
+/// for this task alone through `TaskHandle::stop`
+#[derive(Clone)]
+pub struct Shutdown {
+    all: watch::Receiver<bool>,
+    task: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Wait for shutdown to be requested. Dropping the task's handle is not a
+    /// request to stop.
+    pub async fn wait(&self) {
+        let (mut all, mut task) = (self.all.clone(), self.task.clone());
+        tokio::select! {
+            _ = all.wait_for(|stop| *stop) => {}
+            Ok(_) = task.wait_for(|stop| *stop) => {}
+        }
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.all.borrow() || *self.task.borrow()
+    }
+}
+
+/// A task as reported by `list_background_tasks`
+#[derive(Debug, Clone, Serialize)]
+pub struct BackgroundTask {
+    pub id: u64,
+    pub name: String,
+    pub started_at: String,
+    /// Shutdown has been requested and the task is finishing up
+    pub stopping: bool,
+}
+
+struct TaskEntry {
+    name: String,
+    started_at: String,
+    abort: AbortHandle,
+}
+
+struct Inner {
+    tasks: Mutex<BTreeMap<u64, TaskEntry>>,
+    next_id: AtomicU64,
+    shutdown: watch::Sender<bool>,
+}
+
+/// Handle to one supervised task
+pub struct TaskHandle {
+    id: u64,
+    abort: AbortHandle,
+    stop: watch::Sender<bool>,
+    join: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl TaskHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Stop the task without waiting for it to finish its current work
+    pub fn abort(&self) {
+        self.abort.abort();
+    }
+
+    /// Signal the task to stop, wait up to `grace` for it to finish, then
+    /// abort it. Returns once the task is gone and has dropped what it held,
+    /// like a listening socket.
+    pub async fn stop(self, grace: Duration) {
+        self.stop.send_replace(true);
+        let mut join = self.join;
+        if tokio::time::timeout(grace, &mut join).await.is_err() {
+            log::warn!("Background task {} did not stop in time, aborting", self.id);
+            self.abort.abort();
+            let _ = join.await;
+        }
+    }
+}
+
+/// Registry of the running background tasks. Cheap to clone.
+#[derive(Clone)]
+pub struct TaskSupervisor {
+    inner: Arc<Inner>,
+}
+
+impl TaskSupervisor {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                tasks: Mutex::new(BTreeMap::new()),
+                next_id: AtomicU64::new(1),
+                shutdown: watch::channel(false).0,
+            }),
+        }
+    }
+
+    /// Spawn `task` on the Tauri runtime. It is listed until it returns or is
+    /// aborted, and should return soon after its `Shutdown` resolves.
+    pub fn spawn<F, Fut>(&self, name: impl Into<String>, task: F) -> TaskHandle
+    where
+        F: FnOnce(Shutdown) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let tasks = self.inner.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        self.start(tasks, name.into(), task)
+    }
+
+    /// Like `spawn`, but does nothing and returns `None` while a task with the
+    /// same name is running. The check and the registration are one step, so
+    /// concurrent callers can't both start it.
+    pub fn spawn_unique<F, Fut>(&self, name: impl Into<String>, task: F) -> Option<TaskHandle>
+    where
+        F: FnOnce(Shutdown) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        let tasks = self.inner.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if tasks.values().any(|task| task.name == name) {
+            return None;
+        }
+        Some(self.start(tasks, name, task))
+    }
+
+    fn start<F, Fut>(&self, mut tasks: MutexGuard<'_, BTreeMap<u64, TaskEntry>>, name: String, task: F) -> TaskHandle
+    where
+        F: FnOnce(Shutdown) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let id = self.inner.next_id.fetch_add(1, Ordering::SeqCst);
+        let (abort, registration) = AbortHandle::new_pair();
+        let (stop, stop_rx) = watch::channel(false);
+        let shutdown = Shutdown {
+            all: self.inner.shutdown.subscribe(),
+            task: stop_rx,
+        };
+        let future = Abortable::new(task(shutdown), registration);
+
+        // Registered before the task can run, so it can't remove itself first
+        tasks.insert(
+            id,
+            TaskEntry {
+                name: name.clone(),
+                started_at: chrono::Utc::now().to_rfc3339(),
+                abort: abort.clone(),
+            },
+        );
+        let inner = self.inner.clone();
+        let join = tauri::async_runtime::spawn(async move {
+            if future.await.is_err() {
+                log::info!("Background task {} was interrupted", name);
+            }
+            inner.tasks.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+        });
+
+        TaskHandle { id, abort, stop, join }
+    }
+
+    /// The tasks still running
+    pub fn list(&self) -> Vec<BackgroundTask> {
+        let stopping = *self.inner.shutdown.borrow();
+        self.inner
+            .tasks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(id, task)| BackgroundTask {
+                id: *id,
+                name: task.name.clone(),
+                started_at: task.started_at.clone(),
+                stopping,
+            })
+            .collect()
+    }
+
+    /// Signal every task to stop, wait up to `grace` for them to finish, then
+    /// abort the rest. Tasks spawned afterwards see shutdown immediately.
+    pub async fn shutdown(&self, grace: Duration) {
+        self.inner.shutdown.send_replace(true);
+
+        let deadline = tokio::time::Instant::now() + grace;
+        while !self.is_idle() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        for (_, task) in self.inner.tasks.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            log::warn!("Background task {} did not stop in time, aborting", task.name);
+            task.abort.abort();
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.inner.tasks.lock().map(|tasks| tasks.is_empty()).unwrap_or(true)
+    }
+}
+
+impl Default for TaskSupervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ========== Tauri Commands ==========
+
+/// List the audio subsystem's running background tasks
+#[tauri::command]
+pub async fn list_background_tasks(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<BackgroundTask>, AudioError> {
+    Ok(state.tasks.list())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_waits_then_aborts() {
+        let supervisor = TaskSupervisor::new();
+        supervisor.spawn("cooperative", |shutdown| async move { shutdown.wait().await });
+        supervisor.spawn("stuck", |_| std::future::pending());
+        tokio::task::yield_now().await;
+        assert_eq!(supervisor.list().len(), 2);
+
+        supervisor.shutdown(Duration::from_millis(200)).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(supervisor.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stop_waits_for_one_task() {
+        let supervisor = TaskSupervisor::new();
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let cooperative = supervisor.spawn("cooperative", |shutdown| async move {
+            shutdown.wait().await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let _ = done_tx.send(());
+        });
+        let other = supervisor.spawn("other", |shutdown| async move { shutdown.wait().await });
+
+        cooperative.stop(Duration::from_secs(5)).await;
+        assert!(done_rx.try_recv().is_ok());
+        let names: Vec<_> = supervisor.list().into_iter().map(|task| task.name).collect();
+        assert_eq!(names, vec!["other"]);
+
+        assert!(supervisor.spawn_unique("other", |_| async {}).is_none());
+
+        let stuck = supervisor.spawn("stuck", |_| std::future::pending());
+        stuck.stop(Duration::from_millis(50)).await;
+        other.abort();
+    }
+}
@@ -0,0 +1,624 @@
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+use uuid::Uuid;
+
+use super::cache::{AudioCache, AudioCacheDb, CharacterVoiceDb, SettingsDb, VoiceProfileDb};
+use super::conflicts::{
+    audio_fingerprint, casting_fingerprint, casting_key, compare, device_id, merge, Causality,
+    SyncConflictDb, SyncRevisionDb, KIND_AUDIO, KIND_CASTING,
+};
+use super::error::AudioError;
+use super::remote::{
+    open_remote, RemoteBackend, RemoteStorage, S3Config, WebDavConfig, S3_CONFIG_KEY,
+    WEBDAV_CONFIG_KEY,
+};
+use super::secrets;
+use super::types::*;
+use super::webhooks;
+use super::{ensure_cache, ElevenLabsState};
+
+const MANIFEST_KEY: &str = "manifest.json";
+const OBJECTS_PREFIX: &str = "objects/";
+const MANIFEST_VERSION: u32 = 2;
+
+/// Library manifest stored next to the content-addressed audio objects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryManifest {
+    pub version: u32,
+    pub updated_at: String,
+    pub audio: Vec<RemoteAudioEntry>,
+    pub voices: Vec<VoiceProfile>,
+    /// Character voice assignments (added in version 2)
+    #[serde(default)]
+    pub casting: Vec<RemoteCastingEntry>,
+}
+
+/// A single audio record together with the hash of its file contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteAudioEntry {
+    pub record: GeneratedAudio,
+    pub content_hash: String,
+    pub extension: String,
+    /// Empty for entries written before revisions were tracked
+    #[serde(default)]
+    pub revision: RevisionVector,
+}
+
+/// A character voice assignment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteCastingEntry {
+    pub record: CharacterVoice,
+    pub revision: RevisionVector,
+}
+
+/// Per-backend record of which local files have already been uploaded
+pub struct SyncStateDb;
+
+impl SyncStateDb {
+    /// Get the hash last synced for an audio record on a backend
+    pub fn get_synced_hash(conn: &Connection, audio_id: &str, backend: &str) -> Result<Option<String>> {
+        let mut stmt = conn.prepare_cached(
+            "SELECT content_hash FROM audio_sync_state WHERE audio_id = ?1 AND backend = ?2",
+        )?;
+        let mut rows = stmt.query(params![audio_id, backend])?;
+
+        if let Some(row) = rows.next()? {
+            Ok(Some(row.get(0)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Record that an audio record's content is present on a backend
+    pub fn mark_synced(conn: &Connection, audio_id: &str, backend: &str, content_hash: &str) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO audio_sync_state (audio_id, backend, content_hash, synced_at)
+             VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)",
+            params![audio_id, backend, content_hash],
+        )?;
+        Ok(())
+    }
+}
+
+/// Compute the SHA-256 content hash used as the remote object key
+pub fn content_hash(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
+
+fn object_key(hash: &str) -> String {
+    format!("{}{}", OBJECTS_PREFIX, hash)
+}
+
+fn file_extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("mp3")
+        .to_string()
+}
+
+fn all_audio_records(conn: &Connection) -> Result<Vec<GeneratedAudio>> {
+    let mut records = vec![];
+    for audio_type in [AudioType::Tts, AudioType::Sfx, AudioType::Music] {
+        records.extend(AudioCacheDb::get_audio_records(conn, &audio_type)?);
+    }
+    Ok(records)
+}
+
+async fn load_manifest(remote: &dyn RemoteStorage) -> Result<Option<LibraryManifest>> {
+    match remote.get_object(MANIFEST_KEY).await? {
+        Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        None => Ok(None),
+    }
+}
+
+/// Keep both sides of a concurrent audio edit: the remote copy is downloaded next to
+/// the local one so the user can compare them before resolving.
+async fn record_audio_conflict(
+    conn: &mut Connection,
+    cache: &AudioCache,
+    remote: &dyn RemoteStorage,
+    local: RemoteAudioEntry,
+    theirs: &RemoteAudioEntry,
+) -> Result<()> {
+    let backend = remote.backend_name();
+    let existing = SyncConflictDb::find(conn, KIND_AUDIO, &local.record.id, backend)?;
+
+    let remote_value = match &existing {
+        // Already downloaded for this remote revision
+        Some(existing) if existing.remote_revision == theirs.revision => existing.remote.clone(),
+        _ => {
+            if let Some(existing) = &existing {
+                if let Ok(stale) = serde_json::from_value::<RemoteAudioEntry>(existing.remote.clone()) {
+                    cache.delete_audio(Path::new(&stale.record.local_path)).await?;
+                }
+            }
+
+            let data = remote
+                .get_object(&object_key(&theirs.content_hash))
+                .await?
+                .ok_or_else(|| anyhow!("remote object missing"))?;
+            let path = cache
+                .save_audio(&theirs.record.audio_type, &data, &theirs.extension)
+                .await?;
+
+            let mut theirs = theirs.clone();
+            theirs.record.local_path = path.to_string_lossy().to_string();
+            serde_json::to_value(theirs)?
+        }
+    };
+
+    SyncConflictDb::save(
+        conn,
+        &SyncConflict {
+            id: existing.map(|c| c.id).unwrap_or_else(|| Uuid::new_v4().to_string()),
+            kind: KIND_AUDIO.to_string(),
+            record_id: local.record.id.clone(),
+            backend: backend.to_string(),
+            local_revision: local.revision.clone(),
+            local: serde_json::to_value(local)?,
+            remote: remote_value,
+            remote_revision: theirs.revision.clone(),
+            detected_at: chrono::Utc::now().to_rfc3339(),
+        },
+    )
+}
+
+fn record_casting_conflict(
+    conn: &Connection,
+    backend: &str,
+    local: &RemoteCastingEntry,
+    theirs: &RemoteCastingEntry,
+) -> Result<()> {
+    let key = casting_key(&local.record);
+    let id = SyncConflictDb::find(conn, KIND_CASTING, &key, backend)?
+        .map(|c| c.id)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    SyncConflictDb::save(
+        conn,
+        &SyncConflict {
+            id,
+            kind: KIND_CASTING.to_string(),
+            record_id: key,
+            backend: backend.to_string(),
+            local: serde_json::to_value(&local.record)?,
+            remote: serde_json::to_value(&theirs.record)?,
+            local_revision: local.revision.clone(),
+            remote_revision: theirs.revision.clone(),
+            detected_at: chrono::Utc::now().to_rfc3339(),
+        },
+    )
+}
+
+/// Upload new or changed audio files and merge the local library into the remote manifest.
+///
+/// Objects are keyed by content hash, so unchanged files are never re-uploaded. Entries
+/// that only exist remotely (backed up from another machine) are preserved. Each record
+/// carries a revision vector: local edits replace remote entries they descend from, newer
+/// remote entries are left for `restore_library`, and concurrent edits are recorded as
+/// sync conflicts with both versions kept.
+pub async fn backup_library(
+    conn: &mut Connection,
+    cache: &AudioCache,
+    remote: &dyn RemoteStorage,
+) -> Result<SyncResult> {
+    let backend = remote.backend_name();
+    let device_id = device_id(conn)?;
+    let mut errors = vec![];
+    let mut audio_synced = 0;
+    let mut conflicts = 0;
+
+    let remote_objects: HashSet<String> = remote
+        .list_objects(OBJECTS_PREFIX)
+        .await?
+        .into_iter()
+        .map(|k| k.trim_start_matches(OBJECTS_PREFIX).to_string())
+        .collect();
+
+    let mut entries: HashMap<String, RemoteAudioEntry> = HashMap::new();
+    let mut casting: HashMap<String, RemoteCastingEntry> = HashMap::new();
+    if let Some(manifest) = load_manifest(remote).await? {
+        for entry in manifest.audio {
+            entries.insert(entry.record.id.clone(), entry);
+        }
+        for entry in manifest.casting {
+            casting.insert(casting_key(&entry.record), entry);
+        }
+    }
+
+    for record in all_audio_records(conn)? {
+        let data = match tokio::fs::read(&record.local_path).await {
+            Ok(data) => data,
+            Err(e) => {
+                errors.push(format!("{}: failed to read {}: {}", record.id, record.local_path, e));
+                continue;
+            }
+        };
+        let hash = content_hash(&data);
+        let fingerprint = audio_fingerprint(&record, &hash);
+        let mut revision = SyncRevisionDb::current(conn, &device_id, KIND_AUDIO, &record.id, &fingerprint)?;
+
+        if let Some(existing) = entries.get(&record.id) {
+            match compare(&revision, &existing.revision) {
+                Causality::Equal | Causality::Ahead => {}
+                // Edited elsewhere since our last sync; restore picks it up
+                Causality::Behind => continue,
+                Causality::Concurrent => {
+                    if audio_fingerprint(&existing.record, &existing.content_hash) == fingerprint {
+                        revision = merge(&revision, &existing.revision);
+                        SyncRevisionDb::set(conn, KIND_AUDIO, &record.id, &revision, &fingerprint)?;
+                    } else {
+                        let local = RemoteAudioEntry {
+                            extension: file_extension(&record.local_path),
+                            record,
+                            content_hash: hash,
+                            revision,
+                        };
+                        let theirs = existing.clone();
+                        let id = local.record.id.clone();
+                        match record_audio_conflict(conn, cache, remote, local, &theirs).await {
+                            Ok(()) => conflicts += 1,
+                            Err(e) => errors.push(format!("{}: failed to record conflict: {}", id, e)),
+                        }
+                        continue;
+                    }
+                }
+            }
+        }
+
+        let already_synced = SyncStateDb::get_synced_hash(conn, &record.id, backend)?
+            .map(|h| h == hash)
+            .unwrap_or(false);
+
+        if !already_synced && !remote_objects.contains(&hash) {
+            if let Err(e) = remote.put_object(&object_key(&hash), data.into()).await {
+                errors.push(format!("{}: upload failed: {}", record.id, e));
+                continue;
+            }
+            audio_synced += 1;
+        }
+        SyncStateDb::mark_synced(conn, &record.id, backend, &hash)?;
+
+        entries.insert(
+            record.id.clone(),
+            RemoteAudioEntry {
+                extension: file_extension(&record.local_path),
+                record,
+                content_hash: hash,
+                revision,
+            },
+        );
+    }
+
+    for mapping in CharacterVoiceDb::get_character_voices(conn, None)? {
+        let key = casting_key(&mapping);
+        let fingerprint = casting_fingerprint(&mapping);
+        let mut revision = SyncRevisionDb::current(conn, &device_id, KIND_CASTING, &key, &fingerprint)?;
+
+        if let Some(existing) = casting.get(&key) {
+            match compare(&revision, &existing.revision) {
+                Causality::Equal | Causality::Ahead => {}
+                Causality::Behind => continue,
+                Causality::Concurrent => {
+                    if casting_fingerprint(&existing.record) == fingerprint {
+                        revision = merge(&revision, &existing.revision);
+                        SyncRevisionDb::set(conn, KIND_CASTING, &key, &revision, &fingerprint)?;
+                    } else {
+                        let local = RemoteCastingEntry { record: mapping, revision };
+                        record_casting_conflict(conn, backend, &local, existing)?;
+                        conflicts += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        casting.insert(key, RemoteCastingEntry { record: mapping, revision });
+    }
+
+    let voices = VoiceProfileDb::get_voice_profiles(conn)?;
+    let voices_synced = voices.len() as i32;
+
+    let mut audio: Vec<RemoteAudioEntry> = entries.into_values().collect();
+    audio.sort_by(|a, b| a.record.created_at.cmp(&b.record.created_at));
+    let mut casting: Vec<RemoteCastingEntry> = casting.into_values().collect();
+    casting.sort_by_key(|entry| casting_key(&entry.record));
+
+    let manifest = LibraryManifest {
+        version: MANIFEST_VERSION,
+        updated_at: chrono::Utc::now().to_rfc3339(),
+        audio,
+        voices,
+        casting,
+    };
+    remote
+        .put_object(MANIFEST_KEY, serde_json::to_vec_pretty(&manifest)?.into())
+        .await?;
+
+    Ok(SyncResult {
+        success: errors.is_empty(),
+        voices_synced,
+        audio_synced,
+        conflicts,
+        errors,
+    })
+}
+
+async fn download_entry(remote: &dyn RemoteStorage, entry: &RemoteAudioEntry) -> Result<Bytes> {
+    remote
+        .get_object(&object_key(&entry.content_hash))
+        .await?
+        .ok_or_else(|| anyhow!("remote object missing"))
+}
+
+/// Bring the local library up to date with the remote manifest.
+///
+/// Missing records are downloaded, and local records the remote revision descends from
+/// are updated in place. Records edited on both sides are recorded as sync conflicts
+/// rather than overwritten.
+pub async fn restore_library(
+    conn: &mut Connection,
+    cache: &AudioCache,
+    remote: &dyn RemoteStorage,
+) -> Result<SyncResult> {
+    let backend = remote.backend_name();
+    let device_id = device_id(conn)?;
+    let mut errors = vec![];
+    let mut audio_synced = 0;
+    let mut conflicts = 0;
+
+    let manifest = match load_manifest(remote).await? {
+        Some(manifest) => manifest,
+        None => {
+            return Ok(SyncResult {
+                success: true,
+                voices_synced: 0,
+                audio_synced: 0,
+                conflicts: 0,
+                errors,
+            })
+        }
+    };
+
+    for entry in manifest.audio {
+        let remote_fingerprint = audio_fingerprint(&entry.record, &entry.content_hash);
+
+        let previous = match AudioCacheDb::get_audio_record(conn, &entry.record.id)? {
+            None => None,
+            Some(local) => {
+                let local_hash = match tokio::fs::read(&local.local_path).await {
+                    Ok(data) => content_hash(&data),
+                    Err(e) => {
+                        errors.push(format!("{}: failed to read {}: {}", local.id, local.local_path, e));
+                        continue;
+                    }
+                };
+                let fingerprint = audio_fingerprint(&local, &local_hash);
+                let revision = SyncRevisionDb::current(conn, &device_id, KIND_AUDIO, &local.id, &fingerprint)?;
+
+                match compare(&revision, &entry.revision) {
+                    Causality::Equal | Causality::Ahead => continue,
+                    Causality::Behind => Some((local, local_hash)),
+                    Causality::Concurrent if fingerprint == remote_fingerprint => {
+                        let merged = merge(&revision, &entry.revision);
+                        SyncRevisionDb::set(conn, KIND_AUDIO, &local.id, &merged, &fingerprint)?;
+                        continue;
+                    }
+                    Causality::Concurrent => {
+                        let id = local.id.clone();
+                        let mine = RemoteAudioEntry {
+                            extension: file_extension(&local.local_path),
+                            record: local,
+                            content_hash: local_hash,
+                            revision,
+                        };
+                        match record_audio_conflict(conn, cache, remote, mine, &entry).await {
+                            Ok(()) => conflicts += 1,
+                            Err(e) => errors.push(format!("{}: failed to record conflict: {}", id, e)),
+                        }
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let mut record = entry.record.clone();
+        match &previous {
+            // Metadata-only change: keep the local file
+            Some((local, local_hash)) if *local_hash == entry.content_hash => {
+                record.local_path = local.local_path.clone();
+            }
+            _ => {
+                let data = match download_entry(remote, &entry).await {
+                    Ok(data) => data,
+                    Err(e) => {
+                        errors.push(format!("{}: download failed: {}", entry.record.id, e));
+                        continue;
+                    }
+                };
+                let path = cache
+                    .save_audio(&entry.record.audio_type, &data, &entry.extension)
+                    .await?;
+                record.local_path = path.to_string_lossy().to_string();
+            }
+        }
+
+        AudioCacheDb::save_audio_record(conn, &record)?;
+        SyncStateDb::mark_synced(conn, &record.id, backend, &entry.content_hash)?;
+        SyncRevisionDb::set(conn, KIND_AUDIO, &record.id, &entry.revision, &remote_fingerprint)?;
+        if let Some((local, _)) = previous.filter(|(local, _)| local.local_path != record.local_path) {
+            cache.delete_audio(Path::new(&local.local_path)).await?;
+        }
+        audio_synced += 1;
+    }
+
+    let mut local_casting: HashMap<String, CharacterVoice> = CharacterVoiceDb::get_character_voices(conn, None)?
+        .into_iter()
+        .map(|mapping| (casting_key(&mapping), mapping))
+        .collect();
+
+    for entry in &manifest.casting {
+        let key = casting_key(&entry.record);
+        let remote_fingerprint = casting_fingerprint(&entry.record);
+
+        let mapping = match local_casting.remove(&key) {
+            None => entry.record.clone(),
+            Some(mut local) => {
+                let fingerprint = casting_fingerprint(&local);
+                let revision = SyncRevisionDb::current(conn, &device_id, KIND_CASTING, &key, &fingerprint)?;
+
+                match compare(&revision, &entry.revision) {
+                    Causality::Equal | Causality::Ahead => continue,
+                    Causality::Behind => {
+                        local.voice_id = entry.record.voice_id.clone();
+                        local.voice_name = entry.record.voice_name.clone();
+                        local
+                    }
+                    Causality::Concurrent if fingerprint == remote_fingerprint => {
+                        let merged = merge(&revision, &entry.revision);
+                        SyncRevisionDb::set(conn, KIND_CASTING, &key, &merged, &fingerprint)?;
+                        continue;
+                    }
+                    Causality::Concurrent => {
+                        let mine = RemoteCastingEntry { record: local, revision };
+                        record_casting_conflict(conn, backend, &mine, entry)?;
+                        conflicts += 1;
+                        continue;
+                    }
+                }
+            }
+        };
+
+        CharacterVoiceDb::save_mapping(conn, &mapping)?;
+        SyncRevisionDb::set(conn, KIND_CASTING, &key, &entry.revision, &remote_fingerprint)?;
+    }
+
+    let mut voices_synced = 0;
+    for voice in &manifest.voices {
+        match VoiceProfileDb::save_voice_profile(conn, voice, &voice.voice_id) {
+            Ok(()) => voices_synced += 1,
+            Err(e) => errors.push(format!("voice {}: {}", voice.voice_id, e)),
+        }
+    }
+
+    Ok(SyncResult {
+        success: errors.is_empty(),
+        voices_synced,
+        audio_synced,
+        conflicts,
+        errors,
+    })
+}
+
+// ========== Tauri Commands ==========
+
+/// Save the S3-compatible backup configuration, storing the secret access key
+/// in the OS keyring
+#[tauri::command]
+pub async fn configure_s3_backup(
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: S3Config,
+    secret_access_key: String,
+) -> Result<(), AudioError> {
+    state
+        .call_db(move |conn| {
+            // Drop the secret for a previously configured endpoint/key
+            if let Some(previous) = SettingsDb::get_setting(conn, S3_CONFIG_KEY)? {
+                if let Ok(previous) = serde_json::from_str::<S3Config>(&previous) {
+                    if previous.secret_name() != config.secret_name() {
+                        secrets::delete_secret(&previous.secret_name())?;
+                    }
+                }
+            }
+
+            secrets::store_secret(&config.secret_name(), &secret_access_key)?;
+
+            SettingsDb::save_setting(conn, S3_CONFIG_KEY, &serde_json::to_string(&config)?)
+        })
+        .await
+}
+
+/// Save the WebDAV sync configuration, storing the password in the OS keyring
+#[tauri::command]
+pub async fn configure_webdav_sync(
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: WebDavConfig,
+    password: String,
+) -> Result<(), AudioError> {
+    state
+        .call_db(move |conn| {
+            // Drop the credential for a previously configured server/user
+            if let Some(previous) = SettingsDb::get_setting(conn, WEBDAV_CONFIG_KEY)? {
+                if let Ok(previous) = serde_json::from_str::<WebDavConfig>(&previous) {
+                    if previous.secret_name() != config.secret_name() {
+                        secrets::delete_secret(&previous.secret_name())?;
+                    }
+                }
+            }
+
+            secrets::store_secret(&config.secret_name(), &password)?;
+
+            SettingsDb::save_setting(conn, WEBDAV_CONFIG_KEY, &serde_json::to_string(&config)?)
+        })
+        .await
+}
+
+/// Back up the audio library to a remote backend
+#[tauri::command]
+pub async fn backup_audio_library(
+    state: State<'_, Arc<ElevenLabsState>>,
+    backend: String,
+) -> Result<SyncResult, AudioError> {
+    let backend = RemoteBackend::parse(&backend)?;
+    let cache = ensure_cache(&state).await?;
+
+    // The backup interleaves database work with network transfers, so it holds
+    // a connection of its own throughout
+    let mut conn = state.db()?.dedicated()?;
+    let remote = open_remote(&conn, backend)?;
+
+    let result = backup_library(&mut conn, &cache, remote.as_ref())
+        .await?;
+
+    webhooks::dispatch(
+        &state,
+        webhooks::EVENT_SYNC_COMPLETED,
+        &serde_json::json!({ "direction": "backup", "backend": remote.backend_name(), "result": result }),
+    );
+    Ok(result)
+}
+
+/// Restore the audio library from a remote backend
+#[tauri::command]
+pub async fn restore_audio_library(
+    state: State<'_, Arc<ElevenLabsState>>,
+    backend: String,
+) -> Result<SyncResult, AudioError> {
+    let backend = RemoteBackend::parse(&backend)?;
+    let cache = ensure_cache(&state).await?;
+
+    // The restore interleaves database work with network transfers, so it holds
+    // a connection of its own throughout
+    let mut conn = state.db()?.dedicated()?;
+    let remote = open_remote(&conn, backend)?;
+
+    let result = restore_library(&mut conn, &cache, remote.as_ref())
+        .await?;
+
+    webhooks::dispatch(
+        &state,
+        webhooks::EVENT_SYNC_COMPLETED,
+        &serde_json::json!({ "direction": "restore", "backend": remote.backend_name(), "result": result }),
+    );
+    Ok(result)
+}
@@ -0,0 +1,810 @@
+// Locale-aware text normalization before synthesis. Numbers, dates, times,
+// currencies, percentages and common abbreviations are spelled out the way a
+// reader of the locale would say them, so pronunciation doesn't depend on how
+// much normalization the provider does. Enabled per project with a
+// `TextNormalizationConfig` and switched per request with
+// `TtsRequest::normalize_text`. SSML tags are left untouched.
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use tauri::State;
+
+use super::cache::{AudioDb, SettingsDb};
+use super::error::AudioError;
+use super::types::TtsRequest;
+use super::ElevenLabsState;
+
+/// Settings key of the global configuration; projects use `text_normalization:<project_id>`
+pub const TEXT_NORMALIZATION_KEY: &str = "text_normalization";
+
+/// Numbers above this are read digit by digit
+const MAX_SPELLED: u64 = 999_999_999_999_999;
+
+/// Locales the normalizer has rules for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Locale {
+    EnUs,
+    EnGb,
+    De,
+}
+
+impl Locale {
+    /// Accepts BCP 47 tags (`en-US`, `de_AT`, `de`); other English regions
+    /// read like en-GB except en-CA, which reads like en-US
+    pub fn parse(tag: &str) -> Result<Self, AudioError> {
+        let tag = tag.trim().replace('_', "-").to_ascii_lowercase();
+        match tag.as_str() {
+            "en" | "en-us" | "en-ca" => Ok(Locale::EnUs),
+            t if t.starts_with("en-") => Ok(Locale::EnGb),
+            t if t == "de" || t.starts_with("de-") => Ok(Locale::De),
+            _ => Err(AudioError::Validation(format!(
+                "Text normalization doesn't support locale {} (supported: en-US, en-GB, de-DE)",
+                tag
+            ))),
+        }
+    }
+}
+
+/// Whether and how text is normalized for a project (or globally)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextNormalizationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_locale")]
+    pub locale: String,
+}
+
+fn default_locale() -> String {
+    "en-US".to_string()
+}
+
+impl Default for TextNormalizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            locale: default_locale(),
+        }
+    }
+}
+
+impl TextNormalizationConfig {
+    fn key(project_id: Option<&str>) -> String {
+        match project_id {
+            Some(project_id) => format!("{}:{}", TEXT_NORMALIZATION_KEY, project_id),
+            None => TEXT_NORMALIZATION_KEY.to_string(),
+        }
+    }
+
+    /// The configuration stored for exactly this scope
+    pub async fn load_scope(db: &AudioDb, project_id: Option<&str>) -> Result<Option<Self>, AudioError> {
+        let key = Self::key(project_id);
+        db.call(move |conn| SettingsDb::get_setting(conn, &key))
+            .await?
+            .map(|json| serde_json::from_str(&json).map_err(AudioError::from))
+            .transpose()
+    }
+
+    /// The project's configuration, falling back to the global one
+    pub async fn load(db: &AudioDb, project_id: Option<&str>) -> Result<Self, AudioError> {
+        if let Some(project_id) = project_id {
+            if let Some(config) = Self::load_scope(db, Some(project_id)).await? {
+                return Ok(config);
+            }
+        }
+        Ok(Self::load_scope(db, None).await?.unwrap_or_default())
+    }
+}
+
+/// Normalize the request's text when its `normalize_text` or, without one,
+/// the project's configuration asks for it
+pub async fn prepare(
+    db: &AudioDb,
+    project_id: Option<&str>,
+    mut request: TtsRequest,
+) -> Result<TtsRequest, AudioError> {
+    if request.normalize_text == Some(false) {
+        return Ok(request);
+    }
+    let config = TextNormalizationConfig::load(db, project_id).await?;
+    if request.normalize_text.unwrap_or(config.enabled) {
+        request.text = normalize(&request.text, Locale::parse(&config.locale)?);
+    }
+    Ok(request)
+}
+
+// ========== Spelling out numbers ==========
+
+const EN_ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve",
+    "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const EN_TENS: [&str; 10] = ["", "ten", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+const EN_SCALES: [(u64, &str); 4] = [
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+const EN_MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November",
+    "December",
+];
+
+const DE_ONES: [&str; 20] = [
+    "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun", "zehn", "elf", "zwölf",
+    "dreizehn", "vierzehn", "fünfzehn", "sechzehn", "siebzehn", "achtzehn", "neunzehn",
+];
+const DE_TENS: [&str; 10] = [
+    "", "zehn", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig", "neunzig",
+];
+const DE_SCALES: [(u64, &str, &str); 3] = [
+    (1_000_000_000_000, "Billion", "Billionen"),
+    (1_000_000_000, "Milliarde", "Milliarden"),
+    (1_000_000, "Million", "Millionen"),
+];
+const DE_MONTHS: [&str; 12] = [
+    "Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober", "November",
+    "Dezember",
+];
+
+fn en_below_hundred(n: u64) -> String {
+    match n {
+        0..=19 => EN_ONES[n as usize].to_string(),
+        _ if n.is_multiple_of(10) => EN_TENS[(n / 10) as usize].to_string(),
+        _ => format!("{}-{}", EN_TENS[(n / 10) as usize], EN_ONES[(n % 10) as usize]),
+    }
+}
+
+fn en_below_thousand(n: u64, british: bool) -> String {
+    match (n / 100, n % 100) {
+        (0, rest) => en_below_hundred(rest),
+        (hundreds, 0) => format!("{} hundred", EN_ONES[hundreds as usize]),
+        (hundreds, rest) => format!(
+            "{} hundred {}{}",
+            EN_ONES[hundreds as usize],
+            if british { "and " } else { "" },
+            en_below_hundred(rest)
+        ),
+    }
+}
+
+fn en_cardinal(n: u64, british: bool) -> String {
+    if n == 0 {
+        return EN_ONES[0].to_string();
+    }
+    let mut parts = Vec::new();
+    let mut rest = n;
+    for (scale, name) in EN_SCALES {
+        if rest >= scale {
+            parts.push(format!("{} {}", en_below_thousand(rest / scale, british), name));
+            rest %= scale;
+        }
+    }
+    if rest > 0 {
+        if british && !parts.is_empty() && rest < 100 {
+            parts.push(format!("and {}", en_below_hundred(rest)));
+        } else {
+            parts.push(en_below_thousand(rest, british));
+        }
+    }
+    parts.join(" ")
+}
+
+fn en_ordinal(n: u64, british: bool) -> String {
+    let cardinal = en_cardinal(n, british);
+    let split = cardinal.rfind([' ', '-']).map_or(0, |i| i + 1);
+    let (head, last) = cardinal.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        word if word.ends_with('y') => format!("{}ieth", &word[..word.len() - 1]),
+        word => format!("{}th", word),
+    };
+    format!("{}{}", head, last)
+}
+
+/// Years as they're said: "nineteen oh five", "two thousand nine", "twenty twenty-four"
+fn en_year(n: u64, british: bool) -> String {
+    match n {
+        2000..=2009 => en_cardinal(n, british),
+        1000..=9999 => {
+            let (century, rest) = (n / 100, n % 100);
+            match rest {
+                0 => format!("{} hundred", en_below_hundred(century)),
+                1..=9 => format!("{} oh {}", en_below_hundred(century), EN_ONES[rest as usize]),
+                _ => format!("{} {}", en_below_hundred(century), en_below_hundred(rest)),
+            }
+        }
+        _ => en_cardinal(n, british),
+    }
+}
+
+fn de_unit(n: u64) -> &'static str {
+    if n == 1 {
+        "ein"
+    } else {
+        DE_ONES[n as usize]
+    }
+}
+
+fn de_below_hundred(n: u64) -> String {
+    match n {
+        0..=19 => DE_ONES[n as usize].to_string(),
+        _ if n.is_multiple_of(10) => DE_TENS[(n / 10) as usize].to_string(),
+        _ => format!("{}und{}", de_unit(n % 10), DE_TENS[(n / 10) as usize]),
+    }
+}
+
+fn de_below_thousand(n: u64) -> String {
+    let mut words = String::new();
+    if n >= 100 {
+        words.push_str(de_unit(n / 100));
+        words.push_str("hundert");
+    }
+    if !n.is_multiple_of(100) || n == 0 {
+        words.push_str(&de_below_hundred(n % 100));
+    }
+    words
+}
+
+/// "eins" as the first part of a compound ("eintausend", "ein Euro")
+fn de_compound(words: String) -> String {
+    match words.strip_suffix("eins") {
+        Some(head) => format!("{}ein", head),
+        None => words,
+    }
+}
+
+fn de_cardinal(n: u64) -> String {
+    if n == 0 {
+        return DE_ONES[0].to_string();
+    }
+    let mut parts = Vec::new();
+    let mut rest = n;
+    for (scale, one, many) in DE_SCALES {
+        if rest >= scale {
+            let count = rest / scale;
+            parts.push(match count {
+                1 => format!("eine {}", one),
+                _ => format!("{} {}", de_compound(de_below_thousand(count)), many),
+            });
+            rest %= scale;
+        }
+    }
+    let mut tail = String::new();
+    if rest >= 1000 {
+        tail.push_str(&de_compound(de_below_thousand(rest / 1000)));
+        tail.push_str("tausend");
+        rest %= 1000;
+    }
+    if rest > 0 {
+        tail.push_str(&de_below_thousand(rest));
+    }
+    if !tail.is_empty() {
+        parts.push(tail);
+    }
+    parts.join(" ")
+}
+
+/// Uninflected ordinal ("erste", "dritte", "zwanzigste")
+fn de_ordinal(n: u64) -> String {
+    let rest = n % 100;
+    let head = if n >= 100 && rest > 0 { de_cardinal(n - rest) } else { String::new() };
+    let last = match rest {
+        0 => return format!("{}ste", de_cardinal(n)),
+        1 => "erste".to_string(),
+        3 => "dritte".to_string(),
+        7 => "siebte".to_string(),
+        8 => "achte".to_string(),
+        2..=19 => format!("{}te", DE_ONES[rest as usize]),
+        _ => format!("{}ste", de_below_hundred(rest)),
+    };
+    format!("{}{}", head, last)
+}
+
+fn de_year(n: u64) -> String {
+    match n {
+        1100..=1999 if n.is_multiple_of(100) => format!("{}hundert", de_below_hundred(n / 100)),
+        1100..=1999 => format!("{}hundert{}", de_below_hundred(n / 100), de_below_hundred(n % 100)),
+        _ => de_cardinal(n),
+    }
+}
+
+impl Locale {
+    fn cardinal(self, n: u64) -> String {
+        match self {
+            Locale::EnUs => en_cardinal(n, false),
+            Locale::EnGb => en_cardinal(n, true),
+            Locale::De => de_cardinal(n),
+        }
+    }
+
+    fn ordinal(self, n: u64) -> String {
+        match self {
+            Locale::EnUs => en_ordinal(n, false),
+            Locale::EnGb => en_ordinal(n, true),
+            Locale::De => de_ordinal(n),
+        }
+    }
+
+    fn year(self, n: u64) -> String {
+        match self {
+            Locale::EnUs => en_year(n, false),
+            Locale::EnGb => en_year(n, true),
+            Locale::De => de_year(n),
+        }
+    }
+
+    fn digit(self, d: u32) -> &'static str {
+        match self {
+            Locale::De => DE_ONES[d as usize],
+            _ => EN_ONES[d as usize],
+        }
+    }
+
+    fn month(self, month: u64) -> Option<&'static str> {
+        let months = match self {
+            Locale::De => &DE_MONTHS,
+            _ => &EN_MONTHS,
+        };
+        months.get((month as usize).checked_sub(1)?).copied()
+    }
+
+    fn thousands_separator(self) -> char {
+        match self {
+            Locale::De => '.',
+            _ => ',',
+        }
+    }
+
+    /// Regex for an integer with optional thousands separators
+    fn integer_pattern(self) -> &'static str {
+        match self {
+            Locale::De => r"\d{1,3}(?:\.\d{3})+|\d+",
+            _ => r"\d{1,3}(?:,\d{3})+|\d+",
+        }
+    }
+
+    /// Regex for the decimal separator
+    fn decimal_pattern(self) -> &'static str {
+        match self {
+            Locale::De => ",",
+            _ => r"\.",
+        }
+    }
+
+    fn digits(self, digits: &str) -> String {
+        digits
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| self.digit(d))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// An integer written with optional separators, read digit by digit when too long
+    fn integer(self, written: &str) -> String {
+        let digits: String = written.chars().filter(|c| *c != self.thousands_separator()).collect();
+        match digits.parse::<u64>() {
+            Ok(n) if n <= MAX_SPELLED => self.cardinal(n),
+            _ => self.digits(&digits),
+        }
+    }
+
+    fn number(self, integer: &str, fraction: Option<&str>) -> String {
+        let words = self.integer(integer);
+        match fraction {
+            Some(fraction) => {
+                let point = if self == Locale::De { "Komma" } else { "point" };
+                format!("{} {} {}", words, point, self.digits(fraction))
+            }
+            None => words,
+        }
+    }
+}
+
+// ========== Rewriting text ==========
+
+/// Rules of one locale, compiled once
+struct Rules {
+    abbreviations: Vec<(Regex, &'static str, bool)>,
+    iso_date: Regex,
+    numeric_date: Regex,
+    month_day: Regex,
+    day_month: Regex,
+    time: Regex,
+    currency_before: Regex,
+    currency_after: Regex,
+    percent: Regex,
+    ordinal: Option<Regex>,
+    number: Regex,
+}
+
+/// Abbreviations with their expansion and whether they may end a sentence
+/// (their period is kept then)
+fn abbreviations(locale: Locale) -> &'static [(&'static str, &'static str, bool)] {
+    match locale {
+        Locale::De => &[
+            (r"\bz\.\s?B\.", "zum Beispiel", false),
+            (r"\bd\.\s?h\.", "das heißt", false),
+            (r"\bu\.\s?a\.", "unter anderem", false),
+            (r"\busw\.", "und so weiter", true),
+            (r"\bbzw\.", "beziehungsweise", false),
+            (r"\bca\.", "circa", false),
+            (r"\bggf\.", "gegebenenfalls", false),
+            (r"\bvgl\.", "vergleiche", false),
+            (r"\binkl\.", "inklusive", false),
+            (r"\bzzgl\.", "zuzüglich", false),
+            (r"\bNr\.", "Nummer", false),
+            (r"\bDr\.", "Doktor", false),
+            (r"\bProf\.", "Professor", false),
+            (r"\bHr\.", "Herr", false),
+        ],
+        _ => &[
+            (r"\be\.g\.", "for example", false),
+            (r"\bi\.e\.", "that is", false),
+            (r"\betc\.", "et cetera", true),
+            (r"\bvs\.", "versus", false),
+            (r"\bapprox\.", "approximately", false),
+            (r"\bDr\.", "Doctor", false),
+            (r"\bMr\.", "Mister", false),
+            (r"\bMrs\.", "Missus", false),
+            (r"\bMs\.", "Miz", false),
+            (r"\bProf\.", "Professor", false),
+            (r"\bJr\.", "Junior", false),
+        ],
+    }
+}
+
+impl Rules {
+    fn new(locale: Locale) -> Self {
+        let integer = locale.integer_pattern();
+        let decimal = locale.decimal_pattern();
+        let months = match locale {
+            Locale::De => DE_MONTHS.join("|"),
+            _ => EN_MONTHS.join("|"),
+        };
+        let regex = |pattern: String| Regex::new(&pattern).unwrap();
+        Rules {
+            abbreviations: abbreviations(locale)
+                .iter()
+                .map(|(pattern, expansion, ends_sentence)| (regex(pattern.to_string()), *expansion, *ends_sentence))
+                .collect(),
+            iso_date: regex(r"\b(\d{4})-(\d{2})-(\d{2})\b".to_string()),
+            numeric_date: regex(match locale {
+                Locale::De => r"\b(\d{1,2})\.(\d{1,2})\.(\d{4})\b".to_string(),
+                _ => r"\b(\d{1,2})/(\d{1,2})/(\d{4})\b".to_string(),
+            }),
+            month_day: regex(format!(r"\b({})\s+(\d{{1,2}})(?:st|nd|rd|th)?\b(?:,?\s+(\d{{4}})\b)?", months)),
+            day_month: regex(match locale {
+                Locale::De => format!(r"\b(\d{{1,2}})\.\s?({})\b(?:\s+(\d{{4}})\b)?", months),
+                _ => format!(r"\b(\d{{1,2}})(?:st|nd|rd|th)?\s+({})\b(?:,?\s+(\d{{4}})\b)?", months),
+            }),
+            time: regex(r"\b([01]?\d|2[0-3]):([0-5]\d)\b".to_string()),
+            currency_before: regex(format!(r"([$€£])\s?({})(?:{}(\d{{1,2}}))?\b", integer, decimal)),
+            currency_after: regex(format!(
+                r"\b({})(?:{}(\d{{1,2}}|-{{1,2}}))?\s?(€|\$|£|(?:EUR|USD|GBP)\b)",
+                integer, decimal
+            )),
+            percent: regex(format!(r"({})(?:{}(\d+))?\s?%", integer, decimal)),
+            ordinal: (locale != Locale::De).then(|| regex(r"\b(\d+)(?:st|nd|rd|th)\b".to_string())),
+            number: regex(format!(r"-?({})(?:{}(\d+))?", integer, decimal)),
+        }
+    }
+
+    fn get(locale: Locale) -> &'static Rules {
+        static EN_US: OnceLock<Rules> = OnceLock::new();
+        static EN_GB: OnceLock<Rules> = OnceLock::new();
+        static DE: OnceLock<Rules> = OnceLock::new();
+        let cell = match locale {
+            Locale::EnUs => &EN_US,
+            Locale::EnGb => &EN_GB,
+            Locale::De => &DE,
+        };
+        cell.get_or_init(|| Rules::new(locale))
+    }
+}
+
+/// Names and units of a currency: singular, plural, minor singular, minor plural
+fn currency_units(locale: Locale, symbol: &str) -> [&'static str; 4] {
+    match (locale, symbol) {
+        (Locale::De, "€" | "EUR") => ["Euro", "Euro", "Cent", "Cent"],
+        (Locale::De, "£" | "GBP") => ["Pfund", "Pfund", "Pence", "Pence"],
+        (Locale::De, _) => ["Dollar", "Dollar", "Cent", "Cent"],
+        (_, "€" | "EUR") => ["euro", "euros", "cent", "cents"],
+        (_, "£" | "GBP") => ["pound", "pounds", "penny", "pence"],
+        (_, _) => ["dollar", "dollars", "cent", "cents"],
+    }
+}
+
+fn currency(locale: Locale, symbol: &str, integer: &str, minor: Option<&str>) -> String {
+    let [one, many, minor_one, minor_many] = currency_units(locale, symbol);
+    let major = integer.chars().filter(|c| c.is_ascii_digit()).collect::<String>();
+    let Ok(major) = major.parse::<u64>() else {
+        return format!("{} {}", locale.integer(integer), many);
+    };
+    // "5" after the separator is fifty cents; "12,-" is a whole amount
+    let minor = match minor.filter(|m| m.chars().all(|c| c.is_ascii_digit())) {
+        Some(m) if m.len() == 1 => m.parse::<u64>().unwrap_or(0) * 10,
+        Some(m) => m.parse::<u64>().unwrap_or(0),
+        None => 0,
+    };
+    let unit = if major == 1 { one } else { many };
+    let minor_unit = if minor == 1 { minor_one } else { minor_many };
+    match locale {
+        Locale::De => {
+            let major_words = de_compound(locale.cardinal(major));
+            match (major, minor) {
+                (0, minor) if minor > 0 => format!("{} {}", de_compound(locale.cardinal(minor)), minor_unit),
+                (_, 0) => format!("{} {}", major_words, unit),
+                _ => format!("{} {} {}", major_words, unit, locale.cardinal(minor)),
+            }
+        }
+        _ => match (major, minor) {
+            (0, minor) if minor > 0 => format!("{} {}", locale.cardinal(minor), minor_unit),
+            (_, 0) => format!("{} {}", locale.cardinal(major), unit),
+            _ => format!("{} {} and {} {}", locale.cardinal(major), unit, locale.cardinal(minor), minor_unit),
+        },
+    }
+}
+
+/// Parse day, month and year, `None` for impossible dates
+fn date_parts(day: &str, month: u64, year: Option<&str>) -> Option<(u64, u64, Option<u64>)> {
+    let day = day.parse::<u64>().ok().filter(|d| (1..=31).contains(d))?;
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    let year = year.map(|y| y.parse::<u64>()).transpose().ok()?;
+    Some((day, month, year))
+}
+
+fn date(locale: Locale, day: u64, month: u64, year: Option<u64>) -> Option<String> {
+    let name = locale.month(month)?;
+    let year = year.map(|y| locale.year(y));
+    Some(match (locale, year) {
+        (Locale::De, Some(year)) => format!("{}r {} {}", locale.ordinal(day), name, year),
+        (Locale::De, None) => format!("{}r {}", locale.ordinal(day), name),
+        (Locale::EnGb, Some(year)) => format!("the {} of {} {}", locale.ordinal(day), name, year),
+        (Locale::EnGb, None) => format!("the {} of {}", locale.ordinal(day), name),
+        (_, Some(year)) => format!("{} {}, {}", name, locale.ordinal(day), year),
+        (_, None) => format!("{} {}", name, locale.ordinal(day)),
+    })
+}
+
+fn month_number(locale: Locale, name: &str) -> u64 {
+    let months = match locale {
+        Locale::De => &DE_MONTHS,
+        _ => &EN_MONTHS,
+    };
+    months.iter().position(|m| *m == name).map_or(0, |i| i as u64 + 1)
+}
+
+fn time(locale: Locale, hour: u64, minute: u64) -> String {
+    match locale {
+        Locale::De if minute == 0 => format!("{} Uhr", de_compound(locale.cardinal(hour))),
+        Locale::De => format!("{} Uhr {}", de_compound(locale.cardinal(hour)), locale.cardinal(minute)),
+        _ if minute == 0 => format!("{} o'clock", locale.cardinal(hour)),
+        _ if minute < 10 => format!("{} oh {}", locale.cardinal(hour), locale.cardinal(minute)),
+        _ => format!("{} {}", locale.cardinal(hour), locale.cardinal(minute)),
+    }
+}
+
+fn char_after(text: &str, index: usize) -> Option<char> {
+    text[index..].chars().next()
+}
+
+fn char_before(text: &str, index: usize) -> Option<char> {
+    text[..index].chars().next_back()
+}
+
+fn normalize_segment(text: &str, locale: Locale) -> String {
+    let rules = Rules::get(locale);
+    let mut text = text.to_string();
+
+    for (pattern, expansion, ends_sentence) in &rules.abbreviations {
+        let source = text.clone();
+        text = pattern
+            .replace_all(&source, |caps: &Captures| {
+                let end = caps.get(0).unwrap().end();
+                let next = source[end..].trim_start().chars().next();
+                if *ends_sentence && next.is_none_or(char::is_uppercase) {
+                    format!("{}.", expansion)
+                } else {
+                    expansion.to_string()
+                }
+            })
+            .into_owned();
+    }
+
+    text = rules
+        .iso_date
+        .replace_all(&text, |caps: &Captures| {
+            let month = caps[2].parse().unwrap_or(0);
+            date_parts(&caps[3], month, Some(&caps[1]))
+                .and_then(|(day, month, year)| date(locale, day, month, year))
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned();
+    text = rules
+        .numeric_date
+        .replace_all(&text, |caps: &Captures| {
+            // en-US writes month/day/year
+            let (day, month) = match locale {
+                Locale::EnUs => (&caps[2], &caps[1]),
+                _ => (&caps[1], &caps[2]),
+            };
+            date_parts(day, month.parse().unwrap_or(0), Some(&caps[3]))
+                .and_then(|(day, month, year)| date(locale, day, month, year))
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned();
+    text = rules
+        .month_day
+        .replace_all(&text, |caps: &Captures| {
+            date_parts(&caps[2], month_number(locale, &caps[1]), caps.get(3).map(|y| y.as_str()))
+                .and_then(|(day, month, year)| date(locale, day, month, year))
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned();
+    text = rules
+        .day_month
+        .replace_all(&text, |caps: &Captures| {
+            date_parts(&caps[1], month_number(locale, &caps[2]), caps.get(3).map(|y| y.as_str()))
+                .and_then(|(day, month, year)| date(locale, day, month, year))
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned();
+
+    text = rules
+        .time
+        .replace_all(&text, |caps: &Captures| {
+            time(locale, caps[1].parse().unwrap_or(0), caps[2].parse().unwrap_or(0))
+        })
+        .into_owned();
+
+    text = rules
+        .currency_before
+        .replace_all(&text, |caps: &Captures| currency(locale, &caps[1], &caps[2], caps.get(3).map(|m| m.as_str())))
+        .into_owned();
+    text = rules
+        .currency_after
+        .replace_all(&text, |caps: &Captures| currency(locale, &caps[3], &caps[1], caps.get(2).map(|m| m.as_str())))
+        .into_owned();
+
+    let percent = if locale == Locale::De { "Prozent" } else { "percent" };
+    text = rules
+        .percent
+        .replace_all(&text, |caps: &Captures| {
+            format!("{} {}", locale.number(&caps[1], caps.get(2).map(|m| m.as_str())), percent)
+        })
+        .into_owned();
+
+    if let Some(ordinal) = &rules.ordinal {
+        text = ordinal
+            .replace_all(&text, |caps: &Captures| match caps[1].parse::<u64>() {
+                Ok(n) if n <= MAX_SPELLED => locale.ordinal(n),
+                _ => caps[0].to_string(),
+            })
+            .into_owned();
+    }
+
+    let source = text.clone();
+    rules
+        .number
+        .replace_all(&source, |caps: &Captures| {
+            let whole = caps.get(0).unwrap();
+            // Part of a word or code ("MP3", "x86"): leave it
+            if char_after(&source, whole.end()).is_some_and(char::is_alphabetic)
+                || char_before(&source, whole.start()).is_some_and(char::is_alphabetic)
+            {
+                return whole.as_str().to_string();
+            }
+            let words = locale.number(&caps[1], caps.get(2).map(|m| m.as_str()));
+            if !whole.as_str().starts_with('-') {
+                return words;
+            }
+            // A dash after a word or number is a hyphen or range, not a sign
+            match char_before(&source, whole.start()) {
+                Some(c) if !c.is_whitespace() && c != '(' => format!("-{}", words),
+                _ => format!("minus {}", words),
+            }
+        })
+        .into_owned()
+}
+
+/// Spell out numbers, dates, times, currencies, percentages and abbreviations
+/// in `text` for `locale`, leaving SSML tags as they are
+pub fn normalize(text: &str, locale: Locale) -> String {
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    let tag = TAG.get_or_init(|| Regex::new(r"<[^<>]*>").unwrap());
+
+    let mut normalized = String::with_capacity(text.len());
+    let mut last = 0;
+    for m in tag.find_iter(text) {
+        normalized.push_str(&normalize_segment(&text[last..m.start()], locale));
+        normalized.push_str(m.as_str());
+        last = m.end();
+    }
+    normalized.push_str(&normalize_segment(&text[last..], locale));
+    normalized
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the normalization configuration stored for a project, or the global one without a project
+#[tauri::command]
+pub async fn get_text_normalization(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: Option<String>,
+) -> Result<Option<TextNormalizationConfig>, AudioError> {
+    TextNormalizationConfig::load_scope(&state.db()?, project_id.as_deref()).await
+}
+
+/// Save the normalization configuration for a project (or globally); `None` removes it
+#[tauri::command]
+pub async fn set_text_normalization(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: Option<String>,
+    config: Option<TextNormalizationConfig>,
+) -> Result<Option<TextNormalizationConfig>, AudioError> {
+    if let Some(config) = &config {
+        Locale::parse(&config.locale)?;
+    }
+    let key = TextNormalizationConfig::key(project_id.as_deref());
+    state
+        .call_db(move |conn| {
+            match &config {
+                Some(config) => SettingsDb::save_setting(conn, &key, &serde_json::to_string(config)?)?,
+                None => SettingsDb::remove_setting(conn, &key)?,
+            }
+            Ok(config)
+        })
+        .await
+}
+
+/// Show how `text` would be normalized for `locale`
+#[tauri::command]
+pub async fn preview_text_normalization(text: String, locale: String) -> Result<String, AudioError> {
+    Ok(normalize(&text, Locale::parse(&locale)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_english() {
+        let us = |text| normalize(text, Locale::EnUs);
+        assert_eq!(us("It costs $12.50, about 1,234 units."), "It costs twelve dollars and fifty cents, about one thousand two hundred thirty-four units.");
+        assert_eq!(us("Due 3/5/2024 at 10:05"), "Due March fifth, twenty twenty-four at ten oh five");
+        assert_eq!(us("Dr. Smith scored 99.5% on the 21st try, etc. Then -3 degrees"), "Doctor Smith scored ninety-nine point five percent on the twenty-first try, et cetera. Then minus three degrees");
+        assert_eq!(us("MP3 files, pages 5-10"), "MP3 files, pages five-ten");
+        assert_eq!(us(r#"Wait 2 secs<break time="1.5s"/>"#), r#"Wait two secs<break time="1.5s"/>"#);
+
+        let gb = |text| normalize(text, Locale::EnGb);
+        assert_eq!(gb("On 3/5/1905 it cost £101"), "On the third of May nineteen oh five it cost one hundred and one pounds");
+    }
+
+    #[test]
+    fn test_normalize_german() {
+        let de = |text| normalize(text, Locale::De);
+        assert_eq!(de("Am 05.03.2024 kostet es 12,50 €."), "Am fünfter März zweitausendvierundzwanzig kostet es zwölf Euro fünfzig.");
+        assert_eq!(de("z. B. 1.001 Stück um 13:00, d. h. 21 Euro"), "zum Beispiel eintausendeins Stück um dreizehn Uhr, das heißt einundzwanzig Euro");
+        assert_eq!(de("Nr. 3,5 und 1999"), "Nummer drei Komma fünf und eintausendneunhundertneunundneunzig");
+        assert_eq!(de_ordinal(101), "einhunderterste");
+        assert_eq!(de_year(1999), "neunzehnhundertneunundneunzig");
+    }
+
+    #[test]
+    fn test_locale_parse() {
+        assert_eq!(Locale::parse("en_GB").unwrap(), Locale::EnGb);
+        assert_eq!(Locale::parse("de-AT").unwrap(), Locale::De);
+        assert!(Locale::parse("fr-FR").is_err());
+    }
+}
@@ -0,0 +1,130 @@
+// Speech-to-text of local audio files (Eleven Labs Scribe), so recorded
+// dialogue can be brought back into text. Each result is kept with its word
+// timings for listing and reuse later.
+
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+
+use super::auth;
+use super::error::AudioError;
+use super::types::*;
+use super::{get_client, ElevenLabsState};
+
+/// Transcriptions returned by `list_transcriptions` when no limit is given
+const DEFAULT_LIST_LIMIT: u32 = 50;
+
+/// A stored transcription of a local audio file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcription {
+    pub id: String,
+    pub source_path: String,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    #[serde(default)]
+    pub language_code: Option<String>,
+    pub text: String,
+    #[serde(default)]
+    pub words: Vec<TranscriptionWord>,
+    pub created_at: String,
+}
+
+fn transcription_from_row(row: &rusqlite::Row) -> rusqlite::Result<Transcription> {
+    let words: String = row.get(5)?;
+    Ok(Transcription {
+        id: row.get(0)?,
+        source_path: row.get(1)?,
+        project_id: row.get(2)?,
+        language_code: row.get(3)?,
+        text: row.get(4)?,
+        words: serde_json::from_str(&words).unwrap_or_default(),
+        created_at: row.get(6)?,
+    })
+}
+
+pub fn save_transcription(conn: &Connection, transcription: &Transcription) -> Result<()> {
+    conn.execute(
+        "INSERT INTO transcriptions (id, source_path, project_id, language_code, text, words, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        (
+            &transcription.id,
+            &transcription.source_path,
+            &transcription.project_id,
+            &transcription.language_code,
+            &transcription.text,
+            serde_json::to_string(&transcription.words)?,
+            &transcription.created_at,
+        ),
+    )?;
+    Ok(())
+}
+
+/// Transcriptions newest first, only those of `project_id` when given
+pub fn get_transcriptions(conn: &Connection, project_id: Option<&str>, limit: u32) -> Result<Vec<Transcription>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, source_path, project_id, language_code, text, words, created_at
+         FROM transcriptions
+         WHERE ?1 IS NULL OR project_id = ?1
+         ORDER BY created_at DESC
+         LIMIT ?2",
+    )?;
+    let transcriptions = stmt
+        .query_map((project_id, limit), transcription_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(transcriptions)
+}
+
+/// Transcribe the audio file at `source_path` and store the result
+pub async fn transcribe(
+    state: &ElevenLabsState,
+    source_path: &str,
+    language: Option<&str>,
+    project_id: Option<&str>,
+) -> Result<Transcription, AudioError> {
+    let path = Path::new(source_path);
+    if !path.is_file() {
+        return Err(AudioError::Validation(format!("No audio file at {}", source_path)));
+    }
+    let client = get_client(state).await?;
+    let result = auth::check(state, client.transcribe_file(path, language).await).await?;
+
+    let transcription = Transcription {
+        id: uuid::Uuid::new_v4().to_string(),
+        source_path: source_path.to_string(),
+        project_id: project_id.map(str::to_string),
+        language_code: result.language_code,
+        text: result.text,
+        words: result.words,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let record = transcription.clone();
+    state.call_db(move |conn| save_transcription(conn, &record)).await?;
+    Ok(transcription)
+}
+
+/// Transcribe a local audio file with word timings; `language` (ISO 639) is
+/// detected when not given
+#[tauri::command]
+pub async fn transcribe_audio_file(
+    state: State<'_, Arc<ElevenLabsState>>,
+    source_path: String,
+    language: Option<String>,
+    project_id: Option<String>,
+) -> Result<Transcription, AudioError> {
+    transcribe(&state, &source_path, language.as_deref(), project_id.as_deref()).await
+}
+
+/// Past transcriptions, newest first
+#[tauri::command]
+pub async fn list_transcriptions(
+    state: State<'_, Arc<ElevenLabsState>>,
+    project_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<Transcription>, AudioError> {
+    state
+        .call_db(move |conn| get_transcriptions(conn, project_id.as_deref(), limit.unwrap_or(DEFAULT_LIST_LIMIT)))
+        .await
+}
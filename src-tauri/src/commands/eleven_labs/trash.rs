@@ -0,0 +1,86 @@
+// The trash for cached audio. Deleting a record from the library only marks it
+// (`audio_cache.deleted_at`), which hides it from listings, search and reuse
+// while keeping its file; it can be restored until a purge removes the file
+// and the record for good.
+
+use std::sync::Arc;
+use tauri::State;
+
+use super::cache::AudioCacheDb;
+use super::error::AudioError;
+use super::pipeline;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Move a record to the trash. Records already in it are left as they are.
+pub async fn trash_audio(state: &ElevenLabsState, audio_id: &str) -> Result<(), AudioError> {
+    let id = audio_id.to_string();
+    state
+        .call_db(move |conn| {
+            if AudioCacheDb::get_audio_record(conn, &id)?.is_none() {
+                return Err(AudioError::Validation(format!("No audio record: {}", id)).into());
+            }
+            AudioCacheDb::trash_audio_record(conn, &id)?;
+            Ok(())
+        })
+        .await
+}
+
+/// Permanently delete the records trashed at least `older_than_days` days ago
+/// and their files, returning how many were removed
+pub async fn purge_trash(state: &ElevenLabsState, older_than_days: u32) -> Result<usize, AudioError> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(i64::from(older_than_days));
+    let expired: Vec<String> = state
+        .call_db(|conn| AudioCacheDb::get_trashed_records(conn, None))
+        .await?
+        .into_iter()
+        .filter(|trashed| {
+            chrono::DateTime::parse_from_rfc3339(&trashed.deleted_at).map_or(true, |at| at <= cutoff)
+        })
+        .map(|trashed| trashed.audio.id)
+        .collect();
+
+    for id in &expired {
+        pipeline::delete_audio(state, id).await?;
+    }
+    if !expired.is_empty() {
+        log::info!("Purged {} audio records from the trash", expired.len());
+    }
+    Ok(expired.len())
+}
+
+/// Cached audio in the trash, most recently deleted first
+#[tauri::command]
+pub async fn list_trashed_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_type: Option<String>,
+) -> Result<Vec<TrashedAudio>, AudioError> {
+    let audio_type = audio_type.as_deref().map(AudioType::parse).transpose()?;
+    state.call_db(move |conn| AudioCacheDb::get_trashed_records(conn, audio_type.as_ref())).await
+}
+
+/// Take a record out of the trash, back into the library
+#[tauri::command]
+pub async fn restore_cached_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
+    audio_id: String,
+) -> Result<GeneratedAudio, AudioError> {
+    state
+        .call_db(move |conn| {
+            if !AudioCacheDb::restore_audio_record(conn, &audio_id)? {
+                return Err(AudioError::Validation(format!("Audio is not in the trash: {}", audio_id)).into());
+            }
+            Ok(AudioCacheDb::get_audio_record(conn, &audio_id)?.expect("record restored above"))
+        })
+        .await
+}
+
+/// Permanently delete audio that has been in the trash for at least
+/// `older_than_days` days (`TRASH_RETENTION_DAYS` by default, 0 for all of it)
+#[tauri::command]
+pub async fn purge_trashed_audio(
+    state: State<'_, Arc<ElevenLabsState>>,
+    older_than_days: Option<u32>,
+) -> Result<usize, AudioError> {
+    purge_trash(&state, older_than_days.unwrap_or(TRASH_RETENTION_DAYS)).await
+}
@@ -0,0 +1,146 @@
+// Text-to-speech over the provider's input-streaming WebSocket, which starts
+// sending audio sooner than the HTTP endpoint. Each stream runs as a background
+// task that can be stopped, passing audio to the frontend as `tts-chunk` events
+// while the complete file is assembled in the cache like any other generation.
+
+use base64::Engine;
+use bytes::Bytes;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::error::AudioError;
+use super::pipeline::{self, ChunkTap, TtsTransport};
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Emitted with a `TtsChunk` for each piece of audio received
+pub const TTS_CHUNK_EVENT: &str = "tts-chunk";
+
+/// Emitted with a `TtsStreamEnd` once a stream finishes, fails or is stopped
+pub const TTS_STREAM_END_EVENT: &str = "tts-stream-end";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsChunk {
+    pub stream_id: String,
+    /// Where `data` starts in the file
+    pub offset: u64,
+    /// Base64 audio bytes
+    pub data: String,
+}
+
+/// The end of a stream: `audio` when it completed, `error` when it failed,
+/// neither when it was stopped
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsStreamEnd {
+    pub stream_id: String,
+    pub audio: Option<Box<GeneratedAudio>>,
+    pub error: Option<String>,
+}
+
+/// A tap emitting each chunk as a `TTS_CHUNK_EVENT`
+fn event_tap(app: AppHandle, stream_id: String) -> ChunkTap {
+    let mut offset = 0u64;
+    Box::new(move |chunk: &Bytes| {
+        let event = TtsChunk {
+            stream_id: stream_id.clone(),
+            offset,
+            data: base64::engine::general_purpose::STANDARD.encode(chunk),
+        };
+        offset += chunk.len() as u64;
+        let _ = app.emit(TTS_CHUNK_EVENT, event);
+    })
+}
+
+/// Start streaming `request` in the background, returning the stream id used
+/// in its events and by `stop_tts_stream`
+pub async fn start(app: &AppHandle, request: TtsRequest, project_id: Option<String>) -> String {
+    let state = app.state::<Arc<ElevenLabsState>>();
+    let stream_id = uuid::Uuid::new_v4().to_string();
+
+    // Held until the handle is stored, so a stream that ends straight away
+    // can't try to remove itself first
+    let mut streams = state.tts_streams.lock().await;
+    let (app, id) = (app.clone(), stream_id.clone());
+    let handle = state.tasks().spawn(format!("tts-stream-{}", stream_id), |shutdown| async move {
+        let state = app.state::<Arc<ElevenLabsState>>();
+        let tap = event_tap(app.clone(), id.clone());
+        let result = tokio::select! {
+            result = pipeline::generate_tts_streamed(
+                &state,
+                request,
+                project_id.as_deref(),
+                tap,
+                TtsTransport::WebSocket,
+            ) => Some(result),
+            _ = shutdown.wait() => None,
+        };
+        state.tts_streams.lock().await.remove(&id);
+
+        let (audio, error) = match result {
+            Some(Ok(audio)) => (Some(Box::new(audio)), None),
+            Some(Err(e)) => {
+                log::warn!("Speech stream {} failed: {}", id, e);
+                (None, Some(e.to_string()))
+            }
+            None => (None, None),
+        };
+        let _ = app.emit(TTS_STREAM_END_EVENT, TtsStreamEnd { stream_id: id, audio, error });
+    });
+    streams.insert(stream_id.clone(), handle);
+    stream_id
+}
+
+/// Stop a stream, discarding the partly written file. Returns whether it was
+/// still running.
+pub async fn stop(app: &AppHandle, stream_id: &str) -> bool {
+    let state = app.state::<Arc<ElevenLabsState>>();
+    let Some(handle) = state.tts_streams.lock().await.remove(stream_id) else {
+        return false;
+    };
+    handle.abort();
+    let end = TtsStreamEnd { stream_id: stream_id.to_string(), audio: None, error: None };
+    let _ = app.emit(TTS_STREAM_END_EVENT, end);
+    true
+}
+
+/// Generate text-to-speech over the streaming WebSocket. Audio arrives as
+/// `tts-chunk` events and the saved record with `tts-stream-end`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn start_tts_stream(
+    app: AppHandle,
+    text: String,
+    voice_id: String,
+    model_id: Option<String>,
+    voice_settings: Option<VoiceSettings>,
+    project_id: Option<String>,
+    normalize_text: Option<bool>,
+    preset: Option<String>,
+) -> Result<String, AudioError> {
+    if text.trim().is_empty() {
+        return Err(AudioError::Validation("Text cannot be empty".to_string()));
+    }
+    let request = TtsRequest {
+        text,
+        voice_id,
+        model_id: model_id.unwrap_or_else(default_model_id),
+        voice_settings,
+        output_format: default_output_format(),
+        normalize_text,
+        preset,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
+    };
+    Ok(start(&app, request, project_id).await)
+}
+
+/// Stop a stream started by `start_tts_stream`
+#[tauri::command]
+pub async fn stop_tts_stream(app: AppHandle, stream_id: String) -> Result<bool, AudioError> {
+    Ok(stop(&app, &stream_id).await)
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+use super::billing::billable_characters;
+use super::error::AudioError;
+
 /// Voice settings for TTS generation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VoiceSettings {
     pub stability: f32,
     pub similarity_boost: f32,
@@ -9,6 +12,9 @@ pub struct VoiceSettings {
     pub style: f32,
     #[serde(default)]
     pub use_speaker_boost: bool,
+    /// Speaking rate, 0.7 to 1.2; the voice's natural pace when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
 }
 
 impl Default for VoiceSettings {
@@ -18,6 +24,7 @@ impl Default for VoiceSettings {
             similarity_boost: 0.75,
             style: 0.0,
             use_speaker_boost: true,
+            speed: None,
         }
     }
 }
@@ -37,6 +44,45 @@ pub struct VoiceProfile {
     pub preview_url: Option<String>,
     #[serde(default)]
     pub settings: VoiceSettings,
+    /// Samples of a cloned voice as the provider reports them; not kept in
+    /// the local cache, so empty for cached profiles
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<VoiceSample>,
+}
+
+/// An uploaded sample of a cloned voice
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoiceSample {
+    pub sample_id: String,
+    #[serde(default)]
+    pub file_name: Option<String>,
+}
+
+/// How a batch of fetched voices was written to the local cache
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VoiceCacheStats {
+    pub inserted: usize,
+    pub updated: usize,
+    /// Profiles that could not be written; the rest of the batch is kept
+    pub failed: usize,
+}
+
+/// Voices for the picker, with the result of caching them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceList {
+    pub voices: Vec<VoiceProfile>,
+    /// `None` when the voices were read from the local cache
+    pub cache: Option<VoiceCacheStats>,
+}
+
+/// A voice recently used for generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentVoice {
+    pub voice_id: String,
+    /// Name from the voice cache; `None` when the voice isn't cached
+    pub name: Option<String>,
+    pub use_count: i64,
+    pub last_used_at: String,
 }
 
 /// Character to voice mapping
@@ -48,6 +94,132 @@ pub struct CharacterVoice {
     pub voice_name: String,
     pub project_id: Option<String>,
     pub created_at: String,
+    /// Voice settings preset to speak the character with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+}
+
+/// Agent to voice mapping, used when narrating or announcing an agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentVoice {
+    pub id: String,
+    pub agent_id: i64,
+    pub voice_id: String,
+    pub voice_name: String,
+    pub created_at: String,
+}
+
+/// Records that still use a voice, reported when deleting it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VoiceReferences {
+    pub characters: Vec<CharacterVoice>,
+    pub agents: Vec<AgentVoice>,
+    /// Ids of cached renders made with the voice
+    pub audio_ids: Vec<String>,
+}
+
+impl VoiceReferences {
+    pub fn is_empty(&self) -> bool {
+        self.characters.is_empty() && self.agents.is_empty() && self.audio_ids.is_empty()
+    }
+}
+
+/// Why a voice clone sample can't be uploaded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum SampleIssue {
+    Unreadable,
+    /// The contents aren't audio in a format the clone endpoint accepts
+    UnsupportedCodec,
+    TooShort { seconds: f64 },
+    /// Over the upload limit and couldn't be split
+    TooLarge { bytes: u64 },
+}
+
+/// A voice clone sample that failed the upload checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleDiagnostic {
+    pub path: String,
+    #[serde(flatten)]
+    pub issue: SampleIssue,
+    pub message: String,
+}
+
+/// Where a resolved voice came from, from most to least specific
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceScope {
+    /// The agent's own voice assignment
+    Agent,
+    /// The project's casting for the character
+    Project,
+    /// The caller's configured default voice
+    Default,
+    /// The global casting for the character (no project)
+    Global,
+    /// The narrator's voice, for a character with no casting of its own
+    Narrator,
+}
+
+/// What is speaking, used to pick a voice
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VoiceContext {
+    #[serde(default)]
+    pub agent_id: Option<i64>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Character to look up in the casting; "Narrator" when not given
+    #[serde(default)]
+    pub character: Option<String>,
+    /// Voice configured by the caller (e.g. the narration settings), used
+    /// before the global casting
+    #[serde(default)]
+    pub default_voice_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedVoice {
+    pub voice_id: String,
+    pub scope: VoiceScope,
+    /// Voice settings preset of the character mapping, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+}
+
+/// A single line of a script; `character` is `None` for narration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptLine {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub character: Option<String>,
+    pub text: String,
+}
+
+/// A named script belonging to a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectScript {
+    pub name: String,
+    #[serde(default)]
+    pub lines: Vec<ScriptLine>,
+}
+
+/// Term -> spoken replacement (alias or phoneme string) applied before synthesis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PronunciationGlossary {
+    pub name: String,
+    #[serde(default)]
+    pub entries: std::collections::BTreeMap<String, String>,
+}
+
+/// Delivery pacing applied when rendering scripts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PacingProfile {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub words_per_minute: Option<u32>,
+    #[serde(default)]
+    pub line_pause_ms: u32,
+    #[serde(default)]
+    pub sentence_pause_ms: u32,
 }
 
 /// Generated audio result
@@ -61,6 +233,273 @@ pub struct GeneratedAudio {
     pub supabase_url: Option<String>,
     pub metadata: serde_json::Value,
     pub created_at: String,
+    /// What the audio was requested with; `None` for imported or edited audio
+    #[serde(default)]
+    pub params: Option<GenerationParams>,
+    /// Returned for an identical earlier request rather than rendered again
+    #[serde(default)]
+    pub cached: bool,
+}
+
+impl GeneratedAudio {
+    /// Voice the audio was rendered with. Records from before `params` was
+    /// stored keep it in `metadata.voice_id`.
+    pub fn voice_id(&self) -> Option<&str> {
+        self.params
+            .as_ref()
+            .and_then(|params| params.voice_id.as_deref())
+            .or_else(|| self.metadata.get("voice_id").and_then(|v| v.as_str()))
+    }
+
+    /// The recording a voice change, isolation or dub was made from
+    pub fn source_recording(&self) -> Option<&str> {
+        [VOICE_CHANGED_FROM_KEY, ISOLATED_FROM_KEY, DUBBED_FROM_KEY]
+            .iter()
+            .find_map(|key| self.metadata.get(*key)?.as_str())
+    }
+
+    /// Characters of text sent to render the audio. Audio made from a
+    /// recording had none; its prompt only names the recording.
+    pub fn text_characters(&self) -> usize {
+        match self.source_recording() {
+            Some(_) => 0,
+            None => billable_characters(&self.prompt),
+        }
+    }
+
+    /// Id of the first take in this record's group of takes
+    pub fn take_root(&self) -> &str {
+        self.metadata
+            .get(TAKE_OF_KEY)
+            .and_then(|v| v.as_str())
+            .unwrap_or(&self.id)
+    }
+}
+
+/// Provider recorded for generations made through the Eleven Labs API
+pub const PROVIDER_ELEVENLABS: &str = "elevenlabs";
+
+/// Request parameters a generation was made with, stored with its record so
+/// it can be audited, searched and regenerated exactly
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationParams {
+    pub provider: String,
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub voice_id: Option<String>,
+    #[serde(default)]
+    pub voice_settings: Option<VoiceSettings>,
+    #[serde(default)]
+    pub output_format: Option<String>,
+    #[serde(default)]
+    pub seed: Option<u32>,
+    /// ISO 639-1 language code, when one was requested
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Requested length of sound effects and music
+    #[serde(default)]
+    pub duration_seconds: Option<f32>,
+    #[serde(default)]
+    pub prompt_influence: Option<f32>,
+}
+
+impl GenerationParams {
+    pub fn new(provider: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            model_id: None,
+            voice_id: None,
+            voice_settings: None,
+            output_format: None,
+            seed: None,
+            language: None,
+            duration_seconds: None,
+            prompt_influence: None,
+        }
+    }
+
+    pub fn from_tts(request: &TtsRequest) -> Self {
+        Self {
+            model_id: Some(request.model_id.clone()),
+            voice_id: Some(request.voice_id.clone()),
+            voice_settings: request.voice_settings.clone(),
+            output_format: Some(request.output_format.clone()),
+            seed: request.seed,
+            ..Self::new(PROVIDER_ELEVENLABS)
+        }
+    }
+
+    pub fn from_sfx(request: &SfxRequest) -> Self {
+        Self {
+            duration_seconds: Some(request.duration_seconds),
+            prompt_influence: Some(request.prompt_influence),
+            ..Self::new(PROVIDER_ELEVENLABS)
+        }
+    }
+
+    pub fn from_music(request: &MusicRequest) -> Self {
+        Self {
+            model_id: Some(MUSIC_MODEL_ID.to_string()),
+            duration_seconds: Some(request.duration_seconds),
+            ..Self::new(PROVIDER_ELEVENLABS)
+        }
+    }
+
+    /// The TTS request these parameters describe for `text`; `None` without a voice
+    pub fn to_tts(&self, text: String) -> Option<TtsRequest> {
+        Some(TtsRequest {
+            text,
+            voice_id: self.voice_id.clone()?,
+            model_id: self.model_id.clone().unwrap_or_else(default_model_id),
+            voice_settings: self.voice_settings.clone(),
+            output_format: self.output_format.clone().unwrap_or_else(default_output_format),
+            normalize_text: None,
+            preset: None,
+            previous_text: None,
+            next_text: None,
+            previous_request_ids: Vec::new(),
+            seed: self.seed,
+        })
+    }
+
+    /// The sound effect request these parameters describe for `text`
+    pub fn to_sfx(&self, text: String) -> SfxRequest {
+        SfxRequest {
+            text,
+            duration_seconds: self.duration_seconds.unwrap_or_else(default_sfx_duration),
+            prompt_influence: self.prompt_influence.unwrap_or_else(default_prompt_influence),
+        }
+    }
+}
+
+/// Metadata key holding the provider's id for the request that rendered a
+/// record, for stitching later generations onto it
+pub const REQUEST_ID_KEY: &str = "request_id";
+
+/// Metadata key listing the `SpeechChunk`s of speech too long for one request
+pub const CHUNKS_KEY: &str = "chunks";
+
+/// One request's share of speech rendered in several
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeechChunk {
+    pub text: String,
+    pub request_id: Option<String>,
+    /// Where the chunk's audio starts in the file, and its length
+    pub offset: u64,
+    pub bytes: u64,
+}
+
+/// Metadata key holding the seed a record was rendered with
+pub const SEED_KEY: &str = "seed";
+
+/// Metadata key naming the first take of a group of regenerated takes
+pub const TAKE_OF_KEY: &str = "take_of";
+
+/// Metadata key holding the recording a speech-to-speech record was made from
+pub const VOICE_CHANGED_FROM_KEY: &str = "voice_changed_from";
+
+/// Metadata key holding the file a dubbing record was translated from
+pub const DUBBED_FROM_KEY: &str = "dubbed_from";
+
+/// Metadata key holding the recording an audio isolation record was cleaned from
+pub const ISOLATED_FROM_KEY: &str = "isolated_from";
+
+/// Metadata key holding the `SpeechAlignment` of a record rendered with timestamps
+pub const ALIGNMENT_KEY: &str = "alignment";
+
+/// Changes to apply when regenerating a record; unset fields keep the stored values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegenerateOverrides {
+    #[serde(default)]
+    pub voice_id: Option<String>,
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub voice_settings: Option<VoiceSettings>,
+    #[serde(default)]
+    pub output_format: Option<String>,
+    #[serde(default)]
+    pub duration_seconds: Option<f32>,
+    #[serde(default)]
+    pub prompt_influence: Option<f32>,
+    /// Seed for the new take. Unlike the other fields it isn't taken from the
+    /// original, so each take differs unless one is given.
+    #[serde(default)]
+    pub seed: Option<u32>,
+}
+
+impl RegenerateOverrides {
+    pub fn apply(self, params: GenerationParams) -> GenerationParams {
+        GenerationParams {
+            voice_id: self.voice_id.or(params.voice_id),
+            model_id: self.model_id.or(params.model_id),
+            voice_settings: self.voice_settings.or(params.voice_settings),
+            output_format: self.output_format.or(params.output_format),
+            duration_seconds: self.duration_seconds.or(params.duration_seconds),
+            prompt_influence: self.prompt_influence.or(params.prompt_influence),
+            seed: self.seed,
+            ..params
+        }
+    }
+}
+
+/// Order of a cached audio listing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioSort {
+    #[default]
+    Newest,
+    Oldest,
+    Longest,
+    Shortest,
+    /// Alphabetically by prompt
+    Prompt,
+}
+
+/// What a batch delete removed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchDeleteResult {
+    /// Records deleted or moved to the trash
+    pub deleted: usize,
+    /// Size of the files removed with them; 0 when they went to the trash
+    pub bytes_freed: u64,
+}
+
+/// Days deleted audio stays in the trash before a purge removes it
+pub const TRASH_RETENTION_DAYS: u32 = 30;
+
+/// A record in the trash and when it was put there
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedAudio {
+    #[serde(flatten)]
+    pub audio: GeneratedAudio,
+    pub deleted_at: String,
+}
+
+/// Filters, order and paging for listing cached audio; unset filters match
+/// everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioQuery {
+    #[serde(default)]
+    pub voice_id: Option<String>,
+    /// RFC 3339 bounds on when the audio was created, inclusive
+    #[serde(default)]
+    pub created_after: Option<String>,
+    #[serde(default)]
+    pub created_before: Option<String>,
+    /// Only audio carrying this tag, in any case
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub favorites_only: bool,
+    #[serde(default)]
+    pub sort: AudioSort,
+    /// Most records to return; all of them when unset
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: Option<u32>,
 }
 
 /// Type of generated audio
@@ -72,6 +511,18 @@ pub enum AudioType {
     Music,
 }
 
+impl AudioType {
+    /// Parse a user-supplied audio type name ("tts", "sfx", "music")
+    pub fn parse(value: &str) -> Result<Self, AudioError> {
+        match value.to_lowercase().as_str() {
+            "tts" => Ok(AudioType::Tts),
+            "sfx" => Ok(AudioType::Sfx),
+            "music" => Ok(AudioType::Music),
+            _ => Err(AudioError::Validation("Invalid audio type".to_string())),
+        }
+    }
+}
+
 /// TTS request parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TtsRequest {
@@ -83,16 +534,193 @@ pub struct TtsRequest {
     pub voice_settings: Option<VoiceSettings>,
     #[serde(default = "default_output_format")]
     pub output_format: String,
+    /// Spell out numbers, dates and abbreviations before synthesis (see
+    /// `text_normalize`); `None` follows the project's setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalize_text: Option<bool>,
+    /// Named voice settings (see `presets`) used when `voice_settings` is unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// Text spoken just before this, so the prosody carries on from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_text: Option<String>,
+    /// Text spoken just after this, so the prosody leads into it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_text: Option<String>,
+    /// Provider request ids of the generations just before this one (at most
+    /// `MAX_PREVIOUS_REQUEST_IDS`, oldest first), stored under `REQUEST_ID_KEY`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_request_ids: Vec<String>,
+    /// Makes the rendering repeatable: the same request with the same seed
+    /// should sound the same, though the provider doesn't guarantee it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
 }
 
-fn default_model_id() -> String {
+/// Most previous request ids the provider takes for one generation
+pub const MAX_PREVIOUS_REQUEST_IDS: usize = 3;
+
+pub(crate) fn default_model_id() -> String {
     "eleven_monolingual_v1".to_string()
 }
 
-fn default_output_format() -> String {
+pub(crate) fn default_output_format() -> String {
     "mp3_44100_128".to_string()
 }
 
+pub(crate) fn default_sts_model_id() -> String {
+    "eleven_multilingual_sts_v2".to_string()
+}
+
+/// Speech-to-speech (voice changer) request: a recording re-spoken in another voice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceChangeRequest {
+    /// Path of the recording to convert
+    pub source_path: String,
+    pub voice_id: String,
+    #[serde(default = "default_sts_model_id")]
+    pub model_id: String,
+    #[serde(default)]
+    pub voice_settings: Option<VoiceSettings>,
+    #[serde(default = "default_output_format")]
+    pub output_format: String,
+    /// Strip background noise from the recording before converting it
+    #[serde(default)]
+    pub remove_background_noise: bool,
+}
+
+/// Dubbing request: a video or audio file translated into another language
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DubbingRequest {
+    pub source_path: String,
+    /// ISO 639-1 code of the language to dub into
+    pub target_lang: String,
+    /// Language spoken in the source; detected when unset
+    #[serde(default)]
+    pub source_lang: Option<String>,
+    /// Speakers in the source; detected when unset
+    #[serde(default)]
+    pub num_speakers: Option<u32>,
+}
+
+/// The provider's answer to a dubbing submission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DubbingSubmission {
+    pub dubbing_id: String,
+    /// Rough time the provider expects the dub to take
+    #[serde(default)]
+    pub expected_duration_sec: Option<f32>,
+}
+
+/// State of a dubbing project on the provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DubbingProject {
+    pub dubbing_id: String,
+    /// `dubbing`, `dubbed` or `failed`
+    pub status: String,
+    #[serde(default)]
+    pub target_languages: Vec<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A word (or spacing/sound event) of a transcript with its timing in seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionWord {
+    pub text: String,
+    pub start: f32,
+    pub end: f32,
+    /// `word`, `spacing` or `audio_event`
+    #[serde(rename = "type", default = "default_word_type")]
+    pub word_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<String>,
+}
+
+fn default_word_type() -> String {
+    "word".to_string()
+}
+
+/// When each character of rendered speech starts and ends, in seconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CharacterAlignment {
+    pub characters: Vec<String>,
+    pub character_start_times_seconds: Vec<f32>,
+    pub character_end_times_seconds: Vec<f32>,
+}
+
+impl CharacterAlignment {
+    /// The characters grouped into words at whitespace
+    pub fn words(&self) -> Vec<WordTiming> {
+        let timed = self
+            .characters
+            .iter()
+            .zip(&self.character_start_times_seconds)
+            .zip(&self.character_end_times_seconds);
+        let mut words = Vec::new();
+        let mut current: Option<WordTiming> = None;
+        for (index, ((character, &start), &end)) in timed.enumerate() {
+            if character.trim().is_empty() {
+                words.extend(current.take());
+                continue;
+            }
+            match &mut current {
+                Some(word) => {
+                    word.word.push_str(character);
+                    word.end = end;
+                    word.char_end = index + 1;
+                }
+                None => {
+                    current = Some(WordTiming {
+                        word: character.clone(),
+                        start,
+                        end,
+                        char_start: index,
+                        char_end: index + 1,
+                    })
+                }
+            }
+        }
+        words.extend(current);
+        words
+    }
+}
+
+/// A word of rendered speech with its timing in seconds, and where it is in
+/// the text as a range of character indexes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub word: String,
+    pub start: f32,
+    pub end: f32,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+/// Timing of rendered speech, for highlighting the text as it is spoken
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpeechAlignment {
+    pub characters: CharacterAlignment,
+    pub words: Vec<WordTiming>,
+}
+
+impl From<CharacterAlignment> for SpeechAlignment {
+    fn from(characters: CharacterAlignment) -> Self {
+        Self { words: characters.words(), characters }
+    }
+}
+
+/// Speech-to-text output with word timings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionResult {
+    pub text: String,
+    /// ISO 639 code of the detected (or requested) language
+    #[serde(default)]
+    pub language_code: Option<String>,
+    #[serde(default)]
+    pub words: Vec<TranscriptionWord>,
+}
+
 /// Sound effects request parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SfxRequest {
@@ -127,6 +755,25 @@ fn default_music_duration() -> f32 {
     30.0
 }
 
+/// Model used for music generation
+pub const MUSIC_MODEL_ID: &str = "music_v1";
+
+/// Shortest and longest music the provider composes, in seconds
+pub const MUSIC_DURATION_RANGE: std::ops::RangeInclusive<f32> = 10.0..=300.0;
+
+impl MusicRequest {
+    /// The prompt sent to the provider, with the style and mood appended
+    pub fn full_prompt(&self) -> String {
+        let mut prompt = self.prompt.trim().to_string();
+        for (label, value) in [("Style", &self.style), ("Mood", &self.mood)] {
+            if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                prompt.push_str(&format!("\n{}: {}", label, value));
+            }
+        }
+        prompt
+    }
+}
+
 /// Voice clone request parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceCloneRequest {
@@ -136,6 +783,217 @@ pub struct VoiceCloneRequest {
     pub files: Vec<String>, // File paths
 }
 
+/// Voice design (text-to-voice) request: candidate voices generated from a description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceDesignRequest {
+    /// What the voice should sound like, e.g. "a gravelly old sea captain"
+    pub voice_description: String,
+    /// Text the previews speak; generated by the provider when unset
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+/// A candidate voice from voice design, not yet saved to the account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceDesignPreview {
+    pub generated_voice_id: String,
+    /// Base64 audio of the preview text in this voice
+    pub audio_base_64: String,
+    #[serde(default)]
+    pub media_type: Option<String>,
+    #[serde(default)]
+    pub duration_secs: Option<f32>,
+}
+
+/// Candidate voices for a description and the text they speak
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceDesignPreviews {
+    pub previews: Vec<VoiceDesignPreview>,
+    #[serde(default)]
+    pub text: String,
+}
+
+/// Saves a voice design preview to the account as a new voice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesignedVoiceRequest {
+    pub name: String,
+    pub description: String,
+    pub generated_voice_id: String,
+    #[serde(default)]
+    pub labels: Option<serde_json::Value>,
+}
+
+/// Changes to an existing voice; unset fields keep their current values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VoiceEditRequest {
+    pub voice_id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub labels: Option<serde_json::Value>,
+    /// Sample files to upload
+    #[serde(default)]
+    pub add_files: Vec<String>,
+    /// Samples to delete, by `sample_id`
+    #[serde(default)]
+    pub remove_sample_ids: Vec<String>,
+}
+
+/// An audio sample copied into the managed voice source directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloneSource {
+    pub id: String,
+    pub voice_name: String,
+    pub voice_id: Option<String>,
+    pub original_path: String,
+    pub managed_path: String,
+    pub content_hash: String,
+    pub size_bytes: i64,
+    pub imported_at: String,
+}
+
+/// Filters for searching the shared (community) voice library; unset ones match anything
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedVoiceFilter {
+    /// Free text matched against names and descriptions
+    #[serde(default)]
+    pub search: Option<String>,
+    /// `male`, `female` or `neutral`
+    #[serde(default)]
+    pub gender: Option<String>,
+    /// e.g. `british`, `american`
+    #[serde(default)]
+    pub accent: Option<String>,
+    /// e.g. `narrative_story`, `conversational`
+    #[serde(default)]
+    pub use_case: Option<String>,
+    /// ISO 639-1 code
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Zero-based page
+    #[serde(default)]
+    pub page: u32,
+    #[serde(default = "default_shared_page_size")]
+    pub page_size: u32,
+}
+
+fn default_shared_page_size() -> u32 {
+    30
+}
+
+impl Default for SharedVoiceFilter {
+    fn default() -> Self {
+        Self {
+            search: None,
+            gender: None,
+            accent: None,
+            use_case: None,
+            language: None,
+            page: 0,
+            page_size: default_shared_page_size(),
+        }
+    }
+}
+
+/// A voice in the shared library, not necessarily in the account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedVoice {
+    /// Owner of the voice, needed to add it to the account
+    pub public_owner_id: String,
+    pub voice_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub gender: Option<String>,
+    #[serde(default)]
+    pub accent: Option<String>,
+    #[serde(default)]
+    pub age: Option<String>,
+    #[serde(default)]
+    pub use_case: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub preview_url: Option<String>,
+    /// How many accounts have added the voice
+    #[serde(default)]
+    pub cloned_by_count: u64,
+}
+
+/// One page of shared library search results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedVoicePage {
+    pub voices: Vec<SharedVoice>,
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+/// A generation kept in the provider's history, from any machine using the key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryItem {
+    pub history_item_id: String,
+    #[serde(default)]
+    pub voice_id: Option<String>,
+    #[serde(default)]
+    pub voice_name: Option<String>,
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub text: String,
+    /// Unix seconds
+    #[serde(default)]
+    pub date_unix: i64,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Voice settings as the provider reports them
+    #[serde(default)]
+    pub settings: Option<serde_json::Value>,
+}
+
+/// One page of history, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub history: Vec<HistoryItem>,
+    /// Pass as `start_after` to get the next page
+    #[serde(default)]
+    pub last_history_item_id: Option<String>,
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+/// Metadata key holding the provider history item a record was downloaded from
+pub const HISTORY_ITEM_KEY: &str = "history_item_id";
+
+/// A language a model can speak
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelLanguage {
+    pub language_id: String,
+    pub name: String,
+}
+
+/// An Eleven Labs model and what it can be used for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub model_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Usable for text-to-speech
+    #[serde(default)]
+    pub can_do_text_to_speech: bool,
+    /// Usable for speech-to-speech
+    #[serde(default)]
+    pub can_do_voice_conversion: bool,
+    #[serde(default)]
+    pub languages: Vec<ModelLanguage>,
+    /// Longest text accepted in one request, if the provider reports it
+    #[serde(default)]
+    pub maximum_text_length_per_request: Option<u32>,
+}
+
 /// Eleven Labs API usage info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageInfo {
@@ -157,9 +1015,33 @@ pub struct SyncResult {
     pub success: bool,
     pub voices_synced: i32,
     pub audio_synced: i32,
+    /// Records edited concurrently on another machine, kept for manual resolution
+    #[serde(default)]
+    pub conflicts: i32,
     pub errors: Vec<String>,
 }
 
+/// Edit counters per device; see `conflicts::compare`
+pub type RevisionVector = std::collections::BTreeMap<String, u64>;
+
+/// A record edited on two machines since their last sync. Both versions are kept
+/// until the user picks one with `resolve_sync_conflict`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub id: String,
+    /// `audio` or `casting`
+    pub kind: String,
+    /// Audio id, or `project/character` for casting
+    pub record_id: String,
+    pub backend: String,
+    pub local: serde_json::Value,
+    /// For audio, `record.local_path` points at the downloaded remote copy
+    pub remote: serde_json::Value,
+    pub local_revision: RevisionVector,
+    pub remote_revision: RevisionVector,
+    pub detected_at: String,
+}
+
 /// API response wrapper for voices list
 #[derive(Debug, Deserialize)]
 pub struct VoicesResponse {
@@ -181,6 +1063,8 @@ pub struct ElevenLabsVoice {
     pub preview_url: Option<String>,
     #[serde(default)]
     pub settings: Option<ElevenLabsVoiceSettings>,
+    #[serde(default)]
+    pub samples: Option<Vec<VoiceSample>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -202,6 +1086,7 @@ impl From<ElevenLabsVoice> for VoiceProfile {
             similarity_boost: s.similarity_boost.unwrap_or(0.75),
             style: s.style.unwrap_or(0.0),
             use_speaker_boost: s.use_speaker_boost.unwrap_or(true),
+            speed: None,
         }).unwrap_or_default();
 
         VoiceProfile {
@@ -212,6 +1097,7 @@ impl From<ElevenLabsVoice> for VoiceProfile {
             labels: voice.labels,
             preview_url: voice.preview_url,
             settings,
+            samples: voice.samples.unwrap_or_default(),
         }
     }
 }
@@ -0,0 +1,164 @@
+// Local record of characters and credits consumed per day and provider. The
+// provider only reports a rolling total for the current billing period; this
+// keeps every day so charts can go back further and months can be compared.
+// Updated after each generation is saved.
+
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+
+use super::error::AudioError;
+use super::report::characters_billed;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Size of the buckets returned by `get_usage_history`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroupBy {
+    #[default]
+    Day,
+    /// Weeks starting on Monday
+    Week,
+    Month,
+}
+
+impl UsageGroupBy {
+    /// SQL expression for the bucket of the `day` column
+    fn bucket_sql(&self) -> &'static str {
+        match self {
+            UsageGroupBy::Day => "day",
+            UsageGroupBy::Week => "date(day, '-6 days', 'weekday 1')",
+            UsageGroupBy::Month => "substr(day, 1, 7)",
+        }
+    }
+}
+
+/// Days to include, as `YYYY-MM-DD`, both ends inclusive; open ends are unbounded
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageRange {
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+/// Consumption of one provider in one bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageHistoryEntry {
+    /// `YYYY-MM-DD` for days, the Monday for weeks, `YYYY-MM` for months
+    pub period: String,
+    pub provider: String,
+    pub generations: i64,
+    /// Characters of text sent
+    pub characters: i64,
+    /// Credits billed, which differ from characters for sound effects
+    pub credits: i64,
+}
+
+/// Add a saved generation to today's totals
+pub fn record(conn: &Connection, audio: &GeneratedAudio) -> Result<()> {
+    let provider = audio.params.as_ref().map_or(PROVIDER_ELEVENLABS, |p| p.provider.as_str());
+    conn.prepare_cached(
+        "INSERT INTO usage_history (day, provider, audio_type, generations, characters, credits)
+         VALUES (?1, ?2, ?3, 1, ?4, ?5)
+         ON CONFLICT(day, provider, audio_type) DO UPDATE SET
+             generations = generations + 1,
+             characters = characters + excluded.characters,
+             credits = credits + excluded.credits",
+    )?
+    .execute((
+        chrono::Local::now().format("%Y-%m-%d").to_string(),
+        provider,
+        serde_json::to_string(&audio.audio_type)?,
+        audio.text_characters() as i64,
+        characters_billed(audio),
+    ))?;
+    Ok(())
+}
+
+pub fn get_history(conn: &Connection, range: &UsageRange, group_by: UsageGroupBy) -> Result<Vec<UsageHistoryEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {bucket} AS period, provider, SUM(generations), SUM(characters), SUM(credits)
+         FROM usage_history
+         WHERE (?1 IS NULL OR day >= ?1) AND (?2 IS NULL OR day <= ?2)
+         GROUP BY period, provider
+         ORDER BY period, provider",
+        bucket = group_by.bucket_sql()
+    ))?;
+    let entries = stmt
+        .query_map((&range.from, &range.to), |row| {
+            Ok(UsageHistoryEntry {
+                period: row.get(0)?,
+                provider: row.get(1)?,
+                generations: row.get(2)?,
+                characters: row.get(3)?,
+                credits: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+}
+
+/// Characters and credits consumed per provider, bucketed by day, week or month
+#[tauri::command]
+pub async fn get_usage_history(
+    state: State<'_, Arc<ElevenLabsState>>,
+    range: Option<UsageRange>,
+    group_by: Option<UsageGroupBy>,
+) -> Result<Vec<UsageHistoryEntry>, AudioError> {
+    let range = range.unwrap_or_default();
+    state
+        .call_db(move |conn| get_history(conn, &range, group_by.unwrap_or_default()))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::eleven_labs::schema;
+
+    #[test]
+    fn test_history_buckets() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO usage_history (day, provider, audio_type, generations, characters, credits) VALUES
+                 ('2024-02-28', 'elevenlabs', '\"tts\"', 2, 100, 100),
+                 ('2024-03-04', 'elevenlabs', '\"tts\"', 1, 50, 50),
+                 ('2024-03-06', 'elevenlabs', '\"sfx\"', 1, 10, 80),
+                 ('2024-03-06', 'other', '\"tts\"', 1, 5, 5);",
+        )
+        .unwrap();
+
+        let months = get_history(&conn, &UsageRange::default(), UsageGroupBy::Month).unwrap();
+        let summary: Vec<_> = months.iter().map(|e| (e.period.as_str(), e.provider.as_str(), e.credits)).collect();
+        assert_eq!(summary, vec![("2024-02", "elevenlabs", 100), ("2024-03", "elevenlabs", 130), ("2024-03", "other", 5)]);
+
+        // 2024-03-04 is a Monday
+        let range = UsageRange { from: Some("2024-03-01".to_string()), to: None };
+        let weeks = get_history(&conn, &range, UsageGroupBy::Week).unwrap();
+        assert_eq!(weeks[0].period, "2024-03-04");
+        assert_eq!(weeks[0].characters, 60);
+
+        let audio = GeneratedAudio {
+            id: "a".to_string(),
+            audio_type: AudioType::Tts,
+            prompt: "Hello".to_string(),
+            duration_seconds: 1.0,
+            local_path: String::new(),
+            supabase_url: None,
+            metadata: serde_json::json!({}),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            params: None,
+            cached: false,
+        };
+        record(&conn, &audio).unwrap();
+        record(&conn, &audio).unwrap();
+        let today = UsageRange { from: Some(chrono::Local::now().format("%Y-%m-%d").to_string()), to: None };
+        let days = get_history(&conn, &today, UsageGroupBy::Day).unwrap();
+        assert_eq!((days[0].generations, days[0].characters), (2, 10));
+    }
+}
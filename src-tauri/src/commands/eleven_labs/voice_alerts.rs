@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+use super::cache::{AudioCacheDb, AudioDb, SettingsDb};
+use super::error::AudioError;
+use super::narration::{AgentNarrationConfig, ProjectNarrationConfig};
+use super::pipeline;
+use super::speech_queue::SpeechQueue;
+use super::types::*;
+use super::ElevenLabsState;
+
+/// Settings key holding the voice alert configuration
+pub const VOICE_ALERTS_KEY: &str = "voice_alerts";
+
+/// Settings key prefix mapping a rendered phrase to its cached audio id
+const RENDERED_PREFIX: &str = "voice_alert_audio:";
+
+/// Spoken announcements when agent runs end
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceAlertConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// `{agent}` is replaced with the agent name
+    #[serde(default = "default_completed_phrase")]
+    pub completed_phrase: String,
+    #[serde(default = "default_failed_phrase")]
+    pub failed_phrase: String,
+    /// Agents that never announce
+    #[serde(default)]
+    pub muted_agents: Vec<i64>,
+    /// Voice for agents without an assigned voice; defaults to the narration voice
+    #[serde(default)]
+    pub voice_id: Option<String>,
+}
+
+fn default_completed_phrase() -> String {
+    "{agent} finished".to_string()
+}
+
+fn default_failed_phrase() -> String {
+    "{agent} failed".to_string()
+}
+
+impl Default for VoiceAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            completed_phrase: default_completed_phrase(),
+            failed_phrase: default_failed_phrase(),
+            muted_agents: vec![],
+            voice_id: None,
+        }
+    }
+}
+
+impl VoiceAlertConfig {
+    pub async fn load(db: &AudioDb) -> Result<Self, AudioError> {
+        match db.call(|conn| SettingsDb::get_setting(conn, VOICE_ALERTS_KEY)).await? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn phrase(&self, agent_name: &str, success: bool) -> String {
+        let template = if success { &self.completed_phrase } else { &self.failed_phrase };
+        template.replace("{agent}", agent_name)
+    }
+}
+
+fn rendered_key(voice_id: &str, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(voice_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(text.as_bytes());
+    format!("{}{:x}", RENDERED_PREFIX, hasher.finalize())
+}
+
+/// A previously rendered copy of the phrase whose file still exists
+async fn cached_render(db: &AudioDb, key: String) -> Result<Option<GeneratedAudio>, AudioError> {
+    db.call(move |conn| {
+        let audio = match SettingsDb::get_setting(conn, &key)? {
+            Some(audio_id) => AudioCacheDb::get_audio_record(conn, &audio_id)?,
+            None => None,
+        };
+        Ok(audio.filter(|audio| Path::new(&audio.local_path).exists()))
+    })
+    .await
+}
+
+/// Render a phrase once and reuse the cached file afterwards
+async fn render_phrase(app: &AppHandle, voice_id: String, text: String) -> Result<GeneratedAudio, AudioError> {
+    let state = app.state::<Arc<ElevenLabsState>>();
+    let db = state.db()?;
+    let key = rendered_key(&voice_id, &text);
+    if let Some(audio) = cached_render(&db, key.clone()).await? {
+        return Ok(audio);
+    }
+
+    let request = TtsRequest {
+        text,
+        voice_id,
+        model_id: default_model_id(),
+        voice_settings: None,
+        output_format: default_output_format(),
+        normalize_text: None,
+        preset: None,
+        previous_text: None,
+        next_text: None,
+        previous_request_ids: Vec::new(),
+        seed: None,
+    };
+    let audio = pipeline::generate_tts(&state, request).await?;
+
+    let audio_id = audio.id.clone();
+    db.call(move |conn| SettingsDb::save_setting(conn, &key, &audio_id)).await?;
+    Ok(audio)
+}
+
+/// Agent completion hook: announce the result unless alerts are off for this
+/// agent or voice output is disabled for the project
+pub async fn on_agent_finished(app: &AppHandle, agent_id: i64, agent_name: &str, project_path: &str, success: bool) {
+    let db = match app.state::<Arc<ElevenLabsState>>().db() {
+        Ok(db) => db,
+        Err(e) => {
+            log::warn!("Failed to open the audio database: {}", e);
+            return;
+        }
+    };
+    let config = match VoiceAlertConfig::load(&db).await {
+        Ok(config) => ProjectNarrationConfig::allows_speech(&db, project_path)
+            .await
+            .map(|allowed| (config, allowed)),
+        Err(e) => Err(e),
+    };
+    let config = match config {
+        Ok((config, true)) if config.enabled && !config.muted_agents.contains(&agent_id) => config,
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("Failed to load voice alert settings: {}", e);
+            return;
+        }
+    };
+
+    let default_voice_id = match &config.voice_id {
+        Some(voice_id) => Ok(Some(voice_id.clone())),
+        None => AgentNarrationConfig::load(&db).await.map(|narration| narration.voice_id),
+    };
+    let voice_id = match default_voice_id {
+        Ok(default_voice_id) => {
+            let context = VoiceContext {
+                agent_id: Some(agent_id),
+                project_id: Some(project_path.to_string()),
+                character: None,
+                default_voice_id,
+            };
+            pipeline::resolve_voice(&db, &context).await.map(|voice| voice.voice_id)
+        }
+        Err(e) => Err(e),
+    };
+    let voice_id = match voice_id {
+        Ok(voice_id) => voice_id,
+        Err(e) => {
+            log::warn!("No voice for agent {} alert: {}", agent_id, e);
+            return;
+        }
+    };
+
+    let text = config.phrase(agent_name, success);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match render_phrase(&app, voice_id, text).await {
+            Ok(audio) => app.state::<SpeechQueue>().play(&app, audio, "agent-alert"),
+            Err(e) => log::warn!("Failed to render agent {} alert: {}", agent_id, e),
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the voice alert configuration
+#[tauri::command]
+pub async fn get_voice_alert_config(state: State<'_, Arc<ElevenLabsState>>) -> Result<VoiceAlertConfig, AudioError> {
+    VoiceAlertConfig::load(&state.db()?).await
+}
+
+/// Save the voice alert configuration
+#[tauri::command]
+pub async fn set_voice_alert_config(
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: VoiceAlertConfig,
+) -> Result<VoiceAlertConfig, AudioError> {
+    let json = serde_json::to_string(&config)?;
+    state.call_db(move |conn| SettingsDb::save_setting(conn, VOICE_ALERTS_KEY, &json)).await?;
+    Ok(config)
+}
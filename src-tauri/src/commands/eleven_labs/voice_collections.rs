@@ -0,0 +1,304 @@
+// Favorite voices and user-defined voice collections ("Villains", "Narrators
+// EN"). They are kept in the local database with the other audio settings and
+// are independent of the categories Eleven Labs assigns; a voice can be in any
+// number of collections, and collections may name voices that aren't cached.
+
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tauri::State;
+
+use super::error::AudioError;
+use super::types::VoiceProfile;
+use super::ElevenLabsState;
+
+/// A named group of voices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceCollection {
+    pub id: String,
+    pub name: String,
+    pub voice_ids: Vec<String>,
+    pub created_at: String,
+}
+
+/// Narrows a voice listing; the default keeps every voice
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VoiceFilter {
+    #[serde(default)]
+    pub favorites_only: bool,
+    #[serde(default)]
+    pub collection_id: Option<String>,
+}
+
+fn validate_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AudioError::Validation("Collection name cannot be empty".to_string()).into());
+    }
+    Ok(name.to_string())
+}
+
+fn ensure_unique_name(conn: &Connection, name: &str, except_id: Option<&str>) -> Result<()> {
+    let taken: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM voice_collections WHERE name = ?1 COLLATE NOCASE AND id IS NOT ?2",
+        (name, except_id),
+        |row| row.get(0),
+    )?;
+    if taken {
+        return Err(AudioError::Validation(format!("A voice collection named {} already exists", name)).into());
+    }
+    Ok(())
+}
+
+fn collection_exists(conn: &Connection, id: &str) -> Result<()> {
+    conn.query_row("SELECT 1 FROM voice_collections WHERE id = ?1", [id], |_| Ok(()))
+        .optional()?
+        .ok_or_else(|| AudioError::Validation(format!("No voice collection: {}", id)))?;
+    Ok(())
+}
+
+/// Mark a voice as a favorite or clear the mark
+pub fn set_favorite(conn: &Connection, voice_id: &str, favorite: bool) -> Result<()> {
+    if favorite {
+        conn.execute(
+            "INSERT OR IGNORE INTO voice_favorites (voice_id, created_at) VALUES (?1, ?2)",
+            (voice_id, chrono::Utc::now().to_rfc3339()),
+        )?;
+    } else {
+        conn.execute("DELETE FROM voice_favorites WHERE voice_id = ?1", [voice_id])?;
+    }
+    Ok(())
+}
+
+/// Ids of the favorite voices, in the order they were marked
+pub fn get_favorites(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT voice_id FROM voice_favorites ORDER BY created_at")?;
+    let favorites = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(favorites)
+}
+
+pub fn get_collection(conn: &Connection, id: &str) -> Result<Option<VoiceCollection>> {
+    let Some((name, created_at)) = conn
+        .query_row("SELECT name, created_at FROM voice_collections WHERE id = ?1", [id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    let mut stmt = conn.prepare(
+        "SELECT voice_id FROM voice_collection_members WHERE collection_id = ?1 ORDER BY added_at",
+    )?;
+    let voice_ids = stmt.query_map([id], |row| row.get(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(Some(VoiceCollection {
+        id: id.to_string(),
+        name,
+        voice_ids,
+        created_at,
+    }))
+}
+
+/// All collections, by name
+pub fn get_collections(conn: &Connection) -> Result<Vec<VoiceCollection>> {
+    let ids: Vec<String> = conn
+        .prepare("SELECT id FROM voice_collections ORDER BY name COLLATE NOCASE")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut collections = Vec::with_capacity(ids.len());
+    for id in ids {
+        collections.extend(get_collection(conn, &id)?);
+    }
+    Ok(collections)
+}
+
+pub fn create_collection(conn: &Connection, name: &str) -> Result<VoiceCollection> {
+    let name = validate_name(name)?;
+    ensure_unique_name(conn, &name, None)?;
+    let collection = VoiceCollection {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        voice_ids: Vec::new(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    conn.execute(
+        "INSERT INTO voice_collections (id, name, created_at) VALUES (?1, ?2, ?3)",
+        (&collection.id, &collection.name, &collection.created_at),
+    )?;
+    Ok(collection)
+}
+
+pub fn rename_collection(conn: &Connection, id: &str, name: &str) -> Result<VoiceCollection> {
+    let name = validate_name(name)?;
+    collection_exists(conn, id)?;
+    ensure_unique_name(conn, &name, Some(id))?;
+    conn.execute("UPDATE voice_collections SET name = ?2 WHERE id = ?1", (id, &name))?;
+    Ok(get_collection(conn, id)?.expect("collection checked above"))
+}
+
+pub fn delete_collection(conn: &mut Connection, id: &str) -> Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM voice_collection_members WHERE collection_id = ?1", [id])?;
+    tx.execute("DELETE FROM voice_collections WHERE id = ?1", [id])?;
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn add_voice(conn: &Connection, collection_id: &str, voice_id: &str) -> Result<VoiceCollection> {
+    collection_exists(conn, collection_id)?;
+    conn.execute(
+        "INSERT OR IGNORE INTO voice_collection_members (collection_id, voice_id, added_at) VALUES (?1, ?2, ?3)",
+        (collection_id, voice_id, chrono::Utc::now().to_rfc3339()),
+    )?;
+    Ok(get_collection(conn, collection_id)?.expect("collection checked above"))
+}
+
+pub fn remove_voice(conn: &Connection, collection_id: &str, voice_id: &str) -> Result<VoiceCollection> {
+    collection_exists(conn, collection_id)?;
+    conn.execute(
+        "DELETE FROM voice_collection_members WHERE collection_id = ?1 AND voice_id = ?2",
+        (collection_id, voice_id),
+    )?;
+    Ok(get_collection(conn, collection_id)?.expect("collection checked above"))
+}
+
+/// Forget a deleted voice: its favorite mark and collection memberships
+pub fn forget_voice(conn: &Connection, voice_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM voice_favorites WHERE voice_id = ?1", [voice_id])?;
+    conn.execute("DELETE FROM voice_collection_members WHERE voice_id = ?1", [voice_id])?;
+    Ok(())
+}
+
+/// Keep the voices `filter` selects, in their original order
+pub fn apply_filter(conn: &Connection, voices: Vec<VoiceProfile>, filter: &VoiceFilter) -> Result<Vec<VoiceProfile>> {
+    let mut keep: Option<HashSet<String>> = None;
+    if filter.favorites_only {
+        keep = Some(get_favorites(conn)?.into_iter().collect());
+    }
+    if let Some(collection_id) = &filter.collection_id {
+        let members: HashSet<String> = get_collection(conn, collection_id)?
+            .ok_or_else(|| AudioError::Validation(format!("No voice collection: {}", collection_id)))?
+            .voice_ids
+            .into_iter()
+            .collect();
+        keep = Some(match keep {
+            Some(favorites) => favorites.intersection(&members).cloned().collect(),
+            None => members,
+        });
+    }
+    Ok(match keep {
+        Some(keep) => voices.into_iter().filter(|voice| keep.contains(&voice.voice_id)).collect(),
+        None => voices,
+    })
+}
+
+/// Mark a voice as a favorite, or clear the mark with `favorite: false`
+#[tauri::command]
+pub async fn set_voice_favorite(
+    state: State<'_, Arc<ElevenLabsState>>,
+    voice_id: String,
+    favorite: bool,
+) -> Result<(), AudioError> {
+    state.call_db(move |conn| set_favorite(conn, &voice_id, favorite)).await
+}
+
+/// Ids of the favorite voices
+#[tauri::command]
+pub async fn list_favorite_voices(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<String>, AudioError> {
+    state.call_db(|conn| get_favorites(conn)).await
+}
+
+#[tauri::command]
+pub async fn list_voice_collections(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<VoiceCollection>, AudioError> {
+    state.call_db(|conn| get_collections(conn)).await
+}
+
+/// Create an empty collection; names are unique regardless of case
+#[tauri::command]
+pub async fn create_voice_collection(
+    state: State<'_, Arc<ElevenLabsState>>,
+    name: String,
+) -> Result<VoiceCollection, AudioError> {
+    state.call_db(move |conn| create_collection(conn, &name)).await
+}
+
+#[tauri::command]
+pub async fn rename_voice_collection(
+    state: State<'_, Arc<ElevenLabsState>>,
+    collection_id: String,
+    name: String,
+) -> Result<VoiceCollection, AudioError> {
+    state.call_db(move |conn| rename_collection(conn, &collection_id, &name)).await
+}
+
+/// Delete a collection; its voices are not affected
+#[tauri::command]
+pub async fn delete_voice_collection(
+    state: State<'_, Arc<ElevenLabsState>>,
+    collection_id: String,
+) -> Result<(), AudioError> {
+    state.call_db(move |conn| delete_collection(conn, &collection_id)).await
+}
+
+#[tauri::command]
+pub async fn add_voice_to_collection(
+    state: State<'_, Arc<ElevenLabsState>>,
+    collection_id: String,
+    voice_id: String,
+) -> Result<VoiceCollection, AudioError> {
+    state.call_db(move |conn| add_voice(conn, &collection_id, &voice_id)).await
+}
+
+#[tauri::command]
+pub async fn remove_voice_from_collection(
+    state: State<'_, Arc<ElevenLabsState>>,
+    collection_id: String,
+    voice_id: String,
+) -> Result<VoiceCollection, AudioError> {
+    state.call_db(move |conn| remove_voice(conn, &collection_id, &voice_id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::eleven_labs::schema;
+
+    fn voice(id: &str) -> VoiceProfile {
+        VoiceProfile {
+            voice_id: id.to_string(),
+            name: id.to_uppercase(),
+            description: None,
+            category: "premade".to_string(),
+            labels: None,
+            preview_url: None,
+            settings: Default::default(),
+            samples: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_filter_by_favorites_and_collection() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::init(&conn).unwrap();
+        let villains = create_collection(&conn, "Villains").unwrap();
+        assert!(create_collection(&conn, "villains ").is_err());
+        add_voice(&conn, &villains.id, "b").unwrap();
+        add_voice(&conn, &villains.id, "c").unwrap();
+        set_favorite(&conn, "a", true).unwrap();
+        set_favorite(&conn, "b", true).unwrap();
+
+        let voices = || vec![voice("a"), voice("b"), voice("c")];
+        let ids = |filter: VoiceFilter| -> Vec<String> {
+            apply_filter(&conn, voices(), &filter).unwrap().into_iter().map(|v| v.voice_id).collect()
+        };
+        assert_eq!(ids(VoiceFilter::default()), vec!["a", "b", "c"]);
+        assert_eq!(ids(VoiceFilter { favorites_only: true, collection_id: None }), vec!["a", "b"]);
+        assert_eq!(ids(VoiceFilter { favorites_only: false, collection_id: Some(villains.id.clone()) }), vec!["b", "c"]);
+        assert_eq!(ids(VoiceFilter { favorites_only: true, collection_id: Some(villains.id.clone()) }), vec!["b"]);
+
+        forget_voice(&conn, "b").unwrap();
+        assert_eq!(get_favorites(&conn).unwrap(), vec!["a"]);
+        assert_eq!(get_collection(&conn, &villains.id).unwrap().unwrap().voice_ids, vec!["c"]);
+    }
+}
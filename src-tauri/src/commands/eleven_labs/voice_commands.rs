@@ -0,0 +1,264 @@
+// Keyword intents for voice input. Transcripts from push-to-talk are checked
+// against the configured rules before being sent to a session; a matching rule
+// runs the corresponding app command instead.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::cache::{AudioDb, SettingsDb};
+use super::error::AudioError;
+use super::speech_queue::SpeechQueue;
+use super::voice_input::VoicePromptTarget;
+use super::ElevenLabsState;
+use crate::commands::agents::{execute_agent, kill_agent_session, AgentDb};
+use crate::process::ProcessRegistryState;
+
+/// Settings key holding the voice command configuration
+pub const VOICE_COMMANDS_KEY: &str = "voice_commands";
+
+/// What a matching voice command does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VoiceIntent {
+    /// Run an agent in the target project. Without `agent`, the agent whose
+    /// name is spoken in the command is used.
+    RunAgent {
+        #[serde(default)]
+        agent: Option<String>,
+        /// Task to run; defaults to the agent's default task
+        #[serde(default)]
+        task: Option<String>,
+    },
+    /// Stop the most recently started agent run
+    StopRun,
+    /// Stop speech playback and drop queued speech
+    StopSpeaking,
+}
+
+/// Fires `intent` when every keyword (or phrase) appears in the transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentRule {
+    pub keywords: Vec<String>,
+    pub intent: VoiceIntent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceCommandConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Checked in order; the first matching rule wins
+    #[serde(default = "default_rules")]
+    pub rules: Vec<IntentRule>,
+}
+
+fn default_rules() -> Vec<IntentRule> {
+    let rule = |keywords: &[&str], intent| IntentRule {
+        keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        intent,
+    };
+    vec![
+        rule(&["stop", "speaking"], VoiceIntent::StopSpeaking),
+        rule(&["stop", "run"], VoiceIntent::StopRun),
+        rule(&["run", "agent"], VoiceIntent::RunAgent { agent: None, task: None }),
+    ]
+}
+
+impl Default for VoiceCommandConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rules: default_rules(),
+        }
+    }
+}
+
+impl VoiceCommandConfig {
+    pub async fn load(db: &AudioDb) -> Result<Self, AudioError> {
+        match db.call(|conn| SettingsDb::get_setting(conn, VOICE_COMMANDS_KEY)).await? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// The intent of the first rule matching `text`
+    pub fn match_intent(&self, text: &str) -> Option<&VoiceIntent> {
+        let spoken = normalize(text);
+        self.rules
+            .iter()
+            .find(|rule| !rule.keywords.is_empty() && rule.keywords.iter().all(|k| contains_phrase(&spoken, k)))
+            .map(|rule| &rule.intent)
+    }
+}
+
+/// Lowercase words separated by single spaces, without punctuation
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whole-word match, so "run" doesn't match "running"
+fn contains_phrase(spoken: &str, phrase: &str) -> bool {
+    let phrase = normalize(phrase);
+    !phrase.is_empty() && format!(" {} ", spoken).contains(&format!(" {} ", phrase))
+}
+
+/// Emitted as `voice-command` when a transcript was handled as a command
+#[derive(Debug, Clone, Serialize)]
+pub struct VoiceCommandEvent {
+    pub text: String,
+    pub intent: VoiceIntent,
+    /// Run started or stopped by the command
+    pub run_id: Option<i64>,
+}
+
+/// Pick the agent for a run command: the configured name, otherwise the
+/// longest agent name spoken in the transcript
+async fn find_agent(
+    db: &AudioDb,
+    agent: Option<&str>,
+    text: &str,
+) -> Result<(i64, String, Option<String>), AudioError> {
+    let agents: Vec<(i64, String, Option<String>)> = db
+        .call(|conn| {
+            let mut stmt = conn.prepare("SELECT id, name, default_task FROM agents")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            Ok(rows.collect::<Result<_, _>>()?)
+        })
+        .await?;
+
+    match agent {
+        Some(name) => agents.into_iter().find(|(_, n, _)| n.eq_ignore_ascii_case(name)),
+        None => {
+            let spoken = normalize(text);
+            agents
+                .into_iter()
+                .filter(|(_, name, _)| contains_phrase(&spoken, name))
+                .max_by_key(|(_, name, _)| name.len())
+        }
+    }
+    .ok_or_else(|| AudioError::Validation(format!("No agent matches \"{}\"", agent.unwrap_or(text))))
+}
+
+/// The most recently started running agent run
+async fn latest_running_run(db: &AudioDb) -> Result<Option<i64>, AudioError> {
+    db.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM agent_runs WHERE status = 'running' ORDER BY process_started_at DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query([])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    })
+    .await
+}
+
+async fn run_intent(
+    app: &AppHandle,
+    db: &AudioDb,
+    intent: &VoiceIntent,
+    text: &str,
+    target: Option<&VoicePromptTarget>,
+) -> Result<Option<i64>, AudioError> {
+    match intent {
+        VoiceIntent::RunAgent { agent, task } => {
+            let target = target.ok_or("No project to run the agent in")?;
+            let (agent_id, name, default_task) = find_agent(db, agent.as_deref(), text).await?;
+            let task = task
+                .clone()
+                .or(default_task)
+                .ok_or_else(|| format!("Agent {} has no default task", name))?;
+            let run_id = execute_agent(
+                app.clone(),
+                agent_id,
+                target.project_path.clone(),
+                task,
+                None,
+                app.state::<AgentDb>(),
+                app.state::<ProcessRegistryState>(),
+            )
+            .await?;
+            Ok(Some(run_id))
+        }
+        VoiceIntent::StopRun => {
+            let run_id = latest_running_run(db).await?.ok_or("No agent is running")?;
+            kill_agent_session(
+                app.clone(),
+                app.state::<AgentDb>(),
+                app.state::<ProcessRegistryState>(),
+                run_id,
+            )
+            .await?;
+            Ok(Some(run_id))
+        }
+        VoiceIntent::StopSpeaking => {
+            app.state::<SpeechQueue>().stop(app);
+            Ok(None)
+        }
+    }
+}
+
+/// Run the command in `text` if voice commands are enabled and a rule matches.
+/// Returns whether the transcript was handled as a command.
+pub async fn dispatch(app: &AppHandle, text: &str, target: Option<&VoicePromptTarget>) -> Result<bool, AudioError> {
+    let db = app.state::<Arc<ElevenLabsState>>().db()?;
+    let config = VoiceCommandConfig::load(&db).await?;
+    if !config.enabled {
+        return Ok(false);
+    }
+    let Some(intent) = config.match_intent(text) else {
+        return Ok(false);
+    };
+
+    let run_id = run_intent(app, &db, intent, text, target).await?;
+    let _ = app.emit(
+        "voice-command",
+        VoiceCommandEvent {
+            text: text.to_string(),
+            intent: intent.clone(),
+            run_id,
+        },
+    );
+    Ok(true)
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the voice command configuration
+#[tauri::command]
+pub async fn get_voice_command_config(state: State<'_, Arc<ElevenLabsState>>) -> Result<VoiceCommandConfig, AudioError> {
+    VoiceCommandConfig::load(&state.db()?).await
+}
+
+/// Save the voice command configuration
+#[tauri::command]
+pub async fn set_voice_command_config(
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: VoiceCommandConfig,
+) -> Result<VoiceCommandConfig, AudioError> {
+    let json = serde_json::to_string(&config)?;
+    state.call_db(move |conn| SettingsDb::save_setting(conn, VOICE_COMMANDS_KEY, &json)).await?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_intent() {
+        let config = VoiceCommandConfig::default();
+        assert_eq!(config.match_intent("Stop the current run."), Some(&VoiceIntent::StopRun));
+        assert_eq!(
+            config.match_intent("Run tests agent"),
+            Some(&VoiceIntent::RunAgent { agent: None, task: None })
+        );
+        assert_eq!(config.match_intent("Why is the agent running slowly?"), None);
+        assert!(contains_phrase(&normalize("run the Test-Runner agent"), "test runner"));
+    }
+}
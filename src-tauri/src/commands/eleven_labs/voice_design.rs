@@ -0,0 +1,85 @@
+// Voice design: brand-new synthetic voices generated from a text description.
+// The provider returns a few candidate previews; the one the user likes is
+// then saved to the account and cached like any other voice.
+
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use tauri::State;
+
+use super::auth;
+use super::cache::VoiceProfileDb;
+use super::error::AudioError;
+use super::types::*;
+use super::{get_client, ElevenLabsState};
+
+/// Characters the provider accepts in a voice description
+pub const DESCRIPTION_LENGTH: RangeInclusive<usize> = 20..=1000;
+
+/// Characters the provider accepts in custom preview text
+pub const PREVIEW_TEXT_LENGTH: RangeInclusive<usize> = 100..=1000;
+
+/// Check a request against the provider's length limits before sending it
+pub fn validate(request: &VoiceDesignRequest) -> Result<(), AudioError> {
+    let length = request.voice_description.trim().chars().count();
+    if !DESCRIPTION_LENGTH.contains(&length) {
+        return Err(AudioError::Validation(format!(
+            "Voice description must be {} to {} characters",
+            DESCRIPTION_LENGTH.start(),
+            DESCRIPTION_LENGTH.end()
+        )));
+    }
+    if let Some(text) = &request.text {
+        if !PREVIEW_TEXT_LENGTH.contains(&text.chars().count()) {
+            return Err(AudioError::Validation(format!(
+                "Preview text must be {} to {} characters",
+                PREVIEW_TEXT_LENGTH.start(),
+                PREVIEW_TEXT_LENGTH.end()
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub async fn preview(state: &ElevenLabsState, request: VoiceDesignRequest) -> Result<VoiceDesignPreviews, AudioError> {
+    validate(&request)?;
+    let client = get_client(state).await?;
+    auth::check(state, client.design_voice_previews(request).await).await
+}
+
+/// Save a preview to the account and cache the new voice
+pub async fn create(state: &ElevenLabsState, request: DesignedVoiceRequest) -> Result<VoiceProfile, AudioError> {
+    if request.name.trim().is_empty() {
+        return Err(AudioError::Validation("Voice name cannot be empty".to_string()));
+    }
+    let client = get_client(state).await?;
+    let voice = auth::check(state, client.create_designed_voice(request).await).await?;
+    state
+        .call_db(move |conn| {
+            VoiceProfileDb::save_voice_profile(conn, &voice, &voice.voice_id)?;
+            Ok(voice)
+        })
+        .await
+}
+
+/// Generate candidate voices from a description. `text` is what the previews
+/// say; the provider writes something fitting when it is not given.
+#[tauri::command]
+pub async fn eleven_labs_design_voice_previews(
+    state: State<'_, Arc<ElevenLabsState>>,
+    voice_description: String,
+    text: Option<String>,
+) -> Result<VoiceDesignPreviews, AudioError> {
+    preview(&state, VoiceDesignRequest { voice_description, text }).await
+}
+
+/// Keep one of the voice design previews as a new voice
+#[tauri::command]
+pub async fn eleven_labs_create_designed_voice(
+    state: State<'_, Arc<ElevenLabsState>>,
+    name: String,
+    description: String,
+    generated_voice_id: String,
+    labels: Option<serde_json::Value>,
+) -> Result<VoiceProfile, AudioError> {
+    create(&state, DesignedVoiceRequest { name, description, generated_voice_id, labels }).await
+}
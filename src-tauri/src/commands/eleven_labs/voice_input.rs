@@ -0,0 +1,436 @@
+use anyhow::anyhow;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::SampleFormat;
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Write};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::auth;
+use super::cache::{AudioDb, SettingsDb};
+use super::error::AudioError;
+use super::voice_commands;
+use super::{get_client, ElevenLabsState};
+
+/// Settings key holding the voice prompt configuration
+pub const VOICE_PROMPT_KEY: &str = "voice_prompt";
+
+/// Whisper models expect 16 kHz mono input
+const TRANSCRIBE_SAMPLE_RATE: u32 = 16_000;
+
+/// How often the recording so far is re-transcribed for interim results
+const INTERIM_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Where recordings are transcribed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SttBackend {
+    /// Eleven Labs Scribe
+    #[default]
+    Cloud,
+    /// A local whisper.cpp binary
+    Local,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoicePromptConfig {
+    #[serde(default)]
+    pub backend: SttBackend,
+    /// ISO 639-1 code; detected automatically when unset
+    #[serde(default)]
+    pub language: Option<String>,
+    /// whisper.cpp executable, `whisper-cli` on the PATH by default
+    #[serde(default)]
+    pub local_command: Option<String>,
+    /// Path to the ggml model used by the local backend
+    #[serde(default)]
+    pub local_model: Option<String>,
+    #[serde(default = "default_interim_transcripts")]
+    pub interim_transcripts: bool,
+}
+
+fn default_interim_transcripts() -> bool {
+    true
+}
+
+impl Default for VoicePromptConfig {
+    fn default() -> Self {
+        Self {
+            backend: SttBackend::default(),
+            language: None,
+            local_command: None,
+            local_model: None,
+            interim_transcripts: default_interim_transcripts(),
+        }
+    }
+}
+
+impl VoicePromptConfig {
+    pub async fn load(db: &AudioDb) -> Result<Self, AudioError> {
+        match db.call(|conn| SettingsDb::get_setting(conn, VOICE_PROMPT_KEY)).await? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Session the transcript is sent to once recording stops
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoicePromptTarget {
+    pub project_path: String,
+    /// Resume this session; continues the latest one in the project when unset
+    #[serde(default)]
+    pub session_id: Option<String>,
+    pub model: String,
+}
+
+/// Emitted as `voice-prompt-transcript` while recording and once when it stops
+#[derive(Debug, Clone, Serialize)]
+pub struct VoiceTranscriptEvent {
+    pub text: String,
+    pub is_final: bool,
+}
+
+/// An in-progress push-to-talk recording
+pub struct VoiceRecording {
+    stop: mpsc::Sender<()>,
+    samples: Arc<Mutex<Vec<f32>>>,
+    sample_rate: u32,
+    target: Option<VoicePromptTarget>,
+    interim: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl VoiceRecording {
+    fn snapshot(&self) -> Vec<f32> {
+        self.samples.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+/// Average interleaved frames down to mono and append them to the buffer
+fn push_frames<T: Copy>(buffer: &Mutex<Vec<f32>>, data: &[T], channels: usize, convert: impl Fn(T) -> f32) {
+    if let Ok(mut buffer) = buffer.lock() {
+        buffer.extend(
+            data.chunks(channels)
+                .map(|frame| frame.iter().map(|&s| convert(s)).sum::<f32>() / frame.len() as f32),
+        );
+    }
+}
+
+fn build_stream(buffer: Arc<Mutex<Vec<f32>>>) -> anyhow::Result<(cpal::Stream, u32)> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or_else(|| anyhow!("No microphone available"))?;
+    let config = device.default_input_config()?;
+    let channels = config.channels().max(1) as usize;
+    let sample_rate = config.sample_rate().0;
+    let err_fn = |e: cpal::StreamError| log::warn!("Microphone stream error: {}", e);
+
+    let stream = match config.sample_format() {
+        SampleFormat::F32 => device.build_input_stream(
+            &config.config(),
+            move |data: &[f32], _: &cpal::InputCallbackInfo| push_frames(&buffer, data, channels, |s| s),
+            err_fn,
+            None,
+        ),
+        SampleFormat::I16 => device.build_input_stream(
+            &config.config(),
+            move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                push_frames(&buffer, data, channels, |s| s as f32 / i16::MAX as f32)
+            },
+            err_fn,
+            None,
+        ),
+        SampleFormat::U16 => device.build_input_stream(
+            &config.config(),
+            move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                push_frames(&buffer, data, channels, |s| (s as f32 - 32768.0) / 32768.0)
+            },
+            err_fn,
+            None,
+        ),
+        other => return Err(anyhow!("Unsupported microphone sample format: {:?}", other)),
+    }?;
+
+    stream.play()?;
+    Ok((stream, sample_rate))
+}
+
+/// A running microphone capture
+pub(crate) struct Capture {
+    stop: mpsc::Sender<()>,
+    samples: Arc<Mutex<Vec<f32>>>,
+    sample_rate: u32,
+}
+
+impl Capture {
+    /// Take the mono samples captured since the last call
+    pub(crate) fn drain(&self) -> Vec<f32> {
+        self.samples.lock().map(|mut s| std::mem::take(&mut *s)).unwrap_or_default()
+    }
+
+    pub(crate) fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+/// Start capturing the default microphone. The stream isn't `Send`, so it lives on
+/// its own thread until `stop` is used or dropped.
+pub(crate) fn start_capture() -> Result<Capture, AudioError> {
+    let samples = Arc::new(Mutex::new(Vec::new()));
+    let (ready_tx, ready_rx) = mpsc::channel::<Result<u32, AudioError>>();
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+    let buffer = samples.clone();
+    std::thread::spawn(move || {
+        let _stream = match build_stream(buffer) {
+            Ok((stream, sample_rate)) => {
+                let _ = ready_tx.send(Ok(sample_rate));
+                stream
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(e.into()));
+                return;
+            }
+        };
+        let _ = stop_rx.recv();
+    });
+
+    let sample_rate = ready_rx
+        .recv()
+        .map_err(|_| "Microphone capture thread exited".to_string())??;
+    Ok(Capture {
+        stop: stop_tx,
+        samples,
+        sample_rate,
+    })
+}
+
+/// Linear resampling, good enough for speech recognition
+pub fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let ratio = from as f64 / to as f64;
+    let len = (samples.len() as f64 / ratio) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let index = pos as usize;
+            let frac = (pos - index as f64) as f32;
+            let a = samples[index];
+            let b = samples.get(index + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Encode mono samples as a 16-bit WAV at the transcription sample rate
+fn encode_wav(samples: &[f32], sample_rate: u32) -> anyhow::Result<Vec<u8>> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: TRANSCRIBE_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec)?;
+        for sample in resample(samples, sample_rate, TRANSCRIBE_SAMPLE_RATE) {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            writer.write_sample(value)?;
+        }
+        writer.finalize()?;
+    }
+    Ok(cursor.into_inner())
+}
+
+async fn transcribe_local(config: &VoicePromptConfig, wav: Vec<u8>) -> Result<String, AudioError> {
+    let model = config
+        .local_model
+        .as_deref()
+        .ok_or("No local Whisper model configured")?;
+    let command = config.local_command.as_deref().unwrap_or("whisper-cli");
+
+    let mut file = tempfile::Builder::new()
+        .suffix(".wav")
+        .tempfile()?;
+    file.write_all(&wav)?;
+
+    let mut cmd = tokio::process::Command::new(command);
+    // No timestamps or progress output: stdout is just the transcript
+    cmd.arg("-m").arg(model).arg("-f").arg(file.path()).arg("-nt").arg("-np");
+    if let Some(language) = &config.language {
+        cmd.arg("-l").arg(language);
+    }
+
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", command, e))?;
+    if !output.status.success() {
+        return Err(AudioError::Other(format!(
+            "{} failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    Ok(text.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" "))
+}
+
+async fn transcribe(
+    app: &AppHandle,
+    config: &VoicePromptConfig,
+    samples: &[f32],
+    sample_rate: u32,
+) -> Result<String, AudioError> {
+    let wav = encode_wav(samples, sample_rate)?;
+    match config.backend {
+        SttBackend::Cloud => {
+            let state = app.state::<Arc<ElevenLabsState>>();
+            let client = get_client(&state).await?;
+            auth::check(&state, client.speech_to_text(wav, config.language.as_deref()).await).await
+        }
+        SttBackend::Local => transcribe_local(config, wav).await,
+    }
+}
+
+/// Periodically transcribe the recording so far and emit interim results
+fn spawn_interim(
+    app: AppHandle,
+    config: VoicePromptConfig,
+    samples: Arc<Mutex<Vec<f32>>>,
+    sample_rate: u32,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut transcribed_len = 0;
+        loop {
+            tokio::time::sleep(INTERIM_INTERVAL).await;
+
+            let snapshot = samples.lock().map(|s| s.clone()).unwrap_or_default();
+            if snapshot.len() == transcribed_len {
+                continue;
+            }
+            transcribed_len = snapshot.len();
+
+            match transcribe(&app, &config, &snapshot, sample_rate).await {
+                Ok(text) => {
+                    let _ = app.emit("voice-prompt-transcript", VoiceTranscriptEvent { text, is_final: false });
+                }
+                Err(e) => log::warn!("Interim transcription failed: {}", e),
+            }
+        }
+    })
+}
+
+async fn forward(app: AppHandle, target: VoicePromptTarget, prompt: String) -> Result<(), AudioError> {
+    match target.session_id {
+        Some(session_id) => {
+            crate::commands::claude::resume_claude_code(app, target.project_path, session_id, prompt, target.model)
+                .await?
+        }
+        None => crate::commands::claude::continue_claude_code(app, target.project_path, prompt, target.model).await?,
+    }
+    Ok(())
+}
+
+// ========== Tauri Commands ==========
+
+/// Get the voice prompt configuration
+#[tauri::command]
+pub async fn get_voice_prompt_config(state: State<'_, Arc<ElevenLabsState>>) -> Result<VoicePromptConfig, AudioError> {
+    VoicePromptConfig::load(&state.db()?).await
+}
+
+/// Save the voice prompt configuration
+#[tauri::command]
+pub async fn set_voice_prompt_config(
+    state: State<'_, Arc<ElevenLabsState>>,
+    config: VoicePromptConfig,
+) -> Result<VoicePromptConfig, AudioError> {
+    let json = serde_json::to_string(&config)?;
+    state.call_db(move |conn| SettingsDb::save_setting(conn, VOICE_PROMPT_KEY, &json)).await?;
+    Ok(config)
+}
+
+/// Start push-to-talk recording from the default microphone
+#[tauri::command]
+pub async fn start_voice_prompt(
+    app: AppHandle,
+    state: State<'_, Arc<ElevenLabsState>>,
+    target: Option<VoicePromptTarget>,
+) -> Result<(), AudioError> {
+    let config = VoicePromptConfig::load(&state.db()?).await?;
+
+    let mut recording = state.voice_prompt.lock()?;
+    if recording.is_some() {
+        return Err(AudioError::Other("Already recording".to_string()));
+    }
+
+    let capture = start_capture()?;
+    let interim = config
+        .interim_transcripts
+        .then(|| spawn_interim(app, config, capture.samples.clone(), capture.sample_rate));
+
+    *recording = Some(VoiceRecording {
+        stop: capture.stop,
+        samples: capture.samples,
+        sample_rate: capture.sample_rate,
+        target,
+        interim,
+    });
+    Ok(())
+}
+
+/// Stop recording, transcribe, and run the text as a voice command or send it to
+/// the target session if one was given. Returns the transcript.
+#[tauri::command]
+pub async fn stop_voice_prompt(app: AppHandle, state: State<'_, Arc<ElevenLabsState>>) -> Result<String, AudioError> {
+    let recording = state
+        .voice_prompt
+        .lock()?
+        .take()
+        .ok_or("Not recording")?;
+
+    let _ = recording.stop.send(());
+    if let Some(interim) = &recording.interim {
+        interim.abort();
+    }
+
+    let samples = recording.snapshot();
+    if samples.is_empty() {
+        return Err(AudioError::Validation("No audio was captured".to_string()));
+    }
+
+    let config = VoicePromptConfig::load(&state.db()?).await?;
+    let text = transcribe(&app, &config, &samples, recording.sample_rate).await?;
+    let text = text.trim().to_string();
+    let _ = app.emit(
+        "voice-prompt-transcript",
+        VoiceTranscriptEvent { text: text.clone(), is_final: true },
+    );
+
+    if text.is_empty() || voice_commands::dispatch(&app, &text, recording.target.as_ref()).await? {
+        return Ok(text);
+    }
+    if let Some(target) = recording.target {
+        forward(app, target, text.clone()).await?;
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample_length() {
+        let samples = vec![0.0; 48_000];
+        assert_eq!(resample(&samples, 48_000, TRANSCRIBE_SAMPLE_RATE).len(), 16_000);
+        assert_eq!(resample(&[0.0, 1.0], 16_000, 32_000), vec![0.0, 0.5, 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,62 @@
+// The provider's shared (community) voice library: voices published by other
+// users, searchable by gender, accent and use case. A voice found there is
+// added to the account and then cached like any other voice.
+
+use std::sync::Arc;
+use tauri::State;
+
+use super::auth;
+use super::cache::VoiceProfileDb;
+use super::error::AudioError;
+use super::types::*;
+use super::{get_client, ElevenLabsState};
+
+/// The provider's largest page of shared voices
+const MAX_PAGE_SIZE: u32 = 100;
+
+pub async fn search(state: &ElevenLabsState, mut filter: SharedVoiceFilter) -> Result<SharedVoicePage, AudioError> {
+    filter.page_size = filter.page_size.clamp(1, MAX_PAGE_SIZE);
+    let client = get_client(state).await?;
+    auth::check(state, client.search_shared_voices(&filter).await).await
+}
+
+/// Add a shared voice to the account and cache it
+pub async fn add(
+    state: &ElevenLabsState,
+    public_owner_id: &str,
+    voice_id: &str,
+    name: &str,
+) -> Result<VoiceProfile, AudioError> {
+    if name.trim().is_empty() {
+        return Err(AudioError::Validation("Voice name cannot be empty".to_string()));
+    }
+    let client = get_client(state).await?;
+    let voice = auth::check(state, client.add_shared_voice(public_owner_id, voice_id, name.trim()).await).await?;
+    state
+        .call_db(move |conn| {
+            VoiceProfileDb::save_voice_profile(conn, &voice, &voice.voice_id)?;
+            Ok(voice)
+        })
+        .await
+}
+
+/// Search the shared voice library. Results come a page at a time; ask for
+/// `page + 1` while `has_more` is set.
+#[tauri::command]
+pub async fn eleven_labs_search_shared_voices(
+    state: State<'_, Arc<ElevenLabsState>>,
+    filter: Option<SharedVoiceFilter>,
+) -> Result<SharedVoicePage, AudioError> {
+    search(&state, filter.unwrap_or_default()).await
+}
+
+/// Add a voice from the shared library to the account under `name`
+#[tauri::command]
+pub async fn eleven_labs_add_shared_voice(
+    state: State<'_, Arc<ElevenLabsState>>,
+    public_owner_id: String,
+    voice_id: String,
+    name: String,
+) -> Result<VoiceProfile, AudioError> {
+    add(&state, &public_owner_id, &voice_id, &name).await
+}
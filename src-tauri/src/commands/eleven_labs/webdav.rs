@@ -0,0 +1,288 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use regex::Regex;
+use reqwest::{Client, Method, StatusCode};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use tokio::sync::Mutex;
+
+use super::client::http_client_builder;
+use super::remote::{RemoteStorage, WebDavConfig};
+
+/// WebDAV storage backend (Nextcloud, ownCloud, Apache mod_dav, rclone serve, ...)
+pub struct WebDavStorage {
+    client: Client,
+    config: WebDavConfig,
+    password: String,
+    /// Collections already known to exist, so MKCOL is only issued once per run
+    created_dirs: Mutex<HashSet<String>>,
+}
+
+impl WebDavStorage {
+    pub fn new(config: WebDavConfig, password: String) -> Result<Self> {
+        let client = http_client_builder()
+            .build()
+            .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            config,
+            password,
+            created_dirs: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        let base = self.config.url.trim_end_matches('/');
+        match &self.config.prefix {
+            Some(prefix) if !prefix.is_empty() => {
+                format!("{}/{}/{}", base, prefix.trim_matches('/'), path)
+            }
+            _ => format!("{}/{}", base, path),
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, self.url(path))
+            .basic_auth(&self.config.username, Some(&self.password))
+    }
+
+    /// Create every parent collection of `key` that does not exist yet
+    async fn ensure_parent_dirs(&self, key: &str) -> Result<()> {
+        let mut created = self.created_dirs.lock().await;
+        let mut path = String::new();
+
+        let mut segments: Vec<&str> = match &self.config.prefix {
+            Some(prefix) if !prefix.is_empty() => prefix.trim_matches('/').split('/').collect(),
+            _ => vec![],
+        };
+        let prefix_len = segments.len();
+        segments.extend(key.split('/'));
+        segments.pop();
+
+        for (index, segment) in segments.iter().enumerate() {
+            path.push_str(segment);
+            path.push('/');
+            if created.contains(&path) {
+                continue;
+            }
+
+            let url = format!("{}/{}", self.config.url.trim_end_matches('/'), path);
+            let response = self
+                .client
+                .request(Method::from_bytes(b"MKCOL")?, &url)
+                .basic_auth(&self.config.username, Some(&self.password))
+                .send()
+                .await
+                .map_err(|e| anyhow!("WebDAV request failed: {}", e))?;
+
+            // 405 means the collection already exists
+            let status = response.status();
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                // The configured prefix may live above folders we can't create; only
+                // fail for collections we own.
+                if index >= prefix_len {
+                    return Err(anyhow!("WebDAV MKCOL {} failed: {}", path, status));
+                }
+            }
+            created.insert(path.clone());
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RemoteStorage for WebDavStorage {
+    fn backend_name(&self) -> &'static str {
+        "webdav"
+    }
+
+    async fn put_object(&self, key: &str, data: Bytes) -> Result<()> {
+        self.ensure_parent_dirs(key).await?;
+
+        let response = self
+            .request(Method::PUT, key)
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| anyhow!("WebDAV request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("WebDAV error {}: {}", status, text));
+        }
+
+        Ok(())
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Option<Bytes>> {
+        let response = self
+            .request(Method::GET, key)
+            .send()
+            .await
+            .map_err(|e| anyhow!("WebDAV request failed: {}", e))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("WebDAV error {}: {}", status, text));
+        }
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| anyhow!("Failed to read WebDAV object: {}", e))?;
+
+        Ok(Some(bytes))
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let dir = if prefix.ends_with('/') {
+            prefix.to_string()
+        } else {
+            format!("{}/", prefix)
+        };
+
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/></d:prop></d:propfind>"#;
+
+        let response = self
+            .request(Method::from_bytes(b"PROPFIND")?, &dir)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| anyhow!("WebDAV request failed: {}", e))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(vec![]);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("WebDAV error {}: {}", status, text));
+        }
+
+        let text = response.text().await?;
+        Ok(parse_propfind_files(&text)
+            .into_iter()
+            .map(|name| format!("{}{}", dir, name))
+            .collect())
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        let response = self
+            .request(Method::DELETE, key)
+            .send()
+            .await
+            .map_err(|e| anyhow!("WebDAV request failed: {}", e))?;
+
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("WebDAV error {}: {}", status, text));
+        }
+
+        Ok(())
+    }
+}
+
+/// Extract the file names (not collections) from a Depth: 1 PROPFIND multistatus body
+fn parse_propfind_files(body: &str) -> Vec<String> {
+    response_element()
+        .find_iter(body)
+        .filter(|m| !collection_element().is_match(m.as_str()))
+        .filter_map(|m| href_element().captures(m.as_str()))
+        .filter_map(|cap| {
+            let href = cap[1].trim_end_matches('/');
+            href.rsplit('/').next().map(percent_decode)
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// One `<response>` of a PROPFIND multistatus, whatever its namespace prefix
+fn response_element() -> &'static Regex {
+    static RESPONSE: OnceLock<Regex> = OnceLock::new();
+    RESPONSE.get_or_init(|| Regex::new(r"(?s)<(?:\w+:)?response\b.*?</(?:\w+:)?response>").unwrap())
+}
+
+/// `<href>` of a response
+fn href_element() -> &'static Regex {
+    static HREF: OnceLock<Regex> = OnceLock::new();
+    HREF.get_or_init(|| Regex::new(r"<(?:\w+:)?href>([^<]*)</(?:\w+:)?href>").unwrap())
+}
+
+/// `<collection/>` resource type, also written as an empty open/close pair
+fn collection_element() -> &'static Regex {
+    static COLLECTION: OnceLock<Regex> = OnceLock::new();
+    COLLECTION.get_or_init(|| Regex::new(r"<(?:\w+:)?collection\s*(?:/>|>\s*</(?:\w+:)?collection>)").unwrap())
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            // Decoded from the bytes: what follows `%` may not be ASCII
+            let hex = |b: u8| (b as char).to_digit(16);
+            if let (Some(high), Some(low)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push((high * 16 + low) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_propfind_files() {
+        let body = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/remote.php/dav/files/me/opcode/objects/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/remote.php/dav/files/me/opcode/objects/abc123</d:href>
+    <d:propstat><d:prop><d:resourcetype/></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/remote.php/dav/files/me/opcode/objects/with%20space</d:href>
+    <d:propstat><d:prop><d:resourcetype/></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/remote.php/dav/files/me/opcode/objects/nested/</d:href>
+    <d:propstat><d:prop><d:resourcetype><D:collection></D:collection></d:resourcetype></d:prop></d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+        assert_eq!(parse_propfind_files(body), vec!["abc123", "with space"]);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("caf%C3%A9%2Fx"), "café/x");
+        // A multi-byte character after `%` is kept as it is
+        assert_eq!(percent_decode("%éé"), "%éé");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tauri::State;
+
+use super::cache::{AudioDb, SettingsDb};
+use super::error::AudioError;
+use super::secrets;
+use super::ElevenLabsState;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Settings key holding the configured webhooks
+pub const WEBHOOKS_KEY: &str = "webhooks";
+
+/// Fired when a TTS/SFX render has been saved to the library
+pub const EVENT_JOB_COMPLETED: &str = "job.completed";
+/// Fired when account usage crosses the warning threshold
+pub const EVENT_QUOTA_WARNING: &str = "quota.warning";
+/// Fired after a remote backup or restore finishes
+pub const EVENT_SYNC_COMPLETED: &str = "sync.completed";
+
+pub const ALL_EVENTS: &[&str] = &[EVENT_JOB_COMPLETED, EVENT_QUOTA_WARNING, EVENT_SYNC_COMPLETED];
+
+/// Fraction of the character quota at which `quota.warning` fires
+pub const QUOTA_WARNING_RATIO: f64 = 0.9;
+
+/// Delivery attempts per event before giving up
+const MAX_ATTEMPTS: u32 = 4;
+
+/// A configured webhook endpoint. The signing secret lives in the OS keyring.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub id: String,
+    pub url: String,
+    /// Events to deliver; empty means all events
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WebhookConfig {
+    fn secret_name(&self) -> String {
+        format!("webhook:{}", self.id)
+    }
+
+    fn wants(&self, event: &str) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.iter().any(|e| e == event))
+    }
+}
+
+/// Body POSTed to webhook endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub id: String,
+    pub event: String,
+    pub created_at: String,
+    pub data: serde_json::Value,
+}
+
+fn load_webhooks(conn: &rusqlite::Connection) -> Result<Vec<WebhookConfig>> {
+    match SettingsDb::get_setting(conn, WEBHOOKS_KEY)? {
+        Some(json) => Ok(serde_json::from_str(&json)?),
+        None => Ok(vec![]),
+    }
+}
+
+fn save_webhooks(conn: &rusqlite::Connection, webhooks: &[WebhookConfig]) -> Result<()> {
+    SettingsDb::save_setting(conn, WEBHOOKS_KEY, &serde_json::to_string(webhooks)?)
+}
+
+/// `sha256=<hex>` HMAC over `<timestamp>.<body>`, mirroring common webhook signing schemes
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let signature: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", signature)
+}
+
+/// Deliver one payload to one endpoint, retrying with exponential backoff
+async fn deliver(client: &Client, webhook: &WebhookConfig, payload: &WebhookPayload) -> Result<()> {
+    let body = serde_json::to_vec(payload)?;
+    let secret = secrets::get_secret(&webhook.secret_name())?;
+
+    let mut last_error = String::new();
+    for attempt in 0..MAX_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_secs(2u64.pow(attempt * 2))).await;
+        }
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let mut request = client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header("X-Opcode-Event", &payload.event)
+            .header("X-Opcode-Delivery", &payload.id)
+            .header("X-Opcode-Timestamp", timestamp.to_string())
+            .body(body.clone());
+        if let Some(secret) = &secret {
+            request = request.header("X-Opcode-Signature", sign_payload(secret, timestamp, &body));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            // Client errors other than rate limiting will not succeed on retry
+            Ok(response) if response.status().is_client_error() && response.status().as_u16() != 429 => {
+                return Err(anyhow!("Webhook rejected with {}", response.status()));
+            }
+            Ok(response) => last_error = format!("HTTP {}", response.status()),
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+
+    Err(anyhow!("Webhook delivery failed after {} attempts: {}", MAX_ATTEMPTS, last_error))
+}
+
+/// Deliver an event to every matching webhook and wait for the outcome
+pub async fn deliver_event(db: &AudioDb, event: &str, data: serde_json::Value) -> Result<()> {
+    let webhooks = db.call(|conn| load_webhooks(conn)).await.map_err(|e| anyhow!(e))?;
+    let targets: Vec<_> = webhooks.into_iter().filter(|w| w.wants(event)).collect();
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    let payload = WebhookPayload {
+        id: uuid::Uuid::new_v4().to_string(),
+        event: event.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        data,
+    };
+    let client = Client::builder().timeout(Duration::from_secs(15)).build()?;
+
+    for webhook in &targets {
+        if let Err(e) = deliver(&client, webhook, &payload).await {
+            log::warn!("Webhook {} ({}) failed for {}: {}", webhook.id, webhook.url, event, e);
+        }
+    }
+    Ok(())
+}
+
+/// Fire-and-forget delivery used by the pipeline and sync code. Runs as a
+/// supervised task, so deliveries still retrying at exit are reported.
+pub fn dispatch<T: Serialize>(state: &ElevenLabsState, event: &'static str, data: &T) {
+    let data = match serde_json::to_value(data) {
+        Ok(data) => data,
+        Err(e) => {
+            log::warn!("Failed to serialize {} webhook payload: {}", event, e);
+            return;
+        }
+    };
+    let db = match state.db() {
+        Ok(db) => db,
+        Err(e) => {
+            log::warn!("Failed to dispatch {} webhooks: {}", event, e);
+            return;
+        }
+    };
+
+    state.tasks().spawn(format!("webhook-{}", event), move |_shutdown| async move {
+        if let Err(e) = deliver_event(&db, event, data).await {
+            log::warn!("Failed to dispatch {} webhooks: {}", event, e);
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+/// List configured webhooks
+#[tauri::command]
+pub async fn list_webhooks(state: State<'_, Arc<ElevenLabsState>>) -> Result<Vec<WebhookConfig>, AudioError> {
+    state.call_db(|conn| load_webhooks(conn)).await
+}
+
+/// Create or update a webhook; `secret` replaces the stored signing secret when given
+#[tauri::command]
+pub async fn save_webhook(
+    state: State<'_, Arc<ElevenLabsState>>,
+    mut webhook: WebhookConfig,
+    secret: Option<String>,
+) -> Result<WebhookConfig, AudioError> {
+    let url = reqwest::Url::parse(&webhook.url).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+    if url.scheme() != "https" && url.scheme() != "http" {
+        return Err(AudioError::Validation("Webhook URL must be http(s)".to_string()));
+    }
+    if let Some(unknown) = webhook.events.iter().find(|e| !ALL_EVENTS.contains(&e.as_str())) {
+        return Err(AudioError::Validation(format!("Unknown webhook event: {}", unknown)));
+    }
+    if webhook.id.is_empty() {
+        webhook.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    if let Some(secret) = secret.filter(|s| !s.is_empty()) {
+        secrets::store_secret(&webhook.secret_name(), &secret)?;
+    }
+
+    state
+        .call_db(move |conn| {
+            let mut webhooks = load_webhooks(conn)?;
+            match webhooks.iter_mut().find(|w| w.id == webhook.id) {
+                Some(existing) => *existing = webhook.clone(),
+                None => webhooks.push(webhook.clone()),
+            }
+            save_webhooks(conn, &webhooks)?;
+            Ok(webhook)
+        })
+        .await
+}
+
+/// Remove a webhook and its signing secret
+#[tauri::command]
+pub async fn delete_webhook(state: State<'_, Arc<ElevenLabsState>>, id: String) -> Result<(), AudioError> {
+    state
+        .call_db(move |conn| {
+            let mut webhooks = load_webhooks(conn)?;
+
+            if let Some(webhook) = webhooks.iter().find(|w| w.id == id) {
+                secrets::delete_secret(&webhook.secret_name())?;
+            }
+            webhooks.retain(|w| w.id != id);
+            save_webhooks(conn, &webhooks)
+        })
+        .await
+}
+
+/// Send a signed `ping` to a single webhook and report whether it was accepted
+#[tauri::command]
+pub async fn test_webhook(state: State<'_, Arc<ElevenLabsState>>, id: String) -> Result<(), AudioError> {
+    let webhook = state
+        .call_db(|conn| load_webhooks(conn))
+        .await?
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or_else(|| format!("Webhook not found: {}", id))?;
+
+    let payload = WebhookPayload {
+        id: uuid::Uuid::new_v4().to_string(),
+        event: "ping".to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        data: serde_json::json!({}),
+    };
+    let client = Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()?;
+
+    Ok(deliver(&client, &webhook, &payload).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload() {
+        // HMAC-SHA256 of `1700000000.{}`, as a receiver would compute it
+        assert_eq!(
+            sign_payload("secret", 1700000000, b"{}"),
+            "sha256=b8569b78799ff9e3cbff0fc2d63a33a2b57f3282abd07c37ae5e8e7d79a5f163"
+        );
+        assert_ne!(
+            sign_payload("secret", 1700000000, b"{}"),
+            sign_payload("secret", 1700000001, b"{}")
+        );
+    }
+}
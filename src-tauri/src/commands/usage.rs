@@ -8,15 +8,15 @@ use tauri::command;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageEntry {
-    timestamp: String,
-    model: String,
-    input_tokens: u64,
-    output_tokens: u64,
-    cache_creation_tokens: u64,
-    cache_read_tokens: u64,
-    cost: f64,
-    session_id: String,
-    project_path: String,
+    pub(crate) timestamp: String,
+    pub(crate) model: String,
+    pub(crate) input_tokens: u64,
+    pub(crate) output_tokens: u64,
+    pub(crate) cache_creation_tokens: u64,
+    pub(crate) cache_read_tokens: u64,
+    pub(crate) cost: f64,
+    pub(crate) session_id: String,
+    pub(crate) project_path: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -249,7 +249,7 @@ fn get_earliest_timestamp(path: &PathBuf) -> Option<String> {
     None
 }
 
-fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
+pub(crate) fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
     let mut all_entries = Vec::new();
     let mut processed_hashes = HashSet::new();
     let projects_dir = claude_path.join("projects");
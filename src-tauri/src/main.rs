@@ -36,12 +36,7 @@ use commands::mcp::{
     mcp_serve, mcp_test_connection,
 };
 
-use commands::eleven_labs::{
-    assign_voice_to_character, delete_cached_audio, eleven_labs_clone_voice,
-    eleven_labs_delete_voice, eleven_labs_generate_sfx, eleven_labs_get_usage,
-    eleven_labs_has_api_key, eleven_labs_list_voices, eleven_labs_set_api_key,
-    eleven_labs_tts, get_cached_audio, list_character_voices, ElevenLabsState,
-};
+use commands::eleven_labs::ElevenLabsState;
 use commands::proxy::{apply_proxy_settings, get_proxy_settings, save_proxy_settings};
 use commands::storage::{
     storage_delete_row, storage_execute_sql, storage_insert_row, storage_list_tables,
@@ -51,19 +46,46 @@ use commands::usage::{
     get_session_stats, get_usage_by_date_range, get_usage_details, get_usage_stats,
 };
 use process::ProcessRegistryState;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use tauri::Manager;
 
 #[cfg(target_os = "macos")]
 use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
 
 fn main() {
-    // Initialize logger
-    env_logger::init();
+    // Initialize logger; saved levels and file output are applied in setup
+    commands::eleven_labs::logging::init();
+
+    // `opcode audio ...` runs headless and never opens a window
+    if let Some(code) = commands::eleven_labs::cli::run_from_args() {
+        std::process::exit(code);
+    }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| {
+                    if event.state() == tauri_plugin_global_shortcut::ShortcutState::Pressed {
+                        commands::eleven_labs::hotkeys::handle_shortcut(app, shortcut);
+                    }
+                })
+                .build(),
+        )
+        .register_asynchronous_uri_scheme_protocol(
+            commands::eleven_labs::protocol::SCHEME,
+            |ctx, request, responder| {
+                let app = ctx.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<Arc<ElevenLabsState>>();
+                    responder.respond(commands::eleven_labs::protocol::respond(&state, &request).await);
+                });
+            },
+        )
         .setup(|app| {
             // Initialize agents database
             let conn = init_database(&app.handle()).expect("Failed to initialize agents database");
@@ -154,7 +176,62 @@ fn main() {
             app.manage(ClaudeProcessState::default());
 
             // Initialize Eleven Labs state
-            app.manage(ElevenLabsState::new());
+            app.manage(Arc::new(ElevenLabsState::new()));
+            app.state::<Arc<ElevenLabsState>>().auth().attach(app.handle().clone());
+            let saved_logging = app
+                .state::<Arc<ElevenLabsState>>()
+                .db()
+                .and_then(|db| commands::eleven_labs::logging::apply_saved(&db));
+            if let Err(e) = saved_logging {
+                log::warn!("Failed to apply logging settings: {}", e);
+            }
+            app.manage(commands::eleven_labs::speech_queue::SpeechQueue::start(
+                app.handle().clone(),
+            ));
+            app.manage(commands::eleven_labs::realtime::RealtimeState::default());
+            if let Err(e) = commands::eleven_labs::hotkeys::register_saved(app.handle()) {
+                log::warn!("Failed to register audio hotkeys: {}", e);
+            }
+
+            // Start the local audio HTTP API if the user enabled it, then pre-warm
+            // the Eleven Labs connection
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app_handle.state::<Arc<ElevenLabsState>>();
+                if let Err(e) = commands::eleven_labs::http_api::start_if_enabled(&state).await {
+                    log::warn!("Failed to start audio HTTP API: {}", e);
+                }
+                if let Err(e) = commands::eleven_labs::clipboard::start_if_enabled(&app_handle).await {
+                    log::warn!("Failed to start clipboard speak: {}", e);
+                }
+                if let Err(e) = commands::eleven_labs::warm_up_client(&state).await {
+                    log::debug!("Failed to pre-warm Eleven Labs connection: {}", e);
+                }
+            });
+
+            // Route opcode:// links into the audio deep link handler
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+
+                #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+                if let Err(e) = app.deep_link().register_all() {
+                    log::warn!("Failed to register deep link schemes: {}", e);
+                }
+
+                let app_handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    for url in event.urls() {
+                        commands::eleven_labs::deep_link::handle_url(&app_handle, url.to_string());
+                    }
+                });
+
+                // Links that launched the app
+                if let Ok(Some(urls)) = app.deep_link().get_current() {
+                    for url in urls {
+                        commands::eleven_labs::deep_link::handle_url(app.handle(), url.to_string());
+                    }
+                }
+            }
 
             // Apply window vibrancy with rounded corners on macOS
             #[cfg(target_os = "macos")]
@@ -192,7 +269,12 @@ fn main() {
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Focused(true) = event {
+                commands::eleven_labs::notifications::on_window_focused(window.app_handle());
+            }
+        })
+        .invoke_handler(commands::eleven_labs::generate_handler![
             // Claude & Project Management
             list_projects,
             create_project,
@@ -298,20 +380,18 @@ fn main() {
             // Proxy Settings
             get_proxy_settings,
             save_proxy_settings,
-            // Eleven Labs Audio
-            eleven_labs_set_api_key,
-            eleven_labs_has_api_key,
-            eleven_labs_list_voices,
-            eleven_labs_clone_voice,
-            eleven_labs_delete_voice,
-            eleven_labs_tts,
-            eleven_labs_generate_sfx,
-            eleven_labs_get_usage,
-            assign_voice_to_character,
-            list_character_voices,
-            get_cached_audio,
-            delete_cached_audio,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Let audio tasks finish in-progress work before the process exits
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<Arc<ElevenLabsState>>();
+                tauri::async_runtime::block_on(
+                    state
+                        .tasks()
+                        .shutdown(commands::eleven_labs::supervisor::SHUTDOWN_GRACE),
+                );
+            }
+        });
 }
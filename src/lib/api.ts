@@ -1,3 +1,4 @@
+import { Channel, convertFileSrc } from '@tauri-apps/api/core';
 import { apiCall } from './apiAdapter';
 import type { HooksConfiguration } from '@/types/hooks';
 
@@ -122,6 +123,9 @@ export interface Agent {
   hooks?: string; // JSON string of HooksConfiguration
   created_at: string;
   updated_at: string;
+  tts_model?: string;
+  tts_speed?: number;
+  narration?: 'full' | 'summary' | 'off';
 }
 
 export interface AgentExport {
@@ -440,6 +444,277 @@ export interface VoiceProfile {
   labels?: Record<string, string>;
   preview_url?: string;
   settings: VoiceSettings;
+  /** Samples of a cloned voice, when fetched from the provider */
+  samples?: VoiceSample[];
+}
+
+/**
+ * An uploaded sample of a cloned voice
+ */
+export interface VoiceSample {
+  sample_id: string;
+  file_name?: string;
+}
+
+/**
+ * How fetched voices were written to the local cache
+ */
+export interface VoiceCacheStats {
+  inserted: number;
+  updated: number;
+  failed: number;
+}
+
+/**
+ * Voices for the picker, with the result of caching them
+ */
+export interface VoiceList {
+  voices: VoiceProfile[];
+  /** Null when the voices were read from the local cache */
+  cache: VoiceCacheStats | null;
+}
+
+/**
+ * A user-defined group of voices, independent of Eleven Labs categories
+ */
+export interface VoiceCollection {
+  id: string;
+  name: string;
+  voice_ids: string[];
+  created_at: string;
+}
+
+/**
+ * Narrows a voice listing; omitted fields keep every voice
+ */
+export interface VoiceFilter {
+  favorites_only?: boolean;
+  collection_id?: string | null;
+}
+
+/**
+ * A voice recently used for generation
+ */
+export interface RecentVoice {
+  voice_id: string;
+  /** Null when the voice isn't in the local cache */
+  name: string | null;
+  use_count: number;
+  last_used_at: string;
+}
+
+/**
+ * Whether the Eleven Labs API key must be re-entered. Also emitted as the
+ * `audio-auth-required` event when the key is rejected mid-session; queued
+ * speech resumes once a valid key is saved.
+ */
+export interface AuthStatus {
+  auth_required: boolean;
+  waiting_jobs: number;
+}
+
+/**
+ * Directory the audio cache is using. When the configured or OS cache
+ * directory isn't writable a fallback is used, and `skipped` says why.
+ */
+export interface AudioCacheLocation {
+  path: string;
+  source: "configured" | "default" | "app_data" | "temp";
+  skipped: string[];
+}
+
+/**
+ * Filters for searching the shared voice library; omitted ones match anything
+ */
+export interface SharedVoiceFilter {
+  search?: string;
+  /** "male", "female" or "neutral" */
+  gender?: string;
+  /** e.g. "british", "american" */
+  accent?: string;
+  /** e.g. "narrative_story", "conversational" */
+  use_case?: string;
+  /** ISO 639-1 code */
+  language?: string;
+  /** Zero-based page */
+  page?: number;
+  page_size?: number;
+}
+
+/**
+ * A voice in the shared library
+ */
+export interface SharedVoice {
+  /** Owner of the voice, needed to add it to the account */
+  public_owner_id: string;
+  voice_id: string;
+  name: string;
+  description?: string;
+  gender?: string;
+  accent?: string;
+  age?: string;
+  use_case?: string;
+  language?: string;
+  preview_url?: string;
+  cloned_by_count: number;
+}
+
+/**
+ * A page of shared voice search results
+ */
+export interface SharedVoicePage {
+  voices: SharedVoice[];
+  has_more: boolean;
+}
+
+/**
+ * A generation kept in the provider's history
+ */
+export interface HistoryItem {
+  history_item_id: string;
+  voice_id?: string;
+  voice_name?: string;
+  model_id?: string;
+  text: string;
+  /** Unix seconds */
+  date_unix: number;
+  content_type?: string;
+  settings?: Record<string, unknown>;
+}
+
+/**
+ * A page of generation history, newest first
+ */
+export interface HistoryPage {
+  history: HistoryItem[];
+  /** Pass as startAfter to get the next page */
+  last_history_item_id?: string;
+  has_more: boolean;
+}
+
+/**
+ * An Eleven Labs model and what it can be used for
+ */
+export interface ModelInfo {
+  model_id: string;
+  name: string;
+  description?: string;
+  /** Usable for text-to-speech */
+  can_do_text_to_speech: boolean;
+  /** Usable for speech-to-speech */
+  can_do_voice_conversion: boolean;
+  languages: { language_id: string; name: string }[];
+  /** Longest text accepted in one request, if reported */
+  maximum_text_length_per_request?: number;
+}
+
+/**
+ * A candidate voice generated from a description, not yet saved
+ */
+export interface VoiceDesignPreview {
+  generated_voice_id: string;
+  /** Base64 audio of the preview text in this voice */
+  audio_base_64: string;
+  media_type?: string;
+  duration_secs?: number;
+}
+
+/**
+ * Candidate voices for a description and the text they speak
+ */
+export interface VoiceDesignPreviews {
+  previews: VoiceDesignPreview[];
+  text: string;
+}
+
+/**
+ * A file submitted for dubbing into another language
+ */
+export interface DubbingJob {
+  id: string;
+  /** The provider's id of the dubbing project */
+  dubbing_id: string;
+  source_path: string;
+  source_lang?: string;
+  target_lang: string;
+  status: "dubbing" | "downloading" | "completed" | "failed";
+  error?: string;
+  /** Rough time the provider expects the dub to take */
+  expected_duration_seconds?: number;
+  /** The downloaded dub, once completed */
+  audio_id?: string;
+  project_id?: string;
+  created_at: string;
+  updated_at: string;
+}
+
+/**
+ * A word (or spacing/sound event) of a transcript, timed in seconds
+ */
+export interface TranscriptionWord {
+  text: string;
+  start: number;
+  end: number;
+  type: "word" | "spacing" | "audio_event";
+  speaker_id?: string;
+}
+
+/**
+ * Speech-to-text result of a local audio file
+ */
+export interface Transcription {
+  id: string;
+  source_path: string;
+  project_id?: string;
+  language_code?: string;
+  text: string;
+  words: TranscriptionWord[];
+  created_at: string;
+}
+
+/**
+ * What the last rebuild of damaged audio tables salvaged. The damaged
+ * database is kept at `backup_path`.
+ */
+export interface AudioDbRecoveryReport {
+  backup_path?: string | null;
+  tables: Array<{ table: string; rows_salvaged: number; complete: boolean }>;
+  recovered_at: string;
+}
+
+/**
+ * Whether the app database is encrypted, and whether a passphrase change is
+ * waiting for a restart
+ */
+export interface DbEncryptionStatus {
+  /** This build can encrypt the database */
+  supported: boolean;
+  encrypted: boolean;
+  pending_change: boolean;
+}
+
+/**
+ * The audio schema version of the database against the latest this build knows
+ */
+export interface AudioSchemaStatus {
+  version: number;
+  latest: number;
+  applied: Array<{ version: number; description: string; applied_at: string }>;
+}
+
+/**
+ * Log levels and rotating file output. RUST_LOG, when set, overrides the levels.
+ */
+export interface LoggingConfig {
+  /** error, warn, info, debug or trace */
+  level: string;
+  /** Levels for individual modules, e.g. { "opcode_lib::commands::eleven_labs::client": "debug" } */
+  modules: Record<string, string>;
+  file_enabled: boolean;
+  /** Defaults to the app's local data directory */
+  directory: string | null;
+  rotation: "hourly" | "daily" | "never";
+  max_files: number;
 }
 
 /**
@@ -452,6 +727,48 @@ export interface CharacterVoice {
   voice_name: string;
   project_id?: string;
   created_at: string;
+  /** Voice settings preset the character is spoken with */
+  preset?: string;
+}
+
+/**
+ * Named voice settings, applied by name to requests and character mappings
+ */
+export interface VoicePreset {
+  name: string;
+  settings: VoiceSettings;
+  created_at: string;
+  updated_at: string;
+}
+
+/**
+ * Result of importing a voice presets file
+ */
+export interface PresetImportSummary {
+  imported: number;
+  /** Presets not imported because one with the same name exists */
+  skipped: string[];
+}
+
+/**
+ * Agent to voice mapping
+ */
+export interface AgentVoice {
+  id: string;
+  agent_id: number;
+  voice_id: string;
+  voice_name: string;
+  created_at: string;
+}
+
+/**
+ * Records that still use a voice, reported by a `voice_in_use` error
+ */
+export interface VoiceReferences {
+  characters: CharacterVoice[];
+  agents: AgentVoice[];
+  /** Ids of cached renders made with the voice */
+  audio_ids: string[];
 }
 
 /**
@@ -471,6 +788,276 @@ export interface GeneratedAudio {
   supabase_url?: string;
   metadata: Record<string, any>;
   created_at: string;
+  /** What the audio was requested with; absent for imported or edited audio */
+  params?: GenerationParams | null;
+  /** Returned for an identical earlier request rather than rendered again */
+  cached?: boolean;
+}
+
+/**
+ * Request parameters stored with each generation
+ */
+export interface GenerationParams {
+  provider: string;
+  model_id?: string | null;
+  voice_id?: string | null;
+  voice_settings?: VoiceSettings | null;
+  output_format?: string | null;
+  seed?: number | null;
+  language?: string | null;
+  duration_seconds?: number | null;
+  prompt_influence?: number | null;
+}
+
+/**
+ * Characters and credits one provider consumed in a day, week or month
+ */
+export interface UsageHistoryEntry {
+  /** YYYY-MM-DD for days, the Monday for weeks, YYYY-MM for months */
+  period: string;
+  provider: string;
+  generations: number;
+  characters: number;
+  credits: number;
+}
+
+/**
+ * Days to include (YYYY-MM-DD, inclusive); omitted ends are unbounded
+ */
+export interface UsageRange {
+  from?: string | null;
+  to?: string | null;
+}
+
+/**
+ * Whether text is spelled out for a locale before synthesis (numbers, dates,
+ * currencies, abbreviations). Supported locales: en-US, en-GB, de-DE.
+ */
+export interface TextNormalizationConfig {
+  enabled: boolean;
+  locale: string;
+}
+
+/**
+ * Messages on the channel of a streamed generation: `started`, a `chunk` of
+ * base64 audio per piece received, then `finished` with the saved record
+ */
+export type AudioStreamEvent =
+  | { event: "started"; data: { mimeType: string } }
+  | { event: "chunk"; data: { offset: number; data: string } }
+  | { event: "finished"; data: { audio: GeneratedAudio } };
+
+/**
+ * When each character of rendered speech starts and ends, in seconds
+ */
+export interface CharacterAlignment {
+  characters: string[];
+  character_start_times_seconds: number[];
+  character_end_times_seconds: number[];
+}
+
+/**
+ * A word of rendered speech with its timing in seconds and its character range in the text
+ */
+export interface WordTiming {
+  word: string;
+  start: number;
+  end: number;
+  char_start: number;
+  char_end: number;
+}
+
+/**
+ * Timing of rendered speech, stored as `metadata.alignment` on records rendered with timestamps
+ */
+export interface SpeechAlignment {
+  characters: CharacterAlignment;
+  words: WordTiming[];
+}
+
+/**
+ * Filters, order and paging for listing cached audio; unset filters match everything
+ */
+export interface AudioQuery {
+  voice_id?: string;
+  /** RFC 3339 bounds on when the audio was created, inclusive */
+  created_after?: string;
+  created_before?: string;
+  /** Only audio carrying this tag, in any case */
+  tag?: string;
+  favorites_only?: boolean;
+  sort?: "newest" | "oldest" | "longest" | "shortest" | "prompt";
+  /** Most records to return; all of them when unset */
+  limit?: number;
+  offset?: number;
+}
+
+/**
+ * What a batch delete of cached audio removed
+ */
+export interface BatchDeleteResult {
+  deleted: number;
+  bytes_freed: number;
+}
+
+/**
+ * A cached audio record in the trash
+ */
+export interface TrashedAudio extends GeneratedAudio {
+  deleted_at: string;
+}
+
+/**
+ * The favorite mark and tags of a cached audio record
+ */
+export interface AudioTags {
+  audio_id: string;
+  favorite: boolean;
+  /** In the order they were added */
+  tags: string[];
+}
+
+/**
+ * A tag and how many cached audio records carry it
+ */
+export interface TagCount {
+  tag: string;
+  count: number;
+}
+
+/**
+ * A cached record whose file no longer exists
+ */
+export interface DanglingRecord {
+  id: string;
+  audio_type: "tts" | "sfx" | "music";
+  prompt: string;
+  local_path: string;
+}
+
+/**
+ * What a cache reconciliation found and, when repairing, did
+ */
+export interface ReconcileReport {
+  /** Files in the cache directory that no record references */
+  orphan_files: string[];
+  dangling_records: DanglingRecord[];
+  deleted_files: number;
+  /** Records added for orphan files */
+  registered: GeneratedAudio[];
+  removed_records: number;
+}
+
+/**
+ * One request's share of speech too long for a single request, listed as `metadata.chunks`
+ */
+export interface SpeechChunk {
+  text: string;
+  request_id: string | null;
+  /** Where the chunk's audio starts in the file, in bytes */
+  offset: number;
+  bytes: number;
+}
+
+/**
+ * How words are grouped into subtitle cues
+ */
+export interface CueOptions {
+  /** Longest cue text in characters (default 42) */
+  max_line_length?: number;
+  /** Longest time a cue stays on screen (default 5s) */
+  max_cue_seconds?: number;
+}
+
+/**
+ * What happened in a Conversational AI session
+ */
+export type RealtimeEvent =
+  | { event: "started"; data: { conversationId: string; audioFormat: string } }
+  | { event: "audio"; data: { data: string } }
+  | { event: "userTranscript"; data: { text: string } }
+  | { event: "agentResponse"; data: { text: string } }
+  | { event: "agentResponseCorrection"; data: { text: string } }
+  | { event: "interruption" }
+  | { event: "ended"; data: { error: string | null } };
+
+/**
+ * Payload of a `realtime-session` event
+ */
+export type RealtimeSessionEvent = { sessionId: string } & RealtimeEvent;
+
+/**
+ * An open Conversational AI session
+ */
+export interface RealtimeSessionInfo {
+  session_id: string;
+  agent_id: string;
+  microphone: boolean;
+  started_at: string;
+}
+
+/**
+ * Payload of an `audio-download-progress` event
+ */
+export interface DownloadProgress {
+  downloadId: string;
+  receivedBytes: number;
+  /** Total size announced by the provider, if any */
+  expectedBytes: number | null;
+}
+
+/**
+ * Payload of a `tts-chunk` event from a WebSocket speech stream
+ */
+export interface TtsChunk {
+  streamId: string;
+  /** Where data starts in the file */
+  offset: number;
+  /** Base64 audio bytes */
+  data: string;
+}
+
+/**
+ * Payload of a `tts-stream-end` event: audio when completed, error when failed, neither when stopped
+ */
+export interface TtsStreamEnd {
+  streamId: string;
+  audio: GeneratedAudio | null;
+  error: string | null;
+}
+
+/**
+ * Changes to apply when regenerating a cached record; unset fields keep the stored values
+ */
+export interface RegenerateOverrides {
+  voice_id?: string | null;
+  model_id?: string | null;
+  voice_settings?: VoiceSettings | null;
+  output_format?: string | null;
+  duration_seconds?: number | null;
+  prompt_influence?: number | null;
+  /** Seed for the new take; not taken from the original, so takes differ unless set */
+  seed?: number | null;
+}
+
+/**
+ * A voice setting that differs between the two variants of a comparison
+ */
+export interface SettingChange {
+  field: string;
+  a: unknown;
+  b: unknown;
+}
+
+/**
+ * Two renders of the same text with different voice settings
+ */
+export interface GenerationComparison {
+  id: string;
+  audio_a: GeneratedAudio;
+  audio_b: GeneratedAudio;
+  settings_diff: SettingChange[];
+  created_at: string;
 }
 
 /**
@@ -489,6 +1076,35 @@ export interface ElevenLabsUsageInfo {
   can_use_professional_voice_cloning: boolean;
 }
 
+/**
+ * Error rejected by the audio commands
+ */
+export interface AudioError {
+  code: "db" | "io" | "provider" | "not_configured" | "validation" | "voice_in_use" | "invalid_samples" | "cancelled" | "other";
+  message: string;
+  /** The same request may succeed if made again later */
+  retryable: boolean;
+  /** Set for provider errors */
+  kind?: "unauthorized" | "quota_exceeded" | "rate_limited" | "network" | "unavailable" | "api";
+  /** Set for voice_in_use errors */
+  references?: VoiceReferences;
+  /** Set for invalid_samples errors */
+  samples?: SampleDiagnostic[];
+}
+
+/**
+ * A voice clone sample that would be rejected, found before uploading
+ */
+export type SampleDiagnostic = {
+  path: string;
+  message: string;
+} & (
+  | { issue: "unreadable" }
+  | { issue: "unsupported_codec" }
+  | { issue: "too_short"; seconds: number }
+  | { issue: "too_large"; bytes: number }
+);
+
 /**
  * Result of adding a server
  */
@@ -517,7 +1133,15 @@ export interface ImportServerResult {
 }
 
 /**
- * API client for interacting with the Rust backend
+ * URL the webview can load a cached audio clip from, served by the
+ * `opcode-audio://` protocol (supports range requests for seeking)
+ */
+export function audioSrc(audioId: string): string {
+  return convertFileSrc(audioId, "opcode-audio");
+}
+
+/**
+ * API client for interacting with the Rust backend
  */
 export const api = {
   /**
@@ -643,1587 +1267,2912 @@ export const api = {
    * @param path - Optional path to open the session in
    * @returns Promise resolving when the session is opened
    */
-  async openNewSession(path?: string): Promise<string> {
+  async openNewSession(path?: string): Promise<string> {
+    try {
+      return await apiCall<string>("open_new_session", { path });
+    } catch (error) {
+      console.error("Failed to open new session:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads the CLAUDE.md system prompt file
+   * @returns Promise resolving to the system prompt content
+   */
+  async getSystemPrompt(): Promise<string> {
+    try {
+      return await apiCall<string>("get_system_prompt");
+    } catch (error) {
+      console.error("Failed to get system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks if Claude Code is installed and gets its version
+   * @returns Promise resolving to the version status
+   */
+  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+    try {
+      return await apiCall<ClaudeVersionStatus>("check_claude_version");
+    } catch (error) {
+      console.error("Failed to check Claude version:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the CLAUDE.md system prompt file
+   * @param content - The new content for the system prompt
+   * @returns Promise resolving when the file is saved
+   */
+  async saveSystemPrompt(content: string): Promise<string> {
+    try {
+      return await apiCall<string>("save_system_prompt", { content });
+    } catch (error) {
+      console.error("Failed to save system prompt:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves the Claude settings file
+   * @param settings - The settings object to save
+   * @returns Promise resolving when the settings are saved
+   */
+  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+    try {
+      return await apiCall<string>("save_claude_settings", { settings });
+    } catch (error) {
+      console.error("Failed to save Claude settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Finds all CLAUDE.md files in a project directory
+   * @param projectPath - The absolute path to the project
+   * @returns Promise resolving to an array of CLAUDE.md files
+   */
+  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+    try {
+      return await apiCall<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+    } catch (error) {
+      console.error("Failed to find CLAUDE.md files:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @returns Promise resolving to the file content
+   */
+  async readClaudeMdFile(filePath: string): Promise<string> {
+    try {
+      return await apiCall<string>("read_claude_md_file", { filePath });
+    } catch (error) {
+      console.error("Failed to read CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves a specific CLAUDE.md file
+   * @param filePath - The absolute path to the file
+   * @param content - The new content for the file
+   * @returns Promise resolving when the file is saved
+   */
+  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+    try {
+      return await apiCall<string>("save_claude_md_file", { filePath, content });
+    } catch (error) {
+      console.error("Failed to save CLAUDE.md file:", error);
+      throw error;
+    }
+  },
+
+  // Agent API methods
+  
+  /**
+   * Lists all CC agents
+   * @returns Promise resolving to an array of agents
+   */
+  async listAgents(): Promise<Agent[]> {
+    try {
+      return await apiCall<Agent[]>('list_agents');
+    } catch (error) {
+      console.error("Failed to list agents:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Creates a new agent
+   * @param name - The agent name
+   * @param icon - The icon identifier
+   * @param system_prompt - The system prompt for the agent
+   * @param default_task - Optional default task
+   * @param model - Optional model (defaults to 'sonnet')
+   * @param hooks - Optional hooks configuration as JSON string
+   * @param narration - Optional TTS model, speed and narration verbosity
+   * @returns Promise resolving to the created agent
+   */
+  async createAgent(
+    name: string, 
+    icon: string, 
+    system_prompt: string, 
+    default_task?: string, 
+    model?: string,
+    hooks?: string,
+    narration?: Pick<Agent, 'tts_model' | 'tts_speed' | 'narration'>
+  ): Promise<Agent> {
+    try {
+      return await apiCall<Agent>('create_agent', { 
+        name, 
+        icon, 
+        systemPrompt: system_prompt,
+        defaultTask: default_task,
+        model,
+        hooks,
+        ttsModel: narration?.tts_model,
+        ttsSpeed: narration?.tts_speed,
+        narration: narration?.narration
+      });
+    } catch (error) {
+      console.error("Failed to create agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates an existing agent
+   * @param id - The agent ID
+   * @param name - The updated name
+   * @param icon - The updated icon
+   * @param system_prompt - The updated system prompt
+   * @param default_task - Optional default task
+   * @param model - Optional model
+   * @param hooks - Optional hooks configuration as JSON string
+   * @param narration - Optional narration settings; omitted fields are left unchanged
+   * @returns Promise resolving to the updated agent
+   */
+  async updateAgent(
+    id: number, 
+    name: string, 
+    icon: string, 
+    system_prompt: string, 
+    default_task?: string, 
+    model?: string,
+    hooks?: string,
+    narration?: Pick<Agent, 'tts_model' | 'tts_speed' | 'narration'>
+  ): Promise<Agent> {
+    try {
+      return await apiCall<Agent>('update_agent', { 
+        id, 
+        name, 
+        icon, 
+        systemPrompt: system_prompt,
+        defaultTask: default_task,
+        model,
+        hooks,
+        ttsModel: narration?.tts_model,
+        ttsSpeed: narration?.tts_speed,
+        narration: narration?.narration
+      });
+    } catch (error) {
+      console.error("Failed to update agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes an agent
+   * @param id - The agent ID to delete
+   * @returns Promise resolving when the agent is deleted
+   */
+  async deleteAgent(id: number): Promise<void> {
+    try {
+      return await apiCall('delete_agent', { id });
+    } catch (error) {
+      console.error("Failed to delete agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets a single agent by ID
+   * @param id - The agent ID
+   * @returns Promise resolving to the agent
+   */
+  async getAgent(id: number): Promise<Agent> {
+    try {
+      return await apiCall<Agent>('get_agent', { id });
+    } catch (error) {
+      console.error("Failed to get agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Exports a single agent to JSON format
+   * @param id - The agent ID to export
+   * @returns Promise resolving to the JSON string
+   */
+  async exportAgent(id: number): Promise<string> {
+    try {
+      return await apiCall<string>('export_agent', { id });
+    } catch (error) {
+      console.error("Failed to export agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports an agent from JSON data
+   * @param jsonData - The JSON string containing the agent export
+   * @returns Promise resolving to the imported agent
+   */
+  async importAgent(jsonData: string): Promise<Agent> {
+    try {
+      return await apiCall<Agent>('import_agent', { jsonData });
+    } catch (error) {
+      console.error("Failed to import agent:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports an agent from a file
+   * @param filePath - The path to the JSON file
+   * @returns Promise resolving to the imported agent
+   */
+  async importAgentFromFile(filePath: string): Promise<Agent> {
+    try {
+      return await apiCall<Agent>('import_agent_from_file', { filePath });
+    } catch (error) {
+      console.error("Failed to import agent from file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Executes an agent
+   * @param agentId - The agent ID to execute
+   * @param projectPath - The project path to run the agent in
+   * @param task - The task description
+   * @param model - Optional model override
+   * @returns Promise resolving to the run ID when execution starts
+   */
+  async executeAgent(agentId: number, projectPath: string, task: string, model?: string): Promise<number> {
+    try {
+      return await apiCall<number>('execute_agent', { agentId, projectPath, task, model });
+    } catch (error) {
+      console.error("Failed to execute agent:", error);
+      // Return a sentinel value to indicate error
+      throw new Error(`Failed to execute agent: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Lists agent runs without metrics (basic info only)
+   * @param agentId - Optional agent ID to filter runs
+   * @returns Promise resolving to an array of agent runs
+   */
+  async listAgentRuns(agentId?: number): Promise<AgentRunWithMetrics[]> {
+    try {
+      return await apiCall<AgentRunWithMetrics[]>('list_agent_runs', { agentId });
+    } catch (error) {
+      console.error("Failed to list agent runs:", error);
+      // Return empty array instead of throwing to prevent UI crashes
+      return [];
+    }
+  },
+
+  /**
+   * Lists agent runs with metrics (includes token counts and duration)
+   * @param agentId - Optional agent ID to filter runs
+   * @returns Promise resolving to an array of agent runs with metrics
+   */
+  async listAgentRunsWithMetrics(agentId?: number): Promise<AgentRunWithMetrics[]> {
+    try {
+      return await apiCall<AgentRunWithMetrics[]>('list_agent_runs_with_metrics', { agentId });
+    } catch (error) {
+      console.error("Failed to list agent runs with metrics:", error);
+      // Return empty array instead of throwing to prevent UI crashes
+      return [];
+    }
+  },
+
+  /**
+   * Gets a single agent run by ID with metrics
+   * @param id - The run ID
+   * @returns Promise resolving to the agent run with metrics
+   */
+  async getAgentRun(id: number): Promise<AgentRunWithMetrics> {
+    try {
+      return await apiCall<AgentRunWithMetrics>('get_agent_run', { id });
+    } catch (error) {
+      console.error("Failed to get agent run:", error);
+      throw new Error(`Failed to get agent run: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Gets a single agent run by ID with real-time metrics from JSONL
+   * @param id - The run ID
+   * @returns Promise resolving to the agent run with metrics
+   */
+  async getAgentRunWithRealTimeMetrics(id: number): Promise<AgentRunWithMetrics> {
+    try {
+      return await apiCall<AgentRunWithMetrics>('get_agent_run_with_real_time_metrics', { id });
+    } catch (error) {
+      console.error("Failed to get agent run with real-time metrics:", error);
+      throw new Error(`Failed to get agent run with real-time metrics: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Lists all currently running agent sessions
+   * @returns Promise resolving to list of running agent sessions
+   */
+  async listRunningAgentSessions(): Promise<AgentRun[]> {
+    try {
+      return await apiCall<AgentRun[]>('list_running_sessions');
+    } catch (error) {
+      console.error("Failed to list running agent sessions:", error);
+      throw new Error(`Failed to list running agent sessions: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Kills a running agent session
+   * @param runId - The run ID to kill
+   * @returns Promise resolving to whether the session was successfully killed
+   */
+  async killAgentSession(runId: number): Promise<boolean> {
+    try {
+      return await apiCall<boolean>('kill_agent_session', { runId });
+    } catch (error) {
+      console.error("Failed to kill agent session:", error);
+      throw new Error(`Failed to kill agent session: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Gets the status of a specific agent session
+   * @param runId - The run ID to check
+   * @returns Promise resolving to the session status or null if not found
+   */
+  async getSessionStatus(runId: number): Promise<string | null> {
+    try {
+      return await apiCall<string | null>('get_session_status', { runId });
+    } catch (error) {
+      console.error("Failed to get session status:", error);
+      throw new Error(`Failed to get session status: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Cleanup finished processes and update their status
+   * @returns Promise resolving to list of run IDs that were cleaned up
+   */
+  async cleanupFinishedProcesses(): Promise<number[]> {
+    try {
+      return await apiCall<number[]>('cleanup_finished_processes');
+    } catch (error) {
+      console.error("Failed to cleanup finished processes:", error);
+      throw new Error(`Failed to cleanup finished processes: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Get real-time output for a running session (with live output fallback)
+   * @param runId - The run ID to get output for
+   * @returns Promise resolving to the current session output (JSONL format)
+   */
+  async getSessionOutput(runId: number): Promise<string> {
+    try {
+      return await apiCall<string>('get_session_output', { runId });
+    } catch (error) {
+      console.error("Failed to get session output:", error);
+      throw new Error(`Failed to get session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Get live output directly from process stdout buffer
+   * @param runId - The run ID to get live output for
+   * @returns Promise resolving to the current live output
+   */
+  async getLiveSessionOutput(runId: number): Promise<string> {
+    try {
+      return await apiCall<string>('get_live_session_output', { runId });
+    } catch (error) {
+      console.error("Failed to get live session output:", error);
+      throw new Error(`Failed to get live session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Start streaming real-time output for a running session
+   * @param runId - The run ID to stream output for
+   * @returns Promise that resolves when streaming starts
+   */
+  async streamSessionOutput(runId: number): Promise<void> {
+    try {
+      return await apiCall<void>('stream_session_output', { runId });
+    } catch (error) {
+      console.error("Failed to start streaming session output:", error);
+      throw new Error(`Failed to start streaming session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  },
+
+  /**
+   * Loads the JSONL history for a specific session
+   */
+  async loadSessionHistory(sessionId: string, projectId: string): Promise<any[]> {
+    return apiCall("load_session_history", { sessionId, projectId });
+  },
+
+  /**
+   * Loads the JSONL history for a specific agent session
+   * Similar to loadSessionHistory but searches across all project directories
+   * @param sessionId - The session ID (UUID)
+   * @returns Promise resolving to array of session messages
+   */
+  async loadAgentSessionHistory(sessionId: string): Promise<any[]> {
+    try {
+      return await apiCall<any[]>('load_agent_session_history', { sessionId });
+    } catch (error) {
+      console.error("Failed to load agent session history:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Executes a new interactive Claude Code session with streaming output
+   */
+  async executeClaudeCode(projectPath: string, prompt: string, model: string): Promise<void> {
+    return apiCall("execute_claude_code", { projectPath, prompt, model });
+  },
+
+  /**
+   * Continues an existing Claude Code conversation with streaming output
+   */
+  async continueClaudeCode(projectPath: string, prompt: string, model: string): Promise<void> {
+    return apiCall("continue_claude_code", { projectPath, prompt, model });
+  },
+
+  /**
+   * Resumes an existing Claude Code session by ID with streaming output
+   */
+  async resumeClaudeCode(projectPath: string, sessionId: string, prompt: string, model: string): Promise<void> {
+    return apiCall("resume_claude_code", { projectPath, sessionId, prompt, model });
+  },
+
+  /**
+   * Cancels the currently running Claude Code execution
+   * @param sessionId - Optional session ID to cancel a specific session
+   */
+  async cancelClaudeExecution(sessionId?: string): Promise<void> {
+    return apiCall("cancel_claude_execution", { sessionId });
+  },
+
+  /**
+   * Lists all currently running Claude sessions
+   * @returns Promise resolving to list of running Claude sessions
+   */
+  async listRunningClaudeSessions(): Promise<any[]> {
+    return apiCall("list_running_claude_sessions");
+  },
+
+  /**
+   * Gets live output from a Claude session
+   * @param sessionId - The session ID to get output for
+   * @returns Promise resolving to the current live output
+   */
+  async getClaudeSessionOutput(sessionId: string): Promise<string> {
+    return apiCall("get_claude_session_output", { sessionId });
+  },
+
+  /**
+   * Lists files and directories in a given path
+   */
+  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
+    return apiCall("list_directory_contents", { directoryPath });
+  },
+
+  /**
+   * Searches for files and directories matching a pattern
+   */
+  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
+    return apiCall("search_files", { basePath, query });
+  },
+
+  /**
+   * Gets overall usage statistics
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageStats(): Promise<UsageStats> {
+    try {
+      return await apiCall<UsageStats>("get_usage_stats");
+    } catch (error) {
+      console.error("Failed to get usage stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics filtered by date range
+   * @param startDate - Start date (ISO format)
+   * @param endDate - End date (ISO format)
+   * @returns Promise resolving to usage statistics
+   */
+  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+    try {
+      return await apiCall<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+    } catch (error) {
+      console.error("Failed to get usage by date range:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets usage statistics grouped by session
+   * @param since - Optional start date (YYYYMMDD)
+   * @param until - Optional end date (YYYYMMDD)
+   * @param order - Optional sort order ('asc' or 'desc')
+   * @returns Promise resolving to an array of session usage data
+   */
+  async getSessionStats(
+    since?: string,
+    until?: string,
+    order?: "asc" | "desc"
+  ): Promise<ProjectUsage[]> {
+    try {
+      return await apiCall<ProjectUsage[]>("get_session_stats", {
+        since,
+        until,
+        order,
+      });
+    } catch (error) {
+      console.error("Failed to get session stats:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets detailed usage entries with optional filtering
+   * @param limit - Optional limit for number of entries
+   * @returns Promise resolving to array of usage entries
+   */
+  async getUsageDetails(limit?: number): Promise<UsageEntry[]> {
+    try {
+      return await apiCall<UsageEntry[]>("get_usage_details", { limit });
+    } catch (error) {
+      console.error("Failed to get usage details:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Creates a checkpoint for the current session state
+   */
+  async createCheckpoint(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    messageIndex?: number,
+    description?: string
+  ): Promise<CheckpointResult> {
+    return apiCall("create_checkpoint", {
+      sessionId,
+      projectId,
+      projectPath,
+      messageIndex,
+      description
+    });
+  },
+
+  /**
+   * Restores a session to a specific checkpoint
+   */
+  async restoreCheckpoint(
+    checkpointId: string,
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<CheckpointResult> {
+    return apiCall("restore_checkpoint", {
+      checkpointId,
+      sessionId,
+      projectId,
+      projectPath
+    });
+  },
+
+  /**
+   * Lists all checkpoints for a session
+   */
+  async listCheckpoints(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<Checkpoint[]> {
+    return apiCall("list_checkpoints", {
+      sessionId,
+      projectId,
+      projectPath
+    });
+  },
+
+  /**
+   * Forks a new timeline branch from a checkpoint
+   */
+  async forkFromCheckpoint(
+    checkpointId: string,
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    newSessionId: string,
+    description?: string
+  ): Promise<CheckpointResult> {
+    return apiCall("fork_from_checkpoint", {
+      checkpointId,
+      sessionId,
+      projectId,
+      projectPath,
+      newSessionId,
+      description
+    });
+  },
+
+  /**
+   * Gets the timeline for a session
+   */
+  async getSessionTimeline(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<SessionTimeline> {
+    return apiCall("get_session_timeline", {
+      sessionId,
+      projectId,
+      projectPath
+    });
+  },
+
+  /**
+   * Updates checkpoint settings for a session
+   */
+  async updateCheckpointSettings(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    autoCheckpointEnabled: boolean,
+    checkpointStrategy: CheckpointStrategy
+  ): Promise<void> {
+    return apiCall("update_checkpoint_settings", {
+      sessionId,
+      projectId,
+      projectPath,
+      autoCheckpointEnabled,
+      checkpointStrategy
+    });
+  },
+
+  /**
+   * Gets diff between two checkpoints
+   */
+  async getCheckpointDiff(
+    fromCheckpointId: string,
+    toCheckpointId: string,
+    sessionId: string,
+    projectId: string
+  ): Promise<CheckpointDiff> {
+    try {
+      return await apiCall<CheckpointDiff>("get_checkpoint_diff", {
+        fromCheckpointId,
+        toCheckpointId,
+        sessionId,
+        projectId
+      });
+    } catch (error) {
+      console.error("Failed to get checkpoint diff:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Tracks a message for checkpointing
+   */
+  async trackCheckpointMessage(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    message: string
+  ): Promise<void> {
+    try {
+      await apiCall("track_checkpoint_message", {
+        sessionId,
+        projectId,
+        projectPath,
+        message
+      });
+    } catch (error) {
+      console.error("Failed to track checkpoint message:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Checks if auto-checkpoint should be triggered
+   */
+  async checkAutoCheckpoint(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    message: string
+  ): Promise<boolean> {
+    try {
+      return await apiCall<boolean>("check_auto_checkpoint", {
+        sessionId,
+        projectId,
+        projectPath,
+        message
+      });
+    } catch (error) {
+      console.error("Failed to check auto checkpoint:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Triggers cleanup of old checkpoints
+   */
+  async cleanupOldCheckpoints(
+    sessionId: string,
+    projectId: string,
+    projectPath: string,
+    keepCount: number
+  ): Promise<number> {
+    try {
+      return await apiCall<number>("cleanup_old_checkpoints", {
+        sessionId,
+        projectId,
+        projectPath,
+        keepCount
+      });
+    } catch (error) {
+      console.error("Failed to cleanup old checkpoints:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets checkpoint settings for a session
+   */
+  async getCheckpointSettings(
+    sessionId: string,
+    projectId: string,
+    projectPath: string
+  ): Promise<{
+    auto_checkpoint_enabled: boolean;
+    checkpoint_strategy: CheckpointStrategy;
+    total_checkpoints: number;
+    current_checkpoint_id?: string;
+  }> {
+    try {
+      return await apiCall("get_checkpoint_settings", {
+        sessionId,
+        projectId,
+        projectPath
+      });
+    } catch (error) {
+      console.error("Failed to get checkpoint settings:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Clears checkpoint manager for a session (cleanup on session end)
+   */
+  async clearCheckpointManager(sessionId: string): Promise<void> {
+    try {
+      await apiCall("clear_checkpoint_manager", { sessionId });
+    } catch (error) {
+      console.error("Failed to clear checkpoint manager:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Tracks a batch of messages for a session for checkpointing
+   */
+  trackSessionMessages: (
+    sessionId: string, 
+    projectId: string, 
+    projectPath: string, 
+    messages: string[]
+  ): Promise<void> =>
+    apiCall("track_session_messages", { sessionId, projectId, projectPath, messages }),
+
+  /**
+   * Adds a new MCP server
+   */
+  async mcpAdd(
+    name: string,
+    transport: string,
+    command?: string,
+    args: string[] = [],
+    env: Record<string, string> = {},
+    url?: string,
+    scope: string = "local"
+  ): Promise<AddServerResult> {
+    try {
+      return await apiCall<AddServerResult>("mcp_add", {
+        name,
+        transport,
+        command,
+        args,
+        env,
+        url,
+        scope
+      });
+    } catch (error) {
+      console.error("Failed to add MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Lists all configured MCP servers
+   */
+  async mcpList(): Promise<MCPServer[]> {
+    try {
+      console.log("API: Calling mcp_list...");
+      const result = await apiCall<MCPServer[]>("mcp_list");
+      console.log("API: mcp_list returned:", result);
+      return result;
+    } catch (error) {
+      console.error("API: Failed to list MCP servers:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets details for a specific MCP server
+   */
+  async mcpGet(name: string): Promise<MCPServer> {
+    try {
+      return await apiCall<MCPServer>("mcp_get", { name });
+    } catch (error) {
+      console.error("Failed to get MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Removes an MCP server
+   */
+  async mcpRemove(name: string): Promise<string> {
+    try {
+      return await apiCall<string>("mcp_remove", { name });
+    } catch (error) {
+      console.error("Failed to remove MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Adds an MCP server from JSON configuration
+   */
+  async mcpAddJson(name: string, jsonConfig: string, scope: string = "local"): Promise<AddServerResult> {
+    try {
+      return await apiCall<AddServerResult>("mcp_add_json", { name, jsonConfig, scope });
+    } catch (error) {
+      console.error("Failed to add MCP server from JSON:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Imports MCP servers from Claude Desktop
+   */
+  async mcpAddFromClaudeDesktop(scope: string = "local"): Promise<ImportResult> {
+    try {
+      return await apiCall<ImportResult>("mcp_add_from_claude_desktop", { scope });
+    } catch (error) {
+      console.error("Failed to import from Claude Desktop:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Starts Claude Code as an MCP server
+   */
+  async mcpServe(): Promise<string> {
+    try {
+      return await apiCall<string>("mcp_serve");
+    } catch (error) {
+      console.error("Failed to start MCP server:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Tests connection to an MCP server
+   */
+  async mcpTestConnection(name: string): Promise<string> {
+    try {
+      return await apiCall<string>("mcp_test_connection", { name });
+    } catch (error) {
+      console.error("Failed to test MCP connection:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Resets project-scoped server approval choices
+   */
+  async mcpResetProjectChoices(): Promise<string> {
+    try {
+      return await apiCall<string>("mcp_reset_project_choices");
+    } catch (error) {
+      console.error("Failed to reset project choices:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Gets the status of MCP servers
+   */
+  async mcpGetServerStatus(): Promise<Record<string, ServerStatus>> {
+    try {
+      return await apiCall<Record<string, ServerStatus>>("mcp_get_server_status");
+    } catch (error) {
+      console.error("Failed to get server status:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads .mcp.json from the current project
+   */
+  async mcpReadProjectConfig(projectPath: string): Promise<MCPProjectConfig> {
+    try {
+      return await apiCall<MCPProjectConfig>("mcp_read_project_config", { projectPath });
+    } catch (error) {
+      console.error("Failed to read project MCP config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Saves .mcp.json to the current project
+   */
+  async mcpSaveProjectConfig(projectPath: string, config: MCPProjectConfig): Promise<string> {
+    try {
+      return await apiCall<string>("mcp_save_project_config", { projectPath, config });
+    } catch (error) {
+      console.error("Failed to save project MCP config:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Get the stored Claude binary path from settings
+   * @returns Promise resolving to the path if set, null otherwise
+   */
+  async getClaudeBinaryPath(): Promise<string | null> {
+    try {
+      return await apiCall<string | null>("get_claude_binary_path");
+    } catch (error) {
+      console.error("Failed to get Claude binary path:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Set the Claude binary path in settings
+   * @param path - The absolute path to the Claude binary
+   * @returns Promise resolving when the path is saved
+   */
+  async setClaudeBinaryPath(path: string): Promise<void> {
+    try {
+      return await apiCall<void>("set_claude_binary_path", { path });
+    } catch (error) {
+      console.error("Failed to set Claude binary path:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * List all available Claude installations on the system
+   * @returns Promise resolving to an array of Claude installations
+   */
+  async listClaudeInstallations(): Promise<ClaudeInstallation[]> {
+    try {
+      return await apiCall<ClaudeInstallation[]>("list_claude_installations");
+    } catch (error) {
+      console.error("Failed to list Claude installations:", error);
+      throw error;
+    }
+  },
+
+  // Storage API methods
+
+  /**
+   * Lists all tables in the SQLite database
+   * @returns Promise resolving to an array of table information
+   */
+  async storageListTables(): Promise<any[]> {
+    try {
+      return await apiCall<any[]>("storage_list_tables");
+    } catch (error) {
+      console.error("Failed to list tables:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Reads table data with pagination
+   * @param tableName - Name of the table to read
+   * @param page - Page number (1-indexed)
+   * @param pageSize - Number of rows per page
+   * @param searchQuery - Optional search query
+   * @returns Promise resolving to table data with pagination info
+   */
+  async storageReadTable(
+    tableName: string,
+    page: number,
+    pageSize: number,
+    searchQuery?: string
+  ): Promise<any> {
+    try {
+      return await apiCall<any>("storage_read_table", {
+        tableName,
+        page,
+        pageSize,
+        searchQuery,
+      });
+    } catch (error) {
+      console.error("Failed to read table:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Updates a row in a table
+   * @param tableName - Name of the table
+   * @param primaryKeyValues - Map of primary key column names to values
+   * @param updates - Map of column names to new values
+   * @returns Promise resolving when the row is updated
+   */
+  async storageUpdateRow(
+    tableName: string,
+    primaryKeyValues: Record<string, any>,
+    updates: Record<string, any>
+  ): Promise<void> {
+    try {
+      return await apiCall<void>("storage_update_row", {
+        tableName,
+        primaryKeyValues,
+        updates,
+      });
+    } catch (error) {
+      console.error("Failed to update row:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Deletes a row from a table
+   * @param tableName - Name of the table
+   * @param primaryKeyValues - Map of primary key column names to values
+   * @returns Promise resolving when the row is deleted
+   */
+  async storageDeleteRow(
+    tableName: string,
+    primaryKeyValues: Record<string, any>
+  ): Promise<void> {
+    try {
+      return await apiCall<void>("storage_delete_row", {
+        tableName,
+        primaryKeyValues,
+      });
+    } catch (error) {
+      console.error("Failed to delete row:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Inserts a new row into a table
+   * @param tableName - Name of the table
+   * @param values - Map of column names to values
+   * @returns Promise resolving to the last insert row ID
+   */
+  async storageInsertRow(
+    tableName: string,
+    values: Record<string, any>
+  ): Promise<number> {
+    try {
+      return await apiCall<number>("storage_insert_row", {
+        tableName,
+        values,
+      });
+    } catch (error) {
+      console.error("Failed to insert row:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Executes a raw SQL query
+   * @param query - SQL query string
+   * @returns Promise resolving to query result
+   */
+  async storageExecuteSql(query: string): Promise<any> {
+    try {
+      return await apiCall<any>("storage_execute_sql", { query });
+    } catch (error) {
+      console.error("Failed to execute SQL:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Resets the entire database
+   * @returns Promise resolving when the database is reset
+   */
+  async storageResetDatabase(): Promise<void> {
+    try {
+      return await apiCall<void>("storage_reset_database");
+    } catch (error) {
+      console.error("Failed to reset database:", error);
+      throw error;
+    }
+  },
+
+  // Theme settings helpers
+
+  /**
+   * Gets a setting from the app_settings table
+   * @param key - The setting key to retrieve
+   * @returns Promise resolving to the setting value or null if not found
+   */
+  async getSetting(key: string): Promise<string | null> {
     try {
-      return await apiCall<string>("open_new_session", { path });
+      // Fast path: check localStorage mirror to avoid startup flicker
+      if (typeof window !== 'undefined' && 'localStorage' in window) {
+        const cached = window.localStorage.getItem(`app_setting:${key}`);
+        if (cached !== null) {
+          return cached;
+        }
+      }
+      // Use storageReadTable to safely query the app_settings table
+      const result = await this.storageReadTable('app_settings', 1, 1000);
+      const setting = result?.data?.find((row: any) => row.key === key);
+      return setting?.value || null;
     } catch (error) {
-      console.error("Failed to open new session:", error);
-      throw error;
+      console.error(`Failed to get setting ${key}:`, error);
+      return null;
     }
   },
 
   /**
-   * Reads the CLAUDE.md system prompt file
-   * @returns Promise resolving to the system prompt content
+   * Saves a setting to the app_settings table (insert or update)
+   * @param key - The setting key
+   * @param value - The setting value
+   * @returns Promise resolving when the setting is saved
    */
-  async getSystemPrompt(): Promise<string> {
+  async saveSetting(key: string, value: string): Promise<void> {
     try {
-      return await apiCall<string>("get_system_prompt");
+      // Mirror to localStorage for instant availability on next startup
+      if (typeof window !== 'undefined' && 'localStorage' in window) {
+        try {
+          window.localStorage.setItem(`app_setting:${key}`, value);
+        } catch (_ignore) {
+          // best-effort; continue to persist in DB
+        }
+      }
+      // Try to update first
+      try {
+        await this.storageUpdateRow(
+          'app_settings',
+          { key },
+          { value }
+        );
+      } catch (updateError) {
+        // If update fails (row doesn't exist), insert new row
+        await this.storageInsertRow('app_settings', { key, value });
+      }
     } catch (error) {
-      console.error("Failed to get system prompt:", error);
+      console.error(`Failed to save setting ${key}:`, error);
       throw error;
     }
   },
 
   /**
-   * Checks if Claude Code is installed and gets its version
-   * @returns Promise resolving to the version status
+   * Get hooks configuration for a specific scope
+   * @param scope - The configuration scope: 'user', 'project', or 'local'
+   * @param projectPath - Project path (required for project and local scopes)
+   * @returns Promise resolving to the hooks configuration
    */
-  async checkClaudeVersion(): Promise<ClaudeVersionStatus> {
+  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfiguration> {
     try {
-      return await apiCall<ClaudeVersionStatus>("check_claude_version");
+      return await apiCall<HooksConfiguration>("get_hooks_config", { scope, projectPath });
     } catch (error) {
-      console.error("Failed to check Claude version:", error);
+      console.error("Failed to get hooks config:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the CLAUDE.md system prompt file
-   * @param content - The new content for the system prompt
-   * @returns Promise resolving when the file is saved
+   * Update hooks configuration for a specific scope
+   * @param scope - The configuration scope: 'user', 'project', or 'local'
+   * @param hooks - The hooks configuration to save
+   * @param projectPath - Project path (required for project and local scopes)
+   * @returns Promise resolving to success message
    */
-  async saveSystemPrompt(content: string): Promise<string> {
+  async updateHooksConfig(
+    scope: 'user' | 'project' | 'local',
+    hooks: HooksConfiguration,
+    projectPath?: string
+  ): Promise<string> {
     try {
-      return await apiCall<string>("save_system_prompt", { content });
+      return await apiCall<string>("update_hooks_config", { scope, projectPath, hooks });
     } catch (error) {
-      console.error("Failed to save system prompt:", error);
+      console.error("Failed to update hooks config:", error);
       throw error;
     }
   },
 
   /**
-   * Saves the Claude settings file
-   * @param settings - The settings object to save
-   * @returns Promise resolving when the settings are saved
+   * Validate a hook command syntax
+   * @param command - The shell command to validate
+   * @returns Promise resolving to validation result
    */
-  async saveClaudeSettings(settings: ClaudeSettings): Promise<string> {
+  async validateHookCommand(command: string): Promise<{ valid: boolean; message: string }> {
     try {
-      return await apiCall<string>("save_claude_settings", { settings });
+      return await apiCall<{ valid: boolean; message: string }>("validate_hook_command", { command });
     } catch (error) {
-      console.error("Failed to save Claude settings:", error);
+      console.error("Failed to validate hook command:", error);
       throw error;
     }
   },
 
   /**
-   * Finds all CLAUDE.md files in a project directory
-   * @param projectPath - The absolute path to the project
-   * @returns Promise resolving to an array of CLAUDE.md files
+   * Get merged hooks configuration (respecting priority)
+   * @param projectPath - The project path
+   * @returns Promise resolving to merged hooks configuration
    */
-  async findClaudeMdFiles(projectPath: string): Promise<ClaudeMdFile[]> {
+  async getMergedHooksConfig(projectPath: string): Promise<HooksConfiguration> {
     try {
-      return await apiCall<ClaudeMdFile[]>("find_claude_md_files", { projectPath });
+      const [userHooks, projectHooks, localHooks] = await Promise.all([
+        this.getHooksConfig('user'),
+        this.getHooksConfig('project', projectPath),
+        this.getHooksConfig('local', projectPath)
+      ]);
+
+      // Import HooksManager for merging
+      const { HooksManager } = await import('@/lib/hooksManager');
+      return HooksManager.mergeConfigs(userHooks, projectHooks, localHooks);
     } catch (error) {
-      console.error("Failed to find CLAUDE.md files:", error);
+      console.error("Failed to get merged hooks config:", error);
       throw error;
     }
   },
 
+  // Slash Commands API methods
+
   /**
-   * Reads a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @returns Promise resolving to the file content
+   * Lists all available slash commands
+   * @param projectPath - Optional project path to include project-specific commands
+   * @returns Promise resolving to array of slash commands
    */
-  async readClaudeMdFile(filePath: string): Promise<string> {
+  async slashCommandsList(projectPath?: string): Promise<SlashCommand[]> {
     try {
-      return await apiCall<string>("read_claude_md_file", { filePath });
+      return await apiCall<SlashCommand[]>("slash_commands_list", { projectPath });
     } catch (error) {
-      console.error("Failed to read CLAUDE.md file:", error);
+      console.error("Failed to list slash commands:", error);
       throw error;
     }
   },
 
   /**
-   * Saves a specific CLAUDE.md file
-   * @param filePath - The absolute path to the file
-   * @param content - The new content for the file
-   * @returns Promise resolving when the file is saved
+   * Gets a single slash command by ID
+   * @param commandId - Unique identifier of the command
+   * @returns Promise resolving to the slash command
    */
-  async saveClaudeMdFile(filePath: string, content: string): Promise<string> {
+  async slashCommandGet(commandId: string): Promise<SlashCommand> {
     try {
-      return await apiCall<string>("save_claude_md_file", { filePath, content });
+      return await apiCall<SlashCommand>("slash_command_get", { commandId });
     } catch (error) {
-      console.error("Failed to save CLAUDE.md file:", error);
+      console.error("Failed to get slash command:", error);
       throw error;
     }
   },
 
-  // Agent API methods
-  
   /**
-   * Lists all CC agents
-   * @returns Promise resolving to an array of agents
+   * Creates or updates a slash command
+   * @param scope - Command scope: "project" or "user"
+   * @param name - Command name (without prefix)
+   * @param namespace - Optional namespace for organization
+   * @param content - Markdown content of the command
+   * @param description - Optional description
+   * @param allowedTools - List of allowed tools for this command
+   * @param projectPath - Required for project scope commands
+   * @returns Promise resolving to the saved command
    */
-  async listAgents(): Promise<Agent[]> {
+  async slashCommandSave(
+    scope: string,
+    name: string,
+    namespace: string | undefined,
+    content: string,
+    description: string | undefined,
+    allowedTools: string[],
+    projectPath?: string
+  ): Promise<SlashCommand> {
     try {
-      return await apiCall<Agent[]>('list_agents');
+      return await apiCall<SlashCommand>("slash_command_save", {
+        scope,
+        name,
+        namespace,
+        content,
+        description,
+        allowedTools,
+        projectPath
+      });
     } catch (error) {
-      console.error("Failed to list agents:", error);
+      console.error("Failed to save slash command:", error);
       throw error;
     }
   },
 
   /**
-   * Creates a new agent
-   * @param name - The agent name
-   * @param icon - The icon identifier
-   * @param system_prompt - The system prompt for the agent
-   * @param default_task - Optional default task
-   * @param model - Optional model (defaults to 'sonnet')
-   * @param hooks - Optional hooks configuration as JSON string
-   * @returns Promise resolving to the created agent
+   * Deletes a slash command
+   * @param commandId - Unique identifier of the command to delete
+   * @param projectPath - Optional project path for deleting project commands
+   * @returns Promise resolving to deletion message
    */
-  async createAgent(
-    name: string, 
-    icon: string, 
-    system_prompt: string, 
-    default_task?: string, 
-    model?: string,
-    hooks?: string
-  ): Promise<Agent> {
+  async slashCommandDelete(commandId: string, projectPath?: string): Promise<string> {
     try {
-      return await apiCall<Agent>('create_agent', { 
-        name, 
-        icon, 
-        systemPrompt: system_prompt,
-        defaultTask: default_task,
-        model,
-        hooks
-      });
+      return await apiCall<string>("slash_command_delete", { commandId, projectPath });
     } catch (error) {
-      console.error("Failed to create agent:", error);
+      console.error("Failed to delete slash command:", error);
       throw error;
     }
   },
 
+  // ========== Eleven Labs Audio API ==========
+
   /**
-   * Updates an existing agent
-   * @param id - The agent ID
-   * @param name - The updated name
-   * @param icon - The updated icon
-   * @param system_prompt - The updated system prompt
-   * @param default_task - Optional default task
-   * @param model - Optional model
-   * @param hooks - Optional hooks configuration as JSON string
-   * @returns Promise resolving to the updated agent
+   * Sets the Eleven Labs API key
+   * @param apiKey - The API key to set
+   * @returns Promise resolving to true if valid
    */
-  async updateAgent(
-    id: number, 
-    name: string, 
-    icon: string, 
-    system_prompt: string, 
-    default_task?: string, 
-    model?: string,
-    hooks?: string
-  ): Promise<Agent> {
+  async elevenLabsSetApiKey(apiKey: string): Promise<boolean> {
     try {
-      return await apiCall<Agent>('update_agent', { 
-        id, 
-        name, 
-        icon, 
-        systemPrompt: system_prompt,
-        defaultTask: default_task,
-        model,
-        hooks
-      });
+      return await apiCall<boolean>("eleven_labs_set_api_key", { apiKey });
     } catch (error) {
-      console.error("Failed to update agent:", error);
+      console.error("Failed to set Eleven Labs API key:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes an agent
-   * @param id - The agent ID to delete
-   * @returns Promise resolving when the agent is deleted
+   * Checks if Eleven Labs API key is configured
+   * @returns Promise resolving to true if configured
    */
-  async deleteAgent(id: number): Promise<void> {
+  async elevenLabsHasApiKey(): Promise<boolean> {
     try {
-      return await apiCall('delete_agent', { id });
+      return await apiCall<boolean>("eleven_labs_has_api_key");
     } catch (error) {
-      console.error("Failed to delete agent:", error);
-      throw error;
+      console.error("Failed to check Eleven Labs API key:", error);
+      return false;
     }
   },
 
-  /**
-   * Gets a single agent by ID
-   * @param id - The agent ID
-   * @returns Promise resolving to the agent
+  /**
+   * Lists Eleven Labs voices from the local cache. They are refreshed in the
+   * background and an `audio-voices-updated` event carries the new VoiceList
+   * when they changed; the event is not filtered.
+   * @param filter - Keep only favorites and/or the voices of a collection
+   * @returns Promise resolving to the cached voices
    */
-  async getAgent(id: number): Promise<Agent> {
+  async elevenLabsListVoices(filter?: VoiceFilter): Promise<VoiceList> {
     try {
-      return await apiCall<Agent>('get_agent', { id });
+      return await apiCall<VoiceList>("eleven_labs_list_voices", { filter });
     } catch (error) {
-      console.error("Failed to get agent:", error);
+      console.error("Failed to list Eleven Labs voices:", error);
       throw error;
     }
   },
 
   /**
-   * Exports a single agent to JSON format
-   * @param id - The agent ID to export
-   * @returns Promise resolving to the JSON string
+   * Lists the voices most recently used for generation, newest first
+   * @param limit - Maximum number of voices (8 when omitted)
    */
-  async exportAgent(id: number): Promise<string> {
+  async listRecentVoices(limit?: number): Promise<RecentVoice[]> {
     try {
-      return await apiCall<string>('export_agent', { id });
+      return await apiCall<RecentVoice[]>("list_recent_voices", { limit });
     } catch (error) {
-      console.error("Failed to export agent:", error);
+      console.error("Failed to list recent voices:", error);
       throw error;
     }
   },
 
   /**
-   * Imports an agent from JSON data
-   * @param jsonData - The JSON string containing the agent export
-   * @returns Promise resolving to the imported agent
+   * Marks a voice as a favorite, or clears the mark
+   * @param voiceId - The voice ID
+   * @param favorite - Whether the voice is a favorite
    */
-  async importAgent(jsonData: string): Promise<Agent> {
+  async setVoiceFavorite(voiceId: string, favorite: boolean): Promise<void> {
     try {
-      return await apiCall<Agent>('import_agent', { jsonData });
+      await apiCall<void>("set_voice_favorite", { voiceId, favorite });
     } catch (error) {
-      console.error("Failed to import agent:", error);
+      console.error("Failed to set voice favorite:", error);
       throw error;
     }
   },
 
   /**
-   * Imports an agent from a file
-   * @param filePath - The path to the JSON file
-   * @returns Promise resolving to the imported agent
+   * Lists the IDs of the favorite voices
    */
-  async importAgentFromFile(filePath: string): Promise<Agent> {
+  async listFavoriteVoices(): Promise<string[]> {
     try {
-      return await apiCall<Agent>('import_agent_from_file', { filePath });
+      return await apiCall<string[]>("list_favorite_voices");
     } catch (error) {
-      console.error("Failed to import agent from file:", error);
+      console.error("Failed to list favorite voices:", error);
       throw error;
     }
   },
 
   /**
-   * Executes an agent
-   * @param agentId - The agent ID to execute
-   * @param projectPath - The project path to run the agent in
-   * @param task - The task description
-   * @param model - Optional model override
-   * @returns Promise resolving to the run ID when execution starts
+   * Lists the voice collections by name
    */
-  async executeAgent(agentId: number, projectPath: string, task: string, model?: string): Promise<number> {
+  async listVoiceCollections(): Promise<VoiceCollection[]> {
     try {
-      return await apiCall<number>('execute_agent', { agentId, projectPath, task, model });
+      return await apiCall<VoiceCollection[]>("list_voice_collections");
     } catch (error) {
-      console.error("Failed to execute agent:", error);
-      // Return a sentinel value to indicate error
-      throw new Error(`Failed to execute agent: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to list voice collections:", error);
+      throw error;
     }
   },
 
   /**
-   * Lists agent runs without metrics (basic info only)
-   * @param agentId - Optional agent ID to filter runs
-   * @returns Promise resolving to an array of agent runs
+   * Creates an empty voice collection; names are unique regardless of case
+   * @param name - The collection name
    */
-  async listAgentRuns(agentId?: number): Promise<AgentRunWithMetrics[]> {
+  async createVoiceCollection(name: string): Promise<VoiceCollection> {
     try {
-      return await apiCall<AgentRunWithMetrics[]>('list_agent_runs', { agentId });
+      return await apiCall<VoiceCollection>("create_voice_collection", { name });
     } catch (error) {
-      console.error("Failed to list agent runs:", error);
-      // Return empty array instead of throwing to prevent UI crashes
-      return [];
+      console.error("Failed to create voice collection:", error);
+      throw error;
     }
   },
 
   /**
-   * Lists agent runs with metrics (includes token counts and duration)
-   * @param agentId - Optional agent ID to filter runs
-   * @returns Promise resolving to an array of agent runs with metrics
+   * Renames a voice collection
+   * @param collectionId - The collection ID
+   * @param name - The new name
    */
-  async listAgentRunsWithMetrics(agentId?: number): Promise<AgentRunWithMetrics[]> {
+  async renameVoiceCollection(collectionId: string, name: string): Promise<VoiceCollection> {
     try {
-      return await apiCall<AgentRunWithMetrics[]>('list_agent_runs_with_metrics', { agentId });
+      return await apiCall<VoiceCollection>("rename_voice_collection", { collectionId, name });
     } catch (error) {
-      console.error("Failed to list agent runs with metrics:", error);
-      // Return empty array instead of throwing to prevent UI crashes
-      return [];
+      console.error("Failed to rename voice collection:", error);
+      throw error;
     }
   },
 
   /**
-   * Gets a single agent run by ID with metrics
-   * @param id - The run ID
-   * @returns Promise resolving to the agent run with metrics
+   * Deletes a voice collection; its voices are not affected
+   * @param collectionId - The collection ID
    */
-  async getAgentRun(id: number): Promise<AgentRunWithMetrics> {
+  async deleteVoiceCollection(collectionId: string): Promise<void> {
     try {
-      return await apiCall<AgentRunWithMetrics>('get_agent_run', { id });
+      await apiCall<void>("delete_voice_collection", { collectionId });
     } catch (error) {
-      console.error("Failed to get agent run:", error);
-      throw new Error(`Failed to get agent run: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to delete voice collection:", error);
+      throw error;
     }
   },
 
   /**
-   * Gets a single agent run by ID with real-time metrics from JSONL
-   * @param id - The run ID
-   * @returns Promise resolving to the agent run with metrics
+   * Adds a voice to a collection
+   * @param collectionId - The collection ID
+   * @param voiceId - The voice ID
    */
-  async getAgentRunWithRealTimeMetrics(id: number): Promise<AgentRunWithMetrics> {
+  async addVoiceToCollection(collectionId: string, voiceId: string): Promise<VoiceCollection> {
     try {
-      return await apiCall<AgentRunWithMetrics>('get_agent_run_with_real_time_metrics', { id });
+      return await apiCall<VoiceCollection>("add_voice_to_collection", { collectionId, voiceId });
     } catch (error) {
-      console.error("Failed to get agent run with real-time metrics:", error);
-      throw new Error(`Failed to get agent run with real-time metrics: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to add voice to collection:", error);
+      throw error;
     }
   },
 
   /**
-   * Lists all currently running agent sessions
-   * @returns Promise resolving to list of running agent sessions
+   * Removes a voice from a collection
+   * @param collectionId - The collection ID
+   * @param voiceId - The voice ID
    */
-  async listRunningAgentSessions(): Promise<AgentRun[]> {
+  async removeVoiceFromCollection(collectionId: string, voiceId: string): Promise<VoiceCollection> {
     try {
-      return await apiCall<AgentRun[]>('list_running_sessions');
+      return await apiCall<VoiceCollection>("remove_voice_from_collection", { collectionId, voiceId });
     } catch (error) {
-      console.error("Failed to list running agent sessions:", error);
-      throw new Error(`Failed to list running agent sessions: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to remove voice from collection:", error);
+      throw error;
     }
   },
 
   /**
-   * Kills a running agent session
-   * @param runId - The run ID to kill
-   * @returns Promise resolving to whether the session was successfully killed
+   * Lists the voice settings presets, by name
    */
-  async killAgentSession(runId: number): Promise<boolean> {
+  async listVoicePresets(): Promise<VoicePreset[]> {
     try {
-      return await apiCall<boolean>('kill_agent_session', { runId });
+      return await apiCall<VoicePreset[]>("list_voice_presets");
     } catch (error) {
-      console.error("Failed to kill agent session:", error);
-      throw new Error(`Failed to kill agent session: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to list voice presets:", error);
+      throw error;
     }
   },
 
   /**
-   * Gets the status of a specific agent session
-   * @param runId - The run ID to check
-   * @returns Promise resolving to the session status or null if not found
+   * Creates a preset, or updates the settings of the one with this name
    */
-  async getSessionStatus(runId: number): Promise<string | null> {
+  async saveVoicePreset(name: string, settings: VoiceSettings): Promise<VoicePreset> {
     try {
-      return await apiCall<string | null>('get_session_status', { runId });
+      return await apiCall<VoicePreset>("save_voice_preset", { name, settings });
     } catch (error) {
-      console.error("Failed to get session status:", error);
-      throw new Error(`Failed to get session status: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to save voice preset:", error);
+      throw error;
     }
   },
 
   /**
-   * Cleanup finished processes and update their status
-   * @returns Promise resolving to list of run IDs that were cleaned up
+   * Renames a preset; character mappings using it follow the new name
    */
-  async cleanupFinishedProcesses(): Promise<number[]> {
+  async renameVoicePreset(name: string, newName: string): Promise<VoicePreset> {
     try {
-      return await apiCall<number[]>('cleanup_finished_processes');
+      return await apiCall<VoicePreset>("rename_voice_preset", { name, newName });
     } catch (error) {
-      console.error("Failed to cleanup finished processes:", error);
-      throw new Error(`Failed to cleanup finished processes: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to rename voice preset:", error);
+      throw error;
     }
   },
 
   /**
-   * Get real-time output for a running session (with live output fallback)
-   * @param runId - The run ID to get output for
-   * @returns Promise resolving to the current session output (JSONL format)
+   * Deletes a preset; character mappings using it go back to the voice's settings
    */
-  async getSessionOutput(runId: number): Promise<string> {
+  async deleteVoicePreset(name: string): Promise<void> {
     try {
-      return await apiCall<string>('get_session_output', { runId });
+      return await apiCall<void>("delete_voice_preset", { name });
     } catch (error) {
-      console.error("Failed to get session output:", error);
-      throw new Error(`Failed to get session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to delete voice preset:", error);
+      throw error;
     }
   },
 
   /**
-   * Get live output directly from process stdout buffer
-   * @param runId - The run ID to get live output for
-   * @returns Promise resolving to the current live output
+   * Writes presets to a JSON file for sharing
+   * @param dest - File to write
+   * @param names - Presets to include; all of them when omitted
+   * @returns Promise resolving to the number of presets written
    */
-  async getLiveSessionOutput(runId: number): Promise<string> {
+  async exportVoicePresets(dest: string, names?: string[]): Promise<number> {
     try {
-      return await apiCall<string>('get_live_session_output', { runId });
+      return await apiCall<number>("export_voice_presets", { dest, names });
     } catch (error) {
-      console.error("Failed to get live session output:", error);
-      throw new Error(`Failed to get live session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to export voice presets:", error);
+      throw error;
     }
   },
 
   /**
-   * Start streaming real-time output for a running session
-   * @param runId - The run ID to stream output for
-   * @returns Promise that resolves when streaming starts
+   * Imports a presets file written by exportVoicePresets
+   * @param src - File to read
+   * @param overwrite - Replace presets with the same name instead of skipping them
    */
-  async streamSessionOutput(runId: number): Promise<void> {
+  async importVoicePresets(src: string, overwrite?: boolean): Promise<PresetImportSummary> {
     try {
-      return await apiCall<void>('stream_session_output', { runId });
+      return await apiCall<PresetImportSummary>("import_voice_presets", { src, overwrite });
     } catch (error) {
-      console.error("Failed to start streaming session output:", error);
-      throw new Error(`Failed to start streaming session output: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      console.error("Failed to import voice presets:", error);
+      throw error;
     }
   },
 
   /**
-   * Loads the JSONL history for a specific session
+   * Transcribes a local audio file with word timings
+   * @param sourcePath - Audio file to transcribe
+   * @param language - Optional ISO 639 language code; detected when omitted
+   * @param projectId - Optional project ID
+   * @returns Promise resolving to the stored transcription
    */
-  async loadSessionHistory(sessionId: string, projectId: string): Promise<any[]> {
-    return apiCall("load_session_history", { sessionId, projectId });
+  async transcribeAudioFile(sourcePath: string, language?: string, projectId?: string): Promise<Transcription> {
+    try {
+      return await apiCall<Transcription>("transcribe_audio_file", { sourcePath, language, projectId });
+    } catch (error) {
+      console.error("Failed to transcribe audio file:", error);
+      throw error;
+    }
   },
 
   /**
-   * Loads the JSONL history for a specific agent session
-   * Similar to loadSessionHistory but searches across all project directories
-   * @param sessionId - The session ID (UUID)
-   * @returns Promise resolving to array of session messages
+   * Lists past transcriptions, newest first
+   * @param projectId - Optional project ID to filter by
+   * @param limit - Maximum number to return (default 50)
    */
-  async loadAgentSessionHistory(sessionId: string): Promise<any[]> {
+  async listTranscriptions(projectId?: string, limit?: number): Promise<Transcription[]> {
     try {
-      return await apiCall<any[]>('load_agent_session_history', { sessionId });
+      return await apiCall<Transcription[]>("list_transcriptions", { projectId, limit });
     } catch (error) {
-      console.error("Failed to load agent session history:", error);
+      console.error("Failed to list transcriptions:", error);
       throw error;
     }
   },
 
   /**
-   * Executes a new interactive Claude Code session with streaming output
+   * Searches the shared voice library; request `page + 1` while `has_more` is set
+   * @param filter - Optional search filters and page
+   * @returns Promise resolving to a page of shared voices
    */
-  async executeClaudeCode(projectPath: string, prompt: string, model: string): Promise<void> {
-    return apiCall("execute_claude_code", { projectPath, prompt, model });
+  async elevenLabsSearchSharedVoices(filter?: SharedVoiceFilter): Promise<SharedVoicePage> {
+    try {
+      return await apiCall<SharedVoicePage>("eleven_labs_search_shared_voices", { filter });
+    } catch (error) {
+      console.error("Failed to search shared voices:", error);
+      throw error;
+    }
   },
 
   /**
-   * Continues an existing Claude Code conversation with streaming output
+   * Adds a voice from the shared library to the account
+   * @param publicOwnerId - Owner of the shared voice
+   * @param voiceId - The shared voice's ID
+   * @param name - Name to give the voice in the account
+   * @returns Promise resolving to the added voice profile
    */
-  async continueClaudeCode(projectPath: string, prompt: string, model: string): Promise<void> {
-    return apiCall("continue_claude_code", { projectPath, prompt, model });
+  async elevenLabsAddSharedVoice(publicOwnerId: string, voiceId: string, name: string): Promise<VoiceProfile> {
+    try {
+      return await apiCall<VoiceProfile>("eleven_labs_add_shared_voice", { publicOwnerId, voiceId, name });
+    } catch (error) {
+      console.error("Failed to add shared voice:", error);
+      throw error;
+    }
   },
 
   /**
-   * Resumes an existing Claude Code session by ID with streaming output
+   * Edits a voice; omitted fields are left as they are
+   * @param voiceId - The voice to edit
+   * @param changes - New name, description or labels, sample files to add and sample IDs to remove
+   * @param projectId - Optional project ID, for its before-upload hooks
+   * @returns Promise resolving to the updated voice profile
    */
-  async resumeClaudeCode(projectPath: string, sessionId: string, prompt: string, model: string): Promise<void> {
-    return apiCall("resume_claude_code", { projectPath, sessionId, prompt, model });
+  async elevenLabsEditVoice(
+    voiceId: string,
+    changes: {
+      name?: string;
+      description?: string;
+      labels?: Record<string, string>;
+      addFiles?: string[];
+      removeSampleIds?: string[];
+    },
+    projectId?: string
+  ): Promise<VoiceProfile> {
+    try {
+      return await apiCall<VoiceProfile>("eleven_labs_edit_voice", { voiceId, ...changes, projectId });
+    } catch (error) {
+      console.error("Failed to edit voice:", error);
+      throw error;
+    }
   },
 
   /**
-   * Cancels the currently running Claude Code execution
-   * @param sessionId - Optional session ID to cancel a specific session
+   * Generates candidate voices from a text description (voice design)
+   * @param voiceDescription - What the voice should sound like (20-1000 characters)
+   * @param text - Optional text for the previews to speak (100-1000 characters); generated when omitted
+   * @returns Promise resolving to the previews and the text they speak
    */
-  async cancelClaudeExecution(sessionId?: string): Promise<void> {
-    return apiCall("cancel_claude_execution", { sessionId });
+  async elevenLabsDesignVoicePreviews(voiceDescription: string, text?: string): Promise<VoiceDesignPreviews> {
+    try {
+      return await apiCall<VoiceDesignPreviews>("eleven_labs_design_voice_previews", { voiceDescription, text });
+    } catch (error) {
+      console.error("Failed to design voice:", error);
+      throw error;
+    }
   },
 
   /**
-   * Lists all currently running Claude sessions
-   * @returns Promise resolving to list of running Claude sessions
+   * Saves a voice design preview as a new voice
+   * @param name - Name for the new voice
+   * @param description - The description the preview was generated from
+   * @param generatedVoiceId - ID of the chosen preview
+   * @param labels - Optional labels object
+   * @returns Promise resolving to the new voice profile
    */
-  async listRunningClaudeSessions(): Promise<any[]> {
-    return apiCall("list_running_claude_sessions");
+  async elevenLabsCreateDesignedVoice(
+    name: string,
+    description: string,
+    generatedVoiceId: string,
+    labels?: Record<string, string>
+  ): Promise<VoiceProfile> {
+    try {
+      return await apiCall<VoiceProfile>("eleven_labs_create_designed_voice", {
+        name,
+        description,
+        generatedVoiceId,
+        labels,
+      });
+    } catch (error) {
+      console.error("Failed to create designed voice:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets live output from a Claude session
-   * @param sessionId - The session ID to get output for
-   * @returns Promise resolving to the current live output
+   * Clones a voice from audio files. Samples are checked before uploading:
+   * recordings over the size limit are split, and any other problem files are
+   * reported together as an `invalid_samples` error.
+   * @param name - Name for the cloned voice
+   * @param files - Array of file paths to audio samples
+   * @param description - Optional description
+   * @param labels - Optional labels object
+   * @returns Promise resolving to the cloned voice profile
    */
-  async getClaudeSessionOutput(sessionId: string): Promise<string> {
-    return apiCall("get_claude_session_output", { sessionId });
+  async elevenLabsCloneVoice(
+    name: string,
+    files: string[],
+    description?: string,
+    labels?: Record<string, string>
+  ): Promise<VoiceProfile> {
+    try {
+      return await apiCall<VoiceProfile>("eleven_labs_clone_voice", {
+        name,
+        files,
+        description,
+        labels,
+      });
+    } catch (error) {
+      console.error("Failed to clone voice:", error);
+      throw error;
+    }
   },
 
   /**
-   * Lists files and directories in a given path
+   * Deletes an Eleven Labs voice. Rejects with a `voice_in_use` AudioError
+   * listing its references while characters, agents or renders still use it.
+   * @param voiceId - The voice ID to delete
+   * @param force - Delete anyway, removing the voice's character and agent mappings
    */
-  async listDirectoryContents(directoryPath: string): Promise<FileEntry[]> {
-    return apiCall("list_directory_contents", { directoryPath });
+  async elevenLabsDeleteVoice(voiceId: string, force?: boolean): Promise<void> {
+    try {
+      await apiCall<void>("eleven_labs_delete_voice", { voiceId, force });
+    } catch (error) {
+      console.error("Failed to delete voice:", error);
+      throw error;
+    }
   },
 
   /**
-   * Searches for files and directories matching a pattern
+   * Generates text-to-speech audio
+   * @param text - The text to convert to speech
+   * @param voiceId - The voice ID to use
+   * @param modelId - Optional model ID
+   * @param voiceSettings - Optional voice settings
+   * @param normalizeText - Spell out numbers, dates and abbreviations; omitted follows the setting
+   * @param preset - Name of a voice settings preset, used when voiceSettings is not given
+   * @param jobId - ID to cancel the generation by with elevenLabsCancelGeneration
+   * @param seed - Seed making the take reproducible with regenerateWithSameSeed
+   * @param forceRegenerate - Render even when an identical request was rendered before
+   * @returns Promise resolving to generated audio info, `cached` when an earlier render was reused
    */
-  async searchFiles(basePath: string, query: string): Promise<FileEntry[]> {
-    return apiCall("search_files", { basePath, query });
+  async elevenLabsTTS(
+    text: string,
+    voiceId: string,
+    modelId?: string,
+    voiceSettings?: VoiceSettings,
+    normalizeText?: boolean,
+    preset?: string,
+    jobId?: string,
+    seed?: number,
+    forceRegenerate?: boolean
+  ): Promise<GeneratedAudio> {
+    try {
+      return await apiCall<GeneratedAudio>("eleven_labs_tts", {
+        text,
+        voiceId,
+        modelId,
+        voiceSettings,
+        normalizeText,
+        preset,
+        jobId,
+        seed,
+        forceRegenerate,
+      });
+    } catch (error) {
+      console.error("Failed to generate TTS:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets overall usage statistics
-   * @returns Promise resolving to usage statistics
+   * Generates text-to-speech audio, receiving the audio as it arrives so
+   * playback can start before the file is written (desktop only)
+   * @param text - The text to convert to speech
+   * @param voiceId - The voice ID to use
+   * @param onAudio - Called with each stream event: started, chunk, finished
+   * @param modelId - Optional model ID
+   * @param voiceSettings - Optional voice settings
+   * @param projectId - Project whose after-generation hooks run on the file
+   * @param normalizeText - Spell out numbers, dates and abbreviations; omitted follows the setting
+   * @param preset - Name of a voice settings preset, used when voiceSettings is not given
+   * @returns Promise resolving to generated audio info
    */
-  async getUsageStats(): Promise<UsageStats> {
+  async elevenLabsTTSStream(
+    text: string,
+    voiceId: string,
+    onAudio: (event: AudioStreamEvent) => void,
+    modelId?: string,
+    voiceSettings?: VoiceSettings,
+    projectId?: string,
+    normalizeText?: boolean,
+    preset?: string
+  ): Promise<GeneratedAudio> {
     try {
-      return await apiCall<UsageStats>("get_usage_stats");
+      const channel = new Channel<AudioStreamEvent>();
+      channel.onmessage = onAudio;
+      return await apiCall<GeneratedAudio>("eleven_labs_tts_stream", {
+        text,
+        voiceId,
+        modelId,
+        voiceSettings,
+        projectId,
+        normalizeText,
+        preset,
+        onAudio: channel,
+      });
     } catch (error) {
-      console.error("Failed to get usage stats:", error);
+      console.error("Failed to stream TTS:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics filtered by date range
-   * @param startDate - Start date (ISO format)
-   * @param endDate - End date (ISO format)
-   * @returns Promise resolving to usage statistics
+   * Generates text-to-speech with word timings, stored as `metadata.alignment` on the record
+   * @param text - The text to convert to speech
+   * @param voiceId - The voice ID to use
+   * @param modelId - Optional model ID
+   * @param voiceSettings - Optional voice settings
+   * @param projectId - Project whose after-generation hooks run on the file
+   * @param normalizeText - Spell out numbers, dates and abbreviations; omitted follows the setting
+   * @param preset - Name of a voice settings preset, used when voiceSettings is not given
+   * @param jobId - ID to cancel the generation by with elevenLabsCancelGeneration
+   * @returns Promise resolving to generated audio info
    */
-  async getUsageByDateRange(startDate: string, endDate: string): Promise<UsageStats> {
+  async elevenLabsTTSWithTimestamps(
+    text: string,
+    voiceId: string,
+    modelId?: string,
+    voiceSettings?: VoiceSettings,
+    projectId?: string,
+    normalizeText?: boolean,
+    preset?: string,
+    jobId?: string
+  ): Promise<GeneratedAudio> {
     try {
-      return await apiCall<UsageStats>("get_usage_by_date_range", { startDate, endDate });
+      return await apiCall<GeneratedAudio>("eleven_labs_tts_with_timestamps", {
+        text,
+        voiceId,
+        modelId,
+        voiceSettings,
+        projectId,
+        normalizeText,
+        preset,
+        jobId,
+      });
     } catch (error) {
-      console.error("Failed to get usage by date range:", error);
+      console.error("Failed to generate TTS with timestamps:", error);
       throw error;
     }
   },
 
   /**
-   * Gets usage statistics grouped by session
-   * @param since - Optional start date (YYYYMMDD)
-   * @param until - Optional end date (YYYYMMDD)
-   * @param order - Optional sort order ('asc' or 'desc')
-   * @returns Promise resolving to an array of session usage data
+   * Generates consecutive parts of a text as separate records that sound continuous,
+   * each conditioned on the text around it and the requests before it
+   * @param parts - The parts in order, e.g. paragraphs
+   * @param voiceId - The voice ID to use
+   * @param modelId - Optional model ID
+   * @param voiceSettings - Optional voice settings
+   * @param projectId - Project whose after-generation hooks run on the files
+   * @param normalizeText - Spell out numbers, dates and abbreviations; omitted follows the setting
+   * @param preset - Name of a voice settings preset, used when voiceSettings is not given
+   * @param jobId - ID to cancel the generation by with elevenLabsCancelGeneration
+   * @returns Promise resolving to a record per part, in order
    */
-  async getSessionStats(
-    since?: string,
-    until?: string,
-    order?: "asc" | "desc"
-  ): Promise<ProjectUsage[]> {
+  async elevenLabsTTSParts(
+    parts: string[],
+    voiceId: string,
+    modelId?: string,
+    voiceSettings?: VoiceSettings,
+    projectId?: string,
+    normalizeText?: boolean,
+    preset?: string,
+    jobId?: string
+  ): Promise<GeneratedAudio[]> {
     try {
-      return await apiCall<ProjectUsage[]>("get_session_stats", {
-        since,
-        until,
-        order,
+      return await apiCall<GeneratedAudio[]>("eleven_labs_tts_parts", {
+        parts,
+        voiceId,
+        modelId,
+        voiceSettings,
+        projectId,
+        normalizeText,
+        preset,
+        jobId,
       });
     } catch (error) {
-      console.error("Failed to get session stats:", error);
+      console.error("Failed to generate TTS parts:", error);
       throw error;
     }
   },
 
   /**
-   * Gets detailed usage entries with optional filtering
-   * @param limit - Optional limit for number of entries
-   * @returns Promise resolving to array of usage entries
+   * Writes subtitles for a record generated with timestamps
+   * @param audioId - The cached record
+   * @param dest - File to write
+   * @param format - "srt" (default) or "vtt"
+   * @param options - Cue length and duration limits
+   * @returns Promise resolving to the number of cues written
    */
-  async getUsageDetails(limit?: number): Promise<UsageEntry[]> {
+  async exportSubtitles(
+    audioId: string,
+    dest: string,
+    format?: "srt" | "vtt",
+    options?: CueOptions
+  ): Promise<number> {
     try {
-      return await apiCall<UsageEntry[]>("get_usage_details", { limit });
+      return await apiCall<number>("export_subtitles", { audioId, dest, format, options });
     } catch (error) {
-      console.error("Failed to get usage details:", error);
+      console.error("Failed to export subtitles:", error);
       throw error;
     }
   },
 
   /**
-   * Creates a checkpoint for the current session state
+   * Generates text-to-speech, emitting `audio-download-progress` events while the audio downloads
+   * @param downloadId - ID tagging this download's progress events
+   * @param text - The text to convert to speech
+   * @param voiceId - The voice ID to use
+   * @param modelId - Optional model ID
+   * @param voiceSettings - Optional voice settings
+   * @param projectId - Project whose after-generation hooks run on the file
+   * @param normalizeText - Spell out numbers, dates and abbreviations; omitted follows the setting
+   * @param preset - Name of a voice settings preset, used when voiceSettings is not given
+   * @returns Promise resolving to generated audio info
    */
-  async createCheckpoint(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    messageIndex?: number,
-    description?: string
-  ): Promise<CheckpointResult> {
-    return apiCall("create_checkpoint", {
-      sessionId,
-      projectId,
-      projectPath,
-      messageIndex,
-      description
-    });
+  async elevenLabsTTSWithProgress(
+    downloadId: string,
+    text: string,
+    voiceId: string,
+    modelId?: string,
+    voiceSettings?: VoiceSettings,
+    projectId?: string,
+    normalizeText?: boolean,
+    preset?: string
+  ): Promise<GeneratedAudio> {
+    try {
+      return await apiCall<GeneratedAudio>("eleven_labs_tts_with_progress", {
+        downloadId,
+        text,
+        voiceId,
+        modelId,
+        voiceSettings,
+        projectId,
+        normalizeText,
+        preset,
+      });
+    } catch (error) {
+      console.error("Failed to generate TTS with progress:", error);
+      throw error;
+    }
   },
 
   /**
-   * Restores a session to a specific checkpoint
+   * Starts text-to-speech over the streaming WebSocket. Audio arrives as `tts-chunk`
+   * events and the saved record with `tts-stream-end`.
+   * @param text - The text to convert to speech
+   * @param voiceId - The voice ID to use
+   * @param modelId - Optional model ID
+   * @param voiceSettings - Optional voice settings
+   * @param projectId - Project whose after-generation hooks run on the file
+   * @param normalizeText - Spell out numbers, dates and abbreviations; omitted follows the setting
+   * @param preset - Name of a voice settings preset, used when voiceSettings is not given
+   * @returns Promise resolving to the stream ID used in the events
    */
-  async restoreCheckpoint(
-    checkpointId: string,
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<CheckpointResult> {
-    return apiCall("restore_checkpoint", {
-      checkpointId,
-      sessionId,
-      projectId,
-      projectPath
-    });
+  async startTtsStream(
+    text: string,
+    voiceId: string,
+    modelId?: string,
+    voiceSettings?: VoiceSettings,
+    projectId?: string,
+    normalizeText?: boolean,
+    preset?: string
+  ): Promise<string> {
+    try {
+      return await apiCall<string>("start_tts_stream", {
+        text,
+        voiceId,
+        modelId,
+        voiceSettings,
+        projectId,
+        normalizeText,
+        preset,
+      });
+    } catch (error) {
+      console.error("Failed to start TTS stream:", error);
+      throw error;
+    }
   },
 
   /**
-   * Lists all checkpoints for a session
+   * Stops a WebSocket speech stream, discarding the partly written file
+   * @param streamId - ID returned by startTtsStream
+   * @returns Promise resolving to whether the stream was still running
    */
-  async listCheckpoints(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<Checkpoint[]> {
-    return apiCall("list_checkpoints", {
-      sessionId,
-      projectId,
-      projectPath
-    });
+  async stopTtsStream(streamId: string): Promise<boolean> {
+    try {
+      return await apiCall<boolean>("stop_tts_stream", { streamId });
+    } catch (error) {
+      console.error("Failed to stop TTS stream:", error);
+      throw error;
+    }
   },
 
   /**
-   * Forks a new timeline branch from a checkpoint
+   * Gets locally recorded usage per provider, bucketed by day, week or month
+   * @param range - Days to include
+   * @param groupBy - Bucket size, days by default
    */
-  async forkFromCheckpoint(
-    checkpointId: string,
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    newSessionId: string,
-    description?: string
-  ): Promise<CheckpointResult> {
-    return apiCall("fork_from_checkpoint", {
-      checkpointId,
-      sessionId,
-      projectId,
-      projectPath,
-      newSessionId,
-      description
-    });
+  async getUsageHistory(range?: UsageRange, groupBy?: "day" | "week" | "month"): Promise<UsageHistoryEntry[]> {
+    try {
+      return await apiCall<UsageHistoryEntry[]>("get_usage_history", { range, groupBy });
+    } catch (error) {
+      console.error("Failed to get usage history:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets the timeline for a session
+   * Gets the text normalization configuration of a project, or the global one
+   * @param projectId - The project, omitted for the global configuration
    */
-  async getSessionTimeline(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<SessionTimeline> {
-    return apiCall("get_session_timeline", {
-      sessionId,
-      projectId,
-      projectPath
-    });
+  async getTextNormalization(projectId?: string): Promise<TextNormalizationConfig | null> {
+    try {
+      return await apiCall<TextNormalizationConfig | null>("get_text_normalization", { projectId });
+    } catch (error) {
+      console.error("Failed to get text normalization:", error);
+      throw error;
+    }
   },
 
   /**
-   * Updates checkpoint settings for a session
+   * Saves the text normalization configuration of a project (or globally); null removes it
+   * @param projectId - The project, omitted for the global configuration
+   * @param config - The configuration
    */
-  async updateCheckpointSettings(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    autoCheckpointEnabled: boolean,
-    checkpointStrategy: CheckpointStrategy
-  ): Promise<void> {
-    return apiCall("update_checkpoint_settings", {
-      sessionId,
-      projectId,
-      projectPath,
-      autoCheckpointEnabled,
-      checkpointStrategy
-    });
+  async setTextNormalization(
+    projectId: string | undefined,
+    config: TextNormalizationConfig | null
+  ): Promise<TextNormalizationConfig | null> {
+    try {
+      return await apiCall<TextNormalizationConfig | null>("set_text_normalization", { projectId, config });
+    } catch (error) {
+      console.error("Failed to set text normalization:", error);
+      throw error;
+    }
   },
 
   /**
-   * Gets diff between two checkpoints
+   * Shows how text would be spelled out for a locale
+   * @param text - The text to normalize
+   * @param locale - A supported locale, e.g. "en-US"
    */
-  async getCheckpointDiff(
-    fromCheckpointId: string,
-    toCheckpointId: string,
-    sessionId: string,
-    projectId: string
-  ): Promise<CheckpointDiff> {
+  async previewTextNormalization(text: string, locale: string): Promise<string> {
     try {
-      return await apiCall<CheckpointDiff>("get_checkpoint_diff", {
-        fromCheckpointId,
-        toCheckpointId,
-        sessionId,
-        projectId
-      });
+      return await apiCall<string>("preview_text_normalization", { text, locale });
     } catch (error) {
-      console.error("Failed to get checkpoint diff:", error);
+      console.error("Failed to preview text normalization:", error);
       throw error;
     }
   },
 
   /**
-   * Tracks a message for checkpointing
+   * Generates sound effects
+   * @param text - Description of the sound effect
+   * @param durationSeconds - Optional duration (default 3s)
+   * @param promptInfluence - Optional prompt influence (0-1)
+   * @param jobId - ID to cancel the generation by with elevenLabsCancelGeneration
+   * @returns Promise resolving to generated audio info
    */
-  async trackCheckpointMessage(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    message: string
-  ): Promise<void> {
+  async elevenLabsGenerateSFX(
+    text: string,
+    durationSeconds?: number,
+    promptInfluence?: number,
+    jobId?: string
+  ): Promise<GeneratedAudio> {
     try {
-      await apiCall("track_checkpoint_message", {
-        sessionId,
-        projectId,
-        projectPath,
-        message
+      return await apiCall<GeneratedAudio>("eleven_labs_generate_sfx", {
+        text,
+        durationSeconds,
+        promptInfluence,
+        jobId,
       });
     } catch (error) {
-      console.error("Failed to track checkpoint message:", error);
+      console.error("Failed to generate SFX:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if auto-checkpoint should be triggered
+   * Generates music
+   * @param prompt - Description of the music
+   * @param durationSeconds - Optional length, 10 to 300 seconds (default 30s)
+   * @param style - Optional genre or style, e.g. "synthwave"
+   * @param mood - Optional mood, e.g. "uplifting"
+   * @param projectId - Project whose after-generation hooks run on the file
+   * @param jobId - ID to cancel the generation by with elevenLabsCancelGeneration
+   * @returns Promise resolving to generated audio info
    */
-  async checkAutoCheckpoint(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    message: string
-  ): Promise<boolean> {
+  async elevenLabsGenerateMusic(
+    prompt: string,
+    durationSeconds?: number,
+    style?: string,
+    mood?: string,
+    projectId?: string,
+    jobId?: string
+  ): Promise<GeneratedAudio> {
     try {
-      return await apiCall<boolean>("check_auto_checkpoint", {
-        sessionId,
-        projectId,
-        projectPath,
-        message
+      return await apiCall<GeneratedAudio>("eleven_labs_generate_music", {
+        prompt,
+        durationSeconds,
+        style,
+        mood,
+        projectId,
+        jobId,
       });
     } catch (error) {
-      console.error("Failed to check auto checkpoint:", error);
+      console.error("Failed to generate music:", error);
       throw error;
     }
   },
 
   /**
-   * Triggers cleanup of old checkpoints
+   * Cancels a generation started with a job ID. It rejects with a `cancelled` error.
+   * @param jobId - The ID passed to the generation
+   * @returns Promise resolving to whether the generation was still running
    */
-  async cleanupOldCheckpoints(
-    sessionId: string,
-    projectId: string,
-    projectPath: string,
-    keepCount: number
-  ): Promise<number> {
+  async elevenLabsCancelGeneration(jobId: string): Promise<boolean> {
     try {
-      return await apiCall<number>("cleanup_old_checkpoints", {
-        sessionId,
-        projectId,
-        projectPath,
-        keepCount
-      });
+      return await apiCall<boolean>("eleven_labs_cancel_generation", { jobId });
     } catch (error) {
-      console.error("Failed to cleanup old checkpoints:", error);
+      console.error("Failed to cancel generation:", error);
       throw error;
     }
   },
 
   /**
-   * Gets checkpoint settings for a session
+   * Opens a Conversational AI session. Its audio and transcripts arrive as `realtime-session` events.
+   * @param agentId - Agent to talk to; the saved default when omitted
+   * @param microphone - Stream the default microphone to the agent (default true)
+   * @returns Promise resolving to the session ID
    */
-  async getCheckpointSettings(
-    sessionId: string,
-    projectId: string,
-    projectPath: string
-  ): Promise<{
-    auto_checkpoint_enabled: boolean;
-    checkpoint_strategy: CheckpointStrategy;
-    total_checkpoints: number;
-    current_checkpoint_id?: string;
-  }> {
+  async startRealtimeSession(agentId?: string, microphone?: boolean): Promise<string> {
     try {
-      return await apiCall("get_checkpoint_settings", {
-        sessionId,
-        projectId,
-        projectPath
-      });
+      return await apiCall<string>("start_realtime_session", { agentId, microphone });
     } catch (error) {
-      console.error("Failed to get checkpoint settings:", error);
+      console.error("Failed to start realtime session:", error);
       throw error;
     }
   },
 
   /**
-   * Clears checkpoint manager for a session (cleanup on session end)
+   * Closes a Conversational AI session and its microphone
+   * @param sessionId - ID returned by startRealtimeSession
+   * @returns Promise resolving to whether the session was still open
    */
-  async clearCheckpointManager(sessionId: string): Promise<void> {
+  async stopRealtimeSession(sessionId: string): Promise<boolean> {
     try {
-      await apiCall("clear_checkpoint_manager", { sessionId });
+      return await apiCall<boolean>("stop_realtime_session", { sessionId });
     } catch (error) {
-      console.error("Failed to clear checkpoint manager:", error);
+      console.error("Failed to stop realtime session:", error);
       throw error;
     }
   },
 
   /**
-   * Tracks a batch of messages for a session for checkpointing
+   * Lists the open Conversational AI sessions
    */
-  trackSessionMessages: (
-    sessionId: string, 
-    projectId: string, 
-    projectPath: string, 
-    messages: string[]
-  ): Promise<void> =>
-    apiCall("track_session_messages", { sessionId, projectId, projectPath, messages }),
+  async listRealtimeSessions(): Promise<RealtimeSessionInfo[]> {
+    try {
+      return await apiCall<RealtimeSessionInfo[]>("list_realtime_sessions");
+    } catch (error) {
+      console.error("Failed to list realtime sessions:", error);
+      throw error;
+    }
+  },
 
   /**
-   * Adds a new MCP server
+   * Gets the agent sessions talk to when none is given
    */
-  async mcpAdd(
-    name: string,
-    transport: string,
-    command?: string,
-    args: string[] = [],
-    env: Record<string, string> = {},
-    url?: string,
-    scope: string = "local"
-  ): Promise<AddServerResult> {
+  async getRealtimeAgent(): Promise<string | null> {
     try {
-      return await apiCall<AddServerResult>("mcp_add", {
-        name,
-        transport,
-        command,
-        args,
-        env,
-        url,
-        scope
-      });
+      return await apiCall<string | null>("get_realtime_agent");
     } catch (error) {
-      console.error("Failed to add MCP server:", error);
+      console.error("Failed to get realtime agent:", error);
       throw error;
     }
   },
 
   /**
-   * Lists all configured MCP servers
+   * Sets the agent sessions talk to when none is given
+   * @param agentId - Conversational AI agent ID
    */
-  async mcpList(): Promise<MCPServer[]> {
+  async setRealtimeAgent(agentId: string): Promise<void> {
     try {
-      console.log("API: Calling mcp_list...");
-      const result = await apiCall<MCPServer[]>("mcp_list");
-      console.log("API: mcp_list returned:", result);
-      return result;
+      await apiCall<void>("set_realtime_agent", { agentId });
     } catch (error) {
-      console.error("API: Failed to list MCP servers:", error);
+      console.error("Failed to set realtime agent:", error);
       throw error;
     }
   },
 
   /**
-   * Gets details for a specific MCP server
+   * Lists the job IDs of generations that can be cancelled now
    */
-  async mcpGet(name: string): Promise<MCPServer> {
+  async listGenerationJobs(): Promise<string[]> {
     try {
-      return await apiCall<MCPServer>("mcp_get", { name });
+      return await apiCall<string[]>("list_generation_jobs");
     } catch (error) {
-      console.error("Failed to get MCP server:", error);
+      console.error("Failed to list generation jobs:", error);
       throw error;
     }
   },
 
   /**
-   * Removes an MCP server
+   * Converts a recording into another voice (speech-to-speech)
+   * @param sourcePath - Path of the recording to convert
+   * @param voiceId - Target voice ID
+   * @param modelId - Optional speech-to-speech model ID
+   * @param voiceSettings - Optional voice settings
+   * @param removeBackgroundNoise - Whether to strip background noise from the source
+   * @param projectId - Optional project ID
+   * @returns Promise resolving to generated audio info
    */
-  async mcpRemove(name: string): Promise<string> {
+  async elevenLabsSpeechToSpeech(
+    sourcePath: string,
+    voiceId: string,
+    modelId?: string,
+    voiceSettings?: VoiceSettings,
+    removeBackgroundNoise?: boolean,
+    projectId?: string
+  ): Promise<GeneratedAudio> {
     try {
-      return await apiCall<string>("mcp_remove", { name });
+      return await apiCall<GeneratedAudio>("eleven_labs_speech_to_speech", {
+        sourcePath,
+        voiceId,
+        modelId,
+        voiceSettings,
+        removeBackgroundNoise,
+        projectId,
+      });
     } catch (error) {
-      console.error("Failed to remove MCP server:", error);
+      console.error("Failed to convert voice:", error);
       throw error;
     }
   },
 
   /**
-   * Adds an MCP server from JSON configuration
+   * Removes background noise from a recording, keeping the voice. Useful for
+   * cleaning up samples before cloning.
+   * @param sourcePath - Path of the recording to clean
+   * @param projectId - Optional project ID
+   * @returns Promise resolving to the cleaned audio info
    */
-  async mcpAddJson(name: string, jsonConfig: string, scope: string = "local"): Promise<AddServerResult> {
+  async elevenLabsIsolateAudio(sourcePath: string, projectId?: string): Promise<GeneratedAudio> {
     try {
-      return await apiCall<AddServerResult>("mcp_add_json", { name, jsonConfig, scope });
+      return await apiCall<GeneratedAudio>("eleven_labs_isolate_audio", { sourcePath, projectId });
     } catch (error) {
-      console.error("Failed to add MCP server from JSON:", error);
+      console.error("Failed to isolate audio:", error);
       throw error;
     }
   },
 
   /**
-   * Imports MCP servers from Claude Desktop
+   * Submits a video or audio file for dubbing. Progress is reported with
+   * `audio-dubbing-progress` events carrying the updated job.
+   * @param sourcePath - File to dub
+   * @param targetLang - ISO 639-1 code of the language to dub into
+   * @param sourceLang - Optional language of the source; detected when omitted
+   * @param numSpeakers - Optional number of speakers; detected when omitted
+   * @param projectId - Optional project ID
+   * @returns Promise resolving to the submitted job
    */
-  async mcpAddFromClaudeDesktop(scope: string = "local"): Promise<ImportResult> {
+  async elevenLabsDubFile(
+    sourcePath: string,
+    targetLang: string,
+    sourceLang?: string,
+    numSpeakers?: number,
+    projectId?: string
+  ): Promise<DubbingJob> {
     try {
-      return await apiCall<ImportResult>("mcp_add_from_claude_desktop", { scope });
+      return await apiCall<DubbingJob>("eleven_labs_dub_file", {
+        sourcePath,
+        targetLang,
+        sourceLang,
+        numSpeakers,
+        projectId,
+      });
     } catch (error) {
-      console.error("Failed to import from Claude Desktop:", error);
+      console.error("Failed to submit dubbing:", error);
       throw error;
     }
   },
 
   /**
-   * Starts Claude Code as an MCP server
+   * Checks a dubbing job now, resuming polling if it is still running
+   * @param jobId - The job to check
    */
-  async mcpServe(): Promise<string> {
+  async refreshDubbingJob(jobId: string): Promise<DubbingJob> {
     try {
-      return await apiCall<string>("mcp_serve");
+      return await apiCall<DubbingJob>("refresh_dubbing_job", { jobId });
     } catch (error) {
-      console.error("Failed to start MCP server:", error);
+      console.error("Failed to refresh dubbing job:", error);
       throw error;
     }
   },
 
   /**
-   * Tests connection to an MCP server
+   * Gets a dubbing job
+   * @param jobId - The job to get
    */
-  async mcpTestConnection(name: string): Promise<string> {
+  async getDubbingJob(jobId: string): Promise<DubbingJob | null> {
     try {
-      return await apiCall<string>("mcp_test_connection", { name });
+      return await apiCall<DubbingJob | null>("get_dubbing_job", { jobId });
     } catch (error) {
-      console.error("Failed to test MCP connection:", error);
+      console.error("Failed to get dubbing job:", error);
       throw error;
     }
   },
 
   /**
-   * Resets project-scoped server approval choices
+   * Lists dubbing jobs, newest first
+   * @param projectId - Optional project ID to filter by
    */
-  async mcpResetProjectChoices(): Promise<string> {
+  async listDubbingJobs(projectId?: string): Promise<DubbingJob[]> {
     try {
-      return await apiCall<string>("mcp_reset_project_choices");
+      return await apiCall<DubbingJob[]>("list_dubbing_jobs", { projectId });
     } catch (error) {
-      console.error("Failed to reset project choices:", error);
+      console.error("Failed to list dubbing jobs:", error);
       throw error;
     }
   },
 
   /**
-   * Gets the status of MCP servers
+   * Lists the available models with their languages and capabilities
+   * @returns Promise resolving to the models
    */
-  async mcpGetServerStatus(): Promise<Record<string, ServerStatus>> {
+  async elevenLabsListModels(): Promise<ModelInfo[]> {
     try {
-      return await apiCall<Record<string, ServerStatus>>("mcp_get_server_status");
+      return await apiCall<ModelInfo[]>("eleven_labs_list_models");
     } catch (error) {
-      console.error("Failed to get server status:", error);
+      console.error("Failed to list models:", error);
       throw error;
     }
   },
 
   /**
-   * Reads .mcp.json from the current project
+   * Lists the provider's generation history, including renders made on other machines
+   * @param pageSize - Items per page (default 100)
+   * @param startAfter - `last_history_item_id` of the previous page
+   * @returns Promise resolving to a page of history
    */
-  async mcpReadProjectConfig(projectPath: string): Promise<MCPProjectConfig> {
+  async elevenLabsListHistory(pageSize?: number, startAfter?: string): Promise<HistoryPage> {
     try {
-      return await apiCall<MCPProjectConfig>("mcp_read_project_config", { projectPath });
+      return await apiCall<HistoryPage>("eleven_labs_list_history", { pageSize, startAfter });
     } catch (error) {
-      console.error("Failed to read project MCP config:", error);
+      console.error("Failed to list history:", error);
       throw error;
     }
   },
 
   /**
-   * Saves .mcp.json to the current project
+   * Downloads a history item into the local audio cache
+   * @param historyItemId - The history item to download
+   * @param projectId - Optional project ID
+   * @returns Promise resolving to the cached audio info
    */
-  async mcpSaveProjectConfig(projectPath: string, config: MCPProjectConfig): Promise<string> {
+  async elevenLabsImportHistoryItem(historyItemId: string, projectId?: string): Promise<GeneratedAudio> {
     try {
-      return await apiCall<string>("mcp_save_project_config", { projectPath, config });
+      return await apiCall<GeneratedAudio>("eleven_labs_import_history_item", { historyItemId, projectId });
     } catch (error) {
-      console.error("Failed to save project MCP config:", error);
+      console.error("Failed to import history item:", error);
       throw error;
     }
   },
 
   /**
-   * Get the stored Claude binary path from settings
-   * @returns Promise resolving to the path if set, null otherwise
+   * Deletes a history item on the provider; downloaded copies are kept
+   * @param historyItemId - The history item to delete
    */
-  async getClaudeBinaryPath(): Promise<string | null> {
+  async elevenLabsDeleteHistoryItem(historyItemId: string): Promise<void> {
     try {
-      return await apiCall<string | null>("get_claude_binary_path");
+      return await apiCall<void>("eleven_labs_delete_history_item", { historyItemId });
     } catch (error) {
-      console.error("Failed to get Claude binary path:", error);
+      console.error("Failed to delete history item:", error);
       throw error;
     }
   },
 
   /**
-   * Set the Claude binary path in settings
-   * @param path - The absolute path to the Claude binary
-   * @returns Promise resolving when the path is saved
+   * Gets Eleven Labs usage information
+   * @returns Promise resolving to usage info
    */
-  async setClaudeBinaryPath(path: string): Promise<void> {
+  async elevenLabsGetUsage(): Promise<ElevenLabsUsageInfo> {
     try {
-      return await apiCall<void>("set_claude_binary_path", { path });
+      return await apiCall<ElevenLabsUsageInfo>("eleven_labs_get_usage");
     } catch (error) {
-      console.error("Failed to set Claude binary path:", error);
+      console.error("Failed to get Eleven Labs usage:", error);
       throw error;
     }
   },
 
   /**
-   * List all available Claude installations on the system
-   * @returns Promise resolving to an array of Claude installations
+   * Assigns a voice to a character
+   * @param characterName - Name of the character
+   * @param voiceId - Voice ID to assign
+   * @param voiceName - Name of the voice
+   * @param projectId - Optional project ID
+   * @param preset - Optional voice settings preset to speak the character with
+   * @returns Promise resolving to the character voice mapping
    */
-  async listClaudeInstallations(): Promise<ClaudeInstallation[]> {
+  async assignVoiceToCharacter(
+    characterName: string,
+    voiceId: string,
+    voiceName: string,
+    projectId?: string,
+    preset?: string
+  ): Promise<CharacterVoice> {
     try {
-      return await apiCall<ClaudeInstallation[]>("list_claude_installations");
+      return await apiCall<CharacterVoice>("assign_voice_to_character", {
+        characterName,
+        voiceId,
+        voiceName,
+        projectId,
+        preset,
+      });
     } catch (error) {
-      console.error("Failed to list Claude installations:", error);
+      console.error("Failed to assign voice to character:", error);
       throw error;
     }
   },
 
-  // Storage API methods
-
   /**
-   * Lists all tables in the SQLite database
-   * @returns Promise resolving to an array of table information
+   * Lists all character voice mappings
+   * @param projectId - Optional project ID to filter by
+   * @returns Promise resolving to array of character voice mappings
    */
-  async storageListTables(): Promise<any[]> {
+  async listCharacterVoices(projectId?: string): Promise<CharacterVoice[]> {
     try {
-      return await apiCall<any[]>("storage_list_tables");
+      return await apiCall<CharacterVoice[]>("list_character_voices", { projectId });
     } catch (error) {
-      console.error("Failed to list tables:", error);
+      console.error("Failed to list character voices:", error);
       throw error;
     }
   },
 
   /**
-   * Reads table data with pagination
-   * @param tableName - Name of the table to read
-   * @param page - Page number (1-indexed)
-   * @param pageSize - Number of rows per page
-   * @param searchQuery - Optional search query
-   * @returns Promise resolving to table data with pagination info
+   * Gets cached audio records, newest first unless the query says otherwise
+   * @param audioType - Type of audio: 'tts', 'sfx', or 'music'
+   * @param query - Voice and date filters, sort order, and limit/offset paging
+   * @returns Promise resolving to array of generated audio records
    */
-  async storageReadTable(
-    tableName: string,
-    page: number,
-    pageSize: number,
-    searchQuery?: string
-  ): Promise<any> {
+  async getCachedAudio(audioType: 'tts' | 'sfx' | 'music', query?: AudioQuery): Promise<GeneratedAudio[]> {
     try {
-      return await apiCall<any>("storage_read_table", {
-        tableName,
-        page,
-        pageSize,
-        searchQuery,
-      });
+      return await apiCall<GeneratedAudio[]>("get_cached_audio", { audioType, query });
     } catch (error) {
-      console.error("Failed to read table:", error);
+      console.error("Failed to get cached audio:", error);
       throw error;
     }
   },
 
   /**
-   * Updates a row in a table
-   * @param tableName - Name of the table
-   * @param primaryKeyValues - Map of primary key column names to values
-   * @param updates - Map of column names to new values
-   * @returns Promise resolving when the row is updated
+   * Searches cached audio prompts, best matches first
+   * @param query - Words the prompt must contain; each also matches as a prefix
+   * @param audioType - Only audio of this type, when given
+   * @param limit - Maximum results (default 50)
    */
-  async storageUpdateRow(
-    tableName: string,
-    primaryKeyValues: Record<string, any>,
-    updates: Record<string, any>
-  ): Promise<void> {
+  async searchCachedAudio(
+    query: string,
+    audioType?: 'tts' | 'sfx' | 'music',
+    limit?: number
+  ): Promise<GeneratedAudio[]> {
     try {
-      return await apiCall<void>("storage_update_row", {
-        tableName,
-        primaryKeyValues,
-        updates,
-      });
+      return await apiCall<GeneratedAudio[]>("search_cached_audio", { query, audioType, limit });
     } catch (error) {
-      console.error("Failed to update row:", error);
+      console.error("Failed to search cached audio:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a row from a table
-   * @param tableName - Name of the table
-   * @param primaryKeyValues - Map of primary key column names to values
-   * @returns Promise resolving when the row is deleted
+   * Adds tags to a cached audio record; tags it already has are skipped
+   * @param audioId - The audio ID
+   * @param tags - Tags to add, matched regardless of case
    */
-  async storageDeleteRow(
-    tableName: string,
-    primaryKeyValues: Record<string, any>
-  ): Promise<void> {
+  async tagAudio(audioId: string, tags: string[]): Promise<AudioTags> {
     try {
-      return await apiCall<void>("storage_delete_row", {
-        tableName,
-        primaryKeyValues,
-      });
+      return await apiCall<AudioTags>("tag_audio", { audioId, tags });
     } catch (error) {
-      console.error("Failed to delete row:", error);
+      console.error("Failed to tag audio:", error);
       throw error;
     }
   },
 
   /**
-   * Inserts a new row into a table
-   * @param tableName - Name of the table
-   * @param values - Map of column names to values
-   * @returns Promise resolving to the last insert row ID
+   * Removes a tag from a cached audio record
+   * @param audioId - The audio ID
+   * @param tag - The tag to remove, in any case
    */
-  async storageInsertRow(
-    tableName: string,
-    values: Record<string, any>
-  ): Promise<number> {
+  async untagAudio(audioId: string, tag: string): Promise<AudioTags> {
     try {
-      return await apiCall<number>("storage_insert_row", {
-        tableName,
-        values,
-      });
+      return await apiCall<AudioTags>("untag_audio", { audioId, tag });
     } catch (error) {
-      console.error("Failed to insert row:", error);
+      console.error("Failed to untag audio:", error);
       throw error;
     }
   },
 
   /**
-   * Executes a raw SQL query
-   * @param query - SQL query string
-   * @returns Promise resolving to query result
+   * Marks a cached audio record as a favorite, or clears the mark if it has one
+   * @param audioId - The audio ID
    */
-  async storageExecuteSql(query: string): Promise<any> {
+  async toggleAudioFavorite(audioId: string): Promise<AudioTags> {
     try {
-      return await apiCall<any>("storage_execute_sql", { query });
+      return await apiCall<AudioTags>("toggle_audio_favorite", { audioId });
     } catch (error) {
-      console.error("Failed to execute SQL:", error);
+      console.error("Failed to toggle audio favorite:", error);
       throw error;
     }
   },
 
   /**
-   * Resets the entire database
-   * @returns Promise resolving when the database is reset
+   * Gets the favorite marks and tags of cached audio records; unknown IDs are left out
+   * @param audioIds - The audio IDs
    */
-  async storageResetDatabase(): Promise<void> {
+  async getAudioTags(audioIds: string[]): Promise<AudioTags[]> {
     try {
-      return await apiCall<void>("storage_reset_database");
+      return await apiCall<AudioTags[]>("get_audio_tags", { audioIds });
     } catch (error) {
-      console.error("Failed to reset database:", error);
+      console.error("Failed to get audio tags:", error);
       throw error;
     }
   },
 
-  // Theme settings helpers
-
   /**
-   * Gets a setting from the app_settings table
-   * @param key - The setting key to retrieve
-   * @returns Promise resolving to the setting value or null if not found
+   * Lists every tag in use on cached audio, most used first
    */
-  async getSetting(key: string): Promise<string | null> {
+  async listAudioTags(): Promise<TagCount[]> {
     try {
-      // Fast path: check localStorage mirror to avoid startup flicker
-      if (typeof window !== 'undefined' && 'localStorage' in window) {
-        const cached = window.localStorage.getItem(`app_setting:${key}`);
-        if (cached !== null) {
-          return cached;
-        }
-      }
-      // Use storageReadTable to safely query the app_settings table
-      const result = await this.storageReadTable('app_settings', 1, 1000);
-      const setting = result?.data?.find((row: any) => row.key === key);
-      return setting?.value || null;
+      return await apiCall<TagCount[]>("list_audio_tags");
     } catch (error) {
-      console.error(`Failed to get setting ${key}:`, error);
-      return null;
+      console.error("Failed to list audio tags:", error);
+      throw error;
     }
   },
 
   /**
-   * Saves a setting to the app_settings table (insert or update)
-   * @param key - The setting key
-   * @param value - The setting value
-   * @returns Promise resolving when the setting is saved
+   * Lists cached audio carrying a tag, newest first
+   * @param tag - The tag, in any case
+   * @param audioType - Only audio of this type, when given
    */
-  async saveSetting(key: string, value: string): Promise<void> {
+  async listAudioByTag(tag: string, audioType?: 'tts' | 'sfx' | 'music'): Promise<GeneratedAudio[]> {
     try {
-      // Mirror to localStorage for instant availability on next startup
-      if (typeof window !== 'undefined' && 'localStorage' in window) {
-        try {
-          window.localStorage.setItem(`app_setting:${key}`, value);
-        } catch (_ignore) {
-          // best-effort; continue to persist in DB
-        }
-      }
-      // Try to update first
-      try {
-        await this.storageUpdateRow(
-          'app_settings',
-          { key },
-          { value }
-        );
-      } catch (updateError) {
-        // If update fails (row doesn't exist), insert new row
-        await this.storageInsertRow('app_settings', { key, value });
-      }
+      return await apiCall<GeneratedAudio[]>("list_audio_by_tag", { tag, audioType });
     } catch (error) {
-      console.error(`Failed to save setting ${key}:`, error);
+      console.error("Failed to list audio by tag:", error);
       throw error;
     }
   },
 
   /**
-   * Get hooks configuration for a specific scope
-   * @param scope - The configuration scope: 'user', 'project', or 'local'
-   * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to the hooks configuration
+   * Moves a cached audio record to the trash, or deletes it and its file right away
+   * @param audioId - The audio ID to delete
+   * @param permanent - Skip the trash
    */
-  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfiguration> {
+  async deleteCachedAudio(audioId: string, permanent?: boolean): Promise<void> {
     try {
-      return await apiCall<HooksConfiguration>("get_hooks_config", { scope, projectPath });
+      await apiCall<void>("delete_cached_audio", { audioId, permanent });
     } catch (error) {
-      console.error("Failed to get hooks config:", error);
+      console.error("Failed to delete cached audio:", error);
       throw error;
     }
   },
 
   /**
-   * Update hooks configuration for a specific scope
-   * @param scope - The configuration scope: 'user', 'project', or 'local'
-   * @param hooks - The hooks configuration to save
-   * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to success message
+   * Moves every cached record matching a filter to the trash, or deletes it and its file
+   * @param audioType - Only audio of this type; every type when omitted
+   * @param query - Voice, date, tag and favorite filters; at least one is required
+   * @param permanent - Delete right away instead of moving to the trash
    */
-  async updateHooksConfig(
-    scope: 'user' | 'project' | 'local',
-    hooks: HooksConfiguration,
-    projectPath?: string
-  ): Promise<string> {
+  async deleteCachedAudioBatch(
+    audioType?: 'tts' | 'sfx' | 'music',
+    query?: AudioQuery,
+    permanent?: boolean
+  ): Promise<BatchDeleteResult> {
     try {
-      return await apiCall<string>("update_hooks_config", { scope, projectPath, hooks });
+      return await apiCall<BatchDeleteResult>("delete_cached_audio_batch", { audioType, query, permanent });
     } catch (error) {
-      console.error("Failed to update hooks config:", error);
+      console.error("Failed to batch delete cached audio:", error);
       throw error;
     }
   },
 
   /**
-   * Validate a hook command syntax
-   * @param command - The shell command to validate
-   * @returns Promise resolving to validation result
+   * Lists cached audio in the trash, most recently deleted first
+   * @param audioType - Only audio of this type, when given
    */
-  async validateHookCommand(command: string): Promise<{ valid: boolean; message: string }> {
+  async listTrashedAudio(audioType?: 'tts' | 'sfx' | 'music'): Promise<TrashedAudio[]> {
     try {
-      return await apiCall<{ valid: boolean; message: string }>("validate_hook_command", { command });
+      return await apiCall<TrashedAudio[]>("list_trashed_audio", { audioType });
     } catch (error) {
-      console.error("Failed to validate hook command:", error);
+      console.error("Failed to list trashed audio:", error);
       throw error;
     }
   },
 
   /**
-   * Get merged hooks configuration (respecting priority)
-   * @param projectPath - The project path
-   * @returns Promise resolving to merged hooks configuration
+   * Restores a cached audio record from the trash
+   * @param audioId - The audio ID to restore
    */
-  async getMergedHooksConfig(projectPath: string): Promise<HooksConfiguration> {
+  async restoreCachedAudio(audioId: string): Promise<GeneratedAudio> {
     try {
-      const [userHooks, projectHooks, localHooks] = await Promise.all([
-        this.getHooksConfig('user'),
-        this.getHooksConfig('project', projectPath),
-        this.getHooksConfig('local', projectPath)
-      ]);
-
-      // Import HooksManager for merging
-      const { HooksManager } = await import('@/lib/hooksManager');
-      return HooksManager.mergeConfigs(userHooks, projectHooks, localHooks);
+      return await apiCall<GeneratedAudio>("restore_cached_audio", { audioId });
     } catch (error) {
-      console.error("Failed to get merged hooks config:", error);
+      console.error("Failed to restore cached audio:", error);
       throw error;
     }
   },
 
-  // Slash Commands API methods
-
   /**
-   * Lists all available slash commands
-   * @param projectPath - Optional project path to include project-specific commands
-   * @returns Promise resolving to array of slash commands
+   * Permanently deletes audio that has been in the trash long enough
+   * @param olderThanDays - Minimum days in the trash (default 30, 0 for all of it)
+   * @returns The number of records removed
    */
-  async slashCommandsList(projectPath?: string): Promise<SlashCommand[]> {
+  async purgeTrashedAudio(olderThanDays?: number): Promise<number> {
     try {
-      return await apiCall<SlashCommand[]>("slash_commands_list", { projectPath });
+      return await apiCall<number>("purge_trashed_audio", { olderThanDays });
     } catch (error) {
-      console.error("Failed to list slash commands:", error);
+      console.error("Failed to purge trashed audio:", error);
       throw error;
     }
   },
 
   /**
-   * Gets a single slash command by ID
-   * @param commandId - Unique identifier of the command
-   * @returns Promise resolving to the slash command
+   * Renders a cached record again from its stored parameters, saved as another take
+   * @param audioId - The audio ID to regenerate
+   * @param overrides - Parameters to change for the new take
+   * @param projectId - Project whose after-generation hooks run on the file
    */
-  async slashCommandGet(commandId: string): Promise<SlashCommand> {
+  async regenerateAudio(audioId: string, overrides?: RegenerateOverrides, projectId?: string): Promise<GeneratedAudio> {
     try {
-      return await apiCall<SlashCommand>("slash_command_get", { commandId });
+      return await apiCall<GeneratedAudio>("regenerate_audio", { audioId, overrides, projectId });
     } catch (error) {
-      console.error("Failed to get slash command:", error);
+      console.error("Failed to regenerate audio:", error);
       throw error;
     }
   },
 
   /**
-   * Creates or updates a slash command
-   * @param scope - Command scope: "project" or "user"
-   * @param name - Command name (without prefix)
-   * @param namespace - Optional namespace for organization
-   * @param content - Markdown content of the command
-   * @param description - Optional description
-   * @param allowedTools - List of allowed tools for this command
-   * @param projectPath - Required for project scope commands
-   * @returns Promise resolving to the saved command
+   * Renders a record generated with a seed again with the same seed, reproducing it as another take
+   * @param audioId - The audio ID to reproduce
+   * @param projectId - Project whose after-generation hooks run on the file
    */
-  async slashCommandSave(
-    scope: string,
-    name: string,
-    namespace: string | undefined,
-    content: string,
-    description: string | undefined,
-    allowedTools: string[],
-    projectPath?: string
-  ): Promise<SlashCommand> {
+  async regenerateWithSameSeed(audioId: string, projectId?: string): Promise<GeneratedAudio> {
     try {
-      return await apiCall<SlashCommand>("slash_command_save", {
-        scope,
-        name,
-        namespace,
-        content,
-        description,
-        allowedTools,
-        projectPath
-      });
+      return await apiCall<GeneratedAudio>("regenerate_with_same_seed", { audioId, projectId });
     } catch (error) {
-      console.error("Failed to save slash command:", error);
+      console.error("Failed to regenerate with the same seed:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a slash command
-   * @param commandId - Unique identifier of the command to delete
-   * @param projectPath - Optional project path for deleting project commands
-   * @returns Promise resolving to deletion message
+   * Scans the audio cache against its records; nothing changes unless a repair is requested
+   * @param orphans - Delete files without a record, or register a record for each
+   * @param removeDangling - Remove records whose file is gone
    */
-  async slashCommandDelete(commandId: string, projectPath?: string): Promise<string> {
+  async reconcileAudioCache(orphans?: "delete" | "register", removeDangling?: boolean): Promise<ReconcileReport> {
     try {
-      return await apiCall<string>("slash_command_delete", { commandId, projectPath });
+      return await apiCall<ReconcileReport>("reconcile_audio_cache", { orphans, removeDangling });
     } catch (error) {
-      console.error("Failed to delete slash command:", error);
+      console.error("Failed to reconcile audio cache:", error);
       throw error;
     }
   },
 
-  // ========== Eleven Labs Audio API ==========
-
   /**
-   * Sets the Eleven Labs API key
-   * @param apiKey - The API key to set
-   * @returns Promise resolving to true if valid
+   * Lists every take in the group of a cached record, oldest first
+   * @param audioId - Any take in the group
    */
-  async elevenLabsSetApiKey(apiKey: string): Promise<boolean> {
+  async listAudioTakes(audioId: string): Promise<GeneratedAudio[]> {
     try {
-      return await apiCall<boolean>("eleven_labs_set_api_key", { apiKey });
+      return await apiCall<GeneratedAudio[]>("list_audio_takes", { audioId });
     } catch (error) {
-      console.error("Failed to set Eleven Labs API key:", error);
+      console.error("Failed to list audio takes:", error);
       throw error;
     }
   },
 
   /**
-   * Checks if Eleven Labs API key is configured
-   * @returns Promise resolving to true if configured
+   * Renders the same text with two voice settings variants for A/B listening
+   * @param text - Text to speak
+   * @param voiceId - Voice for both renders
+   * @param settingsA - First variant
+   * @param settingsB - Second variant; must differ from the first
+   * @param modelId - Optional model ID
+   * @param projectId - Optional project whose hooks run on both files
    */
-  async elevenLabsHasApiKey(): Promise<boolean> {
+  async compareGenerations(
+    text: string,
+    voiceId: string,
+    settingsA: VoiceSettings,
+    settingsB: VoiceSettings,
+    modelId?: string,
+    projectId?: string
+  ): Promise<GenerationComparison> {
     try {
-      return await apiCall<boolean>("eleven_labs_has_api_key");
+      return await apiCall<GenerationComparison>("compare_generations", {
+        text,
+        voiceId,
+        settingsA,
+        settingsB,
+        modelId,
+        projectId,
+      });
     } catch (error) {
-      console.error("Failed to check Eleven Labs API key:", error);
-      return false;
+      console.error("Failed to compare generations:", error);
+      throw error;
     }
   },
 
   /**
-   * Lists all available Eleven Labs voices
-   * @returns Promise resolving to array of voice profiles
+   * Gets a comparison made earlier, or null once either render is deleted
    */
-  async elevenLabsListVoices(): Promise<VoiceProfile[]> {
+  async getGenerationComparison(comparisonId: string): Promise<GenerationComparison | null> {
     try {
-      return await apiCall<VoiceProfile[]>("eleven_labs_list_voices");
+      return await apiCall<GenerationComparison | null>("get_generation_comparison", { comparisonId });
     } catch (error) {
-      console.error("Failed to list Eleven Labs voices:", error);
+      console.error("Failed to get generation comparison:", error);
       throw error;
     }
   },
 
   /**
-   * Clones a voice from audio files
-   * @param name - Name for the cloned voice
-   * @param files - Array of file paths to audio samples
-   * @param description - Optional description
-   * @param labels - Optional labels object
-   * @returns Promise resolving to the cloned voice profile
+   * Gets whether the API key needs to be re-entered
    */
-  async elevenLabsCloneVoice(
-    name: string,
-    files: string[],
-    description?: string,
-    labels?: Record<string, string>
-  ): Promise<VoiceProfile> {
+  async getAuthStatus(): Promise<AuthStatus> {
     try {
-      return await apiCall<VoiceProfile>("eleven_labs_clone_voice", {
-        name,
-        files,
-        description,
-        labels,
-      });
+      return await apiCall<AuthStatus>("get_auth_status");
     } catch (error) {
-      console.error("Failed to clone voice:", error);
+      console.error("Failed to get auth status:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes an Eleven Labs voice
-   * @param voiceId - The voice ID to delete
+   * Gets the directory the audio cache is using
    */
-  async elevenLabsDeleteVoice(voiceId: string): Promise<void> {
+  async getAudioCacheLocation(): Promise<AudioCacheLocation> {
     try {
-      await apiCall<void>("eleven_labs_delete_voice", { voiceId });
+      return await apiCall<AudioCacheLocation>("get_audio_cache_location");
     } catch (error) {
-      console.error("Failed to delete voice:", error);
+      console.error("Failed to get audio cache location:", error);
       throw error;
     }
   },
 
   /**
-   * Generates text-to-speech audio
-   * @param text - The text to convert to speech
-   * @param voiceId - The voice ID to use
-   * @param modelId - Optional model ID
-   * @param voiceSettings - Optional voice settings
-   * @returns Promise resolving to generated audio info
+   * Gets the report of the last audio database recovery, if there was one
    */
-  async elevenLabsTTS(
-    text: string,
-    voiceId: string,
-    modelId?: string,
-    voiceSettings?: VoiceSettings
-  ): Promise<GeneratedAudio> {
+  async getAudioDbRecovery(): Promise<AudioDbRecoveryReport | null> {
     try {
-      return await apiCall<GeneratedAudio>("eleven_labs_tts", {
-        text,
-        voiceId,
-        modelId,
-        voiceSettings,
-      });
+      return await apiCall<AudioDbRecoveryReport | null>("get_audio_db_recovery");
     } catch (error) {
-      console.error("Failed to generate TTS:", error);
+      console.error("Failed to get audio database recovery report:", error);
       throw error;
     }
   },
 
   /**
-   * Generates sound effects
-   * @param text - Description of the sound effect
-   * @param durationSeconds - Optional duration (default 3s)
-   * @param promptInfluence - Optional prompt influence (0-1)
-   * @returns Promise resolving to generated audio info
+   * Gets whether the app database is encrypted
    */
-  async elevenLabsGenerateSFX(
-    text: string,
-    durationSeconds?: number,
-    promptInfluence?: number
-  ): Promise<GeneratedAudio> {
+  async getAppDbEncryption(): Promise<DbEncryptionStatus> {
     try {
-      return await apiCall<GeneratedAudio>("eleven_labs_generate_sfx", {
-        text,
-        durationSeconds,
-        promptInfluence,
-      });
+      return await apiCall<DbEncryptionStatus>("get_app_db_encryption");
     } catch (error) {
-      console.error("Failed to generate SFX:", error);
+      console.error("Failed to get database encryption status:", error);
       throw error;
     }
   },
 
   /**
-   * Gets Eleven Labs usage information
-   * @returns Promise resolving to usage info
+   * Encrypts the app database, changes its passphrase, or decrypts it. Takes
+   * effect when the app next starts.
+   * @param passphrase - The new passphrase (at least 8 characters), or null to decrypt
    */
-  async elevenLabsGetUsage(): Promise<ElevenLabsUsageInfo> {
+  async setAppDbPassphrase(passphrase: string | null): Promise<DbEncryptionStatus> {
     try {
-      return await apiCall<ElevenLabsUsageInfo>("eleven_labs_get_usage");
+      return await apiCall<DbEncryptionStatus>("set_app_db_passphrase", { passphrase });
     } catch (error) {
-      console.error("Failed to get Eleven Labs usage:", error);
+      console.error("Failed to set database passphrase:", error);
       throw error;
     }
   },
 
   /**
-   * Assigns a voice to a character
-   * @param characterName - Name of the character
-   * @param voiceId - Voice ID to assign
-   * @param voiceName - Name of the voice
-   * @param projectId - Optional project ID
-   * @returns Promise resolving to the character voice mapping
+   * Gets the audio schema version and the migrations applied to the database
    */
-  async assignVoiceToCharacter(
-    characterName: string,
-    voiceId: string,
-    voiceName: string,
-    projectId?: string
-  ): Promise<CharacterVoice> {
+  async getAudioSchemaStatus(): Promise<AudioSchemaStatus> {
     try {
-      return await apiCall<CharacterVoice>("assign_voice_to_character", {
-        characterName,
-        voiceId,
-        voiceName,
-        projectId,
-      });
+      return await apiCall<AudioSchemaStatus>("get_audio_schema_status");
     } catch (error) {
-      console.error("Failed to assign voice to character:", error);
+      console.error("Failed to get audio schema status:", error);
       throw error;
     }
   },
 
   /**
-   * Lists all character voice mappings
-   * @param projectId - Optional project ID to filter by
-   * @returns Promise resolving to array of character voice mappings
+   * Backs up the database and rebuilds the audio tables, keeping every row
+   * that can still be read
    */
-  async listCharacterVoices(projectId?: string): Promise<CharacterVoice[]> {
+  async repairAudioDb(): Promise<AudioDbRecoveryReport> {
     try {
-      return await apiCall<CharacterVoice[]>("list_character_voices", { projectId });
+      return await apiCall<AudioDbRecoveryReport>("repair_audio_db");
     } catch (error) {
-      console.error("Failed to list character voices:", error);
+      console.error("Failed to repair audio database:", error);
       throw error;
     }
   },
 
   /**
-   * Gets cached audio records
-   * @param audioType - Type of audio: 'tts', 'sfx', or 'music'
-   * @returns Promise resolving to array of generated audio records
+   * Gets the logging configuration
    */
-  async getCachedAudio(audioType: 'tts' | 'sfx' | 'music'): Promise<GeneratedAudio[]> {
+  async getLoggingConfig(): Promise<LoggingConfig> {
     try {
-      return await apiCall<GeneratedAudio[]>("get_cached_audio", { audioType });
+      return await apiCall<LoggingConfig>("get_logging_config");
     } catch (error) {
-      console.error("Failed to get cached audio:", error);
+      console.error("Failed to get logging config:", error);
       throw error;
     }
   },
 
   /**
-   * Deletes a cached audio record
-   * @param audioId - The audio ID to delete
+   * Updates the logging configuration; it takes effect immediately
+   * @param config - Levels and file output to apply
    */
-  async deleteCachedAudio(audioId: string): Promise<void> {
+  async setLoggingConfig(config: LoggingConfig): Promise<LoggingConfig> {
     try {
-      await apiCall<void>("delete_cached_audio", { audioId });
+      return await apiCall<LoggingConfig>("set_logging_config", { config });
     } catch (error) {
-      console.error("Failed to delete cached audio:", error);
+      console.error("Failed to set logging config:", error);
       throw error;
     }
   },
@@ -0,0 +1,1373 @@
+// Generated by src-tauri/build/audio_commands.rs from the #[tauri::command]
+// functions in src-tauri/src/commands/eleven_labs. Do not edit; run
+// `bun run bindings:audio` after changing a command signature.
+
+import type { Channel } from '@tauri-apps/api/core';
+import { apiCall } from './apiAdapter';
+import type {
+  AgentVoice,
+  AudioQuery,
+  AudioStreamEvent,
+  AudioTags,
+  AuthStatus,
+  BatchDeleteResult,
+  CharacterVoice,
+  CueOptions,
+  DbEncryptionStatus,
+  DubbingJob,
+  GeneratedAudio,
+  GenerationComparison,
+  HistoryPage,
+  LoggingConfig,
+  ModelInfo,
+  PresetImportSummary,
+  RealtimeSessionInfo,
+  RecentVoice,
+  ReconcileReport,
+  RegenerateOverrides,
+  SharedVoiceFilter,
+  SharedVoicePage,
+  TagCount,
+  TextNormalizationConfig,
+  Transcription,
+  TrashedAudio,
+  UsageHistoryEntry,
+  UsageRange,
+  VoiceCollection,
+  VoiceDesignPreviews,
+  VoiceFilter,
+  VoiceList,
+  VoicePreset,
+  VoiceProfile,
+  VoiceSettings,
+} from './api';
+
+/** Arguments and result of each audio command */
+export interface AudioCommands {
+  /** Package the given cached audio records into a zip archive at `dest`, with a `manifest.json` listing each file's prompt, voice, generation settings and duration, for handing assets to someone outside the app */
+  export_audio_bundle: {
+    args: {
+      audioIds: string[];
+      dest: string;
+    };
+    result: unknown;
+  };
+  /** Export the full audio library to a single archive for machine migration */
+  export_full_library: {
+    args: {
+      dest: string;
+    };
+    result: unknown;
+  };
+  /** Import a full library archive produced by `export_full_library` */
+  import_full_library: {
+    args: {
+      src: string;
+    };
+    result: unknown;
+  };
+  /** Assign a voice to an agent, looking up the voice name from the cache if not given */
+  assign_voice_to_agent: {
+    args: {
+      agentId: number;
+      voiceId: string;
+      voiceName?: string | null;
+    };
+    result: AgentVoice;
+  };
+  /** Assign a voice to a character, optionally with a voice settings preset */
+  assign_voice_to_character: {
+    args: {
+      characterName: string;
+      voiceId: string;
+      voiceName: string;
+      projectId?: string | null;
+      preset?: string | null;
+    };
+    result: CharacterVoice;
+  };
+  /** Generate text-to-speech like `eleven_labs_tts`, also sending the audio on `on_audio` while it is written to the cache */
+  eleven_labs_tts_stream: {
+    args: {
+      text: string;
+      voiceId: string;
+      modelId?: string | null;
+      voiceSettings?: VoiceSettings | null;
+      projectId?: string | null;
+      normalizeText?: boolean | null;
+      preset?: string | null;
+      onAudio: Channel<AudioStreamEvent>;
+    };
+    result: GeneratedAudio;
+  };
+  /** Favorite marks and tags of several records; unknown ids are left out */
+  get_audio_tags: {
+    args: {
+      audioIds: string[];
+    };
+    result: AudioTags[];
+  };
+  /** Cached audio carrying `tag`, newest first, optionally of one type */
+  list_audio_by_tag: {
+    args: {
+      tag: string;
+      audioType?: string | null;
+    };
+    result: GeneratedAudio[];
+  };
+  /** Every tag in use, with how many records carry it */
+  list_audio_tags: {
+    args: Record<string, never>;
+    result: TagCount[];
+  };
+  /** Add tags to a cached audio record, returning its tags afterwards */
+  tag_audio: {
+    args: {
+      audioId: string;
+      tags: string[];
+    };
+    result: AudioTags;
+  };
+  /** Mark a cached audio record as a favorite, or clear the mark if it has one */
+  toggle_audio_favorite: {
+    args: {
+      audioId: string;
+    };
+    result: AudioTags;
+  };
+  untag_audio: {
+    args: {
+      audioId: string;
+      tag: string;
+    };
+    result: AudioTags;
+  };
+  /** Whether the API key needs to be re-entered */
+  get_auth_status: {
+    args: Record<string, never>;
+    result: AuthStatus;
+  };
+  /** The directory the audio cache is using, and why when it isn't the preferred one */
+  get_audio_cache_location: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Cancel a generation started with `job_id`. It fails with a `cancelled` error and leaves nothing in the cache. Returns whether it was still running. */
+  eleven_labs_cancel_generation: {
+    args: {
+      jobId: string;
+    };
+    result: boolean;
+  };
+  /** Job ids of the generations that can be cancelled now */
+  list_generation_jobs: {
+    args: Record<string, never>;
+    result: string[];
+  };
+  /** Get the clipboard speak configuration */
+  get_clipboard_speak_config: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Update the clipboard speak configuration, starting or stopping the watcher */
+  set_clipboard_speak_config: {
+    args: {
+      config: unknown;
+    };
+    result: unknown;
+  };
+  /** Render the same text and voice with two voice settings variants, returning both records and the settings that differ */
+  compare_generations: {
+    args: {
+      text: string;
+      voiceId: string;
+      settingsA: VoiceSettings;
+      settingsB: VoiceSettings;
+      modelId?: string | null;
+      projectId?: string | null;
+    };
+    result: GenerationComparison;
+  };
+  /** A comparison made earlier by `compare_generations` */
+  get_generation_comparison: {
+    args: {
+      comparisonId: string;
+    };
+    result: GenerationComparison | null;
+  };
+  /** List records edited on two machines that need a decision */
+  list_sync_conflicts: {
+    args: Record<string, never>;
+    result: unknown[];
+  };
+  /** Resolve a sync conflict with `keep_local`, `keep_remote` or `keep_both` */
+  resolve_sync_conflict: {
+    args: {
+      id: string;
+      strategy: unknown;
+    };
+    result: unknown;
+  };
+  /** Claude token usage and audio generation for `period`, bucketed for a combined chart */
+  get_usage_dashboard: {
+    args: {
+      period: unknown;
+    };
+    result: unknown;
+  };
+  /** Write a DAW session referencing a scene's takes laid out on the timeline. Returns the path of the written session file. */
+  export_daw_session: {
+    args: {
+      sceneId: string;
+      format: string;
+      destination?: string | null;
+    };
+    result: string;
+  };
+  /** Move a cached audio record to the trash, or with `permanent` delete it and its file right away */
+  delete_cached_audio: {
+    args: {
+      audioId: string;
+      permanent?: boolean | null;
+    };
+    result: void;
+  };
+  /** Move every cached record matching `query` (its voice, date, tag and favorite filters; paging applies per type) to the trash, of `audio_type` or of every type, or with `permanent` delete them and their files right away. At least one filter besides the type is required. */
+  delete_cached_audio_batch: {
+    args: {
+      audioType?: string | null;
+      query?: AudioQuery | null;
+      permanent?: boolean | null;
+    };
+    result: BatchDeleteResult;
+  };
+  /** Generate text-to-speech like `eleven_labs_tts`, emitting `audio-download-progress` events tagged with `download_id` while the audio is written to the cache */
+  eleven_labs_tts_with_progress: {
+    args: {
+      downloadId: string;
+      text: string;
+      voiceId: string;
+      modelId?: string | null;
+      voiceSettings?: VoiceSettings | null;
+      projectId?: string | null;
+      normalizeText?: boolean | null;
+      preset?: string | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** Submit a video or audio file for dubbing into `target_lang`. Progress is reported with `audio-dubbing-progress` events until the dub is downloaded. */
+  eleven_labs_dub_file: {
+    args: {
+      sourcePath: string;
+      targetLang: string;
+      sourceLang?: string | null;
+      numSpeakers?: number | null;
+      projectId?: string | null;
+    };
+    result: DubbingJob;
+  };
+  get_dubbing_job: {
+    args: {
+      jobId: string;
+    };
+    result: DubbingJob | null;
+  };
+  /** Dubbing jobs, newest first */
+  list_dubbing_jobs: {
+    args: {
+      projectId?: string | null;
+    };
+    result: DubbingJob[];
+  };
+  /** Check a job now, e.g. one left running when the app was closed, and resume polling it if it is still being dubbed */
+  refresh_dubbing_job: {
+    args: {
+      jobId: string;
+    };
+    result: DubbingJob;
+  };
+  /** Clone a voice from audio files */
+  eleven_labs_clone_voice: {
+    args: {
+      name: string;
+      files: string[];
+      description?: string | null;
+      labels?: unknown;
+      projectId?: string | null;
+    };
+    result: VoiceProfile;
+  };
+  /** Delete a voice. Fails with `voice_in_use`, listing the references, while characters, agents or cached renders still use it, unless `force` is set; forcing also removes the voice's character and agent mappings. */
+  eleven_labs_delete_voice: {
+    args: {
+      voiceId: string;
+      force?: boolean | null;
+    };
+    result: void;
+  };
+  /** Edit a voice: rename it, change its description or labels, upload more samples or delete some by `sample_id`. Unset fields are left as they are. */
+  eleven_labs_edit_voice: {
+    args: {
+      voiceId: string;
+      name?: string | null;
+      description?: string | null;
+      labels?: unknown;
+      addFiles?: string[] | null;
+      removeSampleIds?: string[] | null;
+      projectId?: string | null;
+    };
+    result: VoiceProfile;
+  };
+  /** Compose music from a prompt, 10 seconds to 5 minutes long. Cancellable by `job_id` like `eleven_labs_tts`. */
+  eleven_labs_generate_music: {
+    args: {
+      prompt: string;
+      durationSeconds?: number | null;
+      style?: string | null;
+      mood?: string | null;
+      projectId?: string | null;
+      jobId?: string | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** Generate sound effects, cancellable by `job_id` like `eleven_labs_tts` */
+  eleven_labs_generate_sfx: {
+    args: {
+      text: string;
+      durationSeconds?: number | null;
+      promptInfluence?: number | null;
+      projectId?: string | null;
+      jobId?: string | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** Get usage information */
+  eleven_labs_get_usage: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Check if API key is configured */
+  eleven_labs_has_api_key: {
+    args: Record<string, never>;
+    result: boolean;
+  };
+  /** Remove background noise from a recording, keeping the voice; useful for cleaning up samples before cloning */
+  eleven_labs_isolate_audio: {
+    args: {
+      sourcePath: string;
+      projectId?: string | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** List the available models with their languages and whether they do text-to-speech and/or speech-to-speech */
+  eleven_labs_list_models: {
+    args: Record<string, never>;
+    result: ModelInfo[];
+  };
+  /** List voices from the local cache, refreshing them from the provider in the background (see `pipeline::VOICES_UPDATED_EVENT`). `filter` keeps only favorites and/or the voices of a collection; the event is not filtered. */
+  eleven_labs_list_voices: {
+    args: {
+      filter?: VoiceFilter | null;
+    };
+    result: VoiceList;
+  };
+  /** Set the Eleven Labs API key */
+  eleven_labs_set_api_key: {
+    args: {
+      apiKey: string;
+    };
+    result: boolean;
+  };
+  /** Convert a recording into another voice (speech-to-speech) */
+  eleven_labs_speech_to_speech: {
+    args: {
+      sourcePath: string;
+      voiceId: string;
+      modelId?: string | null;
+      voiceSettings?: VoiceSettings | null;
+      removeBackgroundNoise?: boolean | null;
+      projectId?: string | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** Generate text-to-speech. An identical earlier request returns its record, marked `cached`, without using quota unless `force_regenerate` is set. With a `job_id` it can be cancelled with `eleven_labs_cancel_generation` while it runs; with a `seed` it can be reproduced by `regenerate_with_same_seed`. */
+  eleven_labs_tts: {
+    args: {
+      text: string;
+      voiceId: string;
+      modelId?: string | null;
+      voiceSettings?: VoiceSettings | null;
+      projectId?: string | null;
+      normalizeText?: boolean | null;
+      preset?: string | null;
+      jobId?: string | null;
+      seed?: number | null;
+      forceRegenerate?: boolean | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** Generate consecutive parts of a text (paragraphs, say) as separate records that sound continuous: each is conditioned on its neighbours' text and the requests before it. Cancellable by `job_id` like `eleven_labs_tts`. */
+  eleven_labs_tts_parts: {
+    args: {
+      parts: string[];
+      voiceId: string;
+      modelId?: string | null;
+      voiceSettings?: VoiceSettings | null;
+      projectId?: string | null;
+      normalizeText?: boolean | null;
+      preset?: string | null;
+      jobId?: string | null;
+    };
+    result: GeneratedAudio[];
+  };
+  /** Generate text-to-speech with the time each character and word is spoken, stored as `alignment` in the record's metadata for highlighting the text during playback. Cancellable by `job_id` like `eleven_labs_tts`. */
+  eleven_labs_tts_with_timestamps: {
+    args: {
+      text: string;
+      voiceId: string;
+      modelId?: string | null;
+      voiceSettings?: VoiceSettings | null;
+      projectId?: string | null;
+      normalizeText?: boolean | null;
+      preset?: string | null;
+      jobId?: string | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** Whether the app database is encrypted */
+  get_app_db_encryption: {
+    args: Record<string, never>;
+    result: DbEncryptionStatus;
+  };
+  /** Encrypt the app database with `passphrase`, change its passphrase, or with `None` decrypt it. Takes effect when the app next starts. */
+  set_app_db_passphrase: {
+    args: {
+      passphrase?: string | null;
+    };
+    result: DbEncryptionStatus;
+  };
+  /** Generate the built-in default sound for every event that has none yet */
+  generate_default_event_sounds: {
+    args: Record<string, never>;
+    result: unknown[];
+  };
+  /** Generate a sound effect for an event from a prompt (or the built-in default prompt) */
+  generate_event_sound: {
+    args: {
+      event: unknown;
+      prompt?: string | null;
+    };
+    result: unknown;
+  };
+  /** Import an audio file into the library as a sound effect and map it to an event */
+  import_event_sound: {
+    args: {
+      event: unknown;
+      path: string;
+    };
+    result: unknown;
+  };
+  /** List the configured event sounds */
+  list_event_sounds: {
+    args: Record<string, never>;
+    result: unknown[];
+  };
+  /** Remove the sound mapped to an event */
+  remove_event_sound: {
+    args: {
+      event: unknown;
+    };
+    result: void;
+  };
+  /** Map an event to an existing clip from the audio library */
+  set_event_sound: {
+    args: {
+      event: unknown;
+      audioId: string;
+      enabled?: boolean | null;
+    };
+    result: unknown;
+  };
+  /** Get the external editor configuration */
+  get_external_editor_config: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Open a cached file in the configured editor. Each save is imported as a new take whose metadata links back to the original via `edited_from`. Returns the path of the exported copy. */
+  open_in_external_editor: {
+    args: {
+      id: string;
+    };
+    result: string;
+  };
+  /** Save the external editor configuration */
+  set_external_editor_config: {
+    args: {
+      config: unknown;
+    };
+    result: void;
+  };
+  /** Get cached audio records of a type, newest first unless `query` says otherwise. `query` also filters by voice and creation date and pages the results with `limit` and `offset`. */
+  get_cached_audio: {
+    args: {
+      audioType: string;
+      query?: AudioQuery | null;
+    };
+    result: GeneratedAudio[];
+  };
+  /** Delete a history item on the provider. Copies already downloaded stay in the cache. */
+  eleven_labs_delete_history_item: {
+    args: {
+      historyItemId: string;
+    };
+    result: void;
+  };
+  /** Download a history item's audio into the local cache */
+  eleven_labs_import_history_item: {
+    args: {
+      historyItemId: string;
+      projectId?: string | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** A page of the provider's generation history, newest first. Pass the page's `last_history_item_id` as `start_after` for the next one. */
+  eleven_labs_list_history: {
+    args: {
+      pageSize?: number | null;
+      startAfter?: string | null;
+    };
+    result: HistoryPage;
+  };
+  /** Get the audio hotkey configuration */
+  get_audio_hotkeys: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Save and re-register the audio hotkeys */
+  set_audio_hotkeys: {
+    args: {
+      config: unknown;
+    };
+    result: unknown;
+  };
+  /** Get the local HTTP API configuration and status */
+  get_audio_http_api_status: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Replace the HTTP API token, invalidating the old one */
+  regenerate_audio_http_api_token: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Enable or disable the local HTTP API, starting or stopping the server */
+  set_audio_http_api_enabled: {
+    args: {
+      enabled: boolean;
+      port?: number | null;
+    };
+    result: unknown;
+  };
+  /** List agent voice mappings */
+  list_agent_voices: {
+    args: Record<string, never>;
+    result: AgentVoice[];
+  };
+  /** List every take in the group of a cached record, oldest first */
+  list_audio_takes: {
+    args: {
+      audioId: string;
+    };
+    result: GeneratedAudio[];
+  };
+  /** List character voice mappings */
+  list_character_voices: {
+    args: {
+      projectId?: string | null;
+    };
+    result: CharacterVoice[];
+  };
+  /** List the voices most recently used for generation, newest first */
+  list_recent_voices: {
+    args: {
+      limit?: number | null;
+    };
+    result: RecentVoice[];
+  };
+  /** Get the live output configuration */
+  get_live_output_config: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Update the live output configuration */
+  set_live_output_config: {
+    args: {
+      config: unknown;
+    };
+    result: unknown;
+  };
+  /** Get the logging configuration */
+  get_logging_config: {
+    args: Record<string, never>;
+    result: LoggingConfig;
+  };
+  /** Update the logging configuration and apply it immediately */
+  set_logging_config: {
+    args: {
+      config: LoggingConfig;
+    };
+    result: LoggingConfig;
+  };
+  /** Start the MCP SSE server on localhost so agents can call the audio tools. Clients authenticate with the HTTP API token. */
+  start_audio_mcp_server: {
+    args: {
+      port?: number | null;
+    };
+    result: string;
+  };
+  /** Stop the in-app MCP SSE server */
+  stop_audio_mcp_server: {
+    args: Record<string, never>;
+    result: boolean;
+  };
+  /** The audio schema version of the database and the migrations applied to it */
+  get_audio_schema_status: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Get the agent narration configuration */
+  get_agent_narration_config: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Get a project's narration settings; `None` when the project follows the global configuration */
+  get_project_narration_config: {
+    args: {
+      projectPath: string;
+    };
+    result: unknown;
+  };
+  /** Save the agent narration configuration */
+  set_agent_narration_config: {
+    args: {
+      config: unknown;
+    };
+    result: unknown;
+  };
+  /** Save a project's narration settings, or clear them with `None` */
+  set_project_narration_config: {
+    args: {
+      projectPath: string;
+      config?: unknown;
+    };
+    result: unknown;
+  };
+  /** Render an RSS feed for a project's narrated episodes, optionally uploading the episode files and feed through a cloud backend. Returns the local feed path. */
+  export_podcast_feed: {
+    args: {
+      projectId: string;
+      config: unknown;
+    };
+    result: string;
+  };
+  delete_voice_preset: {
+    args: {
+      name: string;
+    };
+    result: void;
+  };
+  /** Write presets to a JSON file for sharing; all of them when `names` is not given */
+  export_voice_presets: {
+    args: {
+      dest: string;
+      names?: string[] | null;
+    };
+    result: number;
+  };
+  /** Import a presets file, replacing same-named presets only with `overwrite` */
+  import_voice_presets: {
+    args: {
+      src: string;
+      overwrite?: boolean | null;
+    };
+    result: PresetImportSummary;
+  };
+  list_voice_presets: {
+    args: Record<string, never>;
+    result: VoicePreset[];
+  };
+  rename_voice_preset: {
+    args: {
+      name: string;
+      newName: string;
+    };
+    result: VoicePreset;
+  };
+  /** Create a preset, or update the settings of the one with this name */
+  save_voice_preset: {
+    args: {
+      name: string;
+      settings: VoiceSettings;
+    };
+    result: VoicePreset;
+  };
+  /** Get the hook configuration stored for a project, or the global one without a project */
+  get_audio_hooks: {
+    args: {
+      projectId?: string | null;
+    };
+    result: unknown;
+  };
+  /** Save the hook configuration for a project (or globally); `None` removes it */
+  set_audio_hooks: {
+    args: {
+      projectId?: string | null;
+      config?: unknown;
+    };
+    result: unknown;
+  };
+  /** Delete a project document */
+  delete_project_document: {
+    args: {
+      projectId: string;
+      kind: string;
+      name: string;
+    };
+    result: void;
+  };
+  /** Serialize the project's casting, scripts, glossaries and pacing profiles to YAML files */
+  export_project_files: {
+    args: {
+      projectId: string;
+      folder: string;
+    };
+    result: string[];
+  };
+  /** Re-import YAML project files written by `export_project_files` */
+  import_project_files: {
+    args: {
+      projectId: string;
+      folder: string;
+    };
+    result: unknown;
+  };
+  /** List a project's documents of one kind */
+  list_project_documents: {
+    args: {
+      projectId: string;
+      kind: string;
+    };
+    result: unknown[];
+  };
+  /** Create or replace a script, glossary or pacing profile for a project */
+  save_project_document: {
+    args: {
+      projectId: string;
+      kind: string;
+      document: unknown;
+    };
+    result: unknown;
+  };
+  /** The agent sessions talk to when none is given */
+  get_realtime_agent: {
+    args: Record<string, never>;
+    result: string | null;
+  };
+  list_realtime_sessions: {
+    args: Record<string, never>;
+    result: RealtimeSessionInfo[];
+  };
+  set_realtime_agent: {
+    args: {
+      agentId: string;
+    };
+    result: void;
+  };
+  /** Open a conversation with an agent (the saved default when `agent_id` is not given), streaming the default microphone to it unless `microphone` is false. Everything that happens arrives as `realtime-session` events tagged with the returned session id. */
+  start_realtime_session: {
+    args: {
+      agentId?: string | null;
+      microphone?: boolean | null;
+    };
+    result: string;
+  };
+  /** Close a session and its microphone. Returns whether it was still open. */
+  stop_realtime_session: {
+    args: {
+      sessionId: string;
+    };
+    result: boolean;
+  };
+  /** Scan the audio cache against its records, reporting files without a record and records without a file. Pass `orphans` to delete or register the former and `remove_dangling` to drop the latter; without either nothing changes. */
+  reconcile_audio_cache: {
+    args: {
+      orphans?: unknown;
+      removeDangling?: boolean | null;
+    };
+    result: ReconcileReport;
+  };
+  /** The report of the last automatic or manual audio database recovery */
+  get_audio_db_recovery: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Back up and rebuild the audio tables now, salvaging what can be read */
+  repair_audio_db: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Render a cached record again from its stored parameters with `overrides` applied, saving the result as another take of the original */
+  regenerate_audio: {
+    args: {
+      audioId: string;
+      overrides?: RegenerateOverrides | null;
+      projectId?: string | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** Render a record generated with a seed again with the same seed, reproducing it as another take */
+  regenerate_with_same_seed: {
+    args: {
+      audioId: string;
+      projectId?: string | null;
+    };
+    result: GeneratedAudio;
+  };
+  /** Produce a CSV or JSON report of generations for invoicing and usage audits. The report is returned, and also written to `destination` when given. */
+  export_library_report: {
+    args: {
+      filter?: unknown;
+      format: string;
+      destination?: string | null;
+    };
+    result: string;
+  };
+  /** Resolve which voice speaks for an agent, project and character, and the scope the assignment came from */
+  resolve_voice: {
+    args: {
+      context: unknown;
+    };
+    result: unknown;
+  };
+  /** Cached audio whose prompt contains every word of `query`, best matches first, optionally of one type */
+  search_cached_audio: {
+    args: {
+      query: string;
+      audioType?: string | null;
+      limit?: number | null;
+    };
+    result: GeneratedAudio[];
+  };
+  /** Export a session as a zip holding the narrated replay, an SRT transcript and a metadata JSON. The latest replay is reused; the session is narrated if it has none. */
+  export_session_audio: {
+    args: {
+      sessionId: string;
+      dest: string;
+    };
+    result: unknown;
+  };
+  /** Render a whole session as a two-voice replay. Each line is saved as a take tagged with the scene `session-<id>` (so it can also be exported to a DAW), and the takes are joined into one recap file. */
+  narrate_session: {
+    args: {
+      sessionId: string;
+    };
+    result: GeneratedAudio;
+  };
+  /** Render one message of a stored session with the session's voice */
+  speak_session_message: {
+    args: {
+      sessionId: string;
+      messageId: string;
+    };
+    result: GeneratedAudio;
+  };
+  /** Move the audio cache to `cache_dir` (the default location when `None`) and re-initialize it. Files already cached stay where they are. Returns the directory now in use, which for `None` may be a fallback (see `cache_location::get_audio_cache_location`). */
+  set_audio_cache_dir: {
+    args: {
+      cacheDir?: string | null;
+    };
+    result: string;
+  };
+  /** Copy dropped audio files into the managed voice source directory for cloning */
+  import_clone_sources: {
+    args: {
+      voiceName: string;
+      paths: string[];
+    };
+    result: unknown[];
+  };
+  /** List the managed source files imported for a voice */
+  list_clone_sources: {
+    args: {
+      voiceName: string;
+    };
+    result: unknown[];
+  };
+  /** Write subtitles for a record rendered with timestamps to `dest`, as SRT unless `format` says otherwise. Returns the number of cues written. */
+  export_subtitles: {
+    args: {
+      audioId: string;
+      dest: string;
+      format?: unknown;
+      options?: CueOptions | null;
+    };
+    result: number;
+  };
+  /** List the audio subsystem's running background tasks */
+  list_background_tasks: {
+    args: Record<string, never>;
+    result: unknown[];
+  };
+  /** Back up the audio library to a remote backend */
+  backup_audio_library: {
+    args: {
+      backend: string;
+    };
+    result: unknown;
+  };
+  /** Save the S3-compatible backup configuration, storing the secret access key in the OS keyring */
+  configure_s3_backup: {
+    args: {
+      config: unknown;
+      secretAccessKey: string;
+    };
+    result: void;
+  };
+  /** Save the WebDAV sync configuration, storing the password in the OS keyring */
+  configure_webdav_sync: {
+    args: {
+      config: unknown;
+      password: string;
+    };
+    result: void;
+  };
+  /** Restore the audio library from a remote backend */
+  restore_audio_library: {
+    args: {
+      backend: string;
+    };
+    result: unknown;
+  };
+  /** Get the normalization configuration stored for a project, or the global one without a project */
+  get_text_normalization: {
+    args: {
+      projectId?: string | null;
+    };
+    result: TextNormalizationConfig | null;
+  };
+  /** Show how `text` would be normalized for `locale` */
+  preview_text_normalization: {
+    args: {
+      text: string;
+      locale: string;
+    };
+    result: string;
+  };
+  /** Save the normalization configuration for a project (or globally); `None` removes it */
+  set_text_normalization: {
+    args: {
+      projectId?: string | null;
+      config?: TextNormalizationConfig | null;
+    };
+    result: TextNormalizationConfig | null;
+  };
+  /** Past transcriptions, newest first */
+  list_transcriptions: {
+    args: {
+      projectId?: string | null;
+      limit?: number | null;
+    };
+    result: Transcription[];
+  };
+  /** Transcribe a local audio file with word timings; `language` (ISO 639) is detected when not given */
+  transcribe_audio_file: {
+    args: {
+      sourcePath: string;
+      language?: string | null;
+      projectId?: string | null;
+    };
+    result: Transcription;
+  };
+  /** Cached audio in the trash, most recently deleted first */
+  list_trashed_audio: {
+    args: {
+      audioType?: string | null;
+    };
+    result: TrashedAudio[];
+  };
+  /** Permanently delete audio that has been in the trash for at least `older_than_days` days (`TRASH_RETENTION_DAYS` by default, 0 for all of it) */
+  purge_trashed_audio: {
+    args: {
+      olderThanDays?: number | null;
+    };
+    result: number;
+  };
+  /** Take a record out of the trash, back into the library */
+  restore_cached_audio: {
+    args: {
+      audioId: string;
+    };
+    result: GeneratedAudio;
+  };
+  /** Generate text-to-speech over the streaming WebSocket. Audio arrives as `tts-chunk` events and the saved record with `tts-stream-end`. */
+  start_tts_stream: {
+    args: {
+      text: string;
+      voiceId: string;
+      modelId?: string | null;
+      voiceSettings?: VoiceSettings | null;
+      projectId?: string | null;
+      normalizeText?: boolean | null;
+      preset?: string | null;
+    };
+    result: string;
+  };
+  /** Stop a stream started by `start_tts_stream` */
+  stop_tts_stream: {
+    args: {
+      streamId: string;
+    };
+    result: boolean;
+  };
+  /** Characters and credits consumed per provider, bucketed by day, week or month */
+  get_usage_history: {
+    args: {
+      range?: UsageRange | null;
+      groupBy?: unknown;
+    };
+    result: UsageHistoryEntry[];
+  };
+  /** Get the voice alert configuration */
+  get_voice_alert_config: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Save the voice alert configuration */
+  set_voice_alert_config: {
+    args: {
+      config: unknown;
+    };
+    result: unknown;
+  };
+  add_voice_to_collection: {
+    args: {
+      collectionId: string;
+      voiceId: string;
+    };
+    result: VoiceCollection;
+  };
+  /** Create an empty collection; names are unique regardless of case */
+  create_voice_collection: {
+    args: {
+      name: string;
+    };
+    result: VoiceCollection;
+  };
+  /** Delete a collection; its voices are not affected */
+  delete_voice_collection: {
+    args: {
+      collectionId: string;
+    };
+    result: void;
+  };
+  /** Ids of the favorite voices */
+  list_favorite_voices: {
+    args: Record<string, never>;
+    result: string[];
+  };
+  list_voice_collections: {
+    args: Record<string, never>;
+    result: VoiceCollection[];
+  };
+  remove_voice_from_collection: {
+    args: {
+      collectionId: string;
+      voiceId: string;
+    };
+    result: VoiceCollection;
+  };
+  rename_voice_collection: {
+    args: {
+      collectionId: string;
+      name: string;
+    };
+    result: VoiceCollection;
+  };
+  /** Mark a voice as a favorite, or clear the mark with `favorite: false` */
+  set_voice_favorite: {
+    args: {
+      voiceId: string;
+      favorite: boolean;
+    };
+    result: void;
+  };
+  /** Get the voice command configuration */
+  get_voice_command_config: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Save the voice command configuration */
+  set_voice_command_config: {
+    args: {
+      config: unknown;
+    };
+    result: unknown;
+  };
+  /** Keep one of the voice design previews as a new voice */
+  eleven_labs_create_designed_voice: {
+    args: {
+      name: string;
+      description: string;
+      generatedVoiceId: string;
+      labels?: unknown;
+    };
+    result: VoiceProfile;
+  };
+  /** Generate candidate voices from a description. `text` is what the previews say; the provider writes something fitting when it is not given. */
+  eleven_labs_design_voice_previews: {
+    args: {
+      voiceDescription: string;
+      text?: string | null;
+    };
+    result: VoiceDesignPreviews;
+  };
+  /** Get the voice prompt configuration */
+  get_voice_prompt_config: {
+    args: Record<string, never>;
+    result: unknown;
+  };
+  /** Save the voice prompt configuration */
+  set_voice_prompt_config: {
+    args: {
+      config: unknown;
+    };
+    result: unknown;
+  };
+  /** Start push-to-talk recording from the default microphone */
+  start_voice_prompt: {
+    args: {
+      target?: unknown;
+    };
+    result: void;
+  };
+  /** Stop recording, transcribe, and run the text as a voice command or send it to the target session if one was given. Returns the transcript. */
+  stop_voice_prompt: {
+    args: Record<string, never>;
+    result: string;
+  };
+  /** Add a voice from the shared library to the account under `name` */
+  eleven_labs_add_shared_voice: {
+    args: {
+      publicOwnerId: string;
+      voiceId: string;
+      name: string;
+    };
+    result: VoiceProfile;
+  };
+  /** Search the shared voice library. Results come a page at a time; ask for `page + 1` while `has_more` is set. */
+  eleven_labs_search_shared_voices: {
+    args: {
+      filter?: SharedVoiceFilter | null;
+    };
+    result: SharedVoicePage;
+  };
+  /** Remove a webhook and its signing secret */
+  delete_webhook: {
+    args: {
+      id: string;
+    };
+    result: void;
+  };
+  /** List configured webhooks */
+  list_webhooks: {
+    args: Record<string, never>;
+    result: unknown[];
+  };
+  /** Create or update a webhook; `secret` replaces the stored signing secret when given */
+  save_webhook: {
+    args: {
+      webhook: unknown;
+      secret?: string | null;
+    };
+    result: unknown;
+  };
+  /** Send a signed `ping` to a single webhook and report whether it was accepted */
+  test_webhook: {
+    args: {
+      id: string;
+    };
+    result: void;
+  };
+}
+
+export type AudioCommand = keyof AudioCommands;
+
+export const AUDIO_COMMANDS: AudioCommand[] = [
+  'export_audio_bundle',
+  'export_full_library',
+  'import_full_library',
+  'assign_voice_to_agent',
+  'assign_voice_to_character',
+  'eleven_labs_tts_stream',
+  'get_audio_tags',
+  'list_audio_by_tag',
+  'list_audio_tags',
+  'tag_audio',
+  'toggle_audio_favorite',
+  'untag_audio',
+  'get_auth_status',
+  'get_audio_cache_location',
+  'eleven_labs_cancel_generation',
+  'list_generation_jobs',
+  'get_clipboard_speak_config',
+  'set_clipboard_speak_config',
+  'compare_generations',
+  'get_generation_comparison',
+  'list_sync_conflicts',
+  'resolve_sync_conflict',
+  'get_usage_dashboard',
+  'export_daw_session',
+  'delete_cached_audio',
+  'delete_cached_audio_batch',
+  'eleven_labs_tts_with_progress',
+  'eleven_labs_dub_file',
+  'get_dubbing_job',
+  'list_dubbing_jobs',
+  'refresh_dubbing_job',
+  'eleven_labs_clone_voice',
+  'eleven_labs_delete_voice',
+  'eleven_labs_edit_voice',
+  'eleven_labs_generate_music',
+  'eleven_labs_generate_sfx',
+  'eleven_labs_get_usage',
+  'eleven_labs_has_api_key',
+  'eleven_labs_isolate_audio',
+  'eleven_labs_list_models',
+  'eleven_labs_list_voices',
+  'eleven_labs_set_api_key',
+  'eleven_labs_speech_to_speech',
+  'eleven_labs_tts',
+  'eleven_labs_tts_parts',
+  'eleven_labs_tts_with_timestamps',
+  'get_app_db_encryption',
+  'set_app_db_passphrase',
+  'generate_default_event_sounds',
+  'generate_event_sound',
+  'import_event_sound',
+  'list_event_sounds',
+  'remove_event_sound',
+  'set_event_sound',
+  'get_external_editor_config',
+  'open_in_external_editor',
+  'set_external_editor_config',
+  'get_cached_audio',
+  'eleven_labs_delete_history_item',
+  'eleven_labs_import_history_item',
+  'eleven_labs_list_history',
+  'get_audio_hotkeys',
+  'set_audio_hotkeys',
+  'get_audio_http_api_status',
+  'regenerate_audio_http_api_token',
+  'set_audio_http_api_enabled',
+  'list_agent_voices',
+  'list_audio_takes',
+  'list_character_voices',
+  'list_recent_voices',
+  'get_live_output_config',
+  'set_live_output_config',
+  'get_logging_config',
+  'set_logging_config',
+  'start_audio_mcp_server',
+  'stop_audio_mcp_server',
+  'get_audio_schema_status',
+  'get_agent_narration_config',
+  'get_project_narration_config',
+  'set_agent_narration_config',
+  'set_project_narration_config',
+  'export_podcast_feed',
+  'delete_voice_preset',
+  'export_voice_presets',
+  'import_voice_presets',
+  'list_voice_presets',
+  'rename_voice_preset',
+  'save_voice_preset',
+  'get_audio_hooks',
+  'set_audio_hooks',
+  'delete_project_document',
+  'export_project_files',
+  'import_project_files',
+  'list_project_documents',
+  'save_project_document',
+  'get_realtime_agent',
+  'list_realtime_sessions',
+  'set_realtime_agent',
+  'start_realtime_session',
+  'stop_realtime_session',
+  'reconcile_audio_cache',
+  'get_audio_db_recovery',
+  'repair_audio_db',
+  'regenerate_audio',
+  'regenerate_with_same_seed',
+  'export_library_report',
+  'resolve_voice',
+  'search_cached_audio',
+  'export_session_audio',
+  'narrate_session',
+  'speak_session_message',
+  'set_audio_cache_dir',
+  'import_clone_sources',
+  'list_clone_sources',
+  'export_subtitles',
+  'list_background_tasks',
+  'backup_audio_library',
+  'configure_s3_backup',
+  'configure_webdav_sync',
+  'restore_audio_library',
+  'get_text_normalization',
+  'preview_text_normalization',
+  'set_text_normalization',
+  'list_transcriptions',
+  'transcribe_audio_file',
+  'list_trashed_audio',
+  'purge_trashed_audio',
+  'restore_cached_audio',
+  'start_tts_stream',
+  'stop_tts_stream',
+  'get_usage_history',
+  'get_voice_alert_config',
+  'set_voice_alert_config',
+  'add_voice_to_collection',
+  'create_voice_collection',
+  'delete_voice_collection',
+  'list_favorite_voices',
+  'list_voice_collections',
+  'remove_voice_from_collection',
+  'rename_voice_collection',
+  'set_voice_favorite',
+  'get_voice_command_config',
+  'set_voice_command_config',
+  'eleven_labs_create_designed_voice',
+  'eleven_labs_design_voice_previews',
+  'get_voice_prompt_config',
+  'set_voice_prompt_config',
+  'start_voice_prompt',
+  'stop_voice_prompt',
+  'eleven_labs_add_shared_voice',
+  'eleven_labs_search_shared_voices',
+  'delete_webhook',
+  'list_webhooks',
+  'save_webhook',
+  'test_webhook',
+];
+
+type AudioCommandArgs<K extends AudioCommand> = Record<string, never> extends AudioCommands[K]['args']
+  ? [args?: AudioCommands[K]['args']]
+  : [args: AudioCommands[K]['args']];
+
+/** Invoke an audio command with its arguments and result type checked */
+export function invokeAudio<K extends AudioCommand>(
+  command: K,
+  ...args: AudioCommandArgs<K>
+): Promise<AudioCommands[K]['result']> {
+  return apiCall<AudioCommands[K]['result']>(command, args[0]);
+}